@@ -0,0 +1,332 @@
+/*!
+ * Asking for frames to be sent again over a lossy link (like the Bluetooth
+ * one to the car), by numbering them and sending back a `NACK` for any that
+ * never came in or came in corrupted
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::*;
+use serde_json::from_str as serde_from_str;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// Constants
+/// How many frames are kept around to be sent again, and so how far back a
+/// missing frame can be asked for
+///
+/// Small, as the Bluetooth link is slow and a status is stale soon anyway
+pub const ARQ_WINDOW: u32 = 8;
+
+/***** Frame numbers *****/
+
+/// Number a frame in its metadata, keeping a checksum on the end if it had
+/// one
+pub fn number_frame(frame: &str, number: u32) -> Result<String, ClientError> {
+    edit_metadata(frame, |metadata| metadata.frame = Some(number))
+}
+
+/// What the number of a frame seems to be, without checking its checksum
+pub fn unchecked_frame_number(frame: &str) -> Option<u32> {
+    let body = frame
+        .rsplit_once(CHECKSUM_SEPARATOR)
+        .map_or(frame, |(body, _)| body);
+    let (_, metadata) = body.rsplit_once(FRAME_SEPARATOR)?;
+    serde_from_str::<MetaData>(metadata).ok()?.frame
+}
+
+/// The frames sent most recently, to send again if they're asked for
+#[derive(Default)]
+pub struct SentFrames {
+    next: u32,
+    /// Oldest first, up to [`ARQ_WINDOW`] of them
+    window: VecDeque<(u32, String)>,
+}
+impl SentFrames {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number a frame to be sent, keeping it in case it's asked for again
+    pub fn number(&mut self, frame: &str) -> Result<String, ClientError> {
+        let numbered = number_frame(frame, self.next)?;
+        if self.window.len() >= ARQ_WINDOW as usize {
+            self.window.pop_front();
+        }
+        self.window.push_back((self.next, numbered.clone()));
+        self.next = self.next.wrapping_add(1);
+        Ok(numbered)
+    }
+
+    /// A frame that was asked for again, as it was sent, if it's still kept
+    pub fn get(&self, number: u32) -> Option<&str> {
+        self.window
+            .iter()
+            .find(|(sent, _)| *sent == number)
+            .map(|(_, frame)| frame.as_str())
+    }
+}
+
+/// Which frames came in, to ask again for the ones that didn't
+#[derive(Default)]
+pub struct ReceivedFrames {
+    /// The number of the next frame, once any have come in
+    expected: Option<u32>,
+    /// Frames asked for again that haven't come in yet, up to [`ARQ_WINDOW`]
+    /// of them
+    missing: VecDeque<u32>,
+}
+impl ReceivedFrames {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note down a frame that came in, returning the frames to ask for again
+    ///
+    /// Frames skipped over are missing, though only the ones the other end
+    /// still keeps are asked for. A frame from well before the one expected
+    /// means the other end started over, so counting starts over from it.
+    pub fn receive(&mut self, number: u32) -> Vec<u32> {
+        if self.found(number) {
+            return vec![];
+        }
+        let expected = *self.expected.get_or_insert(number);
+        let skipped = number.wrapping_sub(expected);
+
+        // Already came in (e.g. sent again for a request that timed out),
+        // unless it's from so far back that the other end started over
+        if skipped > u32::MAX / 2 {
+            if expected.wrapping_sub(number) > ARQ_WINDOW {
+                self.expected = Some(number.wrapping_add(1));
+                self.missing.clear();
+            }
+            return vec![];
+        }
+
+        self.expected = Some(number.wrapping_add(1));
+        let missing = (1..=skipped.min(ARQ_WINDOW - 1))
+            .rev()
+            .map(|back| number.wrapping_sub(back))
+            .collect::<Vec<_>>();
+        self.ask_for(&missing);
+        missing
+    }
+
+    /// Note down a frame that came in corrupted by what its number seems to
+    /// be, returning the frames to ask for again (including it)
+    ///
+    /// The number could be corrupted too, so it's only trusted if it could
+    /// come next; otherwise, the gap is noticed once the next frame comes in.
+    /// A frame that was already asked for again isn't asked for a second time.
+    pub fn corrupted(&mut self, number: u32) -> Vec<u32> {
+        if self.found(number) {
+            return vec![];
+        }
+        let Some(expected) = self.expected else {
+            return vec![];
+        };
+        if number.wrapping_sub(expected) >= ARQ_WINDOW {
+            return vec![];
+        }
+
+        let mut missing = self.receive(number);
+        missing.push(number);
+        self.ask_for(&[number]);
+        missing
+    }
+
+    /// Take a frame off of the missing ones, returning whether it was missing
+    fn found(&mut self, number: u32) -> bool {
+        match self.missing.iter().position(|missing| *missing == number) {
+            Some(idx) => {
+                self.missing.remove(idx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn ask_for(&mut self, numbers: &[u32]) {
+        self.missing.extend(numbers);
+        while self.missing.len() > ARQ_WINDOW as usize {
+            self.missing.pop_front();
+        }
+    }
+}
+
+/***** Transport *****/
+
+/// Which end of the link between the client and the car a transport is on
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LinkEnd {
+    Client,
+    Car,
+}
+impl LinkEnd {
+    /// What frames sent from this end are
+    pub fn sends(&self) -> TransitMode {
+        match self {
+            Self::Client => TransitMode::ClientToServerRequest,
+            Self::Car => TransitMode::ServerToClientResponse,
+        }
+    }
+
+    /// What frames from the other end are
+    pub fn receives(&self) -> TransitMode {
+        match self {
+            Self::Client => TransitMode::ServerToClientResponse,
+            Self::Car => TransitMode::ClientToServerRequest,
+        }
+    }
+}
+
+struct ArqState {
+    end: LinkEnd,
+    /// Frames are only numbered from [`NUMBERED_PROTOCOL_VERSION`] on
+    protocol_version: u32,
+    sent: SentFrames,
+    received: ReceivedFrames,
+}
+
+/// A transport asking again for frames that go missing between the client
+/// and the car
+///
+/// Frames sent to the other end are numbered, and the last [`ARQ_WINDOW`] of
+/// them are kept. Once a frame from the other end is skipped over or comes in
+/// corrupted, a `NACK` asking for it is sent back, and a `NACK` from the
+/// other end is answered by sending that frame again as it was, if it's still
+/// kept. `NACK`s aren't numbered, and never make it out of the transport,
+/// nor do corrupted frames being asked for again.
+///
+/// Anything else, like frames to and from the serial bridge or from an other
+/// end that doesn't number its frames, passes through as is, as does every
+/// frame once a version of the protocol from before numbering is spoken.
+pub struct ArqTransport {
+    inner: Box<dyn Transport>,
+    /// Shared with clones, so frames are numbered the same whichever one
+    /// they're sent from
+    state: Arc<Mutex<ArqState>>,
+}
+impl ArqTransport {
+    pub fn new(inner: Box<dyn Transport>, end: LinkEnd) -> Self {
+        Self {
+            inner,
+            state: Arc::new(Mutex::new(ArqState {
+                end,
+                protocol_version: PROTOCOL_VERSION,
+                sent: SentFrames::new(),
+                received: ReceivedFrames::new(),
+            })),
+        }
+    }
+
+    /// Handle a line that came in, returning it unless it's a `NACK` or is
+    /// being asked for again
+    fn receive(&mut self, line: String) -> Result<Option<String>, ClientError> {
+        // Safety: nothing panics while holding the lock
+        let mut state = self.state.lock().unwrap();
+        let end = state.end;
+        if !line.starts_with(end.receives() as u8 as char) {
+            return Ok(Some(line));
+        }
+
+        let (missing, pass_on) = match RawFrame::split(&line) {
+            Ok(raw_frame) if raw_frame.command == Command::Nack => {
+                let asked_for = raw_frame
+                    .value::<NackArguments>()
+                    .ok()
+                    .and_then(|nack| state.sent.get(nack.frame));
+                if let Some(frame) = asked_for {
+                    self.inner.write_line(frame)?;
+                }
+                return Ok(None);
+            }
+            Ok(raw_frame) => match raw_frame.metadata.frame {
+                Some(number) => (state.received.receive(number), true),
+                None => (vec![], true),
+            },
+            Err(ClientError::ChecksumMismatch { .. }) => {
+                let missing = unchecked_frame_number(&line)
+                    .map(|number| state.received.corrupted(number))
+                    .unwrap_or_default();
+                let pass_on = missing.is_empty();
+                (missing, pass_on)
+            }
+            Err(_) => (vec![], true),
+        };
+        drop(state);
+
+        for number in missing {
+            self.inner.write_line(&nack_frame(end, number)?)?;
+        }
+        Ok(pass_on.then_some(line))
+    }
+}
+impl Transport for ArqTransport {
+    fn read_line(&mut self) -> Result<Option<String>, ClientError> {
+        while let Some(line) = self.inner.read_line()? {
+            if let Some(line) = self.receive(line)? {
+                return Ok(Some(line));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Frames that can't be numbered are sent as they are
+    fn write_line(&mut self, line: &str) -> Result<(), ClientError> {
+        let numbered = {
+            let mut state = self.state.lock().unwrap();
+            match line.starts_with(state.end.sends() as u8 as char)
+                && state.protocol_version >= NUMBERED_PROTOCOL_VERSION
+            {
+                true => state.sent.number(line).ok(),
+                false => None,
+            }
+        };
+        self.inner.write_line(numbered.as_deref().unwrap_or(line))
+    }
+
+    fn connected(&self) -> bool {
+        self.inner.connected()
+    }
+
+    fn try_clone(&self) -> Result<Box<dyn Transport>, ClientError> {
+        Ok(Box::new(Self {
+            inner: self.inner.try_clone()?,
+            state: Arc::clone(&self.state),
+        }))
+    }
+
+    fn speak_protocol_version(&mut self, version: u32) {
+        self.state.lock().unwrap().protocol_version = version;
+        self.inner.speak_protocol_version(version);
+    }
+
+    fn queued(&self) -> usize {
+        self.inner.queued()
+    }
+}
+
+/// Ask the other end to send a frame again
+pub fn nack_frame(end: LinkEnd, number: u32) -> Result<String, ClientError> {
+    Frame::encode_with_checksum(&Event {
+        command: Command::Nack,
+        transit_mode: end.sends(),
+        transit_type: TransitType::Request,
+        value: NackArguments { frame: number },
+        metadata: MetaData {
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs_f64())
+                .unwrap_or(0.0),
+            sequence: None,
+            frame: None,
+            echo: None,
+            client: None,
+        },
+    })
+}
@@ -0,0 +1,165 @@
+/*!
+ * Builders for request arguments, checking required fields at compile time
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{
+    DistanceEstimatorKind, PingArguments, PlanBreakpoint, StartArguments, UploadPlanArguments,
+};
+
+// Constants
+/// Whether to brake by reversing the motor when it isn't specified
+pub const DEFAULT_REVERSE_BRAKE: bool = false;
+
+/// Marks a required field that hasn't been set yet
+///
+/// `build()` only exists once every field marked with this has been set, so
+/// forgetting one is a compile error instead of a zero sent to the car.
+pub struct Missing;
+
+/***** Start *****/
+
+/// Builder for [`StartArguments`], see [`StartArguments::builder`]
+pub struct StartArgumentsBuilder<Distance> {
+    distance: Distance,
+    reverse_brake: bool,
+    max_velocity: Option<f64>,
+    distance_estimator: Option<DistanceEstimatorKind>,
+    coast_deceleration: Option<f64>,
+}
+impl StartArguments {
+    /// Build start arguments, e.g.
+    /// `StartArguments::builder().distance_cm(500.0).reverse_brake(true).build()`
+    pub fn builder() -> StartArgumentsBuilder<Missing> {
+        StartArgumentsBuilder {
+            distance: Missing,
+            reverse_brake: DEFAULT_REVERSE_BRAKE,
+            max_velocity: None,
+            distance_estimator: None,
+            coast_deceleration: None,
+        }
+    }
+}
+impl<Distance> StartArgumentsBuilder<Distance> {
+    /// Required: how far to go in centimeters
+    pub fn distance_cm(self, distance: f64) -> StartArgumentsBuilder<f64> {
+        StartArgumentsBuilder {
+            distance,
+            reverse_brake: self.reverse_brake,
+            max_velocity: self.max_velocity,
+            distance_estimator: self.distance_estimator,
+            coast_deceleration: self.coast_deceleration,
+        }
+    }
+
+    /// Optional: whether to brake by reversing the motor
+    pub fn reverse_brake(mut self, reverse_brake: bool) -> Self {
+        self.reverse_brake = reverse_brake;
+        self
+    }
+
+    /// Optional: the speed limit in centimeters per second, or `None` for no
+    /// limit
+    pub fn max_velocity_cm_s(mut self, max_velocity: Option<f64>) -> Self {
+        self.max_velocity = max_velocity;
+        self
+    }
+
+    /// Optional: how the server works out the distance, or `None` for its
+    /// default
+    pub fn distance_estimator(mut self, distance_estimator: Option<DistanceEstimatorKind>) -> Self {
+        self.distance_estimator = distance_estimator;
+        self
+    }
+
+    /// Optional: how quickly the car slows down once power is cut, in
+    /// centimeters per second squared, or `None` for the server to cut it a
+    /// fixed leeway before the distance
+    pub fn coast_deceleration_cm_s2(mut self, coast_deceleration: Option<f64>) -> Self {
+        self.coast_deceleration = coast_deceleration;
+        self
+    }
+}
+impl StartArgumentsBuilder<f64> {
+    pub fn build(self) -> StartArguments {
+        StartArguments {
+            distance: self.distance,
+            reverse_brake: self.reverse_brake,
+            max_velocity: self.max_velocity,
+            distance_estimator: self.distance_estimator,
+            coast_deceleration: self.coast_deceleration,
+        }
+    }
+}
+
+/***** Upload plan *****/
+
+/// Builder for [`UploadPlanArguments`], see [`UploadPlanArguments::builder`]
+pub struct UploadPlanArgumentsBuilder<Start> {
+    start: Start,
+    breakpoints: Vec<PlanBreakpoint>,
+}
+impl UploadPlanArguments {
+    /// Build upload plan arguments, e.g.
+    /// `UploadPlanArguments::builder().start(start).breakpoint_cm(300.0, Some(20.0)).build()`
+    pub fn builder() -> UploadPlanArgumentsBuilder<Missing> {
+        UploadPlanArgumentsBuilder {
+            start: Missing,
+            breakpoints: vec![],
+        }
+    }
+}
+impl<Start> UploadPlanArgumentsBuilder<Start> {
+    /// Required: the run to carry out
+    pub fn start(self, start: StartArguments) -> UploadPlanArgumentsBuilder<StartArguments> {
+        UploadPlanArgumentsBuilder {
+            start,
+            breakpoints: self.breakpoints,
+        }
+    }
+
+    /// Optional: the speed limit in centimeters per second (or `None` for no
+    /// limit) once the car has gone a distance in centimeters, after any
+    /// breakpoints before it
+    pub fn breakpoint_cm(mut self, distance: f64, max_velocity: Option<f64>) -> Self {
+        self.breakpoints.push(PlanBreakpoint {
+            distance,
+            max_velocity,
+        });
+        self
+    }
+}
+impl UploadPlanArgumentsBuilder<StartArguments> {
+    pub fn build(self) -> UploadPlanArguments {
+        UploadPlanArguments {
+            start: self.start,
+            breakpoints: self.breakpoints,
+        }
+    }
+}
+
+/***** Ping *****/
+
+/// Builder for [`PingArguments`], see [`PingArguments::builder`]
+pub struct PingArgumentsBuilder<Time> {
+    time: Time,
+}
+impl PingArguments {
+    /// Build ping arguments, e.g. `PingArguments::builder().time(now).build()`
+    pub fn builder() -> PingArgumentsBuilder<Missing> {
+        PingArgumentsBuilder { time: Missing }
+    }
+}
+impl<Time> PingArgumentsBuilder<Time> {
+    /// Required: the Unix time in seconds the ping was sent at
+    pub fn time(self, time: f64) -> PingArgumentsBuilder<f64> {
+        PingArgumentsBuilder { time }
+    }
+}
+impl PingArgumentsBuilder<f64> {
+    pub fn build(self) -> PingArguments {
+        PingArguments { time: self.time }
+    }
+}
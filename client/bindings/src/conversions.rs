@@ -0,0 +1,43 @@
+/*!
+ * Converting the `#[repr(u8)]` enums sent over the wire to and from their
+ * discriminants without any `unsafe`
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Conversions *****/
+
+/// Define a `#[repr(u8)]` enum with `TryFrom<u8>` and `From<_> for u8`
+///
+/// Every variant needs its discriminant written out, and only those can be
+/// converted from. Variants listed after `except` (e.g. ones only marking
+/// where a range starts or ends) are errors like any unknown discriminant.
+macro_rules! u8_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $enum:ident {
+            $($(#[$variant_meta:meta])* $variant:ident = $discriminant:literal,)+
+        }
+        $(except $($excluded:ident)|+)?
+    ) => {
+        $(#[$meta])*
+        pub enum $enum {
+            $($(#[$variant_meta])* $variant = $discriminant,)+
+        }
+        impl TryFrom<u8> for $enum {
+            type Error = ();
+
+            fn try_from(value: u8) -> Result<Self, Self::Error> {
+                match value {
+                    $($(_ if value == $enum::$excluded as u8 => Err(()),)+)?
+                    $(_ if value == $enum::$variant as u8 => Ok($enum::$variant),)+
+                    _ => Err(()),
+                }
+            }
+        }
+        impl From<$enum> for u8 {
+            fn from(value: $enum) -> Self {
+                value as u8
+            }
+        }
+    };
+}
@@ -0,0 +1,366 @@
+/*!
+ * Encoding and decoding frames of the `<prefix><COMMAND>$<args>$<metadata>`
+ * wire format (with an optional `*XXXX` checksum on the end), shared by
+ * everything that talks over the serial connection, and splitting a stream of
+ * bytes into frames
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str as serde_from_str, to_string as serde_to_string};
+use std::{
+    collections::VecDeque,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// Constants
+/// Separates the command, the arguments, and the metadata
+pub const FRAME_SEPARATOR: char = '$';
+/// Separates an optional CRC16 (as 4 hex digits) from the end of a frame
+pub const CHECKSUM_SEPARATOR: char = '*';
+/// How long an unfinished frame can get before it's assumed to be garbage
+/// and thrown out
+pub const MAX_FRAME_LENGTH: usize = 4096;
+
+/***** Checksum *****/
+
+/// CRC-16/CCITT-FALSE, the same as the server's `crc16`
+pub fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc = 0xFFFF_u16;
+    for byte in bytes.iter() {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = match crc & 0x8000 {
+                0 => crc << 1,
+                _ => (crc << 1) ^ 0x1021,
+            };
+        }
+    }
+    crc
+}
+
+/// Take the checksum off of the end of a frame and check it, if there is one
+///
+/// Frames without a checksum are passed through as they are; their metadata
+/// always ends with `}`, so they can't be mistaken for having one
+pub fn strip_checksum(frame: &str) -> Result<&str, ClientError> {
+    let (body, expected) = match frame.rsplit_once(CHECKSUM_SEPARATOR) {
+        Some((body, checksum))
+            if checksum.len() == 4 && checksum.chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            // Safety: just checked it's 4 hex digits
+            (body, u16::from_str_radix(checksum, 16).unwrap())
+        }
+        _ => return Ok(frame),
+    };
+
+    let calculated = crc16(body.as_bytes());
+    if calculated != expected {
+        let command = body
+            .split_once(FRAME_SEPARATOR)
+            .and_then(|(command, _)| command.get(1..))
+            .and_then(|command| Command::try_from(command.to_owned()).ok());
+        Err(ClientError::ChecksumMismatch {
+            command,
+            expected,
+            calculated,
+        })?;
+    }
+    Ok(body)
+}
+
+/***** Raw frame *****/
+
+/// A frame split into its parts, with the arguments left as JSON
+///
+/// Useful for requests, which don't have a typed [`Response`] to decode into
+pub struct RawFrame {
+    pub transit_mode: TransitMode,
+    pub command: Command,
+    /// JSON, with unit structs as `null` rather than `{}`
+    pub data: String,
+    pub metadata: MetaData,
+}
+impl RawFrame {
+    /// Split a frame into its parts, checking its checksum if it has one
+    ///
+    /// The arguments are JSON, which may contain the separator inside of
+    /// strings, so only the first and last separators count
+    pub fn split(frame: &str) -> Result<Self, ClientError> {
+        let frame = strip_checksum(frame.trim())?;
+
+        let first_char = frame
+            .chars()
+            .next()
+            .ok_or_else(|| ClientError::Parse("Empty frame".to_owned()))?;
+        let transit_mode = TransitMode::try_from(first_char as u8)
+            .ok()
+            .filter(|_| first_char.is_ascii())
+            .ok_or_else(|| {
+                ClientError::Parse(format!(
+                    "Failed to determine transit mode of `{first_char}`"
+                ))
+            })?;
+        let frame = &frame[first_char.len_utf8()..];
+
+        let (command, rest) = frame
+            .split_once(FRAME_SEPARATOR)
+            .ok_or_else(|| ClientError::Parse(format!("No arguments in `{frame}`")))?;
+        let (data, metadata) = rest
+            .rsplit_once(FRAME_SEPARATOR)
+            .ok_or_else(|| ClientError::Parse(format!("No metadata in `{frame}`")))?;
+
+        let command = Command::try_from(command.to_owned())?;
+        let metadata = serde_from_str::<MetaData>(metadata)?;
+        // XXX: Serde thinks "{}" is a map while "null" is perfectly plausible
+        // pertaining proportionally for paragliding pedantically in terms of
+        // parsing plainly a plain struct free of frills -- fields
+        let data = match data {
+            "{}" => "null",
+            non_empty => non_empty,
+        }
+        .to_owned();
+
+        Ok(Self {
+            transit_mode,
+            command,
+            data,
+            metadata,
+        })
+    }
+
+    /// Parse the arguments
+    pub fn value<S>(&self) -> Result<S, ClientError>
+    where
+        S: Serialize + for<'a> Deserialize<'a>,
+    {
+        Ok(serde_from_str::<S>(&self.data)?)
+    }
+
+    /// Wrap the parsed arguments into an event
+    fn event<S>(&self, transit_type: TransitType) -> Result<Event<S>, ClientError>
+    where
+        S: Serialize + for<'a> Deserialize<'a>,
+    {
+        Ok(Event {
+            command: self.command,
+            transit_mode: self.transit_mode,
+            transit_type,
+            value: self.value()?,
+            metadata: self.metadata,
+        })
+    }
+}
+
+/// Change the metadata of a frame without touching the rest of it, keeping a
+/// checksum on the end if it had one
+pub fn edit_metadata<F>(frame: &str, edit: F) -> Result<String, ClientError>
+where
+    F: FnOnce(&mut MetaData),
+{
+    let body = strip_checksum(frame)?;
+    let had_checksum = body.len() != frame.len();
+    let (rest, metadata) = body
+        .rsplit_once(FRAME_SEPARATOR)
+        .ok_or_else(|| ClientError::Parse(format!("No metadata in `{body}`")))?;
+    let mut metadata = serde_from_str::<MetaData>(metadata)?;
+    edit(&mut metadata);
+
+    let edited = format!("{rest}{FRAME_SEPARATOR}{}", serde_to_string(&metadata)?);
+    Ok(match had_checksum {
+        true => format!(
+            "{edited}{CHECKSUM_SEPARATOR}{:04X}",
+            crc16(edited.as_bytes())
+        ),
+        false => edited,
+    })
+}
+
+/***** Frame *****/
+
+/// The codec for frames
+pub struct Frame;
+impl Frame {
+    /// Encode an event into a frame, without the trailing newline
+    pub fn encode<S>(event: &Event<S>) -> Result<String, ClientError>
+    where
+        S: Serialize + for<'a> Deserialize<'a>,
+    {
+        let prefix = event.transit_mode as u8 as char;
+        let data = serde_to_string(&event.value)?;
+        // The server expects an empty object for no arguments
+        let data = match data.as_str() {
+            "null" => "{}",
+            stringified => stringified,
+        };
+        let metadata = serde_to_string(&event.metadata)?;
+
+        Ok(format!(
+            "{prefix}{}{FRAME_SEPARATOR}{data}{FRAME_SEPARATOR}{metadata}",
+            event.command
+        ))
+    }
+
+    /// Encode an event into a frame with a checksum on the end, without the
+    /// trailing newline
+    pub fn encode_with_checksum<S>(event: &Event<S>) -> Result<String, ClientError>
+    where
+        S: Serialize + for<'a> Deserialize<'a>,
+    {
+        let frame = Self::encode(event)?;
+        let checksum = crc16(frame.as_bytes());
+        Ok(format!("{frame}{CHECKSUM_SEPARATOR}{checksum:04X}"))
+    }
+
+    /// Decode a response frame, from the server or the serial bridge
+    pub fn decode(frame: &str) -> Result<Response, ClientError> {
+        let raw_frame = RawFrame::split(frame)?;
+        match raw_frame.transit_mode {
+            TransitMode::ServerToClientResponse | TransitMode::SerialBridgeToClientResponse => (),
+            _ => Err(ClientError::Parse(format!(
+                "`{}` is a request, not a response",
+                raw_frame.command
+            )))?,
+        }
+
+        let transit_type = TransitType::Response;
+        use Command::*;
+        Ok(match raw_frame.command {
+            Ping => Response::Ping(raw_frame.event(transit_type)?),
+            Start => Response::Start(raw_frame.event(transit_type)?),
+            Stop => Response::Stop(raw_frame.event(transit_type)?),
+            Status => Response::Status(raw_frame.event(transit_type)?),
+            StaticStatus => Response::StaticStatus(raw_frame.event(transit_type)?),
+            Error => Response::Error(raw_frame.event(transit_type)?),
+            Hello => Response::Hello(raw_frame.event(transit_type)?),
+            UploadPlan => Response::UploadPlan(raw_frame.event(transit_type)?),
+            Tune => Response::Tune(raw_frame.event(transit_type)?),
+            Calibrate => Response::Calibrate(raw_frame.event(transit_type)?),
+            BluetoothStatus => Response::BluetoothStatus(raw_frame.event(transit_type)?),
+            BluetoothScan => Response::BluetoothScan(raw_frame.event(transit_type)?),
+            Connect => Response::BluetoothConnect(raw_frame.event(transit_type)?),
+            BluetoothMetrics => Response::BluetoothMetrics(raw_frame.event(transit_type)?),
+            Watchdog => Response::Watchdog(raw_frame.event(transit_type)?),
+            Disconnect | Nack => Err(ClientError::Parse(format!(
+                "No response is sent for `{}`",
+                raw_frame.command
+            )))?,
+        })
+    }
+}
+
+/// Encode a response from the serial bridge, answering the request with
+/// `sequence`, into a frame with a checksum on the end, without the trailing
+/// newline
+///
+/// The bridge answers on its own, so it's timestamped with the time now
+pub fn encode_bridge_response<S>(
+    command: Command,
+    value: S,
+    sequence: Option<u32>,
+) -> Result<String, ClientError>
+where
+    S: Serialize + for<'a> Deserialize<'a>,
+{
+    Frame::encode_with_checksum(&Event {
+        command,
+        transit_mode: TransitMode::SerialBridgeToClientResponse,
+        transit_type: TransitType::Response,
+        value,
+        metadata: MetaData {
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs_f64())
+                .unwrap_or(0.0),
+            sequence,
+            frame: None,
+            echo: None,
+            client: None,
+        },
+    })
+}
+
+impl<S> Event<S>
+where
+    S: Serialize + for<'a> Deserialize<'a>,
+{
+    /// See [`Frame::encode`]
+    pub fn encode(&self) -> Result<String, ClientError> {
+        Frame::encode(self)
+    }
+
+    /// See [`Frame::encode_with_checksum`]
+    pub fn encode_with_checksum(&self) -> Result<String, ClientError> {
+        Frame::encode_with_checksum(self)
+    }
+}
+
+impl Response {
+    /// Decode a single complete frame from raw bytes
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, ClientError> {
+        Frame::decode(&String::from_utf8_lossy(bytes))
+    }
+}
+
+/***** Streaming decoder *****/
+
+/// Splits bytes from the serial connection into frames, however they are
+/// chunked
+///
+/// Bytes are pushed in as they arrive; whatever comes before a `\r` or `\n`
+/// is a frame (`scip` ends lines with `\r`, the server with `\n`), and an
+/// unfinished frame waits in the buffer for the rest of it. Iterating decodes
+/// the finished frames into responses.
+#[derive(Default)]
+pub struct StreamingDecoder {
+    buffer: Vec<u8>,
+    frames: VecDeque<String>,
+}
+impl StreamingDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes.iter() {
+            match byte {
+                b'\r' | b'\n' => {
+                    let frame = String::from_utf8_lossy(&self.buffer).trim().to_owned();
+                    self.buffer.clear();
+                    if !frame.is_empty() {
+                        self.frames.push_back(frame);
+                    }
+                }
+                _ if self.buffer.len() >= MAX_FRAME_LENGTH => self.buffer.clear(),
+                _ => self.buffer.push(*byte),
+            }
+        }
+    }
+
+    /// The next finished frame, without its line ending
+    pub fn next_frame(&mut self) -> Option<String> {
+        self.frames.pop_front()
+    }
+
+    /// The bytes of the unfinished frame
+    pub fn pending(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Throw away the unfinished frame, e.g. what was cut off before the
+    /// connection was opened
+    pub fn discard_unfinished(&mut self) {
+        self.buffer.clear();
+    }
+}
+impl Iterator for StreamingDecoder {
+    type Item = Result<Response, ClientError>;
+
+    /// Decode the next finished frame
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().map(|frame| Frame::decode(&frame))
+    }
+}
@@ -5,18 +5,38 @@
 
 /***** Setup *****/
 // Imports
-use num_derive::FromPrimitive;
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
-use std::{convert::TryFrom, fmt::Display, mem::transmute};
+use std::{convert::TryFrom, fmt::Display};
 use thiserror::Error as ThisError;
+#[macro_use]
+mod conversions;
+pub mod builders;
+pub use builders::*;
+pub mod frame;
+pub use frame::*;
+pub mod transport;
+pub use transport::*;
+pub mod startup_sync;
+pub use startup_sync::*;
+pub mod arq;
+pub use arq::*;
+pub mod protocol;
+pub use protocol::*;
+pub mod round_trip;
+pub use round_trip::*;
+pub mod systemd;
+pub use systemd::*;
 
 // Constants
 pub const BAUD_RATE: u32 = 115200_u32;
+/// The most breakpoints an uploaded plan can have, see [`UploadPlanArguments`]
+pub const MAX_PLAN_BREAKPOINTS: usize = 16;
 
 /***** Events *****/
 
 /// Event encapsulating a request or response
+#[derive(Debug, Clone)]
 pub struct Event<S>
 where
     S: Serialize + for<'a> Deserialize<'a>,
@@ -39,12 +59,74 @@ pub enum ClientError {
     Run(String),
     #[error("There was an error with the serial connection: {0}")]
     Serial(String),
+    #[error("There was an error with the Bluetooth bridge: {0}")]
+    Bluetooth(String),
     #[error("An unknown error occurred: {0}")]
     Unknown(String),
     #[error("Failed handling CSV file: {0}")]
     CSV(String),
+    /// Which error the server returned, and its message
+    #[error("{}: {1}", .0.to_string())]
+    Server(ServerError, String),
+    #[error("Failed handling the data directory: {0}")]
+    DataDirectory(String),
+    #[error("Failed handling diagnostic bundle: {0}")]
+    Diagnostics(String),
+    #[error("Invalid config: {0}")]
+    Config(String),
+    #[error("Failed handling the wire log: {0}")]
+    WireLog(String),
+    #[error("Failed handling the settings file: {0}")]
+    Settings(String),
+    #[error("Failed handling the gateway: {0}")]
+    Gateway(String),
+    #[error("Failed handling annotations: {0}")]
+    Annotations(String),
+    #[error("Failed handling presets: {0}")]
+    Presets(String),
+    #[error("Failed handling the session: {0}")]
+    Session(String),
+    #[error("Failed handling the maintenance counters: {0}")]
+    Maintenance(String),
+    #[error("Failed handling the session log: {0}")]
+    SessionLog(String),
+    #[error("Failed writing the narrative: {0}")]
+    Narrative(String),
+    #[error("Failed handling the changelog: {0}")]
+    Changelog(String),
+    #[error("Failed running the plan: {0}")]
+    Plan(String),
+    #[error("Failed measuring how the car coasts: {0}")]
+    Coast(String),
+    /// An error loaded back from a diagnostic bundle, already formatted
     #[error("{0}")]
-    Server(String),
+    Recorded(String),
+    /// A request went unanswered, even after retrying it
+    #[error("No response to {command} after {attempts} attempts")]
+    Timeout { command: Command, attempts: usize },
+    /// A frame came in corrupted
+    #[error("Checksum mismatch: expected {expected:04X}, calculated {calculated:04X}")]
+    ChecksumMismatch {
+        /// The command of the frame, if that much could be read
+        command: Option<Command>,
+        expected: u16,
+        calculated: u16,
+    },
+    /// The car speaks a different version of the protocol, see
+    /// [`PROTOCOL_VERSION`]
+    #[error(
+        "The car speaks protocol version {car}, but this client speaks version {client}{}",
+        match .fallback {
+            true => format!(", so version {car} is spoken to it instead"),
+            false => ", so update the client".to_owned(),
+        }
+    )]
+    ProtocolVersion {
+        client: u32,
+        car: u32,
+        /// Whether the car's version is spoken to it instead
+        fallback: bool,
+    },
 }
 impl From<serde_json::Error> for ClientError {
     fn from(value: serde_json::Error) -> Self {
@@ -52,49 +134,41 @@ impl From<serde_json::Error> for ClientError {
     }
 }
 
-/// An error returned by the server
-#[repr(u8)]
-#[derive(Deserialize, Serialize, Debug, FromPrimitive, Clone, Copy)]
-pub enum ServerError {
-    MalformedRequestFailedPrefixParsing = 0_u8,
-    MalformedRequestFailedCommandParsing = 1_u8,
-    MalformedRequestFailedSeparatorParsing = 2_u8,
-    MalformedRequestFailedArgumentsParsing = 3_u8,
-    MalformedRequestFailedMetadataParsing = 4_u8,
-    MalformedRequestTypeError = 5_u8,
-    MalformedRequestOtherError = 6_u8,
-    _RequestErrorUpperBound = 7_u8,
-    _ResponseErrorLowerBound = 8_u8,
-    MalformedResponseTypeError = 10_u8,
-    MalformedResponseOtherError = 11_u8,
-    _ResponseErrorUpperBound = 12_u8,
-    _SpecificErrorLowerBound = 13_u8,
-    FailedToStartAlreadyStarted = 21_u8,
-    FailedToStartMagnetOdometerFailed = 22_u8,
-    FailedToStartMotorControlFailed = 23_u8,
-    FailedToStopNotStarted = 24_u8,
-    FailedToStopStartThreadWouldNotRespond = 25_u8,
-    FailedStatusCouldNotAcquireDistanceLock = 26_u8,
-    FailedPingNegativeLatency = 27_u8,
-    _SpecificErrorUpperBound = 28_u8,
-    AnyOtherError = 99_u8,
-}
-impl TryFrom<u8> for ServerError {
-    type Error = ();
-
-    fn try_from(value: u8) -> Result<Self, Self::Error> {
-        // Ensure not out of bounds
-        if (value > 0 && value <= Self::_RequestErrorUpperBound as u8)
-            || (value >= Self::_ResponseErrorLowerBound as u8
-                && value <= Self::_ResponseErrorUpperBound as u8)
-            || (value >= Self::_SpecificErrorLowerBound as u8
-                && value <= Self::_SpecificErrorUpperBound as u8)
-        {
-            Err(())?;
-        }
-        // Safety: not out of bounds
-        Ok(unsafe { transmute(value) })
+u8_enum! {
+    /// An error returned by the server
+    #[repr(u8)]
+    #[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ServerError {
+        MalformedRequestFailedPrefixParsing = 0_u8,
+        MalformedRequestFailedCommandParsing = 1_u8,
+        MalformedRequestFailedSeparatorParsing = 2_u8,
+        MalformedRequestFailedArgumentsParsing = 3_u8,
+        MalformedRequestFailedMetadataParsing = 4_u8,
+        MalformedRequestTypeError = 5_u8,
+        MalformedRequestOtherError = 6_u8,
+        _RequestErrorUpperBound = 7_u8,
+        _ResponseErrorLowerBound = 8_u8,
+        MalformedResponseTypeError = 10_u8,
+        MalformedResponseOtherError = 11_u8,
+        _ResponseErrorUpperBound = 12_u8,
+        _SpecificErrorLowerBound = 13_u8,
+        FailedToStartAlreadyStarted = 21_u8,
+        FailedToStartMagnetOdometerFailed = 22_u8,
+        FailedToStartMotorControlFailed = 23_u8,
+        FailedToStartCouldNotAcquireDistanceLock = 24_u8,
+        FailedToStopNotStarted = 25_u8,
+        FailedToStopStartThreadWouldNotRespond = 26_u8,
+        FailedStatusCouldNotAcquireDistanceLock = 27_u8,
+        FailedPingNegativeLatency = 28_u8,
+        _SpecificErrorUpperBound = 29_u8,
+        AnyOtherError = 99_u8,
     }
+    // Only there to mark where each kind of error starts and ends
+    except _RequestErrorUpperBound
+        | _ResponseErrorLowerBound
+        | _ResponseErrorUpperBound
+        | _SpecificErrorLowerBound
+        | _SpecificErrorUpperBound
 }
 impl ToString for ServerError {
     fn to_string(&self) -> String {
@@ -122,29 +196,68 @@ impl ToString for ServerError {
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct ErrorResponse {
     /// This is a u8 for Serde
     pub error_variant: u8,
     pub message: String,
 }
+impl From<&ErrorResponse> for ClientError {
+    fn from(value: &ErrorResponse) -> Self {
+        Self::Server(
+            ServerError::try_from(value.error_variant).unwrap_or(ServerError::AnyOtherError),
+            value.message.clone(),
+        )
+    }
+}
 
 /***** Generic bindings *****/
 
 /// Metadata is sent alongside with the request and response
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub struct MetaData {
     pub time: f64,
+    /// Numbers a request so its response can be matched up to it; responses
+    /// carry the sequence of the request they answer, and statuses sent on
+    /// their own have none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u32>,
+    /// Numbers the frames sent between the client and the car, separately in
+    /// each direction, so one that goes missing can be asked for again; see
+    /// [`ArqTransport`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frame: Option<u32>,
+    /// Sent back by the server with the response to a request, so the round
+    /// trip can be split into the time spent on the car and the time spent
+    /// getting there and back; see [`RoundTrip`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub echo: Option<Echo>,
+    /// Which of the clients sharing the serial bridge sent a request, added
+    /// by the bridge and sent back by the server with the response so only
+    /// that client is given it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<u32>,
 }
 
-/// The mode of transit
-#[repr(u8)]
-#[derive(FromPrimitive)]
-pub enum TransitMode {
-    ClientToServerRequest = b'?',
-    ServerToClientResponse = b'~',
-    ClientToSerialBridgeRequest = b'^',
-    SerialBridgeToClientResponse = b'&',
+/// What the server sends back about the request a response answers
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Echo {
+    /// The `time` the request was sent with, by the client's clock
+    pub time: f64,
+    /// When the server read the request, by its own clock
+    pub received: f64,
+}
+
+u8_enum! {
+    /// The mode of transit
+    #[repr(u8)]
+    #[derive(PartialEq, Eq, Debug, Clone, Copy)]
+    pub enum TransitMode {
+        ClientToServerRequest = b'?',
+        ServerToClientResponse = b'~',
+        ClientToSerialBridgeRequest = b'^',
+        SerialBridgeToClientResponse = b'&',
+    }
 }
 impl From<Command> for TransitMode {
     fn from(value: Command) -> Self {
@@ -157,15 +270,23 @@ impl From<Command> for TransitMode {
             Status => ClientToServerRequest,
             StaticStatus => ClientToServerRequest,
             Error => ClientToServerRequest,
+            Nack => ClientToServerRequest,
+            Hello => ClientToServerRequest,
+            UploadPlan => ClientToServerRequest,
+            Tune => ClientToServerRequest,
+            Calibrate => ClientToServerRequest,
             Connect => ClientToSerialBridgeRequest,
             Disconnect => ClientToSerialBridgeRequest,
             BluetoothStatus => ClientToSerialBridgeRequest,
+            BluetoothScan => ClientToSerialBridgeRequest,
+            BluetoothMetrics => ClientToSerialBridgeRequest,
+            Watchdog => ClientToSerialBridgeRequest,
         }
     }
 }
 
 /// The type of transit
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum TransitType {
     Request,
     Response,
@@ -173,7 +294,7 @@ pub enum TransitType {
 
 /***** Commands *****/
 
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub enum Command {
     /* Server commands */
     Ping,
@@ -182,10 +303,28 @@ pub enum Command {
     StaticStatus,
     Status,
     Error,
+    /// Asks for a frame to be sent again, either way between the client and
+    /// the car
+    Nack,
+    /// Says which version of the protocol is spoken, see [`PROTOCOL_VERSION`]
+    Hello,
+    /// Starts a run the car carries out on its own, see
+    /// [`UploadPlanArguments`]
+    UploadPlan,
+    /// Sets how the car eases up to the distance, see [`TuneArguments`]
+    Tune,
+    /// Works out the wheel's real circumference from how far the last run
+    /// went, see [`CalibrateArguments`]
+    Calibrate,
     /* Serial bridge commands */
     Connect,
     Disconnect,
     BluetoothStatus,
+    BluetoothScan,
+    /// How well the bridge's link to the device is doing
+    BluetoothMetrics,
+    /// Only ever sent by the bridge, on its own
+    Watchdog,
 }
 impl TryFrom<String> for Command {
     type Error = ClientError; /* Potential type collision */
@@ -199,9 +338,17 @@ impl TryFrom<String> for Command {
             "STATICSTATUS" => Ok(StaticStatus),
             "STATUS" => Ok(Status),
             "UNKNOWN" | "ERROR" => Ok(Error),
+            "NACK" => Ok(Nack),
+            "HELLO" => Ok(Hello),
+            "UPLOADPLAN" => Ok(UploadPlan),
+            "TUNE" => Ok(Tune),
+            "CALIBRATE" => Ok(Calibrate),
             "CONNECT" => Ok(Connect),
             "DISCONNECT" => Ok(Disconnect),
             "BLUETOOTHSTATUS" => Ok(BluetoothStatus),
+            "SCAN" => Ok(BluetoothScan),
+            "METRICS" => Ok(BluetoothMetrics),
+            "WATCHDOG" => Ok(Watchdog),
             _ => Err(ClientError::Parse(format!(
                 "Failed to parse command from {value}"
             ))),
@@ -221,9 +368,17 @@ impl Display for Command {
                 StaticStatus => "STATICSTATUS",
                 Status => "STATUS",
                 Error => "ERROR",
+                Nack => "NACK",
+                Hello => "HELLO",
+                UploadPlan => "UPLOADPLAN",
+                Tune => "TUNE",
+                Calibrate => "CALIBRATE",
                 Connect => "CONNECT",
                 Disconnect => "DISCONNECT",
                 BluetoothStatus => "BLUETOOTHSTATUS",
+                BluetoothScan => "SCAN",
+                BluetoothMetrics => "METRICS",
+                Watchdog => "WATCHDOG",
             }
         )
     }
@@ -232,6 +387,7 @@ impl Display for Command {
 /***** Request and response bindings for commands *****/
 
 /// Possible responses
+#[derive(Debug)]
 pub enum Response {
     Ping(Event<PingResponse>),
     Start(Event<StartResponse>),
@@ -239,7 +395,15 @@ pub enum Response {
     Status(Event<StatusResponse>),
     StaticStatus(Event<StaticStatusResponse>),
     Error(Event<ErrorResponse>),
+    Hello(Event<HelloResponse>),
+    UploadPlan(Event<UploadPlanResponse>),
+    Tune(Event<TuneResponse>),
+    Calibrate(Event<CalibrateResponse>),
     BluetoothStatus(Event<BluetoothStatusResponse>),
+    BluetoothScan(Event<BluetoothScanResponse>),
+    BluetoothConnect(Event<BluetoothConnectResponse>),
+    BluetoothMetrics(Event<BluetoothMetricsResponse>),
+    Watchdog(Event<WatchdogResponse>),
 }
 
 // Ping
@@ -248,62 +412,293 @@ pub enum Response {
 pub struct PingArguments {
     pub time: f64,
 }
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct PingResponse {
     pub sent_time: f64,
 }
 
+// Nack
+
+/// Never answered; the frame asked for is just sent again if it's still kept
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NackArguments {
+    /// The number of the frame to send again
+    pub frame: u32,
+}
+
+// Hello
+
+/// Sent on connecting; firmware from before version 2 answers with an error
+/// instead, see [`predates_hello`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HelloArguments {
+    pub protocol_version: u32,
+}
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HelloResponse {
+    pub protocol_version: u32,
+}
+
 // Start
 
 #[derive(Serialize, Deserialize)]
 pub struct StartArguments {
     pub distance: f64,
     pub reverse_brake: bool,
+    /// Centimeters per second the server keeps the car under by cutting motor
+    /// power, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_velocity: Option<f64>,
+    /// How the server works out how far the car has gone, or `None` for its
+    /// default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance_estimator: Option<DistanceEstimatorKind>,
+    /// Centimeters per second squared the car slows down by once power is
+    /// cut, for the server to cut it early enough to coast to the distance,
+    /// or `None` to cut it a fixed leeway before
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coast_deceleration: Option<f64>,
 }
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct StartResponse;
 
+// Upload plan
+
+/// A run the car carries out on its own once it's been sent, changing the
+/// speed limit at each breakpoint it goes past, so it finishes even if the
+/// link to the client drops
+///
+/// The speed limit stands in for a power level, as the relays can only turn
+/// the motor on or off. Until the first breakpoint, the start's
+/// `max_velocity` is kept to. Once the run is over, the plan is sent back in
+/// the status with when each breakpoint was reached, see
+/// [`StatusResponse::executed_plan`].
+#[derive(Serialize, Deserialize)]
+pub struct UploadPlanArguments {
+    pub start: StartArguments,
+    /// In order of distance, and all before the start's distance
+    pub breakpoints: Vec<PlanBreakpoint>,
+}
+impl UploadPlanArguments {
+    /// Check the breakpoints make sense for the start's distance, saying
+    /// what's wrong if they don't
+    pub fn check(&self) -> Result<(), String> {
+        if self.breakpoints.len() > MAX_PLAN_BREAKPOINTS {
+            return Err(format!(
+                "A plan can have at most {MAX_PLAN_BREAKPOINTS} breakpoints, not {}",
+                self.breakpoints.len()
+            ));
+        }
+        let mut previous = None::<f64>;
+        for breakpoint in self.breakpoints.iter() {
+            if !(breakpoint.distance >= 0.0 && breakpoint.distance < self.start.distance) {
+                return Err(format!(
+                    "The breakpoint at {}cm isn't between 0cm and the distance of {}cm",
+                    breakpoint.distance, self.start.distance
+                ));
+            }
+            if previous.is_some_and(|previous| breakpoint.distance <= previous) {
+                return Err(format!(
+                    "The breakpoint at {}cm is out of order",
+                    breakpoint.distance
+                ));
+            }
+            if breakpoint
+                .max_velocity
+                .is_some_and(|max_velocity| !(max_velocity > 0.0 && max_velocity.is_finite()))
+            {
+                return Err(format!(
+                    "The speed limit at {}cm has to be over 0cm/s",
+                    breakpoint.distance
+                ));
+            }
+            previous = Some(breakpoint.distance);
+        }
+        Ok(())
+    }
+}
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct PlanBreakpoint {
+    /// Centimeters from the start
+    pub distance: f64,
+    /// Centimeters per second from here on, or `None` for no limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_velocity: Option<f64>,
+}
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UploadPlanResponse;
+
+/// A breakpoint of an uploaded plan, with when the car went past it
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ExecutedBreakpoint {
+    /// Centimeters from the start
+    pub distance: f64,
+    /// Centimeters per second
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_velocity: Option<f64>,
+    /// Seconds into the run, or `None` if the run was over first
+    #[serde(default)]
+    pub reached: Option<f64>,
+}
+
+// Tune
+
+/// Gains of the controller the server eases the car up to the distance with
+/// over the last stretch, instead of running it at full power until it's
+/// close enough to cut
+///
+/// The relays can only turn the motor on or off, so the controller's output
+/// is how much of the time the motor is on, from 0 to 1.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ApproachGains {
+    /// Per centimeter left to go
+    pub proportional: f64,
+    /// Per centimeter second left to go, built up over the approach
+    pub integral: f64,
+    /// Per centimeter per second the car is going, holding it back
+    pub derivative: f64,
+}
+impl ApproachGains {
+    /// Check every gain can be used, saying which can't if one can't
+    pub fn check(&self) -> Result<(), String> {
+        for (name, gain) in [
+            ("proportional", self.proportional),
+            ("integral", self.integral),
+            ("derivative", self.derivative),
+        ] {
+            if !(gain >= 0.0 && gain.is_finite()) {
+                return Err(format!("The {name} gain has to be 0 or more, not {gain}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The gains for the server to ease the car up to the distance with from now
+/// on, kept across restarts, or `None` to go back to cutting power once it's
+/// close enough
+#[derive(Serialize, Deserialize)]
+pub struct TuneArguments {
+    pub approach_gains: Option<ApproachGains>,
+}
+/// The gains in effect now
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TuneResponse {
+    pub approach_gains: Option<ApproachGains>,
+}
+
+// Calibrate
+
+/// How far the last run really went, measured by hand from where the car
+/// started to where it stopped
+///
+/// The server's odometer counted the magnets going by on the way, so the
+/// wheel's real circumference (with any slip) is its circumference scaled by
+/// how much further or shorter the car really went. It's kept across
+/// restarts and used for every run from then on.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct CalibrateArguments {
+    /// Centimeters
+    pub distance: f64,
+}
+impl CalibrateArguments {
+    /// Check the distance could have been gone
+    pub fn check(&self) -> Result<(), String> {
+        match self.distance > 0.0 && self.distance.is_finite() {
+            true => Ok(()),
+            false => Err(format!(
+                "The distance gone has to be over 0cm, not {}",
+                self.distance
+            )),
+        }
+    }
+}
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct CalibrateResponse {
+    /// Centimeters, what the wheel was taken to be around before
+    pub previous_wheel_circumference: f64,
+    /// Centimeters, what it's taken to be around now
+    pub wheel_circumference: f64,
+}
+
 // Stop
 #[derive(Serialize, Deserialize)]
 pub struct StopArguments;
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct StopResponse;
 
 // Static status
 
 #[derive(Serialize, Deserialize)]
 pub struct StaticStatusArguments;
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct StaticStatusResponse {
     pub number_of_magnets: usize,
     pub wheel_diameter: f64,
+    /// How the distance is being worked out, or `None` from firmware without
+    /// a choice of estimators
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance_estimator: Option<DistanceEstimatorKind>,
+    /// The gains the car is eased up to the distance with, see
+    /// [`Command::Tune`], or `None` if it isn't
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approach_gains: Option<ApproachGains>,
+    /// Centimeters, the wheel's real circumference once it's been worked out
+    /// with [`Command::Calibrate`], or `None` to go by the diameter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wheel_circumference: Option<f64>,
+}
+u8_enum! {
+    /// How the server works out how far the car has gone, chosen for each run
+    /// so improvements can be compared against each other
+    #[repr(u8)]
+    #[derive(Deserialize_repr, Serialize_repr, Default, Clone, Copy, PartialEq, Eq, Debug)]
+    pub enum DistanceEstimatorKind {
+        /// Counting the magnets going past the hall effect sensors
+        #[default]
+        Odometer = 0_u8,
+        /// The odometer, filled in between magnets by an accelerometer
+        AccelerometerFusion = 1_u8,
+        /// The odometer, corrected by a rangefinder facing the way it's going
+        Rangefinder = 2_u8,
+    }
+}
+impl DistanceEstimatorKind {
+    pub const ALL: [Self; 3] = [Self::Odometer, Self::AccelerometerFusion, Self::Rangefinder];
+}
+impl Display for DistanceEstimatorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use DistanceEstimatorKind::*;
+        write!(
+            f,
+            "{}",
+            match *self {
+                Odometer => "Odometer",
+                AccelerometerFusion => "Odometer + accelerometer",
+                Rangefinder => "Rangefinder assisted",
+            }
+        )
+    }
 }
 
 // Regular (dynamic) status
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct DistanceInformation {
     /// Centimeters
     pub distance: f64,
     pub velocity: f64,
     pub magnet_hit_counter: usize,
 }
-#[repr(u8)]
-#[derive(Deserialize_repr, Serialize_repr, Clone, Copy)]
-pub enum StatusStage {
-    Stopped = 0_u8,
-    Finalized = 4_u8,
-    VehementForward = 1_u8,
-    StallOvershoot = 2_u8,
-    CautiousBackward = 3_u8,
-}
-impl TryFrom<u8> for StatusStage {
-    type Error = ();
-    fn try_from(value: u8) -> Result<Self, Self::Error> {
-        if value > Self::Finalized as u8 {
-            Err(())?;
-        }
-        Ok(unsafe { transmute((Self::Stopped as u8) + value) })
+u8_enum! {
+    #[repr(u8)]
+    #[derive(Deserialize_repr, Serialize_repr, Clone, Copy, Debug)]
+    pub enum StatusStage {
+        Stopped = 0_u8,
+        Finalized = 4_u8,
+        VehementForward = 1_u8,
+        StallOvershoot = 2_u8,
+        CautiousBackward = 3_u8,
     }
 }
 impl Display for StatusStage {
@@ -324,22 +719,79 @@ impl Display for StatusStage {
 }
 #[derive(Serialize, Deserialize)]
 pub struct StatusArguments;
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct StatusResponse {
     pub running: bool,
     pub uptime: usize,
     pub runtime: usize,
     pub stage: StatusStage,
     pub distance: DistanceInformation,
+    /// How many times the car went over the maximum velocity this run
+    #[serde(default)]
+    pub speed_limit_violations: usize,
+    /// The plan the last run was uploaded with (see [`Command::UploadPlan`])
+    /// and how it went, once it's over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executed_plan: Option<Vec<ExecutedBreakpoint>>,
+    /// Missing from servers without a radio to stop the car with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub radio_stop: Option<RadioStopStatus>,
+    /// Missing from servers without a way to measure the battery
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub power: Option<PowerStatus>,
+}
+
+/// The stop button on a radio the server listens to besides the link to the
+/// client, so the car can still be stopped if the link is lost
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct RadioStopStatus {
+    /// Whether it's being held right now, which stops any run straight away
+    pub held: bool,
+    /// How many runs it stopped since the server started
+    pub stops: usize,
+}
+
+/// The battery and what the motor draws from it, as measured by the car
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct PowerStatus {
+    /// Volts
+    pub battery_voltage: f64,
+    /// Amps
+    pub motor_current: f64,
+    /// Percent left, going by the battery voltage, if the server knows what
+    /// battery it has
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub charge: Option<f64>,
 }
 
 // Bluetooth connect
 
-#[derive(Serialize, Deserialize)]
-pub struct BluetoothConnectRequest;
+/// Sent without arguments by older clients, so read as an
+/// `Option<BluetoothConnectRequest>`
+#[derive(Serialize, Deserialize, Default)]
+pub struct BluetoothConnectRequest {
+    /// The device to connect to, e.g. `00:60:37:E9:0B:6F`, or `None` for the
+    /// bridge's default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+}
 
-#[derive(Serialize, Deserialize)]
-pub struct BluetoothConnectResponse;
+/// How connecting is going, sent by the bridge every so often while it looks
+/// for the device and once more when it's done
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BluetoothConnectResponse {
+    /// Seconds since the bridge started looking
+    pub elapsed: f64,
+    /// Every device found so far, whether it's the one being connected to or
+    /// not
+    pub devices: Vec<BluetoothDevice>,
+    /// `None` while still looking, then whether it connected
+    pub connected: Option<bool>,
+    /// The device being looked for, and once connected, the one connected
+    /// to, which older bridges don't send
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+}
 
 // Bluetooth disconnect
 
@@ -352,30 +804,86 @@ pub struct BluetoothDisconnectResponse;
 
 #[derive(Serialize, Deserialize)]
 pub struct BluetoothStatusRequest;
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct BluetoothStatusResponse {
     pub connected: bool,
 }
 
+// Bluetooth scan
+
+#[derive(Serialize, Deserialize)]
+pub struct BluetoothScanRequest;
+/// A device found advertising the serial service
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BluetoothDevice {
+    pub address: String,
+    pub name: Option<String>,
+    /// Signal strength in dBm, if the device has been heard from
+    pub rssi: Option<i16>,
+}
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BluetoothScanResponse {
+    /// Strongest signal first
+    pub devices: Vec<BluetoothDevice>,
+}
+
+// Bluetooth metrics
+
+#[derive(Serialize, Deserialize)]
+pub struct BluetoothMetricsRequest;
+/// How the bridge's link to the device has been doing since the bridge
+/// started, for telling a flaky link apart from a flaky car
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct BluetoothMetricsResponse {
+    /// The device connected to, if any
+    pub address: Option<String>,
+    /// Signal strength of the device in dBm, if it's been heard from
+    pub rssi: Option<i16>,
+    /// Written to the device
+    pub bytes_sent: u64,
+    /// Read from the device, not counting the padding
+    pub bytes_received: u64,
+    /// Times the bridge connected again after being connected, whether asked
+    /// to or by its watchdog
+    pub reconnects: u32,
+    /// Values read from a device that doesn't notify that were thrown away
+    /// as the same value read twice
+    pub duplicates_discarded: u64,
+}
+
+// Watchdog
+
+/// Sent by the bridge on its own when the device it's connected to stops
+/// answering, just before it reconnects
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WatchdogResponse {
+    /// Seconds since the first frame sent to the device that went unanswered
+    pub silent_for: f64,
+    /// The device being reconnected to
+    pub address: String,
+}
+
 /***** Client status *****/
 
-#[repr(u8)]
-#[derive(Default, Copy, Clone)]
-pub enum ClientStatus {
-    #[default]
-    GatheringData = 0_u8,
-    // Pinging
-    SendingPing = 1_u8,
-    ReceivingPing = 2_u8,
-    // Static status
-    RequestingStaticStatus = 3_u8,
-    ReceivingStaticStatus = 4_u8,
-    // Dynamic status
-    RequestingStart = 5_u8,
-    ReceivingStatus = 6_u8,
-    // Stopping
-    RequestingStop = 7_u8,
-    Finished = 8_u8,
+u8_enum! {
+    #[repr(u8)]
+    #[derive(Default, Copy, Clone, PartialEq, Eq, Debug, Serialize_repr, Deserialize_repr)]
+    pub enum ClientStatus {
+        #[default]
+        GatheringData = 0_u8,
+        // Pinging
+        SendingPing = 1_u8,
+        ReceivingPing = 2_u8,
+        // Static status
+        RequestingStaticStatus = 3_u8,
+        ReceivingStaticStatus = 4_u8,
+        // Dynamic status
+        RequestingStart = 5_u8,
+        ReceivingStatus = 6_u8,
+        // Stopping
+        RequestingStop = 7_u8,
+        Finished = 8_u8,
+    }
 }
 impl ToString for ClientStatus {
     fn to_string(&self) -> String {
@@ -394,16 +902,6 @@ impl ToString for ClientStatus {
         .into()
     }
 }
-impl TryFrom<u8> for ClientStatus {
-    type Error = ();
-
-    fn try_from(value: u8) -> Result<Self, Self::Error> {
-        if value > Self::Finished as u8 {
-            Err(())?;
-        }
-        Ok(unsafe { transmute((Self::GatheringData as u8) + value) })
-    }
-}
 impl ClientStatus {
     /// If at a boundary, this will return the same thing
     pub fn next(self) -> Self {
@@ -0,0 +1,60 @@
+/*!
+ * Which version of the protocol is spoken, agreed on with `HELLO` when
+ * connecting, so older firmware is spoken to the way it expects instead of
+ * silently misreading newer frames
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::*;
+
+// Constants
+/// The version of the protocol spoken here, sent with `HELLO`
+///
+/// 1. The original frames, with only the time in the metadata
+/// 2. Checksums, sequence and frame numbers, `NACK`s, and `HELLO`
+pub const PROTOCOL_VERSION: u32 = 2;
+/// The oldest version that can still be spoken, by leaving out what it
+/// doesn't know about
+pub const OLDEST_PROTOCOL_VERSION: u32 = 1;
+/// The first version with checksums, sequence and frame numbers, `NACK`s, and
+/// `HELLO`
+pub const NUMBERED_PROTOCOL_VERSION: u32 = 2;
+
+/***** Negotiation *****/
+
+/// Agree on a version with the version the other end speaks, returning the
+/// version to speak from now on
+///
+/// A version that differs is always an error, though older versions can
+/// still be spoken, see [`ClientError::ProtocolVersion`]; for newer ones, this
+/// end keeps speaking its own.
+pub fn negotiate_protocol_version(car: u32) -> (u32, Result<(), ClientError>) {
+    if car == PROTOCOL_VERSION {
+        return (PROTOCOL_VERSION, Ok(()));
+    }
+    let fallback = (OLDEST_PROTOCOL_VERSION..PROTOCOL_VERSION).contains(&car);
+    let version = match fallback {
+        true => car,
+        false => PROTOCOL_VERSION,
+    };
+    (
+        version,
+        Err(ClientError::ProtocolVersion {
+            client: PROTOCOL_VERSION,
+            car,
+            fallback,
+        }),
+    )
+}
+
+/// Whether an error came from firmware older than `HELLO`, which doesn't know
+/// the command, in answer to it
+///
+/// Such firmware doesn't number its responses either, so an answer to `HELLO`
+/// is one without a sequence.
+pub fn predates_hello(error: &Event<ErrorResponse>) -> bool {
+    error.metadata.sequence.is_none()
+        && error.value.error_variant == ServerError::MalformedRequestFailedCommandParsing as u8
+}
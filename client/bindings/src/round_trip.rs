@@ -0,0 +1,84 @@
+/*!
+ * How long requests take to be answered, split into the time spent on the
+ * car and the time spent getting there and back
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::*;
+
+/***** Round trip *****/
+
+/// How long a request took to be answered, in seconds
+///
+/// Each half is measured by one clock only (the client's for the whole round
+/// trip, the server's for its part), so the clocks being off from each other
+/// doesn't matter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoundTrip {
+    /// From sending the request to receiving the response
+    pub total: f64,
+    /// From the server reading the request to it sending the response
+    pub server: f64,
+}
+impl RoundTrip {
+    /// The round trip of a response received at a Unix time in seconds, if
+    /// the server echoed the request back
+    ///
+    /// A request sent again is sent as is, so its round trip counts from the
+    /// first time it was sent.
+    pub fn of(metadata: &MetaData, received_time: f64) -> Option<Self> {
+        let echo = metadata.echo?;
+        Some(Self {
+            total: (received_time - echo.time).max(0.0),
+            server: (metadata.time - echo.received).max(0.0),
+        })
+    }
+
+    /// The time spent getting to the server and back (over the serial port,
+    /// the bridge, and Bluetooth)
+    pub fn link(&self) -> f64 {
+        (self.total - self.server).max(0.0)
+    }
+}
+
+/***** Response *****/
+
+/// Run the same expression on the event of any response
+macro_rules! on_event {
+    ($response:expr, $event:ident => $body:expr) => {
+        match $response {
+            Response::Ping($event) => $body,
+            Response::Start($event) => $body,
+            Response::Stop($event) => $body,
+            Response::Status($event) => $body,
+            Response::StaticStatus($event) => $body,
+            Response::Error($event) => $body,
+            Response::Hello($event) => $body,
+            Response::UploadPlan($event) => $body,
+            Response::Tune($event) => $body,
+            Response::Calibrate($event) => $body,
+            Response::BluetoothStatus($event) => $body,
+            Response::BluetoothScan($event) => $body,
+            Response::BluetoothConnect($event) => $body,
+            Response::BluetoothMetrics($event) => $body,
+            Response::Watchdog($event) => $body,
+        }
+    };
+}
+
+impl Response {
+    pub fn command(&self) -> Command {
+        on_event!(self, event => event.command)
+    }
+
+    pub fn metadata(&self) -> &MetaData {
+        on_event!(self, event => &event.metadata)
+    }
+
+    /// See [`RoundTrip::of`]
+    pub fn round_trip(&self, received_time: f64) -> Option<RoundTrip> {
+        RoundTrip::of(self.metadata(), received_time)
+    }
+}
@@ -0,0 +1,72 @@
+/*!
+ * Getting past whatever was left on a connection before it was opened (half
+ * sent frames, the Arduino's boot messages), so the first request isn't read
+ * back mangled
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{ClientError, Frame, Transport};
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+// Constants
+/// How long nothing has to come in for before the connection is taken to be
+/// quiet, with nothing left over
+pub const SYNC_QUIET_TIME: Duration = Duration::from_millis(100);
+/// How often the connection is read while synchronizing
+const SYNC_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/***** Startup synchronization *****/
+
+/// How synchronizing went
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Synchronization {
+    /// Lines thrown away for not being frames
+    pub discarded: usize,
+    /// Whether a whole frame came in, which was thrown away too as it can't
+    /// answer anything sent on this connection
+    pub found_frame: bool,
+    /// Whether the time budget ran out before the connection was quiet
+    pub timed_out: bool,
+}
+
+/// Throw away what's left on a freshly opened connection, before anything is
+/// sent over it
+///
+/// Lines are read and thrown away until one is a frame (whatever comes after
+/// it is left to be read), or nothing comes in for [`SYNC_QUIET_TIME`]
+/// (throwing away anything unfinished too), or the `budget` runs out.
+pub fn synchronize(
+    transport: &mut dyn Transport,
+    budget: Duration,
+) -> Result<Synchronization, ClientError> {
+    let start_time = Instant::now();
+    let mut last_line_time = start_time;
+    let mut synchronization = Synchronization::default();
+
+    loop {
+        match transport.read_line()? {
+            Some(line) if Frame::decode(&line).is_ok() => {
+                synchronization.found_frame = true;
+                return Ok(synchronization);
+            }
+            Some(_) => {
+                synchronization.discarded += 1;
+                last_line_time = Instant::now();
+            }
+            None if last_line_time.elapsed() >= SYNC_QUIET_TIME => break,
+            None => sleep(SYNC_POLL_INTERVAL),
+        }
+        if start_time.elapsed() >= budget {
+            synchronization.timed_out = true;
+            break;
+        }
+    }
+
+    transport.discard_unfinished();
+    Ok(synchronization)
+}
@@ -0,0 +1,135 @@
+/*!
+ * Telling systemd when a process is ready and that it's still alive, so its
+ * watchdog can restart it if it hangs
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+#[cfg(not(target_os = "linux"))]
+use no_systemd::{notify, watchdog_enabled, NotifyState};
+#[cfg(target_os = "linux")]
+use sd_notify::{notify, watchdog_enabled, NotifyState};
+use std::time::{Duration, Instant};
+
+// Constants
+/// What systemd sets for a service to notify it, which shouldn't be passed on
+/// to anything it spawns, or systemd would hear from that instead
+pub const NOTIFY_VARIABLES: [&str; 3] = ["NOTIFY_SOCKET", "WATCHDOG_USEC", "WATCHDOG_PID"];
+
+/***** Notifier *****/
+
+/// Notifies systemd of the process being ready, still being alive, and what
+/// it's doing
+///
+/// Nothing is sent if the process wasn't started by systemd with
+/// `Type=notify`, and no heartbeats are sent unless it set `WatchdogSec`.
+/// Off Linux, there's no systemd and nothing is ever sent.
+#[derive(Default, Debug)]
+pub struct SystemdNotifier {
+    /// Half of the watchdog's timeout, if it's on
+    heartbeat_interval: Option<Duration>,
+    last_heartbeat: Option<Instant>,
+    ready: bool,
+    status: String,
+    /// Never notifies, see [`Self::disabled`]
+    disabled: bool,
+}
+impl SystemdNotifier {
+    /// Go by what systemd set in the environment
+    pub fn from_env() -> Self {
+        let mut watchdog_usec = 0;
+        let heartbeat_interval = watchdog_enabled(false, &mut watchdog_usec)
+            .then(|| Duration::from_micros(watchdog_usec / 2));
+        Self {
+            heartbeat_interval,
+            ..Default::default()
+        }
+    }
+
+    /// Never notify, for something run inside of a process that notifies
+    /// systemd itself (e.g. the bridge embedded in the GUI)
+    pub fn disabled() -> Self {
+        Self {
+            disabled: true,
+            ..Default::default()
+        }
+    }
+
+    /// How often a heartbeat is sent, if the watchdog is on
+    pub fn heartbeat_interval(&self) -> Option<Duration> {
+        self.heartbeat_interval
+    }
+
+    /// Tell systemd the process has started up, only the first time
+    pub fn ready(&mut self) {
+        if !self.ready {
+            self.ready = true;
+            self.send(&[NotifyState::Ready]);
+        }
+    }
+
+    /// Tell the watchdog the process is still alive
+    ///
+    /// Meant to be called from the main loop, so it stops once that hangs.
+    /// Only sent every [`Self::heartbeat_interval`], so this can be called as
+    /// often as wanted.
+    pub fn heartbeat(&mut self) {
+        let Some(heartbeat_interval) = self.heartbeat_interval else {
+            return;
+        };
+        if self
+            .last_heartbeat
+            .is_some_and(|last_heartbeat| last_heartbeat.elapsed() < heartbeat_interval)
+        {
+            return;
+        }
+        self.last_heartbeat = Some(Instant::now());
+        self.send(&[NotifyState::Watchdog]);
+    }
+
+    /// Tell systemd what the process is doing, shown by `systemctl status`,
+    /// only if it changed
+    pub fn status(&mut self, status: &str) {
+        if self.status != status {
+            self.status = status.to_owned();
+            self.send(&[NotifyState::Status(status)]);
+        }
+    }
+
+    /// Tell systemd the process is shutting down, so it isn't restarted for
+    /// going quiet
+    pub fn stopping(&mut self) {
+        self.send(&[NotifyState::Stopping]);
+    }
+
+    /// Failing to notify systemd isn't worth stopping over, as it'll restart
+    /// the process anyway if it doesn't hear from it
+    fn send(&self, states: &[NotifyState]) {
+        if !self.disabled {
+            let _ = notify(false, states);
+        }
+    }
+}
+
+/***** Without systemd *****/
+
+/// What's used of `sd_notify` off Linux, doing nothing
+#[cfg(not(target_os = "linux"))]
+mod no_systemd {
+    #[allow(dead_code)]
+    pub enum NotifyState<'a> {
+        Ready,
+        Watchdog,
+        Status(&'a str),
+        Stopping,
+    }
+
+    pub fn watchdog_enabled(_unset_env: bool, _usec: &mut u64) -> bool {
+        false
+    }
+
+    pub fn notify(_unset_env: bool, _states: &[NotifyState]) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,407 @@
+/*!
+ * What frames go over: a serial port, a TCP socket (e.g. over Wi-Fi when
+ * Bluetooth misbehaves), a Unix domain socket (e.g. to the bridge on the same
+ * machine), or an in-memory loopback for tests
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{ClientError, StreamingDecoder};
+use serialport::{new as new_serialport, SerialPort};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::{
+    collections::VecDeque,
+    io::{ErrorKind, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+
+// Constants
+/// Addresses starting with this are connected to over TCP, e.g.
+/// `tcp://raspberrypi.local:7878`
+pub const TCP_PREFIX: &str = "tcp://";
+/// Addresses starting with this are connected to over a Unix domain socket,
+/// by its path, e.g. `unix:///run/car-bridge.sock`
+pub const UNIX_PREFIX: &str = "unix://";
+/// How long connecting over TCP can take before giving up
+pub const TCP_CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/***** Transport *****/
+
+/// A connection lines can be sent and received over
+pub trait Transport: Send {
+    /// The next whole line that came in, without its line ending, or `None`
+    /// if there isn't one yet
+    ///
+    /// This never waits for more to come in, so it can be called every frame.
+    fn read_line(&mut self) -> Result<Option<String>, ClientError>;
+
+    /// Send a line, adding the line ending
+    fn write_line(&mut self, line: &str) -> Result<(), ClientError>;
+
+    /// Whether the other end is still there, as far as has been seen
+    fn connected(&self) -> bool;
+
+    /// Another handle to the same connection, e.g. to write to while reading
+    /// on another thread
+    ///
+    /// Lines are split up separately for each handle, so only one of them
+    /// should be read from.
+    fn try_clone(&self) -> Result<Box<dyn Transport>, ClientError>;
+
+    /// Speak a version of the protocol from now on, once it's agreed on with
+    /// the other end
+    ///
+    /// Only transports that change frames on the way (like [`ArqTransport`])
+    /// have anything to do.
+    ///
+    /// [`ArqTransport`]: crate::ArqTransport
+    fn speak_protocol_version(&mut self, _version: u32) {}
+
+    /// How many lines written are still waiting to go out
+    ///
+    /// Only transports that write in the background have any, everything
+    /// else has already gone out by the time [`Transport::write_line`]
+    /// returns.
+    fn queued(&self) -> usize {
+        0
+    }
+
+    /// Throw away whatever has come in of a line that hasn't finished, see
+    /// [`synchronize`](crate::synchronize)
+    ///
+    /// Only transports that split up lines themselves have anything to do.
+    fn discard_unfinished(&mut self) {}
+}
+
+/// Open a transport by its address: `tcp://<host>:<port>` to connect over TCP,
+/// `unix://<path>` to connect over a Unix domain socket, or else the path of a
+/// serial port
+pub fn open_transport(
+    address: &str,
+    baud_rate: u32,
+    timeout: Duration,
+) -> Result<Box<dyn Transport>, ClientError> {
+    if let Some(address) = address.strip_prefix(TCP_PREFIX) {
+        return Ok(Box::new(TcpTransport::connect(address)?));
+    }
+    if let Some(path) = address.strip_prefix(UNIX_PREFIX) {
+        #[cfg(unix)]
+        return Ok(Box::new(UnixTransport::connect(path)?));
+        #[cfg(not(unix))]
+        return Err(ClientError::Serial(format!(
+            "Can't connect to {path}, as there are no Unix domain sockets here"
+        )));
+    }
+    Ok(Box::new(SerialTransport::open(
+        address, baud_rate, timeout,
+    )?))
+}
+
+/// Read a line after receiving whatever is available, still giving back the
+/// lines received before the connection failed
+fn read_line_after(
+    received: Result<(), ClientError>,
+    decoder: &mut StreamingDecoder,
+    connected: &mut bool,
+) -> Result<Option<String>, ClientError> {
+    match received {
+        Ok(()) => Ok(decoder.next_frame()),
+        Err(e) => {
+            *connected = false;
+            match decoder.next_frame() {
+                Some(line) => Ok(Some(line)),
+                None => Err(e),
+            }
+        }
+    }
+}
+
+/// Move whatever is available on a socket read without blocking into the
+/// decoder
+fn receive_from<R: Read>(
+    stream: &mut R,
+    decoder: &mut StreamingDecoder,
+) -> Result<(), ClientError> {
+    let mut buffer = [0_u8; 1024];
+    loop {
+        match stream.read(&mut buffer) {
+            Ok(0) => Err(ClientError::Serial("The connection was closed".to_owned()))?,
+            Ok(bytes_read) => decoder.push_bytes(&buffer[..bytes_read]),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+            Err(e) if e.kind() == ErrorKind::Interrupted => (),
+            Err(e) => Err(ClientError::Serial(e.to_string()))?,
+        }
+    }
+}
+
+/***** Serial *****/
+
+pub struct SerialTransport {
+    serial: Box<dyn SerialPort>,
+    decoder: StreamingDecoder,
+    connected: bool,
+}
+impl SerialTransport {
+    pub fn new(serial: Box<dyn SerialPort>) -> Self {
+        Self {
+            serial,
+            decoder: StreamingDecoder::new(),
+            connected: true,
+        }
+    }
+
+    /// Open a serial port, waiting up to the timeout for writes
+    pub fn open(path: &str, baud_rate: u32, timeout: Duration) -> Result<Self, ClientError> {
+        new_serialport(path, baud_rate)
+            .timeout(timeout)
+            .open()
+            .map(Self::new)
+            .map_err(|e| ClientError::Serial(e.to_string()))
+    }
+
+    /// Move whatever is available into the decoder
+    fn receive(&mut self) -> Result<(), ClientError> {
+        let bytes_to_read = self
+            .serial
+            .bytes_to_read()
+            .map_err(|e| ClientError::Serial(e.to_string()))?;
+        if bytes_to_read > 0 {
+            let mut buffer = vec![0_u8; bytes_to_read as usize];
+            match self.serial.read(&mut buffer) {
+                Ok(bytes_read) => self.decoder.push_bytes(&buffer[..bytes_read]),
+                Err(e) if e.kind() == ErrorKind::TimedOut => (),
+                Err(e) => Err(ClientError::Serial(e.to_string()))?,
+            }
+        }
+        Ok(())
+    }
+}
+impl Transport for SerialTransport {
+    fn read_line(&mut self) -> Result<Option<String>, ClientError> {
+        let received = self.receive();
+        read_line_after(received, &mut self.decoder, &mut self.connected)
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), ClientError> {
+        writeln!(self.serial, "{line}").map_err(|e| {
+            self.connected = false;
+            ClientError::Serial(e.to_string())
+        })
+    }
+
+    fn connected(&self) -> bool {
+        self.connected
+    }
+
+    fn discard_unfinished(&mut self) {
+        self.decoder.discard_unfinished();
+    }
+
+    fn try_clone(&self) -> Result<Box<dyn Transport>, ClientError> {
+        self.serial
+            .try_clone()
+            .map(|serial| Box::new(Self::new(serial)) as Box<dyn Transport>)
+            .map_err(|e| ClientError::Serial(e.to_string()))
+    }
+}
+
+/***** TCP *****/
+
+/// A TCP socket, read without blocking
+pub struct TcpTransport {
+    stream: TcpStream,
+    decoder: StreamingDecoder,
+    connected: bool,
+}
+impl TcpTransport {
+    pub fn new(stream: TcpStream) -> Result<Self, ClientError> {
+        stream
+            .set_nonblocking(true)
+            .and_then(|()| stream.set_nodelay(true))
+            .map_err(|e| ClientError::Serial(e.to_string()))?;
+        Ok(Self {
+            stream,
+            decoder: StreamingDecoder::new(),
+            connected: true,
+        })
+    }
+
+    /// Connect to a `<host>:<port>`, giving up after [`TCP_CONNECT_TIMEOUT`]
+    pub fn connect(address: &str) -> Result<Self, ClientError> {
+        let socket_address = address
+            .to_socket_addrs()
+            .map_err(|e| ClientError::Serial(format!("Failed to look up {address}: {e}")))?
+            .next()
+            .ok_or_else(|| ClientError::Serial(format!("{address} has no addresses")))?;
+        let stream = TcpStream::connect_timeout(&socket_address, TCP_CONNECT_TIMEOUT)
+            .map_err(|e| ClientError::Serial(format!("Failed to connect to {address}: {e}")))?;
+        Self::new(stream)
+    }
+}
+impl Transport for TcpTransport {
+    fn read_line(&mut self) -> Result<Option<String>, ClientError> {
+        let received = receive_from(&mut self.stream, &mut self.decoder);
+        read_line_after(received, &mut self.decoder, &mut self.connected)
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), ClientError> {
+        self.stream
+            .write_all(format!("{line}\n").as_bytes())
+            .map_err(|e| {
+                self.connected = false;
+                ClientError::Serial(e.to_string())
+            })
+    }
+
+    fn connected(&self) -> bool {
+        self.connected
+    }
+
+    fn discard_unfinished(&mut self) {
+        self.decoder.discard_unfinished();
+    }
+
+    fn try_clone(&self) -> Result<Box<dyn Transport>, ClientError> {
+        self.stream
+            .try_clone()
+            .map_err(|e| ClientError::Serial(e.to_string()))
+            .and_then(Self::new)
+            .map(|transport| Box::new(transport) as Box<dyn Transport>)
+    }
+}
+
+/***** Unix domain socket *****/
+
+/// A Unix domain socket, read without blocking
+#[cfg(unix)]
+pub struct UnixTransport {
+    stream: UnixStream,
+    decoder: StreamingDecoder,
+    connected: bool,
+}
+#[cfg(unix)]
+impl UnixTransport {
+    pub fn new(stream: UnixStream) -> Result<Self, ClientError> {
+        stream
+            .set_nonblocking(true)
+            .map_err(|e| ClientError::Serial(e.to_string()))?;
+        Ok(Self {
+            stream,
+            decoder: StreamingDecoder::new(),
+            connected: true,
+        })
+    }
+
+    /// Connect to the socket at a path
+    pub fn connect(path: &str) -> Result<Self, ClientError> {
+        let stream = UnixStream::connect(path)
+            .map_err(|e| ClientError::Serial(format!("Failed to connect to {path}: {e}")))?;
+        Self::new(stream)
+    }
+}
+#[cfg(unix)]
+impl Transport for UnixTransport {
+    fn read_line(&mut self) -> Result<Option<String>, ClientError> {
+        let received = receive_from(&mut self.stream, &mut self.decoder);
+        read_line_after(received, &mut self.decoder, &mut self.connected)
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), ClientError> {
+        self.stream
+            .write_all(format!("{line}\n").as_bytes())
+            .map_err(|e| {
+                self.connected = false;
+                ClientError::Serial(e.to_string())
+            })
+    }
+
+    fn connected(&self) -> bool {
+        self.connected
+    }
+
+    fn discard_unfinished(&mut self) {
+        self.decoder.discard_unfinished();
+    }
+
+    fn try_clone(&self) -> Result<Box<dyn Transport>, ClientError> {
+        self.stream
+            .try_clone()
+            .map_err(|e| ClientError::Serial(e.to_string()))
+            .and_then(Self::new)
+            .map(|transport| Box::new(transport) as Box<dyn Transport>)
+    }
+}
+
+/***** Loopback *****/
+
+/// One end of an in-memory connection, for tests and for talking to
+/// something in the same process (e.g. the bridge embedded in the GUI)
+///
+/// Lines written to one end are read from the other. An end is disconnected
+/// once the other end (and every clone of it) is dropped.
+pub struct LoopbackTransport {
+    incoming: Arc<Mutex<VecDeque<String>>>,
+    outgoing: Arc<Mutex<VecDeque<String>>>,
+    /// Kept alive by this end and its clones
+    alive: Arc<()>,
+    other_end: Weak<()>,
+}
+impl LoopbackTransport {
+    /// Both ends of a new connection
+    pub fn pair() -> (Self, Self) {
+        let (a_to_b, b_to_a) = (Arc::default(), Arc::default());
+        let (a_alive, b_alive) = (Arc::new(()), Arc::new(()));
+        let (a_end, b_end) = (Arc::downgrade(&a_alive), Arc::downgrade(&b_alive));
+        (
+            Self {
+                incoming: Arc::clone(&b_to_a),
+                outgoing: Arc::clone(&a_to_b),
+                alive: a_alive,
+                other_end: b_end,
+            },
+            Self {
+                incoming: a_to_b,
+                outgoing: b_to_a,
+                alive: b_alive,
+                other_end: a_end,
+            },
+        )
+    }
+}
+impl Transport for LoopbackTransport {
+    fn read_line(&mut self) -> Result<Option<String>, ClientError> {
+        // Safety: nothing panics while holding the lock
+        match self.incoming.lock().unwrap().pop_front() {
+            Some(line) => Ok(Some(line)),
+            None if !self.connected() => {
+                Err(ClientError::Serial("The other end was dropped".to_owned()))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), ClientError> {
+        if !self.connected() {
+            return Err(ClientError::Serial("The other end was dropped".to_owned()));
+        }
+        self.outgoing.lock().unwrap().push_back(line.to_owned());
+        Ok(())
+    }
+
+    fn connected(&self) -> bool {
+        self.other_end.strong_count() > 0
+    }
+
+    fn try_clone(&self) -> Result<Box<dyn Transport>, ClientError> {
+        Ok(Box::new(Self {
+            incoming: Arc::clone(&self.incoming),
+            outgoing: Arc::clone(&self.outgoing),
+            alive: Arc::clone(&self.alive),
+            other_end: Weak::clone(&self.other_end),
+        }))
+    }
+}
@@ -0,0 +1,213 @@
+/*!
+ * Tests for asking again for frames that go missing
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::*;
+
+/***** Helper functions *****/
+
+/// A status from the car, before it's numbered
+fn unnumbered_status() -> String {
+    Frame::encode_with_checksum(&Event {
+        command: Command::Status,
+        transit_mode: TransitMode::ServerToClientResponse,
+        transit_type: TransitType::Response,
+        value: StatusArguments {},
+        metadata: MetaData {
+            time: 1000.0,
+            sequence: None,
+            frame: None,
+            echo: None,
+            client: None,
+        },
+    })
+    .unwrap()
+}
+
+fn status(number: u32) -> String {
+    number_frame(&unnumbered_status(), number).unwrap()
+}
+
+/// The frame a `NACK` asks for, if the line is one
+fn nacked(line: &str) -> Option<u32> {
+    let raw_frame = RawFrame::split(line).unwrap();
+    (raw_frame.command == Command::Nack).then(|| raw_frame.value::<NackArguments>().unwrap().frame)
+}
+
+/// The client's end, wrapped, and the car's end, as is
+fn client() -> (ArqTransport, LoopbackTransport) {
+    let (client, car) = LoopbackTransport::pair();
+    (ArqTransport::new(Box::new(client), LinkEnd::Client), car)
+}
+
+/***** Tests *****/
+
+#[test]
+fn numbered_frames_keep_a_valid_checksum() {
+    let frame = status(7);
+    let raw_frame = RawFrame::split(&frame).unwrap();
+    assert_eq!(raw_frame.metadata.frame, Some(7));
+    assert_eq!(
+        RawFrame::split(&number_frame(&frame, 8).unwrap())
+            .unwrap()
+            .metadata
+            .frame,
+        Some(8)
+    );
+
+    // Without a checksum, none is added
+    let numbered = number_frame("?PING${\"time\": 1.0}${\"time\": 1.0}", 3).unwrap();
+    assert_eq!(numbered, "?PING${\"time\": 1.0}${\"time\":1.0,\"frame\":3}");
+}
+
+#[test]
+fn skipped_frames_are_asked_for_once() {
+    let mut received = ReceivedFrames::new();
+    assert!(received.receive(4).is_empty());
+    assert!(received.receive(5).is_empty());
+    assert_eq!(received.receive(8), vec![6, 7]);
+
+    // Coming in late isn't a gap
+    assert!(received.receive(7).is_empty());
+    assert!(received.receive(6).is_empty());
+    assert!(received.receive(9).is_empty());
+
+    // Only the frames the other end still keeps are asked for
+    assert_eq!(received.receive(30), (23..30).collect::<Vec<_>>());
+    // And counting wraps around
+    let mut received = ReceivedFrames::new();
+    received.receive(u32::MAX);
+    assert_eq!(received.receive(1), vec![0]);
+}
+
+#[test]
+fn starting_over_is_not_a_gap() {
+    let mut received = ReceivedFrames::new();
+    for number in 0..50 {
+        assert!(received.receive(number).is_empty());
+    }
+    // Sent again after a timeout
+    assert!(received.receive(47).is_empty());
+    // The other end restarted
+    assert!(received.receive(0).is_empty());
+    assert!(received.receive(1).is_empty());
+    assert_eq!(received.receive(3), vec![2]);
+}
+
+#[test]
+fn corrupted_frames_are_asked_for_if_their_number_makes_sense() {
+    let mut received = ReceivedFrames::new();
+    received.receive(10);
+    assert_eq!(received.corrupted(12), vec![11, 12]);
+    // Corrupted again, so it's left to whatever reads it
+    assert!(received.corrupted(12).is_empty());
+    assert!(received.receive(11).is_empty());
+
+    // Nowhere near where it should be
+    assert!(received.corrupted(4000).is_empty());
+    assert!(received.corrupted(2).is_empty());
+    assert!(received.receive(13).is_empty());
+}
+
+#[test]
+fn missing_frames_are_asked_for_and_sent_again() {
+    let (mut client, mut car) = client();
+    car.write_line(&status(0)).unwrap();
+    car.write_line(&status(2)).unwrap();
+    assert_eq!(client.read_line().unwrap(), Some(status(0)));
+    assert_eq!(client.read_line().unwrap(), Some(status(2)));
+    assert_eq!(client.read_line().unwrap(), None);
+
+    let nack = car.read_line().unwrap().unwrap();
+    assert!(nack.starts_with("?NACK$"));
+    assert_eq!(nacked(&nack), Some(1));
+    assert_eq!(RawFrame::split(&nack).unwrap().metadata.frame, None);
+    car.write_line(&status(1)).unwrap();
+    assert_eq!(client.read_line().unwrap(), Some(status(1)));
+    assert_eq!(car.read_line().unwrap(), None);
+}
+
+#[test]
+fn corrupted_frames_are_asked_for_instead_of_passed_on() {
+    let (mut client, mut car) = client();
+    car.write_line(&status(0)).unwrap();
+    let corrupted = status(1).replacen("{}", "{ }", 1);
+    car.write_line(&corrupted).unwrap();
+    assert_eq!(client.read_line().unwrap(), Some(status(0)));
+    assert_eq!(client.read_line().unwrap(), None);
+    assert_eq!(nacked(&car.read_line().unwrap().unwrap()), Some(1));
+
+    // Frames from an other end that doesn't number them pass through, even
+    // corrupted
+    let (mut client, mut car) = self::client();
+    car.write_line(&corrupted.replace(",\"frame\":1", ""))
+        .unwrap();
+    assert!(client.read_line().unwrap().is_some());
+    assert_eq!(car.read_line().unwrap(), None);
+}
+
+#[test]
+fn frames_asked_for_are_sent_again_as_they_were() {
+    let (mut client, mut car) = client();
+    client
+        .write_line("?PING${\"time\": 1.0}${\"time\": 1.0}")
+        .unwrap();
+    client
+        .write_line("^BLUETOOTHSTATUS${}${\"time\": 1.0}")
+        .unwrap();
+    client.write_line("?STATUS${}${\"time\": 2.0}").unwrap();
+    let ping = car.read_line().unwrap().unwrap();
+    assert_eq!(RawFrame::split(&ping).unwrap().metadata.frame, Some(0));
+    // Only frames to the car are numbered
+    assert_eq!(
+        car.read_line().unwrap().as_deref(),
+        Some("^BLUETOOTHSTATUS${}${\"time\": 1.0}")
+    );
+    let status = car.read_line().unwrap().unwrap();
+    assert_eq!(RawFrame::split(&status).unwrap().metadata.frame, Some(1));
+
+    // The `NACK` never makes it out
+    car.write_line("~NACK${\"frame\": 0}${\"time\": 3.0}")
+        .unwrap();
+    assert_eq!(client.read_line().unwrap(), None);
+    assert_eq!(car.read_line().unwrap(), Some(ping));
+
+    // Frames no longer kept can't be sent again
+    for _ in 0..ARQ_WINDOW {
+        client.write_line("?STATUS${}${\"time\": 2.0}").unwrap();
+    }
+    while car.read_line().unwrap().is_some() {}
+    car.write_line("~NACK${\"frame\": 1}${\"time\": 3.0}")
+        .unwrap();
+    assert_eq!(client.read_line().unwrap(), None);
+    assert_eq!(car.read_line().unwrap(), None);
+}
+
+#[test]
+fn both_ends_recover_a_lost_frame() {
+    let (client, mut client_link) = LoopbackTransport::pair();
+    let mut client = ArqTransport::new(Box::new(client), LinkEnd::Client);
+    let (car, mut car_link) = LoopbackTransport::pair();
+    let mut car = ArqTransport::new(Box::new(car), LinkEnd::Car);
+
+    // The link in between loses the second status
+    for _ in 0..3 {
+        car.write_line(&unnumbered_status()).unwrap();
+    }
+    let statuses = [(); 3].map(|()| car_link.read_line().unwrap().unwrap());
+    client_link.write_line(&statuses[0]).unwrap();
+    client_link.write_line(&statuses[2]).unwrap();
+    assert_eq!(client.read_line().unwrap(), Some(status(0)));
+    assert_eq!(client.read_line().unwrap(), Some(status(2)));
+
+    let nack = client_link.read_line().unwrap().unwrap();
+    car_link.write_line(&nack).unwrap();
+    assert_eq!(car.read_line().unwrap(), None);
+    let resent = car_link.read_line().unwrap().unwrap();
+    assert_eq!(resent, statuses[1]);
+    client_link.write_line(&resent).unwrap();
+    assert_eq!(client.read_line().unwrap(), Some(status(1)));
+}
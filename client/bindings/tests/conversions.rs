@@ -0,0 +1,77 @@
+/*!
+ * Tests for converting the enums sent over the wire to and from their
+ * discriminants
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::*;
+use std::fmt::Debug;
+
+/***** Helper functions *****/
+
+/// Check every `u8` converts to a variant exactly when it's one of
+/// `discriminants`, and back to itself
+fn check_every_discriminant<E>(discriminants: &[u8])
+where
+    E: TryFrom<u8, Error = ()> + Into<u8> + Copy + Debug,
+{
+    for value in 0..=u8::MAX {
+        match E::try_from(value) {
+            Ok(variant) => {
+                assert!(discriminants.contains(&value), "{value} is {variant:?}");
+                assert_eq!(variant.into(), value, "{variant:?}");
+            }
+            Err(()) => assert!(!discriminants.contains(&value), "{value}"),
+        }
+    }
+}
+
+/***** Tests *****/
+
+#[test]
+fn server_errors_convert_but_not_their_bounds() {
+    let mut discriminants = (0..=6).chain(10..=11).chain(21..=28).collect::<Vec<_>>();
+    discriminants.push(99);
+    check_every_discriminant::<ServerError>(&discriminants);
+
+    // The bounds still have discriminants of their own
+    assert_eq!(u8::from(ServerError::_SpecificErrorUpperBound), 29);
+}
+
+#[test]
+fn transit_modes_convert_from_their_prefixes() {
+    check_every_discriminant::<TransitMode>(b"?~^&");
+    assert_eq!(
+        TransitMode::try_from(b'~'),
+        Ok(TransitMode::ServerToClientResponse)
+    );
+}
+
+#[test]
+fn status_stages_convert() {
+    check_every_discriminant::<StatusStage>(&[0, 1, 2, 3, 4]);
+    assert!(matches!(
+        StatusStage::try_from(4),
+        Ok(StatusStage::Finalized)
+    ));
+}
+
+#[test]
+fn client_statuses_convert_and_go_to_the_next() {
+    check_every_discriminant::<ClientStatus>(&[0, 1, 2, 3, 4, 5, 6, 7, 8]);
+    assert_eq!(ClientStatus::RequestingStop.next(), ClientStatus::Finished);
+    assert_eq!(ClientStatus::Finished.next(), ClientStatus::Finished);
+}
+
+#[test]
+fn distance_estimators_convert() {
+    check_every_discriminant::<DistanceEstimatorKind>(&[0, 1, 2]);
+    for distance_estimator in DistanceEstimatorKind::ALL {
+        assert_eq!(
+            DistanceEstimatorKind::try_from(u8::from(distance_estimator)),
+            Ok(distance_estimator)
+        );
+    }
+}
@@ -0,0 +1,806 @@
+/*!
+ * Round-trip tests for the frame codec
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::*;
+use proptest::{num::f64 as float, prelude::*};
+use serde::{Deserialize, Serialize};
+
+/***** Strategies *****/
+
+/// Any float JSON can hold (no NaN or infinity)
+fn finite() -> impl Strategy<Value = f64> {
+    float::NORMAL | float::SUBNORMAL | float::ZERO
+}
+
+fn echo() -> impl Strategy<Value = Echo> {
+    (finite(), finite()).prop_map(|(time, received)| Echo { time, received })
+}
+
+fn metadata() -> impl Strategy<Value = MetaData> {
+    (
+        finite(),
+        any::<Option<u32>>(),
+        any::<Option<u32>>(),
+        prop::option::of(echo()),
+        any::<Option<u32>>(),
+    )
+        .prop_map(|(time, sequence, frame, echo, client)| MetaData {
+            time,
+            sequence,
+            frame,
+            echo,
+            client,
+        })
+}
+
+fn status_stage() -> impl Strategy<Value = StatusStage> {
+    prop_oneof![
+        Just(StatusStage::Stopped),
+        Just(StatusStage::VehementForward),
+        Just(StatusStage::StallOvershoot),
+        Just(StatusStage::CautiousBackward),
+        Just(StatusStage::Finalized),
+    ]
+}
+
+/// Any estimator, or none
+fn distance_estimator() -> impl Strategy<Value = Option<DistanceEstimatorKind>> {
+    prop::option::of(prop::sample::select(DistanceEstimatorKind::ALL.to_vec()))
+}
+
+/// Any gains, or none
+fn approach_gains() -> impl Strategy<Value = Option<ApproachGains>> {
+    prop::option::of((finite(), finite(), finite()).prop_map(
+        |(proportional, integral, derivative)| ApproachGains {
+            proportional,
+            integral,
+            derivative,
+        },
+    ))
+}
+
+/// How an uploaded plan went, or no plan at all
+fn executed_plan() -> impl Strategy<Value = Option<Vec<ExecutedBreakpoint>>> {
+    prop::option::of(prop::collection::vec(
+        (
+            finite(),
+            prop::option::of(finite()),
+            prop::option::of(finite()),
+        )
+            .prop_map(|(distance, max_velocity, reached)| ExecutedBreakpoint {
+                distance,
+                max_velocity,
+                reached,
+            }),
+        0..4,
+    ))
+}
+
+fn event<S>(command: Command, transit_mode: TransitMode, value: S, metadata: MetaData) -> Event<S>
+where
+    S: Serialize + for<'a> Deserialize<'a>,
+{
+    Event {
+        command,
+        transit_mode,
+        transit_type: TransitType::Response,
+        value,
+        metadata,
+    }
+}
+
+fn bluetooth_device() -> impl Strategy<Value = BluetoothDevice> {
+    (
+        any::<[u8; 6]>(),
+        prop::option::of(any::<String>()),
+        prop::option::of(any::<i16>()),
+    )
+        .prop_map(|(address, name, rssi)| BluetoothDevice {
+            address: address
+                .iter()
+                .map(|byte| format!("{byte:02X}"))
+                .collect::<Vec<_>>()
+                .join(":"),
+            name,
+            rssi,
+        })
+}
+
+/// Every kind of response the client can receive
+fn response() -> impl Strategy<Value = Response> {
+    use TransitMode::*;
+    prop_oneof![
+        (finite(), metadata()).prop_map(|(sent_time, metadata)| Response::Ping(event(
+            Command::Ping,
+            ServerToClientResponse,
+            PingResponse { sent_time },
+            metadata
+        ))),
+        metadata().prop_map(|metadata| Response::Start(event(
+            Command::Start,
+            ServerToClientResponse,
+            StartResponse,
+            metadata
+        ))),
+        metadata().prop_map(|metadata| Response::Stop(event(
+            Command::Stop,
+            ServerToClientResponse,
+            StopResponse,
+            metadata
+        ))),
+        (
+            any::<bool>(),
+            any::<usize>(),
+            any::<usize>(),
+            status_stage(),
+            finite(),
+            finite(),
+            any::<usize>(),
+            any::<usize>(),
+            executed_plan(),
+            prop::option::of((any::<bool>(), any::<usize>())),
+            prop::option::of((finite(), finite(), prop::option::of(finite()))),
+            metadata()
+        )
+            .prop_map(
+                |(
+                    running,
+                    uptime,
+                    runtime,
+                    stage,
+                    distance,
+                    velocity,
+                    hits,
+                    violations,
+                    executed_plan,
+                    radio_stop,
+                    power,
+                    metadata,
+                )| {
+                    Response::Status(event(
+                        Command::Status,
+                        ServerToClientResponse,
+                        StatusResponse {
+                            running,
+                            uptime,
+                            runtime,
+                            stage,
+                            distance: DistanceInformation {
+                                distance,
+                                velocity,
+                                magnet_hit_counter: hits,
+                            },
+                            speed_limit_violations: violations,
+                            executed_plan,
+                            radio_stop: radio_stop
+                                .map(|(held, stops)| RadioStopStatus { held, stops }),
+                            power: power.map(|(battery_voltage, motor_current, charge)| {
+                                PowerStatus {
+                                    battery_voltage,
+                                    motor_current,
+                                    charge,
+                                }
+                            }),
+                        },
+                        metadata,
+                    ))
+                }
+            ),
+        (
+            any::<usize>(),
+            finite(),
+            distance_estimator(),
+            approach_gains(),
+            prop::option::of(finite()),
+            metadata()
+        )
+            .prop_map(
+                |(
+                    number_of_magnets,
+                    wheel_diameter,
+                    distance_estimator,
+                    approach_gains,
+                    wheel_circumference,
+                    metadata,
+                )| {
+                    Response::StaticStatus(event(
+                        Command::StaticStatus,
+                        ServerToClientResponse,
+                        StaticStatusResponse {
+                            number_of_magnets,
+                            wheel_diameter,
+                            distance_estimator,
+                            approach_gains,
+                            wheel_circumference,
+                        },
+                        metadata,
+                    ))
+                }
+            ),
+        // Messages can have anything in them, separators and newlines included
+        (any::<u8>(), any::<String>(), metadata()).prop_map(
+            |(error_variant, message, metadata)| Response::Error(event(
+                Command::Error,
+                ServerToClientResponse,
+                ErrorResponse {
+                    error_variant,
+                    message,
+                },
+                metadata
+            ))
+        ),
+        (any::<u32>(), metadata()).prop_map(|(protocol_version, metadata)| Response::Hello(event(
+            Command::Hello,
+            ServerToClientResponse,
+            HelloResponse { protocol_version },
+            metadata
+        ))),
+        metadata().prop_map(|metadata| Response::UploadPlan(event(
+            Command::UploadPlan,
+            ServerToClientResponse,
+            UploadPlanResponse,
+            metadata
+        ))),
+        (approach_gains(), metadata()).prop_map(|(approach_gains, metadata)| Response::Tune(
+            event(
+                Command::Tune,
+                ServerToClientResponse,
+                TuneResponse { approach_gains },
+                metadata
+            )
+        )),
+        (finite(), finite(), metadata()).prop_map(
+            |(previous_wheel_circumference, wheel_circumference, metadata)| Response::Calibrate(
+                event(
+                    Command::Calibrate,
+                    ServerToClientResponse,
+                    CalibrateResponse {
+                        previous_wheel_circumference,
+                        wheel_circumference,
+                    },
+                    metadata
+                )
+            )
+        ),
+        (any::<bool>(), metadata()).prop_map(|(connected, metadata)| Response::BluetoothStatus(
+            event(
+                Command::BluetoothStatus,
+                SerialBridgeToClientResponse,
+                BluetoothStatusResponse { connected },
+                metadata
+            )
+        )),
+        (prop::collection::vec(bluetooth_device(), 0..4), metadata()).prop_map(
+            |(devices, metadata)| Response::BluetoothScan(event(
+                Command::BluetoothScan,
+                SerialBridgeToClientResponse,
+                BluetoothScanResponse { devices },
+                metadata
+            ))
+        ),
+        (
+            finite(),
+            prop::collection::vec(bluetooth_device(), 0..4),
+            any::<Option<bool>>(),
+            prop::option::of(bluetooth_device().prop_map(|device| device.address)),
+            metadata()
+        )
+            .prop_map(|(elapsed, devices, connected, address, metadata)| {
+                Response::BluetoothConnect(event(
+                    Command::Connect,
+                    SerialBridgeToClientResponse,
+                    BluetoothConnectResponse {
+                        elapsed,
+                        devices,
+                        connected,
+                        address,
+                    },
+                    metadata,
+                ))
+            }),
+        (
+            prop::option::of(bluetooth_device().prop_map(|device| device.address)),
+            any::<Option<i16>>(),
+            any::<(u64, u64, u32, u64)>(),
+            metadata()
+        )
+            .prop_map(|(address, rssi, counters, metadata)| {
+                let (bytes_sent, bytes_received, reconnects, duplicates_discarded) = counters;
+                Response::BluetoothMetrics(event(
+                    Command::BluetoothMetrics,
+                    SerialBridgeToClientResponse,
+                    BluetoothMetricsResponse {
+                        address,
+                        rssi,
+                        bytes_sent,
+                        bytes_received,
+                        reconnects,
+                        duplicates_discarded,
+                    },
+                    metadata,
+                ))
+            }),
+        (
+            finite(),
+            bluetooth_device().prop_map(|device| device.address),
+            metadata()
+        )
+            .prop_map(|(silent_for, address, metadata)| Response::Watchdog(event(
+                Command::Watchdog,
+                SerialBridgeToClientResponse,
+                WatchdogResponse {
+                    silent_for,
+                    address
+                },
+                metadata
+            ))),
+    ]
+}
+
+/***** Helper functions *****/
+
+fn encode_response(response: &Response) -> String {
+    match response {
+        Response::Ping(event) => Frame::encode(event),
+        Response::Start(event) => Frame::encode(event),
+        Response::Stop(event) => Frame::encode(event),
+        Response::Status(event) => Frame::encode(event),
+        Response::StaticStatus(event) => Frame::encode(event),
+        Response::Error(event) => Frame::encode(event),
+        Response::Hello(event) => Frame::encode(event),
+        Response::UploadPlan(event) => Frame::encode(event),
+        Response::Tune(event) => Frame::encode(event),
+        Response::Calibrate(event) => Frame::encode(event),
+        Response::BluetoothStatus(event) => Frame::encode(event),
+        Response::BluetoothScan(event) => Frame::encode(event),
+        Response::BluetoothConnect(event) => Frame::encode(event),
+        Response::BluetoothMetrics(event) => Frame::encode(event),
+        Response::Watchdog(event) => Frame::encode(event),
+    }
+    .unwrap()
+}
+
+fn same_variant(a: &Response, b: &Response) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
+/***** Tests *****/
+
+proptest! {
+    #[test]
+    fn responses_round_trip(response in response()) {
+        let frame = encode_response(&response);
+        prop_assert!(!frame.contains('\n'));
+
+        let decoded = Frame::decode(&frame).unwrap();
+        prop_assert!(same_variant(&response, &decoded));
+        prop_assert_eq!(encode_response(&decoded), frame);
+    }
+
+    #[test]
+    fn requests_split(
+        time in finite(),
+        distance in finite(),
+        reverse_brake in any::<bool>(),
+        max_velocity in prop::option::of(finite()),
+        distance_estimator in distance_estimator(),
+        coast_deceleration in prop::option::of(finite()),
+    ) {
+        let metadata = MetaData {
+            time,
+            sequence: None,
+            frame: None,
+            echo: None,
+            client: None,
+        };
+        let request = |command: Command, value| Frame::encode(&Event {
+            command,
+            transit_mode: TransitMode::from(command),
+            transit_type: TransitType::Request,
+            value,
+            metadata,
+        }).unwrap();
+
+        let frame = Frame::encode(&Event {
+            command: Command::Start,
+            transit_mode: TransitMode::from(Command::Start),
+            transit_type: TransitType::Request,
+            value: StartArguments::builder()
+                .distance_cm(distance)
+                .reverse_brake(reverse_brake)
+                .max_velocity_cm_s(max_velocity)
+                .distance_estimator(distance_estimator)
+                .coast_deceleration_cm_s2(coast_deceleration)
+                .build(),
+            metadata,
+        }).unwrap();
+        let raw_frame = RawFrame::split(&frame).unwrap();
+        prop_assert!(raw_frame.command == Command::Start);
+        prop_assert_eq!(raw_frame.transit_mode, TransitMode::ClientToServerRequest);
+        prop_assert_eq!(raw_frame.metadata.time.to_bits(), time.to_bits());
+        let arguments = raw_frame.value::<StartArguments>().unwrap();
+        prop_assert_eq!(arguments.distance.to_bits(), distance.to_bits());
+        prop_assert_eq!(arguments.reverse_brake, reverse_brake);
+        prop_assert_eq!(arguments.max_velocity.map(f64::to_bits), max_velocity.map(f64::to_bits));
+        prop_assert_eq!(frame.contains("max_velocity"), max_velocity.is_some());
+        prop_assert_eq!(arguments.distance_estimator, distance_estimator);
+        prop_assert_eq!(frame.contains("distance_estimator"), distance_estimator.is_some());
+        prop_assert_eq!(
+            arguments.coast_deceleration.map(f64::to_bits),
+            coast_deceleration.map(f64::to_bits)
+        );
+        prop_assert_eq!(frame.contains("coast_deceleration"), coast_deceleration.is_some());
+
+        let frame = Frame::encode(&Event {
+            command: Command::UploadPlan,
+            transit_mode: TransitMode::from(Command::UploadPlan),
+            transit_type: TransitType::Request,
+            value: UploadPlanArguments::builder()
+                .start(StartArguments::builder().distance_cm(distance).max_velocity_cm_s(max_velocity).build())
+                .breakpoint_cm(distance / 2.0, None)
+                .build(),
+            metadata,
+        }).unwrap();
+        let raw_frame = RawFrame::split(&frame).unwrap();
+        prop_assert!(raw_frame.command == Command::UploadPlan);
+        prop_assert_eq!(raw_frame.transit_mode, TransitMode::ClientToServerRequest);
+        let arguments = raw_frame.value::<UploadPlanArguments>().unwrap();
+        prop_assert_eq!(arguments.start.distance.to_bits(), distance.to_bits());
+        prop_assert_eq!(arguments.start.max_velocity.map(f64::to_bits), max_velocity.map(f64::to_bits));
+        prop_assert_eq!(arguments.breakpoints, vec![PlanBreakpoint { distance: distance / 2.0, max_velocity: None }]);
+
+        let frame = Frame::encode(&Event {
+            command: Command::Ping,
+            transit_mode: TransitMode::from(Command::Ping),
+            transit_type: TransitType::Request,
+            value: PingArguments::builder().time(time).build(),
+            metadata,
+        }).unwrap();
+        let arguments = RawFrame::split(&frame).unwrap().value::<PingArguments>().unwrap();
+        prop_assert_eq!(arguments.time.to_bits(), time.to_bits());
+
+        // Everything without arguments
+        for (command, frame) in [
+            (Command::Stop, request(Command::Stop, ())),
+            (Command::Status, request(Command::Status, ())),
+            (Command::StaticStatus, request(Command::StaticStatus, ())),
+            (Command::Connect, request(Command::Connect, ())),
+            (Command::Disconnect, request(Command::Disconnect, ())),
+            (Command::BluetoothStatus, request(Command::BluetoothStatus, ())),
+            (Command::BluetoothScan, request(Command::BluetoothScan, ())),
+            (Command::BluetoothMetrics, request(Command::BluetoothMetrics, ())),
+        ] {
+            prop_assert!(frame.contains("${}$"), "{}", frame);
+            let raw_frame = RawFrame::split(&frame).unwrap();
+            prop_assert!(raw_frame.command == command);
+            prop_assert_eq!(raw_frame.transit_mode, TransitMode::from(command));
+            prop_assert!(Frame::decode(&frame).is_err());
+        }
+
+        // Connecting to a chosen device, or whichever the bridge defaults to
+        let frame = request(Command::Connect, ());
+        let arguments = RawFrame::split(&frame).unwrap().value::<Option<BluetoothConnectRequest>>();
+        prop_assert!(arguments.unwrap().is_none());
+        let frame = Frame::encode(&Event {
+            command: Command::Connect,
+            transit_mode: TransitMode::from(Command::Connect),
+            transit_type: TransitType::Request,
+            value: BluetoothConnectRequest {
+                address: Some("00:60:37:E9:0B:6F".to_owned()),
+            },
+            metadata,
+        }).unwrap();
+        let arguments = RawFrame::split(&frame).unwrap().value::<Option<BluetoothConnectRequest>>();
+        let address = arguments.unwrap().and_then(|arguments| arguments.address);
+        prop_assert_eq!(address.as_deref(), Some("00:60:37:E9:0B:6F"));
+    }
+
+    #[test]
+    fn streams_however_chunked(
+        responses in prop::collection::vec(response(), 1..8),
+        line_ending in prop_oneof![Just("\n"), Just("\r"), Just("\r\n")],
+        chunk_sizes in prop::collection::vec(1..64_usize, 1..32),
+    ) {
+        let frames = responses.iter().map(encode_response).collect::<Vec<_>>();
+        let stream = frames
+            .iter()
+            .map(|frame| format!("{frame}{line_ending}"))
+            .collect::<String>()
+            .into_bytes();
+
+        let mut decoder = StreamingDecoder::new();
+        let mut decoded = vec![];
+        let mut rest = stream.as_slice();
+        for chunk_size in chunk_sizes.iter().cycle() {
+            if rest.is_empty() {
+                break;
+            }
+            let (chunk, remaining) = rest.split_at((*chunk_size).min(rest.len()));
+            decoder.push_bytes(chunk);
+            decoded.extend(decoder.by_ref().map(|response| response.unwrap()));
+            rest = remaining;
+        }
+
+        prop_assert!(decoder.pending().is_empty());
+        prop_assert_eq!(decoded.len(), frames.len());
+        for (decoded, frame) in decoded.iter().zip(frames.iter()) {
+            prop_assert_eq!(&encode_response(decoded), frame);
+        }
+    }
+
+    #[test]
+    fn checksummed_responses_round_trip(response in response()) {
+        let frame = match &response {
+            Response::Ping(event) => event.encode_with_checksum(),
+            Response::Start(event) => event.encode_with_checksum(),
+            Response::Stop(event) => event.encode_with_checksum(),
+            Response::Status(event) => event.encode_with_checksum(),
+            Response::StaticStatus(event) => event.encode_with_checksum(),
+            Response::Error(event) => event.encode_with_checksum(),
+            Response::Hello(event) => event.encode_with_checksum(),
+            Response::UploadPlan(event) => event.encode_with_checksum(),
+            Response::Tune(event) => event.encode_with_checksum(),
+            Response::Calibrate(event) => event.encode_with_checksum(),
+            Response::BluetoothStatus(event) => event.encode_with_checksum(),
+            Response::BluetoothScan(event) => event.encode_with_checksum(),
+            Response::BluetoothConnect(event) => event.encode_with_checksum(),
+            Response::BluetoothMetrics(event) => event.encode_with_checksum(),
+            Response::Watchdog(event) => event.encode_with_checksum(),
+        }
+        .unwrap();
+
+        let decoded = Frame::decode(&frame).unwrap();
+        prop_assert!(same_variant(&response, &decoded));
+        prop_assert_eq!(encode_response(&decoded), strip_checksum(&frame).unwrap());
+    }
+
+    #[test]
+    fn corruption_is_caught(
+        response in response(),
+        idx in any::<prop::sample::Index>(),
+        replacement in 0x20..0x7f_u8,
+    ) {
+        let frame = match &response {
+            Response::Ping(event) => event.encode_with_checksum(),
+            Response::Status(event) => event.encode_with_checksum(),
+            Response::StaticStatus(event) => event.encode_with_checksum(),
+            _ => return Ok(()),
+        }
+        .unwrap();
+
+        // Corrupt one byte before the checksum
+        let mut bytes = frame.into_bytes();
+        let idx = idx.index(bytes.len() - 5);
+        prop_assume!(bytes[idx] != replacement);
+        bytes[idx] = replacement;
+
+        let is_checksum_mismatch = matches!(
+            Response::try_from_bytes(&bytes),
+            Err(ClientError::ChecksumMismatch { .. })
+        );
+        prop_assert!(is_checksum_mismatch);
+    }
+}
+
+#[test]
+fn checksums() {
+    // The standard check value for CRC-16/CCITT-FALSE
+    assert_eq!(crc16(b"123456789"), 0x29B1);
+
+    let frame = "~PING${\"sent_time\": 1.0}${\"time\": 2.0}";
+    let checksummed = format!("{frame}*{:04X}", crc16(frame.as_bytes()));
+    assert!(matches!(Frame::decode(&checksummed), Ok(Response::Ping(_))));
+
+    let corrupted = checksummed.replace("1.0", "7.0");
+    match Frame::decode(&corrupted) {
+        Err(ClientError::ChecksumMismatch { command, .. }) => {
+            assert!(command == Some(Command::Ping))
+        }
+        _ => panic!("Corruption went unnoticed"),
+    }
+}
+
+#[test]
+fn streaming_keeps_unfinished_frames() {
+    let mut decoder = StreamingDecoder::new();
+    decoder.push_bytes(b"~PING${\"sent_time\": 1.0}${\"ti");
+    assert!(decoder.next().is_none());
+    assert!(!decoder.pending().is_empty());
+
+    decoder.push_bytes(b"me\": 2.0}\n\n~BOGUS\r");
+    assert!(matches!(decoder.next(), Some(Ok(Response::Ping(_)))));
+    assert!(matches!(decoder.next(), Some(Err(_))));
+    assert!(decoder.next().is_none());
+
+    assert!(Response::try_from_bytes(b"~STOP${}${\"time\": 1.0}").is_ok());
+}
+
+#[test]
+fn decodes_frames_from_the_server() {
+    let frame = "~STATUS${\"running\": true, \"uptime\": 12, \"runtime\": 3, \"stage\": 1, \
+                 \"distance\": {\"distance\": 42.5, \"velocity\": 10.0, \"magnet_hit_counter\": 7}}\
+                 ${\"time\": 1700000000.25}\r";
+    match Frame::decode(frame).unwrap() {
+        Response::Status(event) => {
+            assert!(event.value.running);
+            assert_eq!(event.value.distance.distance, 42.5);
+            // Older servers don't send it
+            assert_eq!(event.value.speed_limit_violations, 0);
+            assert_eq!(event.value.radio_stop, None);
+            assert_eq!(event.value.power, None);
+            assert_eq!(event.metadata.time, 1700000000.25);
+        }
+        _ => panic!("Decoded into the wrong response"),
+    }
+
+    match Frame::decode("~ERROR${\"error_variant\": 1, \"message\": \"$5 $\"}${\"time\": 1.0}")
+        .unwrap()
+    {
+        Response::Error(event) => assert_eq!(event.value.message, "$5 $"),
+        _ => panic!("Decoded into the wrong response"),
+    }
+
+    match Frame::decode("~STOP${}${\"time\": 1.0, \"sequence\": 7}").unwrap() {
+        Response::Stop(event) => assert_eq!(event.metadata.sequence, Some(7)),
+        _ => panic!("Decoded into the wrong response"),
+    }
+    match Frame::decode("~STOP${}${\"time\": 1.0}").unwrap() {
+        Response::Stop(event) => assert_eq!(event.metadata.sequence, None),
+        _ => panic!("Decoded into the wrong response"),
+    }
+    assert!(Frame::decode("").is_err());
+    assert!(Frame::decode("~NOTACOMMAND${}${\"time\": 1.0}").is_err());
+    assert!(Frame::decode("~PING${\"sent_time\": 1.0}").is_err());
+}
+
+#[test]
+fn server_errors_are_read_by_their_variant() {
+    // As numbered in the server's bindings
+    for (variant, error) in [
+        (0, ServerError::MalformedRequestFailedPrefixParsing),
+        (6, ServerError::MalformedRequestOtherError),
+        (10, ServerError::MalformedResponseTypeError),
+        (21, ServerError::FailedToStartAlreadyStarted),
+        (24, ServerError::FailedToStartCouldNotAcquireDistanceLock),
+        (25, ServerError::FailedToStopNotStarted),
+        (27, ServerError::FailedStatusCouldNotAcquireDistanceLock),
+        (99, ServerError::AnyOtherError),
+    ] {
+        assert_eq!(ServerError::try_from(variant), Ok(error), "{variant}");
+    }
+    for variant in [7, 8, 9, 12, 13, 20, 29, 100] {
+        assert!(ServerError::try_from(variant).is_err(), "{variant}");
+    }
+
+    let error = ClientError::from(&ErrorResponse {
+        error_variant: 25,
+        message: "Not started".to_owned(),
+    });
+    assert!(matches!(
+        error,
+        ClientError::Server(ServerError::FailedToStopNotStarted, _)
+    ));
+    assert_eq!(
+        error.to_string(),
+        "Failed to stop - Not started: Not started"
+    );
+}
+
+#[test]
+fn bridge_responses_are_encoded() {
+    let frame = encode_bridge_response(
+        Command::BluetoothStatus,
+        BluetoothStatusResponse { connected: true },
+        Some(3),
+    )
+    .unwrap();
+    assert!(frame.starts_with("&BLUETOOTHSTATUS$"), "{frame}");
+    match Frame::decode(&frame).unwrap() {
+        Response::BluetoothStatus(event) => {
+            assert!(event.value.connected);
+            assert_eq!(
+                event.transit_mode,
+                TransitMode::SerialBridgeToClientResponse
+            );
+            assert_eq!(event.metadata.sequence, Some(3));
+            assert!(event.metadata.time > 0.0);
+        }
+        _ => panic!("Decoded into the wrong response"),
+    }
+}
+
+#[test]
+fn metadata_is_edited_in_place() {
+    let frame = "~PING${\"sent_time\": 12.5}${\"time\": 100.0, \"sequence\": 3, \"client\": 2}";
+    let checksummed = format!("{frame}*{:04X}", crc16(frame.as_bytes()));
+
+    // Taking the client back off, as the bridge does before passing it on
+    let edited = edit_metadata(&checksummed, |metadata| metadata.client = None).unwrap();
+    assert!(!edited.contains("client"), "{edited}");
+    assert!(
+        edited.starts_with("~PING${\"sent_time\": 12.5}$"),
+        "{edited}"
+    );
+    let raw_frame = RawFrame::split(&edited).unwrap();
+    assert_eq!(raw_frame.metadata.sequence, Some(3));
+    assert_eq!(raw_frame.metadata.client, None);
+
+    // Without a checksum, none is added
+    let edited = edit_metadata(frame, |metadata| metadata.client = Some(5)).unwrap();
+    assert!(edited.ends_with("\"client\":5}"), "{edited}");
+
+    // A corrupted frame isn't edited into a valid one
+    let corrupted = checksummed.replace("12.5", "12.6");
+    assert!(edit_metadata(&corrupted, |metadata| metadata.client = None).is_err());
+}
+
+#[test]
+fn uploaded_plans_are_checked() {
+    let plan = |distance, breakpoints: &[(f64, Option<f64>)]| {
+        breakpoints
+            .iter()
+            .fold(
+                UploadPlanArguments::builder()
+                    .start(StartArguments::builder().distance_cm(distance).build()),
+                |builder, (distance, max_velocity)| builder.breakpoint_cm(*distance, *max_velocity),
+            )
+            .build()
+    };
+
+    assert!(plan(500.0, &[]).check().is_ok());
+    assert!(plan(
+        500.0,
+        &[(0.0, Some(50.0)), (300.0, Some(20.0)), (450.0, None)]
+    )
+    .check()
+    .is_ok());
+    for (breakpoints, problem) in [
+        (vec![(500.0, None)], "between"),
+        (vec![(-1.0, None)], "between"),
+        (vec![(300.0, None), (100.0, None)], "out of order"),
+        (vec![(300.0, None), (300.0, None)], "out of order"),
+        (vec![(100.0, Some(0.0))], "speed limit"),
+        (
+            (0..=MAX_PLAN_BREAKPOINTS)
+                .map(|index| (index as f64, None))
+                .collect(),
+            "at most",
+        ),
+    ] {
+        let message = plan(500.0, &breakpoints).check().unwrap_err();
+        assert!(message.contains(problem), "{message}");
+    }
+}
+
+#[test]
+fn approach_gains_are_checked() {
+    let gains = |proportional, integral, derivative| ApproachGains {
+        proportional,
+        integral,
+        derivative,
+    };
+    assert!(gains(0.05, 0.0, 0.01).check().is_ok());
+    for (gains, problem) in [
+        (gains(-0.05, 0.0, 0.01), "proportional"),
+        (gains(0.05, f64::NAN, 0.01), "integral"),
+        (gains(0.05, 0.0, f64::INFINITY), "derivative"),
+    ] {
+        let message = gains.check().unwrap_err();
+        assert!(message.contains(problem), "{message}");
+    }
+}
+
+#[test]
+fn calibrated_distances_are_checked() {
+    assert!(CalibrateArguments { distance: 98.5 }.check().is_ok());
+    for distance in [0.0, -98.5, f64::NAN, f64::INFINITY] {
+        assert!(
+            CalibrateArguments { distance }.check().is_err(),
+            "{distance} was taken"
+        );
+    }
+}
@@ -0,0 +1,109 @@
+/*!
+ * Tests for agreeing on a version of the protocol and speaking older ones
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::*;
+
+/***** Helper functions *****/
+
+fn error(metadata: &str) -> Event<ErrorResponse> {
+    match Frame::decode(&format!(
+        "~ERROR${{\"error_variant\": 1, \"message\": \"Invalid command\"}}${metadata}"
+    ))
+    .unwrap()
+    {
+        Response::Error(event) => event,
+        _ => unreachable!(),
+    }
+}
+
+/***** Tests *****/
+
+#[test]
+fn hello_is_answered_with_a_version() {
+    let Response::Hello(hello) =
+        Frame::decode("~HELLO${\"protocol_version\": 2}${\"time\": 1.0, \"sequence\": 0}").unwrap()
+    else {
+        panic!("Not decoded as a HELLO");
+    };
+    assert_eq!(hello.value.protocol_version, PROTOCOL_VERSION);
+    assert_eq!(
+        TransitMode::from(Command::Hello),
+        TransitMode::ClientToServerRequest
+    );
+}
+
+#[test]
+fn only_older_versions_are_fallen_back_to() {
+    assert!(matches!(
+        negotiate_protocol_version(PROTOCOL_VERSION),
+        (PROTOCOL_VERSION, Ok(()))
+    ));
+
+    let (version, result) = negotiate_protocol_version(OLDEST_PROTOCOL_VERSION);
+    assert_eq!(version, OLDEST_PROTOCOL_VERSION);
+    let e = result.unwrap_err();
+    assert!(matches!(
+        e,
+        ClientError::ProtocolVersion { fallback: true, .. }
+    ));
+    assert_eq!(
+        e.to_string(),
+        "The car speaks protocol version 1, but this client speaks version 2, \
+         so version 1 is spoken to it instead"
+    );
+
+    // Newer, or too old to speak
+    for car in [PROTOCOL_VERSION + 1, 0] {
+        let (version, result) = negotiate_protocol_version(car);
+        assert_eq!(version, PROTOCOL_VERSION);
+        assert!(matches!(
+            result,
+            Err(ClientError::ProtocolVersion {
+                fallback: false,
+                ..
+            })
+        ));
+    }
+}
+
+#[test]
+fn firmware_from_before_hello_is_recognized() {
+    assert!(predates_hello(&error("{\"time\": 1.0}")));
+    // Answering some other request
+    assert!(!predates_hello(&error("{\"time\": 1.0, \"sequence\": 3}")));
+}
+
+#[test]
+fn version_1_frames_are_read() {
+    // No sequence, frame number, checksum, or speed limit violations
+    let Response::Status(status) = Frame::decode(
+        "~STATUS${\"running\": true, \"uptime\": 5, \"runtime\": 2, \"stage\": 1, \
+         \"distance\": {\"distance\": 12.5, \"velocity\": 3.0, \"magnet_hit_counter\": 4}}\
+         ${\"time\": 1.0}",
+    )
+    .unwrap() else {
+        panic!("Not decoded as a status");
+    };
+    assert_eq!(status.value.distance.distance, 12.5);
+    assert_eq!(status.value.speed_limit_violations, 0);
+    assert_eq!(status.metadata.sequence, None);
+}
+
+#[test]
+fn frames_are_not_numbered_for_version_1() {
+    let (client, mut car) = LoopbackTransport::pair();
+    let mut client = ArqTransport::new(Box::new(client), LinkEnd::Client);
+    let ping = "?PING${\"time\": 1.0}${\"time\": 1.0}";
+
+    client.write_line(ping).unwrap();
+    let numbered = car.read_line().unwrap().unwrap();
+    assert_eq!(RawFrame::split(&numbered).unwrap().metadata.frame, Some(0));
+
+    client.speak_protocol_version(OLDEST_PROTOCOL_VERSION);
+    client.write_line(ping).unwrap();
+    assert_eq!(car.read_line().unwrap().as_deref(), Some(ping));
+}
@@ -0,0 +1,52 @@
+/*!
+ * Tests for splitting round trips into the time on the car and on the link
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::*;
+
+/***** Tests *****/
+
+#[test]
+fn echoed_requests_are_split_by_each_clock() {
+    // The car's clock is an hour behind, which only the link would notice
+    let response = Frame::decode(
+        "~PING${\"sent_time\": 100.0}${\"time\": 96400.25, \"sequence\": 3, \"echo\": \
+         {\"time\": 100.0, \"received\": 96400.05}}",
+    )
+    .unwrap();
+    assert_eq!(response.command(), Command::Ping);
+    assert_eq!(response.metadata().sequence, Some(3));
+
+    let round_trip = response.round_trip(100.5).unwrap();
+    assert!((round_trip.total - 0.5).abs() < 1e-9);
+    assert!((round_trip.server - 0.2).abs() < 1e-9);
+    assert!((round_trip.link() - 0.3).abs() < 1e-9);
+}
+
+#[test]
+fn responses_without_an_echo_have_no_round_trip() {
+    // Firmware from before the echo, and statuses sent on their own
+    let response = Frame::decode("~PING${\"sent_time\": 100.0}${\"time\": 100.2}").unwrap();
+    assert!(response.metadata().echo.is_none());
+    assert!(response.round_trip(100.5).is_none());
+
+    // The echo isn't sent with requests
+    let request = Frame::encode(&Event {
+        command: Command::Status,
+        transit_mode: TransitMode::from(Command::Status),
+        transit_type: TransitType::Request,
+        value: StatusArguments {},
+        metadata: MetaData {
+            time: 100.0,
+            sequence: Some(1),
+            frame: None,
+            echo: None,
+            client: None,
+        },
+    })
+    .unwrap();
+    assert!(!request.contains("echo"));
+}
@@ -0,0 +1,99 @@
+/*!
+ * Tests for getting past what was left on a connection before it was opened
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::*;
+use std::{
+    io::Write,
+    net::TcpListener,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+// Constants
+const BUDGET: Duration = Duration::from_secs(1);
+const PING: &str = "~PING${\"sent_time\": 1.0}${\"time\": 1.0}";
+
+/***** Helper functions *****/
+
+/// Read the next line, waiting up to a second for it to come in
+fn wait_for_line(transport: &mut dyn Transport) -> String {
+    let start_time = Instant::now();
+    loop {
+        if let Some(line) = transport.read_line().unwrap() {
+            return line;
+        }
+        assert!(
+            start_time.elapsed() < Duration::from_secs(1),
+            "Nothing came in"
+        );
+        sleep(Duration::from_millis(5));
+    }
+}
+
+/***** Tests *****/
+
+#[test]
+fn boot_messages_are_thrown_away() {
+    let (mut client, mut car) = LoopbackTransport::pair();
+    car.write_line("\u{fffd}\u{fffd}rst:0x1").unwrap();
+    car.write_line("Booting...").unwrap();
+
+    let synchronization = synchronize(&mut client, BUDGET).unwrap();
+    assert_eq!(
+        synchronization,
+        Synchronization {
+            discarded: 2,
+            found_frame: false,
+            timed_out: false,
+        }
+    );
+
+    car.write_line(PING).unwrap();
+    assert_eq!(wait_for_line(&mut client), PING);
+}
+
+#[test]
+fn frames_after_the_first_are_left_to_be_read() {
+    let (mut client, mut car) = LoopbackTransport::pair();
+    car.write_line("US${\"running\": fal").unwrap();
+    car.write_line("~STOP${}${\"time\": 1.0}").unwrap();
+    car.write_line(PING).unwrap();
+
+    let synchronization = synchronize(&mut client, BUDGET).unwrap();
+    assert_eq!(synchronization.discarded, 1);
+    assert!(synchronization.found_frame);
+    assert_eq!(wait_for_line(&mut client), PING);
+}
+
+#[test]
+fn a_frame_cut_off_before_connecting_is_thrown_away() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = TcpTransport::connect(&listener.local_addr().unwrap().to_string()).unwrap();
+    let (mut stream, _) = listener.accept().unwrap();
+    stream.write_all(b"~STATUS${\"running\": tr").unwrap();
+    sleep(Duration::from_millis(20));
+
+    let synchronization = synchronize(&mut client, BUDGET).unwrap();
+    assert_eq!(synchronization.discarded, 0);
+    assert!(!synchronization.found_frame);
+
+    // Rather than being read as the start of the first response
+    stream.write_all(format!("{PING}\n").as_bytes()).unwrap();
+    assert_eq!(wait_for_line(&mut client), PING);
+}
+
+#[test]
+fn garbage_that_keeps_coming_is_given_up_on() {
+    let (mut client, mut car) = LoopbackTransport::pair();
+    for _ in 0..10 {
+        car.write_line("noise").unwrap();
+    }
+
+    let synchronization = synchronize(&mut client, Duration::ZERO).unwrap();
+    assert!(synchronization.timed_out);
+    assert_eq!(synchronization.discarded, 1);
+}
@@ -0,0 +1,92 @@
+/*!
+ * Tests for notifying systemd
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::*;
+use std::{
+    env::{remove_var, set_var, temp_dir},
+    fs::remove_file,
+    io::ErrorKind,
+    os::unix::net::UnixDatagram,
+    process,
+    time::Duration,
+};
+
+/***** Helper functions *****/
+
+/// The next notification sent, if there is one
+fn next_notification(socket: &UnixDatagram) -> Option<String> {
+    let mut buffer = [0; 256];
+    match socket.recv(&mut buffer) {
+        Ok(len) => Some(String::from_utf8_lossy(&buffer[..len]).into_owned()),
+        Err(e) if e.kind() == ErrorKind::WouldBlock => None,
+        Err(e) => panic!("Failed to receive: {e}"),
+    }
+}
+
+/***** Tests *****/
+
+#[test]
+fn notifies_systemd_when_started_by_it() {
+    let path = temp_dir().join(format!("bindings-notify-{}.sock", process::id()));
+    let _ = remove_file(&path);
+    let socket = UnixDatagram::bind(&path).unwrap();
+    socket.set_nonblocking(true).unwrap();
+    set_var("NOTIFY_SOCKET", &path);
+    set_var("WATCHDOG_USEC", "10000000");
+    set_var("WATCHDOG_PID", process::id().to_string());
+
+    let mut notifier = SystemdNotifier::from_env();
+    assert_eq!(notifier.heartbeat_interval(), Some(Duration::from_secs(5)));
+
+    // Only told once
+    notifier.ready();
+    notifier.ready();
+    assert_eq!(next_notification(&socket).as_deref(), Some("READY=1\n"));
+    assert_eq!(next_notification(&socket), None);
+
+    // Not again until the interval is up
+    notifier.heartbeat();
+    notifier.heartbeat();
+    assert_eq!(next_notification(&socket).as_deref(), Some("WATCHDOG=1\n"));
+    assert_eq!(next_notification(&socket), None);
+
+    // Only when it changes
+    notifier.status("Idling");
+    notifier.status("Idling");
+    notifier.status("Running");
+    assert_eq!(
+        next_notification(&socket).as_deref(),
+        Some("STATUS=Idling\n")
+    );
+    assert_eq!(
+        next_notification(&socket).as_deref(),
+        Some("STATUS=Running\n")
+    );
+    assert_eq!(next_notification(&socket), None);
+
+    notifier.stopping();
+    assert_eq!(next_notification(&socket).as_deref(), Some("STOPPING=1\n"));
+
+    // Even with systemd there
+    let mut notifier = SystemdNotifier::disabled();
+    notifier.ready();
+    notifier.status("Running");
+    notifier.stopping();
+    assert_eq!(next_notification(&socket), None);
+
+    for variable in NOTIFY_VARIABLES {
+        remove_var(variable);
+    }
+    let _ = remove_file(&path);
+}
+
+#[test]
+fn no_heartbeats_without_the_watchdog() {
+    let mut notifier = SystemdNotifier::default();
+    assert_eq!(notifier.heartbeat_interval(), None);
+    notifier.heartbeat();
+}
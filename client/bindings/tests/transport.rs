@@ -0,0 +1,133 @@
+/*!
+ * Tests for sending lines over each transport
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::*;
+#[cfg(unix)]
+use std::{env::temp_dir, fs::remove_file, os::unix::net::UnixListener};
+use std::{
+    io::Write,
+    net::TcpListener,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+/***** Helper functions *****/
+
+/// Read the next line, waiting up to a second for it to come in
+fn wait_for_line(transport: &mut dyn Transport) -> Result<String, ClientError> {
+    let start_time = Instant::now();
+    loop {
+        if let Some(line) = transport.read_line()? {
+            return Ok(line);
+        }
+        assert!(
+            start_time.elapsed() < Duration::from_secs(1),
+            "Nothing came in"
+        );
+        sleep(Duration::from_millis(5));
+    }
+}
+
+/***** Tests *****/
+
+#[test]
+fn loopback_sends_both_ways() {
+    let (mut client, mut car) = LoopbackTransport::pair();
+    assert_eq!(client.read_line().unwrap(), None);
+
+    client.write_line("?PING${}${}").unwrap();
+    client.write_line("?STATUS${}${}").unwrap();
+    assert_eq!(car.read_line().unwrap().as_deref(), Some("?PING${}${}"));
+    assert_eq!(car.read_line().unwrap().as_deref(), Some("?STATUS${}${}"));
+    assert_eq!(car.read_line().unwrap(), None);
+
+    // A clone writes to the same end
+    let mut car_writer = car.try_clone().unwrap();
+    car_writer.write_line("~PING${}${}").unwrap();
+    assert_eq!(client.read_line().unwrap().as_deref(), Some("~PING${}${}"));
+}
+
+#[test]
+fn loopback_notices_the_other_end_dropped() {
+    let (mut client, car) = LoopbackTransport::pair();
+    let mut car_writer = car.try_clone().unwrap();
+    drop(car);
+    assert!(client.connected());
+
+    // What was sent before going away is still read
+    car_writer.write_line("~PING${}${}").unwrap();
+    drop(car_writer);
+    assert!(!client.connected());
+    assert_eq!(client.read_line().unwrap().as_deref(), Some("~PING${}${}"));
+    assert!(client.read_line().is_err());
+    assert!(client.write_line("?PING${}${}").is_err());
+}
+
+#[test]
+fn tcp_sends_both_ways() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = format!("{TCP_PREFIX}{}", listener.local_addr().unwrap());
+    let mut client = open_transport(&address, BAUD_RATE, Duration::from_secs(1)).unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    let mut car = TcpTransport::new(stream).unwrap();
+
+    client.write_line("?PING${}${}").unwrap();
+    assert_eq!(wait_for_line(&mut car).unwrap(), "?PING${}${}");
+    car.write_line("~PING${}${}").unwrap();
+    assert_eq!(wait_for_line(&mut *client).unwrap(), "~PING${}${}");
+
+    // Nothing is given back until the rest of the line comes in
+    let mut client = TcpTransport::connect(&address[TCP_PREFIX.len()..]).unwrap();
+    let (mut stream, _) = listener.accept().unwrap();
+    stream.write_all(b"~STATUS$").unwrap();
+    sleep(Duration::from_millis(50));
+    assert_eq!(client.read_line().unwrap(), None);
+    stream.write_all(b"{}${}\r\n").unwrap();
+    assert_eq!(wait_for_line(&mut client).unwrap(), "~STATUS${}${}");
+}
+
+#[test]
+fn tcp_notices_the_other_end_closed() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = TcpTransport::connect(&listener.local_addr().unwrap().to_string()).unwrap();
+    let (mut stream, _) = listener.accept().unwrap();
+    writeln!(stream, "~PING${{}}${{}}").unwrap();
+    drop(stream);
+    sleep(Duration::from_millis(50));
+
+    assert_eq!(client.read_line().unwrap().as_deref(), Some("~PING${}${}"));
+    assert!(client.read_line().is_err());
+    assert!(!client.connected());
+
+    assert!(open_transport("tcp://127.0.0.1:1", BAUD_RATE, Duration::from_secs(1)).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_socket_sends_both_ways() {
+    let path = temp_dir().join(format!("bindings-tests-{}.sock", std::process::id()));
+    let _ = remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+    let address = format!("{UNIX_PREFIX}{}", path.display());
+    let mut client = open_transport(&address, BAUD_RATE, Duration::from_secs(1)).unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    let mut car = UnixTransport::new(stream).unwrap();
+
+    client.write_line("?PING${}${}").unwrap();
+    assert_eq!(wait_for_line(&mut car).unwrap(), "?PING${}${}");
+    car.write_line("~PING${}${}").unwrap();
+    assert_eq!(wait_for_line(&mut *client).unwrap(), "~PING${}${}");
+
+    // Like the bridge restarting
+    drop(car);
+    sleep(Duration::from_millis(50));
+    assert!(client.read_line().is_err());
+    assert!(!client.connected());
+    drop(listener);
+    remove_file(&path).unwrap();
+    assert!(open_transport(&address, BAUD_RATE, Duration::from_secs(1)).is_err());
+}
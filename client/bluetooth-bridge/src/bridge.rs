@@ -0,0 +1,1241 @@
+/*!
+ * Serial to bluetooth bridge for wireless UART on the client side
+ * Created by sheepy0125 | MIT License | 2023-02-19
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{
+    cancellation::CancellationToken,
+    clients::{ClientId, Clients, Listener, SERIAL_CLIENT},
+    fragment::{Fragmenter, Reassembler},
+    gatt::{
+        RX_CHARACTERISTIC_SIZE, RX_CHARACTERISTIC_UUID, SERVICE_UUID, TX_CHARACTERISTIC_SIZE,
+        TX_CHARACTERISTIC_UUID,
+    },
+    recording::{Direction, Recorder, Replay},
+    trusted_devices::TrustedDevices,
+    types::{
+        BluetoothError::*,
+        Error::{self, *},
+        ReplyTo, Request, TaskEvent, CONNECT_PROGRESS_INTERVAL, DEFAULT_DEVICE_ADDRESS,
+        NEW_CLIENT_CHANNEL_CAPACITY, POLL_DELAY, RX_CHANNEL_CAPACITY, SCAN_TIMEOUT,
+        SERIAL_CHANNEL_CAPACITY, TASK_EVENT_CHANNEL_CAPACITY, WATCHDOG_INTERVAL, WATCHDOG_TIMEOUT,
+    },
+};
+use bindings::{
+    encode_bridge_response, BluetoothConnectRequest, BluetoothConnectResponse, BluetoothDevice,
+    BluetoothMetricsResponse, BluetoothScanResponse, BluetoothStatusResponse, Command, RawFrame,
+    StreamingDecoder, SystemdNotifier, TransitMode, Transport, WatchdogResponse,
+};
+use bluer::{gatt::remote::Characteristic, Adapter, AdapterEvent, Address, Device};
+use futures::{pin_mut, StreamExt};
+use std::{
+    cmp::Reverse,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+use tokio::{
+    select,
+    sync::mpsc::{channel, Receiver, Sender},
+    task::{JoinHandle, JoinSet},
+    time::{interval, sleep, sleep_until, timeout_at, Instant},
+};
+use tracing::{debug, error, info, instrument, trace, warn};
+
+/***** Helper functions *****/
+
+/// Helper function to search through the characteristics after we have connected
+/// This is needed so we can disconnect on error while also using the idiomatic `?`
+async fn already_connected_find_serial_characteristics(
+    device: &Device,
+) -> Result<SerialCharacteristics, Error> {
+    // Find the service again
+    debug!("Enumerating services");
+    let mut service = None;
+    for service_iter in device.services().await? {
+        let uuid = service_iter.uuid().await?;
+        debug!(%uuid, "Service data: {:?}", service_iter.all_properties().await?);
+        match uuid {
+            SERVICE_UUID => {
+                service = Some(service_iter);
+                break;
+            }
+            _ => continue,
+        }
+    }
+    let service = match service {
+        Some(service) => service,
+        None => Err(BluetoothError(MissingService))?,
+    };
+    debug!("Found our service");
+
+    // Find serial characteristics
+    let mut rx_characteristic = None;
+    let mut tx_characteristic = None;
+    for char in service.characteristics().await? {
+        let uuid = char.uuid().await?;
+        // This line crashes, WTF? \/
+        match uuid {
+            RX_CHARACTERISTIC_UUID => {
+                debug!("Found the RX characteristic");
+                rx_characteristic = Some(char);
+            }
+            TX_CHARACTERISTIC_UUID => {
+                debug!("Found the TX characteristic");
+                tx_characteristic = Some(char);
+            }
+            _ => (),
+        }
+
+        // Are we done?
+        if rx_characteristic.is_some() && tx_characteristic.is_some() {
+            return Ok(SerialCharacteristics {
+                // Safety: We know both of them are `Some` variants
+                rx_characteristic: rx_characteristic.unwrap(),
+                tx_characteristic: tx_characteristic.unwrap(),
+            });
+        }
+    }
+    Err(BluetoothError(MissingCharacteristic))
+}
+
+/// Helper function to find if the scanned device is the one we are looking for
+async fn find_serial_characteristics(
+    device: &Device,
+    target: Address,
+) -> Result<SerialCharacteristics, Error> {
+    // Get GAP information of the device
+    let addr = device.address();
+
+    if addr != target {
+        Err(BluetoothError(MissingService))?;
+    }
+
+    // Get GATT information of the device without connecting
+    let uuids = device.uuids().await?.unwrap_or_default();
+    let md = device.manufacturer_data().await?;
+
+    debug!(%addr, ?uuids, "Discovered device, manufacturer data: {md:x?}");
+
+    // Determine if it is our device (has the serial service)
+    if !uuids.contains(&SERVICE_UUID) {
+        Err(BluetoothError(MissingService))?;
+    }
+    info!(%addr, "Device provides the serial service");
+
+    // Attempt to connect since it is our device
+    if !device.is_connected().await? {
+        info!(%addr, "Connecting");
+        device.connect().await?;
+        info!(%addr, "Connected");
+    } else {
+        info!(%addr, "Already connected");
+    }
+
+    match already_connected_find_serial_characteristics(device).await {
+        Ok(characteristics) => Ok(characteristics),
+        Err(e) => {
+            device.disconnect().await?;
+            Err(e)
+        }
+    }
+}
+
+/// Forward everything the device sends over the Rx characteristic to be
+/// written to the serial port, until the device stops sending
+///
+/// The characteristic's notifications are subscribed to, and each one is
+/// queued up so none are lost. If the device doesn't support notifications,
+/// the characteristic is polled instead, and the same value twice in a row is
+/// taken to be one value read twice, counted in `duplicates_discarded`.
+async fn receive_from_device(
+    rx_characteristic: Characteristic,
+    rx_sender: Sender<Vec<u8>>,
+    duplicates_discarded: Arc<AtomicU64>,
+) -> Result<(), Error> {
+    let notifications = match rx_characteristic.flags().await?.notify {
+        true => match rx_characteristic.notify().await {
+            Ok(notifications) => Some(notifications),
+            Err(e) => {
+                error!("Failed to subscribe to notifications, polling instead: {e}");
+                None
+            }
+        },
+        false => {
+            warn!("The device doesn't notify, polling it instead");
+            None
+        }
+    };
+
+    if let Some(notifications) = notifications {
+        info!("Subscribed to notifications");
+        pin_mut!(notifications);
+        // Full means the serial port is behind, so wait on it instead of
+        // dropping anything
+        while let Some(value) = notifications.next().await {
+            if rx_sender.send(value).await.is_err() {
+                return Ok(());
+            }
+        }
+        Err(BluetoothError(NotificationsStopped))?;
+    }
+
+    let mut previous_value = Vec::with_capacity(RX_CHARACTERISTIC_SIZE);
+    loop {
+        let value = rx_characteristic.read().await?;
+        if value == previous_value {
+            duplicates_discarded.fetch_add(1, Ordering::Relaxed);
+        } else {
+            previous_value = value.clone();
+            if rx_sender.send(value).await.is_err() {
+                return Ok(());
+            }
+        }
+        sleep(Duration::from_millis(POLL_DELAY)).await;
+    }
+}
+
+/// Keep reading whole frames from a client (the serial port, or one sharing
+/// the device with it) until cancelled or it goes away, sending them on to be
+/// handled (this is getting Tx)
+///
+/// Reading blocks, so this is run on its own thread.
+fn read_from_client(
+    mut transport: Box<dyn Transport>,
+    client: ClientId,
+    frame_sender: Sender<(ClientId, String)>,
+    shutdown: CancellationToken,
+) {
+    while !shutdown.is_cancelled() {
+        match transport.read_line() {
+            Ok(Some(frame)) => {
+                trace!(client, %frame, "Read from client {client}");
+                if frame_sender.blocking_send((client, frame)).is_err() {
+                    break;
+                }
+                continue;
+            }
+            Ok(None) => (),
+            Err(e) => {
+                let connected = transport.connected();
+                match client {
+                    SERIAL_CLIENT => error!("Error reading from the serial port: {e}"),
+                    _ if !connected => info!(client, "Client {client} disconnected: {e}"),
+                    _ => error!(client, "Error reading from client {client}: {e}"),
+                }
+                if !connected {
+                    break;
+                }
+            }
+        }
+        thread::sleep(Duration::from_millis(POLL_DELAY));
+    }
+}
+
+/// Keep accepting clients connecting to share the device until cancelled,
+/// sending them on to be read from
+///
+/// Accepting blocks, so this is run on its own thread.
+fn accept_clients(
+    listener: Listener,
+    client_sender: Sender<Box<dyn Transport>>,
+    shutdown: CancellationToken,
+) {
+    if let Err(e) = listener.set_nonblocking() {
+        error!("Failed to listen for clients: {e}");
+        return;
+    }
+    while !shutdown.is_cancelled() {
+        match listener.accept() {
+            Ok(Some(transport)) => {
+                if client_sender.blocking_send(transport).is_err() {
+                    break;
+                }
+                continue;
+            }
+            Ok(None) => (),
+            Err(e) => error!("Error accepting a client: {e}"),
+        }
+        thread::sleep(Duration::from_millis(POLL_DELAY));
+    }
+}
+
+/// Each byte is taken to be a character
+fn bytes_to_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|character| *character as char).collect()
+}
+
+/// The sequence number of a frame, if it has one
+fn sequence_of(frame: &str) -> Option<u32> {
+    RawFrame::split(frame).ok()?.metadata.sequence
+}
+
+/***** Structs *****/
+
+pub struct SerialCharacteristics {
+    pub rx_characteristic: Characteristic,
+    pub tx_characteristic: Characteristic,
+}
+
+pub struct WirelessUartDevice {
+    pub address: Address,
+    pub device: Device,
+    pub serial_characteristics: SerialCharacteristics,
+}
+
+/// Reports how connecting is going back to the client
+pub struct ConnectProgress {
+    /// Whoever asked to connect
+    reply_to: ReplyTo,
+    start_time: Instant,
+    devices: Vec<BluetoothDevice>,
+    /// The device being looked for
+    target: Option<Address>,
+    connection: CancellationToken,
+    task_event_sender: Sender<TaskEvent>,
+}
+
+impl ConnectProgress {
+    pub fn new(
+        reply_to: ReplyTo,
+        connection: CancellationToken,
+        task_event_sender: Sender<TaskEvent>,
+    ) -> Self {
+        Self {
+            reply_to,
+            start_time: Instant::now(),
+            devices: vec![],
+            target: None,
+            connection,
+            task_event_sender,
+        }
+    }
+
+    /// Start looking for another device
+    pub fn looking_for(&mut self, target: Address) {
+        self.target = Some(target);
+    }
+
+    /// Note down a device that was found, replacing it if it already was
+    pub fn found(&mut self, device: BluetoothDevice) {
+        self.devices.retain(|found| found.address != device.address);
+        self.devices.push(device);
+    }
+
+    /// Send how it's going so far, and whether it connected once it's done
+    pub async fn report(&self, connected: Option<bool>) {
+        let progress = BluetoothConnectResponse {
+            elapsed: self.start_time.elapsed().as_secs_f64(),
+            devices: self.devices.clone(),
+            connected,
+            address: self.target.map(|target| target.to_string()),
+        };
+        let _ = self
+            .task_event_sender
+            .send(TaskEvent::ConnectProgress(
+                self.connection.clone(),
+                self.reply_to,
+                progress,
+            ))
+            .await;
+    }
+}
+
+/// Everything the bridge waits on, besides its own tasks finishing
+struct Inbox {
+    /// Whole frames read from the serial port
+    serial_frames: Receiver<(ClientId, String)>,
+    /// Whole frames read from the other clients sharing the device
+    client_frames: Receiver<(ClientId, String)>,
+    /// Clients that connected to share the device, to start reading from
+    new_clients: Receiver<Box<dyn Transport>>,
+    /// What the device sent, to be written to the serial port
+    rx: Receiver<Vec<u8>>,
+    task_events: Receiver<TaskEvent>,
+}
+
+struct SerialBluetoothBridge {
+    /// The serial port, and any other clients sharing the device, which are
+    /// only written to here, as they're read on threads of their own
+    clients: Clients,
+    /// For the clients that connect to send what they send to be handled
+    client_frame_sender: Sender<(ClientId, String)>,
+    /// For the clients that connect to be sent to be read from
+    new_client_sender: Sender<Box<dyn Transport>>,
+    pub wireless_uart_device: Option<WirelessUartDevice>,
+    /// Cancelled once the bridge is shutting down, stopping every task
+    shutdown: CancellationToken,
+    /// Cancelled once the device is forgotten, stopping connecting to it and
+    /// receiving from it
+    connection: CancellationToken,
+    /// Every task the bridge has spawned, which are all waited on before
+    /// shutting down
+    tasks: JoinSet<()>,
+    rx_sender: Sender<Vec<u8>>,
+    task_event_sender: Sender<TaskEvent>,
+    trusted_devices: TrustedDevices,
+    /// Splits what's sent to the device into chunks
+    fragmenter: Fragmenter,
+    /// Puts the chunks the device sent back together
+    reassembler: Reassembler,
+    /// What the device sent, once put back together, split up into whole
+    /// frames
+    rx_decoder: StreamingDecoder,
+    /// When the first frame sent to the device since it last sent back a valid
+    /// one was sent
+    ///
+    /// Nothing being sent either way is just being idle, so the watchdog only
+    /// counts from here
+    unanswered_since: Option<Instant>,
+    /// How the link to the device has been doing since the bridge started,
+    /// without the device and its signal strength, which are looked up when
+    /// asked for
+    metrics: BluetoothMetricsResponse,
+    /// Counted by the task receiving from the device
+    duplicates_discarded: Arc<AtomicU64>,
+    /// Whether a device has been connected to yet, so connecting again counts
+    /// as reconnecting
+    connected_before: bool,
+    /// Tells systemd the bridge is ready and still alive, when it's run as a
+    /// service
+    systemd: SystemdNotifier,
+    /// Where every chunk written to and read from the device is recorded, if
+    /// anywhere
+    recorder: Option<Recorder>,
+}
+
+impl SerialBluetoothBridge {
+    /// Start reading from the serial port, if there is one
+    fn new(
+        serial: Option<Box<dyn Transport>>,
+        trusted_devices: TrustedDevices,
+        systemd: SystemdNotifier,
+    ) -> Result<(Self, Inbox), Error> {
+        let shutdown = CancellationToken::new();
+        let (frame_sender, serial_frames) = channel(SERIAL_CHANNEL_CAPACITY);
+        let (client_frame_sender, client_frames) = channel(SERIAL_CHANNEL_CAPACITY);
+        let (new_client_sender, new_clients) = channel(NEW_CLIENT_CHANNEL_CAPACITY);
+        let (rx_sender, rx) = channel(RX_CHANNEL_CAPACITY);
+        let (task_event_sender, task_events) = channel(TASK_EVENT_CHANNEL_CAPACITY);
+
+        let mut tasks = JoinSet::new();
+        if let Some(serial) = &serial {
+            let serial_reader = serial.try_clone()?;
+            let serial_reader_shutdown = shutdown.clone();
+            tasks.spawn_blocking(move || {
+                read_from_client(
+                    serial_reader,
+                    SERIAL_CLIENT,
+                    frame_sender,
+                    serial_reader_shutdown,
+                )
+            });
+        }
+
+        Ok((
+            Self {
+                clients: Clients::new(serial),
+                client_frame_sender,
+                new_client_sender,
+                wireless_uart_device: None,
+                connection: shutdown.child_token(),
+                shutdown,
+                tasks,
+                rx_sender,
+                task_event_sender,
+                trusted_devices,
+                fragmenter: Fragmenter::new(),
+                reassembler: Reassembler::new(),
+                rx_decoder: StreamingDecoder::new(),
+                unanswered_since: None,
+                metrics: BluetoothMetricsResponse::default(),
+                duplicates_discarded: Arc::new(AtomicU64::new(0_u64)),
+                connected_before: false,
+                systemd,
+                recorder: None,
+            },
+            Inbox {
+                serial_frames,
+                client_frames,
+                new_clients,
+                rx,
+                task_events,
+            },
+        ))
+    }
+
+    /***** Bluetooth handlers *****/
+
+    /// Initialize the bluetooth adapter
+    pub async fn initialize_bluetooth_adapter() -> Result<Adapter, Error> {
+        let session = bluer::Session::new().await?;
+
+        let adapter = session.default_adapter().await?;
+        adapter.set_powered(true).await?;
+
+        info!(
+            "Discovering on Bluetooth adapter {} with address {}",
+            adapter.name(),
+            adapter.address().await?
+        );
+
+        Ok(adapter)
+    }
+
+    /// De-initialize the bluetooth adapter
+    pub async fn deinitialize_bluetooth_adapter() -> Result<(), Error> {
+        let session = bluer::Session::new().await?;
+
+        let adapter = session.default_adapter().await?;
+        adapter.set_powered(false).await?;
+
+        info!("No longer discovering on bluetooth adapter");
+
+        Ok(())
+    }
+
+    /// Connect to the wireless UART device with the address, looking for it
+    /// for up to [`SCAN_TIMEOUT`] milliseconds and reporting every device
+    /// found along the way
+    pub async fn connect_to_device(
+        adapter: &mut Adapter,
+        target: Address,
+        progress: &mut ConnectProgress,
+    ) -> Result<Option<WirelessUartDevice>, Error> {
+        let discover = adapter.discover_devices().await?;
+        pin_mut!(discover);
+
+        let deadline = Instant::now() + Duration::from_millis(SCAN_TIMEOUT);
+        let mut progress_interval = interval(Duration::from_millis(CONNECT_PROGRESS_INTERVAL));
+        let wireless_uart_device = loop {
+            let adapter_event = select! {
+                adapter_event = timeout_at(deadline, discover.next()) => match adapter_event {
+                    Ok(adapter_event) => adapter_event.ok_or(BluetoothError(MissingAdapterEvent))?,
+                    // Stops at the deadline even if nothing else turns up
+                    Err(_) => break None,
+                },
+                _ = progress_interval.tick() => {
+                    progress.report(None).await;
+                    continue;
+                }
+            };
+
+            match adapter_event {
+                AdapterEvent::DeviceAdded(address) => {
+                    let device = adapter.device(address)?;
+                    progress.found(BluetoothDevice {
+                        address: address.to_string(),
+                        name: device.name().await?,
+                        rssi: device.rssi().await?,
+                    });
+                    match find_serial_characteristics(&device, target).await {
+                        Ok(serial_characteristics) => {
+                            break Some(WirelessUartDevice {
+                                address,
+                                device,
+                                serial_characteristics,
+                            });
+                        }
+                        Err(e) => {
+                            error!("Failed to find the serial characteristics for {device:?}: {e}");
+                        }
+                    }
+                }
+                AdapterEvent::DeviceRemoved(address) => {
+                    debug!(%address, "Device removed");
+                }
+                AdapterEvent::PropertyChanged(property) => {
+                    debug!("Property change: {property:?}");
+                }
+            }
+        };
+
+        Ok(wireless_uart_device)
+    }
+
+    /// Find every device advertising the serial service, strongest signal
+    /// first, scanning for [`SCAN_TIMEOUT`] milliseconds
+    pub async fn scan_for_devices(adapter: &mut Adapter) -> Result<Vec<BluetoothDevice>, Error> {
+        let discover = adapter.discover_devices().await?;
+        pin_mut!(discover);
+
+        let mut devices: Vec<BluetoothDevice> = vec![];
+        let deadline = Instant::now() + Duration::from_millis(SCAN_TIMEOUT);
+        // Stops at the deadline even if nothing else turns up
+        while let Ok(adapter_event) = timeout_at(deadline, discover.next()).await {
+            let address = match adapter_event.ok_or(BluetoothError(MissingAdapterEvent))? {
+                AdapterEvent::DeviceAdded(address) => address,
+                _ => continue,
+            };
+            let device = adapter.device(address)?;
+            let uuids = device.uuids().await?.unwrap_or_default();
+            if !uuids.contains(&SERVICE_UUID) {
+                continue;
+            }
+            info!(%address, "Found device providing the serial service");
+
+            let address = address.to_string();
+            devices.retain(|device| device.address != address);
+            devices.push(BluetoothDevice {
+                address,
+                name: device.name().await?,
+                rssi: device.rssi().await?,
+            });
+        }
+
+        devices.sort_by_key(|device| Reverse(device.rssi));
+        Ok(devices)
+    }
+
+    /// Start receiving from a device that was just connected to, keeping it
+    /// around to transmit to
+    pub fn start_receiving(&mut self, wireless_uart_device: WirelessUartDevice) {
+        let connection = self.connection.clone();
+        let rx_characteristic = wireless_uart_device
+            .serial_characteristics
+            .rx_characteristic
+            .clone();
+        let rx_sender = self.rx_sender.clone();
+        let duplicates_discarded = Arc::clone(&self.duplicates_discarded);
+        let task_event_sender = self.task_event_sender.clone();
+        self.tasks.spawn(async move {
+            select! {
+                received = receive_from_device(rx_characteristic, rx_sender, duplicates_discarded) => {
+                    if let Err(e) = received {
+                        error!("Stopped receiving from the device: {e}");
+                    }
+                    let _ = task_event_sender
+                        .send(TaskEvent::ReceivingStopped(connection.clone()))
+                        .await;
+                }
+                _ = connection.cancelled() => (),
+            }
+        });
+        self.wireless_uart_device = Some(wireless_uart_device);
+    }
+
+    /// Pass on what the device sent in a recording as if it were connected,
+    /// as long after starting as it was sent after the recording started
+    pub fn start_replay(&mut self, mut replay: Replay) {
+        info!(
+            path = %replay.path.display(),
+            "Replaying {} chunk(s) from the device",
+            replay.remaining()
+        );
+        let rx_sender = self.rx_sender.clone();
+        let shutdown = self.shutdown.clone();
+        self.tasks.spawn(async move {
+            let started = Instant::now();
+            let replaying = async {
+                while let Some((due, rx)) = replay.next_rx() {
+                    sleep_until(started + due).await;
+                    if rx_sender.send(rx).await.is_err() {
+                        return;
+                    }
+                }
+                info!("Finished replaying");
+            };
+            select! {
+                _ = replaying => (),
+                _ = shutdown.cancelled() => (),
+            }
+        });
+    }
+
+    /// Connect to the first of the targets found, trying each in order in its
+    /// own task, in place of the current device
+    pub fn connect(&mut self, targets: Vec<Address>, reply_to: ReplyTo) {
+        // Terminate current handle
+        self.forget_device();
+
+        let connection = self.connection.clone();
+        let task_event_sender = self.task_event_sender.clone();
+        self.tasks.spawn(async move {
+            let mut progress =
+                ConnectProgress::new(reply_to, connection.clone(), task_event_sender.clone());
+            let connecting = async {
+                // Restart adapter
+                Self::deinitialize_bluetooth_adapter().await?;
+                let mut adapter = Self::initialize_bluetooth_adapter().await?;
+
+                let mut connected = Ok(None);
+                for target in targets {
+                    info!(%target, "Connecting");
+                    progress.looking_for(target);
+                    connected = Self::connect_to_device(&mut adapter, target, &mut progress).await;
+                    match &connected {
+                        Ok(Some(_)) => break,
+                        Ok(None) => warn!(%target, "Couldn't find the device"),
+                        Err(e) => error!("Failed to connect to {target}: {e}"),
+                    }
+                }
+                connected
+            };
+            select! {
+                connected = connecting => {
+                    progress.report(Some(matches!(connected, Ok(Some(_))))).await;
+                    let _ = task_event_sender
+                        .send(TaskEvent::Connected(connection.clone(), connected))
+                        .await;
+                }
+                _ = connection.cancelled() => info!("No longer connecting"),
+            }
+        });
+    }
+
+    /// Connect to the first trusted device found without being asked, so
+    /// nobody has to press connect
+    pub fn connect_to_trusted_devices(&mut self) {
+        let candidates = self.trusted_devices.candidates();
+        info!(
+            "Connecting to {} trusted device(s) at startup",
+            candidates.len()
+        );
+        self.connect(candidates, ReplyTo::default());
+    }
+
+    /// Forget the current device, stopping connecting to it and receiving
+    /// from it
+    pub fn forget_device(&mut self) {
+        self.connection.cancel();
+        self.connection = self.shutdown.child_token();
+        self.wireless_uart_device = None;
+        self.reassembler = Reassembler::new();
+        self.rx_decoder = StreamingDecoder::new();
+        self.unanswered_since = None;
+        self.systemd.status("Not connected");
+    }
+
+    /// Note down what the device sent, returning the whole frames it finished
+    ///
+    /// The watchdog knows the device is answering once a whole valid frame
+    /// comes in.
+    pub fn received_from_device(&mut self, rx: &[u8]) -> Vec<String> {
+        self.metrics.bytes_received += rx.len() as u64;
+        self.reassembler.push_bytes(rx);
+        while let Some(frame) = self.reassembler.recv_frame() {
+            self.rx_decoder.push_bytes(&frame);
+        }
+        let mut frames = vec![];
+        while let Some(frame) = self.rx_decoder.next_frame() {
+            if RawFrame::split(&frame).is_ok() {
+                self.unanswered_since = None;
+            }
+            frames.push(frame);
+        }
+        frames
+    }
+
+    /// Reconnect to the device if it hasn't answered anything sent to it for
+    /// [`WATCHDOG_TIMEOUT`] milliseconds, telling the client first
+    ///
+    /// The R41Z can hang while still seeming to be connected, which only
+    /// reconnecting gets it out of.
+    pub fn check_watchdog(&mut self) -> Result<(), Error> {
+        let Some(unanswered_since) = self.unanswered_since else {
+            return Ok(());
+        };
+        let silent_for = unanswered_since.elapsed();
+        if silent_for < Duration::from_millis(WATCHDOG_TIMEOUT) {
+            return Ok(());
+        }
+        let Some(address) = self
+            .wireless_uart_device
+            .as_ref()
+            .map(|wireless_uart_device| wireless_uart_device.address)
+        else {
+            return Ok(());
+        };
+
+        warn!(
+            %address,
+            "Nothing from the device for {:.1}s, reconnecting",
+            silent_for.as_secs_f64()
+        );
+        self.connect(vec![address], ReplyTo::default());
+        let frame = encode_bridge_response(
+            Command::Watchdog,
+            WatchdogResponse {
+                silent_for: silent_for.as_secs_f64(),
+                address: address.to_string(),
+            },
+            None,
+        )
+        .map_err(|e| RequestError(e.to_string()))?;
+        self.clients.send(None, &frame)?;
+
+        Ok(())
+    }
+
+    /// Send a whole frame to the wireless UART device in chunks (see
+    /// [`Fragmenter`]), returning the number of bytes written
+    ///
+    /// The Tx characteristic only takes a byte at a time, so the chunks are
+    /// written a byte at a time, with the last write padded out with zeros.
+    pub async fn send_frame(&mut self, frame: &str) -> Result<usize, Error> {
+        debug!(%frame, "Writing to the device");
+
+        let bytes = self
+            .fragmenter
+            .send_frame(frame.as_bytes())
+            .map_err(BluetoothError)?;
+        let tx_characteristic = self
+            .wireless_uart_device
+            .as_ref()
+            .ok_or(BluetoothError(NotConnected))?
+            .serial_characteristics
+            .tx_characteristic
+            .clone();
+        for write in bytes.chunks(TX_CHARACTERISTIC_SIZE) {
+            let mut buffer = [0_u8; TX_CHARACTERISTIC_SIZE];
+            buffer[..write.len()].copy_from_slice(write);
+            tx_characteristic.write(&buffer).await?;
+            self.record(Direction::Tx, &buffer);
+        }
+
+        Ok(bytes.len())
+    }
+
+    /// How the link to the device has been doing since the bridge started
+    pub async fn metrics(&mut self) -> BluetoothMetricsResponse {
+        let device = self.wireless_uart_device.as_ref();
+        let rssi =
+            match device {
+                Some(wireless_uart_device) => wireless_uart_device
+                    .device
+                    .rssi()
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!("Failed to get the signal strength: {e}");
+                        None
+                    }),
+                None => None,
+            };
+        BluetoothMetricsResponse {
+            address: device.map(|wireless_uart_device| wireless_uart_device.address.to_string()),
+            rssi,
+            duplicates_discarded: self.duplicates_discarded.load(Ordering::Relaxed),
+            ..self.metrics.clone()
+        }
+    }
+
+    /// Record a chunk written to or read from the device, if recording
+    ///
+    /// Failing to isn't worth stopping the bridge over, so it's only logged.
+    pub fn record(&mut self, direction: Direction, bytes: &[u8]) {
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.record(direction, bytes) {
+                error!("Failed to record to {}: {e}", recorder.path.display());
+            }
+        }
+    }
+
+    /// Send a whole frame from a client on to the device, forgetting it if
+    /// that fails
+    pub async fn pass_to_device(&mut self, tx: &str) {
+        match self.send_frame(&format!("{tx}\n")).await {
+            Ok(bytes_sent) => {
+                self.metrics.bytes_sent += bytes_sent as u64;
+                self.unanswered_since.get_or_insert_with(Instant::now);
+            }
+            Err(e) => {
+                error!("Failed to write to the device: {e}");
+                self.forget_device();
+                let _ = Self::deinitialize_bluetooth_adapter().await;
+            }
+        }
+    }
+
+    /***** Client handlers *****/
+
+    /// Start listening for clients to share the device with, besides the
+    /// serial port
+    pub fn listen(&mut self, listener: Listener) {
+        match listener.address() {
+            Ok(address) => info!(%address, "Listening for clients to share the device with"),
+            Err(e) => warn!("Listening for clients somewhere unknown: {e}"),
+        }
+        self.clients.share();
+        let new_client_sender = self.new_client_sender.clone();
+        let shutdown = self.shutdown.clone();
+        self.tasks
+            .spawn_blocking(move || accept_clients(listener, new_client_sender, shutdown));
+    }
+
+    /// Start reading from a client that connected to share the device
+    pub fn add_client(&mut self, transport: Box<dyn Transport>) {
+        let reader = match transport.try_clone() {
+            Ok(reader) => reader,
+            Err(e) => {
+                error!("Failed to read from the client: {e}");
+                return;
+            }
+        };
+        let client = self.clients.add(transport);
+        info!(
+            client,
+            "Client {client} connected, {} sharing the device",
+            self.clients.count()
+        );
+        let frame_sender = self.client_frame_sender.clone();
+        let shutdown = self.shutdown.clone();
+        self.tasks
+            .spawn_blocking(move || read_from_client(reader, client, frame_sender, shutdown));
+    }
+
+    /***** Events *****/
+
+    /// Parse request from a client
+    pub fn parse_request(data: &str, client: ClientId) -> Result<Request, Error> {
+        let raw_frame = RawFrame::split(data).map_err(|e| RequestError(e.to_string()))?;
+        if raw_frame.transit_mode != TransitMode::ClientToSerialBridgeRequest {
+            Err(RequestError(format!(
+                "`{}` is not a request for the bridge",
+                raw_frame.command
+            )))?;
+        }
+
+        // Hey, none of the commands need anything more than the command
+        // In fact, the only reason why we have anything else is because it'd be
+        // easier to make the GUI send a full thing with no data at all
+        // XXX
+        let reply_to = ReplyTo {
+            client: Some(client),
+            sequence: raw_frame.metadata.sequence,
+        };
+        Ok(match raw_frame.command {
+            Command::BluetoothStatus => Request::BluetoothStatus(reply_to),
+            Command::BluetoothScan => Request::Scan(reply_to),
+            Command::BluetoothMetrics => Request::BluetoothMetrics(reply_to),
+            Command::Connect => {
+                // Older clients don't send any arguments
+                let arguments = raw_frame
+                    .value::<Option<BluetoothConnectRequest>>()
+                    .map_err(|e| RequestError(e.to_string()))?;
+                Request::Connect(
+                    arguments
+                        .and_then(|arguments| arguments.address)
+                        .map(|address| {
+                            Address::from_str(&address).map_err(|e| {
+                                RequestError(format!("`{address}` is not an address: {e}"))
+                            })
+                        })
+                        .transpose()?,
+                    reply_to,
+                )
+            }
+            Command::Disconnect => Request::Disconnect,
+            command => Err(RequestError(format!("`{command}` is not a bridge command")))?,
+        })
+    }
+
+    pub async fn handle_command(&mut self, client: ClientId, data: &str) -> Result<(), Error> {
+        debug!(client, %data, "Handling command");
+
+        let request = Self::parse_request(data, client)?;
+
+        // Connecting and scanning take a while, so they're done in their own
+        // tasks to not hold up everything else
+        use Request::*;
+        match request {
+            Connect(target, reply_to) => {
+                // Safety: the default is a valid address
+                let target =
+                    target.unwrap_or_else(|| Address::from_str(DEFAULT_DEVICE_ADDRESS).unwrap());
+                self.connect(vec![target], reply_to);
+            }
+            Disconnect => {
+                info!("Disconnecting");
+                // Terminate current handle
+                self.forget_device();
+                // Turn off adapter
+                Self::deinitialize_bluetooth_adapter().await?;
+            }
+            BluetoothStatus(reply_to) => {
+                info!("Returning bluetooth status");
+                let frame = encode_bridge_response(
+                    Command::BluetoothStatus,
+                    BluetoothStatusResponse {
+                        connected: self.wireless_uart_device.is_some(),
+                    },
+                    reply_to.sequence,
+                )
+                .map_err(|e| RequestError(e.to_string()))?;
+                self.clients.send(reply_to.client, &frame)?;
+            }
+            BluetoothMetrics(reply_to) => {
+                info!("Returning bluetooth metrics");
+                let frame = encode_bridge_response(
+                    Command::BluetoothMetrics,
+                    self.metrics().await,
+                    reply_to.sequence,
+                )
+                .map_err(|e| RequestError(e.to_string()))?;
+                self.clients.send(reply_to.client, &frame)?;
+            }
+            Scan(reply_to) => {
+                info!("Scanning for devices");
+                let shutdown = self.shutdown.clone();
+                let task_event_sender = self.task_event_sender.clone();
+                self.tasks.spawn(async move {
+                    let scanning = async {
+                        let mut adapter = Self::initialize_bluetooth_adapter().await?;
+                        Self::scan_for_devices(&mut adapter).await
+                    };
+                    select! {
+                        devices = scanning => {
+                            let _ = task_event_sender
+                                .send(TaskEvent::Scanned(reply_to, devices))
+                                .await;
+                        }
+                        _ = shutdown.cancelled() => (),
+                    }
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handle what a task reported back
+    pub async fn handle_task_event(&mut self, task_event: TaskEvent) -> Result<(), Error> {
+        use TaskEvent::*;
+        match task_event {
+            // Anything from a connection that's since been forgotten is stale
+            Connected(connection, _)
+            | ReceivingStopped(connection)
+            | ConnectProgress(connection, ..)
+                if connection.is_cancelled() => {}
+            Connected(_, connected) => match connected? {
+                Some(wireless_uart_device) => {
+                    info!(address = %wireless_uart_device.address, "Connected to the device");
+                    if let Err(e) = self.trusted_devices.trust(wireless_uart_device.address) {
+                        error!("Failed to save the trusted devices: {e}");
+                    }
+                    self.systemd
+                        .status(&format!("Connected to {}", wireless_uart_device.address));
+                    if self.connected_before {
+                        self.metrics.reconnects += 1;
+                    }
+                    self.connected_before = true;
+                    self.start_receiving(wireless_uart_device);
+                }
+                None => warn!("Couldn't find the device to connect to"),
+            },
+            ReceivingStopped(_) => {
+                warn!("No longer receiving from the device");
+                self.forget_device();
+                Self::deinitialize_bluetooth_adapter().await?;
+            }
+            ConnectProgress(_, reply_to, progress) => {
+                let frame = encode_bridge_response(Command::Connect, progress, reply_to.sequence)
+                    .map_err(|e| RequestError(e.to_string()))?;
+                self.clients.send(reply_to.client, &frame)?;
+            }
+            Scanned(reply_to, devices) => {
+                let frame = encode_bridge_response(
+                    Command::BluetoothScan,
+                    BluetoothScanResponse { devices: devices? },
+                    reply_to.sequence,
+                )
+                .map_err(|e| RequestError(e.to_string()))?;
+                self.clients.send(reply_to.client, &frame)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handle a whole frame read from a client
+    #[instrument(name = "request", skip_all, fields(client = client, sequence = sequence_of(&tx)))]
+    pub async fn handle_client_frame(&mut self, client: ClientId, tx: String) {
+        // Handle a command meant for us
+        if tx.starts_with('^') {
+            if let Err(e) = self.handle_command(client, &tx).await {
+                error!("Error handling command: {e}");
+            };
+        } else if self.wireless_uart_device.is_some() {
+            if let Some(tx) = self.clients.to_device(client, tx) {
+                self.pass_to_device(&tx).await;
+            }
+        }
+    }
+
+    /// Handle a whole frame the device sent, passing it on to the client it
+    /// answers (or every client)
+    #[instrument(name = "response", skip_all, fields(sequence = sequence_of(&rx)))]
+    pub async fn handle_device_frame(&mut self, rx: String) {
+        debug!(frame = %rx, "Received from the device");
+        let from_device = self.clients.from_device(rx);
+        for tx in from_device.to_device {
+            // Nothing to ask while replaying
+            if self.wireless_uart_device.is_none() {
+                break;
+            }
+            self.pass_to_device(&tx).await;
+        }
+        if let Some((client, frame)) = from_device.to_client {
+            if let Err(e) = self.clients.send(client, &frame) {
+                error!("Error writing to the serial port: {e}");
+            }
+        }
+    }
+
+    /// Pass everything through until shut down, either by its handle or the
+    /// serial port no longer being read
+    pub async fn run(&mut self, mut inbox: Inbox) -> Result<(), Error> {
+        self.systemd.ready();
+        if self.wireless_uart_device.is_none() {
+            self.systemd.status("Not connected");
+        }
+        let mut watchdog = interval(Duration::from_millis(WATCHDOG_INTERVAL));
+        // Without one, only shutting down stops the bridge
+        let serial_port = self.clients.has_serial_port();
+        let stopped = loop {
+            select! {
+                _ = self.shutdown.cancelled() => break Ok(()),
+                tx = inbox.serial_frames.recv(), if serial_port => match tx {
+                    Some((client, tx)) => self.handle_client_frame(client, tx).await,
+                    None => break Err(TaskError("The serial port stopped being read".to_string())),
+                },
+                Some((client, tx)) = inbox.client_frames.recv() => {
+                    self.handle_client_frame(client, tx).await;
+                }
+                Some(transport) = inbox.new_clients.recv() => self.add_client(transport),
+                Some(rx) = inbox.rx.recv() => {
+                    trace!("Received {} from the device", bytes_to_string(&rx));
+                    self.record(Direction::Rx, &rx);
+                    for frame in self.received_from_device(&rx) {
+                        self.handle_device_frame(frame).await;
+                    }
+                }
+                Some(task_event) = inbox.task_events.recv() => {
+                    if let Err(e) = self.handle_task_event(task_event).await {
+                        error!("Error handling task event: {e}");
+                    }
+                }
+                Some(joined) = self.tasks.join_next() => {
+                    if let Err(e) = joined {
+                        error!("A task failed: {e}");
+                    }
+                }
+                _ = watchdog.tick() => {
+                    if let Err(e) = self.check_watchdog() {
+                        error!("Error checking the watchdog: {e}");
+                    }
+                    // Only sent while this loop is going, so systemd's
+                    // watchdog restarts the bridge if it hangs
+                    self.systemd.heartbeat();
+                }
+            }
+        };
+
+        // Every task stops once cancelled, and nothing is left waiting on the
+        // inbox once it's dropped
+        self.systemd.stopping();
+        self.shutdown.cancel();
+        drop(inbox);
+        while let Some(joined) = self.tasks.join_next().await {
+            if let Err(e) = joined {
+                error!("A task failed: {e}");
+            }
+        }
+        if self.wireless_uart_device.take().is_some() {
+            let _ = Self::deinitialize_bluetooth_adapter().await;
+        }
+
+        stopped
+    }
+}
+
+/***** Handle *****/
+
+/// A bridge running in a task of its own, passing frames between the client
+/// on the other end of a transport and the wireless UART device
+///
+/// The transport can be a serial port (or TCP) for the bridge on its own, or
+/// one end of a [`LoopbackTransport`] when embedded, with the client talking
+/// over the other. Other clients can share the device over TCP or a Unix
+/// domain socket too (see [`Clients`]), in which case there doesn't need to
+/// be a transport at all.
+///
+/// [`LoopbackTransport`]: bindings::LoopbackTransport
+pub struct BridgeHandle {
+    shutdown: CancellationToken,
+    task: JoinHandle<Result<(), Error>>,
+}
+impl BridgeHandle {
+    /// Start the bridge on the current runtime, connecting to the first
+    /// trusted device found, recording everything that goes to and from it if
+    /// given somewhere to, and sharing it with the clients that connect to
+    /// the listener if given one
+    pub fn spawn(
+        serial: Option<Box<dyn Transport>>,
+        trusted_devices: TrustedDevices,
+        systemd: SystemdNotifier,
+        recorder: Option<Recorder>,
+        listener: Option<Listener>,
+    ) -> Result<Self, Error> {
+        let (mut bridge, inbox) = SerialBluetoothBridge::new(serial, trusted_devices, systemd)?;
+        if let Some(recorder) = &recorder {
+            info!(path = %recorder.path.display(), "Recording to and from the device");
+        }
+        bridge.recorder = recorder;
+        if let Some(listener) = listener {
+            bridge.listen(listener);
+        }
+        bridge.connect_to_trusted_devices();
+        Ok(Self::run(bridge, inbox))
+    }
+
+    /// Start the bridge on the current runtime without connecting to
+    /// anything, passing on what the device sent in a recording instead
+    ///
+    /// Frames from the clients that aren't for the bridge go nowhere, like
+    /// when it isn't connected.
+    pub fn replay(
+        serial: Option<Box<dyn Transport>>,
+        trusted_devices: TrustedDevices,
+        systemd: SystemdNotifier,
+        replay: Replay,
+        listener: Option<Listener>,
+    ) -> Result<Self, Error> {
+        let (mut bridge, inbox) = SerialBluetoothBridge::new(serial, trusted_devices, systemd)?;
+        if let Some(listener) = listener {
+            bridge.listen(listener);
+        }
+        bridge.start_replay(replay);
+        Ok(Self::run(bridge, inbox))
+    }
+
+    fn run(mut bridge: SerialBluetoothBridge, inbox: Inbox) -> Self {
+        let shutdown = bridge.shutdown.clone();
+        let task = tokio::spawn(async move { bridge.run(inbox).await });
+        Self { shutdown, task }
+    }
+
+    /// Stop passing frames through and disconnect from the device, which
+    /// [`Self::stopped`] waits on
+    pub fn shutdown(&self) {
+        info!("Shutting down");
+        self.shutdown.cancel();
+    }
+
+    /// Whether the bridge has stopped, by being shut down or otherwise
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Wait for the bridge to stop, with why if it wasn't shut down
+    pub async fn stopped(&mut self) -> Result<(), Error> {
+        (&mut self.task)
+            .await
+            .map_err(|e| TaskError(e.to_string()))?
+    }
+}
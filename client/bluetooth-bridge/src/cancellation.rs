@@ -0,0 +1,77 @@
+/*!
+ * Cancellation tokens for telling tasks to stop
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch::{channel, Sender};
+
+/***** Cancellation token *****/
+
+struct Inner {
+    cancelled: Sender<bool>,
+    children: Mutex<Vec<CancellationToken>>,
+}
+
+/// Shared between tasks and whatever may want them to stop
+///
+/// Cancelling a token also cancels every child token made from it, so
+/// cancelling the bridge's token stops everything it's doing.
+#[derive(Clone)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                cancelled: channel(false).0,
+                children: Mutex::new(vec![]),
+            }),
+        }
+    }
+
+    /// A token cancelled along with this one, which can also be cancelled on
+    /// its own
+    pub fn child_token(&self) -> Self {
+        let child = Self::new();
+        // Safety: nothing panics while holding the lock
+        let mut children = self.inner.children.lock().unwrap();
+        children.retain(|child| !child.is_cancelled());
+        match self.is_cancelled() {
+            true => child.cancel(),
+            false => children.push(child.clone()),
+        }
+        child
+    }
+
+    pub fn cancel(&self) {
+        self.inner.cancelled.send_replace(true);
+        // Safety: nothing panics while holding the lock
+        for child in self.inner.children.lock().unwrap().drain(..) {
+            child.cancel();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.inner.cancelled.borrow()
+    }
+
+    /// Wait until cancelled
+    pub async fn cancelled(&self) {
+        let mut cancelled = self.inner.cancelled.subscribe();
+        while !*cancelled.borrow_and_update() {
+            // Can't fail, as this token holds on to the sender
+            let _ = cancelled.changed().await;
+        }
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,351 @@
+/*!
+ * Sharing the device between clients: the serial port, and any that connect
+ * while the bridge listens for them
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::types::Error;
+use bindings::{
+    edit_metadata, nack_frame, unchecked_frame_number, ClientError, Command, LinkEnd,
+    NackArguments, RawFrame, ReceivedFrames, SentFrames, TcpTransport, Transport, UnixTransport,
+    TCP_PREFIX, UNIX_PREFIX,
+};
+use std::{
+    fs::{remove_file, symlink_metadata},
+    io::{Error as IoError, ErrorKind},
+    net::TcpListener,
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::PathBuf,
+};
+use tracing::{debug, error, info, warn};
+
+// Constants
+/// Whatever's on the serial port, which is always there
+pub const SERIAL_CLIENT: ClientId = 0;
+
+/***** Listening *****/
+
+/// Where the bridge listens for clients to share the device with
+pub enum Listener {
+    Tcp(TcpListener),
+    /// The socket is removed once the bridge stops listening
+    Unix {
+        listener: UnixListener,
+        path: PathBuf,
+    },
+}
+impl Listener {
+    /// Listen at a `<host>:<port>` over TCP, or at a `unix://<path>` over a
+    /// Unix domain socket
+    ///
+    /// A socket left at the path by a bridge that didn't stop cleanly is
+    /// replaced, but not one that's still being listened on.
+    pub fn bind(address: &str) -> Result<Self, Error> {
+        let Some(path) = address.strip_prefix(UNIX_PREFIX) else {
+            return Ok(Self::Tcp(TcpListener::bind(address)?));
+        };
+        let path = PathBuf::from(path);
+        if let Ok(metadata) = symlink_metadata(&path) {
+            if !metadata.file_type().is_socket() {
+                return Err(IoError::new(
+                    ErrorKind::AlreadyExists,
+                    format!("{} is already there and isn't a socket", path.display()),
+                )
+                .into());
+            }
+            if UnixStream::connect(&path).is_ok() {
+                return Err(IoError::new(
+                    ErrorKind::AddrInUse,
+                    format!("Something is already listening on {}", path.display()),
+                )
+                .into());
+            }
+            warn!(path = %path.display(), "Replacing a socket left behind");
+            remove_file(&path)?;
+        }
+        Ok(Self::Unix {
+            listener: UnixListener::bind(&path)?,
+            path,
+        })
+    }
+
+    /// Where clients connect to, as they'd be given it
+    pub fn address(&self) -> Result<String, Error> {
+        Ok(match self {
+            Self::Tcp(listener) => format!("{TCP_PREFIX}{}", listener.local_addr()?),
+            Self::Unix { path, .. } => format!("{UNIX_PREFIX}{}", path.display()),
+        })
+    }
+
+    pub fn set_nonblocking(&self) -> Result<(), Error> {
+        match self {
+            Self::Tcp(listener) => listener.set_nonblocking(true)?,
+            Self::Unix { listener, .. } => listener.set_nonblocking(true)?,
+        }
+        Ok(())
+    }
+
+    /// A client that connected, or `None` if none are waiting
+    pub fn accept(&self) -> Result<Option<Box<dyn Transport>>, Error> {
+        let accepted = match self {
+            Self::Tcp(listener) => listener.accept().map(|(stream, address)| {
+                debug!(%address, "A client connected");
+                TcpTransport::new(stream).map(|transport| Box::new(transport) as Box<dyn Transport>)
+            }),
+            // Unix domain sockets connecting are nameless
+            Self::Unix { listener, .. } => listener.accept().map(|(stream, _)| {
+                debug!("A client connected");
+                UnixTransport::new(stream)
+                    .map(|transport| Box::new(transport) as Box<dyn Transport>)
+            }),
+        };
+        match accepted {
+            Ok(client) => Ok(Some(client?)),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Self::Unix { path, .. } = self {
+            if let Err(e) = remove_file(&*path) {
+                warn!(path = %path.display(), "Failed to remove the socket: {e}");
+            }
+        }
+    }
+}
+
+/***** Clients *****/
+
+/// Tells the clients sharing the device apart, counting up from the serial
+/// port's 0 in the order they connected
+pub type ClientId = u32;
+
+/// The frames sent to and received from the device while it's shared,
+/// numbered by the bridge in place of the clients
+///
+/// Each client numbers the frames it sends on its own (see
+/// [`bindings::ArqTransport`]), so the numbers of clients sending at once
+/// would clash. The device only ever sees the bridge's numbers instead, and
+/// asks the bridge for anything it missed; the bridge asks the device for
+/// anything it missed in turn. The clients are given the device's frames
+/// unnumbered, so they never ask for any.
+#[derive(Default)]
+struct SharedLink {
+    sent: SentFrames,
+    received: ReceivedFrames,
+}
+
+/// What to do with a frame from the device
+pub struct FromDevice {
+    /// `NACK`s for frames the device sent that went missing, and frames it
+    /// asked for again, to write back to it
+    pub to_device: Vec<String>,
+    /// The frame to pass on, with the client it answers (or `None` for every
+    /// client), unless it was only for the bridge
+    pub to_client: Option<(Option<ClientId>, String)>,
+}
+impl FromDevice {
+    fn to_everyone(frame: String) -> Self {
+        Self {
+            to_device: vec![],
+            to_client: Some((None, frame)),
+        }
+    }
+}
+
+/// Everything frames from the device are passed on to
+///
+/// There's only ever the serial port, and frames pass through as they are,
+/// until the bridge starts listening for other clients (see
+/// [`Self::share`]), when there might not be a serial port at all. From then
+/// on, requests to the device are tagged with the `client` that sent them in
+/// their metadata, which the car sends back with its response so only that
+/// client is given it. Anything the car sends without a `client` (statuses
+/// sent on their own during a run, or every response from a server that
+/// doesn't send one back) is given to every client.
+pub struct Clients {
+    /// The serial port first (if there is one), then the clients in the
+    /// order they connected
+    connections: Vec<(ClientId, Box<dyn Transport>)>,
+    next: ClientId,
+    /// Only once the device is shared
+    link: Option<SharedLink>,
+}
+impl Clients {
+    pub fn new(serial: Option<Box<dyn Transport>>) -> Self {
+        Self {
+            connections: serial
+                .map(|serial| (SERIAL_CLIENT, serial))
+                .into_iter()
+                .collect(),
+            next: SERIAL_CLIENT + 1,
+            link: None,
+        }
+    }
+
+    /// Start sharing the device between clients, tagging requests and
+    /// numbering frames to and from it from now on
+    pub fn share(&mut self) {
+        self.link.get_or_insert_with(SharedLink::default);
+    }
+
+    /// Add a client that connected, to write to from now on
+    pub fn add(&mut self, transport: Box<dyn Transport>) -> ClientId {
+        let client = self.next;
+        self.next = self.next.wrapping_add(1);
+        self.connections.push((client, transport));
+        client
+    }
+
+    /// Whether there's a serial port, which there always is unless the device
+    /// is only shared with the clients that connect
+    pub fn has_serial_port(&self) -> bool {
+        self.connections
+            .first()
+            .is_some_and(|(id, _)| *id == SERIAL_CLIENT)
+    }
+
+    /// How many clients there are, including the serial port if there is one
+    pub fn count(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// A frame from a client, as it should be sent to the device, if it
+    /// should be at all
+    ///
+    /// Frames that can't be split up are sent as they are, for the car to
+    /// answer with what's wrong with them, and frames that can't be numbered
+    /// are sent unnumbered. `NACK`s from clients are dropped, as only the
+    /// bridge asks the device for frames again.
+    pub fn to_device(&mut self, client: ClientId, frame: String) -> Option<String> {
+        let Some(link) = &mut self.link else {
+            return Some(frame);
+        };
+        let Ok(raw_frame) = RawFrame::split(&frame) else {
+            return Some(frame);
+        };
+        if raw_frame.command == Command::Nack {
+            return None;
+        }
+
+        let tagged = match edit_metadata(&frame, |metadata| metadata.client = Some(client)) {
+            Ok(tagged) => tagged,
+            Err(e) => {
+                error!(client, "Failed to tag the frame: {e}");
+                return Some(frame);
+            }
+        };
+        // Only renumbered if the client numbers its own, as older ones don't
+        if raw_frame.metadata.frame.is_none() {
+            return Some(tagged);
+        }
+        match link.sent.number(&tagged) {
+            Ok(numbered) => Some(numbered),
+            Err(e) => {
+                error!(client, "Failed to number the frame: {e}");
+                Some(tagged)
+            }
+        }
+    }
+
+    /// What to do with a whole frame the device sent
+    pub fn from_device(&mut self, frame: String) -> FromDevice {
+        let Some(link) = &mut self.link else {
+            return FromDevice::to_everyone(frame);
+        };
+        if !frame.starts_with(LinkEnd::Client.receives() as u8 as char) {
+            return FromDevice::to_everyone(frame);
+        }
+
+        let (missing, to_client) = match RawFrame::split(&frame) {
+            Ok(raw_frame) if raw_frame.command == Command::Nack => {
+                let asked_for = raw_frame
+                    .value::<NackArguments>()
+                    .ok()
+                    .and_then(|nack| link.sent.get(nack.frame));
+                return FromDevice {
+                    to_device: asked_for.map(str::to_owned).into_iter().collect(),
+                    to_client: None,
+                };
+            }
+            Ok(raw_frame) => {
+                let missing = raw_frame
+                    .metadata
+                    .frame
+                    .map(|number| link.received.receive(number))
+                    .unwrap_or_default();
+                // The clients never see the bridge's numbers, nor each other
+                let untagged = edit_metadata(&frame, |metadata| {
+                    metadata.frame = None;
+                    metadata.client = None;
+                })
+                .unwrap_or(frame);
+                (missing, Some((raw_frame.metadata.client, untagged)))
+            }
+            Err(ClientError::ChecksumMismatch { .. }) => {
+                let missing = unchecked_frame_number(&frame)
+                    .map(|number| link.received.corrupted(number))
+                    .unwrap_or_default();
+                // Passed on for the checksum error, unless it's asked for
+                // again
+                let to_client = missing.is_empty().then_some((None, frame));
+                (missing, to_client)
+            }
+            Err(_) => (vec![], Some((None, frame))),
+        };
+
+        let to_device = missing
+            .into_iter()
+            .filter_map(|number| {
+                nack_frame(LinkEnd::Client, number)
+                    .map_err(|e| error!("Failed to ask for frame {number} again: {e}"))
+                    .ok()
+            })
+            .collect();
+        FromDevice {
+            to_device,
+            to_client,
+        }
+    }
+
+    /// Send a frame to a client, or every client with `None`
+    ///
+    /// Clients that can't be written to any more have disconnected, and are
+    /// forgotten; only failing to write to the serial port is an error.
+    pub fn send(&mut self, client: Option<ClientId>, frame: &str) -> Result<(), Error> {
+        let mut serial_failed = None;
+        self.connections.retain_mut(|(id, transport)| {
+            if client.is_some_and(|client| client != *id) {
+                return true;
+            }
+            match transport.write_line(frame) {
+                Ok(()) => true,
+                Err(e) if *id == SERIAL_CLIENT => {
+                    serial_failed = Some(e);
+                    true
+                }
+                Err(e) => {
+                    info!(client = id, "Client {id} disconnected: {e}");
+                    false
+                }
+            }
+        });
+        if let Some(client) = client {
+            if !self.connections.iter().any(|(id, _)| *id == client) {
+                debug!(client, "Client {client} is gone, dropping what's for it");
+            }
+        }
+
+        match serial_failed {
+            Some(e) => Err(e.into()),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+/*!
+ * Splitting frames into numbered chunks to send to the device, and putting
+ * the chunks it sends back together, so frames of any length get through
+ * whole however the values carrying them are split up or interleaved
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{gatt::RX_CHARACTERISTIC_SIZE, types::BluetoothError};
+use std::collections::VecDeque;
+use tracing::warn;
+
+// Constants
+/// Starts every chunk, and is never in a frame
+pub const CHUNK_START: u8 = 0x01;
+/// Ends a chunk with more of the frame after it (ASCII ETB)
+pub const CHUNK_MORE: u8 = 0x17;
+/// Ends the last chunk of a frame (ASCII EOT)
+pub const CHUNK_END: u8 = 0x04;
+/// The start, the frame's sequence, the chunk's index, and the payload's
+/// length
+pub const CHUNK_HEADER_SIZE: usize = 4;
+/// The most of a frame in one chunk, so a whole chunk fits in one Rx value
+pub const MAX_CHUNK_PAYLOAD: usize = RX_CHARACTERISTIC_SIZE - CHUNK_HEADER_SIZE - 1;
+/// How many frames can be partly put together at once before the oldest is
+/// given up on
+pub const MAX_PARTIAL_FRAMES: usize = 4;
+
+/***** Sending *****/
+
+/// Splits frames into chunks, each frame numbered after the last
+///
+/// A chunk is [`CHUNK_START`], the frame's sequence, the chunk's index in the
+/// frame, and the payload's length (a byte each), then the payload, then
+/// [`CHUNK_MORE`] or [`CHUNK_END`] for the last chunk of the frame.
+#[derive(Default)]
+pub struct Fragmenter {
+    sequence: u8,
+}
+impl Fragmenter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The bytes to send a frame as, every chunk of it one after another
+    pub fn send_frame(&mut self, frame: &[u8]) -> Result<Vec<u8>, BluetoothError> {
+        let chunks = frame.chunks(MAX_CHUNK_PAYLOAD).collect::<Vec<_>>();
+        if chunks.len() > usize::from(u8::MAX) + 1 {
+            return Err(BluetoothError::FrameTooLong(frame.len()));
+        }
+
+        let sequence = self.sequence;
+        self.sequence = self.sequence.wrapping_add(1);
+        let mut bytes = Vec::with_capacity(frame.len() + chunks.len() * (CHUNK_HEADER_SIZE + 1));
+        for (idx, payload) in chunks.iter().enumerate() {
+            // Safety: checked above, and limited by the most in a chunk
+            bytes.extend([CHUNK_START, sequence, idx as u8, payload.len() as u8]);
+            bytes.extend_from_slice(payload);
+            bytes.push(match idx + 1 == chunks.len() {
+                true => CHUNK_END,
+                false => CHUNK_MORE,
+            });
+        }
+        Ok(bytes)
+    }
+}
+
+/***** Receiving *****/
+
+/// A frame with only some of its chunks in so far
+struct PartialFrame {
+    sequence: u8,
+    next_idx: u8,
+    payload: Vec<u8>,
+}
+
+/// Puts chunks (see [`Fragmenter`]) back together into frames, even with
+/// chunks of different frames in between each other
+///
+/// Anything between chunks (e.g. the zeros values are padded out with) is
+/// skipped, and a frame missing a chunk is given up on rather than passed on
+/// broken. A chunk that turns out not to be one (too long, or without an
+/// end) is skipped from its start only, as the next chunk could start inside
+/// of it. The same chunk twice in a row is only taken once.
+#[derive(Default)]
+pub struct Reassembler {
+    /// The chunk being read, from its start
+    chunk: Vec<u8>,
+    /// Oldest first
+    partial_frames: VecDeque<PartialFrame>,
+    frames: VecDeque<Vec<u8>>,
+    /// The last chunk taken, as a value read twice when polling is the same
+    /// chunk twice. Only the very same bytes are, so the first frame from a
+    /// device that restarted its numbering isn't taken for one.
+    last_chunk: Option<Vec<u8>>,
+}
+impl Reassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_bytes(&mut self, bytes: &[u8]) {
+        let mut bytes = bytes.iter().copied().collect::<VecDeque<_>>();
+        while let Some(byte) = bytes.pop_front() {
+            if self.chunk.is_empty() && byte != CHUNK_START {
+                continue;
+            }
+            self.chunk.push(byte);
+            let Some(&length) = self.chunk.get(CHUNK_HEADER_SIZE - 1) else {
+                continue;
+            };
+            let length = usize::from(length);
+            if length == 0 || length > MAX_CHUNK_PAYLOAD {
+                warn!("Skipping a chunk {length} bytes long");
+                self.resynchronize(&mut bytes);
+                continue;
+            }
+            if self.chunk.len() < CHUNK_HEADER_SIZE + length + 1 {
+                continue;
+            }
+
+            let last = match self.chunk[CHUNK_HEADER_SIZE + length] {
+                CHUNK_MORE => false,
+                CHUNK_END => true,
+                _ => {
+                    warn!("Skipping a chunk without an end");
+                    self.resynchronize(&mut bytes);
+                    continue;
+                }
+            };
+            let chunk = std::mem::take(&mut self.chunk);
+            if self.last_chunk.as_ref() == Some(&chunk) {
+                continue;
+            }
+            self.take_chunk(
+                chunk[1],
+                chunk[2],
+                &chunk[CHUNK_HEADER_SIZE..CHUNK_HEADER_SIZE + length],
+                last,
+            );
+            self.last_chunk = Some(chunk);
+        }
+    }
+
+    /// The next frame put back together, if there is one
+    pub fn recv_frame(&mut self) -> Option<Vec<u8>> {
+        self.frames.pop_front()
+    }
+
+    /// Give up on the chunk being read, going back over what was read of it
+    /// after its start for where the next chunk starts
+    fn resynchronize(&mut self, bytes: &mut VecDeque<u8>) {
+        let chunk = std::mem::take(&mut self.chunk);
+        for &byte in chunk[1..].iter().rev() {
+            bytes.push_front(byte);
+        }
+    }
+
+    /// Add a whole chunk to its frame
+    fn take_chunk(&mut self, sequence: u8, idx: u8, payload: &[u8], last: bool) {
+        let existing = self
+            .partial_frames
+            .iter()
+            .position(|partial_frame| partial_frame.sequence == sequence);
+        let mut partial_frame = match (existing, idx) {
+            // Anything left over from the last frame with the sequence is stale
+            (existing, 0) => {
+                if let Some(existing) = existing {
+                    self.partial_frames.remove(existing);
+                    self.drop_frame(sequence);
+                }
+                PartialFrame {
+                    sequence,
+                    next_idx: 0,
+                    payload: vec![],
+                }
+            }
+            (Some(existing), idx) if self.partial_frames[existing].next_idx == idx => {
+                // Safety: just found
+                self.partial_frames.remove(existing).unwrap()
+            }
+            (Some(existing), _) => {
+                self.partial_frames.remove(existing);
+                self.drop_frame(sequence);
+                return;
+            }
+            // The start of it never came in
+            (None, _) => return,
+        };
+
+        partial_frame.payload.extend_from_slice(payload);
+        partial_frame.next_idx = partial_frame.next_idx.wrapping_add(1);
+        if last {
+            self.frames.push_back(partial_frame.payload);
+            return;
+        }
+        self.partial_frames.push_back(partial_frame);
+        if self.partial_frames.len() > MAX_PARTIAL_FRAMES {
+            if let Some(oldest) = self.partial_frames.pop_front() {
+                self.drop_frame(oldest.sequence);
+            }
+        }
+    }
+
+    fn drop_frame(&self, sequence: u8) {
+        warn!(sequence, "Giving up on a frame missing a chunk");
+    }
+}
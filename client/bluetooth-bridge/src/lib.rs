@@ -0,0 +1,19 @@
+/*!
+ * The serial to bluetooth bridge, to run on its own over a serial port or
+ * embedded in the GUI
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+mod bridge;
+pub use bridge::BridgeHandle;
+mod cancellation;
+pub mod clients;
+pub mod fragment;
+pub mod gatt;
+pub mod recording;
+pub mod trusted_devices;
+pub use trusted_devices::TrustedDevices;
+pub mod types;
+pub use types::Error;
@@ -0,0 +1,138 @@
+/*!
+ * Recording every chunk written to and read from the device, and replaying
+ * what it sent, so what happened in the field can be gone over on a desk
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::types::Error::{self, RecordingError};
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str as serde_from_str, to_string as serde_to_string};
+use std::{
+    collections::VecDeque,
+    fs::{read_to_string, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/***** Recording *****/
+
+/// Which characteristic a chunk went over, as seen by the bridge
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Written to the device
+    Tx,
+    /// Read from (or notified by) the device
+    Rx,
+}
+
+/// One line of a recording
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct RecordedChunk {
+    pub direction: Direction,
+    /// Seconds since recording started, which never jumps around like the
+    /// wall-clock time can
+    pub monotonic: f64,
+    /// Seconds since the Unix epoch
+    pub time: f64,
+    /// Exactly what was written or read, padding and all
+    pub bytes: Vec<u8>,
+}
+
+/// Appends every chunk written to and read from the device to a file, one
+/// JSON object per line, like the GUI's wire log one level down
+///
+/// Every line is written straight to the file, so nothing recorded is lost
+/// if the bridge is killed.
+pub struct Recorder {
+    pub path: PathBuf,
+    file: File,
+    started: Instant,
+}
+impl Recorder {
+    /// Start recording to a file, after anything already in it
+    pub fn open(path: PathBuf) -> Result<Self, Error> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| RecordingError(format!("Failed to open {}: {e}", path.display())))?;
+        Ok(Self {
+            path,
+            file,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn record(&mut self, direction: Direction, bytes: &[u8]) -> Result<(), Error> {
+        let chunk = RecordedChunk {
+            direction,
+            monotonic: self.started.elapsed().as_secs_f64(),
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|e| RecordingError(e.to_string()))?
+                .as_secs_f64(),
+            bytes: bytes.to_vec(),
+        };
+        let line = serde_to_string(&chunk).map_err(|e| RecordingError(e.to_string()))?;
+        writeln!(self.file, "{line}")
+            .map_err(|e| RecordingError(format!("Failed to write to {}: {e}", self.path.display())))
+    }
+}
+
+/// Read every chunk of a recording
+pub fn read_recording(path: &Path) -> Result<Vec<RecordedChunk>, Error> {
+    let contents = read_to_string(path)
+        .map_err(|e| RecordingError(format!("Failed to read {}: {e}", path.display())))?;
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            serde_from_str(line).map_err(|e| RecordingError(format!("Line {}: {e}", idx + 1_usize)))
+        })
+        .collect()
+}
+
+/***** Replaying *****/
+
+/// What the device sent in a recording, given back as long after the start
+/// of the replay as it came in after the first recorded chunk
+///
+/// What was written to the device is left out, as whatever's on the serial
+/// side now is what's talking to the bridge.
+pub struct Replay {
+    pub path: PathBuf,
+    rx: VecDeque<(Duration, Vec<u8>)>,
+}
+impl Replay {
+    pub fn read(path: PathBuf) -> Result<Self, Error> {
+        let chunks = read_recording(&path)?;
+        let first = chunks.first().map_or(0.0, |chunk| chunk.monotonic);
+        let rx = chunks
+            .into_iter()
+            .filter(|chunk| chunk.direction == Direction::Rx)
+            .map(|chunk| {
+                (
+                    Duration::from_secs_f64((chunk.monotonic - first).max(0.0)),
+                    chunk.bytes,
+                )
+            })
+            .collect();
+        Ok(Self { path, rx })
+    }
+
+    /// The next chunk the device sent, with how long after the start of the
+    /// replay it's due
+    pub fn next_rx(&mut self) -> Option<(Duration, Vec<u8>)> {
+        self.rx.pop_front()
+    }
+
+    /// How many chunks are still to come
+    pub fn remaining(&self) -> usize {
+        self.rx.len()
+    }
+}
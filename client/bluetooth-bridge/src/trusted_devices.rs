@@ -0,0 +1,91 @@
+/*!
+ * Devices connected to before, kept between runs of the bridge
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::types::{Error, DEFAULT_DEVICE_ADDRESS, TRUSTED_DEVICES_FILE};
+use bluer::Address;
+use std::{
+    fs::{create_dir_all, read_to_string, write},
+    io::ErrorKind,
+    path::PathBuf,
+    str::FromStr,
+};
+use tracing::error;
+
+/***** Trusted devices *****/
+
+/// Devices to connect to without being asked, highest priority first
+///
+/// Saved as one address per line, so the order can be changed by hand. Blank
+/// lines and lines starting with `#` are skipped.
+pub struct TrustedDevices {
+    path: PathBuf,
+    pub addresses: Vec<Address>,
+}
+
+impl TrustedDevices {
+    /// Where they're kept when no other file is given
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|directory| directory.join(TRUSTED_DEVICES_FILE))
+    }
+
+    /// Read the trusted devices, with none if there's no file yet
+    ///
+    /// Lines that aren't addresses are skipped (and lost once a device is
+    /// trusted), as they shouldn't stop the bridge from starting.
+    pub fn load(path: PathBuf) -> Result<Self, Error> {
+        let contents = match read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => Err(e)?,
+        };
+
+        let mut addresses: Vec<Address> = vec![];
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match Address::from_str(line) {
+                Ok(address) if !addresses.contains(&address) => addresses.push(address),
+                Ok(_) => (),
+                Err(e) => error!("Skipping trusted device `{line}`: {e}"),
+            }
+        }
+
+        Ok(Self { path, addresses })
+    }
+
+    /// What to connect to at startup, in order: the trusted devices, or the
+    /// default device before any have been connected to
+    pub fn candidates(&self) -> Vec<Address> {
+        match self.addresses.is_empty() {
+            // Safety: the default is a valid address
+            true => vec![Address::from_str(DEFAULT_DEVICE_ADDRESS).unwrap()],
+            false => self.addresses.clone(),
+        }
+    }
+
+    /// Trust a device that was just connected to, after those already trusted
+    pub fn trust(&mut self, address: Address) -> Result<(), Error> {
+        if self.addresses.contains(&address) {
+            return Ok(());
+        }
+        self.addresses.push(address);
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), Error> {
+        if let Some(directory) = self.path.parent() {
+            create_dir_all(directory)?;
+        }
+        let mut contents = "# Connected to at startup, highest priority first\n".to_owned();
+        for address in self.addresses.iter() {
+            contents.push_str(&format!("{address}\n"));
+        }
+        write(&self.path, contents)?;
+        Ok(())
+    }
+}
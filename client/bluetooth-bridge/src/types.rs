@@ -0,0 +1,139 @@
+/*!
+ * Helper types
+ * Created by sheepy0125 | MIT License | 2022-02-19
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{bridge::WirelessUartDevice, cancellation::CancellationToken, clients::ClientId};
+use bindings::{BluetoothConnectResponse, BluetoothDevice, ClientError};
+use bluer::{Address, Error as BluerError};
+use serialport::Error as SerialPortError;
+use std::io::Error as IoError;
+use thiserror::Error as ThisError;
+
+// Constants
+pub const POLL_DELAY: u64 = 20; // Milliseconds
+pub const SCAN_TIMEOUT: u64 = 5000; // Milliseconds
+/// How often the client is told how connecting is going
+pub const CONNECT_PROGRESS_INTERVAL: u64 = 500; // Milliseconds
+/// How long the device can go without answering anything sent to it before
+/// it's taken to have hung and is reconnected to
+pub const WATCHDOG_TIMEOUT: u64 = 10000; // Milliseconds
+pub const WATCHDOG_INTERVAL: u64 = 1000; // Milliseconds
+/// How many Rx values can be waiting to be written to the serial port
+pub const RX_CHANNEL_CAPACITY: usize = 64;
+/// How many frames read from the serial port (or from the other clients
+/// sharing the device) can be waiting to be handled
+pub const SERIAL_CHANNEL_CAPACITY: usize = 64;
+/// How many clients that connected can be waiting to be read from
+pub const NEW_CLIENT_CHANNEL_CAPACITY: usize = 8;
+pub const TASK_EVENT_CHANNEL_CAPACITY: usize = 8;
+/// The R41Z-EVAL, connected to when no address is given
+pub const DEFAULT_DEVICE_ADDRESS: &str = "00:60:37:E9:0B:6F";
+/// In the config directory, or the working directory if there's none
+pub const TRUSTED_DEVICES_FILE: &str = "chemistry-car-controller/trusted-devices";
+
+/***** Enums *****/
+
+/// Error
+///
+/// All of these have the postfix "Error". I'm considering that O.K. because
+/// in `bridge.rs`, I'm wildcard importing these and it leads to no collisions.
+#[derive(Debug, ThisError)]
+pub enum Error {
+    #[error("A bluetooth error has occurred: {0}")]
+    BluerError(BluerError),
+    #[error("A bluetooth error has occurred: {0}")]
+    BluetoothError(BluetoothError),
+    #[error("A serial error has occurred: {0}")]
+    SerialError(SerialPortError),
+    #[error("An IO error has occurred: {0}")]
+    IoError(IoError),
+    #[error("A transport error has occurred: {0}")]
+    TransportError(ClientError),
+    #[error("Request error: {0}")]
+    RequestError(String),
+    #[error("A task has stopped: {0}")]
+    TaskError(String),
+    #[error("Recording error: {0}")]
+    RecordingError(String),
+}
+
+/// A bluetooth error that has not been propagated through Bluer
+#[derive(Debug, ThisError)]
+pub enum BluetoothError {
+    #[error("The service needed could not be found")]
+    MissingService,
+    #[error("The characteristics needed could not be found")]
+    MissingCharacteristic,
+    #[error("Failed to get an adapter event")]
+    MissingAdapterEvent,
+    #[error("Not connected")]
+    NotConnected,
+    #[error("The device stopped sending notifications")]
+    NotificationsStopped,
+    #[error("A frame {0} bytes long is too long to send in chunks")]
+    FrameTooLong(usize),
+}
+
+impl From<BluerError> for Error {
+    fn from(value: BluerError) -> Self {
+        Self::BluerError(value)
+    }
+}
+impl From<SerialPortError> for Error {
+    fn from(value: SerialPortError) -> Self {
+        Self::SerialError(value)
+    }
+}
+impl From<IoError> for Error {
+    fn from(value: IoError) -> Self {
+        Self::IoError(value)
+    }
+}
+impl From<ClientError> for Error {
+    fn from(value: ClientError) -> Self {
+        Self::TransportError(value)
+    }
+}
+
+/***** Request *****/
+
+/// Who a response from the bridge goes back to: the client that asked, with
+/// the sequence of its request, or every client when nobody asked (e.g.
+/// connecting to the trusted devices at startup)
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ReplyTo {
+    pub client: Option<ClientId>,
+    pub sequence: Option<u32>,
+}
+
+/// A simple and very sparse enum of possible requests
+///
+/// Requests that get a response carry who to send it back to
+pub enum Request {
+    /// To the device with the address, or the default device
+    Connect(Option<Address>, ReplyTo),
+    Disconnect,
+    BluetoothStatus(ReplyTo),
+    Scan(ReplyTo),
+    BluetoothMetrics(ReplyTo),
+}
+
+/***** Task event *****/
+
+/// What a task reports back to the bridge when it's done
+///
+/// Anything to do with a connection carries its cancellation token, so what
+/// comes from a connection that's since been forgotten can be told apart
+pub enum TaskEvent {
+    /// Finished connecting, with the device if it was found
+    Connected(CancellationToken, Result<Option<WirelessUartDevice>, Error>),
+    /// The device stopped sending anything
+    ReceivingStopped(CancellationToken),
+    /// How connecting is going, for whoever asked to connect
+    ConnectProgress(CancellationToken, ReplyTo, BluetoothConnectResponse),
+    /// Finished scanning for whoever asked to
+    Scanned(ReplyTo, Result<Vec<BluetoothDevice>, Error>),
+}
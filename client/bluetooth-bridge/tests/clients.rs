@@ -0,0 +1,229 @@
+/*!
+ * Tests for sharing the device between clients, giving each the responses to
+ * its own requests
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::*;
+use bluetooth_bridge::clients::*;
+use serde::{Deserialize, Serialize};
+
+/***** Helper functions *****/
+
+fn frame<S>(
+    transit_mode: TransitMode,
+    command: Command,
+    value: S,
+    frame: Option<u32>,
+    client: Option<ClientId>,
+) -> String
+where
+    S: Serialize + for<'a> Deserialize<'a>,
+{
+    let transit_type = match transit_mode {
+        TransitMode::ClientToServerRequest => TransitType::Request,
+        _ => TransitType::Response,
+    };
+    Frame::encode_with_checksum(&Event {
+        command,
+        transit_mode,
+        transit_type,
+        value,
+        metadata: MetaData {
+            time: 1000.0,
+            sequence: None,
+            frame,
+            echo: None,
+            client,
+        },
+    })
+    .unwrap()
+}
+
+/// A `PING` from a client, numbered by it if it numbers its own frames
+fn ping(frame_number: Option<u32>) -> String {
+    frame(
+        TransitMode::ClientToServerRequest,
+        Command::Ping,
+        PingArguments { time: 1000.0 },
+        frame_number,
+        None,
+    )
+}
+
+/// The car's response to a `PING`, numbered by the car
+fn pong(frame_number: u32, client: Option<ClientId>) -> String {
+    frame(
+        TransitMode::ServerToClientResponse,
+        Command::Ping,
+        PingResponse { sent_time: 1000.0 },
+        Some(frame_number),
+        client,
+    )
+}
+
+fn metadata(frame: &str) -> MetaData {
+    RawFrame::split(frame).unwrap().metadata
+}
+
+/// Clients sharing the device, without a serial port, and the other ends of
+/// the ones that connected
+fn shared(count: usize) -> (Clients, Vec<LoopbackTransport>) {
+    let mut clients = Clients::new(None);
+    clients.share();
+    let other_ends = (0..count)
+        .map(|_| {
+            let (client, other_end) = LoopbackTransport::pair();
+            clients.add(Box::new(client));
+            other_end
+        })
+        .collect();
+    (clients, other_ends)
+}
+
+/// The frame a `NACK` asks for
+fn nacked(frame: &str) -> u32 {
+    let raw_frame = RawFrame::split(frame).unwrap();
+    assert_eq!(raw_frame.command, Command::Nack);
+    raw_frame.value::<NackArguments>().unwrap().frame
+}
+
+fn read_all(transport: &mut LoopbackTransport) -> Vec<String> {
+    std::iter::from_fn(|| transport.read_line().unwrap()).collect()
+}
+
+/***** Tests *****/
+
+#[test]
+fn frames_pass_through_until_the_device_is_shared() {
+    let (serial, mut serial_end) = LoopbackTransport::pair();
+    let mut clients = Clients::new(Some(Box::new(serial)));
+    assert!(clients.has_serial_port());
+
+    assert_eq!(
+        clients.to_device(SERIAL_CLIENT, ping(Some(3))),
+        Some(ping(Some(3)))
+    );
+    let from_device = clients.from_device(pong(0, None));
+    assert!(from_device.to_device.is_empty());
+    assert_eq!(from_device.to_client, Some((None, pong(0, None))));
+
+    clients.send(None, &pong(0, None)).unwrap();
+    assert_eq!(read_all(&mut serial_end), [pong(0, None)]);
+}
+
+#[test]
+fn requests_are_tagged_with_the_client_that_sent_them() {
+    let (mut clients, _other_ends) = shared(2);
+
+    // Both clients number from 0, so the bridge numbers for them
+    let first = clients.to_device(1, ping(Some(0))).unwrap();
+    let second = clients.to_device(2, ping(Some(0))).unwrap();
+    assert_eq!(
+        (metadata(&first).client, metadata(&first).frame),
+        (Some(1), Some(0))
+    );
+    assert_eq!(
+        (metadata(&second).client, metadata(&second).frame),
+        (Some(2), Some(1))
+    );
+    assert!(RawFrame::split(&first).is_ok());
+
+    // Clients that don't number their own frames aren't numbered for
+    let unnumbered = clients.to_device(1, ping(None)).unwrap();
+    assert_eq!(
+        (metadata(&unnumbered).client, metadata(&unnumbered).frame),
+        (Some(1), None)
+    );
+
+    // Sent as they are, for the car to answer with what's wrong
+    assert_eq!(
+        clients.to_device(1, "?PING$$".to_owned()).as_deref(),
+        Some("?PING$$")
+    );
+    // Only the bridge asks the device for frames again
+    assert_eq!(
+        clients.to_device(2, nack_frame(LinkEnd::Client, 0).unwrap()),
+        None
+    );
+}
+
+#[test]
+fn responses_are_given_to_the_client_that_asked() {
+    let (mut clients, mut other_ends) = shared(2);
+
+    let from_device = clients.from_device(pong(0, Some(2)));
+    assert!(from_device.to_device.is_empty());
+    let (client, response) = from_device.to_client.unwrap();
+    assert_eq!(client, Some(2));
+    // Without the bridge's number or the tag
+    assert_eq!(
+        (metadata(&response).client, metadata(&response).frame),
+        (None, None)
+    );
+    assert!(RawFrame::split(&response).is_ok());
+    clients.send(client, &response).unwrap();
+
+    // Statuses sent during a run aren't for anyone in particular
+    let (client, status) = clients.from_device(pong(1, None)).to_client.unwrap();
+    assert_eq!(client, None);
+    clients.send(client, &status).unwrap();
+
+    assert_eq!(read_all(&mut other_ends[0]), std::slice::from_ref(&status));
+    assert_eq!(read_all(&mut other_ends[1]), [response, status]);
+}
+
+#[test]
+fn frames_missing_from_either_end_are_asked_for_again() {
+    let (mut clients, _other_ends) = shared(1);
+    let sent = clients.to_device(1, ping(Some(0))).unwrap();
+
+    // The device skipped frame 1
+    assert!(clients.from_device(pong(0, None)).to_device.is_empty());
+    let from_device = clients.from_device(pong(2, None));
+    assert_eq!(
+        from_device
+            .to_device
+            .iter()
+            .map(String::as_str)
+            .map(nacked)
+            .collect::<Vec<_>>(),
+        [1]
+    );
+    assert!(from_device.to_client.is_some());
+
+    // The device missed the bridge's frame 0, which isn't for any client
+    let nack = frame(
+        TransitMode::ServerToClientResponse,
+        Command::Nack,
+        NackArguments { frame: 0 },
+        None,
+        None,
+    );
+    let from_device = clients.from_device(nack);
+    assert_eq!(from_device.to_device, [sent]);
+    assert!(from_device.to_client.is_none());
+}
+
+#[test]
+fn disconnected_clients_are_forgotten() {
+    let (serial, serial_end) = LoopbackTransport::pair();
+    let mut clients = Clients::new(Some(Box::new(serial)));
+    clients.share();
+    let (client, client_end) = LoopbackTransport::pair();
+    let client = clients.add(Box::new(client));
+    assert_eq!(clients.count(), 2);
+
+    drop(client_end);
+    clients.send(Some(client), &pong(0, None)).unwrap();
+    assert_eq!(clients.count(), 1);
+    // Responses for it are dropped
+    clients.send(Some(client), &pong(1, None)).unwrap();
+
+    // But not being able to write to the serial port is an error
+    drop(serial_end);
+    assert!(clients.send(None, &pong(2, None)).is_err());
+    assert!(clients.has_serial_port());
+}
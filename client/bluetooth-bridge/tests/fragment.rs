@@ -0,0 +1,178 @@
+/*!
+ * Tests for sending frames to and from the device in numbered chunks
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bluetooth_bridge::fragment::*;
+
+/***** Helper functions *****/
+
+/// A frame long enough to take a few chunks
+fn long_frame(length: usize) -> Vec<u8> {
+    (0..length).map(|idx| b'a' + (idx % 26) as u8).collect()
+}
+
+/// Split what a frame is sent as into its chunks
+fn chunks(bytes: &[u8]) -> Vec<Vec<u8>> {
+    let mut chunks = vec![];
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let length = CHUNK_HEADER_SIZE + usize::from(rest[CHUNK_HEADER_SIZE - 1]) + 1;
+        chunks.push(rest[..length].to_vec());
+        rest = &rest[length..];
+    }
+    chunks
+}
+
+fn received(reassembler: &mut Reassembler) -> Vec<Vec<u8>> {
+    std::iter::from_fn(|| reassembler.recv_frame()).collect()
+}
+
+/***** Tests *****/
+
+#[test]
+fn frames_are_put_back_together() {
+    let mut fragmenter = Fragmenter::new();
+    let mut reassembler = Reassembler::new();
+    let frames = [
+        b"~PING$$".to_vec(),
+        long_frame(MAX_CHUNK_PAYLOAD),
+        long_frame(MAX_CHUNK_PAYLOAD * 3 + 7),
+    ];
+
+    for frame in frames.iter() {
+        let bytes = fragmenter.send_frame(frame).unwrap();
+        // Padded out with zeros, like a value read off of the device
+        reassembler.push_bytes(&bytes);
+        reassembler.push_bytes(&[0; 8]);
+    }
+    assert_eq!(received(&mut reassembler), frames);
+}
+
+#[test]
+fn frames_split_across_reads_are_put_back_together() {
+    let mut fragmenter = Fragmenter::new();
+    let mut reassembler = Reassembler::new();
+    let frame = long_frame(MAX_CHUNK_PAYLOAD * 2 + 1);
+    let mut bytes = fragmenter.send_frame(&frame).unwrap();
+    bytes.extend(fragmenter.send_frame(b"~STOP$$").unwrap());
+
+    for read in bytes.chunks(7) {
+        reassembler.push_bytes(read);
+    }
+    assert_eq!(received(&mut reassembler), [frame, b"~STOP$$".to_vec()]);
+
+    // Chunks of different frames in between each other
+    let first = chunks(&fragmenter.send_frame(&long_frame(300)).unwrap());
+    let second = chunks(&fragmenter.send_frame(b"~PING$$").unwrap());
+    reassembler.push_bytes(&first[0]);
+    reassembler.push_bytes(&second[0]);
+    reassembler.push_bytes(&first[1]);
+    assert_eq!(
+        received(&mut reassembler),
+        [b"~PING$$".to_vec(), long_frame(300)]
+    );
+}
+
+#[test]
+fn the_same_chunk_twice_is_taken_once() {
+    let mut fragmenter = Fragmenter::new();
+    let mut reassembler = Reassembler::new();
+    let frame = long_frame(MAX_CHUNK_PAYLOAD * 2 + 1);
+
+    // Read twice when polling, in the middle of a frame and at its end
+    for chunk in chunks(&fragmenter.send_frame(&frame).unwrap()) {
+        reassembler.push_bytes(&chunk);
+        reassembler.push_bytes(&chunk);
+    }
+    let ping = fragmenter.send_frame(b"~PING$$").unwrap();
+    reassembler.push_bytes(&ping);
+    reassembler.push_bytes(&ping);
+    assert_eq!(received(&mut reassembler), [frame, b"~PING$$".to_vec()]);
+}
+
+#[test]
+fn restarted_devices_are_not_taken_for_duplicates() {
+    let mut reassembler = Reassembler::new();
+    reassembler.push_bytes(&Fragmenter::new().send_frame(b"~PING$$").unwrap());
+    // Numbering from the start again
+    reassembler.push_bytes(&Fragmenter::new().send_frame(b"~STOP$$").unwrap());
+    assert_eq!(
+        received(&mut reassembler),
+        [b"~PING$$".to_vec(), b"~STOP$$".to_vec()]
+    );
+}
+
+#[test]
+fn frames_missing_a_chunk_are_given_up_on() {
+    let mut fragmenter = Fragmenter::new();
+    let mut reassembler = Reassembler::new();
+    let frame = chunks(
+        &fragmenter
+            .send_frame(&long_frame(MAX_CHUNK_PAYLOAD * 3))
+            .unwrap(),
+    );
+
+    reassembler.push_bytes(&frame[0]);
+    reassembler.push_bytes(&frame[2]);
+    reassembler.push_bytes(&fragmenter.send_frame(b"~PING$$").unwrap());
+    assert_eq!(received(&mut reassembler), [b"~PING$$".to_vec()]);
+
+    // Or its start
+    let frame = chunks(
+        &fragmenter
+            .send_frame(&long_frame(MAX_CHUNK_PAYLOAD * 2))
+            .unwrap(),
+    );
+    reassembler.push_bytes(&frame[1]);
+    assert!(received(&mut reassembler).is_empty());
+}
+
+#[test]
+fn chunks_are_found_again_after_ones_that_are_not() {
+    let mut fragmenter = Fragmenter::new();
+    let mut reassembler = Reassembler::new();
+
+    // A stray start with a length that's too long, right before a chunk
+    let mut bytes = vec![CHUNK_START, 0, 0, u8::MAX];
+    bytes.extend(fragmenter.send_frame(b"~PING$$").unwrap());
+    // So the cut off chunk's sequence isn't taken for a start too
+    bytes.extend(fragmenter.send_frame(b"~PING$$").unwrap());
+    // A chunk cut off before its end, with the next chunk where its end was
+    let mut cut_off = fragmenter.send_frame(b"~STATUS$$").unwrap();
+    cut_off.pop();
+    bytes.extend(cut_off);
+    bytes.extend(fragmenter.send_frame(b"~STOP$$").unwrap());
+
+    reassembler.push_bytes(&bytes);
+    assert_eq!(
+        received(&mut reassembler),
+        [
+            b"~PING$$".to_vec(),
+            b"~PING$$".to_vec(),
+            b"~STOP$$".to_vec()
+        ]
+    );
+}
+
+#[test]
+fn sequences_wrap_around() {
+    let mut fragmenter = Fragmenter::new();
+    let mut reassembler = Reassembler::new();
+    let frames = (0..300)
+        .map(|idx| format!("~PING${{\"sent_time\": {idx}}}$$").into_bytes())
+        .collect::<Vec<_>>();
+
+    for frame in frames.iter() {
+        reassembler.push_bytes(&fragmenter.send_frame(frame).unwrap());
+    }
+    assert_eq!(received(&mut reassembler), frames);
+}
+
+#[test]
+fn frames_too_long_to_number_their_chunks_are_errors() {
+    let frame = long_frame(MAX_CHUNK_PAYLOAD * (usize::from(u8::MAX) + 1) + 1);
+    assert!(Fragmenter::new().send_frame(&frame).is_err());
+}
@@ -0,0 +1,103 @@
+/*!
+ * Tests for recording chunks to and from the device and replaying them
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bluetooth_bridge::{fragment::*, recording::*};
+use std::{
+    env::temp_dir,
+    fs::{create_dir_all, remove_dir_all, write},
+    path::PathBuf,
+    thread::sleep,
+    time::Duration,
+};
+
+/***** Helper functions *****/
+
+/// An empty directory for recordings, just for one test
+fn recording_directory(test: &str) -> PathBuf {
+    let directory = temp_dir().join(format!("bridge-recording-{test}-{}", std::process::id()));
+    let _ = remove_dir_all(&directory);
+    create_dir_all(&directory).unwrap();
+    directory
+}
+
+/***** Tests *****/
+
+#[test]
+fn recorded_chunks_are_read_back_in_order() {
+    let path = recording_directory("round-trip").join("chunks.jsonl");
+
+    let mut recorder = Recorder::open(path.clone()).unwrap();
+    recorder
+        .record(Direction::Tx, &[CHUNK_START, 0, 0])
+        .unwrap();
+    recorder
+        .record(Direction::Rx, &[CHUNK_START, 0, 1, 0])
+        .unwrap();
+    drop(recorder);
+    // Recording again adds to the end instead of starting over
+    let mut recorder = Recorder::open(path.clone()).unwrap();
+    recorder.record(Direction::Rx, &[]).unwrap();
+
+    let chunks = read_recording(&path).unwrap();
+    assert_eq!(
+        chunks
+            .iter()
+            .map(|chunk| (chunk.direction, chunk.bytes.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (Direction::Tx, vec![CHUNK_START, 0, 0]),
+            (Direction::Rx, vec![CHUNK_START, 0, 1, 0]),
+            (Direction::Rx, vec![]),
+        ]
+    );
+    assert!(chunks[0].monotonic <= chunks[1].monotonic);
+    assert!(chunks[0].time > 0.0);
+}
+
+#[test]
+fn only_what_the_device_sent_is_replayed_on_time() {
+    let path = recording_directory("replay").join("chunks.jsonl");
+    let mut fragmenter = Fragmenter::new();
+    let ping = fragmenter.send_frame(b"~PING$$").unwrap();
+    let status = fragmenter.send_frame(b"~STATUS$$").unwrap();
+
+    let mut recorder = Recorder::open(path.clone()).unwrap();
+    recorder.record(Direction::Tx, b"?PING$$").unwrap();
+    recorder.record(Direction::Rx, &ping).unwrap();
+    sleep(Duration::from_millis(50));
+    recorder.record(Direction::Rx, &status).unwrap();
+    drop(recorder);
+
+    let mut replay = Replay::read(path).unwrap();
+    assert_eq!(replay.remaining(), 2);
+    let (first_due, first) = replay.next_rx().unwrap();
+    let (second_due, second) = replay.next_rx().unwrap();
+    assert!(replay.next_rx().is_none());
+    assert!(second_due - first_due >= Duration::from_millis(50));
+
+    // Put back together like it was coming from the device
+    let mut reassembler = Reassembler::new();
+    reassembler.push_bytes(&first);
+    reassembler.push_bytes(&second);
+    assert_eq!(reassembler.recv_frame().unwrap(), b"~PING$$");
+    assert_eq!(reassembler.recv_frame().unwrap(), b"~STATUS$$");
+}
+
+#[test]
+fn broken_recordings_are_errors() {
+    let path = recording_directory("broken").join("chunks.jsonl");
+    write(
+        &path,
+        "{\"direction\": \"rx\", \"monotonic\": 0.0, \"time\": 1.0, \"bytes\": [1]}\n\n\
+         {\"direction\": \"sideways\"}\n",
+    )
+    .unwrap();
+
+    let error = read_recording(&path).unwrap_err().to_string();
+    assert!(error.contains("Line 3"), "{error}");
+    assert!(Replay::read(path.with_file_name("missing.jsonl")).is_err());
+}
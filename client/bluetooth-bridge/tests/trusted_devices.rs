@@ -0,0 +1,80 @@
+/*!
+ * Tests for keeping the devices connected to before, and the order they're
+ * connected to in at startup
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bluer::Address;
+use bluetooth_bridge::{types::DEFAULT_DEVICE_ADDRESS, TrustedDevices};
+use std::{
+    env::temp_dir,
+    fs::{read_to_string, remove_dir_all, write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+/***** Helper functions *****/
+
+/// Where to keep the trusted devices, just for one test (not created yet)
+fn devices_path(test: &str) -> PathBuf {
+    let directory = temp_dir().join(format!(
+        "bridge-trusted-devices-{test}-{}",
+        std::process::id()
+    ));
+    let _ = remove_dir_all(&directory);
+    directory.join("trusted-devices")
+}
+
+fn address(address: &str) -> Address {
+    Address::from_str(address).unwrap()
+}
+
+/***** Tests *****/
+
+#[test]
+fn the_default_device_is_tried_before_any_are_trusted() {
+    let devices = TrustedDevices::load(devices_path("default")).unwrap();
+    assert!(devices.addresses.is_empty());
+    assert_eq!(devices.candidates(), vec![address(DEFAULT_DEVICE_ADDRESS)]);
+}
+
+#[test]
+fn trusted_devices_are_tried_in_the_order_they_were_trusted() {
+    let path = devices_path("order");
+    let mut devices = TrustedDevices::load(path.clone()).unwrap();
+    devices.trust(address("11:22:33:44:55:66")).unwrap();
+    devices.trust(address("AA:BB:CC:DD:EE:FF")).unwrap();
+    // Already trusted, so it keeps its place
+    devices.trust(address("11:22:33:44:55:66")).unwrap();
+
+    let devices = TrustedDevices::load(path).unwrap();
+    assert_eq!(
+        devices.candidates(),
+        vec![address("11:22:33:44:55:66"), address("AA:BB:CC:DD:EE:FF")]
+    );
+}
+
+#[test]
+fn hand_edited_files_are_read_in_their_order() {
+    let path = devices_path("hand-edited");
+    let mut devices = TrustedDevices::load(path.clone()).unwrap();
+    devices.trust(address("11:22:33:44:55:66")).unwrap();
+    assert!(read_to_string(&path)
+        .unwrap()
+        .contains("11:22:33:44:55:66\n"));
+
+    // Moved to the top by hand, with some mistakes along the way
+    write(
+        &path,
+        "# Connected to at startup, highest priority first\n\n\
+         AA:BB:CC:DD:EE:FF\n  not an address\n11:22:33:44:55:66\nAA:BB:CC:DD:EE:FF\n",
+    )
+    .unwrap();
+    let devices = TrustedDevices::load(path).unwrap();
+    assert_eq!(
+        devices.candidates(),
+        vec![address("AA:BB:CC:DD:EE:FF"), address("11:22:33:44:55:66")]
+    );
+}
@@ -0,0 +1,59 @@
+/*!
+ * A simulated car that answers the server's protocol, for working on the
+ * clients without the real car
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{ClientError, Transport};
+use std::{
+    thread::sleep,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+pub mod physics;
+pub use physics::*;
+pub mod server;
+pub use server::*;
+
+// Constants
+/// How long to wait between checking for requests
+const SERVE_INTERVAL: Duration = Duration::from_millis(10);
+
+/***** Serving *****/
+
+/// The current Unix time in seconds
+pub fn unix_time() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+/// Answer requests over a serial port (or any other transport) in real time
+/// until it fails (e.g. when the other side is closed)
+pub fn serve(
+    transport: &mut dyn Transport,
+    server: &mut SimulatedServer,
+) -> Result<(), ClientError> {
+    loop {
+        serve_once(transport, server)?;
+        sleep(SERVE_INTERVAL);
+    }
+}
+
+/// Simulate the car up to now and answer whatever requests came in meanwhile
+fn serve_once(
+    transport: &mut dyn Transport,
+    server: &mut SimulatedServer,
+) -> Result<(), ClientError> {
+    let now = unix_time();
+    let mut frames = server.tick(now)?;
+    while let Some(frame) = transport.read_line()? {
+        frames.push(server.handle_frame(&frame, now)?);
+    }
+    for frame in frames {
+        transport.write_line(&frame)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,152 @@
+/*!
+ * Run the simulated car on a serial port, on a new pseudo-terminal, or over TCP
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+use bindings::{ArqTransport, LinkEnd, SerialTransport, TcpTransport, BAUD_RATE};
+use car_simulator::*;
+use serialport::{new as new_serialport, SerialPort};
+use std::{env::args, io::Write, net::TcpListener, process::ExitCode};
+use vserial::PtyPair;
+
+const USAGE: &str = "\
+Usage: car-simulator [options] [serial port]
+
+Options:
+    --tcp <address to listen on, e.g. 0.0.0.0:7878>
+    --acceleration <centimeters per second squared>
+    --top-speed <centimeters per second>
+    --coast-deceleration <centimeters per second squared>
+    --wheel-diameter <centimeters>
+    --magnets <number of magnets>
+    --slip <fraction further the wheel turns than the car goes, e.g. 0.1>";
+
+/// Where the simulated car is reached
+enum Listen {
+    /// A serial port, or a new pseudo-terminal without one
+    Serial(Option<String>),
+    /// Clients connecting over TCP, one after another
+    Tcp(String),
+}
+
+/// Parse the settings and where to listen
+fn parse(mut args: impl Iterator<Item = String>) -> Result<(CarSettings, Listen), String> {
+    let mut settings = CarSettings::default();
+    let mut port = None;
+    let mut tcp = None;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("Expected a value after `{name}`"))
+        };
+        let number = |value: String| {
+            value
+                .parse::<f64>()
+                .ok()
+                .filter(|number| *number > 0.0)
+                .ok_or_else(|| format!("`{value}` isn't a positive number"))
+        };
+        match arg.as_str() {
+            "--acceleration" => settings.acceleration = number(value(&arg)?)?,
+            "--top-speed" => settings.top_speed = number(value(&arg)?)?,
+            "--coast-deceleration" => settings.coast_deceleration = number(value(&arg)?)?,
+            "--wheel-diameter" => settings.wheel_diameter = number(value(&arg)?)?,
+            "--magnets" => {
+                let magnets = value(&arg)?;
+                settings.number_of_magnets = magnets
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|magnets| *magnets > 0)
+                    .ok_or_else(|| format!("`{magnets}` isn't a number of magnets"))?;
+            }
+            "--slip" => settings.slip = number(value(&arg)?)?,
+            "--tcp" => tcp = Some(value(&arg)?),
+            flag if flag.starts_with("--") => Err(format!("Unknown option `{flag}`"))?,
+            _ if port.is_some() => Err(format!("Unexpected argument `{arg}`"))?,
+            _ => port = Some(arg),
+        }
+    }
+    match (tcp, port) {
+        (Some(_), Some(port)) => Err(format!("Unexpected argument `{port}` with `--tcp`")),
+        (Some(address), None) => Ok((settings, Listen::Tcp(address))),
+        (None, port) => Ok((settings, Listen::Serial(port))),
+    }
+}
+
+/// Answer every client that connects, one at a time
+fn serve_tcp(address: &str, settings: CarSettings) -> ExitCode {
+    let listener = match TcpListener::bind(address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Failed to listen on {address}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    println!("Listening on tcp://{address}");
+    for stream in listener.incoming() {
+        let transport = stream
+            .map_err(|e| e.to_string())
+            .and_then(|stream| TcpTransport::new(stream).map_err(|e| e.to_string()))
+            .map(|transport| ArqTransport::new(Box::new(transport), LinkEnd::Car));
+        let mut transport = match transport {
+            Ok(transport) => transport,
+            Err(e) => {
+                eprintln!("Failed to accept a client: {e}");
+                continue;
+            }
+        };
+        // Every client gets a freshly booted car
+        let mut server = SimulatedServer::new(settings, unix_time());
+        if let Err(e) = serve(&mut transport, &mut server) {
+            eprintln!("{e}");
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn main() -> ExitCode {
+    let (settings, port) = match parse(args().skip(1_usize)) {
+        Ok((settings, Listen::Serial(port))) => (settings, port),
+        Ok((settings, Listen::Tcp(address))) => return serve_tcp(&address, settings),
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Without a port, make one for the GUI or CLI to open by its path, whose
+    // slave side is held open until the simulator exits so they can close it
+    // and open it again
+    let (serial, _held_slave) = match port {
+        Some(port) => match new_serialport(&port, BAUD_RATE).open() {
+            Ok(serial) => (serial, None),
+            Err(e) => {
+                eprintln!("Failed to open {port}: {e}");
+                return ExitCode::FAILURE;
+            }
+        },
+        None => {
+            let (master, slave) = match PtyPair::open() {
+                Ok(pty_pair) => pty_pair.into_master(),
+                Err(e) => {
+                    eprintln!("{e}");
+                    return ExitCode::FAILURE;
+                }
+            };
+            // Just the path on its own line so scripts can read it
+            println!("{}", slave.path);
+            let _ = std::io::stdout().flush();
+            (Box::new(master) as Box<dyn SerialPort>, Some(slave))
+        }
+    };
+
+    let mut transport = ArqTransport::new(Box::new(SerialTransport::new(serial)), LinkEnd::Car);
+    let mut server = SimulatedServer::new(settings, unix_time());
+    match serve(&mut transport, &mut server) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
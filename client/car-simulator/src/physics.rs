@@ -0,0 +1,126 @@
+/*!
+ * A simple model of the car driving along a straight line
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use std::f64::consts::PI;
+
+// Constants
+/// Centimeters per second squared
+pub const DEFAULT_ACCELERATION: f64 = 40.0;
+/// Centimeters per second
+pub const DEFAULT_TOP_SPEED: f64 = 60.0;
+/// Centimeters per second squared, slowing down with the motor off
+pub const DEFAULT_COAST_DECELERATION: f64 = 30.0;
+/// Centimeters (2.5 inches), the same as the real car's
+pub const DEFAULT_WHEEL_DIAMETER: f64 = 6.35;
+pub const DEFAULT_NUMBER_OF_MAGNETS: usize = 2;
+/// The wheel turns exactly as far as the car goes
+pub const DEFAULT_SLIP: f64 = 0.0;
+
+/***** Car *****/
+
+/// What the car is made of
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CarSettings {
+    /// Centimeters per second squared, from a standstill
+    pub acceleration: f64,
+    /// Centimeters per second the motor can't push the car past
+    pub top_speed: f64,
+    /// Centimeters per second squared lost to friction with the motor off
+    pub coast_deceleration: f64,
+    /// Centimeters
+    pub wheel_diameter: f64,
+    pub number_of_magnets: usize,
+    /// How much further the wheel turns than the car goes, as a fraction
+    /// (0.1 for a tenth further), so the odometer counts more than it should
+    /// until it's calibrated
+    pub slip: f64,
+}
+impl Default for CarSettings {
+    fn default() -> Self {
+        Self {
+            acceleration: DEFAULT_ACCELERATION,
+            top_speed: DEFAULT_TOP_SPEED,
+            coast_deceleration: DEFAULT_COAST_DECELERATION,
+            wheel_diameter: DEFAULT_WHEEL_DIAMETER,
+            number_of_magnets: DEFAULT_NUMBER_OF_MAGNETS,
+            slip: DEFAULT_SLIP,
+        }
+    }
+}
+impl CarSettings {
+    /// Centimeters the car goes between two magnet hits
+    pub fn distance_per_magnet(&self) -> f64 {
+        self.wheel_diameter * PI / self.number_of_magnets as f64
+    }
+}
+
+/// Which way the motor is driving the car, like the server's relays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Motor {
+    Forward,
+    #[default]
+    Stopped,
+    Backward,
+}
+
+/// The car on a straight line, starting at rest
+#[derive(Debug, Clone, Default)]
+pub struct Car {
+    pub settings: CarSettings,
+    pub motor: Motor,
+    /// Centimeters from where it started, negative behind it
+    pub position: f64,
+    /// Centimeters per second, negative going backward
+    pub velocity: f64,
+    /// Centimeters covered in either direction, which is what the magnets
+    /// count
+    pub travelled: f64,
+}
+impl Car {
+    pub fn new(settings: CarSettings) -> Self {
+        Self {
+            settings,
+            ..Default::default()
+        }
+    }
+
+    /// Move the car along for some seconds, returning how many magnets went
+    /// past the sensor
+    ///
+    /// The motor pushes less the closer the car gets to its top speed, and
+    /// friction stops the car once the motor is off. Steps should be short
+    /// (a few milliseconds) for the magnet hits to be timed well.
+    pub fn step(&mut self, seconds: f64) -> usize {
+        let settings = &self.settings;
+        let push = |direction: f64| {
+            settings.acceleration * (direction - self.velocity / settings.top_speed)
+        };
+        let acceleration = match self.motor {
+            Motor::Forward => push(1.0),
+            Motor::Backward => push(-1.0),
+            Motor::Stopped => -self.velocity.signum() * settings.coast_deceleration,
+        };
+
+        let mut velocity = self.velocity + acceleration * seconds;
+        // Friction stops the car rather than pushing it the other way
+        if self.motor == Motor::Stopped && velocity.signum() != self.velocity.signum() {
+            velocity = 0.0;
+        }
+        let moved = (self.velocity + velocity) / 2.0 * seconds;
+        self.velocity = velocity;
+
+        let magnets_before = self.magnet_hits();
+        self.position += moved;
+        self.travelled += moved.abs();
+        self.magnet_hits() - magnets_before
+    }
+
+    /// How many magnets have gone past the sensor in total
+    pub fn magnet_hits(&self) -> usize {
+        (self.travelled * (1.0 + self.settings.slip) / self.settings.distance_per_magnet()) as usize
+    }
+}
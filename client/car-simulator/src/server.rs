@@ -0,0 +1,626 @@
+/*!
+ * The server's side of the protocol, driving the simulated car through the
+ * same run stages as the real server
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::physics::*;
+use bindings::{
+    strip_checksum, ApproachGains, CalibrateArguments, CalibrateResponse, ClientError, Command,
+    DistanceEstimatorKind, DistanceInformation, Echo, ErrorResponse, Event, ExecutedBreakpoint,
+    Frame, HelloResponse, MetaData, PingArguments, PingResponse, RawFrame, ServerError,
+    StartArguments, StartResponse, StaticStatusResponse, StatusResponse, StatusStage, StopResponse,
+    TransitMode, TransitType, TuneArguments, TuneResponse, UploadPlanArguments, UploadPlanResponse,
+    PROTOCOL_VERSION,
+};
+use serde::{Deserialize, Serialize};
+
+// Constants
+/// Seconds between statuses sent on their own during a run
+pub const STATUS_INTERVAL: f64 = 0.25;
+/// Seconds without a magnet hit before the car is assumed to have stopped
+/// after overshooting
+pub const MAGNET_FREE_STALL: f64 = 3.0;
+/// Seconds to wait between backing up a magnet at a time
+pub const CAUTIOUS_REVERSE_STALL: f64 = 1.0;
+/// Seconds to keep backing up after a magnet hit, as the wheel can wobble
+/// over the same magnet
+pub const BACKWARD_COOLDOWN: f64 = 0.1;
+/// Seconds the car is moved along at a time
+pub const PHYSICS_STEP: f64 = 0.001;
+/// Centimeters before the distance the car is eased in from, when it's been
+/// given gains to
+pub const APPROACH_DISTANCE: f64 = 50.0;
+/// Seconds the motor is turned on for part of while easing in
+pub const APPROACH_WINDOW: f64 = 0.2;
+
+/***** Run *****/
+
+/// Everything about the run going on, like the server's `MutexStartData` and
+/// `RunData`
+struct Run {
+    arguments: StartArguments,
+    started_time: f64,
+    stage: StatusStage,
+    distance: DistanceInformation,
+    speed_limit_violations: usize,
+    next_status_time: f64,
+    magnet_time: f64,
+    /// Seconds between the last two magnet hits
+    magnet_interval: f64,
+    start_cautious_reversing_time: f64,
+    stop_cautious_reversing_time: f64,
+    magnet_hits_cautiously_reversing: usize,
+    /// The breakpoints the run was uploaded with, if it was
+    plan: Option<Vec<ExecutedBreakpoint>>,
+    /// What the car is eased in with, as it was tuned when the run started
+    approach_gains: Option<ApproachGains>,
+    /// Centimeter seconds left to go, built up while easing in
+    approach_integral: f64,
+    /// When the car was last eased in, once it's started to be
+    approach_time: Option<f64>,
+    /// Centimeters the distance was counted with a wheel circumference of,
+    /// as it was calibrated when the run started
+    wheel_circumference: f64,
+}
+impl Run {
+    /// The speed limit the run is keeping to now, changed by any breakpoints
+    /// of its plan it's gone past
+    fn max_velocity(&self) -> Option<f64> {
+        self.plan
+            .iter()
+            .flatten()
+            .rev()
+            .find(|breakpoint| breakpoint.reached.is_some())
+            .map_or(self.arguments.max_velocity, |breakpoint| {
+                breakpoint.max_velocity
+            })
+    }
+}
+
+/***** Simulated server *****/
+
+/// Answers requests the way the server on the car does, with a simulated car
+/// instead of the motor and magnets
+///
+/// Time is given in as Unix time, so the binary can use the clock and tests
+/// can skip ahead.
+pub struct SimulatedServer {
+    pub car: Car,
+    boot_time: f64,
+    /// Up to when the car has been simulated
+    clock: f64,
+    /// The last run, which is kept around once it's over for its status
+    run: Option<Run>,
+    /// What the car is eased in with from the next run on, see
+    /// [`Command::Tune`]
+    approach_gains: Option<ApproachGains>,
+    /// Centimeters the wheel goes around in from the next run on, once it's
+    /// been calibrated with [`Command::Calibrate`]
+    calibrated_wheel_circumference: Option<f64>,
+    /// The sequence of the last request (with the client sharing the bridge
+    /// that sent it, if any) and what was sent back for it, so a retried
+    /// request is answered again without running it twice
+    last_sequence: Option<(Option<u32>, u32)>,
+    last_response: Option<String>,
+}
+impl SimulatedServer {
+    pub fn new(settings: CarSettings, now: f64) -> Self {
+        Self {
+            car: Car::new(settings),
+            boot_time: now,
+            clock: now,
+            run: None,
+            approach_gains: None,
+            calibrated_wheel_circumference: None,
+            last_sequence: None,
+            last_response: None,
+        }
+    }
+
+    /// The estimator the last run was started with, or the default before
+    /// any
+    pub fn distance_estimator(&self) -> DistanceEstimatorKind {
+        self.run
+            .as_ref()
+            .and_then(|run| run.arguments.distance_estimator)
+            .unwrap_or_default()
+    }
+
+    /// Centimeters the wheel is taken to go around in, as calibrated or from
+    /// its diameter before it's been
+    pub fn wheel_circumference(&self) -> f64 {
+        self.calibrated_wheel_circumference
+            .unwrap_or(self.car.settings.wheel_diameter * std::f64::consts::PI)
+    }
+
+    pub fn running(&self) -> bool {
+        self.run
+            .as_ref()
+            .is_some_and(|run| !matches!(run.stage, StatusStage::Stopped | StatusStage::Finalized))
+    }
+
+    /// Answer a request, returning the frame to send back
+    ///
+    /// Anything that can't be answered gets an error response, as the server
+    /// sends, with the same error for each way a frame can be malformed.
+    pub fn handle_frame(&mut self, frame: &str, now: f64) -> Result<String, ClientError> {
+        let raw_frame = match RawFrame::split(frame) {
+            Ok(raw_frame) => raw_frame,
+            Err(e) => {
+                let error = ErrorResponse {
+                    error_variant: malformed_error(frame) as u8,
+                    message: e.to_string(),
+                };
+                return respond(Command::Error, error, now, None);
+            }
+        };
+        let sequence = raw_frame
+            .metadata
+            .sequence
+            .map(|sequence| (raw_frame.metadata.client, sequence));
+
+        // A retry of the last request, whose response must have been lost
+        if let (Some(sequence), Some(last_response)) = (sequence, &self.last_response) {
+            if self.last_sequence == Some(sequence) {
+                return Ok(last_response.clone());
+            }
+        }
+
+        let response = match self.answer(&raw_frame, now) {
+            Ok(response) => response,
+            Err(error) => respond(Command::Error, error, now, Some(&raw_frame.metadata))?,
+        };
+        if sequence.is_some() {
+            self.last_sequence = sequence;
+            self.last_response = Some(response.clone());
+        }
+        Ok(response)
+    }
+
+    /// Run a request's callback, returning the response frame or the error to
+    /// send back instead
+    fn answer(&mut self, raw_frame: &RawFrame, now: f64) -> Result<String, ErrorResponse> {
+        if raw_frame.transit_mode != TransitMode::ClientToServerRequest {
+            Err(error(
+                ServerError::MalformedRequestFailedPrefixParsing,
+                format!("`{}` isn't a request for the car", raw_frame.command),
+            ))?;
+        }
+        let request = Some(&raw_frame.metadata);
+        let arguments_error =
+            |e: ClientError| error(ServerError::MalformedRequestFailedArgumentsParsing, e);
+
+        let response = match raw_frame.command {
+            Command::Ping => {
+                let arguments = raw_frame
+                    .value::<PingArguments>()
+                    .map_err(arguments_error)?;
+                let response = PingResponse {
+                    sent_time: arguments.time,
+                };
+                respond(Command::Ping, response, now, request)
+            }
+            Command::Start => {
+                let arguments = raw_frame
+                    .value::<StartArguments>()
+                    .map_err(arguments_error)?;
+                self.start(arguments, None, now)?;
+                respond(Command::Start, StartResponse, now, request)
+            }
+            Command::UploadPlan => {
+                let arguments = raw_frame
+                    .value::<UploadPlanArguments>()
+                    .map_err(arguments_error)?;
+                arguments
+                    .check()
+                    .map_err(|e| error(ServerError::MalformedRequestFailedArgumentsParsing, e))?;
+                let plan = arguments
+                    .breakpoints
+                    .iter()
+                    .map(|breakpoint| ExecutedBreakpoint {
+                        distance: breakpoint.distance,
+                        max_velocity: breakpoint.max_velocity,
+                        reached: None,
+                    })
+                    .collect();
+                self.start(arguments.start, Some(plan), now)?;
+                respond(Command::UploadPlan, UploadPlanResponse, now, request)
+            }
+            Command::Stop => {
+                self.stop()?;
+                respond(Command::Stop, StopResponse, now, request)
+            }
+            Command::StaticStatus => {
+                // The simulated car only has its magnets to go by, so every
+                // estimator works the distance out like the odometer, as the
+                // server's do without readings from their sensors
+                let response = StaticStatusResponse {
+                    number_of_magnets: self.car.settings.number_of_magnets,
+                    wheel_diameter: self.car.settings.wheel_diameter,
+                    distance_estimator: Some(self.distance_estimator()),
+                    approach_gains: self.approach_gains,
+                    wheel_circumference: self.calibrated_wheel_circumference,
+                };
+                respond(Command::StaticStatus, response, now, request)
+            }
+            Command::Status => respond(Command::Status, self.status(now), now, request),
+            Command::Tune => {
+                let arguments = raw_frame
+                    .value::<TuneArguments>()
+                    .map_err(arguments_error)?;
+                if let Some(approach_gains) = &arguments.approach_gains {
+                    approach_gains.check().map_err(|e| {
+                        error(ServerError::MalformedRequestFailedArgumentsParsing, e)
+                    })?;
+                }
+                self.approach_gains = arguments.approach_gains;
+                let response = TuneResponse {
+                    approach_gains: self.approach_gains,
+                };
+                respond(Command::Tune, response, now, request)
+            }
+            Command::Calibrate => {
+                let arguments = raw_frame
+                    .value::<CalibrateArguments>()
+                    .map_err(arguments_error)?;
+                arguments
+                    .check()
+                    .map_err(|e| error(ServerError::MalformedRequestFailedArgumentsParsing, e))?;
+                let response = self.calibrate(arguments.distance)?;
+                respond(Command::Calibrate, response, now, request)
+            }
+            Command::Hello => {
+                let response = HelloResponse {
+                    protocol_version: PROTOCOL_VERSION,
+                };
+                respond(Command::Hello, response, now, request)
+            }
+            command => Err(error(
+                ServerError::MalformedRequestFailedCommandParsing,
+                format!("`{command}` isn't a command the car knows"),
+            ))?,
+        };
+        response.map_err(|e| error(ServerError::MalformedResponseOtherError, e))
+    }
+
+    fn start(
+        &mut self,
+        arguments: StartArguments,
+        plan: Option<Vec<ExecutedBreakpoint>>,
+        now: f64,
+    ) -> Result<(), ErrorResponse> {
+        if self.running() {
+            Err(error(
+                ServerError::FailedToStartAlreadyStarted,
+                "Already started!",
+            ))?;
+        }
+
+        self.run = Some(Run {
+            arguments,
+            started_time: now,
+            stage: StatusStage::VehementForward,
+            distance: DistanceInformation {
+                distance: 0.0,
+                velocity: 0.0,
+                magnet_hit_counter: 0_usize,
+            },
+            speed_limit_violations: 0_usize,
+            next_status_time: now + STATUS_INTERVAL,
+            magnet_time: now,
+            magnet_interval: f64::INFINITY,
+            start_cautious_reversing_time: now,
+            stop_cautious_reversing_time: now,
+            magnet_hits_cautiously_reversing: 0_usize,
+            plan,
+            approach_gains: self.approach_gains,
+            approach_integral: 0.0,
+            approach_time: None,
+            wheel_circumference: self.wheel_circumference(),
+        });
+        // The run's distance starts from wherever the car was left
+        self.car.travelled = 0.0;
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<(), ErrorResponse> {
+        let Some(run) = &mut self.run else {
+            return Err(error(ServerError::FailedToStopNotStarted, "Not started"));
+        };
+        if !matches!(run.stage, StatusStage::Finalized) {
+            run.stage = StatusStage::Stopped;
+        }
+        self.car.motor = Motor::Stopped;
+        Ok(())
+    }
+
+    /// Scale the wheel circumference the last run was counted with by how
+    /// far the car was measured to have actually gone
+    fn calibrate(&mut self, measured_distance: f64) -> Result<CalibrateResponse, ErrorResponse> {
+        if self.running() {
+            Err(error(
+                ServerError::AnyOtherError,
+                "Can't calibrate during a run",
+            ))?;
+        }
+        let run = self
+            .run
+            .as_ref()
+            .filter(|run| run.distance.distance > 0.0)
+            .ok_or_else(|| {
+                error(
+                    ServerError::AnyOtherError,
+                    "There's no run gone forward to calibrate against",
+                )
+            })?;
+
+        let previous_wheel_circumference = self.wheel_circumference();
+        let wheel_circumference =
+            run.wheel_circumference * measured_distance / run.distance.distance;
+        self.calibrated_wheel_circumference = Some(wheel_circumference);
+        Ok(CalibrateResponse {
+            previous_wheel_circumference,
+            wheel_circumference,
+        })
+    }
+
+    fn status(&self, now: f64) -> StatusResponse {
+        let uptime = (now - self.boot_time).max(0.0) as usize;
+        match &self.run {
+            Some(run) => StatusResponse {
+                running: self.running(),
+                uptime,
+                runtime: (now - run.started_time).max(0.0) as usize,
+                stage: run.stage,
+                distance: run.distance.clone(),
+                speed_limit_violations: run.speed_limit_violations,
+                // Only once the run is over, like the server
+                executed_plan: run.plan.clone().filter(|_| !self.running()),
+                // There's no radio to stop the simulated car with
+                radio_stop: None,
+                // Nor a battery to measure
+                power: None,
+            },
+            None => StatusResponse {
+                running: false,
+                uptime,
+                runtime: 0_usize,
+                stage: StatusStage::Stopped,
+                distance: DistanceInformation {
+                    distance: 0.0,
+                    velocity: 0.0,
+                    magnet_hit_counter: 0_usize,
+                },
+                speed_limit_violations: 0_usize,
+                executed_plan: None,
+                radio_stop: None,
+                power: None,
+            },
+        }
+    }
+
+    /// Simulate the car up to now, returning the statuses sent on their own
+    /// meanwhile
+    pub fn tick(&mut self, now: f64) -> Result<Vec<String>, ClientError> {
+        let mut frames = vec![];
+        while self.clock < now {
+            let step = PHYSICS_STEP.min(now - self.clock);
+            self.clock += step;
+            let magnet_hits = self.car.step(step);
+
+            if !self.running() {
+                continue;
+            }
+            if let Some(status_time) = self.drive(self.clock, magnet_hits) {
+                frames.push(respond(
+                    Command::Status,
+                    self.status(status_time),
+                    status_time,
+                    None,
+                )?);
+            }
+            // Once more at the end of the run
+            if !self.running() {
+                frames.push(respond(
+                    Command::Status,
+                    self.status(self.clock),
+                    self.clock,
+                    None,
+                )?);
+            }
+        }
+        Ok(frames)
+    }
+
+    /// Go through the run stages like the server's start thread, returning
+    /// the time of the status to send if one is due
+    fn drive(&mut self, now: f64, magnet_hits: usize) -> Option<f64> {
+        // Safety: only called while running
+        let run = self.run.as_mut().unwrap();
+        let car = &mut self.car;
+        let distance_per_magnet = run.wheel_circumference / car.settings.number_of_magnets as f64;
+
+        let status_due = now >= run.next_status_time;
+        if status_due {
+            run.next_status_time += STATUS_INTERVAL;
+        }
+
+        // Keep track of distance
+        if magnet_hits > 0 {
+            let backward =
+                matches!(run.stage, StatusStage::CautiousBackward) || car.motor == Motor::Backward;
+            if backward {
+                run.magnet_hits_cautiously_reversing += magnet_hits;
+            }
+            run.distance.magnet_hit_counter += magnet_hits;
+            let multiplier = match backward {
+                true => -1.0,
+                false => 1.0,
+            };
+            run.distance.distance += distance_per_magnet * magnet_hits as f64 * multiplier;
+            run.distance.velocity = run.distance.distance / (now - run.started_time);
+            run.magnet_interval = now - run.magnet_time;
+            run.magnet_time = now;
+        }
+
+        match run.stage {
+            StatusStage::Stopped | StatusStage::Finalized => (),
+            StatusStage::VehementForward => {
+                let runtime = now - run.started_time;
+                for breakpoint in run.plan.iter_mut().flatten() {
+                    if breakpoint.reached.is_none() && run.distance.distance >= breakpoint.distance
+                    {
+                        breakpoint.reached = Some(runtime);
+                    }
+                }
+
+                // The relays can only turn the motor on or off, so throttle
+                // it by cutting power while over the speed limit
+                let seconds_per_hit = run.magnet_interval.max(now - run.magnet_time);
+                let current_velocity = distance_per_magnet / seconds_per_hit;
+                let speeding = run
+                    .max_velocity()
+                    .is_some_and(|max_velocity| current_velocity > max_velocity);
+                // Eased in over the last stretch, by turning the motor on for
+                // only as much of the time as the controller says
+                let remaining = run.arguments.distance - run.distance.distance;
+                let easing_off = match run.approach_gains {
+                    Some(gains) if remaining <= APPROACH_DISTANCE => {
+                        let last_time = run.approach_time.replace(now).unwrap_or(now);
+                        run.approach_integral += remaining * (now - last_time);
+                        let power = (gains.proportional * remaining
+                            + gains.integral * run.approach_integral
+                            - gains.derivative * current_velocity)
+                            .clamp(0.0, 1.0);
+                        now % APPROACH_WINDOW >= power * APPROACH_WINDOW
+                    }
+                    _ => false,
+                };
+                if speeding && car.motor == Motor::Forward {
+                    run.speed_limit_violations += 1;
+                    car.motor = Motor::Stopped;
+                } else if easing_off {
+                    car.motor = Motor::Stopped;
+                } else if !speeding && car.motor != Motor::Forward {
+                    car.motor = Motor::Forward;
+                }
+
+                // Close enough to coast the rest of the way, going by how
+                // quickly the car was measured to slow down if it was given
+                // and it isn't being eased in
+                let fixed_leeway =
+                    car.settings.wheel_diameter / car.settings.number_of_magnets as f64;
+                let forward_leeway = match (run.approach_gains, run.arguments.coast_deceleration) {
+                    (None, Some(coast_deceleration)) if coast_deceleration > 0.0 => {
+                        current_velocity.powi(2) / (2.0 * coast_deceleration)
+                    }
+                    _ => fixed_leeway,
+                };
+                if run.distance.distance >= run.arguments.distance - forward_leeway {
+                    run.stage = StatusStage::StallOvershoot;
+                }
+            }
+            StatusStage::StallOvershoot => {
+                car.motor = Motor::Stopped;
+                // We haven't seen a magnet in a while, so we have stopped
+                if now - run.magnet_time >= MAGNET_FREE_STALL {
+                    run.stage = StatusStage::CautiousBackward;
+                }
+            }
+            StatusStage::CautiousBackward => {
+                match car.motor {
+                    // Back up a magnet at a time
+                    Motor::Backward => {
+                        if run.magnet_hits_cautiously_reversing >= 1
+                            && now - run.start_cautious_reversing_time > BACKWARD_COOLDOWN
+                        {
+                            car.motor = Motor::Stopped;
+                            run.magnet_hits_cautiously_reversing = 0;
+                            run.stop_cautious_reversing_time = now;
+                        }
+                    }
+                    _ => {
+                        if now >= run.stop_cautious_reversing_time + CAUTIOUS_REVERSE_STALL {
+                            run.start_cautious_reversing_time = now;
+                            car.motor = Motor::Backward;
+                        }
+                    }
+                }
+
+                // Exceeded distance
+                if run.distance.distance - distance_per_magnet <= run.arguments.distance {
+                    car.motor = Motor::Stopped;
+                    run.stage = StatusStage::Finalized;
+                }
+            }
+        }
+
+        status_due.then_some(now)
+    }
+}
+
+/***** Helper functions *****/
+
+fn error(variant: ServerError, message: impl ToString) -> ErrorResponse {
+    ErrorResponse {
+        error_variant: variant as u8,
+        message: message.to_string(),
+    }
+}
+
+/// The error the server gives a frame that couldn't be split into its parts,
+/// going through its checks in the same order: the checksum, the prefix, the
+/// separators, the command, then the metadata
+fn malformed_error(frame: &str) -> ServerError {
+    let Ok(frame) = strip_checksum(frame.trim()) else {
+        return ServerError::MalformedRequestOtherError;
+    };
+    let Some(rest) = frame.strip_prefix('?').or_else(|| frame.strip_prefix('~')) else {
+        return ServerError::MalformedRequestFailedPrefixParsing;
+    };
+    let Some((command, rest)) = rest.split_once('$') else {
+        return ServerError::MalformedRequestFailedSeparatorParsing;
+    };
+    if !rest.contains('$') {
+        return ServerError::MalformedRequestFailedSeparatorParsing;
+    }
+    match Command::try_from(command.to_owned()) {
+        Ok(_) => ServerError::MalformedRequestFailedMetadataParsing,
+        Err(_) => ServerError::MalformedRequestFailedCommandParsing,
+    }
+}
+
+/// Encode a response from the car, answering a request (with its sequence and
+/// client, and its time echoed back) if it's not sent on its own
+///
+/// The simulated car answers as soon as it reads a request, so it's taken to
+/// have been read just now.
+fn respond<S>(
+    command: Command,
+    value: S,
+    now: f64,
+    request: Option<&MetaData>,
+) -> Result<String, ClientError>
+where
+    S: Serialize + for<'a> Deserialize<'a>,
+{
+    Frame::encode_with_checksum(&Event {
+        command,
+        transit_mode: TransitMode::ServerToClientResponse,
+        transit_type: TransitType::Response,
+        value,
+        metadata: MetaData {
+            time: now,
+            sequence: request.and_then(|request| request.sequence),
+            frame: None,
+            echo: request.map(|request| Echo {
+                time: request.time,
+                received: now,
+            }),
+            client: request.and_then(|request| request.client),
+        },
+    })
+}
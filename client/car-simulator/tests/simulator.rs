@@ -0,0 +1,486 @@
+/*!
+ * Tests for the simulated car, skipping ahead in time instead of waiting
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{
+    edit_metadata, ApproachGains, BluetoothScanRequest, CalibrateArguments, Command,
+    DistanceEstimatorKind, Echo, Event, Frame, HelloArguments, MetaData, PingArguments, Response,
+    ServerError, StartArguments, StaticStatusArguments, StatusArguments, StatusResponse,
+    StatusStage, StopArguments, TransitMode, TransitType, TuneArguments, UploadPlanArguments,
+    PROTOCOL_VERSION,
+};
+use car_simulator::*;
+use serde::{Deserialize, Serialize};
+
+// Constants
+const BOOT_TIME: f64 = 1000.0;
+
+/***** Helper functions *****/
+
+fn request<S>(command: Command, value: S, sequence: u32) -> String
+where
+    S: Serialize + for<'a> Deserialize<'a>,
+{
+    Frame::encode_with_checksum(&Event {
+        command,
+        transit_mode: TransitMode::from(command),
+        transit_type: TransitType::Request,
+        value,
+        metadata: MetaData {
+            time: BOOT_TIME,
+            sequence: Some(sequence),
+            frame: None,
+            echo: None,
+            client: None,
+        },
+    })
+    .unwrap()
+}
+
+fn start(distance: f64, max_velocity: Option<f64>, sequence: u32) -> String {
+    request(
+        Command::Start,
+        StartArguments::builder()
+            .distance_cm(distance)
+            .max_velocity_cm_s(max_velocity)
+            .build(),
+        sequence,
+    )
+}
+
+fn statuses(frames: &[String]) -> Vec<StatusResponse> {
+    frames
+        .iter()
+        .map(|frame| match Frame::decode(frame).unwrap() {
+            Response::Status(event) => event.value,
+            response => panic!("Expected a status, got {response:?}"),
+        })
+        .collect()
+}
+
+/// Run to a distance, returning the statuses sent on their own
+fn run_to(server: &mut SimulatedServer, distance: f64, max_velocity: Option<f64>) -> Vec<String> {
+    run(server, &start(distance, max_velocity, 0))
+}
+
+/// Run until the run is over, returning the statuses sent on their own
+fn run(server: &mut SimulatedServer, start: &str) -> Vec<String> {
+    server.handle_frame(start, BOOT_TIME).unwrap();
+    let mut frames = vec![];
+    let mut now = BOOT_TIME;
+    while server.running() {
+        now += 0.5;
+        assert!(now < BOOT_TIME + 120.0, "The run never finished");
+        frames.append(&mut server.tick(now).unwrap());
+    }
+    frames
+}
+
+/***** Tests *****/
+
+#[test]
+fn car_speeds_up_and_coasts_to_a_stop() {
+    let settings = CarSettings::default();
+    let mut car = Car::new(settings);
+
+    car.motor = Motor::Forward;
+    let mut magnet_hits = 0;
+    for _ in 0..10_000 {
+        magnet_hits += car.step(0.001);
+    }
+    assert!(car.velocity > 0.0 && car.velocity < settings.top_speed);
+    assert_eq!(magnet_hits, car.magnet_hits());
+    assert_eq!(
+        magnet_hits,
+        (car.position / settings.distance_per_magnet()) as usize
+    );
+
+    car.motor = Motor::Stopped;
+    for _ in 0..10_000 {
+        car.step(0.001);
+    }
+    assert_eq!(car.velocity, 0.0);
+    let stopped_at = car.position;
+    car.step(1.0);
+    assert_eq!(car.position, stopped_at);
+}
+
+#[test]
+fn run_finishes_near_the_target() {
+    let mut server = SimulatedServer::new(CarSettings::default(), BOOT_TIME);
+    let distance_per_magnet = server.car.settings.distance_per_magnet();
+
+    let statuses = statuses(&run_to(&mut server, 300.0, None));
+    assert!(statuses.len() > 20);
+    // Overshot, then backed up to it
+    let furthest = statuses
+        .iter()
+        .map(|status| status.distance.distance)
+        .fold(0.0, f64::max);
+    assert!(furthest > 300.0);
+    let last = statuses.last().unwrap();
+    assert!(!last.running);
+    assert!(matches!(last.stage, StatusStage::Finalized));
+    assert!((last.distance.distance - 300.0).abs() <= distance_per_magnet);
+    assert!(last.distance.magnet_hit_counter >= (furthest / distance_per_magnet) as usize);
+    assert!(statuses[..statuses.len() - 1]
+        .iter()
+        .all(|status| status.running));
+}
+
+#[test]
+fn speed_limit_is_kept_by_cutting_power() {
+    let mut server = SimulatedServer::new(CarSettings::default(), BOOT_TIME);
+
+    let limited = statuses(&run_to(&mut server, 300.0, Some(20.0)));
+    assert!(limited.last().unwrap().speed_limit_violations > 0);
+
+    let mut server = SimulatedServer::new(CarSettings::default(), BOOT_TIME);
+    let unlimited = statuses(&run_to(&mut server, 300.0, None));
+    assert_eq!(unlimited.last().unwrap().speed_limit_violations, 0);
+}
+
+#[test]
+fn power_is_cut_to_coast_to_the_distance() {
+    let furthest = |coast_deceleration| {
+        let mut server = SimulatedServer::new(CarSettings::default(), BOOT_TIME);
+        let start = request(
+            Command::Start,
+            StartArguments::builder()
+                .distance_cm(300.0)
+                .coast_deceleration_cm_s2(coast_deceleration)
+                .build(),
+            0,
+        );
+        statuses(&run(&mut server, &start))
+            .iter()
+            .map(|status| status.distance.distance)
+            .fold(0.0, f64::max)
+    };
+    let distance_per_magnet = CarSettings::default().distance_per_magnet();
+
+    // Cut a fixed leeway before, it coasts well past
+    let leeway = furthest(None);
+    assert!(leeway - 300.0 > 2.0 * distance_per_magnet, "{leeway}");
+    let coasted = furthest(Some(DEFAULT_COAST_DECELERATION));
+    assert!((coasted - 300.0).abs() <= distance_per_magnet, "{coasted}");
+}
+
+#[test]
+fn tuned_gains_ease_the_car_in() {
+    let mut server = SimulatedServer::new(CarSettings::default(), BOOT_TIME);
+    let furthest = |server: &mut SimulatedServer| {
+        let statuses = statuses(&run_to(server, 300.0, None));
+        let last = statuses.last().unwrap();
+        assert!(matches!(last.stage, StatusStage::Finalized));
+        statuses
+            .iter()
+            .map(|status| status.distance.distance)
+            .fold(0.0, f64::max)
+    };
+    let bang_bang = furthest(&mut server);
+
+    let gains = ApproachGains {
+        proportional: 0.03,
+        integral: 0.01,
+        derivative: 0.02,
+    };
+    let frame = server
+        .handle_frame(
+            &request(
+                Command::Tune,
+                TuneArguments {
+                    approach_gains: Some(gains),
+                },
+                1,
+            ),
+            BOOT_TIME,
+        )
+        .unwrap();
+    assert!(matches!(
+        Frame::decode(&frame).unwrap(),
+        Response::Tune(event) if event.value.approach_gains == Some(gains)
+    ));
+    // Overshoots by no more than a magnet, where it coasted well past before
+    let eased = furthest(&mut server);
+    let distance_per_magnet = server.car.settings.distance_per_magnet();
+    assert!(bang_bang - 300.0 > 2.0 * distance_per_magnet, "{bang_bang}");
+    assert!(eased - 300.0 <= distance_per_magnet, "{eased}");
+}
+
+#[test]
+fn calibrating_the_wheel_corrects_for_slip() {
+    let settings = CarSettings {
+        slip: 0.2,
+        ..CarSettings::default()
+    };
+    let mut server = SimulatedServer::new(settings, BOOT_TIME);
+    let nominal = server.wheel_circumference();
+    let calibrate = |server: &mut SimulatedServer, distance, sequence| {
+        let frame = server
+            .handle_frame(
+                &request(
+                    Command::Calibrate,
+                    CalibrateArguments { distance },
+                    sequence,
+                ),
+                BOOT_TIME,
+            )
+            .unwrap();
+        Frame::decode(&frame).unwrap()
+    };
+    assert!(matches!(
+        calibrate(&mut server, 100.0, 1),
+        Response::Error(event) if event.value.error_variant == ServerError::AnyOtherError as u8
+    ));
+
+    // How far the odometer counted over how far the car really went
+    let overcounted = |server: &mut SimulatedServer, from: f64| {
+        let statuses = statuses(&run_to(server, 300.0, None));
+        let counted = statuses.last().unwrap().distance.distance;
+        let gone = server.car.position - from;
+        (counted / gone, gone)
+    };
+    let (slipped, gone) = overcounted(&mut server, 0.0);
+    // Off by up to a magnet or so either way, as only whole ones are counted
+    assert!((slipped - 1.2).abs() < 0.1, "{slipped}");
+    assert!(matches!(
+        calibrate(&mut server, 0.0, 2),
+        Response::Error(event)
+            if event.value.error_variant
+                == ServerError::MalformedRequestFailedArgumentsParsing as u8
+    ));
+    let Response::Calibrate(event) = calibrate(&mut server, gone, 3) else {
+        panic!("Expected the wheel to be calibrated");
+    };
+    assert_eq!(event.value.previous_wheel_circumference, nominal);
+    let expected = nominal / 1.2;
+    assert!(
+        (event.value.wheel_circumference - expected).abs() < expected * 0.1,
+        "{}",
+        event.value.wheel_circumference
+    );
+    // Calibrating against the same run again gets the same circumference
+    let Response::Calibrate(again) = calibrate(&mut server, gone, 4) else {
+        panic!("Expected the wheel to be calibrated");
+    };
+    assert_eq!(
+        again.value.wheel_circumference,
+        event.value.wheel_circumference
+    );
+
+    // Counting what the car really goes from then on
+    let from = server.car.position;
+    let (calibrated, _) = overcounted(&mut server, from);
+    assert!(
+        (calibrated - 1.0).abs() < (slipped - 1.0).abs() / 2.0,
+        "{calibrated}"
+    );
+
+    // Not mid run, without being taken for a failed start
+    server
+        .handle_frame(&start(300.0, None, 5), BOOT_TIME)
+        .unwrap();
+    assert!(matches!(
+        calibrate(&mut server, 100.0, 6),
+        Response::Error(event) if event.value.error_variant == ServerError::AnyOtherError as u8
+    ));
+}
+
+#[test]
+fn uploaded_plans_change_the_speed_limit_at_each_breakpoint() {
+    let upload = |breakpoints: &[(f64, Option<f64>)], sequence| {
+        let arguments = breakpoints.iter().fold(
+            UploadPlanArguments::builder()
+                .start(StartArguments::builder().distance_cm(300.0).build()),
+            |builder, (distance, max_velocity)| builder.breakpoint_cm(*distance, *max_velocity),
+        );
+        request(Command::UploadPlan, arguments.build(), sequence)
+    };
+
+    let mut server = SimulatedServer::new(CarSettings::default(), BOOT_TIME);
+    let statuses = statuses(&run(&mut server, &upload(&[(150.0, Some(20.0))], 0)));
+    let (during, last) = statuses.split_at(statuses.len() - 1);
+    assert!(during.iter().all(|status| status.executed_plan.is_none()));
+    let last = &last[0];
+    assert!(last.speed_limit_violations > 0);
+    // Only limited once it went past the breakpoint
+    let reached = last.executed_plan.as_ref().unwrap()[0].reached.unwrap();
+    assert!(during
+        .iter()
+        .filter(|status| (status.runtime as f64) < reached.floor())
+        .all(|status| status.speed_limit_violations == 0));
+
+    // Never starts a plan that doesn't make sense
+    let mut server = SimulatedServer::new(CarSettings::default(), BOOT_TIME);
+    let frame = server
+        .handle_frame(&upload(&[(200.0, None), (100.0, Some(20.0))], 1), BOOT_TIME)
+        .unwrap();
+    assert!(matches!(
+        Frame::decode(&frame).unwrap(),
+        Response::Error(event)
+            if event.value.error_variant == ServerError::MalformedRequestFailedArgumentsParsing as u8
+    ));
+    assert!(!server.running());
+}
+
+#[test]
+fn the_distance_estimator_of_the_run_is_reported() {
+    let mut server = SimulatedServer::new(CarSettings::default(), BOOT_TIME);
+    let distance_estimator = |server: &mut SimulatedServer, sequence| {
+        let frame = server
+            .handle_frame(
+                &request(Command::StaticStatus, StaticStatusArguments, sequence),
+                BOOT_TIME,
+            )
+            .unwrap();
+        match Frame::decode(&frame).unwrap() {
+            Response::StaticStatus(event) => event.value.distance_estimator,
+            response => panic!("Expected a static status, got {response:?}"),
+        }
+    };
+    assert_eq!(
+        distance_estimator(&mut server, 0),
+        Some(DistanceEstimatorKind::Odometer)
+    );
+
+    let start = request(
+        Command::Start,
+        StartArguments::builder()
+            .distance_cm(100.0)
+            .distance_estimator(Some(DistanceEstimatorKind::Rangefinder))
+            .build(),
+        1,
+    );
+    server.handle_frame(&start, BOOT_TIME).unwrap();
+    assert_eq!(
+        distance_estimator(&mut server, 2),
+        Some(DistanceEstimatorKind::Rangefinder)
+    );
+}
+
+#[test]
+fn requests_are_answered_like_the_server() {
+    let mut server = SimulatedServer::new(CarSettings::default(), BOOT_TIME);
+    let answer = |server: &mut SimulatedServer, frame: &str| {
+        Frame::decode(&server.handle_frame(frame, BOOT_TIME + 5.0).unwrap()).unwrap()
+    };
+    let error_variant = |response: Response| match response {
+        Response::Error(event) => event.value.error_variant,
+        response => panic!("Expected an error, got {response:?}"),
+    };
+
+    let ping = request(Command::Ping, PingArguments { time: 12.5 }, 1);
+    assert!(matches!(
+        answer(&mut server, &ping),
+        Response::Ping(event) if event.value.sent_time == 12.5 && event.metadata.sequence == Some(1)
+    ));
+    // Answered again as it was, like a retry
+    assert_eq!(
+        server.handle_frame(&ping, BOOT_TIME + 9.0).unwrap(),
+        server.handle_frame(&ping, BOOT_TIME + 5.0).unwrap()
+    );
+
+    let hello = HelloArguments {
+        protocol_version: PROTOCOL_VERSION,
+    };
+    assert!(matches!(
+        answer(&mut server, &request(Command::Hello, hello, 10)),
+        Response::Hello(event) if event.value.protocol_version == PROTOCOL_VERSION
+    ));
+
+    assert!(matches!(
+        answer(&mut server, &request(Command::StaticStatus, StaticStatusArguments, 2)),
+        Response::StaticStatus(event)
+            if event.value.number_of_magnets == 2 && event.value.wheel_diameter == 6.35
+    ));
+    assert!(matches!(
+        answer(&mut server, &request(Command::Status, StatusArguments, 3)),
+        Response::Status(event) if !event.value.running && event.value.uptime == 5
+    ));
+    // With the request's time echoed back, taken to be read as it came in
+    assert_eq!(
+        answer(&mut server, &request(Command::Status, StatusArguments, 30))
+            .metadata()
+            .echo,
+        Some(Echo {
+            time: BOOT_TIME,
+            received: BOOT_TIME + 5.0
+        })
+    );
+    // Clients sharing the bridge number their requests on their own, so the
+    // same sequence from another client isn't a retry
+    let from_client = |client| {
+        edit_metadata(
+            &request(Command::Ping, PingArguments { time: 1.0 }, 20),
+            |metadata| metadata.client = Some(client),
+        )
+        .unwrap()
+    };
+    for client in [1, 2] {
+        assert!(matches!(
+            answer(&mut server, &from_client(client)),
+            Response::Ping(event) if event.metadata.client == Some(client)
+        ));
+    }
+    assert_eq!(
+        error_variant(answer(
+            &mut server,
+            &request(Command::Stop, StopArguments, 4)
+        )),
+        ServerError::FailedToStopNotStarted as u8
+    );
+
+    answer(&mut server, &start(100.0, None, 5));
+    assert!(server.running());
+    assert_eq!(
+        error_variant(answer(&mut server, &start(100.0, None, 6))),
+        ServerError::FailedToStartAlreadyStarted as u8
+    );
+    assert!(matches!(
+        answer(&mut server, &request(Command::Stop, StopArguments, 7)),
+        Response::Stop(_)
+    ));
+    assert!(!server.running());
+
+    // Meant for the bridge, which isn't there
+    assert_eq!(
+        error_variant(answer(
+            &mut server,
+            &request(Command::BluetoothScan, BluetoothScanRequest, 8)
+        )),
+        ServerError::MalformedRequestFailedPrefixParsing as u8
+    );
+    // Malformed the same ways as for the server
+    for (frame, expected) in [
+        (
+            "?PING$nonsense",
+            ServerError::MalformedRequestFailedSeparatorParsing,
+        ),
+        (
+            "!PING${}${\"time\": 1.0}",
+            ServerError::MalformedRequestFailedPrefixParsing,
+        ),
+        (
+            "?FLY${}${\"time\": 1.0}",
+            ServerError::MalformedRequestFailedCommandParsing,
+        ),
+        (
+            "?PING${}$nonsense",
+            ServerError::MalformedRequestFailedMetadataParsing,
+        ),
+        (
+            "?PING${}${\"time\": 1.0}*0000",
+            ServerError::MalformedRequestOtherError,
+        ),
+    ] {
+        assert_eq!(
+            error_variant(answer(&mut server, frame)),
+            expected as u8,
+            "{frame}"
+        );
+    }
+}
@@ -0,0 +1,547 @@
+/*!
+ * Command line arguments for the headless client
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::coast::{DEFAULT_COAST_DISTANCE, DEFAULT_COAST_SPEEDS};
+use bindings::{
+    ApproachGains, ClientError, DistanceEstimatorKind, PlanBreakpoint, StartArguments,
+    UploadPlanArguments,
+};
+use std::path::PathBuf;
+
+// Constants
+pub const USAGE: &str = "\
+Usage: client-cli [--port <serial port, tcp://host:port, or unix://path>] [--config <path to the config>] [--json] <command>
+
+Commands:
+    ping
+    start --distance <centimeters> [--reverse-brake] [--max-velocity <centimeters per second>]
+          [--estimator <odometer | fusion | rangefinder>]
+    upload-plan --distance <centimeters> --breakpoint <centimeters>:<centimeters per second | none> ...
+                [--reverse-brake] [--max-velocity <centimeters per second>]
+                [--estimator <odometer | fusion | rangefinder>]
+    stop
+    status [--watch]
+    tune <--kp <gain> [--ki <gain>] [--kd <gain>] | --off>
+    calibrate --distance <centimeters>
+    plan <path to the run plan>
+    coast [--speeds <centimeters per second, ...>] [--distance <centimeters>]
+    bluetooth connect [--address <device address>]
+    bluetooth <disconnect | status | scan | metrics>
+    gateway [--listen <address>]
+    conformance
+    sessions [list | summary [<session>] | dump <session>] [--directory <session log directory>]
+    merge <client run> <server run> <output run>";
+/// Where the gateway listens when not told otherwise
+pub const DEFAULT_GATEWAY_ADDRESS: &str = "0.0.0.0:8080";
+
+/***** Arguments *****/
+
+#[derive(PartialEq, Debug)]
+pub enum BluetoothSubcommand {
+    /// To the device with the address, or the bridge's default
+    Connect {
+        address: Option<String>,
+    },
+    Disconnect,
+    Status,
+    Scan,
+    /// How the bridge's link to the device has been doing
+    Metrics,
+}
+
+/// Looking back on the sessions the GUI logged, see
+/// [`gui::session_log::SessionLogger`]
+#[derive(PartialEq, Debug)]
+pub enum SessionsSubcommand {
+    /// Every session, newest first
+    List,
+    /// The runs of a session (by name or `latest`), or of every session
+    Summary { session: Option<String> },
+    /// Everything logged in a session, as CSV or JSON
+    Dump { session: String },
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Subcommand {
+    Ping,
+    Start {
+        /// Centimeters
+        distance: f64,
+        reverse_brake: bool,
+        /// Centimeters per second
+        max_velocity: Option<f64>,
+        /// The server's default when not given
+        distance_estimator: Option<DistanceEstimatorKind>,
+    },
+    /// A run the car carries out on its own, see
+    /// [`bindings::UploadPlanArguments`]
+    UploadPlan {
+        /// Centimeters
+        distance: f64,
+        reverse_brake: bool,
+        /// Centimeters per second, until the first breakpoint
+        max_velocity: Option<f64>,
+        distance_estimator: Option<DistanceEstimatorKind>,
+        breakpoints: Vec<PlanBreakpoint>,
+    },
+    Stop,
+    /// Set how the car eases in on the distance, or cut power once close
+    /// enough when `None`, see [`bindings::TuneArguments`]
+    Tune {
+        approach_gains: Option<ApproachGains>,
+    },
+    /// Set the wheel circumference from how far the car was measured to have
+    /// really gone on its last run, see [`bindings::CalibrateArguments`]
+    Calibrate {
+        /// Centimeters
+        distance: f64,
+    },
+    /// With `watch`, every status is printed as it comes in until interrupted
+    Status {
+        watch: bool,
+    },
+    Bluetooth(BluetoothSubcommand),
+    /// Serve the car over a WebSocket until interrupted
+    Gateway {
+        listen: String,
+    },
+    /// Run each step of a run plan in turn, see [`crate::plan::Plan`]
+    Plan {
+        path: PathBuf,
+    },
+    /// Measure how the car coasts at each speed, and fit how quickly it
+    /// slows down, see [`crate::coast`]
+    Coast {
+        /// Centimeters per second
+        speeds: Vec<f64>,
+        /// Centimeters to go before cutting power each time
+        distance: f64,
+    },
+    /// Check the car against every rule of the protocol
+    Conformance,
+    /// Doesn't need the car
+    Sessions {
+        /// Falls back to the GUI's data directory
+        directory: Option<PathBuf>,
+        subcommand: SessionsSubcommand,
+    },
+    /// Combine a run recorded by the client with the server's log of it into
+    /// another run, which doesn't need the car either
+    Merge {
+        client: PathBuf,
+        server: PathBuf,
+        output: PathBuf,
+    },
+}
+
+impl Subcommand {
+    /// What's sent to upload a plan, given the car's
+    /// [`gui::config::CarConfig::coast_deceleration`], or `None` if this
+    /// isn't [`Self::UploadPlan`]
+    pub fn upload_plan_arguments(
+        &self,
+        coast_deceleration: Option<f64>,
+    ) -> Option<UploadPlanArguments> {
+        let Self::UploadPlan {
+            distance,
+            reverse_brake,
+            max_velocity,
+            distance_estimator,
+            breakpoints,
+        } = self
+        else {
+            return None;
+        };
+        let start = StartArguments::builder()
+            .distance_cm(*distance)
+            .reverse_brake(*reverse_brake)
+            .max_velocity_cm_s(*max_velocity)
+            .distance_estimator(*distance_estimator)
+            .coast_deceleration_cm_s2(coast_deceleration)
+            .build();
+        Some(
+            breakpoints
+                .iter()
+                .fold(
+                    UploadPlanArguments::builder().start(start),
+                    |builder, breakpoint| {
+                        builder.breakpoint_cm(breakpoint.distance, breakpoint.max_velocity)
+                    },
+                )
+                .build(),
+        )
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Arguments {
+    /// Falls back to the port in the config
+    pub port: Option<String>,
+    pub config: Option<PathBuf>,
+    /// Print responses as JSON, one per line, instead of tables
+    pub json: bool,
+    pub subcommand: Subcommand,
+}
+impl Arguments {
+    /// Parse the arguments after the binary name
+    ///
+    /// `--port`, `--config`, and `--json` can go anywhere, but everything else
+    /// has to belong to the command.
+    pub fn parse<I>(args: I) -> Result<Self, ClientError>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut port = None;
+        let mut config = None;
+        let mut json = false;
+        let mut rest = vec![];
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--port" => port = Some(value(&mut args, &arg)?),
+                "--config" => config = Some(PathBuf::from(value(&mut args, &arg)?)),
+                "--json" => json = true,
+                _ => rest.push(arg),
+            }
+        }
+
+        let mut rest = rest.into_iter();
+        let subcommand = match rest.next().as_deref() {
+            Some("ping") => Subcommand::Ping,
+            Some(command @ ("start" | "upload-plan")) => {
+                let uploading = command == "upload-plan";
+                let mut distance = None;
+                let mut reverse_brake = false;
+                let mut max_velocity = None;
+                let mut distance_estimator = None;
+                let mut breakpoints = vec![];
+                while let Some(arg) = rest.next() {
+                    match arg.as_str() {
+                        "--distance" => distance = Some(positive(&mut rest, &arg)?),
+                        "--reverse-brake" => reverse_brake = true,
+                        "--max-velocity" => max_velocity = Some(positive(&mut rest, &arg)?),
+                        "--estimator" => {
+                            distance_estimator = Some(estimator(&mut rest, &arg)?);
+                        }
+                        "--breakpoint" if uploading => {
+                            breakpoints.push(breakpoint(&mut rest, &arg)?);
+                        }
+                        _ => return Err(unexpected(&arg)),
+                    }
+                }
+                let distance = distance.ok_or_else(|| {
+                    ClientError::Parse(format!("`{command}` needs a `--distance`"))
+                })?;
+
+                match uploading {
+                    false => Subcommand::Start {
+                        distance,
+                        reverse_brake,
+                        max_velocity,
+                        distance_estimator,
+                    },
+                    true => {
+                        if breakpoints.is_empty() {
+                            return Err(ClientError::Parse(
+                                "`upload-plan` needs a `--breakpoint`".to_owned(),
+                            ));
+                        }
+                        let subcommand = Subcommand::UploadPlan {
+                            distance,
+                            reverse_brake,
+                            max_velocity,
+                            distance_estimator,
+                            breakpoints,
+                        };
+                        // Caught here rather than by the car
+                        if let Some(arguments) = subcommand.upload_plan_arguments(None) {
+                            arguments.check().map_err(ClientError::Parse)?;
+                        }
+                        subcommand
+                    }
+                }
+            }
+            Some("stop") => Subcommand::Stop,
+            Some("tune") => {
+                let mut gains = [None; 3];
+                let mut off = false;
+                while let Some(arg) = rest.next() {
+                    match arg.as_str() {
+                        "--kp" => gains[0] = Some(gain(&mut rest, &arg)?),
+                        "--ki" => gains[1] = Some(gain(&mut rest, &arg)?),
+                        "--kd" => gains[2] = Some(gain(&mut rest, &arg)?),
+                        "--off" => off = true,
+                        _ => return Err(unexpected(&arg)),
+                    }
+                }
+                let approach_gains = match (off, gains) {
+                    (true, [None, None, None]) => None,
+                    (true, _) => {
+                        return Err(ClientError::Parse(
+                            "`tune --off` can't be given gains".to_owned(),
+                        ))
+                    }
+                    (false, [None, None, None]) => {
+                        return Err(ClientError::Parse(
+                            "`tune` needs gains or `--off`".to_owned(),
+                        ))
+                    }
+                    // Any gain not given is left out of the controller
+                    (false, [proportional, integral, derivative]) => Some(ApproachGains {
+                        proportional: proportional.unwrap_or_default(),
+                        integral: integral.unwrap_or_default(),
+                        derivative: derivative.unwrap_or_default(),
+                    }),
+                };
+                Subcommand::Tune { approach_gains }
+            }
+            Some("calibrate") => {
+                let mut distance = None;
+                while let Some(arg) = rest.next() {
+                    match arg.as_str() {
+                        "--distance" => distance = Some(positive(&mut rest, &arg)?),
+                        _ => return Err(unexpected(&arg)),
+                    }
+                }
+                let distance = distance.ok_or_else(|| {
+                    ClientError::Parse("`calibrate` needs a `--distance`".to_owned())
+                })?;
+                Subcommand::Calibrate { distance }
+            }
+            Some("status") => {
+                let mut watch = false;
+                for arg in rest.by_ref() {
+                    match arg.as_str() {
+                        "--watch" => watch = true,
+                        _ => return Err(unexpected(&arg)),
+                    }
+                }
+                Subcommand::Status { watch }
+            }
+            Some("bluetooth") => Subcommand::Bluetooth(match rest.next().as_deref() {
+                Some("connect") => {
+                    let mut address = None;
+                    while let Some(arg) = rest.next() {
+                        match arg.as_str() {
+                            "--address" => address = Some(value(&mut rest, &arg)?),
+                            _ => return Err(unexpected(&arg)),
+                        }
+                    }
+                    BluetoothSubcommand::Connect { address }
+                }
+                Some("disconnect") => BluetoothSubcommand::Disconnect,
+                Some("status") => BluetoothSubcommand::Status,
+                Some("scan") => BluetoothSubcommand::Scan,
+                Some("metrics") => BluetoothSubcommand::Metrics,
+                _ => {
+                    return Err(ClientError::Parse(
+                        "`bluetooth` needs `connect`, `disconnect`, `status`, `scan`, or \
+                         `metrics`"
+                            .to_owned(),
+                    ))
+                }
+            }),
+            Some("gateway") => {
+                let mut listen = DEFAULT_GATEWAY_ADDRESS.to_owned();
+                while let Some(arg) = rest.next() {
+                    match arg.as_str() {
+                        "--listen" => listen = value(&mut rest, &arg)?,
+                        _ => return Err(unexpected(&arg)),
+                    }
+                }
+                Subcommand::Gateway { listen }
+            }
+            Some("plan") => Subcommand::Plan {
+                path: PathBuf::from(rest.next().ok_or_else(|| {
+                    ClientError::Parse("`plan` needs the path to a run plan".to_owned())
+                })?),
+            },
+            Some("coast") => {
+                let mut speeds = DEFAULT_COAST_SPEEDS.to_vec();
+                let mut distance = DEFAULT_COAST_DISTANCE;
+                while let Some(arg) = rest.next() {
+                    match arg.as_str() {
+                        "--speeds" => speeds = positives(&mut rest, &arg)?,
+                        "--distance" => distance = positive(&mut rest, &arg)?,
+                        _ => return Err(unexpected(&arg)),
+                    }
+                }
+                Subcommand::Coast { speeds, distance }
+            }
+            Some("conformance") => Subcommand::Conformance,
+            Some("sessions") => {
+                let mut directory = None;
+                let mut words = vec![];
+                while let Some(arg) = rest.next() {
+                    match arg.as_str() {
+                        "--directory" => directory = Some(PathBuf::from(value(&mut rest, &arg)?)),
+                        _ if arg.starts_with("--") => return Err(unexpected(&arg)),
+                        _ => words.push(arg),
+                    }
+                }
+                let mut words = words.into_iter();
+                let subcommand = match words.next().as_deref() {
+                    None | Some("list") => SessionsSubcommand::List,
+                    Some("summary") => SessionsSubcommand::Summary {
+                        session: words.next(),
+                    },
+                    Some("dump") => SessionsSubcommand::Dump {
+                        session: words.next().ok_or_else(|| {
+                            ClientError::Parse("`sessions dump` needs a session".to_owned())
+                        })?,
+                    },
+                    Some(other) => {
+                        return Err(ClientError::Parse(format!(
+                            "`sessions` needs `list`, `summary`, or `dump`, not `{other}`"
+                        )))
+                    }
+                };
+                if let Some(arg) = words.next() {
+                    return Err(unexpected(&arg));
+                }
+                Subcommand::Sessions {
+                    directory,
+                    subcommand,
+                }
+            }
+            Some("merge") => {
+                let mut path = || {
+                    rest.next().map(PathBuf::from).ok_or_else(|| {
+                        ClientError::Parse(
+                            "`merge` needs the client's run, the server's run, and where to \
+                             save them merged"
+                                .to_owned(),
+                        )
+                    })
+                };
+                Subcommand::Merge {
+                    client: path()?,
+                    server: path()?,
+                    output: path()?,
+                }
+            }
+            Some(other) => {
+                return Err(ClientError::Parse(format!("Unknown command `{other}`")));
+            }
+            None => return Err(ClientError::Parse("No command given".to_owned())),
+        };
+        if let Some(arg) = rest.next() {
+            return Err(unexpected(&arg));
+        }
+
+        Ok(Self {
+            port,
+            config,
+            json,
+            subcommand,
+        })
+    }
+}
+
+/***** Helper functions *****/
+
+/// The value after an option
+fn value<I>(args: &mut I, option: &str) -> Result<String, ClientError>
+where
+    I: Iterator<Item = String>,
+{
+    args.next()
+        .ok_or_else(|| ClientError::Parse(format!("`{option}` needs a value")))
+}
+
+/// The number after an option, which has to be over 0
+fn positive<I>(args: &mut I, option: &str) -> Result<f64, ClientError>
+where
+    I: Iterator<Item = String>,
+{
+    let value = value(args, option)?;
+    match value.parse::<f64>() {
+        Ok(number) if number > 0.0 && number.is_finite() => Ok(number),
+        _ => Err(ClientError::Parse(format!(
+            "`{option}` needs a number over 0, not `{value}`"
+        ))),
+    }
+}
+
+/// The comma separated numbers after an option, which all have to be over 0
+fn positives<I>(args: &mut I, option: &str) -> Result<Vec<f64>, ClientError>
+where
+    I: Iterator<Item = String>,
+{
+    let value = value(args, option)?;
+    value
+        .split(',')
+        .map(|number| match number.trim().parse::<f64>() {
+            Ok(number) if number > 0.0 && number.is_finite() => Ok(number),
+            _ => Err(ClientError::Parse(format!(
+                "`{option}` needs numbers over 0 separated by commas, not `{value}`"
+            ))),
+        })
+        .collect()
+}
+
+/// A gain of the approach controller after an option, which can't be under 0
+fn gain<I>(args: &mut I, option: &str) -> Result<f64, ClientError>
+where
+    I: Iterator<Item = String>,
+{
+    let value = value(args, option)?;
+    match value.parse::<f64>() {
+        Ok(number) if number >= 0.0 && number.is_finite() => Ok(number),
+        _ => Err(ClientError::Parse(format!(
+            "`{option}` needs a number of 0 or more, not `{value}`"
+        ))),
+    }
+}
+
+/// A breakpoint of an uploaded plan after an option, as
+/// `<centimeters>:<centimeters per second>`, or `<centimeters>:none` to lift
+/// the speed limit
+fn breakpoint<I>(args: &mut I, option: &str) -> Result<PlanBreakpoint, ClientError>
+where
+    I: Iterator<Item = String>,
+{
+    let value = value(args, option)?;
+    let parsed = value.split_once(':').and_then(|(distance, max_velocity)| {
+        let distance = distance.parse::<f64>().ok()?;
+        let max_velocity = match max_velocity {
+            "none" => None,
+            max_velocity => Some(max_velocity.parse::<f64>().ok()?),
+        };
+        Some(PlanBreakpoint {
+            distance,
+            max_velocity,
+        })
+    });
+    parsed.ok_or_else(|| {
+        ClientError::Parse(format!(
+            "`{option}` needs a distance and a speed limit like `150:20` or `150:none`, not \
+             `{value}`"
+        ))
+    })
+}
+
+/// The distance estimator after an option, by a short name
+fn estimator<I>(args: &mut I, option: &str) -> Result<DistanceEstimatorKind, ClientError>
+where
+    I: Iterator<Item = String>,
+{
+    let value = value(args, option)?;
+    match value.as_str() {
+        "odometer" => Ok(DistanceEstimatorKind::Odometer),
+        "fusion" => Ok(DistanceEstimatorKind::AccelerometerFusion),
+        "rangefinder" => Ok(DistanceEstimatorKind::Rangefinder),
+        _ => Err(ClientError::Parse(format!(
+            "`{option}` needs `odometer`, `fusion`, or `rangefinder`, not `{value}`"
+        ))),
+    }
+}
+
+fn unexpected(arg: &str) -> ClientError {
+    ClientError::Parse(format!("Unexpected argument `{arg}`"))
+}
@@ -0,0 +1,531 @@
+/*!
+ * Running commands against the car without the GUI
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+#[cfg(feature = "web")]
+use crate::gateway::*;
+use crate::{args::*, coast::*, conformance::*, merge::*, output::*, plan::*, sessions::*};
+use bindings::{
+    BluetoothConnectRequest, BluetoothDisconnectRequest, BluetoothMetricsRequest,
+    BluetoothScanRequest, BluetoothStatusRequest, CalibrateArguments, ClientError, Command, Frame,
+    PingArguments, RawFrame, Response, StartArguments, StatusArguments, StatusResponse,
+    StopArguments, TuneArguments,
+};
+use chrono::Local;
+use gui::{clock::DisplayTimezone, events::SerialEventPropagator};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "web")]
+use serde_json::json;
+use std::{
+    io::{stdin, BufRead, Write},
+    path::Path,
+    thread::sleep,
+    time::Duration,
+    time::Instant,
+};
+use tracing::warn;
+
+// Constants
+/// How often to ask for a status when watching and none have come in
+pub const WATCH_INTERVAL_SECONDS: f64 = 1.0;
+/// Seconds on top of twice as long as going the distance at the speed that a
+/// run measuring how the car coasts can take, for speeding up, coasting, and
+/// the server seeing it's stopped
+pub const COAST_GRACE_SECONDS: f64 = 15.0;
+
+/***** Client *****/
+
+/// Sends commands over a serial event propagator and prints the responses
+pub struct Client<W: Write> {
+    pub serial_event_propagator: SerialEventPropagator,
+    /// Seconds between reading from the serial connection
+    pub poll_interval: f64,
+    pub json: bool,
+    pub out: W,
+    /// Centimeters per second squared, passed on with every start, see
+    /// [`gui::config::CarConfig::coast_deceleration`]
+    pub coast_deceleration: Option<f64>,
+    /// Wait for Enter before each run measuring how the car coasts, so it can
+    /// be put back at the start
+    pub guided: bool,
+}
+impl<W: Write> Client<W> {
+    pub fn new(
+        serial_event_propagator: SerialEventPropagator,
+        poll_interval: f64,
+        json: bool,
+        out: W,
+    ) -> Self {
+        Self {
+            serial_event_propagator,
+            poll_interval,
+            json,
+            out,
+            coast_deceleration: None,
+            guided: false,
+        }
+    }
+
+    /// Run a command, printing what comes back
+    pub fn run(&mut self, subcommand: &Subcommand) -> Result<(), ClientError> {
+        match subcommand {
+            Subcommand::Ping => {
+                let response =
+                    self.request(Command::Ping, PingArguments::builder().time(now()).build())?;
+                self.print(&response)
+            }
+            Subcommand::Start {
+                distance,
+                reverse_brake,
+                max_velocity,
+                distance_estimator,
+            } => {
+                let response = self.request(
+                    Command::Start,
+                    StartArguments::builder()
+                        .distance_cm(*distance)
+                        .reverse_brake(*reverse_brake)
+                        .max_velocity_cm_s(*max_velocity)
+                        .distance_estimator(*distance_estimator)
+                        .coast_deceleration_cm_s2(self.coast_deceleration)
+                        .build(),
+                )?;
+                self.print(&response)
+            }
+            Subcommand::UploadPlan { .. } => {
+                // Safety: it's an upload plan
+                let arguments = subcommand
+                    .upload_plan_arguments(self.coast_deceleration)
+                    .unwrap();
+                let response = self.request(Command::UploadPlan, arguments)?;
+                self.print(&response)
+            }
+            Subcommand::Stop => {
+                let response = self.request(Command::Stop, StopArguments {})?;
+                self.print(&response)
+            }
+            Subcommand::Tune { approach_gains } => {
+                let response = self.request(
+                    Command::Tune,
+                    TuneArguments {
+                        approach_gains: *approach_gains,
+                    },
+                )?;
+                self.print(&response)
+            }
+            Subcommand::Calibrate { distance } => {
+                let response = self.request(
+                    Command::Calibrate,
+                    CalibrateArguments {
+                        distance: *distance,
+                    },
+                )?;
+                self.print(&response)
+            }
+            Subcommand::Status { watch: false } => {
+                let response = self.request(Command::Status, StatusArguments {})?;
+                self.print(&response)
+            }
+            Subcommand::Status { watch: true } => self.watch_statuses(),
+            Subcommand::Bluetooth(BluetoothSubcommand::Connect { address }) => {
+                // The bridge doesn't answer these, so ask how it went after
+                self.serial_event_propagator.write_to_serial(
+                    Command::Connect,
+                    BluetoothConnectRequest {
+                        address: address.clone(),
+                    },
+                )?;
+                let response = self.request(Command::BluetoothStatus, BluetoothStatusRequest {})?;
+                self.print(&response)
+            }
+            Subcommand::Bluetooth(BluetoothSubcommand::Disconnect) => {
+                self.serial_event_propagator
+                    .write_to_serial(Command::Disconnect, BluetoothDisconnectRequest {})?;
+                let response = self.request(Command::BluetoothStatus, BluetoothStatusRequest {})?;
+                self.print(&response)
+            }
+            Subcommand::Bluetooth(BluetoothSubcommand::Status) => {
+                let response = self.request(Command::BluetoothStatus, BluetoothStatusRequest {})?;
+                self.print(&response)
+            }
+            Subcommand::Bluetooth(BluetoothSubcommand::Scan) => {
+                let response = self.request(Command::BluetoothScan, BluetoothScanRequest {})?;
+                self.print(&response)
+            }
+            Subcommand::Bluetooth(BluetoothSubcommand::Metrics) => {
+                let response =
+                    self.request(Command::BluetoothMetrics, BluetoothMetricsRequest {})?;
+                self.print(&response)
+            }
+            Subcommand::Plan { path } => self.run_plan(path),
+            // `main` saves it to the config
+            Subcommand::Coast { speeds, distance } => {
+                let coast_deceleration = self.measure_coast(speeds, *distance)?;
+                self.print_fitted_deceleration(&FittedDeceleration {
+                    coast_deceleration,
+                    saved_to: None,
+                })
+            }
+            Subcommand::Conformance => self.check_conformance(),
+            // `main` runs this without connecting to the car, and in the
+            // configured time zone
+            Subcommand::Sessions {
+                directory,
+                subcommand,
+            } => print_sessions(
+                directory.clone(),
+                subcommand,
+                self.json,
+                &DisplayTimezone::Local,
+                &mut self.out,
+            ),
+            // `main` runs this without connecting to the car too
+            Subcommand::Merge {
+                client,
+                server,
+                output,
+            } => print_merged(client, server, output, self.json, &mut self.out),
+            #[cfg(feature = "web")]
+            Subcommand::Gateway { listen } => self.serve_gateway(listen),
+            #[cfg(not(feature = "web"))]
+            Subcommand::Gateway { .. } => Err(ClientError::Gateway(
+                "Built without the `web` feature, so there's no gateway".to_owned(),
+            )),
+        }
+    }
+
+    /// Send a request and wait for its response, resending it if needed
+    ///
+    /// Anything else that comes in meanwhile is ignored, and an error response
+    /// is returned as an error.
+    pub fn request<S>(&mut self, command: Command, data: S) -> Result<Response, ClientError>
+    where
+        S: Serialize + for<'a> Deserialize<'a> + Sized,
+    {
+        let frame = self
+            .serial_event_propagator
+            .write_to_serial(command, data)?;
+        let sequence = RawFrame::split(&frame)?.metadata.sequence;
+
+        loop {
+            while let Some(frame) = self.serial_event_propagator.read_from_serial()? {
+                let answers_request = RawFrame::split(&frame)
+                    .map(|raw_frame| raw_frame.metadata.sequence == sequence)
+                    .unwrap_or(false);
+                if !answers_request {
+                    continue;
+                }
+                return match Frame::decode(&frame)? {
+                    Response::Error(event) => Err(ClientError::from(&event.value)),
+                    response => Ok(response),
+                };
+            }
+
+            for result in self.serial_event_propagator.retry_pending() {
+                result?;
+            }
+            sleep(Duration::from_secs_f64(self.poll_interval));
+        }
+    }
+
+    /// Print every status as it comes in, asking for one whenever none have
+    /// come in for a while, until interrupted
+    fn watch_statuses(&mut self) -> Result<(), ClientError> {
+        if !self.json {
+            writeln!(self.out, "{}", status_header())
+                .map_err(|e| ClientError::Unknown(e.to_string()))?;
+        }
+
+        let mut last_status_time = None::<Instant>;
+        loop {
+            let recent = last_status_time
+                .is_some_and(|time| time.elapsed().as_secs_f64() < WATCH_INTERVAL_SECONDS);
+            if !recent {
+                let response = self.request(Command::Status, StatusArguments {})?;
+                self.print_status(&response)?;
+                last_status_time = Some(Instant::now());
+            }
+
+            // Statuses are sent on their own during a run
+            while let Some(frame) = self.serial_event_propagator.read_from_serial()? {
+                let response = Frame::decode(&frame)?;
+                if matches!(response, Response::Status(_)) {
+                    self.print_status(&response)?;
+                    last_status_time = Some(Instant::now());
+                }
+            }
+            sleep(Duration::from_secs_f64(self.poll_interval));
+        }
+    }
+
+    /// Run each step of a run plan in turn, printing when each finished and
+    /// where the car was then
+    ///
+    /// If anything goes wrong after the plan started the car, it's stopped,
+    /// so a plan never leaves it running.
+    fn run_plan(&mut self, path: &Path) -> Result<(), ClientError> {
+        let plan = Plan::read(path)?;
+        let started = Instant::now();
+        let mut status = None;
+        let mut started_car = false;
+
+        for planned in plan.steps.iter() {
+            if let Err(e) = self.run_step(planned, &mut status) {
+                if started_car {
+                    if let Err(stop_error) = self.request(Command::Stop, StopArguments {}) {
+                        warn!("Failed to stop the car after the plan failed: {stop_error}");
+                    }
+                }
+                return Err(e);
+            }
+            started_car |= matches!(
+                planned.step,
+                Step::Command(Subcommand::Start { .. } | Subcommand::UploadPlan { .. })
+            );
+
+            let finished = FinishedStep {
+                line: planned.line,
+                step: &planned.text,
+                elapsed: started.elapsed().as_secs_f64(),
+                status: status.as_ref(),
+            };
+            let line = match self.json {
+                true => serde_json::to_string(&finished)?,
+                false => plan_step_human(&finished),
+            };
+            writeln!(self.out, "{line}").map_err(|e| ClientError::Unknown(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Run a step of a run plan, keeping the latest status from the car
+    fn run_step(
+        &mut self,
+        planned: &PlannedStep,
+        status: &mut Option<StatusResponse>,
+    ) -> Result<(), ClientError> {
+        match &planned.step {
+            Step::Command(subcommand) => self.run(subcommand),
+            Step::Wait(seconds) => self
+                .follow_statuses(Some(*seconds), |_, _| false, status)
+                .map(|_| ()),
+            Step::WaitUntil { condition, within } => {
+                match self.follow_statuses(*within, |status, _| condition.holds(status), status)? {
+                    true => Ok(()),
+                    false => Err(ClientError::Plan(format!(
+                        "Line {}: `{condition}` didn't happen within {}s",
+                        planned.line,
+                        within.unwrap_or_default()
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// Follow the statuses the car sends until one meets `until` (given each
+    /// status with the Unix time it was sent at), or for the seconds given,
+    /// asking for one whenever none have come in for a while
+    ///
+    /// Only statuses that came in since starting are checked, and whether one
+    /// met `until` is returned.
+    fn follow_statuses<F>(
+        &mut self,
+        seconds: Option<f64>,
+        mut until: F,
+        latest: &mut Option<StatusResponse>,
+    ) -> Result<bool, ClientError>
+    where
+        F: FnMut(&StatusResponse, f64) -> bool,
+    {
+        let started = Instant::now();
+        let mut last_status_time = None::<Instant>;
+        loop {
+            let recent = last_status_time
+                .is_some_and(|time| time.elapsed().as_secs_f64() < WATCH_INTERVAL_SECONDS);
+            if !recent {
+                last_status_time = Some(Instant::now());
+                if let Response::Status(event) =
+                    self.request(Command::Status, StatusArguments {})?
+                {
+                    let met = until(&event.value, event.metadata.time);
+                    *latest = Some(event.value);
+                    if met {
+                        return Ok(true);
+                    }
+                }
+            }
+
+            // Statuses are sent on their own during a run
+            while let Some(frame) = self.serial_event_propagator.read_from_serial()? {
+                if let Response::Status(event) = Frame::decode(&frame)? {
+                    let met = until(&event.value, event.metadata.time);
+                    *latest = Some(event.value);
+                    last_status_time = Some(Instant::now());
+                    if met {
+                        return Ok(true);
+                    }
+                }
+            }
+
+            if seconds.is_some_and(|seconds| started.elapsed().as_secs_f64() >= seconds) {
+                return Ok(false);
+            }
+            sleep(Duration::from_secs_f64(self.poll_interval));
+        }
+    }
+
+    /// Run the car at each speed, printing how far it coasted once power was
+    /// cut, and fit how quickly it slows down with power cut to that
+    ///
+    /// When [`Self::guided`], each run waits for Enter first.
+    pub fn measure_coast(&mut self, speeds: &[f64], distance: f64) -> Result<f64, ClientError> {
+        let mut coasts = vec![];
+        for (index, speed) in speeds.iter().enumerate() {
+            if self.guided {
+                eprint!(
+                    "Run {} of {} at {speed}cm/s: put the car at the start with over {distance}cm \
+                     clear ahead, then press Enter",
+                    index + 1,
+                    speeds.len()
+                );
+                stdin()
+                    .lock()
+                    .read_line(&mut String::new())
+                    .map_err(|e| ClientError::Unknown(e.to_string()))?;
+            }
+
+            let coast = self.coast_at(*speed, distance)?;
+            let line = match self.json {
+                true => serde_json::to_string(&coast)?,
+                false => coast_human(&coast),
+            };
+            writeln!(self.out, "{line}").map_err(|e| ClientError::Unknown(e.to_string()))?;
+            coasts.push(coast);
+        }
+
+        fit_deceleration(&coasts).ok_or_else(|| {
+            ClientError::Coast("The car never coasted, so there's nothing to fit".to_owned())
+        })
+    }
+
+    /// Run the car to the distance at a speed, and see how far it coasts
+    ///
+    /// It's stopped once it's done coasting, before the server backs it up,
+    /// and if anything goes wrong.
+    fn coast_at(&mut self, speed: f64, distance: f64) -> Result<Coast, ClientError> {
+        self.request(
+            Command::Start,
+            StartArguments::builder()
+                .distance_cm(distance)
+                .max_velocity_cm_s(Some(speed))
+                .build(),
+        )?;
+        let mut tracker = CoastTracker::new(speed, distance);
+        let mut latest = None;
+        let followed = self.follow_statuses(
+            Some(distance / speed * 2.0 + COAST_GRACE_SECONDS),
+            |status, time| tracker.follow(status, time),
+            &mut latest,
+        );
+        if latest.as_ref().is_none_or(|status| status.running) {
+            if let Err(e) = self.request(Command::Stop, StopArguments {}) {
+                warn!("Failed to stop the car after it coasted: {e}");
+            }
+        }
+        followed?;
+        tracker.coast()
+    }
+
+    pub fn print_fitted_deceleration(
+        &mut self,
+        fitted: &FittedDeceleration,
+    ) -> Result<(), ClientError> {
+        let line = match self.json {
+            true => serde_json::to_string(fitted)?,
+            false => fitted_deceleration_human(fitted),
+        };
+        writeln!(self.out, "{line}").map_err(|e| ClientError::Unknown(e.to_string()))
+    }
+
+    /// Print how the car does on every rule of the protocol, failing if it
+    /// breaks any of them
+    fn check_conformance(&mut self) -> Result<(), ClientError> {
+        let results = check_conformance(&mut self.serial_event_propagator, self.poll_interval)?;
+        for result in results.iter() {
+            let line = match self.json {
+                true => serde_json::to_string(result)?,
+                false => rule_human(result),
+            };
+            writeln!(self.out, "{line}").map_err(|e| ClientError::Unknown(e.to_string()))?;
+        }
+
+        let failed = results
+            .iter()
+            .filter(|result| matches!(result.outcome, Outcome::Fail(_)))
+            .count();
+        if !self.json {
+            writeln!(
+                self.out,
+                "{} of {} protocol rules passed",
+                results
+                    .iter()
+                    .filter(|result| result.outcome == Outcome::Pass)
+                    .count(),
+                results.len()
+            )
+            .map_err(|e| ClientError::Unknown(e.to_string()))?;
+        }
+        match failed {
+            0 => Ok(()),
+            failed => Err(ClientError::Run(format!(
+                "{failed} of {} protocol rules failed",
+                results.len()
+            ))),
+        }
+    }
+
+    /// Forward requests from WebSocket clients to the car until interrupted
+    #[cfg(feature = "web")]
+    fn serve_gateway(&mut self, address: &str) -> Result<(), ClientError> {
+        let mut gateway = Gateway::bind(address)?;
+        let url = format!("ws://{}", gateway.local_addr()?);
+        let line = match self.json {
+            true => json!({ "listening": url }).to_string(),
+            false => format!("Listening on {url}"),
+        };
+        writeln!(self.out, "{line}")
+            .and_then(|()| self.out.flush())
+            .map_err(|e| ClientError::Unknown(e.to_string()))?;
+        loop {
+            gateway.poll(&mut self.serial_event_propagator)?;
+            sleep(Duration::from_secs_f64(self.poll_interval));
+        }
+    }
+
+    /// Print a status as a row of the status table, or as JSON
+    fn print_status(&mut self, response: &Response) -> Result<(), ClientError> {
+        let line = match (self.json, response) {
+            (false, Response::Status(event)) => status_row(&event.value),
+            _ => response_json(response),
+        };
+        writeln!(self.out, "{line}").map_err(|e| ClientError::Unknown(e.to_string()))
+    }
+
+    fn print(&mut self, response: &Response) -> Result<(), ClientError> {
+        let text = match self.json {
+            true => response_json(response),
+            false => response_human(response, now()),
+        };
+        writeln!(self.out, "{text}").map_err(|e| ClientError::Unknown(e.to_string()))
+    }
+}
+
+/***** Helper functions *****/
+
+/// The current Unix time in seconds
+pub(crate) fn now() -> f64 {
+    (Local::now().timestamp_millis() as f64) / 1000.0
+}
@@ -0,0 +1,169 @@
+/*!
+ * Measuring how the car coasts: running it at a few speeds, seeing how far it
+ * goes once power is cut, and fitting how quickly it slows down, for the
+ * server to cut power early enough to coast to the distance
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{ClientError, StatusResponse, StatusStage};
+use serde::Serialize;
+use std::{collections::VecDeque, path::Path};
+
+// Constants
+/// Centimeters per second the car is run at when not told otherwise
+pub const DEFAULT_COAST_SPEEDS: [f64; 3] = [20.0, 35.0, 50.0];
+/// Centimeters the car goes before power is cut when not told otherwise,
+/// enough for it to settle at each speed
+pub const DEFAULT_COAST_DISTANCE: f64 = 200.0;
+/// Seconds of statuses before power is cut that the speed is measured over,
+/// as a status or two apart is only a magnet or two
+pub const VELOCITY_WINDOW: f64 = 1.5;
+
+/***** Coasting *****/
+
+/// How far the car coasted once power was cut
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+pub struct Coast {
+    /// The speed limit it was run at, in centimeters per second
+    pub speed: f64,
+    /// Centimeters per second it was going when power was cut
+    pub velocity: f64,
+    /// Centimeters from where power was cut to where it stopped
+    pub distance: f64,
+}
+
+/// Follows the statuses of a run to see how far the car coasts
+///
+/// The server cuts power as the car reaches the distance (see
+/// [`StatusStage::StallOvershoot`]), and the car has stopped once it moves on
+/// to backing up, or the run is over.
+pub struct CoastTracker {
+    speed: f64,
+    /// Centimeters the run was started with
+    target: f64,
+    /// The Unix times and distances reported before power was cut, over the
+    /// last [`VELOCITY_WINDOW`]
+    before_cut: VecDeque<(f64, f64)>,
+    /// The speed and where power was cut, once it has been
+    cut: Option<(Option<f64>, f64)>,
+    furthest: f64,
+    stopped: bool,
+}
+impl CoastTracker {
+    pub fn new(speed: f64, target: f64) -> Self {
+        Self {
+            speed,
+            target,
+            before_cut: VecDeque::new(),
+            cut: None,
+            furthest: 0.0,
+            stopped: false,
+        }
+    }
+
+    /// Follow a status reported at a Unix time, returning whether the car has
+    /// stopped coasting
+    pub fn follow(&mut self, status: &StatusResponse, time: f64) -> bool {
+        let distance = status.distance.distance;
+        match (status.stage, self.cut) {
+            (StatusStage::VehementForward, None) if status.running => {
+                self.before_cut.push_back((time, distance));
+                while self
+                    .before_cut
+                    .front()
+                    .is_some_and(|(first, _)| time - first > VELOCITY_WINDOW)
+                {
+                    self.before_cut.pop_front();
+                }
+            }
+            (StatusStage::StallOvershoot, cut) if status.running => {
+                if cut.is_none() {
+                    // Somewhere between the last status before and this one,
+                    // close to the distance
+                    let last_before = self
+                        .before_cut
+                        .back()
+                        .map(|(_, distance)| *distance)
+                        .unwrap_or_default();
+                    let cut_at = self.target.clamp(last_before.min(distance), distance);
+                    self.cut = Some((self.velocity(), cut_at));
+                }
+                self.furthest = self.furthest.max(distance);
+            }
+            _ => self.stopped = true,
+        }
+        self.stopped
+    }
+
+    /// How far the car coasted, once it's stopped
+    pub fn coast(&self) -> Result<Coast, ClientError> {
+        let (velocity, cut_at) = match (self.stopped, self.cut) {
+            (true, Some(cut)) => cut,
+            (true, None) => {
+                return Err(ClientError::Coast(format!(
+                    "The run at {}cm/s was over before power was cut",
+                    self.speed
+                )))
+            }
+            (false, _) => {
+                return Err(ClientError::Coast(format!(
+                    "The run at {}cm/s didn't finish coasting in time",
+                    self.speed
+                )))
+            }
+        };
+        let velocity = velocity.ok_or_else(|| {
+            ClientError::Coast(format!(
+                "Not enough statuses came in to measure the speed at {}cm/s",
+                self.speed
+            ))
+        })?;
+        Ok(Coast {
+            speed: self.speed,
+            velocity,
+            distance: (self.furthest - cut_at).max(0.0),
+        })
+    }
+
+    /// Centimeters per second over the statuses before power was cut
+    fn velocity(&self) -> Option<f64> {
+        let (first_time, first_distance) = self.before_cut.front()?;
+        let (last_time, last_distance) = self.before_cut.back()?;
+        let elapsed = last_time - first_time;
+        (elapsed > 0.0).then(|| (last_distance - first_distance) / elapsed)
+    }
+}
+
+/***** Fitting *****/
+
+/// Fit how quickly the car slows down with power cut, in centimeters per
+/// second squared, to how far it coasted from each speed
+///
+/// Slowing down at a constant `a` from `v`, it coasts `v² / 2a`, so `1 / 2a`
+/// is fit to the distances against `v²` by least squares. There's nothing to
+/// fit if it never coasted at all.
+pub fn fit_deceleration(coasts: &[Coast]) -> Option<f64> {
+    let (numerator, denominator) =
+        coasts
+            .iter()
+            .fold((0.0, 0.0), |(numerator, denominator), coast| {
+                let squared = coast.velocity.powi(2);
+                (
+                    numerator + coast.distance * squared,
+                    denominator + squared.powi(2),
+                )
+            });
+    let per_squared_velocity = numerator / denominator;
+    (per_squared_velocity > 0.0 && per_squared_velocity.is_finite())
+        .then(|| 1.0 / (2.0 * per_squared_velocity))
+}
+
+/// The fitted deceleration, and the config it was saved to, for printing
+#[derive(Serialize)]
+pub struct FittedDeceleration<'a> {
+    /// Centimeters per second squared
+    pub coast_deceleration: f64,
+    pub saved_to: Option<&'a Path>,
+}
@@ -0,0 +1,426 @@
+/*!
+ * Checking a car against the protocol with valid and deliberately malformed
+ * frames, so new firmware can be trusted before it's taken to competition
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::client::now;
+use bindings::{
+    crc16, ClientError, Command, ErrorResponse, HelloArguments, HelloResponse, PingArguments,
+    PingResponse, RawFrame, ServerError, StaticStatusArguments, StaticStatusResponse,
+    StatusArguments, StatusResponse, StopArguments, CHECKSUM_SEPARATOR, PROTOCOL_VERSION,
+};
+use gui::events::SerialEventPropagator;
+use serde::{Deserialize, Serialize};
+use std::{thread::sleep, time::Duration, time::Instant};
+
+/***** Report *****/
+
+/// How a rule of the protocol went
+#[derive(Serialize, PartialEq, Debug)]
+#[serde(tag = "outcome", content = "detail", rename_all = "snake_case")]
+pub enum Outcome {
+    Pass,
+    /// What the car did instead
+    Fail(String),
+    /// Why the rule couldn't be checked
+    Skipped(String),
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct RuleResult {
+    pub rule: String,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+}
+
+/***** Conformance *****/
+
+/// Check every rule of the protocol against the car, in order
+///
+/// The car is never started, and is only sent `STOP` when it isn't running.
+/// Requests aren't resent, so each one gets twice the request timeout to be
+/// answered in, with anything answered after the request timeout failing the
+/// rule for timeouts. A serial error cuts the checks short.
+pub fn check_conformance(
+    serial_event_propagator: &mut SerialEventPropagator,
+    poll_interval: f64,
+) -> Result<Vec<RuleResult>, ClientError> {
+    let mut conformance = Conformance {
+        serial_event_propagator,
+        poll_interval,
+        late: vec![],
+        unreadable: vec![],
+        results: vec![],
+    };
+    conformance.check_hello()?;
+    conformance.check_ping()?;
+    conformance.check_static_status()?;
+    let running = conformance.check_status()?;
+    conformance.check_stop(running)?;
+    conformance.check_retry()?;
+    conformance.check_malformed()?;
+    conformance.check_timeouts();
+    Ok(conformance.results)
+}
+
+struct Conformance<'a> {
+    serial_event_propagator: &'a mut SerialEventPropagator,
+    poll_interval: f64,
+    /// Which answers came after the request timeout, and how long they took
+    late: Vec<String>,
+    /// Frames from the car that couldn't be read, and why
+    unreadable: Vec<String>,
+    results: Vec<RuleResult>,
+}
+impl Conformance<'_> {
+    fn check_hello(&mut self) -> Result<(), ClientError> {
+        let sequence = self.send(
+            Command::Hello,
+            HelloArguments {
+                protocol_version: PROTOCOL_VERSION,
+            },
+        )?;
+        let outcome = match self.answer_to("HELLO", sequence)? {
+            Some(raw_frame) if raw_frame.command == Command::Hello => {
+                match raw_frame.value::<HelloResponse>()?.protocol_version {
+                    PROTOCOL_VERSION => Outcome::Pass,
+                    version => Outcome::Fail(format!(
+                        "Answered with version {version}, not {PROTOCOL_VERSION}"
+                    )),
+                }
+            }
+            answer => unexpected(answer)?,
+        };
+        self.record("HELLO is answered with the protocol version", outcome);
+        Ok(())
+    }
+
+    fn check_ping(&mut self) -> Result<(), ClientError> {
+        let time = now();
+        let sequence = self.send(Command::Ping, PingArguments { time })?;
+        let outcome = match self.answer_to("PING", sequence)? {
+            Some(raw_frame) if raw_frame.command == Command::Ping => {
+                match raw_frame.value::<PingResponse>()?.sent_time {
+                    sent_time if sent_time == time => Outcome::Pass,
+                    sent_time => Outcome::Fail(format!("Echoed {sent_time}, not {time}")),
+                }
+            }
+            answer => unexpected(answer)?,
+        };
+        self.record("PING echoes the time it was sent", outcome);
+        Ok(())
+    }
+
+    /// Also checks that answers are numbered, since nothing else sends static
+    /// statuses to be mistaken for the answer
+    fn check_static_status(&mut self) -> Result<(), ClientError> {
+        let sequence = self.send(Command::StaticStatus, StaticStatusArguments)?;
+        let answer = self.answer("STATICSTATUS", |raw_frame| {
+            raw_frame.command == Command::StaticStatus
+                || answers(raw_frame, sequence) && raw_frame.command == Command::Error
+        })?;
+
+        let outcome = match &answer {
+            Some(raw_frame) if raw_frame.metadata.sequence == sequence => Outcome::Pass,
+            Some(raw_frame) => Outcome::Fail(format!(
+                "Answered with sequence {:?}, not {sequence:?}",
+                raw_frame.metadata.sequence
+            )),
+            None => Outcome::Fail("No answer".to_owned()),
+        };
+        self.record("Answers carry the sequence of their request", outcome);
+
+        let outcome = match answer {
+            Some(raw_frame) if raw_frame.command == Command::StaticStatus => {
+                let static_status = raw_frame.value::<StaticStatusResponse>()?;
+                match (
+                    static_status.number_of_magnets,
+                    static_status.wheel_diameter,
+                ) {
+                    (magnets, diameter) if magnets > 0 && diameter > 0.0 => Outcome::Pass,
+                    (magnets, diameter) => {
+                        Outcome::Fail(format!("Gave {magnets} magnets and a {diameter}cm wheel"))
+                    }
+                }
+            }
+            answer => unexpected(answer)?,
+        };
+        self.record(
+            "STATICSTATUS gives the number of magnets and the wheel diameter",
+            outcome,
+        );
+        Ok(())
+    }
+
+    /// Returns whether the car is running, or `None` if that's unknown
+    fn check_status(&mut self) -> Result<Option<bool>, ClientError> {
+        let sequence = self.send(Command::Status, StatusArguments)?;
+        let mut running = None;
+        let outcome = match self.answer_to("STATUS", sequence)? {
+            Some(raw_frame) if raw_frame.command == Command::Status => {
+                running = Some(raw_frame.value::<StatusResponse>()?.running);
+                Outcome::Pass
+            }
+            answer => unexpected(answer)?,
+        };
+        self.record("STATUS is answered with a status", outcome);
+        Ok(running)
+    }
+
+    fn check_stop(&mut self, running: Option<bool>) -> Result<(), ClientError> {
+        let rule = "STOP is refused when the car isn't running";
+        match running {
+            Some(false) => (),
+            Some(true) => {
+                self.record(
+                    rule,
+                    Outcome::Skipped(
+                        "The car is running, and stopping it would cut the run short".to_owned(),
+                    ),
+                );
+                return Ok(());
+            }
+            None => {
+                self.record(
+                    rule,
+                    Outcome::Skipped("Whether the car is running is unknown".to_owned()),
+                );
+                return Ok(());
+            }
+        }
+
+        let sequence = self.send(Command::Stop, StopArguments {})?;
+        let outcome = match self.answer_to("STOP", sequence)? {
+            Some(raw_frame) if raw_frame.command == Command::Stop => {
+                Outcome::Fail("Stopped a car that wasn't running".to_owned())
+            }
+            answer => expect_error(answer, ServerError::FailedToStopNotStarted)?,
+        };
+        self.record(rule, outcome);
+        Ok(())
+    }
+
+    /// Send the same `PING` twice, as if the answer to the first was lost
+    ///
+    /// The answer is only sent again as it was the first time, so the client's
+    /// [`bindings::ArqTransport`] throws it out as a frame that already came
+    /// in; no second answer at all passes too.
+    fn check_retry(&mut self) -> Result<(), ClientError> {
+        let frame = self
+            .serial_event_propagator
+            .write_to_serial(Command::Ping, PingArguments { time: now() })?;
+        let sequence = RawFrame::split(&frame)?.metadata.sequence;
+        let rule = "A retried request isn't carried out again";
+        let Some(first) = self.answer_to("PING", sequence)? else {
+            self.record(
+                rule,
+                Outcome::Skipped("The first PING wasn't answered".to_owned()),
+            );
+            return Ok(());
+        };
+
+        self.send_raw(&frame)?;
+        let outcome = match self.answer_to("the retried PING", sequence)? {
+            None => Outcome::Pass,
+            Some(second)
+                if second.command == first.command
+                    && second.data == first.data
+                    && second.metadata.time == first.metadata.time =>
+            {
+                Outcome::Pass
+            }
+            Some(second) => Outcome::Fail(format!(
+                "Answered again with `{}` (at {}), not `{}` (at {})",
+                second.data, second.metadata.time, first.data, first.metadata.time
+            )),
+        };
+        self.record(rule, outcome);
+        Ok(())
+    }
+
+    /// Send frames wrong in each way the car tells apart, checking each is
+    /// answered with its own error
+    fn check_malformed(&mut self) -> Result<(), ClientError> {
+        let metadata = format!("{{\"time\": {}}}", now());
+        let ping = format!("?PING${{\"time\": {}}}${metadata}", now());
+        let corrupted = format!("{ping}{CHECKSUM_SEPARATOR}{:04X}", !crc16(ping.as_bytes()));
+
+        use ServerError::*;
+        for (description, frame, error) in [
+            (
+                "with an unknown prefix",
+                format!("!PING${{\"time\": {}}}${metadata}", now()),
+                MalformedRequestFailedPrefixParsing,
+            ),
+            (
+                "with an unknown command",
+                format!("?FLY${{}}${metadata}"),
+                MalformedRequestFailedCommandParsing,
+            ),
+            (
+                "missing a separator",
+                format!("?PING${{\"time\": {}}}", now()),
+                MalformedRequestFailedSeparatorParsing,
+            ),
+            (
+                "with the wrong arguments",
+                format!("?PING${{\"nope\": 1}}${metadata}"),
+                MalformedRequestFailedArgumentsParsing,
+            ),
+            (
+                "with unreadable metadata",
+                format!("?PING${{\"time\": {}}}$nonsense", now()),
+                MalformedRequestFailedMetadataParsing,
+            ),
+            (
+                "with a corrupted checksum",
+                corrupted,
+                MalformedRequestOtherError,
+            ),
+        ] {
+            self.send_raw(&frame)?;
+            let answer = self.answer(&format!("the frame {description}"), |raw_frame| {
+                raw_frame.command == Command::Error
+            })?;
+            let outcome = expect_error(answer, error)?;
+            self.record(
+                &format!(
+                    "A frame {description} is answered with error {:02}",
+                    error as u8
+                ),
+                outcome,
+            );
+        }
+        Ok(())
+    }
+
+    fn check_timeouts(&mut self) {
+        let outcome = match self.late.is_empty() {
+            true => Outcome::Pass,
+            false => Outcome::Fail(self.late.join(", ")),
+        };
+        self.record(
+            &format!(
+                "Every request is answered within the request timeout ({}s)",
+                self.serial_event_propagator.request_timeout
+            ),
+            outcome,
+        );
+
+        let outcome = match self.unreadable.is_empty() {
+            true => Outcome::Pass,
+            false => Outcome::Fail(self.unreadable.join(", ")),
+        };
+        self.record("Every frame from the car can be read", outcome);
+    }
+
+    /// Send a request, returning its sequence
+    fn send<S>(&mut self, command: Command, data: S) -> Result<Option<u32>, ClientError>
+    where
+        S: Serialize + for<'a> Deserialize<'a> + Sized,
+    {
+        let frame = self
+            .serial_event_propagator
+            .write_to_serial(command, data)?;
+        Ok(RawFrame::split(&frame)?.metadata.sequence)
+    }
+
+    /// Send a frame exactly as it is, malformed or not
+    fn send_raw(&mut self, frame: &str) -> Result<(), ClientError> {
+        self.serial_event_propagator
+            .transport
+            .as_mut()
+            .ok_or_else(|| ClientError::Serial("Not connected".to_owned()))?
+            .write_line(frame)
+    }
+
+    /// Wait for the answer to the request with `sequence`
+    fn answer_to(
+        &mut self,
+        what: &str,
+        sequence: Option<u32>,
+    ) -> Result<Option<RawFrame>, ClientError> {
+        self.answer(what, |raw_frame| answers(raw_frame, sequence))
+    }
+
+    /// Wait for a frame from the car matching `matches`, for up to twice the
+    /// request timeout, noting down whatever came in late or unreadable
+    fn answer<F>(&mut self, what: &str, matches: F) -> Result<Option<RawFrame>, ClientError>
+    where
+        F: Fn(&RawFrame) -> bool,
+    {
+        let request_timeout = self.serial_event_propagator.request_timeout;
+        let start_time = Instant::now();
+        while start_time.elapsed().as_secs_f64() < request_timeout * 2.0 {
+            while let Some(frame) = self.serial_event_propagator.read_from_serial()? {
+                let raw_frame = match RawFrame::split(&frame) {
+                    Ok(raw_frame) => raw_frame,
+                    Err(e) => {
+                        self.unreadable.push(format!("`{}`: {e}", frame.trim()));
+                        continue;
+                    }
+                };
+                if !matches(&raw_frame) {
+                    continue;
+                }
+                let elapsed = start_time.elapsed().as_secs_f64();
+                if elapsed > request_timeout {
+                    self.late.push(format!("{what} after {elapsed:.1}s"));
+                }
+                return Ok(Some(raw_frame));
+            }
+            sleep(Duration::from_secs_f64(self.poll_interval));
+        }
+        Ok(None)
+    }
+
+    fn record(&mut self, rule: &str, outcome: Outcome) {
+        self.results.push(RuleResult {
+            rule: rule.to_owned(),
+            outcome,
+        });
+    }
+}
+
+/***** Helper functions *****/
+
+/// Whether a frame answers the request with `sequence`, taking an error that
+/// doesn't say which request it answers as answering it
+fn answers(raw_frame: &RawFrame, sequence: Option<u32>) -> bool {
+    match raw_frame.metadata.sequence {
+        Some(_) => raw_frame.metadata.sequence == sequence,
+        None => raw_frame.command == Command::Error,
+    }
+}
+
+/// The failure for an answer that isn't the one expected
+fn unexpected(answer: Option<RawFrame>) -> Result<Outcome, ClientError> {
+    Ok(Outcome::Fail(match answer {
+        None => "No answer".to_owned(),
+        Some(raw_frame) if raw_frame.command == Command::Error => {
+            let error = raw_frame.value::<ErrorResponse>()?;
+            format!(
+                "Answered with error {:02}: {}",
+                error.error_variant, error.message
+            )
+        }
+        Some(raw_frame) => format!("Answered with {}", raw_frame.command),
+    }))
+}
+
+/// Whether an answer is the error expected
+fn expect_error(answer: Option<RawFrame>, expected: ServerError) -> Result<Outcome, ClientError> {
+    match &answer {
+        Some(raw_frame) if raw_frame.command == Command::Error => {
+            let error = raw_frame.value::<ErrorResponse>()?;
+            if error.error_variant == expected as u8 {
+                return Ok(Outcome::Pass);
+            }
+        }
+        _ => (),
+    }
+    unexpected(answer)
+}
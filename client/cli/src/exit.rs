@@ -0,0 +1,94 @@
+/*!
+ * Exit codes for each kind of failure, so scripts can tell them apart
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{ClientError, ServerError};
+use std::{fmt, process::ExitCode};
+
+/***** Failures *****/
+
+/// What kind of failure the CLI exited with, each with its own exit code (0
+/// being success)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Failure {
+    /// Anything else, like a file that couldn't be read
+    Other = 1,
+    /// Bad arguments, a request the car turned down as not making sense
+    /// right now (starting while started, or stopping while stopped), or a
+    /// run plan that couldn't be read, had a mistake, or waited on something
+    /// that never happened
+    Validation = 2,
+    /// The car or the bridge couldn't be reached, or the connection to it was
+    /// lost
+    Connection = 3,
+    /// Frames that came in corrupted or couldn't be read, the car speaking
+    /// another version of the protocol or not understanding a request, and
+    /// the car breaking a rule of the protocol in `conformance`
+    Protocol = 4,
+    /// A request went unanswered, even after retrying it
+    Timeout = 5,
+}
+impl Failure {
+    pub const ALL: [Self; 5] = [
+        Self::Other,
+        Self::Validation,
+        Self::Connection,
+        Self::Protocol,
+        Self::Timeout,
+    ];
+
+    /// The kind of failure an error from running a command is
+    ///
+    /// Arguments that couldn't be parsed are also [`ClientError::Parse`], but
+    /// never get this far, so those are frames that couldn't be read.
+    pub fn of(error: &ClientError) -> Self {
+        match error {
+            ClientError::Serial(_) | ClientError::Bluetooth(_) => Self::Connection,
+            ClientError::Timeout { .. } => Self::Timeout,
+            // Only failing `conformance` is a run error here
+            ClientError::Parse(_)
+            | ClientError::Run(_)
+            | ClientError::ChecksumMismatch { .. }
+            | ClientError::ProtocolVersion { .. } => Self::Protocol,
+            ClientError::Server(
+                ServerError::FailedToStartAlreadyStarted | ServerError::FailedToStopNotStarted,
+                _,
+            )
+            | ClientError::Plan(_) => Self::Validation,
+            // Malformed requests and responses
+            ClientError::Server(error, _)
+                if (*error as u8) < ServerError::_ResponseErrorUpperBound as u8 =>
+            {
+                Self::Protocol
+            }
+            _ => Self::Other,
+        }
+    }
+
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+}
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Other => "other",
+                Self::Validation => "validation",
+                Self::Connection => "connection",
+                Self::Protocol => "protocol",
+                Self::Timeout => "timeout",
+            }
+        )
+    }
+}
+impl From<Failure> for ExitCode {
+    fn from(failure: Failure) -> Self {
+        ExitCode::from(failure.code())
+    }
+}
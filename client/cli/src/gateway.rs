@@ -0,0 +1,302 @@
+/*!
+ * Letting browsers and other tools drive the car over a WebSocket, through
+ * the one serial connection
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{client::now, output::response_json};
+use bindings::{
+    ClientError, Command, DistanceEstimatorKind, Frame, PingArguments, RawFrame, Response,
+    StartArguments, StatusArguments, StopArguments,
+};
+use gui::events::SerialEventPropagator;
+use serde::Deserialize;
+use serde_json::json;
+use std::{
+    io::ErrorKind,
+    net::{SocketAddr, TcpListener, TcpStream},
+    time::Duration,
+};
+use tracing::{debug, info, info_span, warn};
+use tungstenite::{accept, Error as WebSocketError, Message, WebSocket};
+
+// Constants
+/// How long a new connection has to finish the WebSocket handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/***** Requests *****/
+
+/// A request from a WebSocket client, as JSON, e.g.
+///
+/// ```json
+/// {"command": "start", "distance": 300, "reverse_brake": false, "max_velocity": null}
+/// ```
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(tag = "command", rename_all = "snake_case", deny_unknown_fields)]
+pub enum GatewayRequest {
+    // Braces so extra fields are refused, which unit variants don't do
+    Ping {},
+    Start {
+        /// Centimeters
+        distance: f64,
+        #[serde(default)]
+        reverse_brake: bool,
+        /// Centimeters per second
+        #[serde(default)]
+        max_velocity: Option<f64>,
+        /// As its number, e.g. 1 for the accelerometer fusion, or the
+        /// server's default when left out
+        #[serde(default)]
+        distance_estimator: Option<DistanceEstimatorKind>,
+    },
+    Stop {},
+    Status {},
+}
+
+/***** Gateway *****/
+
+struct Subscriber {
+    id: usize,
+    socket: WebSocket<TcpStream>,
+}
+
+/// A WebSocket server forwarding requests to the car
+///
+/// Every client gets the response to its own requests, and every status
+/// (asked for or sent on its own during a run) is sent to every client.
+/// Responses are sent as the JSON the CLI prints with `--json`, and anything
+/// that goes wrong as `{"error": "<what went wrong>"}`.
+pub struct Gateway {
+    listener: TcpListener,
+    subscribers: Vec<Subscriber>,
+    next_id: usize,
+    /// Which client sent each request still waiting on a response, by
+    /// sequence
+    requesters: Vec<(u32, usize)>,
+}
+impl Gateway {
+    pub fn bind(address: &str) -> Result<Self, ClientError> {
+        let listener = TcpListener::bind(address)
+            .and_then(|listener| listener.set_nonblocking(true).map(|()| listener))
+            .map_err(|e| ClientError::Gateway(format!("Failed to listen on {address}: {e}")))?;
+        Ok(Self {
+            listener,
+            subscribers: vec![],
+            next_id: 0,
+            requesters: vec![],
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, ClientError> {
+        self.listener
+            .local_addr()
+            .map_err(|e| ClientError::Gateway(e.to_string()))
+    }
+
+    /// How many clients are connected
+    pub fn subscribers(&self) -> usize {
+        self.subscribers.len()
+    }
+
+    /// Accept new clients, forward their requests, and send out whatever came
+    /// back from the car, without waiting on anything
+    ///
+    /// Only losing the listener is an error; clients that misbehave are
+    /// dropped or told what went wrong.
+    pub fn poll(
+        &mut self,
+        serial_event_propagator: &mut SerialEventPropagator,
+    ) -> Result<(), ClientError> {
+        self.accept_subscribers()?;
+
+        for (id, text) in self.receive() {
+            let _subscriber = info_span!("subscriber", id).entered();
+            debug!(%text, "Received");
+            if let Err(e) = self.forward(serial_event_propagator, id, &text) {
+                warn!("Refused a request: {e}");
+                self.send_to(id, &error_json(&e));
+            }
+        }
+
+        serial_event_propagator.maintain_connection();
+        loop {
+            match serial_event_propagator.read_from_serial() {
+                Ok(Some(frame)) => self.respond(&frame),
+                Ok(None) => break,
+                Err(e) => {
+                    self.broadcast(&error_json(&e));
+                    break;
+                }
+            }
+        }
+        // Nobody in particular is waiting on a request given up on
+        for result in serial_event_propagator.retry_pending() {
+            if let Err(e) = result {
+                self.broadcast(&error_json(&e));
+            }
+        }
+        self.requesters.retain(|(sequence, _)| {
+            serial_event_propagator
+                .pending_requests
+                .iter()
+                .any(|pending| pending.sequence == *sequence)
+        });
+
+        // Partly sent messages go out once there's room
+        self.subscribers
+            .retain_mut(|subscriber| still_open(subscriber.socket.flush()));
+        Ok(())
+    }
+
+    fn accept_subscribers(&mut self) -> Result<(), ClientError> {
+        loop {
+            let stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(ClientError::Gateway(e.to_string())),
+            };
+            // The handshake is short, so it's waited on rather than tracked
+            let handshake = stream
+                .set_nonblocking(false)
+                .and_then(|()| stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)));
+            if handshake.is_err() {
+                continue;
+            }
+            let Ok(socket) = accept(stream) else {
+                continue;
+            };
+            if socket.get_ref().set_nonblocking(true).is_err() {
+                continue;
+            }
+            info!(id = self.next_id, "Subscribed");
+            self.subscribers.push(Subscriber {
+                id: self.next_id,
+                socket,
+            });
+            self.next_id += 1;
+        }
+    }
+
+    /// Every text message that came in, with who it came from, dropping
+    /// clients that went away
+    fn receive(&mut self) -> Vec<(usize, String)> {
+        let mut messages = vec![];
+        self.subscribers.retain_mut(|subscriber| loop {
+            match subscriber.socket.read() {
+                Ok(Message::Text(text)) => messages.push((subscriber.id, text)),
+                Ok(Message::Close(_)) => {
+                    info!(id = subscriber.id, "Unsubscribed");
+                    return false;
+                }
+                Ok(_) => (),
+                Err(WebSocketError::Io(e)) if e.kind() == ErrorKind::WouldBlock => return true,
+                Err(e) => {
+                    info!(id = subscriber.id, "Dropped: {e}");
+                    return false;
+                }
+            }
+        });
+        messages
+    }
+
+    /// Send a client's request to the car
+    fn forward(
+        &mut self,
+        serial_event_propagator: &mut SerialEventPropagator,
+        id: usize,
+        text: &str,
+    ) -> Result<(), ClientError> {
+        let frame = match serde_json::from_str::<GatewayRequest>(text)? {
+            GatewayRequest::Ping {} => serial_event_propagator
+                .write_to_serial(Command::Ping, PingArguments::builder().time(now()).build())?,
+            GatewayRequest::Start {
+                distance,
+                reverse_brake,
+                max_velocity,
+                distance_estimator,
+            } => {
+                if !(distance > 0.0 && distance.is_finite()) {
+                    return Err(ClientError::Parse(format!(
+                        "The distance has to be a number over 0, not {distance}"
+                    )));
+                }
+                serial_event_propagator.write_to_serial(
+                    Command::Start,
+                    StartArguments::builder()
+                        .distance_cm(distance)
+                        .reverse_brake(reverse_brake)
+                        .max_velocity_cm_s(max_velocity)
+                        .distance_estimator(distance_estimator)
+                        .build(),
+                )?
+            }
+            GatewayRequest::Stop {} => {
+                serial_event_propagator.write_to_serial(Command::Stop, StopArguments {})?
+            }
+            GatewayRequest::Status {} => {
+                serial_event_propagator.write_to_serial(Command::Status, StatusArguments {})?
+            }
+        };
+        if let Some(sequence) = RawFrame::split(&frame)?.metadata.sequence {
+            self.requesters.push((sequence, id));
+        }
+        Ok(())
+    }
+
+    /// Send a frame from the car to whoever should get it
+    fn respond(&mut self, frame: &str) {
+        let response = match Frame::decode(frame) {
+            Ok(response) => response,
+            Err(e) => return self.broadcast(&error_json(&e)),
+        };
+        let requester = RawFrame::split(frame)
+            .ok()
+            .and_then(|raw_frame| raw_frame.metadata.sequence)
+            .and_then(|sequence| {
+                self.requesters
+                    .iter()
+                    .find(|(requested, _)| *requested == sequence)
+                    .map(|(_, id)| *id)
+            });
+
+        match (&response, requester) {
+            (Response::Status(_), _) | (_, None) => self.broadcast(&response_json(&response)),
+            (_, Some(id)) => {
+                let _subscriber = info_span!("subscriber", id).entered();
+                debug!("Answered");
+                self.send_to(id, &response_json(&response))
+            }
+        }
+    }
+
+    fn send_to(&mut self, id: usize, text: &str) {
+        self.subscribers.retain_mut(|subscriber| {
+            subscriber.id != id || still_open(subscriber.socket.send(Message::Text(text.into())))
+        });
+    }
+
+    fn broadcast(&mut self, text: &str) {
+        self.subscribers.retain_mut(|subscriber| {
+            still_open(subscriber.socket.send(Message::Text(text.into())))
+        });
+    }
+}
+
+/***** Helper functions *****/
+
+/// Whether a client is still there after sending to it, counting a send that
+/// has to wait for room as fine
+fn still_open(result: Result<(), WebSocketError>) -> bool {
+    match result {
+        Ok(()) => true,
+        Err(WebSocketError::Io(e)) => e.kind() == ErrorKind::WouldBlock,
+        Err(_) => false,
+    }
+}
+
+fn error_json(error: &ClientError) -> String {
+    json!({ "error": error.to_string() }).to_string()
+}
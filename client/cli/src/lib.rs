@@ -0,0 +1,18 @@
+/*!
+ * A headless client for driving the car from a terminal, e.g. over SSH
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+pub mod args;
+pub mod client;
+pub mod coast;
+pub mod conformance;
+pub mod exit;
+#[cfg(feature = "web")]
+pub mod gateway;
+pub mod merge;
+pub mod output;
+pub mod plan;
+pub mod sessions;
@@ -0,0 +1,150 @@
+/*!
+ * The headless client, for when there's no screen to put the GUI on
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::ClientError;
+use client_cli::{
+    args::*, client::*, coast::FittedDeceleration, exit::*, merge::*, output::error_json,
+    sessions::*,
+};
+use gui::{config::*, events::*, logging};
+use std::{
+    env::args,
+    io::{stdin, IsTerminal},
+    process::ExitCode,
+};
+use tracing::warn;
+
+/// Report an error as JSON on stdout with `--json`, or as it reads to stderr
+/// otherwise, returning the exit code for its kind of failure
+fn fail(e: &ClientError, failure: Failure, json: bool, human: &str) -> ExitCode {
+    match json {
+        true => println!("{}", error_json(e, failure)),
+        false => eprintln!("{human}"),
+    }
+    failure.into()
+}
+
+fn main() -> ExitCode {
+    let args = args().skip(1_usize).collect::<Vec<_>>();
+    // So even bad arguments are reported as JSON when asked
+    let json = args.iter().any(|arg| arg == "--json");
+    let arguments = match Arguments::parse(args) {
+        Ok(arguments) => arguments,
+        Err(e) => return fail(&e, Failure::Validation, json, &format!("{e}\n\n{USAGE}")),
+    };
+
+    // A bad config is only a warning, like in the GUI
+    let (config, config_error) = Config::load(arguments.config.clone());
+    // Logged to stderr, out of the way of what's printed
+    logging::init(config.log.level, None);
+    if let Some(e) = &config_error {
+        warn!("{e}");
+    }
+
+    // Looking back on sessions doesn't need the car
+    if let Subcommand::Sessions {
+        directory,
+        subcommand,
+    } = &arguments.subcommand
+    {
+        let printed = print_sessions(
+            directory.clone(),
+            subcommand,
+            arguments.json,
+            &config.time.timezone,
+            &mut std::io::stdout(),
+        );
+        return match printed {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => fail(&e, Failure::of(&e), arguments.json, &e.to_string()),
+        };
+    }
+
+    // Nor does merging runs
+    if let Subcommand::Merge {
+        client,
+        server,
+        output,
+    } = &arguments.subcommand
+    {
+        let merged = print_merged(
+            client,
+            server,
+            output,
+            arguments.json,
+            &mut std::io::stdout(),
+        );
+        return match merged {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => fail(&e, Failure::of(&e), arguments.json, &e.to_string()),
+        };
+    }
+
+    // Measuring how the car coasts saves it to the config, which would lose
+    // everything in one that couldn't be loaded
+    if let (Subcommand::Coast { .. }, Some(e)) = (&arguments.subcommand, &config_error) {
+        return fail(
+            e,
+            Failure::of(e),
+            arguments.json,
+            &format!("Not measuring how the car coasts, as it couldn't be saved: {e}"),
+        );
+    }
+
+    // Without a port, whatever USB serial port matches the config is used
+    let mut connection = ConnectionManager::new(arguments.port.clone(), &config.serial);
+    let transport = match connection.connect() {
+        Ok(transport) => transport,
+        Err(e) => {
+            return fail(
+                &e,
+                Failure::Connection,
+                arguments.json,
+                &format!("Failed to connect to the car: {e}\n\n{USAGE}"),
+            )
+        }
+    };
+
+    let mut serial_event_propagator = SerialEventPropagator::new(transport);
+    serial_event_propagator.request_timeout = config.serial.request_timeout;
+    serial_event_propagator.max_request_attempts = config.serial.max_request_attempts;
+
+    let mut client = Client::new(
+        serial_event_propagator,
+        config.serial.poll_interval,
+        arguments.json,
+        std::io::stdout(),
+    );
+    client.coast_deceleration = config.car.coast_deceleration;
+    client.guided = stdin().is_terminal();
+
+    if let Subcommand::Coast { speeds, distance } = &arguments.subcommand {
+        let saved_to = arguments.config.clone().or_else(Config::default_path);
+        let measured = client
+            .measure_coast(speeds, *distance)
+            .and_then(|coast_deceleration| {
+                if let Some(path) = &saved_to {
+                    let mut config = config.clone();
+                    config.car.coast_deceleration = Some(coast_deceleration);
+                    config.write(path)?;
+                }
+                client.print_fitted_deceleration(&FittedDeceleration {
+                    coast_deceleration,
+                    saved_to: saved_to.as_deref(),
+                })
+            });
+        return match measured {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => fail(&e, Failure::of(&e), arguments.json, &e.to_string()),
+        };
+    }
+
+    match client.run(&arguments.subcommand) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => fail(&e, Failure::of(&e), arguments.json, &e.to_string()),
+    }
+}
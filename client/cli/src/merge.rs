@@ -0,0 +1,32 @@
+/*!
+ * Merging a run with the server's log of it from a terminal, like the GUI's
+ * `--merge`
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::ClientError;
+use gui::export::merge_runs;
+use serde_json::json;
+use std::{io::Write, path::Path};
+
+/***** Merging *****/
+
+/// Merge a run recorded by the client with the server's log of it into
+/// another run (see [`gui::export::merge_status_tables`]), and print how many
+/// statuses it has
+pub fn print_merged<W: Write>(
+    client: &Path,
+    server: &Path,
+    output: &Path,
+    json: bool,
+    out: &mut W,
+) -> Result<(), ClientError> {
+    let merged = merge_runs(client, server, output).map_err(|e| ClientError::CSV(e.to_string()))?;
+    let line = match json {
+        true => json!({ "merged": merged, "output": output }).to_string(),
+        false => format!("Merged {merged} statuses into {}", output.display()),
+    };
+    writeln!(out, "{line}").map_err(|e| ClientError::Unknown(e.to_string()))
+}
@@ -0,0 +1,362 @@
+/*!
+ * Printing responses as human readable tables or JSON
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{
+    coast::{Coast, FittedDeceleration},
+    conformance::{Outcome, RuleResult},
+    exit::Failure,
+    plan::FinishedStep,
+};
+use bindings::{
+    ApproachGains, ClientError, Event, ExecutedBreakpoint, PowerStatus, RadioStopStatus, Response,
+    StatusResponse,
+};
+use gui::{clock::DisplayTimezone, session_log::SessionRun};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/***** JSON *****/
+
+/// A response as one line of JSON, with its command, value, and metadata
+pub fn response_json(response: &Response) -> String {
+    use Response::*;
+    match response {
+        Ping(event) => event_json(event),
+        Start(event) => event_json(event),
+        Stop(event) => event_json(event),
+        Status(event) => event_json(event),
+        StaticStatus(event) => event_json(event),
+        Error(event) => event_json(event),
+        Hello(event) => event_json(event),
+        UploadPlan(event) => event_json(event),
+        Tune(event) => event_json(event),
+        Calibrate(event) => event_json(event),
+        BluetoothStatus(event) => event_json(event),
+        BluetoothScan(event) => event_json(event),
+        BluetoothConnect(event) => event_json(event),
+        BluetoothMetrics(event) => event_json(event),
+        Watchdog(event) => event_json(event),
+    }
+    .to_string()
+}
+
+/// An error as one line of JSON, with its kind of failure and the exit code
+/// that goes with it
+pub fn error_json(error: &ClientError, failure: Failure) -> String {
+    json!({
+        "error": error.to_string(),
+        "failure": failure.to_string(),
+        "exit_code": failure.code(),
+    })
+    .to_string()
+}
+
+fn event_json<S>(event: &Event<S>) -> Value
+where
+    S: Serialize + for<'a> Deserialize<'a>,
+{
+    json!({
+        "command": event.command.to_string(),
+        "value": event.value,
+        "metadata": event.metadata,
+    })
+}
+
+/***** Human readable *****/
+
+/// The header of the status table, see [`status_row`]
+pub fn status_header() -> String {
+    format!(
+        "{:>8} {:>10} {:>12} {:>14} {:>8} {:>8}",
+        "Runtime", "Stage", "Distance", "Velocity", "Magnets", "Speeding"
+    )
+}
+
+/// A status as a row of the status table
+pub fn status_row(status: &StatusResponse) -> String {
+    format!(
+        "{:>7}s {:>10} {:>10.3}cm {:>10.3}cm/s {:>8} {:>8}",
+        status.runtime,
+        // The stage is displayed with a newline on the end
+        status.stage.to_string().trim_end(),
+        status.distance.distance,
+        status.distance.velocity,
+        status.distance.magnet_hit_counter,
+        status.speed_limit_violations,
+    )
+}
+
+/// A response as a line or two of text, given the Unix time in seconds it
+/// was received at
+pub fn response_human(response: &Response, received_time: f64) -> String {
+    use Response::*;
+    match response {
+        Ping(event) => format!(
+            "Round-trip latency: {:.0}ms",
+            (received_time - event.value.sent_time) * 1000.0
+        ),
+        Start(_) => "Started".to_owned(),
+        Stop(_) => "Stopped".to_owned(),
+        Status(event) => {
+            let mut lines = vec![status_header(), status_row(&event.value)];
+            if let Some(executed_plan) = &event.value.executed_plan {
+                lines.push(executed_plan_human(executed_plan));
+            }
+            if let Some(radio_stop) = &event.value.radio_stop {
+                lines.push(radio_stop_human(radio_stop));
+            }
+            if let Some(power) = &event.value.power {
+                lines.push(power_human(power));
+            }
+            lines.join("\n")
+        }
+        StaticStatus(event) => format!(
+            "Magnets: {}, wheel diameter: {:.3}cm{}{}{}",
+            event.value.number_of_magnets,
+            event.value.wheel_diameter,
+            event
+                .value
+                .distance_estimator
+                .map(|distance_estimator| format!(", distance from: {distance_estimator}"))
+                .unwrap_or_default(),
+            event
+                .value
+                .approach_gains
+                .map(|approach_gains| format!("\n{}", approach_gains_human(Some(&approach_gains))))
+                .unwrap_or_default(),
+            event
+                .value
+                .wheel_circumference
+                .map(|wheel_circumference| format!(
+                    "\nCalibrated wheel circumference: {wheel_circumference:.3}cm"
+                ))
+                .unwrap_or_default()
+        ),
+        Error(event) => format!("Error: {}", event.value.message),
+        Hello(event) => format!("Protocol version: {}", event.value.protocol_version),
+        UploadPlan(_) => "Plan uploaded, the car is running it".to_owned(),
+        Tune(event) => approach_gains_human(event.value.approach_gains.as_ref()),
+        Calibrate(event) => format!(
+            "Wheel circumference: {:.3}cm, was {:.3}cm",
+            event.value.wheel_circumference, event.value.previous_wheel_circumference
+        ),
+        BluetoothStatus(event) => format!(
+            "Bluetooth: {}",
+            match event.value.connected {
+                true => "connected",
+                false => "disconnected",
+            }
+        ),
+        BluetoothScan(event) if event.value.devices.is_empty() => "No devices found".to_owned(),
+        BluetoothScan(event) => event
+            .value
+            .devices
+            .iter()
+            .map(|device| {
+                format!(
+                    "{} {:>7} {}",
+                    device.address,
+                    device
+                        .rssi
+                        .map(|rssi| format!("{rssi}dBm"))
+                        .unwrap_or_else(|| "-".to_owned()),
+                    device.name.as_deref().unwrap_or("Unnamed")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        BluetoothConnect(event) => format!(
+            "{}{} after {:.1}s, {} device{} found",
+            match event.value.connected {
+                None => "Connecting",
+                Some(true) => "Connected",
+                Some(false) => "Couldn't connect",
+            },
+            event
+                .value
+                .address
+                .as_ref()
+                .map(|address| format!(" to {address}"))
+                .unwrap_or_default(),
+            event.value.elapsed,
+            event.value.devices.len(),
+            match event.value.devices.len() {
+                1 => "",
+                _ => "s",
+            }
+        ),
+        BluetoothMetrics(event) => format!(
+            "Device: {}\nSignal: {}\nSent: {} bytes\nReceived: {} bytes\nReconnects: {}\n\
+             Duplicates discarded: {}",
+            event.value.address.as_deref().unwrap_or("none"),
+            event
+                .value
+                .rssi
+                .map(|rssi| format!("{rssi}dBm"))
+                .unwrap_or_else(|| "-".to_owned()),
+            event.value.bytes_sent,
+            event.value.bytes_received,
+            event.value.reconnects,
+            event.value.duplicates_discarded
+        ),
+        Watchdog(event) => format!(
+            "Nothing from {} for {:.1}s, reconnecting",
+            event.value.address, event.value.silent_for
+        ),
+    }
+}
+
+/// The breakpoints of the plan the last run was uploaded with, and when the
+/// car went past each
+pub fn executed_plan_human(executed_plan: &[ExecutedBreakpoint]) -> String {
+    executed_plan
+        .iter()
+        .map(|breakpoint| {
+            format!(
+                "At {:.1}cm, limited to {}: {}",
+                breakpoint.distance,
+                breakpoint
+                    .max_velocity
+                    .map(|max_velocity| format!("{max_velocity:.1}cm/s"))
+                    .unwrap_or_else(|| "nothing".to_owned()),
+                breakpoint
+                    .reached
+                    .map(|reached| format!("reached after {reached:.2}s"))
+                    .unwrap_or_else(|| "never reached".to_owned())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether the radio's stop button is held, and how many runs it stopped
+pub fn radio_stop_human(radio_stop: &RadioStopStatus) -> String {
+    format!(
+        "Radio stop: {}, stopped {} run{}",
+        match radio_stop.held {
+            true => "held",
+            false => "ready",
+        },
+        radio_stop.stops,
+        match radio_stop.stops {
+            1 => "",
+            _ => "s",
+        }
+    )
+}
+
+/// How the car eases in on the distance, see [`bindings::ApproachGains`]
+pub fn approach_gains_human(approach_gains: Option<&ApproachGains>) -> String {
+    match approach_gains {
+        Some(gains) => format!(
+            "Easing in with proportional {}, integral {}, derivative {}",
+            gains.proportional, gains.integral, gains.derivative
+        ),
+        None => "Cutting power once close enough".to_owned(),
+    }
+}
+
+/// The battery's voltage (and charge, if known), and what the motor draws
+pub fn power_human(power: &PowerStatus) -> String {
+    format!(
+        "Battery: {:.2}V{}, motor drawing {:.2}A",
+        power.battery_voltage,
+        power
+            .charge
+            .map(|charge| format!(" ({charge:.0}% left)"))
+            .unwrap_or_default(),
+        power.motor_current
+    )
+}
+
+/// How a rule of the protocol went, see [`crate::conformance`]
+pub fn rule_human(result: &RuleResult) -> String {
+    match &result.outcome {
+        Outcome::Pass => format!("PASS {}", result.rule),
+        Outcome::Fail(detail) => format!("FAIL {}: {detail}", result.rule),
+        Outcome::Skipped(detail) => format!("SKIP {}: {detail}", result.rule),
+    }
+}
+
+/// A step of a run plan that finished, with when it did and where the car
+/// was then, see [`crate::plan`]
+pub fn plan_step_human(finished: &FinishedStep) -> String {
+    format!(
+        "{:>7.2}s {}{}",
+        finished.elapsed,
+        finished.step,
+        finished
+            .status
+            .map(|status| format!(
+                ": {:.3}cm at {:.3}cm/s",
+                status.distance.distance, status.distance.velocity
+            ))
+            .unwrap_or_default()
+    )
+}
+
+/// How far the car coasted from a speed, see [`crate::coast`]
+pub fn coast_human(coast: &Coast) -> String {
+    format!(
+        "At {:.1}cm/s: coasted {:.1}cm from {:.1}cm/s",
+        coast.speed, coast.distance, coast.velocity
+    )
+}
+
+/// How quickly the car slows down with power cut, and where that was saved
+pub fn fitted_deceleration_human(fitted: &FittedDeceleration) -> String {
+    format!(
+        "Slows down at {:.2}cm/s² with power cut{}",
+        fitted.coast_deceleration,
+        fitted
+            .saved_to
+            .map(|path| format!(", saved to {}", path.display()))
+            .unwrap_or_default()
+    )
+}
+
+/// The header of the session table, see [`session_row`]
+pub fn session_header() -> String {
+    format!(
+        "{:<22} {:<16} {:>5} {:>7}",
+        "Session", "Venue", "Runs", "Errors"
+    )
+}
+
+/// A session log as a row of the session table, by its name, where it was,
+/// and how many runs and errors it has
+pub fn session_row(name: &str, venue: &str, runs: usize, errors: usize) -> String {
+    format!(
+        "{name:<22} {:<16} {runs:>5} {errors:>7}",
+        match venue.is_empty() {
+            true => "-",
+            false => venue,
+        }
+    )
+}
+
+/// The header of the run summary table, see [`session_run_row`]
+pub fn session_run_header() -> String {
+    format!(
+        "{:<16} {:>8} {:<22} {}",
+        "Date", "Target", "Result", "Stop reason"
+    )
+}
+
+/// A run from a session log as a row of the run summary table
+pub fn session_run_row(run: &SessionRun, timezone: &DisplayTimezone) -> String {
+    format!(
+        "{:<16} {:>6.0}cm {:<22} {}",
+        timezone.format_unix(run.time, "%Y-%m-%d %H:%M"),
+        run.target,
+        run.result.describe(),
+        run.stop_reason
+            .as_ref()
+            .map(|reason| reason.describe())
+            .unwrap_or_else(|| "-".to_owned())
+    )
+}
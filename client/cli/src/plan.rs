@@ -0,0 +1,328 @@
+/*!
+ * Run plans: commands and waits to run one after another, like "start, let it
+ * run 3 seconds, stop, and wait for it to coast to a stop"
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::args::{Arguments, Subcommand};
+use bindings::{ClientError, StatusResponse};
+use serde::Serialize;
+use std::{fmt, fs::read_to_string, path::Path};
+
+/***** Conditions *****/
+
+/// What the car reports that a plan can wait on
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Quantity {
+    /// Centimeters
+    Distance,
+    /// Centimeters per second
+    Velocity,
+    /// Seconds since the run started
+    Runtime,
+}
+impl Quantity {
+    pub fn of(self, status: &StatusResponse) -> f64 {
+        match self {
+            Self::Distance => status.distance.distance,
+            Self::Velocity => status.distance.velocity,
+            Self::Runtime => status.runtime as f64,
+        }
+    }
+
+    /// What its values are written with
+    pub fn unit(self) -> &'static str {
+        match self {
+            Self::Distance => "cm",
+            Self::Velocity => "cm/s",
+            Self::Runtime => "s",
+        }
+    }
+}
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Distance => "distance",
+                Self::Velocity => "velocity",
+                Self::Runtime => "runtime",
+            }
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Comparison {
+    Over,
+    AtLeast,
+    Under,
+    AtMost,
+}
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Over => ">",
+                Self::AtLeast => ">=",
+                Self::Under => "<",
+                Self::AtMost => "<=",
+            }
+        )
+    }
+}
+
+/// What a plan waits until
+#[derive(Clone, PartialEq, Debug)]
+pub enum Condition {
+    /// The run is over, however it ended
+    Stopped,
+    /// What the car reports compared with a value, in the quantity's unit
+    Reported {
+        quantity: Quantity,
+        comparison: Comparison,
+        value: f64,
+    },
+}
+impl Condition {
+    pub fn holds(&self, status: &StatusResponse) -> bool {
+        match self {
+            Self::Stopped => !status.running,
+            Self::Reported {
+                quantity,
+                comparison,
+                value,
+            } => {
+                let reported = quantity.of(status);
+                match comparison {
+                    Comparison::Over => reported > *value,
+                    Comparison::AtLeast => reported >= *value,
+                    Comparison::Under => reported < *value,
+                    Comparison::AtMost => reported <= *value,
+                }
+            }
+        }
+    }
+}
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stopped => write!(f, "stopped"),
+            Self::Reported {
+                quantity,
+                comparison,
+                value,
+            } => write!(f, "{quantity} {comparison} {value}{}", quantity.unit()),
+        }
+    }
+}
+
+/***** Plans *****/
+
+#[derive(PartialEq, Debug)]
+pub enum Step {
+    /// A command, as it'd be given on the command line
+    Command(Subcommand),
+    /// Seconds to wait, following the car's statuses meanwhile
+    Wait(f64),
+    /// Wait until the car reports the condition holds, failing the plan if it
+    /// doesn't within the seconds given
+    WaitUntil {
+        condition: Condition,
+        within: Option<f64>,
+    },
+}
+impl Step {
+    /// Parse a line of a plan, without its comment
+    ///
+    /// Commands are written as on the command line (`start --distance 300`,
+    /// `stop`), and waits as `wait 3s` or `wait 500ms`, or `wait until` a
+    /// condition: `stopped`, or `distance`, `velocity`, or `runtime` compared
+    /// with `>`, `>=`, `<`, or `<=` to a value in `cm`, `cm/s`, or `s`, e.g.
+    /// `wait until distance > 300cm within 10s`. Any step can start with
+    /// `then` to read better.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace().peekable();
+        words.next_if_eq(&"then");
+
+        if words.next_if_eq(&"wait").is_none() {
+            return command(words.map(str::to_owned).collect());
+        }
+        let words = words.collect::<Vec<_>>();
+        match words.as_slice() {
+            [duration] => Ok(Self::Wait(seconds(duration)?)),
+            ["until", condition @ ..] => {
+                let (condition, within) = match condition {
+                    [condition @ .., "within", duration] => (condition, Some(seconds(duration)?)),
+                    condition => (condition, None),
+                };
+                Ok(Self::WaitUntil {
+                    condition: self::condition(condition)?,
+                    within,
+                })
+            }
+            _ => Err(
+                "`wait` needs a time (like `3s` or `500ms`), or `until` and a condition".to_owned(),
+            ),
+        }
+    }
+}
+
+/// A step of a plan, with where it is in the plan
+#[derive(PartialEq, Debug)]
+pub struct PlannedStep {
+    /// Counting from 1
+    pub line: usize,
+    /// As it was written, without its comment
+    pub text: String,
+    pub step: Step,
+}
+
+/// The steps of a plan, in the order they're run
+///
+/// A plan is written one step per line, with anything after a `#` being a
+/// comment. See [`Step::parse`].
+#[derive(PartialEq, Debug)]
+pub struct Plan {
+    pub steps: Vec<PlannedStep>,
+}
+impl Plan {
+    pub fn read(path: &Path) -> Result<Self, ClientError> {
+        let text = read_to_string(path)
+            .map_err(|e| ClientError::Plan(format!("Couldn't read {}: {e}", path.display())))?;
+        Self::parse(&text)
+    }
+
+    /// Parse every step, so a plan with a mistake anywhere is never started
+    pub fn parse(text: &str) -> Result<Self, ClientError> {
+        let mut steps = vec![];
+        for (index, line) in text.lines().enumerate() {
+            let text = line.split('#').next().unwrap_or_default().trim();
+            if text.is_empty() {
+                continue;
+            }
+            let step = Step::parse(text)
+                .map_err(|e| ClientError::Plan(format!("Line {}: {e}", index + 1)))?;
+            steps.push(PlannedStep {
+                line: index + 1,
+                text: text.to_owned(),
+                step,
+            });
+        }
+
+        match steps.is_empty() {
+            true => Err(ClientError::Plan("The plan has no steps".to_owned())),
+            false => Ok(Self { steps }),
+        }
+    }
+}
+
+/// A step that finished, with what the car last reported, for printing
+#[derive(Serialize)]
+pub struct FinishedStep<'a> {
+    pub line: usize,
+    pub step: &'a str,
+    /// Seconds since the plan started
+    pub elapsed: f64,
+    pub status: Option<&'a StatusResponse>,
+}
+
+/***** Helper functions *****/
+
+/// A command in a plan, which can only be one that's over once it's answered
+fn command(words: Vec<String>) -> Result<Step, String> {
+    let name = words.first().cloned().unwrap_or_default();
+    let arguments = Arguments::parse(words).map_err(|e| match e {
+        ClientError::Parse(message) => message,
+        e => e.to_string(),
+    })?;
+    if arguments.port.is_some() || arguments.config.is_some() || arguments.json {
+        return Err(
+            "`--port`, `--config`, and `--json` go on the command line, not in the plan".to_owned(),
+        );
+    }
+
+    match arguments.subcommand {
+        subcommand @ (Subcommand::Ping
+        | Subcommand::Start { .. }
+        | Subcommand::UploadPlan { .. }
+        | Subcommand::Stop
+        | Subcommand::Tune { .. }
+        | Subcommand::Calibrate { .. }
+        | Subcommand::Status { watch: false }
+        | Subcommand::Bluetooth(_)) => Ok(Step::Command(subcommand)),
+        _ => Err(format!("`{name}` can't be run from a plan")),
+    }
+}
+
+/// A time to wait, like `3s`, `1.5s`, or `500ms`, in seconds
+fn seconds(word: &str) -> Result<f64, String> {
+    let parsed = match word.strip_suffix("ms") {
+        Some(milliseconds) => milliseconds.parse::<f64>().map(|number| number / 1000.0),
+        None => word.strip_suffix('s').unwrap_or("-").parse::<f64>(),
+    };
+    match parsed {
+        Ok(seconds) if seconds >= 0.0 && seconds.is_finite() => Ok(seconds),
+        _ => Err(format!(
+            "Expected a time like `3s` or `500ms`, not `{word}`"
+        )),
+    }
+}
+
+/// A condition after `wait until`
+fn condition(words: &[&str]) -> Result<Condition, String> {
+    let (quantity, comparison, value) = match words {
+        ["stopped"] => return Ok(Condition::Stopped),
+        [quantity, comparison, value] => (quantity, comparison, value),
+        _ => {
+            return Err(format!(
+                "Expected `stopped` or a condition like `distance > 300cm`, not `{}`",
+                words.join(" ")
+            ))
+        }
+    };
+
+    let quantity = match *quantity {
+        "distance" => Quantity::Distance,
+        "velocity" => Quantity::Velocity,
+        "runtime" => Quantity::Runtime,
+        _ => {
+            return Err(format!(
+                "Can only wait on `distance`, `velocity`, or `runtime`, not `{quantity}`"
+            ))
+        }
+    };
+    let comparison = match *comparison {
+        ">" => Comparison::Over,
+        ">=" => Comparison::AtLeast,
+        "<" => Comparison::Under,
+        "<=" => Comparison::AtMost,
+        _ => {
+            return Err(format!(
+                "Expected `>`, `>=`, `<`, or `<=`, not `{comparison}`"
+            ))
+        }
+    };
+    let value = value
+        .strip_suffix(quantity.unit())
+        .and_then(|number| number.parse::<f64>().ok())
+        .filter(|number| number.is_finite())
+        .ok_or_else(|| {
+            format!(
+                "Expected the {quantity} in {}, like `10{}`, not `{value}`",
+                quantity.unit(),
+                quantity.unit()
+            )
+        })?;
+
+    Ok(Condition::Reported {
+        quantity,
+        comparison,
+        value,
+    })
+}
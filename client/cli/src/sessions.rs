@@ -0,0 +1,157 @@
+/*!
+ * Looking back on the sessions the GUI logged from a terminal, to grep and
+ * script over past runs without the GUI
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{args::SessionsSubcommand, output::*};
+use bindings::ClientError;
+use gui::{clock::DisplayTimezone, data_directory::DataDirectory, session_log::*};
+use serde_json::json;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+use tracing::warn;
+
+// Constants
+/// Stands in for the name of the newest session
+pub const LATEST_SESSION: &str = "latest";
+
+/***** Sessions *****/
+
+/// Print the sessions logged in a directory (the GUI's when not given) as
+/// tables, or as one JSON object per line
+pub fn print_sessions<W: Write>(
+    directory: Option<PathBuf>,
+    subcommand: &SessionsSubcommand,
+    json: bool,
+    timezone: &DisplayTimezone,
+    out: &mut W,
+) -> Result<(), ClientError> {
+    let directory = match directory {
+        Some(directory) => directory,
+        None => DataDirectory::new()?.session_log_directory(),
+    };
+    let mut lines = vec![];
+    match subcommand {
+        SessionsSubcommand::List => {
+            let session_logs = session_logs(&directory);
+            if session_logs.is_empty() {
+                return Err(no_sessions(&directory));
+            }
+            if !json {
+                lines.push(session_header());
+            }
+            for path in session_logs.iter().rev() {
+                let Some(entries) = read_or_warn(path) else {
+                    continue;
+                };
+                let name = session_name(path);
+                let venue = entries
+                    .iter()
+                    .find_map(|entry| match &entry.event {
+                        SessionEvent::Opened(metadata) => Some(metadata.venue.clone()),
+                        _ => None,
+                    })
+                    .unwrap_or_default();
+                let runs = session_runs(&entries).len();
+                let errors = entries
+                    .iter()
+                    .filter(|entry| matches!(entry.event, SessionEvent::Error { .. }))
+                    .count();
+                lines.push(match json {
+                    true => json!({
+                        "session": name,
+                        "venue": venue,
+                        "runs": runs,
+                        "errors": errors,
+                    })
+                    .to_string(),
+                    false => session_row(&name, &venue, runs, errors),
+                });
+            }
+        }
+        SessionsSubcommand::Summary { session } => {
+            // Oldest first, like the runs in each
+            let session_logs = match session {
+                Some(session) => vec![find_session(&directory, session)?],
+                None => session_logs(&directory),
+            };
+            if session_logs.is_empty() {
+                return Err(no_sessions(&directory));
+            }
+            if !json {
+                lines.push(session_run_header());
+            }
+            for path in session_logs.iter() {
+                let entries = match session {
+                    Some(_) => read_session_log(path)?,
+                    None => match read_or_warn(path) {
+                        Some(entries) => entries,
+                        None => continue,
+                    },
+                };
+                let name = session_name(path);
+                for run in session_runs(&entries) {
+                    lines.push(match json {
+                        true => json!({ "session": name, "run": run }).to_string(),
+                        false => session_run_row(&run, timezone),
+                    });
+                }
+            }
+        }
+        SessionsSubcommand::Dump { session } => {
+            let entries = read_session_log(&find_session(&directory, session)?)?;
+            if !json {
+                return write_session_csv(&entries, out);
+            }
+            for entry in entries.iter() {
+                lines.push(serde_json::to_string(entry)?);
+            }
+        }
+    }
+
+    for line in lines {
+        writeln!(out, "{line}").map_err(|e| ClientError::Unknown(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// A session log in a directory by its name (e.g. `2026-10-16_14-03-22`), or
+/// the newest with [`LATEST_SESSION`]
+pub fn find_session(directory: &Path, session: &str) -> Result<PathBuf, ClientError> {
+    let session_logs = session_logs(directory);
+    let found = match session {
+        LATEST_SESSION => session_logs.last(),
+        _ => session_logs
+            .iter()
+            .find(|path| session_name(path) == session),
+    };
+    found.cloned().ok_or_else(|| {
+        ClientError::SessionLog(format!("No session `{session}` in {}", directory.display()))
+    })
+}
+
+/***** Helper functions *****/
+
+/// The name of a session log, its file name without `.jsonl`
+fn session_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Read a session log, only warning if it can't be so the rest are still
+/// printed
+fn read_or_warn(path: &Path) -> Option<Vec<SessionLogEntry>> {
+    read_session_log(path)
+        .map_err(|e| warn!("Skipping {}: {e}", path.display()))
+        .ok()
+}
+
+fn no_sessions(directory: &Path) -> ClientError {
+    ClientError::SessionLog(format!("No sessions in {}", directory.display()))
+}
@@ -0,0 +1,1097 @@
+/*!
+ * Tests for the headless client, with a loopback standing in for the car
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{
+    ApproachGains, ClientError, Command, DistanceEstimatorKind, LoopbackTransport, PlanBreakpoint,
+    RawFrame, Response, ServerError, StaticStatusArguments, StatusArguments, Transport,
+};
+use car_simulator::*;
+use client_cli::{
+    args::*, client::*, coast::*, conformance::*, exit::*, merge::*, output::error_json, plan::*,
+    sessions::*,
+};
+use gui::{clock::DisplayTimezone, events::*, shared::*};
+use std::{
+    collections::HashSet,
+    env::temp_dir,
+    fs::{create_dir_all, remove_dir_all, write},
+    mem::take,
+    path::PathBuf,
+    thread::{sleep, spawn},
+    time::Duration,
+};
+
+/***** Helper functions *****/
+
+fn parse(args: &str) -> Result<Arguments, ClientError> {
+    Arguments::parse(args.split_whitespace().map(str::to_owned))
+}
+
+/// Run a command against a car that answers every request with `respond`,
+/// given the request's command and metadata, returning what was printed
+fn run_against_car(
+    subcommand: Subcommand,
+    json: bool,
+    respond: fn(&str, &str) -> String,
+) -> Result<String, ClientError> {
+    let (transport, mut car) = LoopbackTransport::pair();
+    spawn(move || loop {
+        let line = match car.read_line() {
+            Ok(Some(line)) => line,
+            Ok(None) => {
+                sleep(Duration::from_millis(1));
+                continue;
+            }
+            Err(_) => break,
+        };
+        let raw_frame = RawFrame::split(&line).unwrap();
+        let metadata = serde_json::to_string(&raw_frame.metadata).unwrap();
+        let response = respond(&raw_frame.command.to_string(), &metadata);
+        if car.write_line(&response).is_err() {
+            break;
+        }
+    });
+
+    let mut client = Client::new(
+        SerialEventPropagator::new(Box::new(transport)),
+        SERIAL_DELAY_TIME,
+        json,
+        vec![],
+    );
+    client.run(&subcommand)?;
+    Ok(String::from_utf8(client.out).unwrap())
+}
+
+/***** Tests *****/
+
+#[test]
+fn commands_are_parsed() {
+    assert_eq!(parse("ping").unwrap().subcommand, Subcommand::Ping);
+    assert_eq!(
+        parse("start --distance 300 --reverse-brake").unwrap(),
+        Arguments {
+            port: None,
+            config: None,
+            json: false,
+            subcommand: Subcommand::Start {
+                distance: 300.0,
+                reverse_brake: true,
+                max_velocity: None,
+                distance_estimator: None,
+            },
+        }
+    );
+    assert_eq!(
+        parse("start --distance 300 --estimator fusion")
+            .unwrap()
+            .subcommand,
+        Subcommand::Start {
+            distance: 300.0,
+            reverse_brake: false,
+            max_velocity: None,
+            distance_estimator: Some(DistanceEstimatorKind::AccelerometerFusion),
+        }
+    );
+    assert_eq!(
+        parse(
+            "upload-plan --distance 300 --breakpoint 50:20 --breakpoint 200:none --reverse-brake"
+        )
+        .unwrap()
+        .subcommand,
+        Subcommand::UploadPlan {
+            distance: 300.0,
+            reverse_brake: true,
+            max_velocity: None,
+            distance_estimator: None,
+            breakpoints: vec![
+                PlanBreakpoint {
+                    distance: 50.0,
+                    max_velocity: Some(20.0),
+                },
+                PlanBreakpoint {
+                    distance: 200.0,
+                    max_velocity: None,
+                },
+            ],
+        }
+    );
+    assert_eq!(
+        parse("tune --kp 0.03 --kd 0.02").unwrap().subcommand,
+        Subcommand::Tune {
+            approach_gains: Some(ApproachGains {
+                proportional: 0.03,
+                integral: 0.0,
+                derivative: 0.02,
+            }),
+        }
+    );
+    assert_eq!(
+        parse("tune --off").unwrap().subcommand,
+        Subcommand::Tune {
+            approach_gains: None
+        }
+    );
+    assert_eq!(
+        parse("calibrate --distance 95.5").unwrap().subcommand,
+        Subcommand::Calibrate { distance: 95.5 }
+    );
+    assert_eq!(
+        parse("--json status --watch --port /dev/pts/3").unwrap(),
+        Arguments {
+            port: Some("/dev/pts/3".to_owned()),
+            config: None,
+            json: true,
+            subcommand: Subcommand::Status { watch: true },
+        }
+    );
+    assert_eq!(
+        parse("bluetooth disconnect").unwrap().subcommand,
+        Subcommand::Bluetooth(BluetoothSubcommand::Disconnect)
+    );
+    assert_eq!(
+        parse("bluetooth metrics").unwrap().subcommand,
+        Subcommand::Bluetooth(BluetoothSubcommand::Metrics)
+    );
+    assert_eq!(
+        parse("bluetooth connect --address 00:60:37:E9:0B:6F")
+            .unwrap()
+            .subcommand,
+        Subcommand::Bluetooth(BluetoothSubcommand::Connect {
+            address: Some("00:60:37:E9:0B:6F".to_owned()),
+        })
+    );
+    assert_eq!(
+        parse("gateway").unwrap().subcommand,
+        Subcommand::Gateway {
+            listen: "0.0.0.0:8080".to_owned(),
+        }
+    );
+    assert_eq!(
+        parse("plan coast.plan").unwrap().subcommand,
+        Subcommand::Plan {
+            path: PathBuf::from("coast.plan"),
+        }
+    );
+    assert_eq!(
+        parse("coast").unwrap().subcommand,
+        Subcommand::Coast {
+            speeds: DEFAULT_COAST_SPEEDS.to_vec(),
+            distance: DEFAULT_COAST_DISTANCE,
+        }
+    );
+    assert_eq!(
+        parse("coast --speeds 15,30.5 --distance 150")
+            .unwrap()
+            .subcommand,
+        Subcommand::Coast {
+            speeds: vec![15.0, 30.5],
+            distance: 150.0,
+        }
+    );
+    assert_eq!(
+        parse("conformance").unwrap().subcommand,
+        Subcommand::Conformance
+    );
+    assert_eq!(
+        parse("gateway --listen 127.0.0.1:9000").unwrap().subcommand,
+        Subcommand::Gateway {
+            listen: "127.0.0.1:9000".to_owned(),
+        }
+    );
+    assert_eq!(
+        parse("sessions").unwrap().subcommand,
+        Subcommand::Sessions {
+            directory: None,
+            subcommand: SessionsSubcommand::List,
+        }
+    );
+    assert_eq!(
+        parse("--json sessions dump latest --directory /tmp/sessions")
+            .unwrap()
+            .subcommand,
+        Subcommand::Sessions {
+            directory: Some(PathBuf::from("/tmp/sessions")),
+            subcommand: SessionsSubcommand::Dump {
+                session: "latest".to_owned(),
+            },
+        }
+    );
+    assert_eq!(
+        parse("merge run.csv server.jsonl merged.csv")
+            .unwrap()
+            .subcommand,
+        Subcommand::Merge {
+            client: PathBuf::from("run.csv"),
+            server: PathBuf::from("server.jsonl"),
+            output: PathBuf::from("merged.csv"),
+        }
+    );
+}
+
+#[test]
+fn bad_commands_are_rejected() {
+    for args in [
+        "",
+        "fly",
+        "start",
+        "start --distance -5",
+        "start --distance far",
+        "start --distance 300 --max-velocity",
+        "start --distance 300 --estimator",
+        "start --distance 300 --estimator gps",
+        "start --distance 300 --breakpoint 50:20",
+        "upload-plan --distance 300",
+        "upload-plan --breakpoint 50:20",
+        "upload-plan --distance 300 --breakpoint",
+        "upload-plan --distance 300 --breakpoint 50",
+        "upload-plan --distance 300 --breakpoint 50:fast",
+        "upload-plan --distance 300 --breakpoint 300:20",
+        "upload-plan --distance 300 --breakpoint 50:0",
+        "upload-plan --distance 300 --breakpoint 200:20 --breakpoint 50:none",
+        "tune",
+        "tune --kp",
+        "tune --kp -0.1",
+        "tune --ki lots",
+        "tune --off --kp 0.03",
+        "calibrate",
+        "calibrate --distance 0",
+        "calibrate --distance 95.5 --off",
+        "ping --watch",
+        "status --distance 300",
+        "bluetooth",
+        "bluetooth pair",
+        "bluetooth connect --address",
+        "bluetooth scan --address 00:60:37:E9:0B:6F",
+        "bluetooth metrics --watch",
+        "stop now",
+        "ping --port",
+        "gateway --listen",
+        "gateway --port",
+        "conformance --distance 300",
+        "plan",
+        "plan coast.plan stop.plan",
+        "coast --speeds",
+        "coast --speeds 20,,40",
+        "coast --speeds 20,-40",
+        "coast --distance 0",
+        "sessions dump",
+        "sessions delete",
+        "sessions summary latest 2026-10-16_09-00-00",
+        "sessions list --watch",
+        "sessions --directory",
+        "merge run.csv server.csv",
+        "merge run.csv server.csv merged.csv again.csv",
+    ] {
+        assert!(
+            matches!(parse(args), Err(ClientError::Parse(_))),
+            "`{args}` was parsed"
+        );
+    }
+}
+
+#[test]
+fn ping_is_answered() {
+    let output = run_against_car(Subcommand::Ping, false, |command, metadata| {
+        assert_eq!(command, "PING");
+        format!("~PING${{\"sent_time\": 1.0}}${metadata}")
+    })
+    .unwrap();
+    assert!(output.starts_with("Round-trip latency: "), "{output}");
+}
+
+#[test]
+fn status_is_printed_as_json() {
+    let output = run_against_car(Subcommand::Status { watch: false }, true, |_, metadata| {
+        format!(
+            "~STATUS${{\"running\": true, \"uptime\": 5, \"runtime\": 2, \"stage\": 1, \
+                 \"distance\": {{\"distance\": 42.5, \"velocity\": 10.0, \
+                 \"magnet_hit_counter\": 3}}}}${metadata}"
+        )
+    })
+    .unwrap();
+    let json: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    assert_eq!(json["command"], "STATUS");
+    assert_eq!(json["value"]["distance"]["distance"], 42.5);
+}
+
+#[test]
+fn the_radio_stop_is_printed_with_the_status() {
+    let output = run_against_car(Subcommand::Status { watch: false }, false, |_, metadata| {
+        format!(
+            "~STATUS${{\"running\": false, \"uptime\": 5, \"runtime\": 0, \"stage\": 0, \
+                 \"distance\": {{\"distance\": 0.0, \"velocity\": 0.0, \
+                 \"magnet_hit_counter\": 0}}, \"radio_stop\": {{\"held\": true, \
+                 \"stops\": 1}}}}${metadata}"
+        )
+    })
+    .unwrap();
+    assert_eq!(
+        output.lines().last(),
+        Some("Radio stop: held, stopped 1 run"),
+        "{output}"
+    );
+}
+
+#[test]
+fn power_is_printed_with_the_status() {
+    let output = run_against_car(Subcommand::Status { watch: false }, false, |_, metadata| {
+        format!(
+            "~STATUS${{\"running\": false, \"uptime\": 5, \"runtime\": 0, \"stage\": 0, \
+                 \"distance\": {{\"distance\": 0.0, \"velocity\": 0.0, \
+                 \"magnet_hit_counter\": 0}}, \"power\": {{\"battery_voltage\": 7.42, \
+                 \"motor_current\": 0.5, \"charge\": 64.0}}}}${metadata}"
+        )
+    })
+    .unwrap();
+    assert_eq!(
+        output.lines().last(),
+        Some("Battery: 7.42V (64% left), motor drawing 0.50A"),
+        "{output}"
+    );
+}
+
+#[test]
+fn error_responses_are_errors() {
+    let result = run_against_car(
+        Subcommand::Start {
+            distance: 300.0,
+            reverse_brake: false,
+            max_velocity: None,
+            distance_estimator: None,
+        },
+        false,
+        |_, metadata| {
+            format!("~ERROR${{\"error_variant\": 21, \"message\": \"Already started\"}}${metadata}")
+        },
+    );
+    assert!(
+        matches!(
+            result,
+            Err(ClientError::Server(
+                ServerError::FailedToStartAlreadyStarted,
+                _
+            ))
+        ),
+        "{result:?}"
+    );
+    assert_eq!(Failure::of(&result.unwrap_err()), Failure::Validation);
+}
+
+#[test]
+fn failures_have_their_own_exit_codes() {
+    let codes = Failure::ALL.map(Failure::code);
+    assert!(!codes.contains(&0));
+    assert_eq!(codes.iter().collect::<HashSet<_>>().len(), codes.len());
+
+    let timeout = ClientError::Timeout {
+        command: Command::Ping,
+        attempts: 3,
+    };
+    for (error, failure) in [
+        (
+            ClientError::Serial("Unplugged".to_owned()),
+            Failure::Connection,
+        ),
+        (
+            ClientError::Bluetooth("No adapter".to_owned()),
+            Failure::Connection,
+        ),
+        (
+            ClientError::ChecksumMismatch {
+                command: None,
+                expected: 1,
+                calculated: 2,
+            },
+            Failure::Protocol,
+        ),
+        (
+            ClientError::Server(
+                ServerError::MalformedRequestFailedPrefixParsing,
+                String::new(),
+            ),
+            Failure::Protocol,
+        ),
+        (
+            ClientError::Server(ServerError::FailedToStopNotStarted, String::new()),
+            Failure::Validation,
+        ),
+        (
+            ClientError::Server(ServerError::FailedToStartMotorControlFailed, String::new()),
+            Failure::Other,
+        ),
+        (
+            ClientError::SessionLog("No sessions".to_owned()),
+            Failure::Other,
+        ),
+    ] {
+        assert_eq!(Failure::of(&error), failure, "{error}");
+    }
+    assert_eq!(Failure::of(&timeout), Failure::Timeout);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&error_json(&timeout, Failure::Timeout)).unwrap();
+    assert_eq!(json["error"], timeout.to_string());
+    assert_eq!(json["failure"], "timeout");
+    assert_eq!(json["exit_code"], 5);
+}
+
+#[test]
+fn scanned_devices_are_listed() {
+    let output = run_against_car(
+        Subcommand::Bluetooth(BluetoothSubcommand::Scan),
+        false,
+        |command, metadata| {
+            assert_eq!(command, "SCAN");
+            format!(
+                "&SCAN${{\"devices\": [{{\"address\": \"00:60:37:E9:0B:6F\", \
+                     \"name\": \"R41Z\", \"rssi\": -60}}, {{\"address\": \
+                     \"00:60:37:E9:0B:70\", \"name\": null, \"rssi\": null}}]}}${metadata}"
+            )
+        },
+    )
+    .unwrap();
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            "00:60:37:E9:0B:6F  -60dBm R41Z",
+            "00:60:37:E9:0B:70       - Unnamed"
+        ]
+    );
+}
+
+#[test]
+fn bridge_metrics_are_printed() {
+    let output = run_against_car(
+        Subcommand::Bluetooth(BluetoothSubcommand::Metrics),
+        false,
+        |command, metadata| {
+            assert_eq!(command, "METRICS");
+            format!(
+                "&METRICS${{\"address\": \"00:60:37:E9:0B:6F\", \"rssi\": -72, \
+                     \"bytes_sent\": 1200, \"bytes_received\": 3400, \"reconnects\": 2, \
+                     \"duplicates_discarded\": 5}}${metadata}"
+            )
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        output.lines().collect::<Vec<_>>(),
+        [
+            "Device: 00:60:37:E9:0B:6F",
+            "Signal: -72dBm",
+            "Sent: 1200 bytes",
+            "Received: 3400 bytes",
+            "Reconnects: 2",
+            "Duplicates discarded: 5"
+        ]
+    );
+}
+
+#[test]
+fn run_against_the_simulator() {
+    let (transport, mut car) = LoopbackTransport::pair();
+    spawn(move || {
+        let mut server = SimulatedServer::new(CarSettings::default(), unix_time());
+        serve(&mut car, &mut server)
+    });
+    let mut client = Client::new(
+        SerialEventPropagator::new(Box::new(transport)),
+        SERIAL_DELAY_TIME,
+        false,
+        vec![],
+    );
+
+    client.run(&Subcommand::Ping).unwrap();
+    client
+        .run(&Subcommand::Start {
+            distance: 100.0,
+            reverse_brake: false,
+            max_velocity: None,
+            distance_estimator: None,
+        })
+        .unwrap();
+    sleep(Duration::from_secs(2));
+    let Response::Status(status) = client.request(Command::Status, StatusArguments).unwrap() else {
+        panic!("Expected a status");
+    };
+    assert!(status.value.running);
+    assert!(status.value.distance.distance > 0.0);
+
+    client.run(&Subcommand::Stop).unwrap();
+    let output = String::from_utf8(take(&mut client.out)).unwrap();
+    assert!(output.starts_with("Round-trip latency: "), "{output}");
+}
+
+#[test]
+fn uploaded_plans_run_against_the_simulator() {
+    let (transport, mut car) = LoopbackTransport::pair();
+    spawn(move || {
+        let mut server = SimulatedServer::new(CarSettings::default(), unix_time());
+        serve(&mut car, &mut server)
+    });
+    let mut client = Client::new(
+        SerialEventPropagator::new(Box::new(transport)),
+        SERIAL_DELAY_TIME,
+        false,
+        vec![],
+    );
+
+    client
+        .run(
+            &parse("upload-plan --distance 60 --breakpoint 20:15")
+                .unwrap()
+                .subcommand,
+        )
+        .unwrap();
+    // Carried out on its own, without the client asking for anything
+    let mut finished = false;
+    for _ in 0..300 {
+        sleep(Duration::from_millis(100));
+        let Response::Status(status) = client.request(Command::Status, StatusArguments).unwrap()
+        else {
+            panic!("Expected a status");
+        };
+        if !status.value.running {
+            let executed_plan = status.value.executed_plan.unwrap();
+            assert_eq!(executed_plan.len(), 1);
+            assert_eq!(executed_plan[0].max_velocity, Some(15.0));
+            assert!(executed_plan[0].reached.is_some());
+            finished = true;
+            break;
+        }
+    }
+    assert!(finished, "The plan never finished");
+
+    client.run(&Subcommand::Status { watch: false }).unwrap();
+    let output = String::from_utf8(take(&mut client.out)).unwrap();
+    assert!(output.starts_with("Plan uploaded"), "{output}");
+    assert!(
+        output.contains("At 20.0cm, limited to 15.0cm/s: reached after "),
+        "{output}"
+    );
+}
+
+#[test]
+fn tuning_against_the_simulator() {
+    let (transport, mut car) = LoopbackTransport::pair();
+    spawn(move || {
+        let mut server = SimulatedServer::new(CarSettings::default(), unix_time());
+        serve(&mut car, &mut server)
+    });
+    let mut client = Client::new(
+        SerialEventPropagator::new(Box::new(transport)),
+        SERIAL_DELAY_TIME,
+        false,
+        vec![],
+    );
+
+    client
+        .run(&parse("tune --kp 0.03").unwrap().subcommand)
+        .unwrap();
+    // Kept by the car, rather than only for the next run
+    let Response::StaticStatus(static_status) = client
+        .request(Command::StaticStatus, StaticStatusArguments)
+        .unwrap()
+    else {
+        panic!("Expected a static status");
+    };
+    assert_eq!(
+        static_status
+            .value
+            .approach_gains
+            .map(|gains| gains.proportional),
+        Some(0.03)
+    );
+    client
+        .run(&parse("tune --off").unwrap().subcommand)
+        .unwrap();
+
+    let output = String::from_utf8(take(&mut client.out)).unwrap();
+    assert_eq!(
+        output.lines().collect::<Vec<_>>(),
+        [
+            "Easing in with proportional 0.03, integral 0, derivative 0",
+            "Cutting power once close enough"
+        ]
+    );
+}
+
+#[test]
+fn calibrating_against_the_simulator() {
+    let (transport, mut car) = LoopbackTransport::pair();
+    spawn(move || {
+        let mut server = SimulatedServer::new(CarSettings::default(), unix_time());
+        serve(&mut car, &mut server)
+    });
+    let mut client = Client::new(
+        SerialEventPropagator::new(Box::new(transport)),
+        SERIAL_DELAY_TIME,
+        false,
+        vec![],
+    );
+    let calibrate = parse("calibrate --distance 30").unwrap().subcommand;
+
+    // Nothing to calibrate against before a run
+    assert!(matches!(
+        client.run(&calibrate),
+        Err(ClientError::Server(ServerError::AnyOtherError, _))
+    ));
+    client
+        .run(&parse("start --distance 30").unwrap().subcommand)
+        .unwrap();
+    let mut counted = None;
+    for _ in 0..300 {
+        sleep(Duration::from_millis(100));
+        let Response::Status(status) = client.request(Command::Status, StatusArguments).unwrap()
+        else {
+            panic!("Expected a status");
+        };
+        if !status.value.running {
+            counted = Some(status.value.distance.distance);
+            break;
+        }
+    }
+    let counted = counted.expect("The run never finished");
+    take(&mut client.out);
+
+    client.run(&calibrate).unwrap();
+    let wheel_circumference = 6.35 * std::f64::consts::PI;
+    let output = String::from_utf8(take(&mut client.out)).unwrap();
+    assert_eq!(
+        output.trim_end(),
+        format!(
+            "Wheel circumference: {:.3}cm, was {wheel_circumference:.3}cm",
+            wheel_circumference * 30.0 / counted
+        )
+    );
+    // Reported by the car from then on
+    let Response::StaticStatus(static_status) = client
+        .request(Command::StaticStatus, StaticStatusArguments)
+        .unwrap()
+    else {
+        panic!("Expected a static status");
+    };
+    assert!(static_status.value.wheel_circumference.is_some());
+}
+
+#[test]
+fn plans_are_parsed() {
+    let plan = Plan::parse(
+        "# Coast distance\n\
+         start --distance 1000 --max-velocity 40\n\
+         \n\
+         wait 3s # Let it get going\n\
+         then stop\n\
+         wait until velocity <= 0cm/s within 10s\n\
+         wait until stopped\n\
+         wait 250ms",
+    )
+    .unwrap();
+    let steps = plan
+        .steps
+        .iter()
+        .map(|planned| (planned.line, &planned.step))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        steps,
+        [
+            (
+                2,
+                &Step::Command(Subcommand::Start {
+                    distance: 1000.0,
+                    reverse_brake: false,
+                    max_velocity: Some(40.0),
+                    distance_estimator: None,
+                })
+            ),
+            (4, &Step::Wait(3.0)),
+            (5, &Step::Command(Subcommand::Stop)),
+            (
+                6,
+                &Step::WaitUntil {
+                    condition: Condition::Reported {
+                        quantity: Quantity::Velocity,
+                        comparison: Comparison::AtMost,
+                        value: 0.0,
+                    },
+                    within: Some(10.0),
+                }
+            ),
+            (
+                7,
+                &Step::WaitUntil {
+                    condition: Condition::Stopped,
+                    within: None,
+                }
+            ),
+            (8, &Step::Wait(0.25)),
+        ]
+    );
+    assert_eq!(plan.steps[2].text, "then stop");
+
+    assert!(matches!(
+        Plan::parse("# Nothing but a comment\n\n"),
+        Err(ClientError::Plan(_))
+    ));
+    for text in [
+        "fly",
+        "start",
+        "status --watch",
+        "gateway",
+        "plan coast.plan",
+        "stop --port /dev/pts/3",
+        "wait",
+        "wait 3",
+        "wait -3s",
+        "wait soon",
+        "wait until",
+        "wait until distance > 300",
+        "wait until distance > 300cm/s",
+        "wait until distance = 300cm",
+        "wait until heading > 90s",
+        "wait until stopped within",
+        "wait until stopped within 10",
+    ] {
+        let result = Plan::parse(&format!("ping\n{text}"));
+        assert!(
+            matches!(&result, Err(ClientError::Plan(message)) if message.starts_with("Line 2")),
+            "`{text}` was parsed"
+        );
+    }
+}
+
+#[test]
+fn plans_run_against_the_simulator() {
+    let (transport, mut car) = LoopbackTransport::pair();
+    spawn(move || {
+        let mut server = SimulatedServer::new(CarSettings::default(), unix_time());
+        serve(&mut car, &mut server)
+    });
+    let mut client = Client::new(
+        SerialEventPropagator::new(Box::new(transport)),
+        SERIAL_DELAY_TIME,
+        true,
+        vec![],
+    );
+    let directory = temp_dir().join(format!("cli-tests-plans-{}", std::process::id()));
+    create_dir_all(&directory).unwrap();
+    let plan = |name: &str, text: &str| {
+        let path = directory.join(name);
+        write(&path, text).unwrap();
+        Subcommand::Plan { path }
+    };
+
+    client
+        .run(&plan(
+            "coast.plan",
+            "start --distance 1000\n\
+             wait 1s\n\
+             wait until distance > 5cm within 10s\n\
+             then stop\n\
+             wait until stopped within 5s",
+        ))
+        .unwrap();
+    let output = String::from_utf8(take(&mut client.out)).unwrap();
+    let finished = output
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter(|json| json.get("step").is_some())
+        .collect::<Vec<_>>();
+    assert_eq!(finished.len(), 5, "{output}");
+    assert!(finished[1]["elapsed"].as_f64().unwrap() >= 1.0);
+    assert!(
+        finished[2]["status"]["distance"]["distance"]
+            .as_f64()
+            .unwrap()
+            > 5.0
+    );
+    assert_eq!(finished[4]["step"], "wait until stopped within 5s");
+    assert_eq!(finished[4]["status"]["running"], false);
+
+    // Never leaves the car running when a wait isn't met
+    let result = client.run(&plan(
+        "too-far.plan",
+        "start --distance 1000\n\
+         wait until distance > 100000cm within 1s",
+    ));
+    assert!(
+        matches!(&result, Err(ClientError::Plan(message)) if message.starts_with("Line 2")),
+        "{result:?}"
+    );
+    assert_eq!(Failure::of(&result.unwrap_err()), Failure::Validation);
+    let Response::Status(status) = client.request(Command::Status, StatusArguments).unwrap() else {
+        panic!("Expected a status");
+    };
+    assert!(!status.value.running);
+
+    assert!(matches!(
+        client.run(&Subcommand::Plan {
+            path: directory.join("missing.plan"),
+        }),
+        Err(ClientError::Plan(_))
+    ));
+    let _ = remove_dir_all(&directory);
+}
+
+#[test]
+fn deceleration_is_fit_to_coasts() {
+    let coast = |velocity: f64, distance| Coast {
+        speed: velocity,
+        velocity,
+        distance,
+    };
+    // v² / 2a at 25cm/s²
+    let deceleration = fit_deceleration(&[coast(20.0, 8.0), coast(40.0, 32.0)]).unwrap();
+    assert!((deceleration - 25.0).abs() < 1e-9, "{deceleration}");
+    // Off either way, it's in between
+    let deceleration = fit_deceleration(&[coast(20.0, 6.0), coast(40.0, 40.0)]).unwrap();
+    assert!(deceleration > 15.0 && deceleration < 25.0, "{deceleration}");
+
+    assert_eq!(fit_deceleration(&[]), None);
+    assert_eq!(fit_deceleration(&[coast(20.0, 0.0)]), None);
+}
+
+#[test]
+fn coasting_is_measured_against_the_simulator() {
+    let (transport, mut car) = LoopbackTransport::pair();
+    spawn(move || {
+        let mut server = SimulatedServer::new(CarSettings::default(), unix_time());
+        serve(&mut car, &mut server)
+    });
+    let mut client = Client::new(
+        SerialEventPropagator::new(Box::new(transport)),
+        SERIAL_DELAY_TIME,
+        true,
+        vec![],
+    );
+
+    let deceleration = client.measure_coast(&[30.0, 50.0], 120.0).unwrap();
+    // Magnets are far apart, so it's only roughly what the simulator uses
+    assert!(
+        (deceleration - DEFAULT_COAST_DECELERATION).abs() < DEFAULT_COAST_DECELERATION / 2.0,
+        "{deceleration}"
+    );
+    let output = String::from_utf8(take(&mut client.out)).unwrap();
+    let coasts = output
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(coasts.len(), 2, "{output}");
+    assert_eq!(coasts[1]["speed"], 50.0);
+    assert!(coasts[1]["distance"].as_f64().unwrap() > coasts[0]["distance"].as_f64().unwrap());
+
+    // Stopped before it backed up
+    let Response::Status(status) = client.request(Command::Status, StatusArguments).unwrap() else {
+        panic!("Expected a status");
+    };
+    assert!(!status.value.running);
+    assert!(status.value.distance.distance > 120.0);
+}
+
+#[test]
+fn the_simulator_conforms_to_the_protocol() {
+    let (transport, mut car) = LoopbackTransport::pair();
+    spawn(move || {
+        let mut server = SimulatedServer::new(CarSettings::default(), unix_time());
+        serve(&mut car, &mut server)
+    });
+    let mut serial_event_propagator = SerialEventPropagator::new(Box::new(transport));
+    let results = check_conformance(&mut serial_event_propagator, SERIAL_DELAY_TIME).unwrap();
+
+    for result in results.iter() {
+        assert_eq!(result.outcome, Outcome::Pass, "{}", result.rule);
+    }
+    assert!(results.len() > 10, "{results:?}");
+
+    let mut client = Client::new(serial_event_propagator, SERIAL_DELAY_TIME, true, vec![]);
+    client.run(&Subcommand::Conformance).unwrap();
+    let output = String::from_utf8(client.out).unwrap();
+    let json: serde_json::Value = serde_json::from_str(output.lines().next().unwrap()).unwrap();
+    assert_eq!(json["outcome"], "pass");
+    assert_eq!(json["rule"], "HELLO is answered with the protocol version");
+}
+
+#[test]
+fn firmware_breaking_the_protocol_fails() {
+    // Everything is answered with the same ping, whatever was asked
+    let (transport, mut car) = LoopbackTransport::pair();
+    spawn(move || loop {
+        match car.read_line() {
+            Ok(Some(_)) => (),
+            Ok(None) => {
+                sleep(Duration::from_millis(1));
+                continue;
+            }
+            Err(_) => break,
+        }
+        if car
+            .write_line("~PING${\"sent_time\": 1.0}${\"time\": 1.0, \"sequence\": 0}")
+            .is_err()
+        {
+            break;
+        }
+    });
+    let mut serial_event_propagator = SerialEventPropagator::new(Box::new(transport));
+    serial_event_propagator.request_timeout = 0.1;
+    let mut client = Client::new(serial_event_propagator, SERIAL_DELAY_TIME, false, vec![]);
+
+    let result = client.run(&Subcommand::Conformance);
+    assert!(
+        matches!(&result, Err(ClientError::Run(message)) if message.contains("protocol rules failed")),
+        "{result:?}"
+    );
+    assert_eq!(Failure::of(result.as_ref().unwrap_err()), Failure::Protocol);
+    let output = String::from_utf8(client.out).unwrap();
+    assert!(
+        output.contains("FAIL HELLO is answered with the protocol version: Answered with PING"),
+        "{output}"
+    );
+    assert!(
+        output.contains("FAIL A frame with an unknown prefix is answered with error 00: No answer"),
+        "{output}"
+    );
+}
+
+#[test]
+fn sessions_are_summed_up_and_dumped() {
+    let directory = temp_dir().join(format!("cli-tests-sessions-{}", std::process::id()));
+    let _ = remove_dir_all(&directory);
+    create_dir_all(&directory).unwrap();
+    write(
+        directory.join("2026-10-15_09-00-00.jsonl"),
+        "{\"time\": 0.0, \"event\": {\"opened\": {\"version\": \"0.1.0\", \"venue\": \"gym\", \
+         \"port\": null}}}\n\
+         {\"time\": 60.0, \"event\": {\"action\": {\"Start\": {\"distance\": 500.0, \
+         \"reverse_braking\": false}}}}\n\
+         {\"time\": 70.0, \"event\": {\"finished\": {\"distance\": 497.3}}}\n",
+    )
+    .unwrap();
+    write(
+        directory.join("2026-10-16_09-00-00.jsonl"),
+        "{\"time\": 120.0, \"event\": {\"action\": {\"Start\": {\"distance\": 300.0, \
+         \"reverse_braking\": false}}}}\n\
+         {\"time\": 125.0, \"event\": {\"action\": \"Stop\"}}\n\
+         {\"time\": 130.0, \"event\": {\"action\": {\"GiveStopReason\": {\"kind\": \
+         \"obstacle\"}}}}\n",
+    )
+    .unwrap();
+    let print = |subcommand, json| {
+        let mut out = vec![];
+        print_sessions(
+            Some(directory.clone()),
+            &subcommand,
+            json,
+            &DisplayTimezone::Utc,
+            &mut out,
+        )
+        .map(|()| String::from_utf8(out).unwrap())
+    };
+
+    let list = print(SessionsSubcommand::List, false).unwrap();
+    let list = list.lines().collect::<Vec<_>>();
+    assert_eq!(list.len(), 3);
+    assert!(list[1].starts_with("2026-10-16_09-00-00    -"), "{list:?}");
+    assert!(
+        list[2].starts_with("2026-10-15_09-00-00    gym"),
+        "{list:?}"
+    );
+
+    let summary = print(SessionsSubcommand::Summary { session: None }, false).unwrap();
+    assert_eq!(
+        summary.lines().skip(1).collect::<Vec<_>>(),
+        [
+            "1970-01-01 00:01    500cm Finished at 497.3cm    -",
+            "1970-01-01 00:02    300cm Stopped                Obstacle",
+        ]
+    );
+    let summary = print(
+        SessionsSubcommand::Summary {
+            session: Some(LATEST_SESSION.to_owned()),
+        },
+        true,
+    )
+    .unwrap();
+    let json: serde_json::Value = serde_json::from_str(summary.trim()).unwrap();
+    assert_eq!(json["session"], "2026-10-16_09-00-00");
+    assert_eq!(json["run"]["result"], "stopped");
+
+    let dump = print(
+        SessionsSubcommand::Dump {
+            session: "2026-10-15_09-00-00".to_owned(),
+        },
+        false,
+    )
+    .unwrap();
+    assert_eq!(dump.lines().count(), 4);
+    assert!(
+        dump.ends_with("70,finished,Finished at 497.3cm\n"),
+        "{dump}"
+    );
+    assert!(matches!(
+        print(
+            SessionsSubcommand::Dump {
+                session: "2026-10-14_09-00-00".to_owned(),
+            },
+            false,
+        ),
+        Err(ClientError::SessionLog(_))
+    ));
+}
+
+#[test]
+fn runs_are_merged_without_the_car() {
+    let directory = temp_dir().join(format!("cli-tests-merge-{}", std::process::id()));
+    let _ = remove_dir_all(&directory);
+    create_dir_all(&directory).unwrap();
+    let header = "Unix time (UTC),Running,Uptime,Runtime,Distance in centimeters,\
+                  Velocity in centimeters/second,Magnet hit counter,Stage,Speed limit \
+                  violations,Time (UTC),Sequence number,Frame number\n";
+    // The client lost frame 1, which the server logged
+    write(
+        directory.join("client.csv"),
+        format!("{header}1000,true,1000,0,0,0,0,3,0,,,0\n1002,true,1002,2,5,2,1,3,0,,,2\n"),
+    )
+    .unwrap();
+    write(
+        directory.join("server.csv"),
+        format!("{header}1001,true,1001,1,2,2,0,3,0,,,1\n"),
+    )
+    .unwrap();
+    let merge = |output: &str, json| {
+        let mut out = vec![];
+        print_merged(
+            &directory.join("client.csv"),
+            &directory.join("server.csv"),
+            &directory.join(output),
+            json,
+            &mut out,
+        )
+        .map(|()| String::from_utf8(out).unwrap())
+    };
+
+    let printed = merge("merged.jsonl", false).unwrap();
+    assert!(printed.starts_with("Merged 3 statuses into "), "{printed}");
+    let merged = gui::export::read_run(&directory.join("merged.jsonl")).unwrap();
+    assert_eq!(
+        merged
+            .iter()
+            .map(|status| status.metadata.frame)
+            .collect::<Vec<_>>(),
+        [Some(0), Some(1), Some(2)]
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_str(merge("merged.csv", true).unwrap().trim()).unwrap();
+    assert_eq!(json["merged"], 3);
+
+    std::fs::remove_file(directory.join("server.csv")).unwrap();
+    assert!(matches!(
+        merge("merged.csv", false),
+        Err(ClientError::CSV(_))
+    ));
+}
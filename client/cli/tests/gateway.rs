@@ -0,0 +1,129 @@
+/*!
+ * Tests for the WebSocket gateway, with the simulator standing in for the car
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+#![cfg(feature = "web")]
+
+/***** Setup *****/
+// Imports
+use bindings::LoopbackTransport;
+use car_simulator::*;
+use client_cli::gateway::*;
+use gui::{events::*, shared::*};
+use serde_json::{json, Value};
+use std::{
+    net::{SocketAddr, TcpStream},
+    thread::{sleep, spawn},
+    time::{Duration, Instant},
+};
+use tungstenite::{client, Message, WebSocket};
+
+/***** Helper functions *****/
+
+/// A gateway to a simulated car, polled on its own thread, given back as
+/// where it's listening
+fn start_gateway() -> SocketAddr {
+    let (transport, mut car) = LoopbackTransport::pair();
+    spawn(move || {
+        let mut server = SimulatedServer::new(CarSettings::default(), unix_time());
+        serve(&mut car, &mut server)
+    });
+
+    let mut gateway = Gateway::bind("127.0.0.1:0").unwrap();
+    let address = gateway.local_addr().unwrap();
+    let mut serial_event_propagator = SerialEventPropagator::new(Box::new(transport));
+    spawn(move || loop {
+        gateway.poll(&mut serial_event_propagator).unwrap();
+        sleep(Duration::from_secs_f64(SERIAL_DELAY_TIME));
+    });
+    address
+}
+
+fn connect(address: SocketAddr) -> WebSocket<TcpStream> {
+    let stream = TcpStream::connect(address).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(1)))
+        .unwrap();
+    client(format!("ws://{address}/"), stream).unwrap().0
+}
+
+fn send(socket: &mut WebSocket<TcpStream>, request: Value) {
+    socket.send(Message::Text(request.to_string())).unwrap();
+}
+
+/// The next message matching `wanted`, skipping everything before it, giving
+/// up after a few seconds
+fn wait_for(socket: &mut WebSocket<TcpStream>, wanted: impl Fn(&Value) -> bool) -> Value {
+    let start_time = Instant::now();
+    while start_time.elapsed() < Duration::from_secs(5) {
+        let Ok(Message::Text(text)) = socket.read() else {
+            continue;
+        };
+        let message = serde_json::from_str::<Value>(&text).unwrap();
+        if wanted(&message) {
+            return message;
+        }
+    }
+    panic!("Nothing wanted came in");
+}
+
+/***** Tests *****/
+
+#[test]
+fn responses_go_to_the_requester_and_statuses_to_everyone() {
+    let address = start_gateway();
+    let mut driver = connect(address);
+    let mut watcher = connect(address);
+
+    send(&mut driver, json!({"command": "ping"}));
+    let pong = wait_for(&mut driver, |message| message["command"] == "PING");
+    assert!(pong["value"]["sent_time"].as_f64().unwrap() > 0.0);
+
+    send(
+        &mut driver,
+        json!({"command": "start", "distance": 100, "reverse_brake": false}),
+    );
+    wait_for(&mut driver, |message| message["command"] == "START");
+
+    // Statuses are sent on their own during the run, to both
+    for socket in [&mut driver, &mut watcher] {
+        let status = wait_for(socket, |message| message["command"] == "STATUS");
+        assert_eq!(status["value"]["running"], true);
+    }
+
+    // Anyone can stop it, and only hears back about their own requests
+    send(&mut watcher, json!({"command": "stop"}));
+    wait_for(&mut watcher, |message| message["command"] == "STOP");
+    send(&mut driver, json!({"command": "ping"}));
+    let driver_pong = wait_for(&mut driver, |message| message["command"] == "PING");
+    send(&mut watcher, json!({"command": "ping"}));
+    let watcher_pong = wait_for(&mut watcher, |message| message["command"] != "STATUS");
+    assert_eq!(watcher_pong["command"], "PING");
+    assert!(
+        watcher_pong["metadata"]["sequence"].as_u64()
+            > driver_pong["metadata"]["sequence"].as_u64()
+    );
+}
+
+#[test]
+fn bad_requests_are_answered_with_errors() {
+    let address = start_gateway();
+    let mut socket = connect(address);
+
+    for request in [
+        json!({"command": "fly"}),
+        json!({"command": "start"}),
+        json!({"command": "start", "distance": -5}),
+        json!({"command": "stop", "now": true}),
+    ] {
+        send(&mut socket, request.clone());
+        let error = wait_for(&mut socket, |message| message.get("error").is_some());
+        assert!(error["error"].is_string(), "{request} wasn't refused");
+    }
+    socket.send(Message::Text("not json".to_owned())).unwrap();
+    wait_for(&mut socket, |message| message.get("error").is_some());
+
+    // Still connected after all that
+    send(&mut socket, json!({"command": "status"}));
+    wait_for(&mut socket, |message| message["command"] == "STATUS");
+}
@@ -0,0 +1,128 @@
+/*!
+ * Figures worked out from a run's statuses as they come in: when the car will
+ * get to the target, where it'll stop, and how fast it's gone
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{estimate::deceleration, splits::start_time};
+use bindings::{Event, StatusResponse};
+
+/***** Run analytics *****/
+
+/// What's worked out from a run up to some status
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RunAnalytics {
+    /// Seconds until the car gets to the target at its speed and deceleration,
+    /// `None` if it isn't going to (or there's no target)
+    pub time_to_target: Option<f64>,
+    /// Centimeters past the target the car is going to stop at (negative if
+    /// it's going to stop short), `None` if it isn't slowing down (or there's
+    /// no target)
+    pub projected_overshoot: Option<f64>,
+    /// Centimeters per second over the whole run so far
+    pub average_velocity: f64,
+    /// Centimeters per second
+    pub peak_velocity: f64,
+}
+impl RunAnalytics {
+    /// The analytics after the latest status, `None` if there are none
+    pub fn of(statuses: &[Event<StatusResponse>], target: Option<f64>) -> Option<Self> {
+        let start_time = start_time(statuses)?;
+        let peak_velocity = statuses
+            .iter()
+            .map(|status| status.value.distance.velocity)
+            .fold(f64::MIN, f64::max);
+        let previous = statuses.len().checked_sub(2).map(|idx| &statuses[idx]);
+        Some(Self::at(
+            previous,
+            statuses.last()?,
+            start_time,
+            peak_velocity,
+            target,
+        ))
+    }
+
+    /// The analytics after every status, for saving alongside them
+    pub fn over(statuses: &[Event<StatusResponse>], target: Option<f64>) -> Vec<Self> {
+        let Some(start_time) = start_time(statuses) else {
+            return vec![];
+        };
+        let mut peak_velocity = f64::MIN;
+        let mut previous = None;
+        statuses
+            .iter()
+            .map(|status| {
+                peak_velocity = peak_velocity.max(status.value.distance.velocity);
+                let analytics = Self::at(previous, status, start_time, peak_velocity, target);
+                previous = Some(status);
+                analytics
+            })
+            .collect()
+    }
+
+    /// The analytics at a status, going by the one before it for the
+    /// deceleration
+    fn at(
+        previous: Option<&Event<StatusResponse>>,
+        status: &Event<StatusResponse>,
+        start_time: f64,
+        peak_velocity: f64,
+        target: Option<f64>,
+    ) -> Self {
+        let distance = status.value.distance.distance;
+        let velocity = status.value.distance.velocity;
+        let elapsed = status.metadata.time - start_time;
+        let average_velocity = match elapsed > 0.0 {
+            true => distance / elapsed,
+            false => 0.0,
+        };
+        let deceleration = previous.and_then(|previous| deceleration(previous, status));
+
+        let (time_to_target, projected_overshoot) = match target {
+            Some(target) => (
+                time_to_target(target - distance, velocity, deceleration),
+                projected_stop(distance, velocity, deceleration).map(|stop| stop - target),
+            ),
+            None => (None, None),
+        };
+        Self {
+            time_to_target,
+            projected_overshoot,
+            average_velocity,
+            peak_velocity,
+        }
+    }
+}
+
+/***** Helper functions *****/
+
+/// Seconds to go `remaining` centimeters, slowing down steadily if it is
+fn time_to_target(remaining: f64, velocity: f64, deceleration: Option<f64>) -> Option<f64> {
+    if remaining <= 0.0 {
+        return Some(0.0);
+    }
+    if velocity <= 0.0 {
+        return None;
+    }
+    match deceleration {
+        // Solving remaining = velocity * t - deceleration * t^2 / 2, which has
+        // no answer if the car stops first
+        Some(deceleration) => {
+            let discriminant = velocity.powi(2) - 2.0 * deceleration * remaining;
+            (discriminant >= 0.0).then(|| (velocity - discriminant.sqrt()) / deceleration)
+        }
+        None => Some(remaining / velocity),
+    }
+}
+
+/// Where the car is going to stop if it keeps slowing down steadily
+fn projected_stop(distance: f64, velocity: f64, deceleration: Option<f64>) -> Option<f64> {
+    match velocity <= 0.0 {
+        true => Some(distance),
+        false => {
+            deceleration.map(|deceleration| distance + velocity.powi(2) / (2.0 * deceleration))
+        }
+    }
+}
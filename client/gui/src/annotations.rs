@@ -0,0 +1,165 @@
+/*!
+ * Notes drawn on the telemetry plot, like where the car clipped the lane
+ * marker, kept next to the run they're about
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::ClientError;
+#[cfg(feature = "ui")]
+use egui::{
+    plot::{LineStyle, PlotPoint, PlotUi, Polygon, Text, VLine},
+    Align2, Color32,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str as serde_from_str, to_string_pretty as serde_to_string_pretty};
+use std::{
+    fs::{read_to_string, remove_file, write},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+// Constants
+/// Added to a run's file name (without its extension) for its annotations
+pub const ANNOTATIONS_SUFFIX: &str = ".annotations.json";
+#[cfg(feature = "ui")]
+const ANNOTATION_COLOR: Color32 = Color32::from_rgb(255, 200, 0);
+
+/***** Annotations *****/
+
+/// Something noted on the telemetry plot, in seconds since the run started
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Annotation {
+    /// A line across the plot at a moment
+    Marker { time: f64, label: String },
+    /// Text at a point on the plot
+    Note { time: f64, value: f64, text: String },
+    /// A stretch of time, shaded
+    Region { start: f64, end: f64, label: String },
+}
+impl Annotation {
+    /// A one line description
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Marker { time, label } => format!("{time:.1}s: {label}"),
+            Self::Note { time, text, .. } => format!("{time:.1}s: {text}"),
+            Self::Region { start, end, label } => format!("{start:.1}s to {end:.1}s: {label}"),
+        }
+    }
+}
+
+/// What can be added by clicking on the telemetry plot
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum AnnotationTool {
+    /// Clicking does nothing
+    #[default]
+    Off,
+    Marker,
+    Note,
+    /// Clicked twice, at the start and then the end
+    Region,
+}
+impl AnnotationTool {
+    pub const ALL: [Self; 4] = [Self::Off, Self::Marker, Self::Note, Self::Region];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Off => "Annotate",
+            Self::Marker => "Marker",
+            Self::Note => "Note",
+            Self::Region => "Region",
+        }
+    }
+}
+
+/// A change to a run's annotations
+#[derive(Clone, PartialEq, Debug)]
+pub enum AnnotationEdit {
+    Add(Annotation),
+    /// By index
+    Remove(usize),
+}
+
+#[cfg(feature = "ui")]
+/// Draw annotations on a plot whose values go up to `top`
+pub fn plot_annotations(plot_ui: &mut PlotUi, annotations: &[Annotation], top: f64) {
+    for annotation in annotations.iter() {
+        match annotation {
+            Annotation::Marker { time, label } => {
+                plot_ui.vline(
+                    VLine::new(*time)
+                        .color(ANNOTATION_COLOR)
+                        .style(LineStyle::dashed_loose()),
+                );
+                plot_ui.text(
+                    Text::new(PlotPoint::new(*time, top), label.as_str())
+                        .color(ANNOTATION_COLOR)
+                        .anchor(Align2::LEFT_TOP),
+                );
+            }
+            Annotation::Note { time, value, text } => plot_ui.text(
+                Text::new(PlotPoint::new(*time, *value), text.as_str())
+                    .color(ANNOTATION_COLOR)
+                    .anchor(Align2::LEFT_BOTTOM),
+            ),
+            Annotation::Region { start, end, label } => {
+                plot_ui.polygon(
+                    Polygon::new(vec![[*start, 0.0], [*end, 0.0], [*end, top], [*start, top]])
+                        .color(ANNOTATION_COLOR)
+                        .width(0.0),
+                );
+                plot_ui.text(
+                    Text::new(PlotPoint::new(*start, top), label.as_str())
+                        .color(ANNOTATION_COLOR)
+                        .anchor(Align2::LEFT_TOP),
+                );
+            }
+        }
+    }
+}
+
+/***** Persistence *****/
+
+/// Where a run's annotations are kept, e.g. `run.annotations.json` for
+/// `run.csv`
+pub fn annotations_path(run_path: &Path) -> PathBuf {
+    let stem = run_path.file_stem().unwrap_or_default().to_string_lossy();
+    run_path.with_file_name(format!("{stem}{ANNOTATIONS_SUFFIX}"))
+}
+
+/// Keep a run's annotations next to it, removing any old ones if there are
+/// none now
+pub fn write_annotations(run_path: &Path, annotations: &[Annotation]) -> Result<(), ClientError> {
+    let path = annotations_path(run_path);
+    if annotations.is_empty() {
+        return match remove_file(&path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(ClientError::Annotations(format!(
+                "Failed to remove {}: {e}",
+                path.display()
+            ))),
+            _ => Ok(()),
+        };
+    }
+
+    let json =
+        serde_to_string_pretty(annotations).map_err(|e| ClientError::Annotations(e.to_string()))?;
+    write(&path, json)
+        .map_err(|e| ClientError::Annotations(format!("Failed to write {}: {e}", path.display())))
+}
+
+/// The annotations kept next to a run, if there are any
+pub fn read_annotations(run_path: &Path) -> Result<Vec<Annotation>, ClientError> {
+    let path = annotations_path(run_path);
+    match read_to_string(&path) {
+        Ok(json) => serde_from_str(&json).map_err(|e| {
+            ClientError::Annotations(format!("Failed to read {}: {e}", path.display()))
+        }),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(ClientError::Annotations(format!(
+            "Failed to read {}: {e}",
+            path.display()
+        ))),
+    }
+}
@@ -0,0 +1,40 @@
+/*!
+ * Arming before a run, so one accidental tap on START doesn't launch the car
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use std::time::{Duration, Instant};
+
+/***** Arming *****/
+
+/// Whether START was pressed once and is waiting to be confirmed
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Arming {
+    #[default]
+    Disarmed,
+    /// The start is only sent if it's confirmed before `until`
+    Armed { until: Instant },
+}
+impl Arming {
+    /// Arm, waiting on a confirmation for `countdown`
+    pub fn armed(now: Instant, countdown: Duration) -> Self {
+        Self::Armed {
+            until: now + countdown,
+        }
+    }
+
+    /// How long is left to confirm, or `None` if disarmed or out of time
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        match *self {
+            Self::Disarmed => None,
+            Self::Armed { until } => (now < until).then(|| until - now),
+        }
+    }
+
+    /// Whether the countdown ran out without being confirmed
+    pub fn expired(&self, now: Instant) -> bool {
+        matches!(self, Self::Armed { .. }) && self.remaining(now).is_none()
+    }
+}
@@ -0,0 +1,259 @@
+/*!
+ * Spawning and supervising the serial to bluetooth bridge from the GUI
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{ArqTransport, ClientError, LinkEnd, SerialTransport, Transport, NOTIFY_VARIABLES};
+use serialport::SerialPort;
+use std::{
+    fmt::Display,
+    io::{BufRead, BufReader, Read},
+    path::PathBuf,
+    process::{Child, Command as ProcessCommand, ExitStatus, Stdio},
+    sync::mpsc::{channel, Receiver, Sender},
+    thread::spawn,
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+use vserial::HeldSlave;
+#[cfg(target_os = "linux")]
+use vserial::PtyPair;
+
+// Constants
+/// How many lines of bridge output to keep around
+pub const BRIDGE_OUTPUT_LINES: usize = 200;
+/// How long to wait before automatically restarting a bridge that exited
+pub const BRIDGE_RESTART_COOLDOWN: Duration = Duration::from_secs(2);
+
+/***** Bridge process *****/
+
+/// The state of the bridge process
+pub enum BridgeState {
+    Running(u32),
+    Exited(ExitStatus),
+    Stopped,
+}
+impl Display for BridgeState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Running(pid) => write!(f, "Running (PID {pid})"),
+            Self::Exited(status) => write!(f, "Exited ({status})"),
+            Self::Stopped => write!(f, "Stopped"),
+        }
+    }
+}
+
+/// A serial to bluetooth bridge spawned and supervised by the GUI
+///
+/// The pseudo-terminal pair is created here instead of with `socat`: the GUI
+/// talks over the master side, and the bridge is handed the slave side's path.
+/// The slave side is held open for as long as this lives, so the pair
+/// survives the bridge restarting.
+pub struct BridgeProcess {
+    pub binary: PathBuf,
+    pub state: BridgeState,
+    pub auto_restart: bool,
+    pub restarts: usize,
+    pub output: Vec<String>,
+    slave: HeldSlave,
+    child: Option<Child>,
+    exited_at: Option<Instant>,
+    output_tx: Sender<String>,
+    output_rx: Receiver<String>,
+}
+impl BridgeProcess {
+    /// Create the pseudo-terminal pair and spawn the bridge on it, returning
+    /// the supervisor alongside the master side for the GUI to use, which
+    /// waits up to the timeout for writes and asks again for frames lost on
+    /// the way to and from the car
+    pub fn spawn(
+        binary: PathBuf,
+        timeout: Duration,
+    ) -> Result<(Self, Box<dyn Transport>), ClientError> {
+        let (mut master, slave) = open_pty()?;
+        master
+            .set_timeout(timeout)
+            .map_err(|e| ClientError::Serial(e.to_string()))?;
+
+        let (output_tx, output_rx) = channel();
+        let mut bridge = Self {
+            binary,
+            state: BridgeState::Stopped,
+            auto_restart: true,
+            restarts: 0_usize,
+            output: Vec::with_capacity(BRIDGE_OUTPUT_LINES),
+            slave,
+            child: None,
+            exited_at: None,
+            output_tx,
+            output_rx,
+        };
+        bridge.start()?;
+
+        let transport = SerialTransport::new(master);
+        Ok((
+            bridge,
+            Box::new(ArqTransport::new(Box::new(transport), LinkEnd::Client)),
+        ))
+    }
+
+    /// Start the bridge process if it isn't running already
+    pub fn start(&mut self) -> Result<(), ClientError> {
+        if self.child.is_some() {
+            return Ok(());
+        }
+
+        let mut command = ProcessCommand::new(&self.binary);
+        // Otherwise systemd would hear from the bridge as if it were the GUI
+        for variable in NOTIFY_VARIABLES {
+            command.env_remove(variable);
+        }
+        let mut child = command
+            .arg(&self.slave.path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                ClientError::Bluetooth(format!(
+                    "Failed to spawn the bridge `{}`: {e}",
+                    self.binary.display()
+                ))
+            })?;
+
+        // Forward everything the bridge prints
+        if let Some(stdout) = child.stdout.take() {
+            Self::forward_output(stdout, self.output_tx.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            Self::forward_output(stderr, self.output_tx.clone());
+        }
+
+        self.state = BridgeState::Running(child.id());
+        info!(binary = %self.binary.display(), state = %self.state, "Started the bridge");
+        self.child = Some(child);
+        self.exited_at = None;
+        self.push_output(format!(
+            "=== Started bridge on {} ({})",
+            self.slave.path, self.state
+        ));
+
+        Ok(())
+    }
+
+    /// Stop the bridge process
+    pub fn stop(&mut self) -> Result<(), ClientError> {
+        self.auto_restart = false;
+        if let Some(mut child) = self.child.take() {
+            child
+                .kill()
+                .map_err(|e| ClientError::Bluetooth(format!("Failed to kill the bridge: {e}")))?;
+            let _ = child.wait();
+            self.push_output("=== Stopped bridge".to_owned());
+        }
+        self.state = BridgeState::Stopped;
+
+        Ok(())
+    }
+
+    /// Stop then start the bridge process
+    pub fn restart(&mut self) -> Result<(), ClientError> {
+        self.stop()?;
+        self.auto_restart = true;
+        self.restarts += 1;
+        self.start()
+    }
+
+    /// Collect new output and check if the bridge is still alive, restarting
+    /// it if it has exited and that is desired
+    ///
+    /// This is meant to be called every frame
+    pub fn poll(&mut self) -> Result<(), ClientError> {
+        while let Ok(line) = self.output_rx.try_recv() {
+            info!(target: "bridge", "{line}");
+            self.push_output(line);
+        }
+
+        if let Some(child) = &mut self.child {
+            if let Some(status) = child
+                .try_wait()
+                .map_err(|e| ClientError::Bluetooth(e.to_string()))?
+            {
+                self.child = None;
+                self.state = BridgeState::Exited(status);
+                self.exited_at = Some(Instant::now());
+                warn!("The bridge exited with {status}");
+                self.push_output(format!("=== Bridge exited with {status}"));
+                if !self.auto_restart {
+                    return Err(ClientError::Bluetooth(format!(
+                        "The bridge exited with {status}"
+                    )));
+                }
+            }
+        }
+
+        if let Some(exited_at) = self.exited_at {
+            if self.auto_restart && exited_at.elapsed() > BRIDGE_RESTART_COOLDOWN {
+                // Wait out the cooldown again if this fails
+                self.exited_at = Some(Instant::now());
+                self.restarts += 1;
+                self.start()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn push_output(&mut self, line: String) {
+        if self.output.len() >= BRIDGE_OUTPUT_LINES {
+            self.output.remove(0);
+        }
+        self.output.push(line);
+    }
+
+    fn forward_output<R>(reader: R, output_tx: Sender<String>)
+    where
+        R: Read + Send + 'static,
+    {
+        spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                match line {
+                    // The GUI going away is the only reason this would fail
+                    Ok(line) => {
+                        if output_tx.send(line).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+    }
+}
+impl Drop for BridgeProcess {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+/***** Helper functions *****/
+
+/// Create the pseudo-terminal pair, returning the master side and the slave
+/// side to hold open
+#[cfg(target_os = "linux")]
+fn open_pty() -> Result<(Box<dyn SerialPort>, HeldSlave), ClientError> {
+    let (master, slave) = PtyPair::open()
+        .map_err(|e| ClientError::Serial(e.to_string()))?
+        .into_master();
+    Ok((Box::new(master), slave))
+}
+
+/// The bridge needs BlueZ, so there's no point in a pseudo-terminal for it
+#[cfg(not(target_os = "linux"))]
+fn open_pty() -> Result<(Box<dyn SerialPort>, HeldSlave), ClientError> {
+    Err(ClientError::Bluetooth(
+        "The bridge needs BlueZ, which is only on Linux".to_owned(),
+    ))
+}
@@ -0,0 +1,87 @@
+/*!
+ * Calibrating the wheel from how far the car was measured to have really
+ * gone, so the odometer stops over or undercounting from slipping wheels and
+ * worn tires
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::CalibrateResponse;
+use egui::{Button, Context, DragValue, Window};
+
+// Constants
+/// Centimeters, the furthest a run is measured to have gone
+const MAX_MEASURED_DISTANCE: f64 = 10_000.0;
+
+/***** Calibration window *****/
+
+/// A window walking through calibrating the wheel against the last run
+pub struct CalibrationWindow {
+    pub open: bool,
+    /// Centimeters the car was measured to have gone on the last run, from
+    /// where it started to where it stopped
+    pub measured_distance: f64,
+}
+impl Default for CalibrationWindow {
+    fn default() -> Self {
+        Self {
+            open: true,
+            measured_distance: 0.0,
+        }
+    }
+}
+impl CalibrationWindow {
+    /// Show the window, returning the measured distance once it's to be sent
+    ///
+    /// `counted` is how far the odometer made the last run out to be, if it
+    /// was run here, and `calibration` is what the car last answered with.
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        counted: Option<f64>,
+        running: bool,
+        calibration: Option<&CalibrateResponse>,
+    ) -> Option<f64> {
+        let mut calibrate = false;
+        let mut open = self.open;
+        Window::new("Calibrate the wheel")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    "Run the car a known distance, measure how far it really went from where \
+                     it started, and enter that here",
+                );
+                if let Some(counted) = counted {
+                    ui.label(format!("The odometer counted {counted:.1}cm"));
+                }
+                ui.horizontal(|ui| {
+                    ui.add(
+                        DragValue::new(&mut self.measured_distance)
+                            .clamp_range(0.0..=MAX_MEASURED_DISTANCE)
+                            .speed(0.5)
+                            .max_decimals(1)
+                            .suffix("cm measured"),
+                    );
+                    calibrate = ui
+                        .add_enabled(
+                            !running && self.measured_distance > 0.0,
+                            Button::new("Send"),
+                        )
+                        .on_disabled_hover_text("Wait for the run to finish, and enter a distance")
+                        .clicked();
+                });
+                if let Some(calibration) = calibration {
+                    ui.label(format!(
+                        "Wheel circumference: {:.2}cm, was {:.2}cm",
+                        calibration.wheel_circumference, calibration.previous_wheel_circumference
+                    ));
+                }
+            });
+
+        self.open = open;
+        calibrate.then_some(self.measured_distance)
+    }
+}
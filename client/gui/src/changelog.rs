@@ -0,0 +1,172 @@
+/*!
+ * What's new in each version, from the changelog built into the GUI, shown
+ * once per version so new (especially safety) features don't go unnoticed
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::ClientError;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{create_dir_all, read_to_string, write},
+    path::{Path, PathBuf},
+};
+
+// Constants
+/// `changelog.toml`, built in so it always matches the version running
+pub const CHANGELOG: &str = include_str!("../changelog.toml");
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// The last version whose notes were seen, inside of the XDG config
+/// directory, next to the config
+pub const WHATS_NEW_FILE_NAME: &str = "chemistry-car-controller/whats-new.toml";
+
+/***** Changelog *****/
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Note {
+    pub title: String,
+    /// What it does and where to find it
+    pub details: String,
+    /// Listed first
+    #[serde(default)]
+    pub safety: bool,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Release {
+    pub version: String,
+    pub notes: Vec<Note>,
+}
+impl Release {
+    /// The notes with the safety ones first, otherwise as they're written
+    pub fn sorted_notes(&self) -> Vec<&Note> {
+        let mut notes = self.notes.iter().collect::<Vec<_>>();
+        notes.sort_by_key(|note| !note.safety);
+        notes
+    }
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Changelog {
+    /// Newest first
+    pub releases: Vec<Release>,
+}
+impl Changelog {
+    pub fn parse(contents: &str) -> Result<Self, ClientError> {
+        toml::from_str(contents)
+            .map_err(|e| ClientError::Changelog(e.to_string().trim_end().to_owned()))
+    }
+
+    /// The changelog built in
+    pub fn built_in() -> Result<Self, ClientError> {
+        Self::parse(CHANGELOG)
+    }
+
+    /// The releases after the one last seen, newest first, or only the
+    /// newest if none was ever seen, as everything's new then
+    pub fn since(&self, seen: Option<&str>) -> Vec<Release> {
+        match seen {
+            Some(seen) => self
+                .releases
+                .iter()
+                .take_while(|release| release.version != seen)
+                .cloned()
+                .collect(),
+            None => self.releases.iter().take(1_usize).cloned().collect(),
+        }
+    }
+}
+
+/***** What's new *****/
+
+/// What's saved to `whats-new.toml`
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct WhatsNewFile {
+    seen_version: Option<String>,
+}
+
+/// The notes to show, and the last version whose notes were seen so they're
+/// only shown once
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct WhatsNew {
+    pub seen_version: Option<String>,
+    /// Where the version seen is saved, or `None` to only keep it in memory
+    pub path: Option<PathBuf>,
+    /// Being shown, newest first, or nothing if the window's closed
+    pub releases: Vec<Release>,
+}
+impl WhatsNew {
+    /// `whats-new.toml` in the XDG config directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|config_dir| config_dir.join(WHATS_NEW_FILE_NAME))
+    }
+
+    /// Load the version seen at a path, if any
+    ///
+    /// Like the maintenance counters, anything wrong with the file is handed
+    /// back alongside nothing having been seen.
+    pub fn load(path: Option<PathBuf>) -> (Self, Option<ClientError>) {
+        let Some(path) = path else {
+            return (Self::default(), None);
+        };
+        let (file, error) = match path.exists() {
+            true => match Self::read(&path) {
+                Ok(file) => (file, None),
+                Err(e) => (WhatsNewFile::default(), Some(e)),
+            },
+            false => (WhatsNewFile::default(), None),
+        };
+        let whats_new = Self {
+            seen_version: file.seen_version,
+            path: Some(path),
+            releases: vec![],
+        };
+        (whats_new, error)
+    }
+
+    fn read(path: &Path) -> Result<WhatsNewFile, ClientError> {
+        let contents = read_to_string(path).map_err(|e| {
+            ClientError::Changelog(format!("Failed to read {}: {e}", path.display()))
+        })?;
+        toml::from_str(&contents)
+            .map_err(|e| ClientError::Changelog(e.to_string().trim_end().to_owned()))
+    }
+
+    /// Show what's new since the version last seen, unless it's this one
+    pub fn check(&mut self, changelog: &Changelog) {
+        if self.seen_version.as_deref() != Some(VERSION) {
+            self.releases = changelog.since(self.seen_version.as_deref());
+        }
+    }
+
+    /// The notes were read, so don't show them again until the next version
+    pub fn seen(&mut self) -> Result<(), ClientError> {
+        self.releases.clear();
+        self.seen_version = Some(VERSION.to_owned());
+        self.save()
+    }
+
+    /// Save the version seen, if there's a path
+    pub fn save(&self) -> Result<(), ClientError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = WhatsNewFile {
+            seen_version: self.seen_version.clone(),
+        };
+        let contents =
+            toml::to_string_pretty(&file).map_err(|e| ClientError::Changelog(e.to_string()))?;
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).map_err(|e| {
+                ClientError::Changelog(format!("Failed to create {}: {e}", parent.display()))
+            })?;
+        }
+        write(path, contents)
+            .map_err(|e| ClientError::Changelog(format!("Failed to write {}: {e}", path.display())))
+    }
+}
@@ -0,0 +1,179 @@
+/*!
+ * Telling the time: times are kept as Unix times (which are UTC) and only put
+ * in a time zone to be shown, and the clocks are checked against each other
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::shared::*;
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/***** Time *****/
+
+/// The current Unix time in seconds
+pub fn unix_now() -> f64 {
+    (Utc::now().timestamp_millis() as f64) / 1000.0
+}
+
+/// A Unix time in seconds as a date and time, if it's in range
+pub fn utc_from_unix(unix_time: f64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_millis_opt((unix_time * 1000.0).round() as i64)
+        .single()
+}
+
+/***** Time zones *****/
+
+/// The time zone times are shown in, written in the config as `"local"`,
+/// `"UTC"`, or an offset from UTC like `"+02:00"`
+///
+/// A Pi that was never set up is in UTC whatever its local time zone should
+/// be, so the time zone can be set rather than going by the computer's.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(try_from = "String", into = "String")]
+pub enum DisplayTimezone {
+    #[default]
+    Local,
+    Utc,
+    Fixed(FixedOffset),
+}
+impl DisplayTimezone {
+    /// Format a time in this time zone, e.g. with `"%H:%M:%S"`
+    pub fn format(&self, time: DateTime<Utc>, format: &str) -> String {
+        match self {
+            Self::Local => time.with_timezone(&Local).format(format).to_string(),
+            Self::Utc => time.format(format).to_string(),
+            Self::Fixed(offset) => time.with_timezone(offset).format(format).to_string(),
+        }
+    }
+
+    /// Format a Unix time in seconds in this time zone, with `?` for times out
+    /// of range
+    pub fn format_unix(&self, unix_time: f64, format: &str) -> String {
+        match utc_from_unix(unix_time) {
+            Some(time) => self.format(time, format),
+            None => "?".to_owned(),
+        }
+    }
+}
+impl FromStr for DisplayTimezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("local") {
+            return Ok(Self::Local);
+        }
+        if s.eq_ignore_ascii_case("utc") || s == "Z" {
+            return Ok(Self::Utc);
+        }
+        parse_offset(s).map(Self::Fixed).ok_or_else(|| {
+            format!("`{s}` isn't a time zone, use \"local\", \"UTC\", or an offset like \"+02:00\"")
+        })
+    }
+}
+impl fmt::Display for DisplayTimezone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Local => write!(f, "local"),
+            Self::Utc => write!(f, "UTC"),
+            Self::Fixed(offset) => {
+                let seconds = offset.local_minus_utc();
+                let sign = match seconds < 0 {
+                    true => '-',
+                    false => '+',
+                };
+                let seconds = seconds.abs();
+                write!(f, "{sign}{:02}:{:02}", seconds / 3600, seconds % 3600 / 60)
+            }
+        }
+    }
+}
+impl TryFrom<String> for DisplayTimezone {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+impl From<DisplayTimezone> for String {
+    fn from(value: DisplayTimezone) -> Self {
+        value.to_string()
+    }
+}
+
+/// An offset from UTC like `+02:00`, `-05`, or `+0530`
+fn parse_offset(s: &str) -> Option<FixedOffset> {
+    let (sign, rest) = match (s.strip_prefix('+'), s.strip_prefix('-')) {
+        (Some(rest), _) => (1, rest),
+        (_, Some(rest)) => (-1, rest),
+        _ => return None,
+    };
+    if !rest.is_ascii() {
+        return None;
+    }
+    let (hours, minutes) = match rest.split_once(':') {
+        Some(split) => split,
+        None if rest.len() == 4 => rest.split_at(2),
+        None => (rest, "0"),
+    };
+    let hours = hours.parse::<u8>().ok().filter(|hours| *hours <= 23)?;
+    let minutes = minutes.parse::<u8>().ok().filter(|minutes| *minutes < 60)?;
+    FixedOffset::east_opt(sign * (hours as i32 * 3600 + minutes as i32 * 60))
+}
+
+/***** Clock check *****/
+
+/// The times of a ping, which tell how far apart the clocks are
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PingTimes {
+    /// Our Unix time when the ping was sent
+    pub sent: f64,
+    /// The car's Unix time when it answered
+    pub car: f64,
+    /// Our Unix time when the answer came back
+    pub received: f64,
+}
+impl PingTimes {
+    /// Seconds the car's clock is ahead of ours (behind if negative), taking
+    /// the car to have answered halfway through the round trip
+    pub fn clock_offset(&self) -> f64 {
+        self.car - (self.sent + self.received) / 2.0
+    }
+}
+
+/// Whether a Unix time is from a clock that was never set, like a Pi's
+/// without a network or a real time clock
+pub fn clock_unset(unix_time: f64) -> bool {
+    unix_time < MIN_PLAUSIBLE_UNIX_TIME
+}
+
+/// Why the times can't be trusted, if the clocks look wrong
+///
+/// Our clock is checked against [`MIN_PLAUSIBLE_UNIX_TIME`], and the car's
+/// against ours once it's been pinged.
+pub fn clock_warning(now: f64, ping: Option<PingTimes>, max_offset: f64) -> Option<String> {
+    if clock_unset(now) {
+        return Some(format!(
+            "This computer's clock appears wrong (it says {}), set it so times are right",
+            DisplayTimezone::Utc.format_unix(now, "%Y-%m-%d")
+        ));
+    }
+    let ping = ping?;
+    if clock_unset(ping.car) {
+        return Some("The car's clock appears wrong, it was never set".to_owned());
+    }
+    let offset = ping.clock_offset();
+    (offset.abs() > max_offset).then(|| {
+        format!(
+            "The car's clock appears wrong, it's {:.1}s {} this computer's",
+            offset.abs(),
+            match offset > 0.0 {
+                true => "ahead of",
+                false => "behind",
+            }
+        )
+    })
+}
@@ -0,0 +1,381 @@
+/*!
+ * Settings loaded from a TOML file, falling back to the defaults in `shared`
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{
+    clock::DisplayTimezone, logging::LogLevel, maintenance::ServiceReminder, shared::*, theme::*,
+};
+use bindings::{ClientError, BAUD_RATE};
+use serde::{Deserialize, Serialize};
+use smart_default::SmartDefault;
+use std::{
+    fs::{create_dir_all, read_to_string, write},
+    path::{Path, PathBuf},
+};
+
+// Constants
+/// The config file inside of the XDG config directory
+pub const CONFIG_FILE_NAME: &str = "chemistry-car-controller/config.toml";
+
+/***** Config *****/
+
+/// Everything that can be set from the config file, e.g.
+///
+/// ```toml
+/// [serial]
+/// usb_vid = 0x2341
+/// description = "Arduino"
+/// baud_rate = 115200
+///
+/// [car]
+/// number_of_magnets = 2
+/// ```
+///
+/// Anything left out is the default
+#[derive(Serialize, Deserialize, SmartDefault, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub serial: SerialConfig,
+    pub run: RunConfig,
+    pub screen: ScreenConfig,
+    pub car: CarConfig,
+    pub time: TimeConfig,
+    pub log: LogConfig,
+    pub maintenance: MaintenanceConfig,
+    pub storage: StorageConfig,
+}
+
+#[derive(Serialize, Deserialize, SmartDefault, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct SerialConfig {
+    /// Used when no serial port is given on the command line
+    pub port: Option<String>,
+    /// Without a port, the first USB serial port matching all of these is
+    /// used (or the first USB serial port at all if none are set)
+    pub usb_vid: Option<u16>,
+    pub usb_pid: Option<u16>,
+    /// Part of the USB product or manufacturer name, ignoring case
+    pub description: Option<String>,
+    #[default(BAUD_RATE)]
+    pub baud_rate: u32,
+    /// Seconds between reading from the serial connection
+    #[default(SERIAL_DELAY_TIME)]
+    pub poll_interval: f64,
+    /// Seconds to wait for a response before sending a request again
+    #[default(REQUEST_TIMEOUT_SECONDS)]
+    pub request_timeout: f64,
+    #[default(MAX_REQUEST_ATTEMPTS)]
+    pub max_request_attempts: usize,
+    /// The longest (in seconds) to throw away partial frames and boot
+    /// messages left on the connection for after connecting, or 0 to not
+    #[default(DEFAULT_SYNC_TIMEOUT)]
+    pub sync_timeout: f64,
+}
+
+#[derive(Serialize, Deserialize, SmartDefault, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct RunConfig {
+    /// The most centimeters the distance slider goes up to
+    #[default(MAX_DISTANCE_RANGE_CENTIMETERS)]
+    pub max_distance: f64,
+    /// Seconds to confirm a start in once START is pressed, or 0 to start
+    /// straight away
+    #[default(DEFAULT_ARMING_COUNTDOWN)]
+    pub arming_countdown: f64,
+    /// Seconds without a status mid run before the car is stopped, or 0 to
+    /// never stop it
+    #[default(DEFAULT_STATUS_WATCHDOG)]
+    pub status_watchdog: f64,
+    /// Whether to guess where the car is (and show it as a guess) once
+    /// statuses stop coming in mid run, until they come back
+    #[default = true]
+    pub estimate_position: bool,
+    /// Seconds after a run finalizes and is saved to reset for the next one,
+    /// or 0 to wait for RESET
+    #[default(DEFAULT_AUTO_RESET)]
+    pub auto_reset: f64,
+    /// How many statuses of a run are kept in memory before they're
+    /// downsampled, with every one of them spilled to the data directory
+    #[default(DEFAULT_STATUS_HISTORY)]
+    pub status_history: usize,
+}
+
+#[derive(Serialize, Deserialize, SmartDefault, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScreenConfig {
+    #[default(WIDTH)]
+    pub width: f32,
+    #[default(HEIGHT)]
+    pub height: f32,
+    /// `"metric"` or `"imperial"`, for distances and speeds
+    pub units: Units,
+    /// `"dark"`, `"light"`, or `"high-contrast"`
+    pub theme: Theme,
+    /// `"standard"`, `"kiosk"` for bigger touch targets, or `"operator"` for
+    /// only the essentials
+    pub layout: ScreenLayout,
+    /// The most rows shown in the expanded status table (newest first), or 0
+    /// to show every one
+    #[default(DEFAULT_TABLE_ROWS)]
+    pub table_rows: usize,
+}
+
+/// What the car is assumed to have when it doesn't say itself
+#[derive(Serialize, Deserialize, SmartDefault, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct CarConfig {
+    #[default(DEFAULT_NUMBER_OF_MAGNETS)]
+    pub number_of_magnets: usize,
+    /// Centimeters
+    #[default(DEFAULT_WHEEL_DIAMETER)]
+    pub wheel_diameter: f64,
+    /// Centimeters per second squared the car slows down by once power is
+    /// cut, as measured by the command line client's `coast` routine, for the
+    /// server to cut power early enough to coast to the distance
+    pub coast_deceleration: Option<f64>,
+    /// Percent of charge left the battery is warned about under, for a car
+    /// that says how much is left
+    #[default(DEFAULT_LOW_BATTERY_CHARGE)]
+    pub low_battery_charge: f64,
+    /// Volts the battery is warned about under, e.g. for a car that doesn't
+    /// say how much charge is left
+    pub low_battery_voltage: Option<f64>,
+}
+
+/// How times are shown, e.g.
+///
+/// ```toml
+/// [time]
+/// timezone = "+02:00"
+/// ```
+#[derive(Serialize, Deserialize, SmartDefault, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimeConfig {
+    /// `"local"`, `"UTC"`, or an offset from UTC
+    pub timezone: DisplayTimezone,
+    /// Seconds the car's clock can be off from ours before it's warned about
+    #[default(DEFAULT_MAX_CLOCK_OFFSET)]
+    pub max_clock_offset: f64,
+}
+
+/// What's logged, e.g.
+///
+/// ```toml
+/// [log]
+/// level = "debug"
+/// ```
+#[derive(Serialize, Deserialize, SmartDefault, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// `"error"`, `"warn"`, `"info"`, `"debug"`, or `"trace"`, with
+    /// `RUST_LOG` taking precedence
+    pub level: LogLevel,
+    /// How many of the most recent events are kept for the logs panel
+    #[default(DEFAULT_LOG_EVENTS)]
+    pub events: usize,
+}
+
+/// What to remind about looking after the car, e.g.
+///
+/// ```toml
+/// [[maintenance.reminders]]
+/// task = "Check the battery terminals"
+/// every_runs = 50
+/// ```
+///
+/// Reminders that are due are shown when the GUI starts.
+#[derive(Serialize, Deserialize, SmartDefault, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct MaintenanceConfig {
+    /// Inspecting the wheels every 2km unless it's set
+    #[default(ServiceReminder::defaults())]
+    pub reminders: Vec<ServiceReminder>,
+}
+
+/// How much room has to be left for logging, e.g.
+///
+/// ```toml
+/// [storage]
+/// min_free_mb = 500
+/// prune = false
+/// ```
+///
+/// It's checked at startup and before every run.
+#[derive(Serialize, Deserialize, SmartDefault, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    /// Megabytes free in the data directory below which it's warned about,
+    /// or 0 to never warn
+    #[default(DEFAULT_MIN_FREE_MB)]
+    pub min_free_mb: u64,
+    /// Whether to delete the oldest automatically named runs to get back
+    /// above it
+    #[default(true)]
+    pub prune: bool,
+}
+
+impl Config {
+    /// `config.toml` in the XDG config directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|config_dir| config_dir.join(CONFIG_FILE_NAME))
+    }
+
+    /// Load the config from a file given on the command line, or else the
+    /// default path
+    ///
+    /// Anything wrong with it is handed back alongside the defaults, so the
+    /// GUI can still start and show it. Not having a config file at the
+    /// default path is fine.
+    pub fn load(path: Option<PathBuf>) -> (Self, Option<ClientError>) {
+        let path = match path {
+            Some(path) => path,
+            None => match Self::default_path() {
+                Some(path) if path.exists() => path,
+                _ => return (Self::default(), None),
+            },
+        };
+        match Self::read(&path) {
+            Ok(config) => (config, None),
+            Err(e) => (Self::default(), Some(e)),
+        }
+    }
+
+    /// Read and validate a config file
+    pub fn read(path: &Path) -> Result<Self, ClientError> {
+        let contents = read_to_string(path)
+            .map_err(|e| ClientError::Config(format!("Failed to read {}: {e}", path.display())))?;
+        Self::parse(&contents)
+    }
+
+    /// Write the config back out, e.g. after it's been tuned from the
+    /// settings window
+    ///
+    /// Comments in the file aren't kept.
+    pub fn write(&self, path: &Path) -> Result<(), ClientError> {
+        let contents =
+            toml::to_string_pretty(self).map_err(|e| ClientError::Config(e.to_string()))?;
+        if let Some(directory) = path.parent() {
+            create_dir_all(directory).map_err(|e| {
+                ClientError::Config(format!("Failed to create {}: {e}", directory.display()))
+            })?;
+        }
+        write(path, contents)
+            .map_err(|e| ClientError::Config(format!("Failed to write {}: {e}", path.display())))
+    }
+
+    /// Parse and validate a config
+    pub fn parse(contents: &str) -> Result<Self, ClientError> {
+        let config = toml::from_str::<Self>(contents)
+            .map_err(|e| ClientError::Config(e.to_string().trim_end().to_owned()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check every value makes sense, listing everything that doesn't
+    pub fn validate(&self) -> Result<(), ClientError> {
+        let mut problems = vec![];
+        let mut check = |valid: bool, problem: &str| {
+            if !valid {
+                problems.push(problem.to_owned());
+            }
+        };
+
+        check(self.serial.baud_rate > 0, "serial.baud_rate must be over 0");
+        check(
+            self.serial.poll_interval > 0.0 && self.serial.poll_interval.is_finite(),
+            "serial.poll_interval must be over 0 seconds",
+        );
+        check(
+            self.serial.request_timeout > 0.0 && self.serial.request_timeout.is_finite(),
+            "serial.request_timeout must be over 0 seconds",
+        );
+        check(
+            self.serial.max_request_attempts > 0,
+            "serial.max_request_attempts must be at least 1",
+        );
+        check(
+            self.serial.sync_timeout >= 0.0 && self.serial.sync_timeout.is_finite(),
+            "serial.sync_timeout must be 0 seconds or more",
+        );
+        check(
+            self.run.max_distance > 0.0 && self.run.max_distance.is_finite(),
+            "run.max_distance must be over 0 centimeters",
+        );
+        check(
+            self.run.arming_countdown >= 0.0 && self.run.arming_countdown.is_finite(),
+            "run.arming_countdown must be 0 seconds or more",
+        );
+        check(
+            self.run.status_watchdog >= 0.0 && self.run.status_watchdog.is_finite(),
+            "run.status_watchdog must be 0 seconds or more",
+        );
+        check(
+            self.run.auto_reset >= 0.0 && self.run.auto_reset.is_finite(),
+            "run.auto_reset must be 0 seconds or more",
+        );
+        check(
+            self.run.status_history >= MIN_STATUS_HISTORY,
+            &format!("run.status_history must be at least {MIN_STATUS_HISTORY}"),
+        );
+        check(
+            self.screen.table_rows <= MAX_TABLE_ROWS,
+            &format!("screen.table_rows must be at most {MAX_TABLE_ROWS}"),
+        );
+        check(
+            self.screen.width >= MIN_SCREEN_SIZE[0] && self.screen.height >= MIN_SCREEN_SIZE[1],
+            &format!(
+                "screen must be at least {}x{}",
+                MIN_SCREEN_SIZE[0], MIN_SCREEN_SIZE[1]
+            ),
+        );
+        check(
+            self.car.number_of_magnets > 0,
+            "car.number_of_magnets must be at least 1",
+        );
+        check(
+            self.car.wheel_diameter > 0.0 && self.car.wheel_diameter.is_finite(),
+            "car.wheel_diameter must be over 0 centimeters",
+        );
+        check(
+            self.car
+                .coast_deceleration
+                .is_none_or(|deceleration| deceleration > 0.0 && deceleration.is_finite()),
+            "car.coast_deceleration must be over 0 centimeters per second squared",
+        );
+        check(
+            (0.0..=100.0).contains(&self.car.low_battery_charge),
+            "car.low_battery_charge must be between 0 and 100 percent",
+        );
+        check(
+            self.car
+                .low_battery_voltage
+                .is_none_or(|voltage| voltage > 0.0 && voltage.is_finite()),
+            "car.low_battery_voltage must be over 0 volts",
+        );
+        check(
+            self.time.max_clock_offset > 0.0 && self.time.max_clock_offset.is_finite(),
+            "time.max_clock_offset must be over 0 seconds",
+        );
+        check(
+            self.log.events > 0 && self.log.events <= MAX_LOG_EVENTS,
+            &format!("log.events must be between 1 and {MAX_LOG_EVENTS}"),
+        );
+        for reminder in &self.maintenance.reminders {
+            check(
+                reminder.valid(),
+                &format!(
+                    "maintenance.reminders \"{}\" needs a task, and an every_distance, every_motor_hours, or every_runs over 0",
+                    reminder.task
+                ),
+            );
+        }
+
+        match problems.is_empty() {
+            true => Ok(()),
+            false => Err(ClientError::Config(problems.join("; "))),
+        }
+    }
+}
@@ -0,0 +1,163 @@
+/*!
+ * The managed data directory where runs are automatically saved
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{annotations::annotations_path, export::RunFormat, shared::*};
+use bindings::ClientError;
+use chrono::Local;
+use std::{
+    fs::{create_dir_all, metadata, read_dir, read_to_string, remove_file},
+    path::{Path, PathBuf},
+};
+
+/***** Data directory *****/
+
+/// A directory of runs named like `2024-05-12_run03_500cm.csv`, in any format
+/// runs can be exported as
+pub struct DataDirectory {
+    pub path: PathBuf,
+}
+impl DataDirectory {
+    /// Use the fixed directory on the Pi and the XDG data directory elsewhere,
+    /// creating it if needed
+    pub fn new() -> Result<Self, ClientError> {
+        let path = match is_raspberry_pi() {
+            true => PathBuf::from(PI_DATA_DIRECTORY),
+            false => dirs::data_dir()
+                .ok_or_else(|| {
+                    ClientError::DataDirectory("No data directory for this user".to_owned())
+                })?
+                .join(DATA_DIRECTORY_NAME),
+        };
+        Self::at(path)
+    }
+
+    /// Use a specific directory, creating it if needed
+    pub fn at(path: PathBuf) -> Result<Self, ClientError> {
+        create_dir_all(&path).map_err(|e| {
+            ClientError::DataDirectory(format!("Failed to create {}: {e}", path.display()))
+        })?;
+        Ok(Self { path })
+    }
+
+    /// Where session logs are kept, see [`crate::session_log::SessionLogger`]
+    pub fn session_log_directory(&self) -> PathBuf {
+        self.path.join(SESSION_LOG_DIRECTORY_NAME)
+    }
+
+    /// Where the statuses of a downsampled run are spilled to, see
+    /// [`crate::status_history::StatusHistory`]
+    pub fn spill_path(&self) -> PathBuf {
+        self.path.join(SPILL_FILE_NAME)
+    }
+
+    /// The path for a new run today, numbered after the runs already saved
+    /// today and never overwriting anything
+    pub fn next_run_path(&self, distance: f64, format: RunFormat) -> PathBuf {
+        let date = Local::now().format("%Y-%m-%d").to_string();
+        let mut run_number = self
+            .runs()
+            .iter()
+            .filter_map(|run| parse_run_name(run))
+            .filter(|(run_date, _)| *run_date == date)
+            .map(|(_, run_number)| run_number)
+            .max()
+            .unwrap_or(0_u32)
+            + 1_u32;
+
+        loop {
+            let path = self.path.join(format!(
+                "{date}_run{run_number:02}_{distance:.0}cm.{}",
+                format.extension()
+            ));
+            if !path.exists() {
+                return path;
+            }
+            run_number += 1_u32;
+        }
+    }
+
+    /// All automatically named runs, oldest first
+    pub fn runs(&self) -> Vec<PathBuf> {
+        let mut runs = read_dir(&self.path)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| parse_run_name(path).is_some())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        runs.sort_by_key(|path| parse_run_name(path));
+        runs
+    }
+
+    /// Delete the oldest automatically named runs past `keep` (with their
+    /// annotations), returning how many were deleted
+    ///
+    /// Files named by hand are never touched
+    pub fn enforce_retention(&self, keep: usize) -> Result<usize, ClientError> {
+        let runs = self.runs();
+        let excess = runs.len().saturating_sub(keep);
+        for run in runs.iter().take(excess) {
+            remove_file(run).map_err(|e| {
+                ClientError::DataDirectory(format!("Failed to remove {}: {e}", run.display()))
+            })?;
+            // Most runs were never annotated
+            let _ = remove_file(annotations_path(run));
+        }
+        Ok(excess)
+    }
+
+    /// Delete the oldest automatically named runs (with their annotations)
+    /// until `needed` bytes would be free, never the latest
+    /// [`MIN_RETAINED_RUNS`], returning how many were deleted and the bytes
+    /// that freed
+    pub fn prune_for_space(&self, free: u64, needed: u64) -> Result<(usize, u64), ClientError> {
+        let runs = self.runs();
+        let prunable = runs.len().saturating_sub(MIN_RETAINED_RUNS);
+        let mut freed = 0_u64;
+        let mut pruned = 0_usize;
+        for run in runs.iter().take(prunable) {
+            if free + freed >= needed {
+                break;
+            }
+            let annotations = annotations_path(run);
+            for (path, size) in [(run, metadata(run)), (&annotations, metadata(&annotations))] {
+                let Ok(size) = size else {
+                    continue;
+                };
+                remove_file(path).map_err(|e| {
+                    ClientError::DataDirectory(format!("Failed to remove {}: {e}", path.display()))
+                })?;
+                freed += size.len();
+            }
+            pruned += 1;
+        }
+        Ok((pruned, freed))
+    }
+}
+
+/***** Helper functions *****/
+
+/// Get the date and run number out of an automatically named run
+fn parse_run_name(path: &Path) -> Option<(String, u32)> {
+    RunFormat::of(path)?;
+    let stem = path.file_stem()?.to_str()?;
+    let mut parts = stem.splitn(3, '_');
+    let date = parts.next()?;
+    let run_number = parts.next()?.strip_prefix("run")?.parse().ok()?;
+    parts.next()?.strip_suffix("cm")?;
+    if date.len() != 10 || !date.chars().all(|c| c.is_ascii_digit() || c == '-') {
+        return None;
+    }
+    Some((date.to_owned(), run_number))
+}
+
+fn is_raspberry_pi() -> bool {
+    read_to_string("/proc/device-tree/model")
+        .map(|model| model.starts_with("Raspberry Pi"))
+        .unwrap_or(false)
+}
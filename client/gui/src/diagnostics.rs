@@ -0,0 +1,249 @@
+/*!
+ * Diagnostic bundles to attach to bug reports, and loading them back in
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{annotations::Annotation, export::*, journal::*, ErrorData};
+use bindings::{ClientError, Event, StatusResponse};
+use chrono::{DateTime, Utc};
+use serde_json::{from_reader as serde_from_reader, to_string_pretty as serde_to_string_pretty};
+use std::{
+    env::{
+        consts::{ARCH, OS},
+        var,
+    },
+    fs::File,
+    io::{Read, Write},
+    path::Path,
+};
+use zip::{write::FileOptions, ZipArchive, ZipWriter};
+
+// Constants
+const VERSION_FILE: &str = "version.txt";
+const CONFIG_FILE: &str = "config.json";
+const ERRORS_FILE: &str = "errors.json";
+const BRIDGE_LOG_FILE: &str = "bridge.log";
+const FRAMES_FILE: &str = "frames.log";
+const STATUS_FILE: &str = "status.csv";
+const JOURNAL_FILE: &str = "journal.json";
+const ANNOTATIONS_FILE: &str = "annotations.json";
+
+/***** Helper functions *****/
+
+/// Information about this build of the GUI
+pub fn version_info() -> String {
+    format!(
+        "{} {} ({OS} {ARCH}, {})",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        match cfg!(debug_assertions) {
+            true => "debug",
+            false => "release",
+        }
+    )
+}
+
+/// Strip anything that identifies the person or the car out of some text:
+/// the home directory, the user name in any other home directory, and
+/// bluetooth addresses
+///
+/// The user name is only replaced in paths, as short ones (like the Pi's
+/// `pi`) turn up everywhere else, including the frames that are replayed.
+pub fn anonymize(text: &str) -> String {
+    let mut text = text.to_owned();
+    if let Ok(home) = var("HOME") {
+        if !home.is_empty() {
+            text = replace_directory(&text, &home, "~");
+        }
+    }
+    if let Ok(user) = var("USER").or_else(|_| var("USERNAME")) {
+        if !user.is_empty() {
+            for parent in ["/home/", "/Users/", "\\Users\\"] {
+                text = replace_directory(
+                    &text,
+                    &format!("{parent}{user}"),
+                    &format!("{parent}<user>"),
+                );
+            }
+        }
+    }
+
+    // Bluetooth addresses look like `00:60:37:E9:0B:6F`
+    let is_address = |candidate: &[u8]| {
+        candidate
+            .iter()
+            .enumerate()
+            .all(|(idx, character)| match idx % 3 {
+                2 => *character == b':',
+                _ => character.is_ascii_hexdigit(),
+            })
+    };
+    let mut anonymized = String::with_capacity(text.len());
+    let mut idx = 0_usize;
+    while idx < text.len() {
+        let candidate = text.as_bytes().get(idx..idx + 17);
+        match candidate {
+            Some(candidate) if is_address(candidate) => {
+                anonymized.push_str("XX:XX:XX:XX:XX:XX");
+                idx += 17;
+            }
+            _ => {
+                // Safety: `idx` only ever lands on character boundaries
+                let character = text[idx..].chars().next().unwrap();
+                anonymized.push(character);
+                idx += character.len_utf8();
+            }
+        }
+    }
+
+    anonymized
+}
+
+/// Replace a directory wherever it's the whole of a path, so `/home/pi`
+/// doesn't match the start of `/home/pip`
+fn replace_directory(text: &str, directory: &str, with: &str) -> String {
+    let mut replaced = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(idx) = rest.find(directory) {
+        replaced.push_str(&rest[..idx]);
+        rest = &rest[idx + directory.len()..];
+        match rest.chars().next() {
+            Some(character) if character.is_alphanumeric() || "-_".contains(character) => {
+                replaced.push_str(directory);
+            }
+            _ => replaced.push_str(with),
+        }
+    }
+    replaced.push_str(rest);
+    replaced
+}
+
+/***** Diagnostic bundle *****/
+
+/// Everything needed to figure out what went wrong, as a zip file
+pub struct DiagnosticBundle {
+    pub version: String,
+    /// JSON object of the settings at the time
+    pub config: String,
+    pub errors: Vec<ErrorData>,
+    pub bridge_output: Vec<String>,
+    /// Raw frames prefixed by their direction (`>` sent, `<` received)
+    pub frames: Vec<String>,
+    pub status_responses: Vec<Event<StatusResponse>>,
+    pub journal: Journal,
+    pub annotations: Vec<Annotation>,
+}
+impl DiagnosticBundle {
+    /// Write the bundle as a zip file, anonymizing everything on the way out
+    pub fn write(&self, file_path: &Path) -> Result<(), ClientError> {
+        let error = |e: &dyn ToString| ClientError::Diagnostics(e.to_string());
+
+        let mut zip = ZipWriter::new(File::create(file_path).map_err(|e| error(&e))?);
+        let mut add_file = |name: &str, contents: &str| -> Result<(), ClientError> {
+            zip.start_file(name, FileOptions::default())
+                .map_err(|e| error(&e))?;
+            zip.write_all(anonymize(contents).as_bytes())
+                .map_err(|e| error(&e))
+        };
+
+        add_file(VERSION_FILE, &self.version)?;
+        add_file(CONFIG_FILE, &self.config)?;
+        let errors = self
+            .errors
+            .iter()
+            .map(|error| (error.time.to_rfc3339(), error.error.to_string()))
+            .collect::<Vec<_>>();
+        add_file(
+            ERRORS_FILE,
+            &serde_to_string_pretty(&errors).map_err(|e| error(&e))?,
+        )?;
+        add_file(BRIDGE_LOG_FILE, &self.bridge_output.join("\n"))?;
+        add_file(FRAMES_FILE, &self.frames.join("\n"))?;
+
+        let mut status_csv = vec![];
+        CSVDynamicStatus::write_to(&mut status_csv, &self.status_responses)
+            .map_err(|e| error(&e))?;
+        add_file(STATUS_FILE, &String::from_utf8_lossy(&status_csv))?;
+        add_file(JOURNAL_FILE, &self.journal.to_json()?)?;
+        add_file(
+            ANNOTATIONS_FILE,
+            &serde_to_string_pretty(&self.annotations).map_err(|e| error(&e))?,
+        )?;
+
+        zip.finish().map_err(|e| error(&e))?;
+        Ok(())
+    }
+
+    /// Read a bundle back from a zip file
+    pub fn read(file_path: &Path) -> Result<Self, ClientError> {
+        let error = |e: &dyn ToString| ClientError::Diagnostics(e.to_string());
+
+        let mut zip = ZipArchive::new(File::open(file_path).map_err(|e| error(&e))?)
+            .map_err(|e| error(&e))?;
+        let mut read_file = |name: &str| -> Result<String, ClientError> {
+            let mut contents = String::new();
+            zip.by_name(name)
+                .map_err(|e| error(&e))?
+                .read_to_string(&mut contents)
+                .map_err(|e| error(&e))?;
+            Ok(contents)
+        };
+
+        let version = read_file(VERSION_FILE)?;
+        let config = read_file(CONFIG_FILE)?;
+        let errors =
+            serde_from_reader::<_, Vec<(String, String)>>(read_file(ERRORS_FILE)?.as_bytes())
+                .map_err(|e| error(&e))?
+                .into_iter()
+                .map(|(time, error)| ErrorData {
+                    error: ClientError::Recorded(error),
+                    time: DateTime::parse_from_rfc3339(&time)
+                        .map(|time| time.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                    // Only offered for errors from the server this run
+                    recovery: None,
+                })
+                .collect();
+        let lines = |contents: String| {
+            contents
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        };
+        let bridge_output = lines(read_file(BRIDGE_LOG_FILE)?);
+        let frames = lines(read_file(FRAMES_FILE)?);
+        let status_responses = CSVDynamicStatus::read_from(read_file(STATUS_FILE)?.as_bytes())
+            .map_err(|e| error(&e))?;
+        // Bundles from before the journal existed don't have one
+        let journal = match read_file(JOURNAL_FILE) {
+            Ok(json) => Journal::from_json(&json)?,
+            Err(_) => Journal::default(),
+        };
+        // Nor annotations
+        let annotations = match read_file(ANNOTATIONS_FILE) {
+            Ok(json) => serde_from_reader(json.as_bytes()).map_err(|e| error(&e))?,
+            Err(_) => vec![],
+        };
+
+        Ok(Self {
+            version,
+            config,
+            errors,
+            bridge_output,
+            frames,
+            status_responses,
+            journal,
+            annotations,
+        })
+    }
+
+    /// The received frames, in order, ready to be parsed again
+    pub fn received_frames(&self) -> impl Iterator<Item = &str> {
+        self.frames
+            .iter()
+            .filter_map(|frame| frame.strip_prefix("< "))
+    }
+}
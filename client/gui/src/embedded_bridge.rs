@@ -0,0 +1,91 @@
+/*!
+ * Running the serial to bluetooth bridge inside of the GUI, talking to it in
+ * memory instead of over a pseudo-terminal
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{ArqTransport, ClientError, LinkEnd, LoopbackTransport, SystemdNotifier, Transport};
+use bluetooth_bridge::{types::TRUSTED_DEVICES_FILE, BridgeHandle, TrustedDevices};
+use std::path::PathBuf;
+use tokio::runtime::{Builder, Runtime};
+use tracing::{info, warn};
+
+/***** Embedded bridge *****/
+
+/// The bridge running on a thread of its own in the GUI's process
+///
+/// Its logs go wherever the GUI's do, and it's shut down along with the GUI.
+/// Unlike a spawned bridge, there's nothing to restart, as it only stops once
+/// the GUI lets go of its end of the connection.
+pub struct EmbeddedBridge {
+    runtime: Runtime,
+    /// `None` once it's stopped and that's been reported
+    handle: Option<BridgeHandle>,
+}
+impl EmbeddedBridge {
+    /// Start the bridge with the trusted devices it would use on its own,
+    /// returning it alongside the GUI's end of the connection to it, which
+    /// asks again for frames lost on the way to and from the car
+    pub fn spawn() -> Result<(Self, Box<dyn Transport>), ClientError> {
+        let trusted_devices = TrustedDevices::load(
+            TrustedDevices::default_path().unwrap_or_else(|| PathBuf::from(TRUSTED_DEVICES_FILE)),
+        )
+        .map_err(|e| ClientError::Bluetooth(e.to_string()))?;
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(1_usize)
+            .thread_name("bluetooth-bridge")
+            .enable_all()
+            .build()
+            .map_err(|e| {
+                ClientError::Bluetooth(format!("Failed to start the bridge's runtime: {e}"))
+            })?;
+
+        let (transport, bridge_end) = LoopbackTransport::pair();
+        let handle = {
+            let _runtime = runtime.enter();
+            // The GUI already tells systemd how it's doing
+            BridgeHandle::spawn(
+                Some(Box::new(bridge_end)),
+                trusted_devices,
+                SystemdNotifier::disabled(),
+                None,
+                None,
+            )
+            .map_err(|e| ClientError::Bluetooth(e.to_string()))?
+        };
+        info!("Started the embedded bridge");
+
+        Ok((
+            Self {
+                runtime,
+                handle: Some(handle),
+            },
+            Box::new(ArqTransport::new(Box::new(transport), LinkEnd::Client)),
+        ))
+    }
+
+    /// Check if the bridge is still running, returning why it stopped the
+    /// first time it's found to have
+    ///
+    /// This is meant to be called every frame
+    pub fn poll(&mut self) -> Result<(), ClientError> {
+        let Some(handle) = self.handle.as_mut().filter(|handle| handle.is_finished()) else {
+            return Ok(());
+        };
+        let stopped = self.runtime.block_on(handle.stopped());
+        self.handle = None;
+        warn!("The embedded bridge stopped");
+        stopped.map_err(|e| ClientError::Bluetooth(format!("The embedded bridge stopped: {e}")))
+    }
+}
+impl Drop for EmbeddedBridge {
+    /// Disconnect from the device before the runtime goes away
+    fn drop(&mut self) {
+        if let Some(mut handle) = self.handle.take() {
+            handle.shutdown();
+            let _ = self.runtime.block_on(handle.stopped());
+        }
+    }
+}
@@ -0,0 +1,27 @@
+/*!
+ * Stands in for the embedded bridge off Linux, where there's no BlueZ for it
+ * to talk to, so the GUI still builds with every feature
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{ClientError, Transport};
+
+/***** Embedded bridge *****/
+
+/// Never started, as the bridge needs BlueZ
+pub struct EmbeddedBridge {
+    _private: (),
+}
+impl EmbeddedBridge {
+    pub fn spawn() -> Result<(Self, Box<dyn Transport>), ClientError> {
+        Err(ClientError::Bluetooth(
+            "The embedded bridge needs BlueZ, which is only on Linux".to_owned(),
+        ))
+    }
+
+    pub fn poll(&mut self) -> Result<(), ClientError> {
+        Ok(())
+    }
+}
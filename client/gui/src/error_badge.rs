@@ -0,0 +1,82 @@
+/*!
+ * Sorting errors by the part of the system they came from, to count them in a
+ * badge in the banner instead of covering the screen mid run
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::ErrorData;
+use bindings::ClientError;
+use std::fmt;
+
+/***** Subsystems *****/
+
+/// Which part of the system an error came from
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Subsystem {
+    /// The serial port (or TCP connection) to the car, and requests going
+    /// unanswered over it
+    Serial,
+    /// The Bluetooth bridge and its connection to the car
+    Bluetooth,
+    /// Frames that came in corrupted or couldn't be read, and the car speaking
+    /// another version of the protocol
+    Protocol,
+    /// Errors the server returned
+    Server,
+    /// Everything else, like files that couldn't be saved
+    Other,
+}
+impl Subsystem {
+    pub const ALL: [Self; 5] = [
+        Self::Serial,
+        Self::Bluetooth,
+        Self::Protocol,
+        Self::Server,
+        Self::Other,
+    ];
+
+    pub fn of(error: &ClientError) -> Self {
+        match error {
+            ClientError::Serial(_) | ClientError::Timeout { .. } => Self::Serial,
+            ClientError::Bluetooth(_) => Self::Bluetooth,
+            ClientError::Parse(_)
+            | ClientError::ChecksumMismatch { .. }
+            | ClientError::ProtocolVersion { .. } => Self::Protocol,
+            ClientError::Server(..) => Self::Server,
+            _ => Self::Other,
+        }
+    }
+}
+impl fmt::Display for Subsystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Serial => "Serial",
+                Self::Bluetooth => "Bluetooth",
+                Self::Protocol => "Protocol",
+                Self::Server => "Server",
+                Self::Other => "Other",
+            }
+        )
+    }
+}
+
+/// How many errors came from each subsystem, in the order of
+/// [`Subsystem::ALL`] and leaving out those with none
+pub fn error_counts(errors: &[ErrorData]) -> Vec<(Subsystem, usize)> {
+    Subsystem::ALL
+        .into_iter()
+        .map(|subsystem| {
+            let count = errors
+                .iter()
+                .filter(|error| Subsystem::of(&error.error) == subsystem)
+                .count();
+            (subsystem, count)
+        })
+        .filter(|(_, count)| *count > 0)
+        .collect()
+}
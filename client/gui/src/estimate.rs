@@ -0,0 +1,88 @@
+/*!
+ * Guessing where the car is while no statuses come in mid run, from its last
+ * velocity and how fast it was slowing down, so whoever's running it can tell
+ * whether to walk over
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::shared::*;
+use bindings::{Event, StatusResponse, StatusStage};
+
+/***** Position estimate *****/
+
+/// Where the car probably is some time after the latest status
+///
+/// Only a guess: the car is taken to keep slowing down as it was between the
+/// last two statuses, or to keep going at its last velocity if it wasn't, and
+/// how far off that could be grows with every second nothing comes in.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PositionEstimate {
+    /// Seconds since the latest status
+    pub silent_for: f64,
+    /// Centimeters, as of the latest status
+    pub last_distance: f64,
+    /// Centimeters, where the car most likely is
+    pub distance: f64,
+    /// Centimeters either way the car could be from `distance`
+    pub uncertainty: f64,
+    /// Whether the car has probably stopped by now
+    pub stopped: bool,
+}
+impl PositionEstimate {
+    /// Where the car probably is `silent_for` seconds after the latest
+    /// status, `None` unless the car said it was running
+    pub fn after(statuses: &[Event<StatusResponse>], silent_for: f64) -> Option<Self> {
+        let latest = statuses.last()?;
+        if !latest.value.running || matches!(latest.value.stage, StatusStage::Finalized) {
+            return None;
+        }
+        let previous = statuses.len().checked_sub(2).map(|idx| &statuses[idx]);
+        let velocity = latest.value.distance.velocity.max(0.0);
+        let (travelled, stopped) =
+            match previous.and_then(|previous| deceleration(previous, latest)) {
+                Some(deceleration) if velocity / deceleration <= silent_for => {
+                    (velocity.powi(2) / (2.0 * deceleration), true)
+                }
+                Some(deceleration) => (
+                    velocity * silent_for - deceleration * silent_for.powi(2) / 2.0,
+                    false,
+                ),
+                None => (velocity * silent_for, velocity == 0.0),
+            };
+
+        let last_distance = latest.value.distance.distance;
+        Some(Self {
+            silent_for,
+            last_distance,
+            distance: last_distance + travelled,
+            uncertainty: ESTIMATE_UNCERTAINTY * velocity * silent_for,
+            stopped,
+        })
+    }
+
+    /// The centimeters the car could be between, never short of where it
+    /// last was
+    pub fn range(&self) -> (f64, f64) {
+        (
+            (self.distance - self.uncertainty).max(self.last_distance),
+            self.distance + self.uncertainty,
+        )
+    }
+}
+
+/***** Helper functions *****/
+
+/// Centimeters per second squared between two statuses, only if the car was
+/// slowing down
+pub fn deceleration(
+    previous: &Event<StatusResponse>,
+    status: &Event<StatusResponse>,
+) -> Option<f64> {
+    Some(
+        (previous.value.distance.velocity - status.value.distance.velocity)
+            / (status.metadata.time - previous.metadata.time),
+    )
+    .filter(|deceleration| deceleration.is_finite() && *deceleration > 0.0)
+}
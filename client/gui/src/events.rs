@@ -7,236 +7,842 @@ use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 /***** Setup *****/
 // Imports
+use crate::{
+    annotations::Annotation,
+    clock::PingTimes,
+    config::{CarConfig, SerialConfig},
+    fault_injection::Injection,
+    outgoing::OutgoingQueue,
+    run_history::RunParameters,
+    serial_worker::SerialWorker,
+    shared::*,
+    status_history::StatusHistory,
+    stop_reason::StopReason,
+    wire_log::*,
+};
 use bindings::{
-    BluetoothStatusResponse, ClientError, Command, ErrorResponse, Event, MetaData, PingResponse,
-    Response, StartResponse, StaticStatusResponse, StatusResponse, StopResponse, TransitMode,
-    TransitType,
+    negotiate_protocol_version, open_transport, predates_hello, synchronize, ArqTransport,
+    BluetoothConnectResponse, BluetoothDevice, BluetoothMetricsResponse, CalibrateResponse,
+    ClientError, Command, Event, Frame, LinkEnd, MetaData, PingResponse, RawFrame, Response,
+    StaticStatusResponse, TransitMode, TransitType, Transport, NUMBERED_PROTOCOL_VERSION,
+    OLDEST_PROTOCOL_VERSION, PROTOCOL_VERSION, TCP_PREFIX, UNIX_PREFIX,
 };
 use serde::{Deserialize, Serialize};
-use serde_json::{from_str as serde_from_str, to_string as serde_to_string};
-use serialport::SerialPort;
+use serialport::{available_ports, SerialPortType};
 use smart_default::SmartDefault;
-use std::mem::take;
+use std::{collections::VecDeque, mem::take, path::Path, time::Duration};
+use tracing::{debug, debug_span, info, warn};
+
+// Constants
+/// How many raw frames (sent and received) to keep around for diagnostics
+pub const RECENT_FRAMES: usize = 500;
+
+/// A command sent to the car, kept next to its statuses so what was asked of
+/// it can be lined up with what it did
+#[derive(Clone, PartialEq, Debug)]
+pub struct SentCommand {
+    pub command: Command,
+    /// JSON, e.g. `{"distance":500.0}`
+    pub arguments: String,
+    /// Unix time in seconds it was sent at, by our clock
+    pub time: f64,
+    /// Whether it was sent again after going unanswered
+    pub retry: bool,
+}
 
 /// Run data
 #[derive(SmartDefault)]
 pub struct RunData {
     pub bluetooth_bridge_connected: bool,
+    /// Devices found by the bridge's last scan, strongest signal first
+    pub bluetooth_devices: Vec<BluetoothDevice>,
+    /// How the bridge's last attempt at connecting went, or is going
+    pub bluetooth_connect_progress: Option<BluetoothConnectResponse>,
+    /// How the bridge's link to the device has been doing, as of the last
+    /// time it was asked
+    pub bluetooth_metrics: Option<BluetoothMetricsResponse>,
     pub ping_status_response: Option<(Box<Event<PingResponse>>, f64)>,
     pub static_status_response: Option<Box<Event<StaticStatusResponse>>>,
-    pub status_responses: Vec<Event<StatusResponse>>,
+    /// Whether the server answers static status requests, once known
+    ///
+    /// Some firmware doesn't, in which case the static status is assumed from
+    /// the config instead
+    pub static_status_supported: Option<bool>,
+    /// Downsampled on long runs
+    pub status_responses: StatusHistory,
+    /// Every command sent to the car this run, oldest first
+    pub sent_commands: Vec<SentCommand>,
     pub other_responses: Vec<Response>,
+    /// What the run was started with, if it was started here
+    pub parameters: Option<RunParameters>,
+    /// Drawn on the telemetry plot, and saved next to the run
+    pub annotations: Vec<Annotation>,
+    /// Why the run was stopped by hand, if it was and it was said
+    pub stop_reason: Option<StopReason>,
+    /// What the wheel was last calibrated to, kept through resets as it's
+    /// the car's rather than the run's
+    pub calibration: Option<CalibrateResponse>,
     #[default = false]
     pub running: bool,
+    /// Whether the status responses have been saved anywhere
+    #[default = false]
+    pub saved: bool,
+}
+impl RunData {
+    /// Add a parsed response to the corresponding run data, with the Unix time
+    /// in seconds it was received at
+    ///
+    /// Error responses from the server and the bridge's watchdog are handed
+    /// back as errors
+    pub fn apply_response(
+        &mut self,
+        parsed_response: Response,
+        received_time: f64,
+    ) -> Result<(), ClientError> {
+        use Response::*;
+        match parsed_response {
+            Ping(resp) => self.ping_status_response = Some((Box::new(resp), received_time)),
+            StaticStatus(resp) => {
+                self.static_status_response = Some(Box::new(resp));
+                self.static_status_supported = Some(true);
+            }
+            Status(resp) => self.status_responses.push(resp),
+            // Counted with from the next run on
+            Calibrate(resp) => {
+                if let Some(static_status) = &mut self.static_status_response {
+                    static_status.value.wheel_circumference = Some(resp.value.wheel_circumference);
+                }
+                self.calibration = Some(resp.value);
+            }
+            Error(resp) => Err(ClientError::from(&resp.value))?,
+            BluetoothStatus(resp) => self.bluetooth_bridge_connected = resp.value.connected,
+            BluetoothScan(resp) => self.bluetooth_devices = resp.value.devices,
+            BluetoothMetrics(resp) => self.bluetooth_metrics = Some(resp.value),
+            BluetoothConnect(resp) => {
+                if let Some(connected) = resp.value.connected {
+                    self.bluetooth_bridge_connected = connected;
+                }
+                self.bluetooth_connect_progress = Some(resp.value);
+            }
+            // The device stopped answering, so the bridge is reconnecting
+            Watchdog(resp) => {
+                self.bluetooth_bridge_connected = false;
+                Err(ClientError::Bluetooth(format!(
+                    "Nothing came back from {} for {:.1}s, so the bridge is reconnecting",
+                    resp.value.address, resp.value.silent_for
+                )))?
+            }
+            _ => self.other_responses.push(parsed_response),
+        };
+        Ok(())
+    }
+
+    /// Keep a frame sent at a Unix time in seconds, if it was a command for
+    /// the car rather than the bridge
+    pub fn record_sent(&mut self, frame: &str, time: f64, retry: bool) {
+        let Ok(raw_frame) = RawFrame::split(frame) else {
+            return;
+        };
+        if raw_frame.transit_mode != TransitMode::ClientToServerRequest {
+            return;
+        }
+        self.sent_commands.push(SentCommand {
+            command: raw_frame.command,
+            arguments: match raw_frame.data.as_str() {
+                "null" => "{}".to_owned(),
+                arguments => arguments.to_owned(),
+            },
+            time,
+            retry,
+        });
+    }
+
+    /// The times of the last ping, to check the car's clock with
+    pub fn ping_times(&self) -> Option<PingTimes> {
+        self.ping_status_response
+            .as_ref()
+            .map(|(ping, received_time)| PingTimes {
+                sent: ping.value.sent_time,
+                car: ping.metadata.time,
+                received: *received_time,
+            })
+    }
+
+    /// Use the car from the config as the static status, for when the server
+    /// doesn't send one
+    pub fn assume_static_status(&mut self, car: &CarConfig, time: f64) {
+        self.static_status_response = Some(Box::new(Event {
+            command: Command::StaticStatus,
+            transit_mode: TransitMode::ServerToClientResponse,
+            transit_type: TransitType::Response,
+            value: StaticStatusResponse {
+                number_of_magnets: car.number_of_magnets,
+                wheel_diameter: car.wheel_diameter,
+                distance_estimator: None,
+                approach_gains: None,
+                wheel_circumference: None,
+            },
+            metadata: MetaData {
+                time,
+                sequence: None,
+                frame: None,
+                echo: None,
+                client: None,
+            },
+        }));
+    }
+}
+
+/// A request that hasn't been answered yet
+pub struct PendingRequest {
+    pub sequence: u32,
+    pub command: Command,
+    /// Resent as is, so the server can tell it's the same request
+    pub frame: String,
+    pub last_sent_time: Instant,
+    pub attempts: usize,
+}
+
+/// How the serial connection is doing
+#[derive(Clone, PartialEq, Debug)]
+pub enum ConnectionState {
+    Connected {
+        port: String,
+    },
+    /// Waiting to try connecting again
+    Reconnecting {
+        /// Failed attempts since the connection was lost
+        attempts: usize,
+        /// Why the last attempt failed, if there was one
+        reason: Option<String>,
+    },
+}
+
+/// Finds the car's serial port and connects to it again whenever it's lost
+///
+/// A port given on the command line or in the config is used as is, and can
+/// also be a `tcp://<host>:<port>` address to connect over TCP instead, or a
+/// `unix://<path>` to connect over a Unix domain socket (e.g. to a bridge
+/// listening for clients, which is connected to again once it restarts).
+/// Otherwise, the first USB serial port matching the USB vendor ID, product
+/// ID, and description in the config is used. Failed attempts are spaced out
+/// further and further, up to [`RECONNECT_MAX_DELAY_SECONDS`] apart.
+///
+/// Connections can be handed over to a [`SerialWorker`], for callers (like
+/// the GUI) that can't wait on them.
+pub struct ConnectionManager {
+    pub port: Option<String>,
+    pub usb_vid: Option<u16>,
+    pub usb_pid: Option<u16>,
+    pub description: Option<String>,
+    pub baud_rate: u32,
+    /// Seconds to wait for reads
+    pub timeout: f64,
+    /// Seconds to throw away what was left on the connection for once
+    /// connected, see [`synchronize`]
+    pub sync_timeout: f64,
+    /// Whether to read and write on a worker thread
+    pub worker_thread: bool,
+    pub state: ConnectionState,
+    /// Seconds to wait after the next failed attempt
+    pub delay: f64,
+    next_attempt_time: Instant,
+}
+impl ConnectionManager {
+    /// A port from the command line overrides the one in the config
+    pub fn new(port: Option<String>, config: &SerialConfig) -> Self {
+        Self {
+            port: port.or_else(|| config.port.clone()),
+            usb_vid: config.usb_vid,
+            usb_pid: config.usb_pid,
+            description: config.description.clone(),
+            baud_rate: config.baud_rate,
+            timeout: config.poll_interval,
+            sync_timeout: config.sync_timeout,
+            worker_thread: false,
+            state: ConnectionState::Reconnecting {
+                attempts: 0_usize,
+                reason: None,
+            },
+            delay: RECONNECT_INITIAL_DELAY_SECONDS,
+            next_attempt_time: Instant::now(),
+        }
+    }
+
+    /// Whether a port is the car's, going by its USB information
+    pub fn matches(&self, port_type: &SerialPortType) -> bool {
+        let SerialPortType::UsbPort(usb) = port_type else {
+            return false;
+        };
+        let description_matches = match &self.description {
+            Some(description) => [&usb.product, &usb.manufacturer]
+                .into_iter()
+                .flatten()
+                .any(|name| name.to_lowercase().contains(&description.to_lowercase())),
+            None => true,
+        };
+        self.usb_vid.unwrap_or(usb.vid) == usb.vid
+            && self.usb_pid.unwrap_or(usb.pid) == usb.pid
+            && description_matches
+    }
+
+    /// The path of the car's serial port, if it's plugged in
+    pub fn find_port(&self) -> Result<String, ClientError> {
+        if let Some(port) = &self.port {
+            let exists = match port.strip_prefix(UNIX_PREFIX) {
+                Some(path) => Path::new(path).exists(),
+                None => port.starts_with(TCP_PREFIX) || Path::new(port).exists(),
+            };
+            return match exists {
+                true => Ok(port.clone()),
+                false => Err(ClientError::Serial(format!("{port} doesn't exist"))),
+            };
+        }
+
+        available_ports()
+            .map_err(|e| ClientError::Serial(e.to_string()))?
+            .into_iter()
+            .find(|port| self.matches(&port.port_type))
+            .map(|port| port.port_name)
+            .ok_or_else(|| ClientError::Serial("No matching serial port found".to_owned()))
+    }
+
+    /// Try connecting now, waiting longer before the next attempt if it fails
+    ///
+    /// Whatever was left on the connection (like half a frame, or the
+    /// Arduino's boot messages) is thrown away first, so it isn't read as the
+    /// start of the first response. Frames lost on the way to and from the car
+    /// are asked for again, see [`ArqTransport`].
+    pub fn connect(&mut self) -> Result<Box<dyn Transport>, ClientError> {
+        let result = self.find_port().and_then(|port| {
+            let mut transport =
+                open_transport(&port, self.baud_rate, Duration::from_secs_f64(self.timeout))?;
+            self.synchronize(transport.as_mut())?;
+            Ok((port, transport))
+        });
+        let result = result.map(|(port, transport)| {
+            let transport: Box<dyn Transport> =
+                Box::new(ArqTransport::new(transport, LinkEnd::Client));
+            let transport = match self.worker_thread {
+                true => Box::new(SerialWorker::spawn(transport)),
+                false => transport,
+            };
+            (port, transport)
+        });
+
+        match result {
+            Ok((port, transport)) => {
+                info!(%port, "Connected");
+                self.state = ConnectionState::Connected { port };
+                self.delay = RECONNECT_INITIAL_DELAY_SECONDS;
+                Ok(transport)
+            }
+            Err(e) => {
+                let attempts = match &self.state {
+                    ConnectionState::Reconnecting { attempts, .. } => attempts + 1,
+                    ConnectionState::Connected { .. } => 1_usize,
+                };
+                debug!(attempts, "Failed to connect: {e}");
+                self.state = ConnectionState::Reconnecting {
+                    attempts,
+                    reason: Some(e.to_string()),
+                };
+                self.next_attempt_time = Instant::now() + Duration::from_secs_f64(self.delay);
+                self.delay = (self.delay * 2.0).min(RECONNECT_MAX_DELAY_SECONDS);
+                Err(e)
+            }
+        }
+    }
+
+    /// Throw away what was left on a connection that was just opened
+    fn synchronize(&self, transport: &mut dyn Transport) -> Result<(), ClientError> {
+        if self.sync_timeout <= 0.0 {
+            return Ok(());
+        }
+        let synchronization = synchronize(transport, Duration::from_secs_f64(self.sync_timeout))?;
+        if synchronization.discarded > 0 {
+            info!(
+                discarded = synchronization.discarded,
+                "Threw away what was left on the connection"
+            );
+        }
+        if synchronization.timed_out {
+            warn!(
+                "Garbage was still coming in after {:.1}s, starting anyway",
+                self.sync_timeout
+            );
+        }
+        Ok(())
+    }
+
+    /// Try connecting again if it's time to
+    pub fn poll(&mut self) -> Option<Box<dyn Transport>> {
+        match self.state {
+            ConnectionState::Reconnecting { .. } if Instant::now() >= self.next_attempt_time => {
+                self.connect().ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// The connection was lost, so start trying to get it back right away
+    pub fn lost(&mut self, reason: String) {
+        self.state = ConnectionState::Reconnecting {
+            attempts: 0_usize,
+            reason: Some(reason),
+        };
+        self.delay = RECONNECT_INITIAL_DELAY_SECONDS;
+        self.next_attempt_time = Instant::now();
+    }
 }
 
 /// A serial event propagator
 ///
-/// This will connect to the serial connection and await for events
+/// This will connect to the serial connection (or any other [`Transport`])
+/// and await for events
+///
+/// With a [`ConnectionManager`], losing the serial connection isn't fatal:
+/// reading gives nothing, writing fails, and nothing is resent until
+/// [`SerialEventPropagator::maintain_connection`] gets it back.
+///
+/// Every request is numbered, and requests that get a response are kept
+/// pending until a response with their number comes back. See
+/// [`SerialEventPropagator::retry_pending`].
+///
+/// The version of the protocol is agreed on with `HELLO` whenever the car is
+/// connected to, see [`SerialEventPropagator::negotiate`].
 pub struct SerialEventPropagator {
-    pub serial: Box<dyn SerialPort>,
+    /// `None` while disconnected
+    pub transport: Option<Box<dyn Transport>>,
+    /// Only present if the serial port is ours to find and reconnect to
+    pub connection: Option<ConnectionManager>,
     pub last_get_time: Instant,
-    rx_data: String,
+    /// Raw frames prefixed by their direction (`>` sent, `<` received)
+    pub recent_frames: VecDeque<String>,
+    pub pending_requests: Vec<PendingRequest>,
+    /// Frames waiting to be written, most urgent first
+    pub outgoing: OutgoingQueue,
+    /// Seconds to wait for a response before sending a request again
+    pub request_timeout: f64,
+    pub max_request_attempts: usize,
+    /// Only present while every frame is being logged
+    pub wire_logger: Option<WireLogger>,
+    /// Why the wire logger was stopped, if writing to it failed
+    pub wire_log_error: Option<ClientError>,
+    /// Read before anything from the connection, see [`Injection`]
+    pub injected: VecDeque<Injection>,
+    /// The version of the protocol spoken to the car
+    pub protocol_version: u32,
+    /// Whether to say `HELLO` to the car, once connected to it
+    pub hello_due: bool,
+    next_sequence: u32,
 }
 impl SerialEventPropagator {
-    pub fn new(serial: Box<dyn SerialPort>) -> Self {
+    pub fn new(transport: Box<dyn Transport>) -> Self {
+        Self {
+            transport: Some(transport),
+            ..Self::new_disconnected()
+        }
+    }
+
+    /// Connect with a connection manager, which keeps trying in the
+    /// background if the first attempt fails
+    pub fn connecting(mut connection: ConnectionManager) -> Self {
+        let transport = connection.connect().ok();
+        Self {
+            hello_due: transport.is_some(),
+            transport,
+            connection: Some(connection),
+            ..Self::new_disconnected()
+        }
+    }
+
+    fn new_disconnected() -> Self {
         Self {
-            serial,
-            rx_data: String::new(),
+            transport: None,
+            connection: None,
             last_get_time: Instant::now(),
+            recent_frames: VecDeque::with_capacity(RECENT_FRAMES),
+            pending_requests: vec![],
+            outgoing: OutgoingQueue::new(),
+            request_timeout: REQUEST_TIMEOUT_SECONDS,
+            max_request_attempts: MAX_REQUEST_ATTEMPTS,
+            wire_logger: None,
+            wire_log_error: None,
+            injected: VecDeque::new(),
+            protocol_version: PROTOCOL_VERSION,
+            hello_due: false,
+            next_sequence: 0_u32,
+        }
+    }
+
+    /// Use the timings from the config, which can be tuned while running
+    ///
+    /// The read timeout only changes the next time the port is opened.
+    pub fn apply_serial_config(&mut self, config: &SerialConfig) {
+        self.request_timeout = config.request_timeout;
+        self.max_request_attempts = config.max_request_attempts;
+        if let Some(connection) = &mut self.connection {
+            connection.timeout = config.poll_interval;
+            connection.sync_timeout = config.sync_timeout;
+        }
+    }
+
+    /// Reconnect if the connection was lost and it's time to try again,
+    /// returning whether it was reconnected
+    ///
+    /// Anything half received was lost with the old connection, and pending
+    /// requests are sent again right away. The car could have been flashed in
+    /// the meantime, so the version of the protocol is agreed on again.
+    pub fn maintain_connection(&mut self) -> bool {
+        if self.transport.is_some() {
+            return false;
+        }
+        let Some(transport) = self.connection.as_mut().and_then(ConnectionManager::poll) else {
+            return false;
+        };
+
+        info!("Reconnected");
+        self.transport = Some(transport);
+        self.protocol_version = PROTOCOL_VERSION;
+        self.hello_due = true;
+        let overdue = Instant::now()
+            .checked_sub(Duration::from_secs_f64(self.request_timeout))
+            .unwrap_or_else(Instant::now);
+        for pending in self.pending_requests.iter_mut() {
+            pending.last_sent_time = overdue;
+        }
+        true
+    }
+
+    /// Give up on the serial port after an error, if it can be reconnected to
+    ///
+    /// Nothing still waiting to be written is written to the new connection,
+    /// as pending requests are sent again anyway.
+    fn lose_connection(&mut self, reason: String) {
+        warn!("Lost the connection: {reason}");
+        if let Some(connection) = &mut self.connection {
+            self.transport = None;
+            self.outgoing.clear();
+            connection.lost(reason);
+        }
+    }
+
+    /// The connection, or an error if disconnected
+    fn transport(&mut self) -> Result<&mut Box<dyn Transport>, ClientError> {
+        self.transport
+            .as_mut()
+            .ok_or_else(|| ClientError::Serial("Not connected".to_owned()))
+    }
+
+    /// Keep a raw frame around for diagnostics, and log it if logging
+    fn capture_frame(&mut self, direction: Direction, frame: &str) {
+        if self.recent_frames.len() >= RECENT_FRAMES {
+            self.recent_frames.pop_front();
+        }
+        self.recent_frames.push_back(match direction {
+            Direction::Sent => format!("> {frame}"),
+            Direction::Received => format!("< {frame}"),
+        });
+
+        // Stop logging instead of failing every frame from now on
+        if let Some(wire_logger) = &mut self.wire_logger {
+            if let Err(e) = wire_logger.log(direction, frame) {
+                self.wire_logger = None;
+                self.wire_log_error = Some(e);
+            }
         }
     }
 
-    /// Read from the serial connection until a newline is hit
+    /// Read whatever is available from the connection, returning the next
+    /// finished frame
     ///
-    /// If there are no bytes to be read, or if it was unfinished, this will
-    /// return `None`.
+    /// If nothing was finished, this will return `None`. Unfinished frames wait
+    /// in the transport for the rest of them, and frames that came in together
+    /// are returned one per call.
     ///
-    /// The serial connection has a timeout, therefore if we stop receiving before
-    /// a newline is present, then the data will temporarily be written to
-    /// `self.rx_data` and this will pick back up where it left off
+    /// Anything injected comes first, going through everything a frame from
+    /// the car would.
     pub fn read_from_serial(&mut self) -> Result<Option<String>, ClientError> {
-        let max_bytes_to_read = match self
-            .serial
-            .bytes_to_read()
-            .map_err(|e| ClientError::Serial(e.to_string()))
-        {
-            Ok(0) => return Ok(None),
-            Ok(bytes) => bytes,
-            Err(e) => return Err(e),
+        let data = match self.injected.pop_front() {
+            Some(Injection::Frame(data)) => data,
+            Some(Injection::LinkDown) => {
+                let e = ClientError::Serial("The link went down (injected)".to_owned());
+                self.lose_connection(e.to_string());
+                return Err(e);
+            }
+            // Nothing comes in while disconnected
+            None => match self
+                .transport
+                .as_mut()
+                .map(|transport| transport.read_line())
+            {
+                None | Some(Ok(None)) => return Ok(None),
+                Some(Ok(Some(data))) => data,
+                Some(Err(e)) => {
+                    self.lose_connection(e.to_string());
+                    return Err(e);
+                }
+            },
         };
+        self.capture_frame(Direction::Received, &data);
 
-        let mut character_buffer = [0_u8; 1];
-        for _ in 0..max_bytes_to_read {
-            character_buffer[0] = 0_u8;
-            match self.serial.read_exact(&mut character_buffer) {
-                Ok(()) => (),
-                Err(_) => break,
-            };
-            let character = character_buffer[0] as char;
-            self.rx_data.push(character);
-            match character {
-                '\r' => break, // 'Tis what `scip` does
-                '\n' => break, // 'Tis what the server does
-                _ => (),
+        // Anything that can be read answers the request with its sequence,
+        // errors included
+        let sequence = RawFrame::split(&data)
+            .ok()
+            .and_then(|raw_frame| raw_frame.metadata.sequence);
+        let _response = debug_span!("response", sequence).entered();
+        debug!(frame = %data, "Received");
+        match sequence {
+            Some(sequence) => self
+                .pending_requests
+                .retain(|pending| pending.sequence != sequence),
+            None if self.protocol_version < NUMBERED_PROTOCOL_VERSION => {
+                self.answered_in_order(&data)
             }
+            None => (),
         }
 
-        if self.rx_data.trim().is_empty() || !self.rx_data.ends_with(['\n', '\r']) {
-            return Ok(None);
+        Ok(Some(data))
+    }
+
+    /// Take the request a response answers off of the pending ones, for
+    /// firmware that doesn't say which one it's answering
+    ///
+    /// Requests are answered in order, so a response answers the oldest
+    /// request for the same command, and an error answers the oldest request.
+    fn answered_in_order(&mut self, frame: &str) {
+        let Ok(raw_frame) = RawFrame::split(frame) else {
+            return;
+        };
+        let answered = self.pending_requests.iter().position(|pending| {
+            raw_frame.command == Command::Error || pending.command == raw_frame.command
+        });
+        if let Some(idx) = answered {
+            self.pending_requests.remove(idx);
         }
+    }
 
-        // Remove the ending \r or \n
-        self.rx_data.pop();
+    /// Agree on the version of the protocol with the car's answer to `HELLO`,
+    /// returning whether the response was that answer
+    ///
+    /// Firmware from before `HELLO` answers it with an error, and is spoken to
+    /// in the oldest version. A version that differs is handed back as an
+    /// error, after falling back to it if it's older.
+    pub fn negotiate(&mut self, response: &Response) -> Result<bool, ClientError> {
+        let hello_pending = self
+            .pending_requests
+            .iter()
+            .any(|pending| pending.command == Command::Hello);
+        let car = match response {
+            Response::Hello(hello) => hello.value.protocol_version,
+            Response::Error(error) if hello_pending && predates_hello(error) => {
+                OLDEST_PROTOCOL_VERSION
+            }
+            _ => return Ok(false),
+        };
+        self.pending_requests
+            .retain(|pending| pending.command != Command::Hello);
 
-        Ok(Some(take(&mut self.rx_data)))
+        let (version, result) = negotiate_protocol_version(car);
+        if version != self.protocol_version {
+            info!(version, "Speaking another version of the protocol");
+            self.protocol_version = version;
+            if let Some(transport) = &mut self.transport {
+                transport.speak_protocol_version(version);
+            }
+        }
+        result.map(|()| true)
     }
 
-    /// Write a command to the serial connection
-    pub fn write_to_serial<S>(&mut self, command: Command, data: S) -> Result<(), ClientError>
+    /// Write a command like [`Self::write_to_serial`], unless it's a status
+    /// poll (see [`POLL_COMMANDS`]) and one for the same status is already on
+    /// its way or waiting for its response, returning the frame if it was
+    /// written
+    pub fn queue<S>(&mut self, command: Command, data: S) -> Result<Option<String>, ClientError>
     where
         S: Serialize + for<'a> Deserialize<'a> + Sized,
     {
-        let prefix = TransitMode::from(command) as u8 as char;
-        let stringified_data =
-            serde_to_string(&data).map_err(|e| ClientError::Parse(e.to_string()))?;
-        let stringified_data = match stringified_data.as_str() {
-            "null" => "{}",
-            stringified => stringified,
-        };
+        if POLL_COMMANDS.contains(&command)
+            && self
+                .pending_requests
+                .iter()
+                .any(|pending| pending.command == command)
+        {
+            debug!(%command, "Already asked for");
+            return Ok(None);
+        }
+        self.write_to_serial(command, data).map(Some)
+    }
 
-        let metadata = MetaData {
-            time: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .map_err(|e| ClientError::Unknown(e.to_string()))?
-                .as_secs_f64(),
-        };
-        let stringified_metadata =
-            serde_to_string(&metadata).map_err(|e| ClientError::Parse(e.to_string()))?;
-
-        writeln!(
-            self.serial,
-            "{prefix}{command}${stringified_data}${stringified_metadata}"
-        )
-        .map_err(|e| ClientError::Serial(e.to_string()))
-    }
-
-    /// Helper function to encapsulate a *response* into an event
-    fn encapsulate_response_to_event<S>(
-        transit_mode: TransitMode,
-        command: Command,
-        metadata: MetaData,
-        value: S,
-    ) -> Event<S>
+    /// Write a command to the serial connection, returning the frame written
+    ///
+    /// The frame is queued behind anything as urgent that's still waiting to
+    /// be written, but ahead of anything less urgent (e.g. a STOP goes out
+    /// before everything else), see [`Self::flush_outgoing`].
+    ///
+    /// Versions of the protocol from before numbering get neither a sequence
+    /// nor a checksum.
+    pub fn write_to_serial<S>(&mut self, command: Command, data: S) -> Result<String, ClientError>
     where
         S: Serialize + for<'a> Deserialize<'a> + Sized,
     {
-        Event {
+        let numbered = self.protocol_version >= NUMBERED_PROTOCOL_VERSION;
+        let event = Event {
             command,
-            transit_mode,
-            transit_type: TransitType::Response,
-            value,
-            metadata,
-        }
-    }
-
-    /// Parse response
-    pub fn parse_response(data: &str) -> Result<Response, ClientError> {
-        let data = data.trim();
-
-        // Sanity check (to prevent out of range panics)
-        // 5 is just an arbitrary (empirical?) value
-        if data.chars().take(5).count() < 5 {
-            Err(ClientError::Parse(
-                "Too short to be a valid response".to_owned(),
-            ))?;
-        }
-
-        // Find the transit *mode*
-        // Safety: Length is guaranteed to be at least 5 characters
-        let first_char = data.chars().next().unwrap();
-        let transit_mode = match first_char {
-            '~' => Ok(TransitMode::ServerToClientResponse),
-            '&' => Ok(TransitMode::SerialBridgeToClientResponse),
-            _ => Err(ClientError::Parse(format!(
-                "Failed to determine transit mode of `{first_char}`"
-            ))),
-        }?;
-
-        // Find the command
-        let split_data = data.trim().split('$').collect::<Vec<_>>();
-        let command = Command::try_from(split_data[0][1..].to_string())?;
-
-        // Find the metadata
-        let metadata_data = split_data[2];
-        // Parse the metadata
-        let metadata = serde_from_str::<MetaData>(metadata_data)?;
-
-        // Get the response
-        // XXX: Serde thinks "{}" is a map while "null" is perfectly plausible
-        // pertaining proportionally for paragliding pedantically in terms of
-        // parsing plainly a plain struct free of frills -- fields
-        let response_data = match split_data[1] {
-            "{}" => "null",
-            non_empty => non_empty,
+            transit_mode: TransitMode::from(command),
+            transit_type: TransitType::Request,
+            value: data,
+            metadata: MetaData {
+                time: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_err(|e| ClientError::Unknown(e.to_string()))?
+                    .as_secs_f64(),
+                sequence: numbered.then_some(self.next_sequence),
+                frame: None,
+                echo: None,
+                client: None,
+            },
         };
+        let sequence = self.next_sequence;
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+        let _request = debug_span!("request", %command, sequence).entered();
 
-        // Parse the response (if error then parse that)
-        match command {
-            // An unknown command
-            Command::Error => {
-                let value = serde_from_str::<ErrorResponse>(response_data)?;
-                Ok(Response::Error(Self::encapsulate_response_to_event(
-                    transit_mode,
-                    command,
-                    metadata,
-                    value,
-                )))
-            }
-            _ => {
-                use Command::*;
-                Ok(match command {
-                    Ping => Response::Ping(Self::encapsulate_response_to_event(
-                        transit_mode,
-                        command,
-                        metadata,
-                        serde_from_str::<PingResponse>(response_data)?,
-                    )),
-                    Start => Response::Start(Self::encapsulate_response_to_event(
-                        transit_mode,
-                        command,
-                        metadata,
-                        serde_from_str::<StartResponse>(response_data)?,
-                    )),
-                    Stop => Response::Stop(Self::encapsulate_response_to_event(
-                        transit_mode,
-                        command,
-                        metadata,
-                        serde_from_str::<StopResponse>(response_data)?,
-                    )),
-                    Status => Response::Status(Self::encapsulate_response_to_event(
-                        transit_mode,
-                        command,
-                        metadata,
-                        serde_from_str::<StatusResponse>(response_data)?,
-                    )),
-                    StaticStatus => Response::StaticStatus(Self::encapsulate_response_to_event(
-                        transit_mode,
-                        command,
-                        metadata,
-                        serde_from_str::<StaticStatusResponse>(response_data)?,
-                    )),
-                    BluetoothStatus => {
-                        Response::BluetoothStatus(Self::encapsulate_response_to_event(
-                            transit_mode,
-                            command,
-                            metadata,
-                            serde_from_str::<BluetoothStatusResponse>(response_data)?,
-                        ))
-                    }
-                    _ => panic!("Got unhandled command {command}"),
-                })
+        let frame = match SEND_CHECKSUMS && numbered {
+            true => Frame::encode_with_checksum(&event)?,
+            false => Frame::encode(&event)?,
+        };
+        self.transport()?;
+        self.outgoing.push(command, frame.clone());
+
+        if expects_response(command) {
+            // A newer request for the same thing replaces the older one
+            self.pending_requests
+                .retain(|pending| pending.command != command);
+            self.pending_requests.push(PendingRequest {
+                sequence,
+                command,
+                frame: frame.clone(),
+                last_sent_time: Instant::now(),
+                attempts: 1_usize,
+            });
+        }
+
+        self.flush_outgoing()?;
+        Ok(frame)
+    }
+
+    /// Write what's waiting to be written, most urgent first, for as long as
+    /// the connection has nothing else waiting to go out
+    ///
+    /// A connection that writes in the background (like [`SerialWorker`]) is
+    /// only ever handed one frame at a time, so the rest wait here where
+    /// anything more urgent can still go ahead of them.
+    pub fn flush_outgoing(&mut self) -> Result<(), ClientError> {
+        loop {
+            let Some(transport) = self.transport.as_mut() else {
+                return Ok(());
+            };
+            if transport.queued() > 0 {
+                return Ok(());
+            }
+            let Some(queued) = self.outgoing.pop() else {
+                return Ok(());
+            };
+            if let Err(e) = transport.write_line(&queued.frame) {
+                self.lose_connection(e.to_string());
+                return Err(e);
             }
+            self.capture_frame(Direction::Sent, &queued.frame);
+            debug!(frame = %queued.frame, "Sent");
+
+            // Only waited on from when it actually went out
+            if let Some(pending) = self
+                .pending_requests
+                .iter_mut()
+                .find(|pending| pending.frame == queued.frame)
+            {
+                pending.last_sent_time = Instant::now();
+            }
+        }
+    }
+
+    /// How many frames are waiting to be written, here or by the connection
+    pub fn queue_depth(&self) -> usize {
+        self.outgoing.len()
+            + self
+                .transport
+                .as_ref()
+                .map(|transport| transport.queued())
+                .unwrap_or_default()
+    }
+
+    /// Resend every request that has gone unanswered for too long, returning
+    /// the frames resent
+    ///
+    /// A request is sent at most `self.max_request_attempts` times, after which
+    /// it is given up on and returned as a [`ClientError::Timeout`]. Nothing
+    /// is resent (or given up on) while disconnected, nor while it's still
+    /// waiting to be written.
+    pub fn retry_pending(&mut self) -> Vec<Result<String, ClientError>> {
+        let mut results = vec![];
+        let (request_timeout, max_request_attempts) =
+            (self.request_timeout, self.max_request_attempts);
+        if self.transport.is_none() {
+            return results;
+        }
+        let outgoing = &mut self.outgoing;
+        let mut pending_requests = take(&mut self.pending_requests);
+        pending_requests.retain_mut(|pending| {
+            if pending.last_sent_time.elapsed().as_secs_f64()
+                < response_time(pending.command, request_timeout)
+                || outgoing.contains(&pending.frame)
+            {
+                return true;
+            }
+            let _request =
+                debug_span!("request", command = %pending.command, sequence = pending.sequence)
+                    .entered();
+            if pending.attempts >= max_request_attempts {
+                warn!(
+                    attempts = pending.attempts,
+                    "Gave up waiting for a response"
+                );
+                results.push(Err(ClientError::Timeout {
+                    command: pending.command,
+                    attempts: pending.attempts,
+                }));
+                return false;
+            }
+
+            info!(attempt = pending.attempts + 1, "No response, sending again");
+            outgoing.push(pending.command, pending.frame.clone());
+            results.push(Ok(pending.frame.clone()));
+            pending.attempts += 1;
+            pending.last_sent_time = Instant::now();
+            true
+        });
+        self.pending_requests = pending_requests;
+        if let Err(e) = self.flush_outgoing() {
+            results.push(Err(e));
         }
+
+        results
     }
 }
+
+/// How long a request can go unanswered before it's sent again, given the
+/// usual request timeout; the bridge scans for a while before answering
+fn response_time(command: Command, request_timeout: f64) -> f64 {
+    match command {
+        Command::BluetoothScan => request_timeout + BLUETOOTH_SCAN_SECONDS,
+        _ => request_timeout,
+    }
+}
+
+/// Whether anything answers a command; the bridge sends nothing back for
+/// connecting or disconnecting
+fn expects_response(command: Command) -> bool {
+    !matches!(command, Command::Connect | Command::Disconnect)
+}
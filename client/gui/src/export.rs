@@ -0,0 +1,867 @@
+/*!
+ * Interface for saving and loading the status information as CSV, JSON lines,
+ * or Parquet
+ * Created by sheepy0125 | MIT license | 2023-04-25
+ */
+
+use std::{
+    collections::HashSet,
+    error::Error,
+    fmt::{self, Display},
+    fs::{metadata, remove_file, File},
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/***** Setup *****/
+// Imports
+#[cfg(feature = "analytics")]
+use crate::analytics::RunAnalytics;
+use crate::{
+    annotations::{write_annotations, Annotation},
+    clock::DisplayTimezone,
+    shared::Units,
+    worker::*,
+};
+use bindings::{
+    ClientError, Command, DistanceInformation, Event, MetaData, StatusResponse, TransitMode,
+    TransitType,
+};
+use csv::{Reader, ReaderBuilder, StringRecord, Writer};
+use serde::{Deserialize, Serialize};
+
+// Constants
+/// How much of the end of a table to read to get its last row, which is far
+/// more than a row takes up
+const LAST_ROW_BYTES: u64 = 1024;
+/// The headers of the CSV columns with each status's sequence and frame
+/// numbers, which are found by name as they come after the analytics
+const SEQUENCE_HEADER: &str = "Sequence number";
+const FRAME_HEADER: &str = "Frame number";
+
+/***** Run exporter *****/
+
+/// How CSV tables are written, as they're for people to read
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct TableOptions {
+    pub units: Units,
+    /// Centimeters, for working out the time to the target and the overshoot
+    /// in the derived columns
+    pub target: Option<f64>,
+}
+
+/// A format runs can be saved in and read back from
+pub trait RunExporter {
+    fn write(file_path: &Path, data: &[Event<StatusResponse>]) -> Result<(), Box<dyn Error>> {
+        Self::write_to(File::create(file_path)?, data)
+    }
+    fn read(file_path: &Path) -> Result<Vec<Event<StatusResponse>>, Box<dyn Error>> {
+        Self::read_from(File::open(file_path)?)
+    }
+    /// Read only the last status, `None` if there are none
+    fn read_last(file_path: &Path) -> Result<Option<Event<StatusResponse>>, Box<dyn Error>> {
+        Ok(Self::read(file_path)?.pop())
+    }
+    fn write_to<W: Write>(writer: W, data: &[Event<StatusResponse>]) -> Result<(), Box<dyn Error>>;
+    /// Write a run, counting off every status as it's written and stopping
+    /// once cancelled
+    ///
+    /// Formats that can't be written a status at a time count them all off
+    /// at the end
+    fn write_tracked<W: Write>(
+        writer: W,
+        data: &[Event<StatusResponse>],
+        progress: &JobProgress,
+    ) -> Result<(), Box<dyn Error>> {
+        Self::write_to(writer, data)?;
+        progress.advance(data.len());
+        Ok(())
+    }
+    fn read_from<R: Read>(reader: R) -> Result<Vec<Event<StatusResponse>>, Box<dyn Error>>;
+}
+
+/// The formats a run can be exported as, told apart by their extension
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum RunFormat {
+    #[default]
+    CSV,
+    /// One JSON object per status
+    JSONLines,
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+impl RunFormat {
+    pub const ALL: &'static [Self] = &[
+        Self::CSV,
+        Self::JSONLines,
+        #[cfg(feature = "parquet")]
+        Self::Parquet,
+    ];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::CSV => "csv",
+            Self::JSONLines => "jsonl",
+            #[cfg(feature = "parquet")]
+            Self::Parquet => "parquet",
+        }
+    }
+
+    /// The format of a run going by its extension, `None` if it isn't a run
+    pub fn of(file_path: &Path) -> Option<Self> {
+        let extension = file_path.extension()?;
+        Self::ALL
+            .iter()
+            .find(|format| extension == format.extension())
+            .copied()
+    }
+
+    /// The path with this format's extension
+    pub fn path(&self, file_path: &Path) -> PathBuf {
+        file_path.with_extension(self.extension())
+    }
+
+    pub fn write(
+        &self,
+        file_path: &Path,
+        data: &[Event<StatusResponse>],
+    ) -> Result<(), Box<dyn Error>> {
+        self.write_with(file_path, data, TableOptions::default())
+    }
+
+    /// Write a run with CSV tables written to some options, while the other
+    /// formats are always only the statuses in centimeters
+    pub fn write_with(
+        &self,
+        file_path: &Path,
+        data: &[Event<StatusResponse>],
+        options: TableOptions,
+    ) -> Result<(), Box<dyn Error>> {
+        match self {
+            Self::CSV => CSVDynamicStatus::write_with(
+                File::create(file_path)?,
+                data,
+                options,
+                &JobProgress::default(),
+            ),
+            Self::JSONLines => JSONLinesDynamicStatus::write(file_path, data),
+            #[cfg(feature = "parquet")]
+            Self::Parquet => ParquetDynamicStatus::write(file_path, data),
+        }
+    }
+
+    pub fn write_tracked(
+        &self,
+        file_path: &Path,
+        data: &[Event<StatusResponse>],
+        options: TableOptions,
+        progress: &JobProgress,
+    ) -> Result<(), Box<dyn Error>> {
+        let file = File::create(file_path)?;
+        match self {
+            Self::CSV => CSVDynamicStatus::write_with(file, data, options, progress),
+            Self::JSONLines => JSONLinesDynamicStatus::write_tracked(file, data, progress),
+            #[cfg(feature = "parquet")]
+            Self::Parquet => ParquetDynamicStatus::write_tracked(file, data, progress),
+        }
+    }
+
+    pub fn read(&self, file_path: &Path) -> Result<Vec<Event<StatusResponse>>, Box<dyn Error>> {
+        match self {
+            Self::CSV => CSVDynamicStatus::read(file_path),
+            Self::JSONLines => JSONLinesDynamicStatus::read(file_path),
+            #[cfg(feature = "parquet")]
+            Self::Parquet => ParquetDynamicStatus::read(file_path),
+        }
+    }
+
+    pub fn read_last(
+        &self,
+        file_path: &Path,
+    ) -> Result<Option<Event<StatusResponse>>, Box<dyn Error>> {
+        match self {
+            Self::CSV => CSVDynamicStatus::read_last(file_path),
+            Self::JSONLines => JSONLinesDynamicStatus::read_last(file_path),
+            #[cfg(feature = "parquet")]
+            Self::Parquet => ParquetDynamicStatus::read_last(file_path),
+        }
+    }
+}
+impl Display for RunFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::CSV => "CSV",
+                Self::JSONLines => "JSONL",
+                #[cfg(feature = "parquet")]
+                Self::Parquet => "Parquet",
+            }
+        )
+    }
+}
+
+/// Read a run in whatever format its extension says, taking anything else to
+/// be CSV as that's all runs used to be saved as
+pub fn read_run(file_path: &Path) -> Result<Vec<Event<StatusResponse>>, Box<dyn Error>> {
+    RunFormat::of(file_path).unwrap_or_default().read(file_path)
+}
+
+/// Read the last status of a run in whatever format its extension says
+pub fn read_last_of_run(file_path: &Path) -> Result<Option<Event<StatusResponse>>, Box<dyn Error>> {
+    RunFormat::of(file_path)
+        .unwrap_or_default()
+        .read_last(file_path)
+}
+
+/***** Saving in the background *****/
+
+/// A run saved in the background
+#[derive(Clone, PartialEq, Debug)]
+pub struct SavedRun {
+    pub path: PathBuf,
+    /// Bytes
+    pub size: u64,
+}
+
+/// Save a run and its annotations on a background thread, counting off
+/// statuses as they're written
+///
+/// A run that's cancelled partway through is deleted rather than left cut
+/// short. The statuses can be shared with a [`RunSnapshot`](crate::run_snapshot::RunSnapshot)
+/// instead of copied for it.
+pub fn save_in_background(
+    format: RunFormat,
+    options: TableOptions,
+    file_path: PathBuf,
+    statuses: impl Into<Arc<[Event<StatusResponse>]>>,
+    annotations: Vec<Annotation>,
+) -> BackgroundJob<SavedRun> {
+    let statuses = statuses.into();
+    let description = format!(
+        "Saving {}",
+        file_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    );
+    BackgroundJob::spawn(description, statuses.len(), move |progress| {
+        let written = format
+            .write_tracked(&file_path, &statuses, options, progress)
+            .map_err(|e| ClientError::CSV(e.to_string()))
+            .and_then(|()| write_annotations(&file_path, &annotations));
+        if written.is_err() || progress.cancelled() {
+            let _ = remove_file(&file_path);
+            let _ = write_annotations(&file_path, &[]);
+            written?;
+            return Err(ClientError::CSV("Cancelled".to_owned()));
+        }
+
+        let size = metadata(&file_path)
+            .map(|metadata| metadata.len())
+            .map_err(|e| ClientError::CSV(e.to_string()))?;
+        Ok(SavedRun {
+            path: file_path,
+            size,
+        })
+    })
+}
+
+/// A file size for people, e.g. `1.5 MB`
+pub fn size_text(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0_usize;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{size:.1} {}", UNITS[unit]),
+    }
+}
+
+/***** CSV *****/
+
+pub struct CSVDynamicStatus;
+impl RunExporter for CSVDynamicStatus {
+    fn read_from<R: Read>(reader: R) -> Result<Vec<Event<StatusResponse>>, Box<dyn Error>> {
+        let mut csv_reader = Reader::from_reader(reader);
+        let columns = TableColumns::of(csv_reader.headers()?);
+        let mut ret_events = vec![];
+
+        for record_result in csv_reader.records() {
+            ret_events.push(parse_record(&record_result?, &columns)?);
+        }
+
+        Ok(ret_events)
+    }
+
+    fn write_to<W: Write>(writer: W, data: &[Event<StatusResponse>]) -> Result<(), Box<dyn Error>> {
+        Self::write_tracked(writer, data, &JobProgress::default())
+    }
+
+    fn write_tracked<W: Write>(
+        writer: W,
+        data: &[Event<StatusResponse>],
+        progress: &JobProgress,
+    ) -> Result<(), Box<dyn Error>> {
+        Self::write_with(writer, data, TableOptions::default(), progress)
+    }
+
+    /// Only the end of the file is read, which is enough for one row, so this
+    /// stays quick however long the run was
+    fn read_last(file_path: &Path) -> Result<Option<Event<StatusResponse>>, Box<dyn Error>> {
+        let (tail, _) = read_tail(file_path)?;
+
+        // The first line is either the header or cut off partway through
+        let last_line = tail
+            .split_once('\n')
+            .and_then(|(_, rows)| rows.lines().rfind(|line| !line.trim().is_empty()));
+        let Some(last_line) = last_line else {
+            return Ok(None);
+        };
+        // The header is still needed for the units and where the numbers are
+        let mut header = String::new();
+        BufReader::new(File::open(file_path)?).read_line(&mut header)?;
+        let columns = TableColumns::of(Reader::from_reader(header.as_bytes()).headers()?);
+        let mut csv_reader = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(last_line.as_bytes());
+        match csv_reader.records().next() {
+            Some(record) => Ok(Some(parse_record(&record?, &columns)?)),
+            None => Ok(None),
+        }
+    }
+}
+impl CSVDynamicStatus {
+    /// Write a run with the distances and velocities in some units, which the
+    /// header says so it's read back in centimeters, the analytics after
+    /// every status in columns at the end (when built with them), and the
+    /// sequence and frame numbers after those (blank where there are none)
+    pub fn write_with<W: Write>(
+        writer: W,
+        data: &[Event<StatusResponse>],
+        options: TableOptions,
+        progress: &JobProgress,
+    ) -> Result<(), Box<dyn Error>> {
+        let units = options.units;
+        let mut csv_writer = Writer::from_writer(writer);
+
+        // The Unix time is UTC already, but spelled out as well so nobody has
+        // to guess which time zone the table is in
+        let mut header = vec![
+            "Unix time (UTC)".to_owned(),
+            "Running".to_owned(),
+            "Uptime".to_owned(),
+            "Runtime".to_owned(),
+            distance_header(units),
+            format!("Velocity in {}/second", units.distance_name()),
+            "Magnet hit counter".to_owned(),
+            "Stage".to_owned(),
+            "Speed limit violations".to_owned(),
+            "Time (UTC)".to_owned(),
+        ];
+        #[cfg(feature = "analytics")]
+        header.extend([
+            format!("Average velocity in {}/second", units.distance_name()),
+            format!("Peak velocity in {}/second", units.distance_name()),
+            "Seconds to target".to_owned(),
+            format!("Projected overshoot in {}", units.distance_name()),
+        ]);
+        header.extend([SEQUENCE_HEADER.to_owned(), FRAME_HEADER.to_owned()]);
+        csv_writer.write_record(&header)?;
+
+        // Left blank where there's nothing to work out
+        #[cfg(feature = "analytics")]
+        let (derived, mut analytics) = (
+            |value: Option<f64>| value.map(|value| value.to_string()).unwrap_or_default(),
+            RunAnalytics::over(data, options.target).into_iter(),
+        );
+        let number = |number: Option<u32>| number.map(|number| number.to_string());
+        for record in data {
+            if progress.cancelled() {
+                return Err("Cancelled".into());
+            }
+            let time = format!("{}", record.metadata.time);
+            let running = format!("{}", record.value.running);
+            let uptime = format!("{}", record.value.uptime);
+            let runtime = format!("{}", record.value.runtime);
+            let distance = format!("{}", units.from_centimeters(record.value.distance.distance));
+            let velocity = format!("{}", units.from_centimeters(record.value.distance.velocity));
+            let magnet_hit_counter = format!("{}", record.value.distance.magnet_hit_counter);
+            let stage = format!("{}", record.value.stage as u8);
+            let speed_limit_violations = format!("{}", record.value.speed_limit_violations);
+            let utc_time =
+                DisplayTimezone::Utc.format_unix(record.metadata.time, "%Y-%m-%dT%H:%M:%S%.3fZ");
+            let mut row = vec![
+                time,
+                running,
+                uptime,
+                runtime,
+                distance,
+                velocity,
+                magnet_hit_counter,
+                stage,
+                speed_limit_violations,
+                utc_time,
+            ];
+            #[cfg(feature = "analytics")]
+            if let Some(analytics) = analytics.next() {
+                row.extend([
+                    units
+                        .from_centimeters(analytics.average_velocity)
+                        .to_string(),
+                    units.from_centimeters(analytics.peak_velocity).to_string(),
+                    derived(analytics.time_to_target),
+                    derived(
+                        analytics
+                            .projected_overshoot
+                            .map(|overshoot| units.from_centimeters(overshoot)),
+                    ),
+                ]);
+            }
+            row.extend([
+                number(record.metadata.sequence).unwrap_or_default(),
+                number(record.metadata.frame).unwrap_or_default(),
+            ]);
+            csv_writer.write_record(&row)?;
+            progress.advance(1);
+        }
+
+        csv_writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// The distance column's header for tables in some units
+fn distance_header(units: Units) -> String {
+    format!("Distance in {}", units.distance_name())
+}
+
+/// What a status table's header says about the rows under it
+struct TableColumns {
+    /// Anything else is taken to be centimeters, as that's all tables used to
+    /// be in
+    units: Units,
+    /// Not in tables saved before the numbers were
+    sequence: Option<usize>,
+    frame: Option<usize>,
+}
+impl TableColumns {
+    fn of(header: &StringRecord) -> Self {
+        let position = |name: &str| header.iter().position(|column| column == name);
+        Self {
+            units: Units::ALL
+                .into_iter()
+                .find(|units| header.get(4) == Some(distance_header(*units).as_str()))
+                .unwrap_or_default(),
+            sequence: position(SEQUENCE_HEADER),
+            frame: position(FRAME_HEADER),
+        }
+    }
+}
+
+/// Parse one row of a status table
+fn parse_record(
+    record: &StringRecord,
+    columns: &TableColumns,
+) -> Result<Event<StatusResponse>, Box<dyn Error>> {
+    let units = columns.units;
+    // Left blank for statuses without one
+    let number = |column: Option<usize>| -> Result<Option<u32>, Box<dyn Error>> {
+        match column.and_then(|column| record.get(column)) {
+            Some(number) if !number.is_empty() => Ok(Some(number.parse()?)),
+            _ => Ok(None),
+        }
+    };
+    let time = record[0].parse()?;
+    let running = record[1].parse()?;
+    let uptime = record[2].parse()?;
+    let runtime = record[3].parse()?;
+    let distance = units.to_centimeters(record[4].parse()?);
+    let velocity = units.to_centimeters(record[5].parse()?);
+    let magnet_hit_counter = record[6].parse()?;
+    let stage = record[7]
+        .parse::<u8>()?
+        .try_into()
+        .map_err(|_| "Failed to get status stage")?;
+    // Not in tables saved before the speed limit existed
+    let speed_limit_violations = match record.get(8) {
+        Some(violations) => violations.parse()?,
+        None => 0,
+    };
+
+    Ok(status_event(
+        time,
+        number(columns.sequence)?,
+        number(columns.frame)?,
+        StatusResponse {
+            running,
+            uptime,
+            runtime,
+            distance: DistanceInformation {
+                distance,
+                velocity,
+                magnet_hit_counter,
+            },
+            stage,
+            speed_limit_violations,
+            executed_plan: None,
+            radio_stop: None,
+            power: None,
+        },
+    ))
+}
+
+/***** JSON lines *****/
+
+/// One line of a JSON lines run
+#[derive(Serialize, Deserialize)]
+struct JSONLinesRecord {
+    /// Unix time
+    time: f64,
+    /// Left out for statuses without them, and in runs saved before them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sequence: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    frame: Option<u32>,
+    status: StatusResponse,
+}
+
+pub struct JSONLinesDynamicStatus;
+impl RunExporter for JSONLinesDynamicStatus {
+    fn read_from<R: Read>(reader: R) -> Result<Vec<Event<StatusResponse>>, Box<dyn Error>> {
+        let mut ret_events = vec![];
+
+        for line in BufReader::new(reader).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            ret_events.push(parse_line(&line)?);
+        }
+
+        Ok(ret_events)
+    }
+
+    fn write_to<W: Write>(writer: W, data: &[Event<StatusResponse>]) -> Result<(), Box<dyn Error>> {
+        Self::write_tracked(writer, data, &JobProgress::default())
+    }
+
+    fn write_tracked<W: Write>(
+        mut writer: W,
+        data: &[Event<StatusResponse>],
+        progress: &JobProgress,
+    ) -> Result<(), Box<dyn Error>> {
+        for record in data {
+            if progress.cancelled() {
+                return Err("Cancelled".into());
+            }
+            Self::write_line(&mut writer, record)?;
+            progress.advance(1);
+        }
+
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Only the end of the file is read, like for CSV
+    fn read_last(file_path: &Path) -> Result<Option<Event<StatusResponse>>, Box<dyn Error>> {
+        let (tail, whole_file) = read_tail(file_path)?;
+
+        // Without a header, the first line is only cut off if the file is
+        // longer than what was read
+        let lines = match whole_file {
+            true => Some(tail.as_str()),
+            false => tail.split_once('\n').map(|(_, lines)| lines),
+        };
+        match lines.and_then(|lines| lines.lines().rfind(|line| !line.trim().is_empty())) {
+            Some(last_line) => Ok(Some(parse_line(last_line)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl JSONLinesDynamicStatus {
+    /// Write one status as a line, e.g. to append it to a run as it comes in
+    pub fn write_line<W: Write>(
+        mut writer: W,
+        record: &Event<StatusResponse>,
+    ) -> Result<(), Box<dyn Error>> {
+        let line = JSONLinesRecord {
+            time: record.metadata.time,
+            sequence: record.metadata.sequence,
+            frame: record.metadata.frame,
+            status: record.value.clone(),
+        };
+        serde_json::to_writer(&mut writer, &line)?;
+        writer.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+/// Parse one line of a JSON lines run
+fn parse_line(line: &str) -> Result<Event<StatusResponse>, Box<dyn Error>> {
+    let record = serde_json::from_str::<JSONLinesRecord>(line)?;
+    Ok(status_event(
+        record.time,
+        record.sequence,
+        record.frame,
+        record.status,
+    ))
+}
+
+/***** Parquet *****/
+
+#[cfg(feature = "parquet")]
+pub use parquet_table::*;
+
+#[cfg(feature = "parquet")]
+mod parquet_table {
+    use super::*;
+    use arrow_array::{
+        Array, ArrayRef, BooleanArray, Float64Array, RecordBatch, UInt32Array, UInt64Array,
+        UInt8Array,
+    };
+    use arrow_schema::{DataType, Field, Schema};
+    use bytes::Bytes;
+    use parquet::arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ArrowWriter};
+    use std::sync::Arc;
+
+    pub struct ParquetDynamicStatus;
+    impl RunExporter for ParquetDynamicStatus {
+        fn read_from<R: Read>(mut reader: R) -> Result<Vec<Event<StatusResponse>>, Box<dyn Error>> {
+            // Parquet keeps its metadata at the end, so the whole file is
+            // needed up front
+            let mut buffer = vec![];
+            reader.read_to_end(&mut buffer)?;
+            let batches = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(buffer))?.build()?;
+            let mut ret_events = vec![];
+
+            for batch in batches {
+                let batch = batch?;
+                let time = column::<Float64Array>(&batch, "time")?;
+                let running = column::<BooleanArray>(&batch, "running")?;
+                let uptime = column::<UInt64Array>(&batch, "uptime")?;
+                let runtime = column::<UInt64Array>(&batch, "runtime")?;
+                let distance = column::<Float64Array>(&batch, "distance")?;
+                let velocity = column::<Float64Array>(&batch, "velocity")?;
+                let magnet_hit_counter = column::<UInt64Array>(&batch, "magnet_hit_counter")?;
+                let stage = column::<UInt8Array>(&batch, "stage")?;
+                let speed_limit_violations =
+                    column::<UInt64Array>(&batch, "speed_limit_violations")?;
+                // Not in runs saved before the numbers were
+                let sequence = column::<UInt32Array>(&batch, "sequence").ok();
+                let frame = column::<UInt32Array>(&batch, "frame").ok();
+                let number = |numbers: Option<&UInt32Array>, idx: usize| {
+                    numbers
+                        .filter(|numbers| numbers.is_valid(idx))
+                        .map(|numbers| numbers.value(idx))
+                };
+
+                for idx in 0..batch.num_rows() {
+                    ret_events.push(status_event(
+                        time.value(idx),
+                        number(sequence, idx),
+                        number(frame, idx),
+                        StatusResponse {
+                            running: running.value(idx),
+                            uptime: uptime.value(idx) as usize,
+                            runtime: runtime.value(idx) as usize,
+                            distance: DistanceInformation {
+                                distance: distance.value(idx),
+                                velocity: velocity.value(idx),
+                                magnet_hit_counter: magnet_hit_counter.value(idx) as usize,
+                            },
+                            stage: stage
+                                .value(idx)
+                                .try_into()
+                                .map_err(|_| "Failed to get status stage")?,
+                            speed_limit_violations: speed_limit_violations.value(idx) as usize,
+                            executed_plan: None,
+                            radio_stop: None,
+                            power: None,
+                        },
+                    ));
+                }
+            }
+
+            Ok(ret_events)
+        }
+
+        fn write_to<W: Write>(
+            mut writer: W,
+            data: &[Event<StatusResponse>],
+        ) -> Result<(), Box<dyn Error>> {
+            let schema = Arc::new(Schema::new(vec![
+                Field::new("time", DataType::Float64, false),
+                Field::new("running", DataType::Boolean, false),
+                Field::new("uptime", DataType::UInt64, false),
+                Field::new("runtime", DataType::UInt64, false),
+                Field::new("distance", DataType::Float64, false),
+                Field::new("velocity", DataType::Float64, false),
+                Field::new("magnet_hit_counter", DataType::UInt64, false),
+                Field::new("stage", DataType::UInt8, false),
+                Field::new("speed_limit_violations", DataType::UInt64, false),
+                Field::new("sequence", DataType::UInt32, true),
+                Field::new("frame", DataType::UInt32, true),
+            ]));
+            let values = |value: fn(&Event<StatusResponse>) -> u64| -> ArrayRef {
+                Arc::new(UInt64Array::from_iter_values(data.iter().map(value)))
+            };
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(Float64Array::from_iter_values(
+                    data.iter().map(|record| record.metadata.time),
+                )),
+                Arc::new(BooleanArray::from(
+                    data.iter()
+                        .map(|record| record.value.running)
+                        .collect::<Vec<_>>(),
+                )),
+                values(|record| record.value.uptime as u64),
+                values(|record| record.value.runtime as u64),
+                Arc::new(Float64Array::from_iter_values(
+                    data.iter().map(|record| record.value.distance.distance),
+                )),
+                Arc::new(Float64Array::from_iter_values(
+                    data.iter().map(|record| record.value.distance.velocity),
+                )),
+                values(|record| record.value.distance.magnet_hit_counter as u64),
+                Arc::new(UInt8Array::from_iter_values(
+                    data.iter().map(|record| record.value.stage as u8),
+                )),
+                values(|record| record.value.speed_limit_violations as u64),
+                Arc::new(UInt32Array::from(
+                    data.iter()
+                        .map(|record| record.metadata.sequence)
+                        .collect::<Vec<_>>(),
+                )),
+                Arc::new(UInt32Array::from(
+                    data.iter()
+                        .map(|record| record.metadata.frame)
+                        .collect::<Vec<_>>(),
+                )),
+            ];
+            let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+            // The writer has to be `Send`, which any writer here may not be
+            let mut buffer = vec![];
+            let mut parquet_writer = ArrowWriter::try_new(&mut buffer, schema, None)?;
+            parquet_writer.write(&batch)?;
+            parquet_writer.close()?;
+            writer.write_all(&buffer)?;
+            writer.flush()?;
+
+            Ok(())
+        }
+    }
+
+    /// Get a column of a batch as the type it was written as
+    fn column<'a, A: Array + 'static>(
+        batch: &'a RecordBatch,
+        name: &str,
+    ) -> Result<&'a A, Box<dyn Error>> {
+        batch
+            .column_by_name(name)
+            .and_then(|column| column.as_any().downcast_ref::<A>())
+            .ok_or_else(|| format!("Missing the {name} column").into())
+    }
+}
+
+/***** Merging *****/
+
+/// Combine the statuses recorded by the client with the server's log of them
+/// into one timeline
+///
+/// Statuses are the same status on both sides when they have the same
+/// sequence and frame numbers. Ones without either (from runs saved before
+/// the numbers were) go by the time the server sent them instead, to the
+/// millisecond as that's the precision it sends times with. Where both have
+/// the same status, the server's copy wins as it can't have been lost in
+/// transit.
+pub fn merge_status_tables(
+    client: Vec<Event<StatusResponse>>,
+    server: Vec<Event<StatusResponse>>,
+) -> Vec<Event<StatusResponse>> {
+    #[derive(PartialEq, Eq, Hash)]
+    enum Key {
+        Numbers(Option<u32>, Option<u32>),
+        Milliseconds(i64),
+    }
+    let key = |status: &Event<StatusResponse>| match status.metadata {
+        MetaData {
+            sequence: None,
+            frame: None,
+            time,
+            ..
+        } => Key::Milliseconds((time * 1000.0).round() as i64),
+        MetaData {
+            sequence, frame, ..
+        } => Key::Numbers(sequence, frame),
+    };
+
+    let mut merged = server;
+    let mut seen = merged.iter().map(key).collect::<HashSet<_>>();
+    for status in client {
+        if seen.insert(key(&status)) {
+            merged.push(status);
+        }
+    }
+    merged.sort_by(|a, b| a.metadata.time.total_cmp(&b.metadata.time));
+
+    merged
+}
+
+/// Merge a run recorded by the client with the server's log of it into
+/// another run, in whichever format its extension says, returning how many
+/// statuses it has
+pub fn merge_runs(client: &Path, server: &Path, output: &Path) -> Result<usize, Box<dyn Error>> {
+    let merged = merge_status_tables(read_run(client)?, read_run(server)?);
+    RunFormat::of(output)
+        .unwrap_or_default()
+        .write(output, &merged)?;
+    Ok(merged.len())
+}
+
+/***** Helper functions *****/
+
+/// A status as if it was just received at `time`, with the numbers it came
+/// with
+fn status_event(
+    time: f64,
+    sequence: Option<u32>,
+    frame: Option<u32>,
+    value: StatusResponse,
+) -> Event<StatusResponse> {
+    Event {
+        command: Command::Status,
+        transit_mode: TransitMode::ServerToClientResponse,
+        transit_type: TransitType::Response,
+        value,
+        metadata: MetaData {
+            time,
+            sequence,
+            frame,
+            echo: None,
+            client: None,
+        },
+    }
+}
+
+/// Read the end of a file, which is enough for one row, and whether that's
+/// the whole file
+fn read_tail(file_path: &Path) -> Result<(String, bool), Box<dyn Error>> {
+    let mut file = File::open(file_path)?;
+    let length = file.seek(SeekFrom::End(0))?;
+    let start = length.saturating_sub(LAST_ROW_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+    let mut tail = String::new();
+    file.read_to_string(&mut tail)?;
+    Ok((tail, start == 0))
+}
@@ -0,0 +1,113 @@
+/*!
+ * Making the car misbehave on cue, for trying out how the GUI handles errors
+ * without waiting on the hardware to fail
+ *
+ * Only injections themselves are built without the `debug-menu` feature, as
+ * replaying wire logs feeds frames in the same way
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+#[cfg(feature = "debug-menu")]
+use bindings::{
+    ClientError, Command, ErrorResponse, Event, Frame, MetaData, ServerError, TransitMode,
+    TransitType,
+};
+
+// Constants
+#[cfg(feature = "debug-menu")]
+/// The error responses offered in the debug menu, one of each kind the server
+/// sends
+pub const INJECTABLE_ERRORS: [ServerError; 6] = [
+    ServerError::MalformedRequestOtherError,
+    ServerError::FailedToStartAlreadyStarted,
+    ServerError::FailedToStartMotorControlFailed,
+    ServerError::FailedToStopNotStarted,
+    ServerError::FailedStatusCouldNotAcquireDistanceLock,
+    ServerError::AnyOtherError,
+];
+#[cfg(feature = "debug-menu")]
+/// What injected error responses say, so they aren't mistaken for real ones
+pub const INJECTED_MESSAGE: &str = "Injected from the debug menu";
+
+/***** Fault injection *****/
+
+/// Something fed into the client as if it came from the car
+#[derive(Clone, PartialEq, Debug)]
+pub enum Injection {
+    /// A frame, read like any other
+    Frame(String),
+    /// The connection going down
+    LinkDown,
+}
+
+/// The ways a frame can come in broken
+#[cfg(feature = "debug-menu")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum MalformedFrame {
+    /// Not a frame at all, like line noise
+    #[default]
+    Garbage,
+    /// A status whose checksum doesn't match, which is asked for again
+    BadChecksum,
+    /// Cut off partway through
+    Truncated,
+    /// A command nothing knows about
+    UnknownCommand,
+}
+#[cfg(feature = "debug-menu")]
+impl MalformedFrame {
+    pub const ALL: [Self; 4] = [
+        Self::Garbage,
+        Self::BadChecksum,
+        Self::Truncated,
+        Self::UnknownCommand,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Garbage => "Garbage",
+            Self::BadChecksum => "Bad checksum",
+            Self::Truncated => "Truncated",
+            Self::UnknownCommand => "Unknown command",
+        }
+    }
+
+    /// The frame itself
+    pub fn frame(&self) -> String {
+        match self {
+            Self::Garbage => "\u{1}\u{7f}~~$garbage$%",
+            Self::BadChecksum => "~STATUS${}${\"time\": 0.0}*0000",
+            Self::Truncated => "~PING${\"sent_time\": 1.0}${\"ti",
+            Self::UnknownCommand => "~SELFDESTRUCT${}${\"time\": 0.0}",
+        }
+        .to_owned()
+    }
+}
+
+/// An error response from the server, answering the request with the sequence
+/// if there is one
+#[cfg(feature = "debug-menu")]
+pub fn error_response(
+    error: ServerError,
+    sequence: Option<u32>,
+    time: f64,
+) -> Result<String, ClientError> {
+    Frame::encode_with_checksum(&Event {
+        command: Command::Error,
+        transit_mode: TransitMode::ServerToClientResponse,
+        transit_type: TransitType::Response,
+        value: ErrorResponse {
+            error_variant: error as u8,
+            message: INJECTED_MESSAGE.to_owned(),
+        },
+        metadata: MetaData {
+            time,
+            sequence,
+            frame: None,
+            echo: None,
+            client: None,
+        },
+    })
+}
@@ -0,0 +1,277 @@
+/*!
+ * A file picker made for the small touch screen, replacing `egui_file` there
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{export::RunFormat, run_cache::RunCache};
+use eframe::epaint::{pos2, vec2};
+use egui::{Button, Context, Label, Rect, ScrollArea, Ui, Window};
+use std::{
+    fs::read_dir,
+    path::{Path, PathBuf},
+};
+
+// Constants
+const KEYPAD_ROWS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl-", "zxcvbnm_."];
+const ENTRY_BUTTON_HEIGHT: f32 = 36.0;
+const KEY_BUTTON_SIZE: [f32; 2] = [40.0, 36.0];
+const CONTROL_BUTTON_SIZE: [f32; 2] = [70.0, 36.0];
+
+/***** File picker *****/
+
+/// What the picked file is for
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum FilePickerMode {
+    Open,
+    Save,
+    /// Merging into the current statuses
+    Merge,
+    /// Comparing live runs against
+    Baseline,
+    /// Importing a settings file instead of a run
+    Settings,
+}
+
+/// A full screen file picker with touch-sized targets
+///
+/// Shows the recent files, a browser for the session directory (only
+/// directories and runs, or settings files when importing them), and for
+/// saving, a keypad to type a new name.
+pub struct FilePicker {
+    pub mode: FilePickerMode,
+    pub directory: PathBuf,
+    pub new_name: String,
+    /// What a new name is saved as
+    pub format: RunFormat,
+    pub show_keypad: bool,
+    pub open: bool,
+    entries: Vec<PathBuf>,
+}
+impl FilePicker {
+    pub fn new(mode: FilePickerMode, directory: PathBuf) -> Self {
+        let mut picker = Self {
+            mode,
+            directory,
+            new_name: String::new(),
+            format: RunFormat::default(),
+            show_keypad: false,
+            open: true,
+            entries: vec![],
+        };
+        picker.refresh();
+        picker
+    }
+
+    /// Re-read the entries of the current directory, directories first
+    pub fn refresh(&mut self) {
+        self.entries = read_dir(&self.directory)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| {
+                        path.is_dir()
+                            || match self.mode {
+                                FilePickerMode::Settings => path
+                                    .extension()
+                                    .is_some_and(|extension| extension == "toml"),
+                                _ => RunFormat::of(path).is_some(),
+                            }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.entries
+            .sort_by_key(|path| (!path.is_dir(), path.file_name().map(|name| name.to_owned())));
+    }
+
+    /// Move into another directory
+    pub fn change_directory(&mut self, directory: PathBuf) {
+        self.directory = directory;
+        self.refresh();
+    }
+
+    /// Show the picker, returning the chosen path once one is picked
+    ///
+    /// Runs are listed with a summary from `run_cache`, only read once
+    /// they're scrolled into view
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        recent_files: &[PathBuf],
+        run_cache: &mut RunCache,
+    ) -> Option<PathBuf> {
+        let mut picked = None;
+        // Fill whatever size the screen was configured to be
+        let screen_size = ctx.screen_rect().size();
+        let entry_button_size = [screen_size.x - 60.0, ENTRY_BUTTON_HEIGHT];
+
+        Window::new(match self.mode {
+            FilePickerMode::Open => "Open status",
+            FilePickerMode::Save => "Save status",
+            FilePickerMode::Merge => "Merge status",
+            FilePickerMode::Baseline => "Choose baseline",
+            FilePickerMode::Settings => "Import settings",
+        })
+        .collapsible(false)
+        .resizable(false)
+        .title_bar(false)
+        .fixed_pos(pos2(0.0, 0.0))
+        .fixed_size(screen_size - vec2(16.0, 16.0))
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_sized(CONTROL_BUTTON_SIZE, Button::new("Cancel"))
+                    .clicked()
+                {
+                    self.open = false;
+                }
+                if ui
+                    .add_sized(CONTROL_BUTTON_SIZE, Button::new("Up"))
+                    .clicked()
+                {
+                    if let Some(parent) = self.directory.parent() {
+                        self.change_directory(parent.to_path_buf());
+                    }
+                }
+                if self.mode == FilePickerMode::Save
+                    && ui
+                        .add_sized(
+                            CONTROL_BUTTON_SIZE,
+                            Button::new(match self.show_keypad {
+                                true => "Browse",
+                                false => "New name",
+                            }),
+                        )
+                        .clicked()
+                {
+                    self.show_keypad = !self.show_keypad;
+                }
+                ui.add(Label::new(self.directory.display().to_string()).wrap(true));
+            });
+            ui.separator();
+
+            if self.show_keypad {
+                picked = self.show_keypad(ui);
+                return;
+            }
+
+            ScrollArea::vertical()
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    // Only runs are recent files
+                    if !recent_files.is_empty() && self.mode != FilePickerMode::Settings {
+                        ui.strong("Recent");
+                        for file in recent_files.iter() {
+                            if ui
+                                .add_sized(entry_button_size, Button::new(display_name(file)))
+                                .clicked()
+                            {
+                                picked = Some(file.clone());
+                            }
+                        }
+                        ui.strong("Session directory");
+                    }
+
+                    let mut change_directory = None;
+                    for entry in self.entries.iter() {
+                        let is_dir = entry.is_dir();
+                        let visible = ui.is_rect_visible(Rect::from_min_size(
+                            ui.next_widget_position(),
+                            entry_button_size.into(),
+                        ));
+                        let is_run = self.mode != FilePickerMode::Settings;
+                        let text = match (is_dir, visible && is_run) {
+                            (true, _) => format!("{}/", display_name(entry)),
+                            (false, true) => match run_cache.summary(entry) {
+                                Some(summary) => format!(
+                                    "{}  ({:.1}cm in {}s)",
+                                    display_name(entry),
+                                    summary.distance,
+                                    summary.runtime
+                                ),
+                                None => display_name(entry),
+                            },
+                            (false, false) => display_name(entry),
+                        };
+                        if ui.add_sized(entry_button_size, Button::new(text)).clicked() {
+                            match is_dir {
+                                true => change_directory = Some(entry.clone()),
+                                false => picked = Some(entry.clone()),
+                            }
+                        }
+                    }
+                    if self.entries.is_empty() {
+                        ui.label("Nothing to pick here");
+                    }
+                    if let Some(directory) = change_directory {
+                        self.change_directory(directory);
+                    }
+                });
+        });
+
+        if picked.is_some() {
+            self.open = false;
+        }
+        picked
+    }
+
+    /// Show the keypad for typing a new file name, returning the path once
+    /// the name is confirmed
+    fn show_keypad(&mut self, ui: &mut Ui) -> Option<PathBuf> {
+        let mut picked = None;
+
+        ui.horizontal(|ui| {
+            ui.add_sized(
+                [
+                    ui.ctx().screen_rect().width() - 2.0 * CONTROL_BUTTON_SIZE[0] - 40.0,
+                    KEY_BUTTON_SIZE[1],
+                ],
+                Label::new(format!("{}.{}", self.new_name, self.format.extension())),
+            );
+            if ui
+                .add_sized(CONTROL_BUTTON_SIZE, Button::new("Delete"))
+                .clicked()
+            {
+                self.new_name.pop();
+            }
+            if ui
+                .add_enabled(
+                    !self.new_name.is_empty(),
+                    Button::new("Save")
+                        .min_size(vec2(CONTROL_BUTTON_SIZE[0], CONTROL_BUTTON_SIZE[1])),
+                )
+                .clicked()
+            {
+                picked = Some(self.directory.join(format!(
+                    "{}.{}",
+                    self.new_name,
+                    self.format.extension()
+                )));
+            }
+        });
+
+        for row in KEYPAD_ROWS {
+            ui.horizontal(|ui| {
+                for key in row.chars() {
+                    if ui
+                        .add_sized(KEY_BUTTON_SIZE, Button::new(key.to_string()))
+                        .clicked()
+                    {
+                        self.new_name.push(key);
+                    }
+                }
+            });
+        }
+
+        picked
+    }
+}
+
+/// The file name of a path, or the whole path if it has none
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
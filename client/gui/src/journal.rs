@@ -0,0 +1,363 @@
+/*!
+ * An append-only journal of everything the client state machine went through,
+ * and a debug panel to step through it
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{
+    annotations::Annotation, clock::*, events::*, recovery::Recovery, stop_reason::StopReason,
+};
+use bindings::{ClientError, ClientStatus, Frame};
+#[cfg(feature = "ui")]
+use eframe::epaint::vec2;
+#[cfg(feature = "ui")]
+use egui::{Button, Context, Label, ScrollArea, Window};
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str as serde_from_str, to_string_pretty as serde_to_string_pretty};
+use tracing::info;
+
+// Constants
+#[cfg(feature = "ui")]
+const STEP_BUTTON_SIZE: [f32; 2] = [40., 30.];
+
+/***** Journal *****/
+
+/// Something the user did that changes the run data
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub enum UserAction {
+    Start {
+        distance: f64,
+        reverse_braking: bool,
+        /// Centimeters, if the operator measured it instead of going by the
+        /// car's wheel diameter
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        wheel_circumference: Option<f64>,
+    },
+    /// START was pressed, waiting on it being confirmed
+    Arm,
+    /// The start was called off, or wasn't confirmed in time
+    Disarm,
+    Stop,
+    /// E-STOP was pressed, or statuses stopped coming in mid run
+    EmergencyStop,
+    Reset,
+    Annotate(Annotation),
+    /// By index
+    RemoveAnnotation(usize),
+    /// "Fix it" was pressed for an error from the server
+    Recover(Recovery),
+    /// Why the run was stopped by hand, answered after stopping it
+    GiveStopReason(StopReason),
+    /// The wheel was calibrated against the last run, measured to have gone
+    /// this many centimeters
+    Calibrate(f64),
+}
+
+/// What happened
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub enum JournalEntryKind {
+    /// The current job changed
+    Transition {
+        from: ClientStatus,
+        to: ClientStatus,
+    },
+    /// A raw frame was received, whether or not it could be parsed
+    Received(String),
+    /// A raw frame was sent
+    Sent(String),
+    Action(UserAction),
+}
+impl JournalEntryKind {
+    /// A one line description
+    pub fn describe(&self) -> String {
+        use JournalEntryKind::*;
+        match self {
+            Transition { from, to } => format!("{} -> {}", from.to_string(), to.to_string()),
+            Received(frame) => format!("< {frame}"),
+            Sent(frame) => format!("> {frame}"),
+            Action(UserAction::Start {
+                distance,
+                reverse_braking,
+                wheel_circumference,
+            }) => format!(
+                "Start {distance}cm (reverse braking: {reverse_braking}{})",
+                wheel_circumference
+                    .map(|circumference| format!(", wheel: {circumference}cm around"))
+                    .unwrap_or_default()
+            ),
+            Action(UserAction::Arm) => "Arm".to_owned(),
+            Action(UserAction::Disarm) => "Disarm".to_owned(),
+            Action(UserAction::Stop) => "Stop".to_owned(),
+            Action(UserAction::EmergencyStop) => "Emergency stop".to_owned(),
+            Action(UserAction::Reset) => "Reset".to_owned(),
+            Action(UserAction::Annotate(annotation)) => {
+                format!("Annotate {}", annotation.describe())
+            }
+            Action(UserAction::RemoveAnnotation(idx)) => {
+                format!("Remove annotation {}", idx + 1)
+            }
+            Action(UserAction::Recover(recovery)) => format!("Fix it: {recovery}"),
+            Action(UserAction::GiveStopReason(reason)) => {
+                format!("Stopped for: {}", reason.describe())
+            }
+            Action(UserAction::Calibrate(distance)) => {
+                format!("Calibrate against {distance}cm measured")
+            }
+        }
+    }
+}
+
+/// A journal entry
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JournalEntry {
+    /// Unix time in seconds
+    pub time: f64,
+    pub kind: JournalEntryKind,
+}
+impl JournalEntry {
+    /// A one line description, with the time in a time zone
+    pub fn describe(&self, timezone: &DisplayTimezone) -> String {
+        let time = timezone.format_unix(self.time, "%H:%M:%S%.3f");
+        format!("{time} {}", self.kind.describe())
+    }
+}
+
+/// The state of the client after some step of the journal
+#[derive(Default)]
+pub struct JournalState {
+    pub run_data: RunData,
+    pub current_job: ClientStatus,
+    /// Errors hit while getting here, from the server or from parsing
+    pub errors: Vec<ClientError>,
+}
+
+/// Every state transition, received frame, sent frame, and user action, in
+/// order
+///
+/// Nothing is ever removed, so the run data at any step can be rebuilt by
+/// replaying everything up to it.
+#[derive(Default, Clone)]
+pub struct Journal {
+    entries: Vec<JournalEntry>,
+    last_job: ClientStatus,
+}
+impl Journal {
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    pub fn record(&mut self, kind: JournalEntryKind) {
+        // Frames are logged as they're sent and received
+        if !matches!(
+            kind,
+            JournalEntryKind::Sent(_) | JournalEntryKind::Received(_)
+        ) {
+            info!("{}", kind.describe());
+        }
+        self.entries.push(JournalEntry {
+            time: unix_now(),
+            kind,
+        });
+    }
+
+    /// Record a transition if the current job changed since last observed
+    pub fn observe_job(&mut self, current_job: ClientStatus) {
+        if current_job != self.last_job {
+            self.record(JournalEntryKind::Transition {
+                from: self.last_job,
+                to: current_job,
+            });
+            self.last_job = current_job;
+        }
+    }
+
+    /// Rebuild the state of the client right after the entry at `step`
+    pub fn replay(&self, step: usize) -> JournalState {
+        use JournalEntryKind::*;
+        let mut state = JournalState::default();
+
+        for entry in self.entries.iter().take(step.saturating_add(1)) {
+            match &entry.kind {
+                Transition { to, .. } => state.current_job = *to,
+                Received(frame) => {
+                    let result = Frame::decode(frame).and_then(|parsed_response| {
+                        state.run_data.apply_response(parsed_response, entry.time)
+                    });
+                    if let Err(e) = result {
+                        state.errors.push(e);
+                    }
+                }
+                Sent(_) => (),
+                Action(UserAction::Start { .. }) => state.run_data.running = true,
+                Action(UserAction::Arm | UserAction::Disarm) => (),
+                Action(UserAction::Stop | UserAction::EmergencyStop) => {
+                    state.run_data.running = false
+                }
+                Action(UserAction::Reset) => state.run_data = RunData::default(),
+                Action(UserAction::Annotate(annotation)) => {
+                    state.run_data.annotations.push(annotation.clone())
+                }
+                Action(UserAction::RemoveAnnotation(idx)) => {
+                    if *idx < state.run_data.annotations.len() {
+                        state.run_data.annotations.remove(*idx);
+                    }
+                }
+                Action(UserAction::Recover(Recovery::StopAndStartAgain | Recovery::StartAgain)) => {
+                    state.run_data.running = true
+                }
+                Action(UserAction::Recover(Recovery::GiveUpOnRun)) => {
+                    state.run_data.running = false
+                }
+                Action(UserAction::Recover(_)) => (),
+                Action(UserAction::GiveStopReason(reason)) => {
+                    state.run_data.stop_reason = Some(reason.clone())
+                }
+                Action(UserAction::Calibrate(_)) => (),
+            }
+        }
+
+        state
+    }
+
+    pub fn to_json(&self) -> Result<String, ClientError> {
+        serde_to_string_pretty(&self.entries).map_err(|e| ClientError::Parse(e.to_string()))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ClientError> {
+        let entries = serde_from_str::<Vec<JournalEntry>>(json)?;
+        let last_job = entries
+            .iter()
+            .rev()
+            .find_map(|entry| match entry.kind {
+                JournalEntryKind::Transition { to, .. } => Some(to),
+                _ => None,
+            })
+            .unwrap_or_default();
+        Ok(Self { entries, last_job })
+    }
+}
+
+/***** Journal viewer *****/
+
+#[cfg(feature = "ui")]
+/// A debug panel to step backward and forward through a journal
+#[derive(Default)]
+pub struct JournalViewer {
+    pub step: usize,
+    pub open: bool,
+    /// The replayed state and the step it is for, as replaying is not cheap
+    state: Option<(usize, JournalState)>,
+}
+#[cfg(feature = "ui")]
+impl JournalViewer {
+    /// Start at the most recent entry
+    pub fn new(journal: &Journal) -> Self {
+        Self {
+            step: journal.entries().len().saturating_sub(1),
+            open: true,
+            state: None,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context, journal: &Journal, timezone: &DisplayTimezone) {
+        let last_step = journal.entries().len().saturating_sub(1);
+        self.step = self.step.min(last_step);
+        if self.state.as_ref().map(|(step, _)| *step) != Some(self.step) {
+            self.state = Some((self.step, journal.replay(self.step)));
+        }
+        // Safety: just set
+        let (_, state) = self.state.as_ref().unwrap();
+
+        let mut step = self.step;
+        let mut open = true;
+        Window::new("Journal")
+            .resizable(false)
+            .default_size(vec2(400.0, 260.0))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.add_sized([60., 30.], Button::new("Close")).clicked() {
+                        open = false;
+                    }
+                    if ui.add_sized(STEP_BUTTON_SIZE, Button::new("|<")).clicked() {
+                        step = 0;
+                    }
+                    if ui.add_sized(STEP_BUTTON_SIZE, Button::new("<")).clicked() {
+                        step = step.saturating_sub(1);
+                    }
+                    if ui.add_sized(STEP_BUTTON_SIZE, Button::new(">")).clicked() {
+                        step = (step + 1).min(last_step);
+                    }
+                    if ui.add_sized(STEP_BUTTON_SIZE, Button::new(">|")).clicked() {
+                        step = last_step;
+                    }
+                    ui.label(format!(
+                        "{} / {}",
+                        (step + 1).min(journal.entries().len()),
+                        journal.entries().len()
+                    ));
+                });
+
+                let entry = match journal.entries().get(self.step) {
+                    Some(entry) => entry,
+                    None => {
+                        ui.label("Nothing has happened yet");
+                        return;
+                    }
+                };
+                ui.add(Label::new(entry.describe(timezone)).wrap(true));
+                ui.separator();
+
+                let run_data = &state.run_data;
+                ui.label(format!("Job: {}", state.current_job.to_string()));
+                ui.label(format!("Running: {}", run_data.running));
+                ui.label(format!(
+                    "Ping: {}",
+                    match &run_data.ping_status_response {
+                        Some((ping, received_time)) => format!(
+                            "{:.0}ms round-trip",
+                            (received_time - ping.value.sent_time) * 1000.0
+                        ),
+                        None => "none".to_owned(),
+                    }
+                ));
+                ui.label(format!(
+                    "Static status: {}",
+                    match run_data.static_status_response.is_some() {
+                        true => "received",
+                        false => "none",
+                    }
+                ));
+                ui.label(format!(
+                    "Statuses: {}{}",
+                    run_data.status_responses.len(),
+                    run_data
+                        .status_responses
+                        .last()
+                        .map(|status| format!(
+                            " (last: {:.3}cm, {})",
+                            status.value.distance.distance, status.value.stage
+                        ))
+                        .unwrap_or_default()
+                ));
+                ui.label(format!("Annotations: {}", run_data.annotations.len()));
+                ui.label(format!(
+                    "Bridge connected: {}",
+                    run_data.bluetooth_bridge_connected
+                ));
+
+                if !state.errors.is_empty() {
+                    ui.separator();
+                    ScrollArea::vertical().max_height(60.0).show(ui, |ui| {
+                        for error in state.errors.iter() {
+                            ui.add(Label::new(error.to_string()).wrap(true));
+                        }
+                    });
+                }
+            });
+
+        self.step = step;
+        self.open = open;
+    }
+}
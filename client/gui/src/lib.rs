@@ -0,0 +1,4262 @@
+/*!
+ * The Raspberry PI 3B GUI for car controller
+ * Created by sheepy0125 | MIT license | 2023-02-16
+ */
+// Most of what's imported is only for the GUI, when it's built
+#![cfg_attr(not(feature = "ui"), allow(unused_imports))]
+
+/***** Setup *****/
+// Imports
+use bindings::*;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "ui")]
+use eframe::{epaint::vec2, App};
+#[cfg(feature = "ui")]
+use egui::{
+    Align, Align2, Button, CentralPanel, Checkbox, Color32, ComboBox, Context, DragValue, Key,
+    Label, Layout, ProgressBar, RichText, ScrollArea, SelectableLabel, SidePanel, Slider, TextEdit,
+    TopBottomPanel, Ui, Window,
+};
+#[cfg(feature = "ui")]
+use egui_extras::{Column, TableBuilder};
+#[cfg(feature = "ui")]
+use egui_file::FileDialog;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use smart_default::SmartDefault;
+use std::{
+    env::current_dir,
+    f64::consts::PI,
+    mem::take,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use tracing::{error, warn, Level};
+pub mod events;
+use events::*;
+pub mod shared;
+use shared::*;
+pub mod export;
+use export::*;
+#[cfg(feature = "bluetooth")]
+pub mod bridge;
+#[cfg(feature = "bluetooth")]
+use bridge::*;
+#[cfg(feature = "embedded-bridge")]
+#[cfg_attr(not(target_os = "linux"), path = "embedded_bridge_stub.rs")]
+pub mod embedded_bridge;
+#[cfg(feature = "embedded-bridge")]
+use embedded_bridge::*;
+pub mod diagnostics;
+use diagnostics::*;
+pub mod round_trips;
+use round_trips::*;
+#[cfg(feature = "ui")]
+pub mod file_picker;
+#[cfg(feature = "ui")]
+use file_picker::*;
+pub mod data_directory;
+use data_directory::*;
+pub mod journal;
+use journal::*;
+pub mod splits;
+use splits::*;
+pub mod config;
+use config::*;
+pub mod odometry;
+use odometry::*;
+pub mod run_cache;
+use run_cache::*;
+pub mod telemetry_plot;
+use telemetry_plot::*;
+pub mod wire_log;
+use wire_log::*;
+pub mod settings;
+use settings::*;
+pub mod startup_checks;
+use startup_checks::*;
+pub mod fault_injection;
+use fault_injection::*;
+pub mod run_history;
+use run_history::*;
+pub mod annotations;
+use annotations::*;
+pub mod presets;
+use presets::*;
+pub mod arming;
+use arming::*;
+pub mod worker;
+use worker::*;
+pub mod clock;
+use clock::*;
+pub mod session;
+use session::*;
+pub mod steps;
+use steps::*;
+#[cfg(feature = "analytics")]
+pub mod analytics;
+#[cfg(feature = "analytics")]
+use analytics::*;
+pub mod narrative;
+use narrative::*;
+pub mod theme;
+use theme::*;
+pub mod logging;
+use logging::*;
+pub mod recovery;
+use recovery::*;
+pub mod session_log;
+use session_log::*;
+pub mod stop_reason;
+use stop_reason::*;
+#[cfg(feature = "ui")]
+pub mod calibration;
+#[cfg(feature = "ui")]
+use calibration::*;
+pub mod error_badge;
+use error_badge::*;
+pub mod maintenance;
+use maintenance::*;
+pub mod outgoing;
+pub mod serial_worker;
+pub mod status_history;
+pub mod storage;
+use storage::*;
+pub mod estimate;
+use estimate::*;
+pub mod changelog;
+use changelog::*;
+pub mod run_snapshot;
+use run_snapshot::*;
+pub mod power;
+use power::*;
+
+/***** Client *****/
+
+/// Error message data
+pub struct ErrorData {
+    pub error: ClientError,
+    pub time: DateTime<Utc>,
+    /// What to do about it, offered as a "Fix it" button
+    pub recovery: Option<Recovery>,
+}
+impl ErrorData {
+    /// Logged too, so nothing that goes wrong is only ever shown on screen
+    pub fn new(error: ClientError) -> Self {
+        error!("{error}");
+        Self {
+            recovery: Recovery::for_error(&error),
+            error,
+            time: Utc::now(),
+        }
+    }
+}
+impl From<ClientError> for ErrorData {
+    fn from(value: ClientError) -> Self {
+        Self::new(value)
+    }
+}
+
+/// A message shown for a few seconds, for things that went fine
+pub struct Toast {
+    pub text: String,
+    pub shown: Instant,
+}
+impl Toast {
+    pub fn new(text: String) -> Self {
+        Self {
+            text,
+            shown: Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "ui")]
+/// GUI data
+#[derive(SmartDefault)]
+pub struct GUIData {
+    /// Distance in centimeters
+    #[default = 0.0]
+    pub distance: f64,
+    #[default = false]
+    pub reverse_braking: bool,
+    /// Stop the car from the client once it's gone too far, on top of the
+    /// server's own limit
+    #[default = false]
+    pub auto_stop: bool,
+    /// Percentage of the distance to stop at
+    #[default(DEFAULT_AUTO_STOP_PERCENTAGE)]
+    pub auto_stop_percentage: f64,
+    /// Have the server keep the car under a speed, e.g. for practice rooms
+    #[default = false]
+    pub speed_limit: bool,
+    /// Centimeters per second
+    #[default(DEFAULT_MAX_VELOCITY)]
+    pub max_velocity: f64,
+    /// How the server works out the distance, or `None` for its default
+    pub distance_estimator: Option<DistanceEstimatorKind>,
+    /// Saved start parameters, e.g. for the standard distances
+    pub presets: Presets,
+    /// The name of the preset last chosen or saved
+    pub selected_preset: Option<String>,
+    #[default = false]
+    pub show_preset_window: bool,
+    /// The name to save the start parameters as a preset under
+    pub preset_name: String,
+    /// The venue and runs so far, to name runs after
+    pub session: Session,
+    /// How much the car has been used across sessions
+    pub maintenance: Maintenance,
+    /// The tasks of the service reminders that are due, until they're done
+    /// or put off
+    pub service_notices: Vec<String>,
+    /// What's new since the version last run, until it's been read
+    pub whats_new: WhatsNew,
+    /// Problems with what runs are saved to that were already shown, so
+    /// they're only shown once
+    pub storage_problems: Vec<String>,
+    /// Use a measured wheel circumference instead of the car's wheel diameter
+    #[default = false]
+    pub override_wheel_circumference: bool,
+    /// Centimeters
+    #[default(DEFAULT_WHEEL_DIAMETER * PI)]
+    pub wheel_circumference: f64,
+    /// Recompute the distance and velocity from the magnet hits to check the
+    /// server's against
+    #[default = false]
+    pub recompute_distance: bool,
+    /// Centimeters between the markers split times are taken at
+    #[default(DEFAULT_SPLIT_INTERVAL)]
+    pub split_interval: f64,
+    #[default = false]
+    pub expanded_status_table: bool,
+    #[default = true]
+    pub show_bluetooth_connect_screen: bool,
+    #[default = false]
+    pub show_connect_progress_window: bool,
+    /// The address of the Bluetooth device to connect to, or `None` for the
+    /// bridge's default
+    pub bluetooth_address: Option<String>,
+    #[default = false]
+    pub show_bridge_window: bool,
+    #[default = false]
+    pub show_diagnostics_window: bool,
+    /// Where the last diagnostic bundle was exported to
+    pub diagnostics_path: Option<PathBuf>,
+    /// Whether the errors window was opened from the error badge, as it isn't
+    /// opened by itself mid run
+    #[default = false]
+    pub show_error_drawer: bool,
+    /// Only the errors from this subsystem are listed, if any
+    pub error_filter: Option<Subsystem>,
+    #[default = false]
+    pub show_settings_window: bool,
+    /// Where the settings were last exported to
+    pub settings_path: Option<PathBuf>,
+    /// A settings file picked to import, waiting on its conflicts
+    pub settings_import: Option<SettingsImport>,
+    /// Whether the debug menu is offered, from `--dev`
+    #[default = false]
+    pub dev: bool,
+    #[default = false]
+    pub show_debug_window: bool,
+    /// Of [`INJECTABLE_ERRORS`]
+    #[default = 0]
+    pub injected_error: usize,
+    #[cfg(feature = "debug-menu")]
+    pub malformed_frame: MalformedFrame,
+    #[default = false]
+    pub show_run_history: bool,
+    /// The layout to go back to from the operator layout
+    pub detailed_layout: ScreenLayout,
+    #[default = false]
+    pub show_logs: bool,
+    /// The least severe events shown in the logs panel
+    pub log_level: LogLevel,
+    pub current_job: ClientStatus,
+    #[default = "status.csv"]
+    pub display_file_path: String,
+    pub file_path: Option<PathBuf>,
+    /// What runs are saved as, both by hand and automatically
+    pub export_format: RunFormat,
+    pub file_dialog: Option<FileDialog>,
+    pub file_picker: Option<FilePicker>,
+    pub journal_viewer: Option<JournalViewer>,
+    pub session_log_viewer: Option<SessionLogViewer>,
+    /// Asking why the last run was stopped by hand, until it's answered or
+    /// dismissed
+    pub stop_reason_prompt: Option<StopReasonPrompt>,
+    pub calibration_window: Option<CalibrationWindow>,
+    pub telemetry_plot: Option<TelemetryPlot>,
+    /// Most recent first
+    pub recent_files: Vec<PathBuf>,
+    pub toast: Option<Toast>,
+    pub battery_alert: BatteryAlert,
+}
+#[cfg(feature = "ui")]
+impl GUIData {
+    /// Set the file to load from or save to
+    pub fn set_file_path(&mut self, file: PathBuf) {
+        self.display_file_path = file
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.file_path = Some(file);
+    }
+
+    /// Remember a file that was loaded or saved
+    pub fn push_recent_file(&mut self, file: PathBuf) {
+        self.recent_files.retain(|recent_file| *recent_file != file);
+        self.recent_files.insert(0, file);
+        self.recent_files.truncate(RECENT_FILES);
+    }
+}
+
+#[cfg(feature = "ui")]
+/// Possible values for the large button
+pub enum LargeButton {
+    Start,
+    /// Confirming an armed start
+    Confirm,
+    Reset,
+    Stop,
+}
+#[cfg(feature = "ui")]
+impl ToString for LargeButton {
+    fn to_string(&self) -> String {
+        match *self {
+            Self::Start => "START",
+            Self::Confirm => "CONFIRM",
+            Self::Reset => "RESET",
+            Self::Stop => "STOP",
+        }
+        .to_owned()
+    }
+}
+
+#[cfg(feature = "ui")]
+pub trait ClientGUIHandlers {
+    fn new(serial_event_propagator: SerialEventPropagator) -> Self;
+    fn get_serial_responses(&mut self) -> Result<(), ClientError>;
+    fn handle_response(&mut self, parsed_response: Response);
+    fn request_resend(&mut self, command: Command) -> Result<(), ClientError>;
+    fn send<S>(&mut self, command: Command, data: S) -> Result<(), ClientError>
+    where
+        S: Serialize + for<'a> Deserialize<'a> + Sized;
+    fn export_diagnostics(&mut self) -> Result<PathBuf, ClientError>;
+    fn autosave(&mut self) -> Result<(), ClientError>;
+    fn save(&mut self);
+    fn poll_save(&mut self);
+    fn file_picker_directory(&self) -> PathBuf;
+    fn suggested_save_path(&self) -> PathBuf;
+    fn load_diagnostics(&mut self, bundle: DiagnosticBundle);
+    fn start_wire_log(&mut self) -> Result<(), ClientError>;
+    fn load_wire_log(&mut self, replay: WireLogReplay);
+    fn replay_wire_log(&mut self);
+    fn apply_config(&mut self, config: Config);
+    fn settings(&self) -> SettingsFile;
+    fn apply_settings(&mut self, settings: SettingsFile);
+    fn export_settings(&self) -> Result<PathBuf, ClientError>;
+    fn export_narrative(&self) -> Result<PathBuf, ClientError>;
+    fn save_config(&mut self);
+    fn show_error_messages(&mut self, ctx: &Context);
+    fn show_bluetooth_connect_screen(&mut self, ctx: &Context);
+    fn show_connect_progress_window(&mut self, ctx: &Context);
+    #[cfg(feature = "bluetooth")]
+    fn show_bridge_window(&mut self, ctx: &Context);
+    fn show_diagnostics_window(&mut self, ctx: &Context);
+    fn show_settings_window(&mut self, ctx: &Context);
+    fn show_preset_window(&mut self, ctx: &Context);
+    fn show_arming_window(&mut self, ctx: &Context);
+    fn show_saving_window(&mut self, ctx: &Context);
+    fn show_toast(&mut self, ctx: &Context);
+    fn check_service_reminders(&mut self);
+    fn check_storage(&mut self);
+    fn show_service_notices(&mut self, ctx: &Context);
+    fn show_whats_new(&mut self, ctx: &Context);
+    fn show_log_panel(&mut self, ctx: &Context);
+    fn inject(&mut self, injection: Injection);
+    fn annotate(&mut self, edit: AnnotationEdit);
+    #[cfg(feature = "debug-menu")]
+    fn show_debug_window(&mut self, ctx: &Context);
+    fn run_startup_checks(&mut self, checks: StartupChecks);
+    fn show_startup_problems(&mut self, ctx: &Context);
+    fn show_status_table(&self, ui: &mut Ui);
+    fn show_split_table(&self, ui: &mut Ui);
+    fn load_previous_run(&mut self);
+    fn comparison_run(&self) -> &[Event<StatusResponse>];
+    fn wheel_circumference(&self) -> Option<f64>;
+    fn table_options(&self) -> TableOptions;
+    fn recomputed_statuses(&self) -> Vec<Recomputed>;
+    fn logic(&mut self);
+    fn arm(&mut self);
+    fn disarm(&mut self);
+    fn start(&mut self);
+    fn stop(&mut self);
+    fn emergency_stop(&mut self);
+    fn reset(&mut self);
+    fn recover(&mut self, recovery: Recovery);
+    fn ask_stop_reason(&mut self);
+    fn give_stop_reason(&mut self, reason: StopReason);
+    fn calibrate(&mut self, distance: f64);
+}
+#[cfg(feature = "ui")]
+pub struct ClientGUI {
+    pub serial_event_propagator: SerialEventPropagator,
+    pub run_data: RunData,
+    /// Snapshots of the run data, published once a frame for the frame to be
+    /// drawn from and for anything reading it on another thread
+    pub shared_run_data: SharedRunData,
+    pub gui_data: GUIData,
+    pub errors: Vec<ErrorData>,
+    /// Only present if the GUI spawned the bridge itself
+    #[cfg(feature = "bluetooth")]
+    pub bridge: Option<BridgeProcess>,
+    /// Only present if the GUI is running the bridge itself
+    #[cfg(feature = "embedded-bridge")]
+    pub embedded_bridge: Option<EmbeddedBridge>,
+    /// Only present if the GUI was started to look at a diagnostic bundle
+    pub diagnostic_bundle: Option<DiagnosticBundle>,
+    /// How long each command has taken to be answered, shown in the
+    /// diagnostics window
+    pub round_trips: RoundTrips,
+    /// Only present if the GUI was started to replay a wire log
+    pub wire_log_replay: Option<WireLogReplay>,
+    /// Where runs are automatically saved, if it could be created
+    pub data_directory: Option<DataDirectory>,
+    pub journal: Journal,
+    /// Only present while this session is being logged to disk
+    pub session_log: Option<SessionLogger>,
+    /// The statuses of the last saved run, to compare split times against
+    pub previous_run: Vec<Event<StatusResponse>>,
+    /// A stored run chosen to compare against instead, if any
+    pub baseline: Option<Baseline>,
+    /// Saved runs that have been looked at, so they aren't read again
+    pub run_cache: RunCache,
+    /// Runs since starting, kept when they're reset
+    pub run_history: RunHistory,
+    pub config: Config,
+    /// Where the config is saved when it's tuned from the settings window,
+    /// `None` to not save it (e.g. when it couldn't be read)
+    pub config_path: Option<PathBuf>,
+    /// What was checked at startup, to check again
+    pub startup_checks: Option<StartupChecks>,
+    /// Shown until they're fixed or dismissed
+    pub startup_problems: Vec<StartupProblem>,
+    /// Whether START is waiting to be confirmed
+    pub arming: Arming,
+    /// The run being saved in the background, if it is
+    pub save_job: Option<BackgroundJob<SavedRun>>,
+    /// When the last status came in mid run (or the start was sent), to stop
+    /// the car if they stop coming
+    pub last_status_time: Option<Instant>,
+    /// When the last status came in, whether or not the run was going, to
+    /// guess where the car is once they stop coming
+    pub last_status_received: Option<Instant>,
+    /// When the last run finalized and was saved, to reset for the next one
+    /// once it's been long enough
+    pub finalized_time: Option<Instant>,
+    /// The most recent events logged, for the logs panel
+    pub logs: LogBuffer,
+    /// Tells systemd the GUI is ready and still alive, when it's run as a
+    /// service
+    pub systemd: SystemdNotifier,
+}
+#[cfg(feature = "ui")]
+impl ClientGUIHandlers for ClientGUI {
+    fn new(serial_event_propagator: SerialEventPropagator) -> Self {
+        let mut errors: Vec<ErrorData> = vec![];
+        let data_directory = DataDirectory::new().map_err(|e| errors.push(e.into())).ok();
+        let mut run_data = RunData::default();
+        run_data.status_responses.spill_path =
+            data_directory.as_ref().map(DataDirectory::spill_path);
+
+        Self {
+            serial_event_propagator,
+            run_data,
+            shared_run_data: Default::default(),
+            gui_data: Default::default(),
+            errors,
+            #[cfg(feature = "bluetooth")]
+            bridge: None,
+            #[cfg(feature = "embedded-bridge")]
+            embedded_bridge: None,
+            diagnostic_bundle: None,
+            round_trips: RoundTrips::new(),
+            wire_log_replay: None,
+            data_directory,
+            journal: Default::default(),
+            session_log: None,
+            previous_run: vec![],
+            baseline: None,
+            run_cache: RunCache::new(),
+            run_history: RunHistory::new(),
+            config: Default::default(),
+            config_path: None,
+            startup_checks: None,
+            startup_problems: vec![],
+            arming: Arming::Disarmed,
+            save_job: None,
+            last_status_time: None,
+            last_status_received: None,
+            finalized_time: None,
+            logs: LogBuffer::new(DEFAULT_LOG_EVENTS),
+            systemd: Default::default(),
+        }
+    }
+
+    /// Show the bluetooth connection screen
+    fn show_bluetooth_connect_screen(&mut self, ctx: &Context) {
+        Window::new("Microwave-Blaster 9000 Utility")
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let close_button_size = [60., 40.];
+                    if ui
+                        .add_sized(close_button_size, Button::new("Close"))
+                        .clicked()
+                    {
+                        self.gui_data.show_bluetooth_connect_screen = false;
+                    };
+
+                    // For seeing exactly what went over the wire after a race
+                    let mut logging = self.serial_event_propagator.wire_logger.is_some();
+                    let checkbox = ui.checkbox(&mut logging, "Log every frame");
+                    if checkbox.changed() {
+                        match logging {
+                            true => self
+                                .start_wire_log()
+                                .unwrap_or_else(|e| self.errors.push(e.into())),
+                            false => self.serial_event_propagator.wire_logger = None,
+                        }
+                    }
+                    if let Some(wire_logger) = &self.serial_event_propagator.wire_logger {
+                        checkbox
+                            .on_hover_text(format!("Logging to {}", wire_logger.path.display()));
+                    }
+                });
+
+                ui.heading(format!(
+                    "We *think* we are {}",
+                    match self.run_data.bluetooth_bridge_connected {
+                        true => "CONNECTED",
+                        false => "DISCONNECTED",
+                    },
+                ));
+                // Also reported when the bridge connects to a trusted device by
+                // itself at startup
+                if let Some(BluetoothConnectResponse {
+                    connected: Some(true),
+                    address: Some(address),
+                    ..
+                }) = &self.run_data.bluetooth_connect_progress
+                {
+                    if self.run_data.bluetooth_bridge_connected {
+                        ui.label(format!("Connected to {address}"));
+                    }
+                }
+                // For telling a flaky link apart from a flaky car
+                if let Some(metrics) = &self.run_data.bluetooth_metrics {
+                    ui.label(format!(
+                        "Signal: {}",
+                        metrics
+                            .rssi
+                            .map(|rssi| format!("{rssi}dBm"))
+                            .unwrap_or_else(|| "unknown".to_owned())
+                    ));
+                    ui.label(format!(
+                        "Sent {}, received {}",
+                        size_text(metrics.bytes_sent),
+                        size_text(metrics.bytes_received)
+                    ));
+                    ui.label(format!(
+                        "Reconnected {} time(s), discarded {} duplicate(s)",
+                        metrics.reconnects, metrics.duplicates_discarded
+                    ));
+                }
+
+                ui.horizontal(|ui| {
+                    let bluetooth_control_button_size = [80., 40.];
+                    if ui
+                        .add_sized(bluetooth_control_button_size, Button::new("Connect"))
+                        .clicked()
+                    {
+                        self.send(
+                            Command::Connect,
+                            BluetoothConnectRequest {
+                                address: self.gui_data.bluetooth_address.clone(),
+                            },
+                        )
+                        .unwrap_or_else(|e| {
+                            self.errors
+                                .push(ErrorData::new(ClientError::Serial(e.to_string())))
+                        });
+                        self.run_data.bluetooth_connect_progress = None;
+                        self.gui_data.show_connect_progress_window = true;
+                    }
+                    if ui
+                        .add_sized(bluetooth_control_button_size, Button::new("Disconnect"))
+                        .clicked()
+                    {
+                        self.send(Command::Disconnect, BluetoothDisconnectRequest {})
+                            .unwrap_or_else(|e| {
+                                self.errors
+                                    .push(ErrorData::new(ClientError::Serial(e.to_string())))
+                            });
+                    }
+                    if ui
+                        .add_sized(bluetooth_control_button_size, Button::new("Update status"))
+                        .clicked()
+                    {
+                        self.send(Command::BluetoothStatus, BluetoothStatusRequest {})
+                            .unwrap_or_else(|e| {
+                                self.errors
+                                    .push(ErrorData::new(ClientError::Serial(e.to_string())))
+                            });
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    if ui.add_sized([60., 30.], Button::new("Metrics")).clicked() {
+                        self.send(Command::BluetoothMetrics, BluetoothMetricsRequest {})
+                            .unwrap_or_else(|e| {
+                                self.errors
+                                    .push(ErrorData::new(ClientError::Serial(e.to_string())))
+                            });
+                    }
+                    if ui.add_sized([60., 30.], Button::new("Scan")).clicked() {
+                        self.send(Command::BluetoothScan, BluetoothScanRequest {})
+                            .unwrap_or_else(|e| {
+                                self.errors
+                                    .push(ErrorData::new(ClientError::Serial(e.to_string())))
+                            });
+                    }
+                    let selected = match &self.gui_data.bluetooth_address {
+                        Some(address) => address.clone(),
+                        None => "Default device".to_owned(),
+                    };
+                    ComboBox::from_id_source("bluetooth device")
+                        .selected_text(selected)
+                        .width(240.)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(
+                                &mut self.gui_data.bluetooth_address,
+                                None,
+                                "Default device",
+                            );
+                            for device in self.run_data.bluetooth_devices.iter() {
+                                ui.selectable_value(
+                                    &mut self.gui_data.bluetooth_address,
+                                    Some(device.address.clone()),
+                                    device_label(device),
+                                );
+                            }
+                        });
+                });
+                if self
+                    .serial_event_propagator
+                    .pending_requests
+                    .iter()
+                    .any(|pending| pending.command == Command::BluetoothScan)
+                {
+                    ui.label("Scanning...");
+                }
+            });
+    }
+
+    /// Show how the bridge is getting on with connecting, as it finds devices
+    fn show_connect_progress_window(&mut self, ctx: &Context) {
+        Window::new("Connecting").resizable(false).show(ctx, |ui| {
+            if ui.add_sized([60., 30.], Button::new("Close")).clicked() {
+                self.gui_data.show_connect_progress_window = false;
+            }
+
+            // Older bridges don't say how it's going
+            let progress = match &self.run_data.bluetooth_connect_progress {
+                Some(progress) => progress,
+                None => {
+                    ui.label("Waiting for the bridge...");
+                    return;
+                }
+            };
+            // Older bridges don't say which device either
+            let device = progress.address.as_deref();
+            let to_device = device
+                .map(|device| format!(" to {device}"))
+                .unwrap_or_default();
+            match progress.connected {
+                None => {
+                    ui.label(format!(
+                        "Looking for {}... {:.1}s",
+                        device.unwrap_or("the device"),
+                        progress.elapsed
+                    ));
+                    ui.add(ProgressBar::new(
+                        (progress.elapsed / BLUETOOTH_SCAN_SECONDS) as f32,
+                    ));
+                }
+                Some(true) => {
+                    ui.label(format!(
+                        "Connected{to_device} after {:.1}s",
+                        progress.elapsed
+                    ));
+                }
+                Some(false) => {
+                    ui.label(format!(
+                        "Couldn't connect{to_device} after {:.1}s",
+                        progress.elapsed
+                    ));
+                }
+            }
+
+            ui.label(format!("Found {} device(s)", progress.devices.len()));
+            ScrollArea::vertical().max_height(100.0).show(ui, |ui| {
+                for device in progress.devices.iter() {
+                    ui.label(device_label(device));
+                }
+            });
+        });
+    }
+
+    /// Show the window for the bridge process spawned by the GUI
+    ///
+    /// Assumes there is a bridge process
+    #[cfg(feature = "bluetooth")]
+    fn show_bridge_window(&mut self, ctx: &Context) {
+        let bridge = match &mut self.bridge {
+            Some(bridge) => bridge,
+            None => return,
+        };
+
+        Window::new("Bluetooth bridge")
+            .resizable(false)
+            .default_size(vec2(
+                ctx.screen_rect().width() - 40.0,
+                ctx.screen_rect().height() - 80.0,
+            ))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let bridge_button_size = [60., 30.];
+                    if ui
+                        .add_sized(bridge_button_size, Button::new("Close"))
+                        .clicked()
+                    {
+                        self.gui_data.show_bridge_window = false;
+                    }
+                    if ui
+                        .add_sized(bridge_button_size, Button::new("Restart"))
+                        .clicked()
+                    {
+                        bridge
+                            .restart()
+                            .unwrap_or_else(|e| self.errors.push(e.into()));
+                    }
+                    if ui
+                        .add_sized(bridge_button_size, Button::new("Stop"))
+                        .clicked()
+                    {
+                        bridge.stop().unwrap_or_else(|e| self.errors.push(e.into()));
+                    }
+                    ui.add(Checkbox::new(&mut bridge.auto_restart, "Auto restart"));
+                });
+
+                ui.label(format!(
+                    "{} - restarted {} time(s)",
+                    bridge.state, bridge.restarts
+                ));
+
+                ui.separator();
+                ScrollArea::vertical()
+                    .stick_to_bottom(true)
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        for line in bridge.output.iter() {
+                            ui.add(Label::new(line.as_str()).wrap(true));
+                        }
+                    });
+            });
+    }
+
+    /// Show the window with how long each command has taken to be answered,
+    /// and the loaded diagnostic bundle if there is one
+    fn show_diagnostics_window(&mut self, ctx: &Context) {
+        Window::new("Diagnostics")
+            .resizable(false)
+            .default_size(vec2(
+                ctx.screen_rect().width() - 40.0,
+                ctx.screen_rect().height() - 80.0,
+            ))
+            .show(ctx, |ui| {
+                let close_button_size = [60., 30.];
+                if ui
+                    .add_sized(close_button_size, Button::new("Close"))
+                    .clicked()
+                {
+                    self.gui_data.show_diagnostics_window = false;
+                }
+
+                /* Round trips */
+
+                if !self.round_trips.is_empty() {
+                    ui.push_id("round trips table", |ui| {
+                        let round_trips_table = TableBuilder::new(ui)
+                            .striped(true)
+                            .resizable(false)
+                            .cell_layout(Layout::left_to_right(Align::Center))
+                            .column(Column::auto()) // Command
+                            .column(Column::auto()) // Answered
+                            .column(Column::auto()) // Round trip
+                            .column(Column::auto()) // On the car
+                            .column(Column::auto()) // Link
+                            .min_scrolled_height(0.0);
+
+                        // The last, average, and slowest of a part of each round trip
+                        let format_milliseconds =
+                            |round_trips: &CommandRoundTrips, part: fn(&RoundTrip) -> f64| {
+                                format!(
+                                    "{:.0} / {:.0} / {:.0}",
+                                    part(&round_trips.last) * 1000.0,
+                                    part(&round_trips.average()) * 1000.0,
+                                    part(&round_trips.slowest) * 1000.0,
+                                )
+                            };
+
+                        round_trips_table
+                            .header(20.0, |mut header| {
+                                for heading in
+                                    ["Command", "Answered", "Round trip", "On the car", "Link"]
+                                {
+                                    header.col(|ui| {
+                                        ui.strong(heading);
+                                    });
+                                }
+                            })
+                            .body(|mut body| {
+                                for round_trips in self.round_trips.commands() {
+                                    body.row(18.0, |mut row| {
+                                        row.col(|ui| {
+                                            ui.label(format!("{:?}", round_trips.command));
+                                        });
+                                        row.col(|ui| {
+                                            ui.label(round_trips.count.to_string());
+                                        });
+                                        row.col(|ui| {
+                                            ui.label(format_milliseconds(
+                                                round_trips,
+                                                |round_trip| round_trip.total,
+                                            ));
+                                        });
+                                        row.col(|ui| {
+                                            ui.label(format_milliseconds(
+                                                round_trips,
+                                                |round_trip| round_trip.server,
+                                            ));
+                                        });
+                                        row.col(|ui| {
+                                            ui.label(format_milliseconds(
+                                                round_trips,
+                                                RoundTrip::link,
+                                            ));
+                                        });
+                                    });
+                                }
+                            });
+                    });
+                    ui.label("Each as the last, average, and slowest, in milliseconds");
+                }
+
+                let Some(bundle) = &self.diagnostic_bundle else {
+                    return;
+                };
+                ui.separator();
+                ui.label(bundle.version.as_str());
+                ui.add(Label::new(bundle.config.as_str()).wrap(true));
+
+                ui.separator();
+                ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        ui.strong("Bridge output");
+                        for line in bundle.bridge_output.iter() {
+                            ui.add(Label::new(line.as_str()).wrap(true));
+                        }
+                        ui.strong("Frames");
+                        for frame in bundle.frames.iter() {
+                            ui.add(Label::new(frame.as_str()).wrap(true));
+                        }
+                    });
+            });
+    }
+
+    /// Show the settings window, for carrying the settings over to another
+    /// computer and picking through the conflicts of an import
+    fn show_settings_window(&mut self, ctx: &Context) {
+        let current = self.settings();
+
+        Window::new("Settings").resizable(false).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let settings_button_size = [60., 30.];
+                if ui
+                    .add_sized(settings_button_size, Button::new("Close"))
+                    .clicked()
+                {
+                    self.gui_data.show_settings_window = false;
+                }
+                if ui
+                    .add_sized(settings_button_size, Button::new("Export"))
+                    .clicked()
+                {
+                    match self.export_settings() {
+                        Ok(file_path) => self.gui_data.settings_path = Some(file_path),
+                        Err(e) => self.errors.push(e.into()),
+                    }
+                }
+                if ui
+                    .add_sized(settings_button_size, Button::new("Import"))
+                    .clicked()
+                {
+                    self.gui_data.file_picker = Some(FilePicker::new(
+                        FilePickerMode::Settings,
+                        self.file_picker_directory(),
+                    ));
+                }
+            });
+            if let Some(file_path) = &self.gui_data.settings_path {
+                ui.add(Label::new(format!("Exported to {}", file_path.display())).wrap(true));
+            }
+            // Runs are named after it, and saved where they were last time
+            // here
+            ui.horizontal(|ui| {
+                ui.label("Venue");
+                if ui
+                    .add(
+                        TextEdit::singleline(&mut self.gui_data.session.venue)
+                            .hint_text("e.g. gym"),
+                    )
+                    .changed()
+                {
+                    self.gui_data
+                        .session
+                        .save()
+                        .unwrap_or_else(|e| self.errors.push(e.into()));
+                }
+            });
+            // Saved to the config file straight away, like the timings
+            let mut save = false;
+            ui.horizontal(|ui| {
+                ui.label("Units");
+                for units in Units::ALL {
+                    save |= ui
+                        .selectable_value(&mut self.config.screen.units, units, units.to_string())
+                        .changed();
+                }
+            });
+
+            // Sent with every start and saved with presets, but here so the
+            // route planner still fits on the small screen
+            ui.horizontal(|ui| {
+                ui.label("Distance from");
+                ComboBox::from_id_source("distance estimator")
+                    .selected_text(
+                        self.gui_data
+                            .distance_estimator
+                            .map(|distance_estimator| distance_estimator.to_string())
+                            .unwrap_or_else(|| "Car's default".to_owned()),
+                    )
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut self.gui_data.distance_estimator,
+                            None,
+                            "Car's default",
+                        );
+                        for distance_estimator in DistanceEstimatorKind::ALL {
+                            ui.selectable_value(
+                                &mut self.gui_data.distance_estimator,
+                                Some(distance_estimator),
+                                distance_estimator.to_string(),
+                            );
+                        }
+                    });
+            });
+
+            // Counted towards the service reminders
+            let total = self.gui_data.maintenance.total;
+            ui.label(format!(
+                "Car used for {} over {} runs ({:.1}h of motors)",
+                self.config
+                    .screen
+                    .units
+                    .format_long_distance(total.distance, 1),
+                total.runs,
+                total.motor_seconds / SECONDS_PER_HOUR
+            ));
+
+            // Out of the way while importing so it all fits on the small screen
+            if self.gui_data.settings_import.is_none() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Version {VERSION}"));
+                    if ui.button("What's new").clicked() {
+                        match Changelog::built_in() {
+                            Ok(changelog) => self.gui_data.whats_new.releases = changelog.releases,
+                            Err(e) => self.errors.push(e.into()),
+                        }
+                    }
+                });
+                let screen = &mut self.config.screen;
+                ui.horizontal(|ui| {
+                    ComboBox::from_label("Theme")
+                        .selected_text(screen.theme.to_string())
+                        .show_ui(ui, |ui| {
+                            for theme in Theme::ALL {
+                                save |= ui
+                                    .selectable_value(&mut screen.theme, theme, theme.to_string())
+                                    .changed();
+                            }
+                        });
+                    ComboBox::from_label("Layout")
+                        .selected_text(screen.layout.to_string())
+                        .show_ui(ui, |ui| {
+                            for layout in ScreenLayout::ALL {
+                                save |= ui
+                                    .selectable_value(
+                                        &mut screen.layout,
+                                        layout,
+                                        layout.to_string(),
+                                    )
+                                    .changed();
+                            }
+                        });
+                });
+
+                // Tuned while connected, e.g. polling slower on a slow Pi
+                ui.separator();
+                let serial = &mut self.config.serial;
+                let mut tuned = false;
+                ui.horizontal(|ui| {
+                    let response = ui.add(
+                        DragValue::new(&mut serial.poll_interval)
+                            .clamp_range(MIN_POLL_INTERVAL..=MAX_POLL_INTERVAL)
+                            .speed(0.01)
+                            .max_decimals(2)
+                            .prefix("Poll every ")
+                            .suffix("s"),
+                    );
+                    (tuned, save) = tuning(&response, tuned, save);
+                    let response = ui.add(
+                        DragValue::new(&mut serial.request_timeout)
+                            .clamp_range(serial.poll_interval..=MAX_TUNED_REQUEST_TIMEOUT)
+                            .speed(0.05)
+                            .max_decimals(2)
+                            .prefix("Resend after ")
+                            .suffix("s"),
+                    );
+                    (tuned, save) = tuning(&response, tuned, save);
+                });
+                ui.horizontal(|ui| {
+                    let response = ui.add(
+                        DragValue::new(&mut serial.max_request_attempts)
+                            .clamp_range(1..=MAX_TUNED_REQUEST_ATTEMPTS)
+                            .prefix("Give up after ")
+                            .suffix(" tries"),
+                    );
+                    (tuned, save) = tuning(&response, tuned, save);
+                    let response = ui
+                        .add(
+                            DragValue::new(&mut self.config.screen.table_rows)
+                                .clamp_range(0..=MAX_TABLE_ROWS)
+                                .prefix("Show ")
+                                .suffix(" rows"),
+                        )
+                        .on_hover_text("Rows in the expanded status table, or 0 for all of them");
+                    (tuned, save) = tuning(&response, tuned, save);
+                });
+                if tuned {
+                    self.serial_event_propagator
+                        .apply_serial_config(&self.config.serial);
+                }
+            }
+            if save {
+                self.save_config();
+            }
+
+            let Some(import) = &mut self.gui_data.settings_import else {
+                return;
+            };
+            ui.separator();
+            ui.label(format!(
+                "Importing {}",
+                import
+                    .path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+            ));
+            match import.conflicts.is_empty() {
+                true => ui.label("Nothing differs from the current settings"),
+                false => ui.label("Where they differ, keep the current setting or use the file's?"),
+            };
+            ScrollArea::vertical().max_height(150.).show(ui, |ui| {
+                for (conflict, keep) in import.conflicts.iter().zip(import.keep.iter_mut()) {
+                    ui.strong(conflict.key.as_str());
+                    ui.horizontal(|ui| {
+                        ui.radio_value(
+                            keep,
+                            true,
+                            format!("Keep {}", setting_text(conflict.current.as_ref())),
+                        );
+                        ui.radio_value(
+                            keep,
+                            false,
+                            format!("Use {}", setting_text(conflict.imported.as_ref())),
+                        );
+                    });
+                }
+            });
+
+            let mut apply = false;
+            let mut cancel = false;
+            ui.horizontal(|ui| {
+                let settings_button_size = [60., 30.];
+                apply = ui
+                    .add_sized(settings_button_size, Button::new("Apply"))
+                    .clicked();
+                cancel = ui
+                    .add_sized(settings_button_size, Button::new("Cancel"))
+                    .clicked();
+            });
+            if apply {
+                match import.resolve(&current) {
+                    Ok(settings) => {
+                        self.gui_data.settings_import = None;
+                        self.apply_settings(settings);
+                    }
+                    Err(e) => self.errors.push(e.into()),
+                }
+            } else if cancel {
+                self.gui_data.settings_import = None;
+            }
+        });
+    }
+
+    /// Show the window to save the start parameters as a preset
+    fn show_preset_window(&mut self, ctx: &Context) {
+        Window::new("Preset").resizable(false).show(ctx, |ui| {
+            ui.label(format!(
+                "Save the start parameters for {:.0}cm as",
+                self.gui_data.distance
+            ));
+            ui.add(TextEdit::singleline(&mut self.gui_data.preset_name).hint_text("e.g. 15 m"));
+
+            let mut save = false;
+            let mut cancel = false;
+            ui.horizontal(|ui| {
+                let preset_button_size = [90., 30.];
+                save = ui
+                    .add_sized(preset_button_size, Button::new("Save preset"))
+                    .clicked();
+                cancel = ui
+                    .add_sized(preset_button_size, Button::new("Cancel"))
+                    .clicked();
+            });
+            if save {
+                let name = self.gui_data.preset_name.trim().to_owned();
+                let preset = Preset::from_gui_data(name.clone(), &self.gui_data);
+                match self.gui_data.presets.save(preset) {
+                    Ok(()) => {
+                        self.gui_data.selected_preset = Some(name);
+                        self.gui_data.preset_name.clear();
+                        self.gui_data.show_preset_window = false;
+                    }
+                    Err(e) => self.errors.push(e.into()),
+                }
+            } else if cancel {
+                self.gui_data.show_preset_window = false;
+            }
+        });
+    }
+
+    /// Show how long is left to confirm an armed start, big enough to see
+    /// from the track
+    fn show_arming_window(&mut self, ctx: &Context) {
+        let Some(remaining) = self.arming.remaining(Instant::now()) else {
+            return;
+        };
+        Window::new("ARMED")
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.label(
+                        RichText::new(format!("{:.1}", remaining.as_secs_f64()))
+                            .size(64.0)
+                            .strong(),
+                    );
+                    ui.label(format!(
+                        "Press CONFIRM to start the run to {:.0}cm",
+                        self.gui_data.distance
+                    ));
+                    if ui.add_sized([120., 40.], Button::new("DISARM")).clicked() {
+                        self.disarm();
+                    }
+                });
+            });
+    }
+
+    /// Show how far along saving the run is, to cancel it
+    fn show_saving_window(&mut self, ctx: &Context) {
+        let Some(save_job) = &self.save_job else {
+            return;
+        };
+        Window::new("Saving")
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(&save_job.description);
+                let (done, total) = save_job.progress.steps();
+                ui.add(
+                    ProgressBar::new(save_job.progress.fraction())
+                        .desired_width(200.0)
+                        .text(format!("{done} of {total} statuses")),
+                );
+                if ui.add_sized([60., 30.], Button::new("Cancel")).clicked() {
+                    save_job.cancel();
+                }
+            });
+    }
+
+    /// Put up a notice for every service reminder that's due
+    fn check_service_reminders(&mut self) {
+        self.gui_data.service_notices = self
+            .gui_data
+            .maintenance
+            .due(&self.config.maintenance.reminders)
+            .into_iter()
+            .map(|reminder| reminder.task.clone())
+            .collect();
+    }
+
+    /// Check there's room to save runs to the data directory and that what
+    /// it's on isn't failing, before anything's logged to it
+    ///
+    /// Below the free space needed, the oldest runs are pruned to make room
+    /// (unless that's turned off), so a run doesn't fail to save halfway.
+    fn check_storage(&mut self) {
+        let Some(data_directory) = &self.data_directory else {
+            return;
+        };
+        let mut problems = StorageChecks::new(data_directory.path.clone()).run();
+        let needed = self.config.storage.min_free_mb * BYTES_PER_MB;
+        match free_space(&data_directory.path) {
+            Ok(free) if free < needed => {
+                let (pruned, freed) = match self.config.storage.prune {
+                    true => data_directory
+                        .prune_for_space(free, needed)
+                        .unwrap_or_else(|e| {
+                            self.errors.push(e.into());
+                            (0_usize, 0_u64)
+                        }),
+                    false => (0_usize, 0_u64),
+                };
+                if pruned > 0 {
+                    self.gui_data.toast = Some(Toast::new(format!(
+                        "Deleted the oldest {pruned} runs to make room"
+                    )));
+                }
+                if free + freed < needed {
+                    problems.push(format!(
+                        "Only {}MB is free in {}, under the {}MB needed, so runs may fail to \
+                         save; free some up or copy old runs off",
+                        (free + freed) / BYTES_PER_MB,
+                        data_directory.path.display(),
+                        self.config.storage.min_free_mb
+                    ));
+                }
+            }
+            Ok(_) => {}
+            Err(e) => self.errors.push(e.into()),
+        }
+
+        for problem in problems {
+            if self.gui_data.storage_problems.contains(&problem) {
+                continue;
+            }
+            self.gui_data.storage_problems.push(problem.clone());
+            self.errors
+                .push(ErrorData::new(ClientError::DataDirectory(problem)));
+        }
+    }
+
+    /// Show the service reminders that are due out of the way in a corner,
+    /// with how much the car has been used since each was last done
+    fn show_service_notices(&mut self, ctx: &Context) {
+        let units = self.config.screen.units;
+        let mut done = None;
+        let mut later = false;
+        Window::new("Service due")
+            .anchor(Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                for task in self.gui_data.service_notices.iter() {
+                    let since = self.gui_data.maintenance.since_serviced(task);
+                    ui.horizontal(|ui| {
+                        if ui.button("Done").clicked() {
+                            done = Some(task.clone());
+                        }
+                        ui.label(format!(
+                            "{task} ({} and {} runs since)",
+                            units.format_long_distance(since.distance, 1),
+                            since.runs
+                        ));
+                    });
+                }
+                later = ui.button("Later").clicked();
+            });
+
+        if let Some(task) = done {
+            self.gui_data
+                .maintenance
+                .serviced(&task)
+                .unwrap_or_else(|e| self.errors.push(e.into()));
+            self.gui_data
+                .service_notices
+                .retain(|notice| *notice != task);
+        }
+        if later {
+            self.gui_data.service_notices.clear();
+        }
+    }
+
+    /// Show what's new since the version last run, safety features first, so
+    /// they're known about before they're needed
+    fn show_whats_new(&mut self, ctx: &Context) {
+        let mut seen = false;
+        Window::new(format!("What's new in {VERSION}"))
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for release in self.gui_data.whats_new.releases.iter() {
+                        ui.heading(&release.version);
+                        for note in release.sorted_notes() {
+                            let title = match note.safety {
+                                true => RichText::new(format!("Safety: {}", note.title))
+                                    .color(Color32::LIGHT_RED),
+                                false => RichText::new(&note.title),
+                            };
+                            ui.label(title.strong());
+                            ui.label(&note.details);
+                        }
+                    }
+                });
+                seen = ui.add_sized([80., 30.], Button::new("Got it")).clicked();
+            });
+
+        if seen {
+            self.gui_data
+                .whats_new
+                .seen()
+                .unwrap_or_else(|e| self.errors.push(e.into()));
+        }
+    }
+
+    /// Show the last toast until it's been up for long enough
+    fn show_toast(&mut self, ctx: &Context) {
+        let Some(toast) = &self.gui_data.toast else {
+            return;
+        };
+        if toast.shown.elapsed() > Duration::from_secs_f64(TOAST_SECONDS) {
+            self.gui_data.toast = None;
+            return;
+        }
+        Window::new("toast")
+            .title_bar(false)
+            .anchor(Align2::CENTER_BOTTOM, [0.0, -10.0])
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(&toast.text);
+            });
+    }
+
+    /// The most recent events logged at least as severe as the level picked,
+    /// newest at the bottom
+    fn show_log_panel(&mut self, ctx: &Context) {
+        TopBottomPanel::bottom("logs")
+            .resizable(false)
+            .show_animated(ctx, self.gui_data.show_logs, |ui| {
+                let events = self.logs.recent(self.gui_data.log_level);
+                ui.horizontal(|ui| {
+                    ui.strong("Logs");
+                    ComboBox::from_id_source("log level")
+                        .selected_text(self.gui_data.log_level.to_string())
+                        .show_ui(ui, |ui| {
+                            for level in LogLevel::ALL {
+                                ui.selectable_value(
+                                    &mut self.gui_data.log_level,
+                                    level,
+                                    level.to_string(),
+                                );
+                            }
+                        });
+                    ui.weak(format!("{} of {} kept", events.len(), self.logs.len()));
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.button("Hide").clicked() {
+                            self.gui_data.show_logs = false;
+                        }
+                    });
+                });
+                ScrollArea::vertical()
+                    .max_height(80.0)
+                    .auto_shrink([false, true])
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        if events.is_empty() {
+                            ui.weak("Nothing logged yet");
+                        }
+                        for event in events {
+                            let text = RichText::new(format!(
+                                "{} {:<5} {}",
+                                self.config.time.timezone.format(event.time, "%H:%M:%S"),
+                                event.level,
+                                event.describe()
+                            ))
+                            .monospace();
+                            ui.add(
+                                Label::new(match event.level {
+                                    Level::ERROR => text.color(Color32::RED),
+                                    Level::WARN => text.color(Color32::YELLOW),
+                                    Level::INFO => text,
+                                    _ => text.weak(),
+                                })
+                                .wrap(true),
+                            );
+                        }
+                    });
+            });
+    }
+
+    /// Feed something in as if it came from the car
+    fn inject(&mut self, injection: Injection) {
+        self.serial_event_propagator.injected.push_back(injection);
+    }
+
+    /// Show the debug menu, for making the car misbehave on cue
+    /// Change the run's annotations, keeping the saved copy up to date
+    fn annotate(&mut self, edit: AnnotationEdit) {
+        let action = match edit {
+            AnnotationEdit::Add(annotation) => {
+                self.run_data.annotations.push(annotation.clone());
+                UserAction::Annotate(annotation)
+            }
+            AnnotationEdit::Remove(idx) if idx < self.run_data.annotations.len() => {
+                self.run_data.annotations.remove(idx);
+                UserAction::RemoveAnnotation(idx)
+            }
+            AnnotationEdit::Remove(_) => return,
+        };
+        self.journal.record(JournalEntryKind::Action(action));
+
+        if let (true, Some(file_path)) = (self.run_data.saved, &self.gui_data.file_path) {
+            write_annotations(file_path, &self.run_data.annotations)
+                .unwrap_or_else(|e| self.errors.push(e.into()));
+        }
+    }
+
+    #[cfg(feature = "debug-menu")]
+    fn show_debug_window(&mut self, ctx: &Context) {
+        Window::new("Debug").resizable(false).show(ctx, |ui| {
+            let close_button_size = [60., 30.];
+            if ui
+                .add_sized(close_button_size, Button::new("Close"))
+                .clicked()
+            {
+                self.gui_data.show_debug_window = false;
+            }
+            ui.label("Everything here is handled as if it came from the car");
+
+            ui.separator();
+            ComboBox::from_id_source("injected error")
+                .selected_text(format!(
+                    "{:?}",
+                    INJECTABLE_ERRORS[self.gui_data.injected_error]
+                ))
+                .show_ui(ui, |ui| {
+                    for (index, error) in INJECTABLE_ERRORS.iter().enumerate() {
+                        ui.selectable_value(
+                            &mut self.gui_data.injected_error,
+                            index,
+                            format!("{error:?}"),
+                        );
+                    }
+                });
+            if ui.button("Inject error response").clicked() {
+                // Answers the oldest request still waiting, like a real one
+                let sequence = self
+                    .serial_event_propagator
+                    .pending_requests
+                    .first()
+                    .map(|pending| pending.sequence);
+                match error_response(
+                    INJECTABLE_ERRORS[self.gui_data.injected_error],
+                    sequence,
+                    unix_now(),
+                ) {
+                    Ok(frame) => self.inject(Injection::Frame(frame)),
+                    Err(e) => self.errors.push(e.into()),
+                }
+            }
+
+            ui.separator();
+            ComboBox::from_id_source("malformed frame")
+                .selected_text(self.gui_data.malformed_frame.label())
+                .show_ui(ui, |ui| {
+                    for malformed_frame in MalformedFrame::ALL {
+                        ui.selectable_value(
+                            &mut self.gui_data.malformed_frame,
+                            malformed_frame,
+                            malformed_frame.label(),
+                        );
+                    }
+                });
+            if ui.button("Inject malformed frame").clicked() {
+                self.inject(Injection::Frame(self.gui_data.malformed_frame.frame()));
+            }
+
+            ui.separator();
+            if ui.button("Drop the link").clicked() {
+                self.inject(Injection::LinkDown);
+            }
+        });
+    }
+
+    /// Check the computer is set up to talk to the car, showing anything
+    /// that isn't until it's fixed
+    fn run_startup_checks(&mut self, checks: StartupChecks) {
+        self.startup_problems = checks.run();
+        self.startup_checks = Some(checks);
+    }
+
+    /// Show what's wrong with the computer and how to fix it
+    fn show_startup_problems(&mut self, ctx: &Context) {
+        Window::new("Before starting")
+            .resizable(false)
+            .default_width(ctx.screen_rect().width() - 60.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let startup_button_size = [90., 30.];
+                    if ui
+                        .add_sized(startup_button_size, Button::new("Check again"))
+                        .clicked()
+                    {
+                        if let Some(checks) = self.startup_checks.take() {
+                            self.run_startup_checks(checks);
+                        }
+                    }
+                    if ui
+                        .add_sized(startup_button_size, Button::new("Dismiss"))
+                        .clicked()
+                    {
+                        self.startup_problems.clear();
+                    }
+                });
+
+                ScrollArea::vertical().max_height(200.).show(ui, |ui| {
+                    for startup_problem in self.startup_problems.iter() {
+                        ui.separator();
+                        ui.add(
+                            Label::new(RichText::new(startup_problem.problem.as_str()).strong())
+                                .wrap(true),
+                        );
+                        ui.add(Label::new(startup_problem.fix.as_str()).wrap(true));
+                    }
+                });
+            });
+    }
+
+    /// Show error messages, only those from the subsystem filtered to if any
+    ///
+    /// Assumes there are error messages, otherwise the window it shows would be
+    /// pretty useless
+    fn show_error_messages(&mut self, ctx: &Context) {
+        Window::new("Errors!").resizable(false).show(ctx, |ui| {
+            ui.heading(match self.errors.len() {
+                0 => unreachable!(),
+                1 => "An error has occurred!",
+                2..=5 => "Some errors have occurred!",
+                _ => "Something has *definitely* gone wrong!",
+            });
+
+            ui.horizontal(|ui| {
+                let clear_errors_button_size = [60., 40.];
+                if ui
+                    .add_sized(clear_errors_button_size, Button::new("Clear"))
+                    .clicked()
+                {
+                    self.errors.clear();
+                    self.gui_data.show_error_drawer = false;
+                    self.gui_data.error_filter = None;
+                };
+                // Back to the badge, until the run is over
+                if self.run_data.running
+                    && ui
+                        .add_sized(clear_errors_button_size, Button::new("Hide"))
+                        .clicked()
+                {
+                    self.gui_data.show_error_drawer = false;
+                }
+                let export_diagnostics_button_size = [120., 40.];
+                if ui
+                    .add_sized(
+                        export_diagnostics_button_size,
+                        Button::new("Export diagnostics"),
+                    )
+                    .clicked()
+                {
+                    match self.export_diagnostics() {
+                        Ok(file_path) => self.gui_data.diagnostics_path = Some(file_path),
+                        Err(e) => self.errors.push(e.into()),
+                    }
+                }
+            });
+            if let Some(file_path) = &self.gui_data.diagnostics_path {
+                ui.label(format!("Diagnostics saved to {}", file_path.display()));
+            }
+            ui.horizontal(|ui| {
+                let filter = &mut self.gui_data.error_filter;
+                if ui.selectable_label(filter.is_none(), "All").clicked() {
+                    *filter = None;
+                }
+                for (subsystem, count) in error_counts(&self.errors) {
+                    if ui
+                        .selectable_label(
+                            *filter == Some(subsystem),
+                            format!("{subsystem} ({count})"),
+                        )
+                        .clicked()
+                    {
+                        *filter = Some(subsystem);
+                    }
+                }
+            });
+
+            let errors_table = TableBuilder::new(ui)
+                .striped(true)
+                .resizable(false)
+                .cell_layout(Layout::left_to_right(Align::Center))
+                .column(Column::auto())
+                .column(Column::auto())
+                .column(Column::remainder())
+                .min_scrolled_height(0.0);
+
+            // By index, fixed once the table is done with the errors
+            let mut fixed = None;
+            errors_table
+                .header(20.0, |mut header| {
+                    header.col(|ui| {
+                        ui.strong("Time");
+                    });
+                    header.col(|_| ());
+                    header.col(|ui| {
+                        ui.strong("Error");
+                    });
+                })
+                .body(|mut body| {
+                    let filter = self.gui_data.error_filter;
+                    for (idx, error) in self.errors.iter().enumerate() {
+                        if filter.is_some_and(|filter| Subsystem::of(&error.error) != filter) {
+                            continue;
+                        }
+                        let error_text = error.error.to_string();
+                        body.row(18.0, |mut row| {
+                            row.col(|ui| {
+                                ui.label(self.config.time.timezone.format(error.time, "%H:%M:%S"));
+                            });
+                            row.col(|ui| {
+                                if let Some(recovery) = error.recovery {
+                                    if ui
+                                        .button("Fix it")
+                                        .on_hover_text(recovery.to_string())
+                                        .clicked()
+                                    {
+                                        fixed = Some(idx);
+                                    }
+                                }
+                            });
+                            row.col(|ui| {
+                                ui.add(
+                                    Label::new(error_text)
+                                        .wrap(false /* FIXME: fix wrapping */),
+                                );
+                            });
+                        });
+                    }
+                });
+
+            if let Some(idx) = fixed {
+                let error = self.errors.remove(idx);
+                if let Some(recovery) = error.recovery {
+                    self.recover(recovery);
+                }
+            }
+        });
+    }
+
+    /// Read the serial port for any responses and parse them, placing them in `self.run_data`
+    fn get_serial_responses(&mut self) -> Result<(), ClientError> {
+        // Get down everything available
+        while let Some(data) = self.serial_event_propagator.read_from_serial()? {
+            self.journal
+                .record(JournalEntryKind::Received(data.clone()));
+
+            // Parse into a response, asking again for anything that came in corrupted
+            match Frame::decode(&data) {
+                Ok(parsed_response) => self.handle_response(parsed_response),
+                Err(ClientError::ChecksumMismatch {
+                    command: Some(command),
+                    ..
+                }) if RESENDABLE_COMMANDS.contains(&command) => self.request_resend(command)?,
+                Err(e) => Err(e)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add a parsed response to the corresponding run data
+    fn handle_response(&mut self, parsed_response: Response) {
+        match self.serial_event_propagator.negotiate(&parsed_response) {
+            Ok(false) => (),
+            Ok(true) => return,
+            Err(e) => return self.errors.push(e.into()),
+        }
+        // Whatever the bridge connected to could speak any version
+        if matches!(
+            &parsed_response,
+            Response::BluetoothConnect(resp) if resp.value.connected == Some(true)
+        ) {
+            self.serial_event_propagator.hello_due = true;
+        }
+        let finalized = matches!(
+            &parsed_response,
+            Response::Status(resp) if matches!(resp.value.stage, StatusStage::Finalized)
+        );
+        if matches!(parsed_response, Response::Status(_)) {
+            self.last_status_received = Some(Instant::now());
+            if self.last_status_time.is_some() {
+                self.last_status_time = Some(Instant::now());
+            }
+        }
+
+        let now = unix_now();
+        if let Some(round_trip) = parsed_response.round_trip(now) {
+            self.round_trips
+                .record(parsed_response.command(), round_trip);
+        }
+        self.run_data
+            .apply_response(parsed_response, now)
+            .unwrap_or_else(|e| self.errors.push(e.into()));
+
+        // An extra line of defense against the car running away, as it was set
+        // up when the run started (not as the sliders have been moved since)
+        if let (
+            true,
+            Some(RunParameters {
+                distance: target,
+                auto_stop_percentage: Some(percentage),
+                ..
+            }),
+        ) = (self.run_data.running, self.run_data.parameters)
+        {
+            let limit = target * percentage / 100.0;
+            if let Some(status) = self.run_data.status_responses.last() {
+                let distance = status.value.distance.distance;
+                if distance > limit {
+                    self.stop();
+                    self.errors.push(ErrorData::new(ClientError::Run(format!(
+                        "Stopped automatically at {distance:.1}cm, past {percentage:.0}% of \
+                         {target:.1}cm"
+                    ))));
+                }
+            }
+        }
+
+        if finalized && !self.run_data.saved {
+            let distance = self
+                .run_data
+                .status_responses
+                .last()
+                .map(|status| status.value.distance.distance);
+            if let (Some(session_log), Some(distance)) = (&mut self.session_log, distance) {
+                if let Err(e) = session_log.log(unix_now(), SessionEvent::Finished { distance }) {
+                    self.session_log = None;
+                    self.errors.push(ErrorData::new(e));
+                }
+            }
+            self.autosave()
+                .unwrap_or_else(|e| self.errors.push(e.into()));
+            // Never thrown away unless it was saved
+            if self.run_data.saved && self.config.run.auto_reset > 0.0 {
+                self.finalized_time = Some(Instant::now());
+            }
+        }
+    }
+
+    /// Send the request for a response that came in corrupted again
+    ///
+    /// Only requests that are safe to repeat are resent, see [`RESENDABLE_COMMANDS`]
+    fn request_resend(&mut self, command: Command) -> Result<(), ClientError> {
+        match command {
+            Command::Ping => self.send(command, PingArguments::builder().time(unix_now()).build()),
+            Command::StaticStatus => self.send(command, StaticStatusArguments {}),
+            Command::Status => self.send(command, StatusArguments {}),
+            Command::BluetoothStatus => self.send(command, BluetoothStatusRequest {}),
+            Command::BluetoothMetrics => self.send(command, BluetoothMetricsRequest {}),
+            _ => Err(ClientError::Run(format!("Can't safely resend {command}"))),
+        }
+    }
+
+    /// Write a command to the serial connection, keeping it in the journal
+    ///
+    /// A status that's already been asked for isn't asked for again until
+    /// it's answered.
+    fn send<S>(&mut self, command: Command, data: S) -> Result<(), ClientError>
+    where
+        S: Serialize + for<'a> Deserialize<'a> + Sized,
+    {
+        let Some(frame) = self.serial_event_propagator.queue(command, data)? else {
+            return Ok(());
+        };
+        self.run_data.record_sent(&frame, unix_now(), false);
+        self.journal.record(JournalEntryKind::Sent(frame));
+        Ok(())
+    }
+
+    /// The directory the file picker starts in: the current file's, the data
+    /// directory, or the working directory, in that order
+    fn file_picker_directory(&self) -> PathBuf {
+        self.gui_data
+            .file_path
+            .as_ref()
+            .and_then(|file| file.parent())
+            .filter(|directory| directory.is_dir())
+            .map(PathBuf::from)
+            .or_else(|| {
+                self.data_directory
+                    .as_ref()
+                    .map(|data_directory| data_directory.path.clone())
+            })
+            .or_else(|| current_dir().ok())
+            .unwrap_or_default()
+    }
+
+    /// Where to save the latest run unless another file is picked: named
+    /// after the session, in the directory last saved to at this venue
+    fn suggested_save_path(&self) -> PathBuf {
+        let date = self.config.time.timezone.format(Utc::now(), "%Y-%m-%d");
+        let directory = self
+            .gui_data
+            .session
+            .directory()
+            .filter(|directory| directory.is_dir())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.file_picker_directory());
+        self.gui_data.export_format.path(
+            &directory.join(
+                self.gui_data
+                    .session
+                    .run_name(&date, self.gui_data.distance),
+            ),
+        )
+    }
+
+    /// Save the run into the data directory with an automatic name
+    fn autosave(&mut self) -> Result<(), ClientError> {
+        let data_directory = match &self.data_directory {
+            Some(data_directory) => data_directory,
+            None => return Ok(()),
+        };
+
+        let file_path =
+            data_directory.next_run_path(self.gui_data.distance, self.gui_data.export_format);
+        self.gui_data
+            .export_format
+            .write_with(
+                &file_path,
+                &self.run_data.status_responses.full()?,
+                self.table_options(),
+            )
+            .map_err(|e| {
+                ClientError::DataDirectory(format!("Failed to save {}: {e}", file_path.display()))
+            })?;
+        self.run_data.saved = true;
+        write_annotations(&file_path, &self.run_data.annotations)?;
+        data_directory.enforce_retention(RETAINED_RUNS)?;
+
+        self.gui_data.set_file_path(file_path.clone());
+        self.gui_data.push_recent_file(file_path);
+
+        Ok(())
+    }
+
+    /// Start saving the run to the chosen file in the background, as long
+    /// runs take a while
+    fn save(&mut self) {
+        if self.save_job.is_some() {
+            return;
+        }
+        let export_format = self.gui_data.export_format;
+        let file_path = export_format.path(
+            &(self
+                .gui_data
+                .file_path
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(&self.gui_data.display_file_path))),
+        );
+        // Shared with the job rather than copied, unless some were spilled
+        let snapshot = self.shared_run_data.publish(&self.run_data);
+        let statuses = match self.run_data.status_responses.downsampled() {
+            true => match self.run_data.status_responses.full() {
+                Ok(statuses) => statuses.into(),
+                Err(e) => return self.errors.push(e.into()),
+            },
+            false => snapshot.statuses.clone(),
+        };
+        self.save_job = Some(save_in_background(
+            export_format,
+            self.table_options(),
+            file_path,
+            statuses,
+            snapshot.annotations.to_vec(),
+        ));
+    }
+
+    /// Check on the run being saved in the background
+    fn poll_save(&mut self) {
+        let Some(outcome) = self.save_job.as_ref().and_then(BackgroundJob::poll) else {
+            return;
+        };
+        self.save_job = None;
+        match outcome {
+            JobOutcome::Finished(saved_run) => {
+                self.run_data.saved = true;
+                if let Some(directory) = saved_run.path.parent() {
+                    self.gui_data
+                        .session
+                        .remember_directory(directory.to_path_buf())
+                        .unwrap_or_else(|e| self.errors.push(e.into()));
+                }
+                self.gui_data.toast = Some(Toast::new(format!(
+                    "Saved {} ({})",
+                    saved_run.path.display(),
+                    size_text(saved_run.size)
+                )));
+                self.gui_data.set_file_path(saved_run.path.clone());
+                self.gui_data.push_recent_file(saved_run.path);
+            }
+            JobOutcome::Cancelled => {
+                self.gui_data.toast = Some(Toast::new("Stopped saving".to_owned()))
+            }
+            JobOutcome::Failed(e) => self.errors.push(e.into()),
+        }
+    }
+
+    /// Export everything needed to figure out what went wrong as a diagnostic
+    /// bundle, returning where it was saved
+    fn export_diagnostics(&mut self) -> Result<PathBuf, ClientError> {
+        let bundle = DiagnosticBundle {
+            version: version_info(),
+            config: json!({
+                "distance": self.gui_data.distance,
+                "reverse_braking": self.gui_data.reverse_braking,
+                "settings": self.config,
+                "auto_stop": self.gui_data.auto_stop,
+                "auto_stop_percentage": self.gui_data.auto_stop_percentage,
+                "speed_limit": self.gui_data.speed_limit,
+                "max_velocity": self.gui_data.max_velocity,
+                "recompute_distance": self.gui_data.recompute_distance,
+                "wheel_circumference_override": self
+                    .gui_data
+                    .override_wheel_circumference
+                    .then_some(self.gui_data.wheel_circumference),
+                "file_path": self
+                    .gui_data
+                    .file_path
+                    .clone()
+                    .unwrap_or_else(|| PathBuf::from(&self.gui_data.display_file_path)),
+            })
+            .to_string(),
+            errors: self
+                .errors
+                .iter()
+                .map(|error| ErrorData {
+                    error: ClientError::Recorded(error.error.to_string()),
+                    time: error.time,
+                    recovery: None,
+                })
+                .collect(),
+            #[cfg(feature = "bluetooth")]
+            bridge_output: self
+                .bridge
+                .as_ref()
+                .map(|bridge| bridge.output.clone())
+                .unwrap_or_default(),
+            #[cfg(not(feature = "bluetooth"))]
+            bridge_output: vec![],
+            frames: self
+                .serial_event_propagator
+                .recent_frames
+                .iter()
+                .cloned()
+                .collect(),
+            status_responses: self.run_data.status_responses.full()?,
+            journal: self.journal.clone(),
+            annotations: self.run_data.annotations.clone(),
+        };
+
+        let file_path = PathBuf::from(
+            self.config
+                .time
+                .timezone
+                .format(Utc::now(), "diagnostics-%Y-%m-%d_%H-%M-%S.zip"),
+        );
+        bundle.write(&file_path)?;
+
+        Ok(file_path)
+    }
+
+    /// Start logging every frame sent and received to a new file
+    fn start_wire_log(&mut self) -> Result<(), ClientError> {
+        let file_path = PathBuf::from(
+            self.config
+                .time
+                .timezone
+                .format(Utc::now(), "wire-%Y-%m-%d_%H-%M-%S.log"),
+        );
+        self.serial_event_propagator.wire_logger = Some(WireLogger::open(file_path)?);
+        Ok(())
+    }
+
+    /// Start replaying a wire log, which feeds its received frames in as they
+    /// were received
+    fn load_wire_log(&mut self, replay: WireLogReplay) {
+        // This is a run that already happened, don't save it again
+        self.run_data.saved = true;
+        self.gui_data.show_bluetooth_connect_screen = false;
+        self.wire_log_replay = Some(replay);
+    }
+
+    /// Handle the frames of the wire log being replayed that are due by now
+    fn replay_wire_log(&mut self) {
+        let Some(replay) = &mut self.wire_log_replay else {
+            return;
+        };
+        for frame in replay.due() {
+            self.journal
+                .record(JournalEntryKind::Received(frame.clone()));
+            match Frame::decode(&frame) {
+                Ok(parsed_response) => self.handle_response(parsed_response),
+                Err(e) => self.errors.push(e.into()),
+            }
+        }
+    }
+
+    /// Use settings from the config file
+    fn apply_config(&mut self, config: Config) {
+        self.serial_event_propagator
+            .apply_serial_config(&config.serial);
+        self.run_data.status_responses.capacity = config.run.status_history;
+        self.gui_data.wheel_circumference = config.car.wheel_diameter * PI;
+        self.config = config;
+    }
+
+    /// Everything that's carried over to another computer
+    fn settings(&self) -> SettingsFile {
+        SettingsFile::new(
+            self.config.clone(),
+            RunSettings {
+                reverse_braking: self.gui_data.reverse_braking,
+                auto_stop: self.gui_data.auto_stop,
+                auto_stop_percentage: self.gui_data.auto_stop_percentage,
+                speed_limit: self.gui_data.speed_limit,
+                max_velocity: self.gui_data.max_velocity,
+                override_wheel_circumference: self.gui_data.override_wheel_circumference,
+                wheel_circumference: self.gui_data.wheel_circumference,
+                recompute_distance: self.gui_data.recompute_distance,
+                split_interval: self.gui_data.split_interval,
+                export_format: self.gui_data.export_format,
+                bluetooth_address: self.gui_data.bluetooth_address.clone(),
+            },
+        )
+    }
+
+    /// Use settings brought over from another computer
+    ///
+    /// The screen size only takes effect after a restart
+    fn apply_settings(&mut self, settings: SettingsFile) {
+        self.apply_config(settings.config);
+        let run = settings.run;
+        self.gui_data.reverse_braking = run.reverse_braking;
+        self.gui_data.auto_stop = run.auto_stop;
+        self.gui_data.auto_stop_percentage = run.auto_stop_percentage;
+        self.gui_data.speed_limit = run.speed_limit;
+        self.gui_data.max_velocity = run.max_velocity;
+        self.gui_data.override_wheel_circumference = run.override_wheel_circumference;
+        self.gui_data.wheel_circumference = run.wheel_circumference;
+        self.gui_data.recompute_distance = run.recompute_distance;
+        self.gui_data.split_interval = run.split_interval;
+        self.gui_data.export_format = run.export_format;
+        self.gui_data.bluetooth_address = run.bluetooth_address;
+    }
+
+    /// Export the settings where the file picker would look for them,
+    /// returning where they were saved
+    fn export_settings(&self) -> Result<PathBuf, ClientError> {
+        let file_path = self.file_picker_directory().join(
+            self.config
+                .time
+                .timezone
+                .format(Utc::now(), "settings-%Y-%m-%d_%H-%M-%S.toml"),
+        );
+        self.settings().write(&file_path)?;
+        Ok(file_path)
+    }
+
+    /// Save the config where it was read from, if it can be
+    fn save_config(&mut self) {
+        let Some(config_path) = &self.config_path else {
+            return;
+        };
+        if let Err(e) = self.config.write(config_path) {
+            self.errors.push(e.into());
+        }
+    }
+
+    /// Write what was sent and received this run, interleaved, next to where
+    /// it's saved (or would be)
+    fn export_narrative(&self) -> Result<PathBuf, ClientError> {
+        let run_path = self
+            .gui_data
+            .file_path
+            .clone()
+            .unwrap_or_else(|| self.suggested_save_path());
+        let clock_offset = self
+            .run_data
+            .ping_times()
+            .map(|ping| ping.clock_offset())
+            .unwrap_or(0.0);
+        write_narrative(
+            &run_path,
+            &narrative(
+                &self.run_data.status_responses,
+                &self.run_data.sent_commands,
+                clock_offset,
+                &self.config.time.timezone,
+                self.config.screen.units,
+            ),
+        )
+    }
+
+    /// Load a diagnostic bundle, replaying its received frames to rebuild the
+    /// run data as it was
+    fn load_diagnostics(&mut self, mut bundle: DiagnosticBundle) {
+        self.errors.append(&mut bundle.errors);
+        // This is someone else's run, don't save it
+        self.run_data.saved = true;
+
+        let received_frames = bundle
+            .received_frames()
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        for frame in received_frames {
+            match Frame::decode(&frame) {
+                Ok(parsed_response) => self.handle_response(parsed_response),
+                Err(e) => self.errors.push(e.into()),
+            }
+        }
+
+        // Only the most recent frames are kept, but every status is
+        if bundle.status_responses.len() > self.run_data.status_responses.len() {
+            self.run_data
+                .status_responses
+                .replace(take(&mut bundle.status_responses));
+        }
+        self.run_data.annotations = bundle.annotations.clone();
+
+        self.gui_data.show_bluetooth_connect_screen = false;
+        self.gui_data.show_diagnostics_window = true;
+        self.diagnostic_bundle = Some(bundle);
+    }
+
+    /// All logic that is run every time the window is updated (i.e. every frame)
+    fn logic(&mut self) {
+        // Not confirmed in time
+        if self.arming.expired(Instant::now()) {
+            self.disarm();
+        }
+        self.poll_save();
+
+        // Ready for the next heat without pressing RESET, as long as nothing
+        // changed since it was saved
+        if let Some(finalized_time) = self.finalized_time {
+            if finalized_time.elapsed() > Duration::from_secs_f64(self.config.run.auto_reset) {
+                self.finalized_time = None;
+                if self.run_data.saved && self.save_job.is_none() {
+                    self.reset();
+                    self.gui_data.current_job = ClientStatus::GatheringData;
+                }
+            }
+        }
+
+        // The car might be running away with nobody able to see it
+        if let Some(last_status_time) = self.last_status_time {
+            let watchdog = self.config.run.status_watchdog;
+            if self.run_data.running
+                && watchdog > 0.0
+                && last_status_time.elapsed() > Duration::from_secs_f64(watchdog)
+            {
+                self.emergency_stop();
+                self.errors.push(ErrorData::new(ClientError::Run(format!(
+                    "Stopped automatically, no status came in for {watchdog:.1}s"
+                ))));
+            }
+        }
+
+        // Before it cuts out mid run
+        let power = self
+            .run_data
+            .status_responses
+            .last()
+            .and_then(|status| status.value.power);
+        if let Some(power) = power {
+            if let Some(warning) = self.gui_data.battery_alert.check(&power, &self.config.car) {
+                warn!("{warning}");
+                self.gui_data.toast = Some(Toast::new(warning));
+            }
+        }
+
+        // Keep the bridge alive if we are in charge of it
+        #[cfg(feature = "bluetooth")]
+        if let Some(bridge) = &mut self.bridge {
+            bridge.poll().unwrap_or_else(|e| self.errors.push(e.into()));
+        }
+        #[cfg(feature = "embedded-bridge")]
+        if let Some(embedded_bridge) = &mut self.embedded_bridge {
+            embedded_bridge
+                .poll()
+                .unwrap_or_else(|e| self.errors.push(e.into()));
+        }
+
+        // Receive new serial information if needed
+        {
+            // `Instant::elapsed()` *does* exist, but if we are going to update
+            // the last_get_time with the current time instead of just adding the
+            // delay to it, then it's practical to just get the current time here
+            // and use `Instant::duration_since(...)`
+            let current_time = Instant::now();
+            if current_time.duration_since(self.serial_event_propagator.last_get_time)
+                > Duration::from_secs_f64(self.config.serial.poll_interval)
+            {
+                self.serial_event_propagator.maintain_connection();
+                if take(&mut self.serial_event_propagator.hello_due) {
+                    self.send(
+                        Command::Hello,
+                        HelloArguments {
+                            protocol_version: PROTOCOL_VERSION,
+                        },
+                    )
+                    .unwrap_or_else(|e| self.errors.push(e.into()));
+                }
+                self.get_serial_responses()
+                    .unwrap_or_else(|e| self.errors.push(e.into()));
+                self.replay_wire_log();
+                if let Some(e) = self.serial_event_propagator.wire_log_error.take() {
+                    self.errors.push(e.into());
+                }
+                if let Some(e) = self.run_data.status_responses.spill_error.take() {
+                    self.errors.push(e.into());
+                }
+                self.serial_event_propagator.last_get_time = current_time;
+
+                // Whatever waited behind something more urgent
+                self.serial_event_propagator
+                    .flush_outgoing()
+                    .unwrap_or_else(|e| self.errors.push(e.into()));
+                // Send again whatever went unanswered
+                for result in self.serial_event_propagator.retry_pending() {
+                    match result {
+                        Ok(frame) => {
+                            self.run_data.record_sent(&frame, unix_now(), true);
+                            self.journal.record(JournalEntryKind::Sent(frame));
+                        }
+                        // Some firmware doesn't know about static statuses,
+                        // which shouldn't stop the run
+                        Err(ClientError::Timeout {
+                            command: Command::StaticStatus,
+                            ..
+                        }) if matches!(
+                            self.gui_data.current_job,
+                            ClientStatus::ReceivingStaticStatus
+                        ) =>
+                        {
+                            self.run_data.static_status_supported = Some(false);
+                            self.run_data
+                                .assume_static_status(&self.config.car, unix_now());
+                            self.gui_data.current_job = self.gui_data.current_job.next();
+                            self.errors.push(ErrorData::new(ClientError::Run(format!(
+                                "The server didn't send car information, \
+                                 assuming {} magnet(s) and a {}cm wheel",
+                                self.config.car.number_of_magnets, self.config.car.wheel_diameter
+                            ))));
+                        }
+                        // Nothing answered, not even old firmware, so
+                        // there's nothing to go on but the version spoken here
+                        Err(ClientError::Timeout {
+                            command: Command::Hello,
+                            ..
+                        }) => (),
+                        Err(e) => self.errors.push(e.into()),
+                    }
+                }
+            }
+        }
+
+        // Handle current job / status
+        use ClientStatus::*;
+        match self.gui_data.current_job {
+            GatheringData => Ok(()),
+            SendingPing => {
+                self.gui_data.current_job = self.gui_data.current_job.next();
+                self.send(
+                    Command::Ping,
+                    PingArguments::builder().time(unix_now()).build(),
+                )
+            }
+            ReceivingPing => {
+                if self.run_data.ping_status_response.is_some() {
+                    self.gui_data.current_job = self.gui_data.current_job.next();
+                }
+                Ok(())
+            }
+            RequestingStaticStatus => match self.run_data.static_status_supported {
+                // Don't wait on it again
+                Some(false) => {
+                    self.run_data
+                        .assume_static_status(&self.config.car, unix_now());
+                    self.gui_data.current_job = self.gui_data.current_job.next().next();
+                    Ok(())
+                }
+                _ => {
+                    self.gui_data.current_job = self.gui_data.current_job.next();
+                    self.send(Command::StaticStatus, StaticStatusArguments {})
+                }
+            },
+            ReceivingStaticStatus => {
+                if self.run_data.static_status_response.is_some() {
+                    self.gui_data.current_job = self.gui_data.current_job.next();
+                }
+                Ok(())
+            }
+            RequestingStart => {
+                self.gui_data.current_job = self.gui_data.current_job.next();
+                self.last_status_time = Some(Instant::now());
+                self.send(
+                    Command::Start,
+                    StartArguments::builder()
+                        .distance_cm(self.gui_data.distance)
+                        .reverse_brake(self.gui_data.reverse_braking)
+                        .max_velocity_cm_s(
+                            self.gui_data
+                                .speed_limit
+                                .then_some(self.gui_data.max_velocity),
+                        )
+                        .distance_estimator(self.gui_data.distance_estimator)
+                        .coast_deceleration_cm_s2(self.config.car.coast_deceleration)
+                        .build(),
+                )
+            }
+            ReceivingStatus => Ok(()),
+            RequestingStop => {
+                self.gui_data.current_job = self.gui_data.current_job.next();
+                self.send(Command::Stop, StopArguments {})
+            }
+            Finished => Ok(()),
+            #[allow(unreachable_patterns)]
+            unhandled => {
+                self.gui_data.current_job = self.gui_data.current_job.next();
+                Err(ClientError::Unknown(format!(
+                    "Not sure how to handle current job of '{}', skipping it!",
+                    unhandled.to_string()
+                )))
+            }
+        }
+        .unwrap_or_else(|e| self.errors.push(e.into()));
+        self.journal.observe_job(self.gui_data.current_job);
+    }
+
+    /// Wait on START being pressed again before starting, unless arming is
+    /// turned off
+    fn arm(&mut self) {
+        if self.run_data.running {
+            return;
+        }
+
+        // Without a distance, starting would only show why it can't
+        #[allow(clippy::neg_cmp_op_on_partial_ord)]
+        if self.config.run.arming_countdown <= 0.0 || !(self.gui_data.distance > 0.0) {
+            return self.start();
+        }
+        self.arming = Arming::armed(
+            Instant::now(),
+            Duration::from_secs_f64(self.config.run.arming_countdown),
+        );
+        self.journal
+            .record(JournalEntryKind::Action(UserAction::Arm));
+    }
+
+    fn disarm(&mut self) {
+        if self.arming == Arming::Disarmed {
+            return;
+        }
+        self.arming = Arming::Disarmed;
+        self.journal
+            .record(JournalEntryKind::Action(UserAction::Disarm));
+    }
+
+    fn start(&mut self) {
+        self.arming = Arming::Disarmed;
+        if self.run_data.running {
+            return;
+        }
+
+        // Ensure we have all the user input
+        #[allow(clippy::neg_cmp_op_on_partial_ord)]
+        if !(self.gui_data.distance > 0.0) {
+            return self.errors.push(ErrorData::new(ClientError::Run(
+                "Distance is not over 0 centimeters".to_owned(),
+            )));
+        }
+
+        self.check_storage();
+        self.load_previous_run();
+        self.run_data.parameters = Some(RunParameters {
+            distance: self.gui_data.distance,
+            reverse_braking: self.gui_data.reverse_braking,
+            auto_stop_percentage: self
+                .gui_data
+                .auto_stop
+                .then_some(self.gui_data.auto_stop_percentage),
+            max_velocity: self
+                .gui_data
+                .speed_limit
+                .then_some(self.gui_data.max_velocity),
+        });
+        self.run_data.running = true;
+        self.gui_data.current_job = ClientStatus::SendingPing;
+        // Named ahead of time, so saving it is one tap
+        self.gui_data.session.runs += 1;
+        let file_path = self.suggested_save_path();
+        self.gui_data.set_file_path(file_path);
+        self.journal
+            .record(JournalEntryKind::Action(UserAction::Start {
+                distance: self.gui_data.distance,
+                reverse_braking: self.gui_data.reverse_braking,
+                wheel_circumference: self
+                    .gui_data
+                    .override_wheel_circumference
+                    .then_some(self.gui_data.wheel_circumference),
+            }));
+    }
+
+    fn stop(&mut self) {
+        self.run_data.running = false;
+        self.last_status_time = None;
+        self.gui_data.current_job = ClientStatus::RequestingStop;
+        self.journal
+            .record(JournalEntryKind::Action(UserAction::Stop));
+    }
+
+    /// Send STOP right now, whatever the run is in the middle of
+    ///
+    /// Unlike [`ClientGUIHandlers::stop`], this doesn't wait on the next
+    /// frame's logic, and the start is given up on so it isn't sent again
+    /// after the stop.
+    fn emergency_stop(&mut self) {
+        self.arming = Arming::Disarmed;
+        self.run_data.running = false;
+        self.last_status_time = None;
+        self.gui_data.current_job = ClientStatus::Finished;
+        self.serial_event_propagator
+            .pending_requests
+            .retain(|pending| pending.command != Command::Start);
+        self.journal
+            .record(JournalEntryKind::Action(UserAction::EmergencyStop));
+        self.send(Command::Stop, StopArguments {})
+            .unwrap_or_else(|e| self.errors.push(e.into()));
+    }
+
+    fn reset(&mut self) {
+        // Don't lose a run that never finished
+        if !self.run_data.saved && !self.run_data.status_responses.is_empty() {
+            self.autosave()
+                .unwrap_or_else(|e| self.errors.push(e.into()));
+        }
+        // Counted towards the service reminders, which are only checked at
+        // startup so they don't pop up between heats
+        let usage = Usage::of_run(&self.run_data.status_responses);
+        self.gui_data
+            .maintenance
+            .record_run(&usage)
+            .unwrap_or_else(|e| self.errors.push(e.into()));
+        // Kept to look back on, rather than thrown away
+        let archived = self.run_history.archive(&mut self.run_data);
+        if let Some(prompt) = &mut self.gui_data.stop_reason_prompt {
+            if prompt.run == StoppedRun::Current {
+                prompt.run = archived.map_or(StoppedRun::Gone, StoppedRun::Archived);
+            }
+        }
+
+        self.run_data.running = false;
+        self.last_status_time = None;
+        self.last_status_received = None;
+        self.finalized_time = None;
+        self.run_data.saved = false;
+        self.run_data.parameters = None;
+        self.run_data.annotations.clear();
+        self.run_data.other_responses.clear();
+        self.run_data.ping_status_response = None;
+        self.run_data.static_status_response = None;
+        self.run_data.status_responses.clear();
+        self.run_data.sent_commands.clear();
+        self.run_data.stop_reason = None;
+        self.journal
+            .record(JournalEntryKind::Action(UserAction::Reset));
+    }
+
+    /// Ask why the run is being stopped by hand, if it's still going
+    ///
+    /// Called before stopping it, and never for the car being stopped
+    /// automatically.
+    fn ask_stop_reason(&mut self) {
+        if self.run_data.running {
+            self.gui_data.stop_reason_prompt = Some(StopReasonPrompt::default());
+        }
+    }
+
+    /// Keep why the run was stopped with it, wherever it is by now
+    fn give_stop_reason(&mut self, reason: StopReason) {
+        self.journal
+            .record(JournalEntryKind::Action(UserAction::GiveStopReason(
+                reason.clone(),
+            )));
+        let run = match &self.gui_data.stop_reason_prompt {
+            Some(prompt) => prompt.run,
+            None => StoppedRun::Current,
+        };
+        match run {
+            StoppedRun::Current => self.run_data.stop_reason = Some(reason),
+            StoppedRun::Archived(idx) => {
+                if let Some(archived) = self.run_history.runs.get_mut(idx) {
+                    archived.stop_reason = Some(reason);
+                }
+            }
+            StoppedRun::Gone => (),
+        }
+    }
+
+    /// Have the car calibrate its wheel against the last run, measured to
+    /// have gone a distance in centimeters
+    fn calibrate(&mut self, distance: f64) {
+        self.journal
+            .record(JournalEntryKind::Action(UserAction::Calibrate(distance)));
+        self.send(Command::Calibrate, CalibrateArguments { distance })
+            .unwrap_or_else(|e| self.errors.push(e.into()));
+    }
+
+    /// Try to get past an error from the server, see [`Recovery`]
+    fn recover(&mut self, recovery: Recovery) {
+        self.journal
+            .record(JournalEntryKind::Action(UserAction::Recover(recovery)));
+        use Recovery::*;
+        match recovery {
+            // Sent in order, so the car has stopped by the time the start
+            // goes out on the next frame
+            StopAndStartAgain => self.send(Command::Stop, StopArguments {}).map(|_| {
+                self.run_data.running = true;
+                self.gui_data.current_job = ClientStatus::RequestingStart;
+            }),
+            StartAgain => {
+                self.run_data.running = true;
+                self.gui_data.current_job = ClientStatus::RequestingStart;
+                Ok(())
+            }
+            StopAgain => self.send(Command::Stop, StopArguments {}),
+            GiveUpOnRun => {
+                self.run_data.running = false;
+                self.last_status_time = None;
+                self.gui_data.current_job = ClientStatus::Finished;
+                Ok(())
+            }
+            RequestStaticStatus => self.send(Command::StaticStatus, StaticStatusArguments {}),
+            PingAgain => self.send(
+                Command::Ping,
+                PingArguments::builder().time(unix_now()).build(),
+            ),
+            ReconnectBluetooth => self
+                .send(Command::Disconnect, BluetoothDisconnectRequest {})
+                .and_then(|_| {
+                    self.send(
+                        Command::Connect,
+                        BluetoothConnectRequest {
+                            address: self.gui_data.bluetooth_address.clone(),
+                        },
+                    )
+                })
+                .map(|_| {
+                    self.run_data.bluetooth_connect_progress = None;
+                    self.gui_data.show_connect_progress_window = true;
+                }),
+        }
+        .unwrap_or_else(|e| self.errors.push(e.into()));
+        self.journal.observe_job(self.gui_data.current_job);
+    }
+
+    /// The run to compare splits against: the baseline, or the last saved run
+    /// if there isn't one
+    fn comparison_run(&self) -> &[Event<StatusResponse>] {
+        match &self.baseline {
+            Some(baseline) => &baseline.statuses,
+            None => &self.previous_run,
+        }
+    }
+
+    /// How the run is written to CSV tables: in the units shown, and with the
+    /// target it was started with
+    fn table_options(&self) -> TableOptions {
+        TableOptions {
+            units: self.config.screen.units,
+            target: self
+                .run_data
+                .parameters
+                .as_ref()
+                .map(|parameters| parameters.distance),
+        }
+    }
+
+    /// The wheel circumference in centimeters: the measured one if it's
+    /// overridden, or else the car's calibrated one, or else going by its
+    /// wheel diameter
+    fn wheel_circumference(&self) -> Option<f64> {
+        match self.gui_data.override_wheel_circumference {
+            true => Some(self.gui_data.wheel_circumference),
+            false => self
+                .run_data
+                .static_status_response
+                .as_ref()
+                .map(|static_status| {
+                    static_status
+                        .value
+                        .wheel_circumference
+                        .unwrap_or(static_status.value.wheel_diameter * PI)
+                }),
+        }
+    }
+
+    /// The distance and velocity at every status worked out from the magnet
+    /// hits, if recomputing them is on and the car's wheel is known
+    fn recomputed_statuses(&self) -> Vec<Recomputed> {
+        let number_of_magnets = self
+            .run_data
+            .static_status_response
+            .as_ref()
+            .map(|static_status| static_status.value.number_of_magnets);
+        match (
+            self.gui_data.recompute_distance,
+            number_of_magnets,
+            self.wheel_circumference(),
+        ) {
+            (true, Some(number_of_magnets), Some(circumference)) => recompute(
+                &self.run_data.status_responses,
+                &Wheel {
+                    number_of_magnets,
+                    circumference,
+                },
+            ),
+            _ => vec![],
+        }
+    }
+
+    /// Load the most recently saved run from the data directory to compare
+    /// split times against, if there is one
+    fn load_previous_run(&mut self) {
+        let previous_run = match self
+            .data_directory
+            .as_ref()
+            .and_then(|data_directory| data_directory.runs().pop())
+        {
+            Some(previous_run) => previous_run,
+            None => return,
+        };
+        match self.run_cache.statuses(&previous_run) {
+            Ok(statuses) => self.previous_run = statuses,
+            Err(e) => self.errors.push(ErrorData::new(ClientError::CSV(format!(
+                "Failed to load {} to compare splits against: {e}",
+                previous_run.display()
+            )))),
+        }
+    }
+
+    fn show_split_table(&self, ui: &mut Ui) {
+        let units = self.config.screen.units;
+        let splits = split_times(
+            &self.run_data.status_responses,
+            self.gui_data.split_interval,
+        );
+        let deltas = split_deltas(
+            &splits,
+            &split_times(self.comparison_run(), self.gui_data.split_interval),
+        );
+        if splits.is_empty() {
+            ui.label("No splits yet");
+            return;
+        }
+
+        let split_table = TableBuilder::new(ui)
+            .striped(true)
+            .resizable(false)
+            .cell_layout(Layout::left_to_right(Align::Center))
+            .column(Column::auto()) // Marker
+            .column(Column::auto()) // Time
+            .column(Column::auto()) // Speed
+            .column(Column::auto()) // Versus the previous run
+            .min_scrolled_height(0.0);
+
+        split_table
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.strong("Marker");
+                });
+                header.col(|ui| {
+                    ui.strong("Time");
+                });
+                header.col(|ui| {
+                    ui.strong("Speed");
+                });
+                header.col(|ui| {
+                    ui.strong(match self.baseline.is_some() {
+                        true => "Vs. baseline",
+                        false => "Vs. last run",
+                    });
+                });
+            })
+            .body(|mut body| {
+                for (idx, split) in splits.iter().enumerate() {
+                    body.row(18.0, |mut row| {
+                        row.col(|ui| {
+                            ui.label(units.format_distance(split.marker, 0));
+                        });
+                        row.col(|ui| {
+                            ui.label(format!("{:.2}s", split.time));
+                        });
+                        row.col(|ui| {
+                            ui.label(units.format_velocity(split.speed, 3));
+                        });
+                        row.col(|ui| {
+                            ui.label(
+                                deltas
+                                    .get(idx)
+                                    .map(|delta| format!("{delta:+.2}s"))
+                                    .unwrap_or_else(|| "-".to_owned()),
+                            );
+                        });
+                    });
+                }
+            });
+    }
+
+    fn show_status_table(&self, ui: &mut Ui) {
+        let units = self.config.screen.units;
+        let status_table = TableBuilder::new(ui)
+            .striped(true)
+            .resizable(false)
+            .cell_layout(Layout::left_to_right(Align::Center))
+            .column(Column::auto()) // Runtime
+            .column(Column::auto()) // Distance
+            .column(Column::auto()) // Velocity
+            .column(Column::auto()) // Magnet odometer hits
+            .column(Column::auto()) // Stage
+            .min_scrolled_height(0.0);
+
+        status_table
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.strong("Runtime");
+                });
+                header.col(|ui| {
+                    ui.strong("Distance");
+                });
+                header.col(|ui| {
+                    ui.strong("Speed");
+                });
+                header.col(|ui| {
+                    ui.strong("Spins");
+                });
+                header.col(|ui| {
+                    ui.strong("Status");
+                });
+            })
+            .body(|body| {
+                let snapshot = self.shared_run_data.load();
+                // Only lined up with the snapshot if no statuses came in since
+                let recomputed =
+                    match snapshot.statuses_revision == self.run_data.status_responses.revision() {
+                        true => self.recomputed_statuses(),
+                        false => vec![],
+                    };
+                let clock_offset = self
+                    .run_data
+                    .ping_times()
+                    .map(|ping| ping.clock_offset())
+                    .unwrap_or(0.0);
+                let timeline = timeline(
+                    &snapshot.statuses,
+                    &self.run_data.sent_commands,
+                    clock_offset,
+                );
+                let rows = match self.config.screen.table_rows {
+                    0 => timeline.len(),
+                    rows => rows,
+                };
+                let timeline = timeline.into_iter().rev().take(rows).collect::<Vec<_>>();
+                // Only the rows scrolled to are shown, however long the run
+                body.rows(18.0, timeline.len(), |row_idx, mut row| {
+                    let idx = match &timeline[row_idx] {
+                        TimelineEntry::Status(idx) => *idx,
+                        // Commands sent are shown between the statuses around them
+                        TimelineEntry::Sent(sent) => {
+                            row.col(|ui| {
+                                ui.weak(
+                                    self.config.time.timezone.format_unix(sent.time, "%H:%M:%S"),
+                                );
+                            });
+                            row.col(|ui| {
+                                let again = match sent.retry {
+                                    true => " (again)",
+                                    false => "",
+                                };
+                                ui.colored_label(
+                                    Color32::LIGHT_BLUE,
+                                    format!("> {}{again}", sent.command),
+                                )
+                                .on_hover_text(&sent.arguments);
+                            });
+                            return;
+                        }
+                    };
+                    let status = &snapshot.statuses[idx];
+                    let recomputed = recomputed.get(idx);
+                    row.col(|ui| {
+                        ui.label(format!("{}", status.value.runtime));
+                    });
+                    row.col(|ui| {
+                        let distance = units.format_distance(status.value.distance.distance, 3);
+                        match recomputed {
+                            Some(recomputed) => ui.colored_label(
+                                discrepancy_color(recomputed.distance_discrepancy),
+                                format!(
+                                    "{distance} ({})",
+                                    units.format_distance(recomputed.distance, 3)
+                                ),
+                            ),
+                            None => ui.label(distance),
+                        };
+                    });
+                    row.col(|ui| {
+                        let velocity = units.format_velocity(status.value.distance.velocity, 3);
+                        match recomputed {
+                            Some(recomputed) => ui.colored_label(
+                                discrepancy_color(recomputed.velocity_discrepancy),
+                                format!(
+                                    "{velocity} ({})",
+                                    units.format_velocity(recomputed.velocity, 3)
+                                ),
+                            ),
+                            None => ui.label(velocity),
+                        };
+                    });
+                    row.col(|ui| {
+                        ui.label(format!("{}", status.value.distance.magnet_hit_counter));
+                    });
+                    row.col(|ui| {
+                        ui.label(format!("{}", status.value.stage));
+                    });
+                });
+            });
+    }
+}
+#[cfg(feature = "ui")]
+impl ClientGUI {
+    /// Run the logic and show everything for one frame
+    ///
+    /// This is all [`App::update`] does, so it can be driven without a window
+    pub fn show(&mut self, ctx: &Context) {
+        self.logic();
+        let snapshot = self.shared_run_data.publish(&self.run_data);
+        apply_appearance(
+            ctx,
+            self.config.screen.theme,
+            self.config.screen.layout,
+            vec2(self.config.screen.width, self.config.screen.height),
+        );
+
+        // E-STOP from the keyboard, with space left for typing
+        let emergency_stop = ctx.input(|input| input.key_pressed(Key::Escape))
+            || (ctx.memory(|memory| memory.focus().is_none())
+                && ctx.input(|input| input.key_pressed(Key::Space)));
+        if emergency_stop {
+            self.ask_stop_reason();
+            self.emergency_stop();
+        }
+
+        // Handle file dialog if needed
+        if let Some(dialog) = &mut self.gui_data.file_dialog {
+            if dialog.show(ctx).selected() {
+                if let Some(file) = dialog.path() {
+                    self.gui_data.set_file_path(file);
+                }
+            }
+        }
+        if let Some(mut picker) = self.gui_data.file_picker.take() {
+            match picker.show(ctx, &self.gui_data.recent_files, &mut self.run_cache) {
+                Some(file) if picker.mode == FilePickerMode::Merge => {
+                    match self.run_cache.statuses(&file) {
+                        Ok(server_table) => match self.run_data.status_responses.full() {
+                            Ok(statuses) => {
+                                self.run_data
+                                    .status_responses
+                                    .replace(merge_status_tables(statuses, server_table));
+                                self.run_data.saved = false;
+                            }
+                            Err(e) => self.errors.push(e.into()),
+                        },
+                        Err(e) => self
+                            .errors
+                            .push(ErrorData::new(ClientError::CSV(e.to_string()))),
+                    }
+                }
+                Some(file) if picker.mode == FilePickerMode::Settings => {
+                    match SettingsImport::read(file, &self.settings()) {
+                        Ok(import) => self.gui_data.settings_import = Some(import),
+                        Err(e) => self.errors.push(e.into()),
+                    }
+                }
+                Some(file) if picker.mode == FilePickerMode::Baseline => {
+                    match self.run_cache.statuses(&file) {
+                        Ok(statuses) => {
+                            self.baseline = Some(Baseline {
+                                path: file,
+                                statuses,
+                            })
+                        }
+                        Err(e) => self
+                            .errors
+                            .push(ErrorData::new(ClientError::CSV(e.to_string()))),
+                    }
+                }
+                Some(file) => self.gui_data.set_file_path(file),
+                None => (),
+            }
+            if picker.open {
+                self.gui_data.file_picker = Some(picker);
+            }
+        }
+
+        // Show what's wrong with the computer
+        if !self.startup_problems.is_empty() {
+            self.show_startup_problems(ctx);
+        }
+
+        // Show error messages, only counted in the banner mid run unless
+        // they're asked for
+        if self.errors.is_empty() {
+            self.gui_data.show_error_drawer = false;
+        } else if !self.run_data.running || self.gui_data.show_error_drawer {
+            self.show_error_messages(ctx);
+        }
+
+        // Show expanded status table
+        if self.gui_data.expanded_status_table {
+            Window::new("Status table")
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        let retract_button_size = [60., 20.];
+                        if ui
+                            .add_sized(retract_button_size, Button::new("Retract"))
+                            .clicked()
+                        {
+                            self.gui_data.expanded_status_table = false;
+                        }
+                        // Combine with the server's log of the run
+                        if !self.run_data.running
+                            && !self.run_data.status_responses.is_empty()
+                            && ui
+                                .add_sized(retract_button_size, Button::new("Merge"))
+                                .clicked()
+                        {
+                            self.gui_data.file_picker = Some(FilePicker::new(
+                                FilePickerMode::Merge,
+                                self.file_picker_directory(),
+                            ));
+                        }
+                        if ui
+                            .add_sized(retract_button_size, Button::new("Baseline"))
+                            .clicked()
+                        {
+                            self.gui_data.file_picker = Some(FilePicker::new(
+                                FilePickerMode::Baseline,
+                                self.file_picker_directory(),
+                            ));
+                        }
+                        if ui
+                            .add_sized(retract_button_size, Button::new("Plot"))
+                            .clicked()
+                        {
+                            self.gui_data.telemetry_plot = Some(TelemetryPlot::new());
+                        }
+                        if ui
+                            .add_sized(retract_button_size, Button::new("Narrative"))
+                            .on_hover_text("Save what was sent and received, in order")
+                            .clicked()
+                        {
+                            match self.export_narrative() {
+                                Ok(file_path) => {
+                                    self.gui_data.toast =
+                                        Some(Toast::new(format!("Saved {}", file_path.display())))
+                                }
+                                Err(e) => self.errors.push(e.into()),
+                            }
+                        }
+                        ui.add(
+                            DragValue::new(&mut self.gui_data.split_interval)
+                                .clamp_range(MIN_SPLIT_INTERVAL..=self.config.run.max_distance)
+                                .prefix("Splits every ")
+                                .suffix("cm"),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(
+                            &mut self.gui_data.override_wheel_circumference,
+                            "Measured wheel",
+                        );
+                        ui.add_enabled(
+                            self.gui_data.override_wheel_circumference,
+                            DragValue::new(&mut self.gui_data.wheel_circumference)
+                                .clamp_range(MIN_WHEEL_CIRCUMFERENCE..=MAX_WHEEL_CIRCUMFERENCE)
+                                .speed(0.1)
+                                .max_decimals(2)
+                                .suffix("cm around"),
+                        );
+                        ui.checkbox(&mut self.gui_data.recompute_distance, "Recompute")
+                            .on_hover_text(
+                                "Work out the distance and speed from the magnet hits too, \
+                                 shown in brackets and red where the server's are off",
+                            );
+                        if ui
+                            .button("Calibrate")
+                            .on_hover_text("Have the car count with a measured run from now on")
+                            .clicked()
+                        {
+                            self.gui_data.calibration_window = Some(CalibrationWindow::default());
+                        }
+                    });
+                    if let Some(baseline) = &self.baseline {
+                        let mut clear = false;
+                        ui.horizontal(|ui| {
+                            ui.label(format!(
+                                "Baseline: {}",
+                                baseline
+                                    .path
+                                    .file_name()
+                                    .map(|name| name.to_string_lossy().to_string())
+                                    .unwrap_or_default()
+                            ));
+                            clear = ui.button("Clear").clicked();
+                        });
+                        if clear {
+                            self.baseline = None;
+                        }
+                    }
+                    ui.push_id("status table", |ui| self.show_status_table(ui));
+                    ui.separator();
+                    ui.push_id("split table", |ui| self.show_split_table(ui));
+                });
+        }
+
+        // Show connection window
+        if self.gui_data.show_bluetooth_connect_screen {
+            self.show_bluetooth_connect_screen(ctx);
+        }
+
+        // Show how connecting is going
+        if self.gui_data.show_connect_progress_window {
+            self.show_connect_progress_window(ctx);
+        }
+
+        // Show bridge window
+        #[cfg(feature = "bluetooth")]
+        if self.gui_data.show_bridge_window {
+            self.show_bridge_window(ctx);
+        }
+
+        // Show diagnostics window
+        if self.gui_data.show_diagnostics_window {
+            self.show_diagnostics_window(ctx);
+        }
+
+        // Show settings window
+        if self.gui_data.show_settings_window {
+            self.show_settings_window(ctx);
+        }
+        if self.gui_data.show_preset_window {
+            self.show_preset_window(ctx);
+        }
+        self.show_arming_window(ctx);
+        if let Some(prompt) = &mut self.gui_data.stop_reason_prompt {
+            let reason = prompt.show(ctx);
+            let open = prompt.open;
+            if let Some(reason) = reason {
+                self.give_stop_reason(reason);
+            }
+            if !open {
+                self.gui_data.stop_reason_prompt = None;
+            }
+        }
+        if let Some(window) = &mut self.gui_data.calibration_window {
+            let counted = self
+                .run_data
+                .status_responses
+                .last()
+                .map(|status| status.value.distance.distance);
+            let measured = window.show(
+                ctx,
+                counted,
+                self.run_data.running,
+                self.run_data.calibration.as_ref(),
+            );
+            let open = window.open;
+            if let Some(distance) = measured {
+                self.calibrate(distance);
+            }
+            if !open {
+                self.gui_data.calibration_window = None;
+            }
+        }
+        self.show_saving_window(ctx);
+        self.show_toast(ctx);
+        if !self.gui_data.service_notices.is_empty() {
+            self.show_service_notices(ctx);
+        }
+        if !self.gui_data.whats_new.releases.is_empty() {
+            self.show_whats_new(ctx);
+        }
+
+        // Show the debug menu
+        #[cfg(feature = "debug-menu")]
+        if self.gui_data.show_debug_window {
+            self.show_debug_window(ctx);
+        }
+
+        // Show the journal, the recorded one if looking at a diagnostic bundle
+        if let Some(viewer) = &mut self.gui_data.journal_viewer {
+            let journal = match &self.diagnostic_bundle {
+                Some(bundle) => &bundle.journal,
+                None => &self.journal,
+            };
+            viewer.show(ctx, journal, &self.config.time.timezone);
+            if !viewer.open {
+                self.gui_data.journal_viewer = None;
+            }
+        }
+
+        // Show a previous session
+        if let Some(viewer) = &mut self.gui_data.session_log_viewer {
+            viewer.show(ctx, &self.config.time.timezone);
+            if !viewer.open {
+                self.gui_data.session_log_viewer = None;
+            }
+        }
+
+        // Show the telemetry plot
+        let estimate = self.position_estimate();
+        if let Some(plot) = &mut self.gui_data.telemetry_plot {
+            let edit = plot.show(
+                ctx,
+                &snapshot.statuses,
+                &snapshot.annotations,
+                estimate.as_ref(),
+            );
+            if !plot.open {
+                self.gui_data.telemetry_plot = None;
+            }
+            if let Some(edit) = edit {
+                self.annotate(edit);
+            }
+        }
+
+        // Show the runs being compared
+        self.run_history.show_comparison(ctx);
+
+        // Only the essentials, as big as they go, or everything
+        match self.config.screen.layout {
+            ScreenLayout::Operator => self.show_operator_layout(ctx),
+            ScreenLayout::Standard | ScreenLayout::Kiosk => self.show_detailed_layout(ctx),
+        }
+
+        // Catch the jobs changed by buttons
+        self.journal.observe_job(self.gui_data.current_job);
+
+        // Stop logging the session instead of failing every frame from now on
+        if let Some(session_log) = &mut self.session_log {
+            if let Err(e) = session_log.observe(
+                &self.errors,
+                &self.journal,
+                self.serial_event_propagator.transport.is_some(),
+                self.run_data.bluetooth_bridge_connected,
+            ) {
+                self.session_log = None;
+                self.errors.push(ErrorData::new(e));
+            }
+        }
+
+        ctx.request_repaint();
+    }
+
+    /// Show everything, with the route planner on the left and the run on
+    /// the right
+    fn show_detailed_layout(&mut self, ctx: &Context) {
+        let units = self.config.screen.units;
+        // Bigger than the configured screen when the window's a different
+        // shape, e.g. on a laptop
+        let screen_size = ctx.screen_rect().size();
+
+        TopBottomPanel::top("banner")
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal_centered(|ui| {
+                    ui.heading("CHARGE Dynamics' EC1B-Horme Route Planner");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let e_stop =
+                            Button::new(RichText::new("E-STOP").strong().color(Color32::WHITE))
+                                .fill(Color32::RED);
+                        if ui
+                            .add(e_stop)
+                            .on_hover_text("Stop the car right now (Esc or Space)")
+                            .clicked()
+                        {
+                            self.ask_stop_reason();
+                            self.emergency_stop();
+                        }
+                        // Counted rather than covering the screen
+                        for (subsystem, count) in error_counts(&self.errors).into_iter().rev() {
+                            let badge = Button::new(
+                                RichText::new(format!("{subsystem} {count}"))
+                                    .small()
+                                    .color(Color32::WHITE),
+                            )
+                            .fill(Color32::DARK_RED);
+                            if ui
+                                .add(badge)
+                                .on_hover_text(format!("Show the {subsystem} errors"))
+                                .clicked()
+                            {
+                                self.gui_data.show_error_drawer = true;
+                                self.gui_data.error_filter = Some(subsystem);
+                            }
+                        }
+                        // Up here so it's seen before the run disappears
+                        if let Some(finalized_time) = self.finalized_time {
+                            if ui
+                                .button("Keep")
+                                .on_hover_text("Keep the run up until RESET is pressed")
+                                .clicked()
+                            {
+                                self.finalized_time = None;
+                            }
+                            let remaining =
+                                self.config.run.auto_reset - finalized_time.elapsed().as_secs_f64();
+                            ui.label(format!("Resetting in {:.0}s", remaining.max(0.0).ceil()));
+                        }
+                    });
+                });
+            });
+        // Under both side panels
+        self.show_log_panel(ctx);
+        SidePanel::left("route-planner")
+            .resizable(false)
+            .exact_width(150.0)
+            .show(ctx, |ui| {
+                ui.heading("Plan your route");
+
+                /* Distance input */
+
+                ui.push_id("distance input", |ui| {
+                    if self.run_data.running {
+                        ui.set_enabled(false);
+                    }
+
+                    let distance: f64 = self.gui_data.distance;
+                    ui.separator();
+                    // The slider says what the distance is in, so the presets
+                    // fit on the small screen
+                    ui.horizontal(|ui| {
+                        let mut chosen = None;
+                        ComboBox::from_id_source("preset")
+                            .width(80.0)
+                            .selected_text(match &self.gui_data.selected_preset {
+                                Some(name) => name.as_str(),
+                                None => "Presets",
+                            })
+                            .show_ui(ui, |ui| {
+                                for preset in self.gui_data.presets.presets.iter() {
+                                    if ui.selectable_label(false, &preset.name).clicked() {
+                                        chosen = Some(preset.clone());
+                                    }
+                                }
+                            });
+                        if let Some(preset) = chosen {
+                            preset.apply(&mut self.gui_data);
+                            self.gui_data.selected_preset = Some(preset.name);
+                        }
+
+                        if ui.button("💾").on_hover_text("Save as a preset").clicked() {
+                            self.gui_data.preset_name =
+                                self.gui_data.selected_preset.clone().unwrap_or_default();
+                            self.gui_data.show_preset_window = true;
+                        }
+                        if ui
+                            .add_enabled(self.gui_data.selected_preset.is_some(), Button::new("🗑"))
+                            .on_hover_text("Delete this preset")
+                            .clicked()
+                        {
+                            if let Some(name) = self.gui_data.selected_preset.take() {
+                                self.gui_data
+                                    .presets
+                                    .delete(&name)
+                                    .unwrap_or_else(|e| self.errors.push(e.into()));
+                            }
+                        }
+                    });
+                    let max_distance = match distance > self.config.run.max_distance {
+                        true => distance,
+                        false => self.config.run.max_distance,
+                    };
+                    ui.add(
+                        Slider::from_get_set(0.0..=units.from_centimeters(max_distance), |value| {
+                            if let Some(value) = value {
+                                self.gui_data.distance = units.to_centimeters(value);
+                            }
+                            units.from_centimeters(self.gui_data.distance)
+                        })
+                        .suffix(units.distance_unit()),
+                    );
+                    // Increment buttons, stepping in whatever units are shown
+                    let increment_button_size = [70., 60.];
+                    let (small_step, large_step) =
+                        (units.to_centimeters(10.0), units.to_centimeters(100.0));
+                    // This is a slightly strange way of layout out items *vertically*
+                    // by using two horizontals... but whatever!
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_sized(increment_button_size, Button::new("-10"))
+                            .clicked()
+                        {
+                            if self.gui_data.distance < small_step {
+                                self.gui_data.distance = 0.0;
+                            } else {
+                                self.gui_data.distance -= small_step;
+                            }
+                        }
+                        if ui
+                            .add_sized(increment_button_size, Button::new("+10"))
+                            .clicked()
+                        {
+                            self.gui_data.distance += small_step;
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_sized(increment_button_size, Button::new("-100"))
+                            .clicked()
+                        {
+                            if self.gui_data.distance < large_step {
+                                self.gui_data.distance = 0.0;
+                            } else {
+                                self.gui_data.distance -= large_step;
+                            }
+                        }
+                        if ui
+                            .add_sized(increment_button_size, Button::new("+100"))
+                            .clicked()
+                        {
+                            self.gui_data.distance += large_step;
+                        }
+                    });
+                });
+
+                /* Automatic stop */
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.add(Checkbox::new(&mut self.gui_data.auto_stop, "Auto stop"));
+                    ui.add_enabled(
+                        self.gui_data.auto_stop,
+                        DragValue::new(&mut self.gui_data.auto_stop_percentage)
+                            .clamp_range(100.0..=200.0)
+                            .suffix("%"),
+                    );
+                });
+
+                /* Speed limit */
+
+                ui.horizontal(|ui| {
+                    ui.add(Checkbox::new(&mut self.gui_data.speed_limit, "Limit"));
+                    ui.add_enabled(
+                        self.gui_data.speed_limit,
+                        DragValue::from_get_set(|value| {
+                            if let Some(value) = value {
+                                self.gui_data.max_velocity = units.to_centimeters(value);
+                            }
+                            units.from_centimeters(self.gui_data.max_velocity)
+                        })
+                        .clamp_range(
+                            units.from_centimeters(MIN_MAX_VELOCITY)
+                                ..=units.from_centimeters(MAX_MAX_VELOCITY),
+                        )
+                        .suffix(units.velocity_unit()),
+                    );
+                });
+
+                /* Reverse motor braking */
+
+                // FIXME: The server doesn't do anything with this information; it's literally useless
+                // ui.add(Checkbox::new(
+                //     &mut self.gui_data.reverse_braking,
+                //     "Reverse motor braking",
+                // ));
+
+                /* Large control button */
+
+                ui.separator();
+                let large_button_size = [150.0, 50.0];
+                let large_button = self.large_button();
+                if ui
+                    .add_sized(large_button_size, Button::new(large_button.to_string()))
+                    .clicked()
+                {
+                    self.press(large_button);
+                };
+
+                /* Bluetooth control panel */
+
+                ui.separator();
+                let bluetooth_control_button_size = [150., 20.];
+                if ui
+                    .add_sized(bluetooth_control_button_size, Button::new("Connection"))
+                    .clicked()
+                {
+                    self.gui_data.show_bluetooth_connect_screen = true;
+                }
+                #[cfg(feature = "bluetooth")]
+                if self.bridge.is_some()
+                    && ui
+                        .add_sized(bluetooth_control_button_size, Button::new("Bridge"))
+                        .clicked()
+                {
+                    self.gui_data.show_bridge_window = true;
+                }
+                if (self.diagnostic_bundle.is_some() || !self.round_trips.is_empty())
+                    && ui
+                        .add_sized(bluetooth_control_button_size, Button::new("Diagnostics"))
+                        .clicked()
+                {
+                    self.gui_data.show_diagnostics_window = true;
+                }
+                if ui
+                    .add_sized(bluetooth_control_button_size, Button::new("Journal"))
+                    .clicked()
+                {
+                    let journal = match &self.diagnostic_bundle {
+                        Some(bundle) => &bundle.journal,
+                        None => &self.journal,
+                    };
+                    self.gui_data.journal_viewer = Some(JournalViewer::new(journal));
+                }
+                if let Some(data_directory) = &self.data_directory {
+                    if ui
+                        .add_sized(
+                            bluetooth_control_button_size,
+                            Button::new("Previous session"),
+                        )
+                        .clicked()
+                    {
+                        self.gui_data.session_log_viewer = Some(SessionLogViewer::new(
+                            &data_directory.session_log_directory(),
+                            self.session_log
+                                .as_ref()
+                                .map(|session_log| session_log.path.as_path()),
+                        ));
+                    }
+                }
+                if ui
+                    .add_sized(bluetooth_control_button_size, Button::new("Settings"))
+                    .clicked()
+                {
+                    self.gui_data.show_settings_window = true;
+                }
+                if ui
+                    .add_sized(bluetooth_control_button_size, Button::new("Runs"))
+                    .clicked()
+                {
+                    self.gui_data.show_run_history = !self.gui_data.show_run_history;
+                }
+                if ui
+                    .add_sized(bluetooth_control_button_size, Button::new("Logs"))
+                    .clicked()
+                {
+                    self.gui_data.show_logs = !self.gui_data.show_logs;
+                }
+                #[cfg(feature = "debug-menu")]
+                if self.gui_data.dev
+                    && ui
+                        .add_sized(bluetooth_control_button_size, Button::new("Debug"))
+                        .clicked()
+                {
+                    self.gui_data.show_debug_window = true;
+                }
+            });
+        // Squeezes the status panel rather than covering it
+        let run_history_width = match self.gui_data.show_run_history {
+            true => 150.0,
+            false => 0.0,
+        };
+        if self.gui_data.show_run_history {
+            SidePanel::right("run-history")
+                .exact_width(run_history_width)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    self.run_history
+                        .show_panel(ui, &self.config.time.timezone, units)
+                });
+        }
+        SidePanel::right("status")
+            .exact_width(screen_size.x - 150.0 - run_history_width)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Information");
+                    if ui
+                        .button("Operator")
+                        .on_hover_text("Only the essentials, as big as they go")
+                        .clicked()
+                    {
+                        self.switch_layout(ScreenLayout::Operator);
+                    }
+                });
+
+                /* Connection */
+
+                if let Some(connection) = &self.serial_event_propagator.connection {
+                    match &connection.state {
+                        ConnectionState::Connected { port } => {
+                            ui.colored_label(Color32::GREEN, format!("Connected to {port}"))
+                        }
+                        ConnectionState::Reconnecting { attempts, reason } => ui
+                            .colored_label(
+                                Color32::YELLOW,
+                                format!("Reconnecting (attempt {})", attempts + 1),
+                            )
+                            .on_hover_text(reason.as_deref().unwrap_or("Not connected yet")),
+                    };
+                }
+                // Only ever more than nothing on a slow link
+                let queue_depth = self.serial_event_propagator.queue_depth();
+                if queue_depth > 0 {
+                    ui.colored_label(
+                        Color32::YELLOW,
+                        format!("{queue_depth} frames waiting to be sent"),
+                    );
+                }
+                if let Some(estimate) = self.position_estimate() {
+                    show_position_estimate(ui, &estimate, units);
+                }
+                if let Some(replay) = &self.wire_log_replay {
+                    ui.colored_label(
+                        Color32::YELLOW,
+                        format!(
+                            "Replaying {} ({} frames left)",
+                            replay
+                                .path
+                                .file_name()
+                                .unwrap_or_default()
+                                .to_string_lossy(),
+                            replay.remaining()
+                        ),
+                    );
+                }
+
+                /* Current job */
+
+                let error_times = self
+                    .errors
+                    .iter()
+                    .map(|error| error.time.timestamp_millis() as f64 / 1000.0)
+                    .collect::<Vec<_>>();
+                StepIndicator::from_journal(self.journal.entries(), &error_times).show(
+                    ui,
+                    self.gui_data.current_job,
+                    unix_now(),
+                );
+
+                /* Ping */
+
+                ui.separator();
+                if let Some((ping_response, got_time)) = &self.run_data.ping_status_response {
+                    ui.label(format!(
+                        "Round-trip latency: {}ms",
+                        (got_time - ping_response.value.sent_time) * 1000.0
+                    ));
+                } else {
+                    ui.label("No ping information available");
+                }
+                if let Some(warning) = clock_warning(
+                    unix_now(),
+                    self.run_data.ping_times(),
+                    self.config.time.max_clock_offset,
+                ) {
+                    ui.colored_label(Color32::RED, warning);
+                }
+
+                /* Static status */
+
+                ui.separator();
+                if let Some(static_status) = &self.run_data.static_status_response {
+                    ui.push_id("static status table", |ui| {
+                        let static_status_table = TableBuilder::new(ui)
+                            .striped(true)
+                            .resizable(false)
+                            .cell_layout(Layout::left_to_right(Align::Center))
+                            .column(Column::auto()) // Number of magnets
+                            .column(Column::auto()) // Wheel diameter
+                            .column(Column::auto()) // Wheel circumference
+                            .min_scrolled_height(0.0);
+
+                        static_status_table
+                            .header(20.0, |mut header| {
+                                header.col(|ui| {
+                                    ui.strong("Magnets");
+                                });
+                                header.col(|ui| {
+                                    ui.strong("Wheel Diameter");
+                                });
+                                header.col(|ui| {
+                                    ui.strong("Circumference");
+                                });
+                            })
+                            .body(|mut body| {
+                                body.row(18.0, |mut row| {
+                                    row.col(|ui| {
+                                        ui.label(format!(
+                                            "{}",
+                                            static_status.value.number_of_magnets
+                                        ));
+                                    });
+                                    row.col(|ui| {
+                                        ui.label(units.format_distance(
+                                            static_status.value.wheel_diameter,
+                                            3,
+                                        ));
+                                    });
+                                    row.col(|ui| {
+                                        let circumference = self
+                                            .wheel_circumference()
+                                            .unwrap_or(static_status.value.wheel_diameter * PI);
+                                        ui.label(format!(
+                                            "{}{}",
+                                            units.format_distance(circumference, 3),
+                                            match (
+                                                self.gui_data.override_wheel_circumference,
+                                                static_status.value.wheel_circumference,
+                                            ) {
+                                                (true, _) => " (measured)",
+                                                (false, Some(_)) => " (calibrated)",
+                                                (false, None) => "",
+                                            }
+                                        ));
+                                    });
+                                });
+                            });
+                    });
+                    if let Some(distance_estimator) = static_status.value.distance_estimator {
+                        ui.label(format!("Distance from: {distance_estimator}"));
+                    }
+                    if self.run_data.static_status_supported == Some(false) {
+                        ui.colored_label(
+                            Color32::YELLOW,
+                            "Assumed from the config, the server didn't send it",
+                        );
+                    }
+                } else {
+                    ui.label("No static status available");
+                }
+
+                /* Dynamic status */
+
+                if let Some(latest_and_greatest_status) = self.run_data.status_responses.last() {
+                    ui.push_id("dynamic status latest and greatest status table", |ui| {
+                        let latest_and_greatest_status_table = TableBuilder::new(ui)
+                            .striped(true)
+                            .resizable(false)
+                            .cell_layout(Layout::left_to_right(Align::Center))
+                            .column(Column::auto()) // Running
+                            .column(Column::auto()) // Uptime
+                            .column(Column::auto()) // Runtime
+                            .column(Column::remainder()) // Last received
+                            .min_scrolled_height(0.0);
+
+                        latest_and_greatest_status_table
+                            .header(20.0, |mut header| {
+                                header.col(|ui| {
+                                    ui.strong("Running");
+                                });
+                                header.col(|ui| {
+                                    ui.strong("Uptime");
+                                });
+                                header.col(|ui| {
+                                    ui.strong("Runtime");
+                                });
+                                header.col(|ui| {
+                                    ui.strong("Last received");
+                                });
+                            })
+                            .body(|mut body| {
+                                body.row(18.0, |mut row| {
+                                    row.col(|ui| {
+                                        ui.label(match latest_and_greatest_status.value.running {
+                                            true => "YES",
+                                            false => "NO",
+                                        });
+                                    });
+                                    row.col(|ui| {
+                                        ui.label(format!(
+                                            "{}",
+                                            latest_and_greatest_status.value.uptime
+                                        ));
+                                    });
+                                    row.col(|ui| {
+                                        ui.label(format!(
+                                            "{}",
+                                            latest_and_greatest_status.value.runtime
+                                        ));
+                                    });
+                                    row.col(|ui| {
+                                        // By our clock, not the car's
+                                        let clock_offset = self
+                                            .run_data
+                                            .ping_times()
+                                            .map(|ping| ping.clock_offset())
+                                            .unwrap_or_default();
+                                        ui.label(format!(
+                                            " {:.1} seconds ago",
+                                            unix_now()
+                                                - (latest_and_greatest_status.metadata.time
+                                                    - clock_offset)
+                                        ));
+                                    });
+                                });
+                            });
+                    });
+                    let splits = split_times(
+                        &self.run_data.status_responses,
+                        self.gui_data.split_interval,
+                    );
+                    if let Some(split) = splits.last() {
+                        let previous_splits =
+                            split_times(self.comparison_run(), self.gui_data.split_interval);
+                        let delta = split_deltas(&splits, &previous_splits)
+                            .get(splits.len() - 1)
+                            .map(|delta| format!(" ({delta:+.2}s)"))
+                            .unwrap_or_default();
+                        ui.label(format!(
+                            "Split at {}: {:.2}s, {}{delta}",
+                            units.format_distance(split.marker, 0),
+                            split.time,
+                            units.format_velocity(split.speed, 1)
+                        ));
+                    }
+
+                    if let Some(delta) = self.baseline.as_ref().and_then(|baseline| {
+                        baseline_delta(&self.run_data.status_responses, &baseline.statuses)
+                    }) {
+                        ui.label(format!(
+                            "{} baseline by {}",
+                            match delta >= 0.0 {
+                                true => "Ahead of",
+                                false => "Behind",
+                            },
+                            units.format_distance(delta.abs(), 1)
+                        ));
+                    }
+
+                    if let Some(recomputed) = self.recomputed_statuses().last() {
+                        if recomputed.distance_discrepancy || recomputed.velocity_discrepancy {
+                            ui.colored_label(
+                                discrepancy_color(true),
+                                format!(
+                                    "The magnets say {} at {}",
+                                    units.format_distance(recomputed.distance, 1),
+                                    units.format_velocity(recomputed.velocity, 1)
+                                ),
+                            );
+                        }
+                    }
+
+                    let violations = latest_and_greatest_status.value.speed_limit_violations;
+                    if violations > 0 {
+                        ui.label(format!(
+                            "Went over the speed limit {violations} time{}",
+                            match violations {
+                                1 => "",
+                                _ => "s",
+                            }
+                        ));
+                    }
+
+                    if let Some(radio_stop) = latest_and_greatest_status.value.radio_stop {
+                        match radio_stop.held {
+                            true => ui.colored_label(
+                                Color32::RED,
+                                "The radio stop is held, so the car won't run",
+                            ),
+                            false => ui.label(format!(
+                                "Radio stop ready, stopped {} run{}",
+                                radio_stop.stops,
+                                match radio_stop.stops {
+                                    1 => "",
+                                    _ => "s",
+                                }
+                            )),
+                        };
+                    }
+
+                    if let Some(power) = latest_and_greatest_status.value.power {
+                        show_power(ui, &power, &self.config.car);
+                        if let Some(warning) = battery_warning(&power, &self.config.car) {
+                            ui.colored_label(Color32::RED, warning);
+                        }
+                    }
+
+                    #[cfg(feature = "analytics")]
+                    if let Some(analytics) = RunAnalytics::of(
+                        &self.run_data.status_responses,
+                        self.table_options().target,
+                    ) {
+                        ui.label(format!(
+                            "Average {}, peak {}",
+                            units.format_velocity(analytics.average_velocity, 1),
+                            units.format_velocity(analytics.peak_velocity, 1)
+                        ));
+                        // Only meaningful while the car is still going
+                        if self.run_data.running {
+                            if let Some(time_to_target) = analytics.time_to_target {
+                                ui.label(format!("{time_to_target:.1}s to the target"));
+                            }
+                            if let Some(overshoot) = analytics.projected_overshoot {
+                                ui.label(format!(
+                                    "Projected to stop {} {} the target",
+                                    units.format_distance(overshoot.abs(), 1),
+                                    match overshoot >= 0.0 {
+                                        true => "past",
+                                        false => "short of",
+                                    }
+                                ));
+                            }
+                        }
+                    }
+                } else {
+                    ui.label("No dynamic status available");
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    /* Expand */
+                    let expand_button_size = [60., 20.];
+                    if ui
+                        .add_sized(expand_button_size, Button::new("Expand"))
+                        .clicked()
+                    {
+                        self.gui_data.expanded_status_table = true;
+                    }
+                    /* Load / save */
+                    ui.push_id("load and save", |ui| {
+                        if self.run_data.running {
+                            ui.set_enabled(false);
+                        }
+
+                        let text_input_size = [70., 20.];
+                        // Room for the format next to saving
+                        let save_button_size = [40., 20.];
+                        let export_format_width = 45.;
+                        ui.push_id("text input disabled", |ui| {
+                            ui.set_enabled(false);
+                            ui.add_sized(
+                                text_input_size,
+                                TextEdit::singleline(&mut self.gui_data.display_file_path),
+                            )
+                        });
+                        let csv_handling_button_size = [60., 20.];
+                        if ui
+                            .add_sized(csv_handling_button_size, Button::new("Browse"))
+                            .clicked()
+                        {
+                            // The generic dialog is nearly unusable on the touch screen
+                            if ui.ctx().screen_rect().width() <= SMALL_SCREEN_WIDTH {
+                                let mut picker = match self.run_data.status_responses.is_empty() {
+                                    true => FilePicker::new(
+                                        FilePickerMode::Open,
+                                        self.file_picker_directory(),
+                                    ),
+                                    // With the suggested name ready to save as
+                                    false => {
+                                        let suggested = self.suggested_save_path();
+                                        let mut picker = FilePicker::new(
+                                            FilePickerMode::Save,
+                                            suggested
+                                                .parent()
+                                                .map(PathBuf::from)
+                                                .unwrap_or_default(),
+                                        );
+                                        picker.new_name = suggested
+                                            .file_stem()
+                                            .map(|stem| stem.to_string_lossy().to_string())
+                                            .unwrap_or_default();
+                                        picker
+                                    }
+                                };
+                                picker.format = self.gui_data.export_format;
+                                self.gui_data.file_picker = Some(picker);
+                            } else {
+                                // `FileDialog::new` is private for some reason...
+                                let mut dialog = match self.run_data.status_responses.is_empty() {
+                                    true => FileDialog::open_file(None),
+                                    false => FileDialog::save_file(None),
+                                }
+                                .default_size(vec2(screen_size.x, screen_size.y - 120.));
+                                dialog.open();
+                                self.gui_data.file_dialog = Some(dialog);
+                            }
+                        }
+                        if self.run_data.status_responses.is_empty() {
+                            if ui
+                                .add_sized(csv_handling_button_size, Button::new("Load status"))
+                                .clicked()
+                            {
+                                match self.run_cache.statuses(
+                                    &(self.gui_data.file_path.clone().unwrap_or_else(|| {
+                                        PathBuf::from(&self.gui_data.display_file_path)
+                                    })),
+                                ) {
+                                    Ok(new_table) => {
+                                        self.run_data.status_responses.replace(new_table);
+                                        self.run_data.saved = true;
+                                        let file_path =
+                                            self.gui_data.file_path.clone().unwrap_or_else(|| {
+                                                PathBuf::from(&self.gui_data.display_file_path)
+                                            });
+                                        match read_annotations(&file_path) {
+                                            Ok(annotations) => {
+                                                self.run_data.annotations = annotations
+                                            }
+                                            Err(e) => self.errors.push(e.into()),
+                                        }
+                                        if let Some(file) = self.gui_data.file_path.clone() {
+                                            self.gui_data.push_recent_file(file);
+                                        }
+                                    }
+                                    Err(e) => {
+                                        self.errors
+                                            .push(ErrorData::new(ClientError::CSV(e.to_string())));
+                                    }
+                                };
+                            }
+                        } else {
+                            if ui
+                                .add_enabled(
+                                    self.save_job.is_none(),
+                                    Button::new("Save").min_size(save_button_size.into()),
+                                )
+                                .clicked()
+                            {
+                                self.save();
+                            }
+                            ComboBox::from_id_source("export format")
+                                .width(export_format_width)
+                                .selected_text(self.gui_data.export_format.to_string())
+                                .show_ui(ui, |ui| {
+                                    for format in RunFormat::ALL {
+                                        ui.selectable_value(
+                                            &mut self.gui_data.export_format,
+                                            *format,
+                                            format.to_string(),
+                                        );
+                                    }
+                                });
+                        }
+                    });
+                });
+
+                if self.gui_data.expanded_status_table {
+                    ui.label("Table rendered elsewhere");
+                    // The table is rendered outside of this current UI in
+                    // the right panel for it to freely move around
+                } else {
+                    ui.push_id("dynamic status history table", |ui| {
+                        self.show_status_table(ui);
+                    });
+                }
+            });
+    }
+
+    /// Show only what's needed to run the car, as big as it goes for gloved
+    /// fingers: the distance presets, how far along the run is, START (or
+    /// whatever it is right now), and E-STOP
+    fn show_operator_layout(&mut self, ctx: &Context) {
+        let units = self.config.screen.units;
+        TopBottomPanel::top("operator-banner")
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_sized(OPERATOR_SMALL_BUTTON_SIZE, Button::new("Details"))
+                        .on_hover_text("Show everything")
+                        .clicked()
+                    {
+                        self.switch_layout(self.gui_data.detailed_layout);
+                    }
+                    if !self.errors.is_empty()
+                        && ui
+                            .add_sized(
+                                OPERATOR_SMALL_BUTTON_SIZE,
+                                Button::new(
+                                    RichText::new(format!("{} errors", self.errors.len()))
+                                        .color(Color32::WHITE),
+                                )
+                                .fill(Color32::DARK_RED),
+                            )
+                            .clicked()
+                    {
+                        self.gui_data.show_error_drawer = true;
+                        self.gui_data.error_filter = None;
+                    }
+                });
+            });
+        CentralPanel::default().show(ctx, |ui| {
+            let width = ui.available_width();
+
+            /* Distance presets */
+
+            let mut chosen = None;
+            ui.add_enabled_ui(!self.run_data.running, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    for preset in self.gui_data.presets.presets.iter() {
+                        let selected =
+                            self.gui_data.selected_preset.as_deref() == Some(&preset.name);
+                        if ui
+                            .add_sized(
+                                OPERATOR_PRESET_BUTTON_SIZE,
+                                SelectableLabel::new(selected, &preset.name),
+                            )
+                            .clicked()
+                        {
+                            chosen = Some(preset.clone());
+                        }
+                    }
+                });
+            });
+            if let Some(preset) = chosen {
+                preset.apply(&mut self.gui_data);
+                self.gui_data.selected_preset = Some(preset.name);
+            }
+
+            /* Progress */
+
+            let distance = self
+                .run_data
+                .status_responses
+                .last()
+                .map(|status| status.value.distance.distance)
+                .unwrap_or_default();
+            let target = self.gui_data.distance;
+            let fraction = match target > 0.0 {
+                true => (distance / target).clamp(0.0, 1.0) as f32,
+                false => 0.0,
+            };
+            ui.add_sized(
+                [width, OPERATOR_PROGRESS_HEIGHT],
+                ProgressBar::new(fraction).text(format!(
+                    "{} of {}",
+                    units.format_distance(distance, 1),
+                    units.format_distance(target, 0)
+                )),
+            );
+            if let Some(estimate) = self.position_estimate() {
+                show_position_estimate(ui, &estimate, units);
+            }
+
+            /* START and E-STOP */
+
+            let large_button = self.large_button();
+            if ui
+                .add_sized(
+                    [width, OPERATOR_BUTTON_HEIGHT],
+                    Button::new(RichText::new(large_button.to_string()).strong()),
+                )
+                .clicked()
+            {
+                self.press(large_button);
+            }
+            let e_stop = Button::new(RichText::new("E-STOP").strong().color(Color32::WHITE))
+                .fill(Color32::RED);
+            if ui
+                .add_sized([width, OPERATOR_BUTTON_HEIGHT], e_stop)
+                .on_hover_text("Stop the car right now (Esc or Space)")
+                .clicked()
+            {
+                self.ask_stop_reason();
+                self.emergency_stop();
+            }
+        });
+    }
+
+    /// Switch between the operator layout and the detailed ones in one tap,
+    /// remembering which detailed one to go back to
+    pub fn switch_layout(&mut self, layout: ScreenLayout) {
+        if self.config.screen.layout != ScreenLayout::Operator {
+            self.gui_data.detailed_layout = self.config.screen.layout;
+        }
+        self.config.screen.layout = layout;
+        self.save_config();
+    }
+
+    /// Where the car probably is, once statuses have stopped coming in mid
+    /// run for [`ESTIMATE_AFTER_SECONDS`]
+    pub fn position_estimate(&self) -> Option<PositionEstimate> {
+        if !self.config.run.estimate_position {
+            return None;
+        }
+        let silent_for = self.last_status_received?.elapsed().as_secs_f64();
+        if silent_for < ESTIMATE_AFTER_SECONDS {
+            return None;
+        }
+        PositionEstimate::after(&self.run_data.status_responses, silent_for)
+    }
+
+    /// What the large button does right now
+    fn large_button(&self) -> LargeButton {
+        match self.run_data.running {
+            false => match self.run_data.ping_status_response.is_none()
+                && self.run_data.status_responses.is_empty()
+            {
+                false => LargeButton::Reset,
+                true => match self.arming {
+                    Arming::Disarmed => LargeButton::Start,
+                    Arming::Armed { .. } => LargeButton::Confirm,
+                },
+            },
+            true => LargeButton::Stop,
+        }
+    }
+
+    fn press(&mut self, large_button: LargeButton) {
+        match large_button {
+            LargeButton::Start => self.arm(),
+            LargeButton::Confirm => self.start(),
+            LargeButton::Reset => self.reset(),
+            LargeButton::Stop => {
+                self.ask_stop_reason();
+                self.stop();
+            }
+        }
+    }
+}
+#[cfg(feature = "ui")]
+impl App for ClientGUI {
+    fn update(&mut self, ctx: &eframe::egui::Context, _frame: &mut eframe::Frame) {
+        self.show(ctx);
+
+        // Only sent while frames are being drawn, so the watchdog restarts the
+        // GUI if it hangs
+        self.systemd.ready();
+        self.systemd.status(&self.gui_data.current_job.to_string());
+        self.systemd.heartbeat();
+    }
+}
+
+/***** Helper functions *****/
+
+#[cfg(feature = "ui")]
+/// Whether a tuned setting changed and whether it's done changing, on top of
+/// the others', so it's only saved once it's let go of
+fn tuning(response: &egui::Response, tuned: bool, save: bool) -> (bool, bool) {
+    (
+        tuned || response.changed(),
+        save || response.drag_released() || (response.changed() && !response.dragged()),
+    )
+}
+
+#[cfg(feature = "ui")]
+/// Red if the server's numbers are off from the recomputed ones
+fn discrepancy_color(discrepancy: bool) -> Color32 {
+    match discrepancy {
+        true => Color32::RED,
+        false => Color32::GRAY,
+    }
+}
+
+#[cfg(feature = "ui")]
+/// Show where the car probably is, marked as a guess so it's never taken for
+/// a status
+fn show_position_estimate(ui: &mut Ui, estimate: &PositionEstimate, units: Units) {
+    let (low, high) = estimate.range();
+    ui.colored_label(
+        Color32::from_rgb(255, 140, 0),
+        format!(
+            "ESTIMATE, no status for {:.1}s: about {} ({} to {}), {}",
+            estimate.silent_for,
+            units.format_distance(estimate.distance, 0),
+            units.format_distance(low, 0),
+            units.format_distance(high, 0),
+            match estimate.stopped {
+                true => "probably stopped",
+                false => "probably still moving",
+            }
+        ),
+    )
+    .on_hover_text("Guessed from the last velocity and deceleration until statuses come back");
+}
+
+#[cfg(feature = "ui")]
+/// A device found by the bridge, as its name, address, and signal strength
+fn device_label(device: &BluetoothDevice) -> String {
+    format!(
+        "{} ({}){}",
+        device.name.as_deref().unwrap_or("Unnamed"),
+        device.address,
+        device
+            .rssi
+            .map(|rssi| format!(" {rssi}dBm"))
+            .unwrap_or_default()
+    )
+}
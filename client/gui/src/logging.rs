@@ -0,0 +1,268 @@
+/*!
+ * Structured logging with `tracing`, printed and also kept in memory so the
+ * most recent events can be read from the logs panel
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    fmt::{self, Write},
+    io::{stderr, IsTerminal},
+    mem::take,
+    sync::{Arc, Mutex},
+};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Level, Subscriber,
+};
+use tracing_subscriber::{
+    filter::LevelFilter, layer::Context, prelude::*, registry::LookupSpan, EnvFilter, Layer,
+};
+
+/***** Levels *****/
+
+/// How much gets logged, or shown in the logs panel
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+impl LogLevel {
+    pub const ALL: [Self; 5] = [
+        Self::Error,
+        Self::Warn,
+        Self::Info,
+        Self::Debug,
+        Self::Trace,
+    ];
+
+    pub fn level(&self) -> Level {
+        match self {
+            Self::Error => Level::ERROR,
+            Self::Warn => Level::WARN,
+            Self::Info => Level::INFO,
+            Self::Debug => Level::DEBUG,
+            Self::Trace => Level::TRACE,
+        }
+    }
+
+    /// Whether an event at some level is at least this severe
+    pub fn shows(&self, level: Level) -> bool {
+        // More verbose levels are greater
+        level <= self.level()
+    }
+}
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Error => "Error",
+                Self::Warn => "Warn",
+                Self::Info => "Info",
+                Self::Debug => "Debug",
+                Self::Trace => "Trace",
+            }
+        )
+    }
+}
+
+/***** Log events *****/
+
+/// Something logged
+#[derive(Clone, PartialEq, Debug)]
+pub struct LogEvent {
+    pub time: DateTime<Utc>,
+    pub level: Level,
+    /// The module it was logged from, e.g. `gui::events`
+    pub target: String,
+    /// The spans it was logged in, outermost first, e.g.
+    /// `request{command=START sequence=3}`
+    pub spans: Vec<String>,
+    /// The message followed by the rest of the fields, e.g.
+    /// `Sent frame=?START$...`
+    pub message: String,
+}
+impl LogEvent {
+    /// The spans and message on one line, e.g.
+    /// `request{command=START sequence=3}: Sent frame=?START$...`
+    pub fn describe(&self) -> String {
+        match self.spans.is_empty() {
+            true => self.message.clone(),
+            false => format!("{}: {}", self.spans.join(":"), self.message),
+        }
+    }
+}
+
+/// The most recent events logged, dropping the oldest after a while
+///
+/// Clones share the same events, so one can be handed to the subscriber and
+/// another kept for the logs panel.
+#[derive(Clone, Debug)]
+pub struct LogBuffer {
+    events: Arc<Mutex<VecDeque<LogEvent>>>,
+    capacity: usize,
+}
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    pub fn push(&self, event: LogEvent) {
+        let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        if events.len() >= self.capacity {
+            events.pop_front();
+        }
+        if self.capacity > 0 {
+            events.push_back(event);
+        }
+    }
+
+    /// Every event kept that is at least as severe as some level, oldest
+    /// first
+    pub fn recent(&self, level: LogLevel) -> Vec<LogEvent> {
+        self.events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|event| level.shows(event.level))
+            .cloned()
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+impl<S> Layer<S> for LogBuffer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attributes: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let mut fields = FieldWriter::default();
+        attributes.record(&mut fields);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanFields(fields.fields));
+        }
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(SpanFields(fields)) = extensions.get_mut::<SpanFields>() {
+            let mut writer = FieldWriter {
+                fields: take(fields),
+                ..Default::default()
+            };
+            values.record(&mut writer);
+            *fields = writer.fields;
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| match span.extensions().get::<SpanFields>() {
+                        Some(SpanFields(fields)) if !fields.is_empty() => {
+                            format!("{}{{{fields}}}", span.name())
+                        }
+                        _ => span.name().to_owned(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut writer = FieldWriter::default();
+        event.record(&mut writer);
+        let message = match (writer.message.is_empty(), writer.fields.is_empty()) {
+            (_, true) => writer.message,
+            (true, false) => writer.fields,
+            (false, false) => format!("{} {}", writer.message, writer.fields),
+        };
+
+        self.push(LogEvent {
+            time: Utc::now(),
+            level: *event.metadata().level(),
+            target: event.metadata().target().to_owned(),
+            spans,
+            message,
+        });
+    }
+}
+
+/***** Setting up *****/
+
+/// Print everything logged at some level or more severe to stderr, and keep
+/// it in a buffer for the logs panel too if there is one
+///
+/// `RUST_LOG` overrides the level, e.g. `RUST_LOG=gui::events=trace`. Only
+/// the first call does anything.
+pub fn init(level: LogLevel, buffer: Option<LogBuffer>) {
+    let filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::from_level(level.level()).into())
+        .from_env_lossy();
+    let printer = tracing_subscriber::fmt::layer()
+        .with_writer(stderr)
+        .with_ansi(stderr().is_terminal());
+    let _ = tracing_subscriber::registry()
+        .with(filter)
+        .with(printer)
+        .with(buffer)
+        .try_init();
+}
+
+/***** Helper functions *****/
+
+/// A span's fields written out, kept with the span
+struct SpanFields(String);
+
+/// Writes out fields as `name=value`, apart from the message
+#[derive(Default)]
+struct FieldWriter {
+    message: String,
+    fields: String,
+}
+impl FieldWriter {
+    fn write_field(&mut self, field: &Field, value: fmt::Arguments) {
+        if field.name() == "message" {
+            let _ = self.message.write_fmt(value);
+            return;
+        }
+        if !self.fields.is_empty() {
+            self.fields.push(' ');
+        }
+        let _ = write!(self.fields, "{}=", field.name());
+        let _ = self.fields.write_fmt(value);
+    }
+}
+impl Visit for FieldWriter {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.write_field(field, format_args!("{value}"));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.write_field(field, format_args!("{value:?}"));
+    }
+}
@@ -0,0 +1,251 @@
+/*!
+ * How much the car has been used across sessions, and reminders to look after
+ * it every so often (e.g. inspecting the wheels every 2km)
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{ClientError, Event, StatusResponse};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{create_dir_all, read_to_string, write},
+    path::{Path, PathBuf},
+};
+
+// Constants
+/// The maintenance file inside of the XDG config directory, next to the config
+pub const MAINTENANCE_FILE_NAME: &str = "chemistry-car-controller/maintenance.toml";
+pub const SECONDS_PER_HOUR: f64 = 3_600.0;
+
+/***** Usage *****/
+
+/// How much the car has been used, added up over runs
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Usage {
+    /// Centimeters
+    pub distance: f64,
+    /// Seconds the motors were running for
+    pub motor_seconds: f64,
+    pub runs: u32,
+}
+impl Usage {
+    /// How much a run used the car, going by its statuses, or nothing if it
+    /// never sent any
+    ///
+    /// The runtime goes back to 0 once the car stops, so the longest one is
+    /// taken, while the distance is kept and the last one is taken.
+    pub fn of_run(statuses: &[Event<StatusResponse>]) -> Self {
+        let Some(last) = statuses.last() else {
+            return Self::default();
+        };
+        Self {
+            distance: last.value.distance.distance.abs(),
+            motor_seconds: statuses
+                .iter()
+                .map(|status| status.value.runtime)
+                .max()
+                .unwrap_or_default() as f64,
+            runs: 1_u32,
+        }
+    }
+
+    pub fn add(&mut self, other: &Self) {
+        self.distance += other.distance;
+        self.motor_seconds += other.motor_seconds;
+        self.runs += other.runs;
+    }
+
+    /// How much more this is than an earlier usage
+    pub fn since(&self, earlier: &Self) -> Self {
+        Self {
+            distance: self.distance - earlier.distance,
+            motor_seconds: self.motor_seconds - earlier.motor_seconds,
+            runs: self.runs.saturating_sub(earlier.runs),
+        }
+    }
+}
+
+/***** Service reminders *****/
+
+/// Something to do to the car every so often, by distance, motor run time, or
+/// runs (whichever comes first), e.g.
+///
+/// ```toml
+/// [[maintenance.reminders]]
+/// task = "Inspect the wheels"
+/// every_distance = 200000.0
+/// ```
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceReminder {
+    /// What to do, which is also what it's kept track of by
+    pub task: String,
+    /// Centimeters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every_distance: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every_motor_hours: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every_runs: Option<u32>,
+}
+impl ServiceReminder {
+    /// Inspecting the wheels every 2km, for when the config doesn't say
+    pub fn defaults() -> Vec<Self> {
+        vec![Self {
+            task: "Inspect the wheels".to_owned(),
+            every_distance: Some(200_000.0),
+            ..Default::default()
+        }]
+    }
+
+    /// Whether it's due after the car has been used this much since it was
+    /// last done
+    pub fn due(&self, since: &Usage) -> bool {
+        self.every_distance
+            .is_some_and(|every| since.distance >= every)
+            || self
+                .every_motor_hours
+                .is_some_and(|every| since.motor_seconds / SECONDS_PER_HOUR >= every)
+            || self.every_runs.is_some_and(|every| since.runs >= every)
+    }
+
+    /// Whether it has anything to go by, and nothing that would make it due
+    /// all the time
+    pub fn valid(&self) -> bool {
+        let intervals = [
+            self.every_distance,
+            self.every_motor_hours,
+            self.every_runs.map(f64::from),
+        ];
+        !self.task.trim().is_empty()
+            && intervals.iter().any(Option::is_some)
+            && intervals
+                .into_iter()
+                .flatten()
+                .all(|every| every > 0.0 && every.is_finite())
+    }
+}
+
+/***** Maintenance *****/
+
+/// What's kept across restarts, e.g.
+///
+/// ```toml
+/// [total]
+/// distance = 123456.7
+/// motor_seconds = 5400.0
+/// runs = 80
+///
+/// [serviced."Inspect the wheels"]
+/// distance = 100000.0
+/// ```
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct MaintenanceFile {
+    total: Usage,
+    serviced: BTreeMap<String, Usage>,
+}
+
+/// How much the car has been used in total, and how much it had been used when
+/// each service reminder was last done
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct Maintenance {
+    pub total: Usage,
+    /// The total when each task was last done, by task
+    pub serviced: BTreeMap<String, Usage>,
+    /// Where the counters are saved, or `None` to only keep them in memory
+    pub path: Option<PathBuf>,
+}
+impl Maintenance {
+    /// `maintenance.toml` in the XDG config directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|config_dir| config_dir.join(MAINTENANCE_FILE_NAME))
+    }
+
+    /// Load the counters saved at a path, if any
+    ///
+    /// Like the session, anything wrong with the file is handed back
+    /// alongside counters starting from nothing. Those only replace the file
+    /// once the next run is recorded, so a file that's only unreadable for now
+    /// isn't lost.
+    pub fn load(path: Option<PathBuf>) -> (Self, Option<ClientError>) {
+        let Some(path) = path else {
+            return (Self::default(), None);
+        };
+        let (file, error) = match path.exists() {
+            true => match Self::read(&path) {
+                Ok(file) => (file, None),
+                Err(e) => (MaintenanceFile::default(), Some(e)),
+            },
+            false => (MaintenanceFile::default(), None),
+        };
+        let maintenance = Self {
+            total: file.total,
+            serviced: file.serviced,
+            path: Some(path),
+        };
+        (maintenance, error)
+    }
+
+    fn read(path: &Path) -> Result<MaintenanceFile, ClientError> {
+        let contents = read_to_string(path).map_err(|e| {
+            ClientError::Maintenance(format!("Failed to read {}: {e}", path.display()))
+        })?;
+        toml::from_str(&contents)
+            .map_err(|e| ClientError::Maintenance(e.to_string().trim_end().to_owned()))
+    }
+
+    /// Add a run to the total, if it used the car at all
+    pub fn record_run(&mut self, run: &Usage) -> Result<(), ClientError> {
+        if run.runs == 0 {
+            return Ok(());
+        }
+        self.total.add(run);
+        self.save()
+    }
+
+    /// How much the car has been used since a task was last done, or since
+    /// the counters started if it never was
+    pub fn since_serviced(&self, task: &str) -> Usage {
+        self.total
+            .since(&self.serviced.get(task).copied().unwrap_or_default())
+    }
+
+    /// The reminders that are due, in the order they're given
+    pub fn due<'a>(&self, reminders: &'a [ServiceReminder]) -> Vec<&'a ServiceReminder> {
+        reminders
+            .iter()
+            .filter(|reminder| reminder.due(&self.since_serviced(&reminder.task)))
+            .collect()
+    }
+
+    /// A task was just done, so count from now until it's due again
+    pub fn serviced(&mut self, task: &str) -> Result<(), ClientError> {
+        self.serviced.insert(task.to_owned(), self.total);
+        self.save()
+    }
+
+    /// Save the counters, if they have a path
+    pub fn save(&self) -> Result<(), ClientError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = MaintenanceFile {
+            total: self.total,
+            serviced: self.serviced.clone(),
+        };
+        let contents =
+            toml::to_string_pretty(&file).map_err(|e| ClientError::Maintenance(e.to_string()))?;
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).map_err(|e| {
+                ClientError::Maintenance(format!("Failed to create {}: {e}", parent.display()))
+            })?;
+        }
+        write(path, contents).map_err(|e| {
+            ClientError::Maintenance(format!("Failed to write {}: {e}", path.display()))
+        })
+    }
+}
@@ -0,0 +1,109 @@
+/*!
+ * A run as one timeline of what was sent to the car and what it sent back,
+ * for lining up what was asked of it with what it did
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{clock::DisplayTimezone, events::SentCommand, shared::Units};
+use bindings::{ClientError, Event, StatusResponse};
+use std::{
+    fs::write,
+    path::{Path, PathBuf},
+};
+
+// Constants
+/// Added onto a run's file stem for its narrative
+pub const NARRATIVE_SUFFIX: &str = ".narrative.txt";
+
+/***** Timeline *****/
+
+/// Something that happened in a run
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TimelineEntry<'a> {
+    /// A status, by its index in the run
+    Status(usize),
+    Sent(&'a SentCommand),
+}
+
+/// Everything sent and received in a run in the order it happened
+///
+/// Statuses are timed by the car's clock, so they're put on ours going by how
+/// many seconds the car's clock is ahead. Anything sent at the same time as a
+/// status goes before it.
+pub fn timeline<'a>(
+    statuses: &[Event<StatusResponse>],
+    sent_commands: &'a [SentCommand],
+    clock_offset: f64,
+) -> Vec<TimelineEntry<'a>> {
+    let mut timeline = Vec::with_capacity(statuses.len() + sent_commands.len());
+    let mut sent_commands = sent_commands.iter().peekable();
+    for (idx, status) in statuses.iter().enumerate() {
+        let status_time = status.metadata.time - clock_offset;
+        while let Some(sent) = sent_commands.next_if(|sent| sent.time <= status_time) {
+            timeline.push(TimelineEntry::Sent(sent));
+        }
+        timeline.push(TimelineEntry::Status(idx));
+    }
+    timeline.extend(sent_commands.map(TimelineEntry::Sent));
+    timeline
+}
+
+/***** Narrative *****/
+
+/// The timeline written out a line at a time, e.g.
+///
+/// ```text
+/// 12:00:01.250 > START {"distance":500.0}
+/// 12:00:01.500 < STATUS 12.500cm at 25.000cm/s, Forward
+/// ```
+pub fn narrative(
+    statuses: &[Event<StatusResponse>],
+    sent_commands: &[SentCommand],
+    clock_offset: f64,
+    timezone: &DisplayTimezone,
+    units: Units,
+) -> String {
+    let format = "%H:%M:%S%.3f";
+    timeline(statuses, sent_commands, clock_offset)
+        .into_iter()
+        .map(|entry| match entry {
+            TimelineEntry::Sent(sent) => format!(
+                "{} > {}{} {}\n",
+                timezone.format_unix(sent.time, format),
+                sent.command,
+                match sent.retry {
+                    true => " (again)",
+                    false => "",
+                },
+                sent.arguments
+            ),
+            TimelineEntry::Status(idx) => {
+                let status = &statuses[idx];
+                format!(
+                    "{} < STATUS {} at {}, {}\n",
+                    timezone.format_unix(status.metadata.time - clock_offset, format),
+                    units.format_distance(status.value.distance.distance, 3),
+                    units.format_velocity(status.value.distance.velocity, 3),
+                    // Its Display ends in a newline
+                    status.value.stage.to_string().trim_end()
+                )
+            }
+        })
+        .collect()
+}
+
+/// Where a run's narrative is kept, e.g. `run.narrative.txt` for `run.csv`
+pub fn narrative_path(run_path: &Path) -> PathBuf {
+    let stem = run_path.file_stem().unwrap_or_default().to_string_lossy();
+    run_path.with_file_name(format!("{stem}{NARRATIVE_SUFFIX}"))
+}
+
+/// Write a run's narrative next to it, giving back where
+pub fn write_narrative(run_path: &Path, narrative: &str) -> Result<PathBuf, ClientError> {
+    let path = narrative_path(run_path);
+    write(&path, narrative)
+        .map_err(|e| ClientError::Narrative(format!("Failed to write {}: {e}", path.display())))?;
+    Ok(path)
+}
@@ -0,0 +1,89 @@
+/*!
+ * Recomputing distance and velocity from the raw magnet hits, to check the
+ * server's own numbers against
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::shared::*;
+use bindings::{Event, StatusResponse, StatusStage};
+
+/***** Odometry *****/
+
+/// What the odometer is made of
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Wheel {
+    pub number_of_magnets: usize,
+    /// Centimeters
+    pub circumference: f64,
+}
+impl Wheel {
+    /// How far the car goes between magnet hits in centimeters
+    pub fn distance_per_hit(&self) -> f64 {
+        match self.number_of_magnets {
+            0 => 0.0,
+            number_of_magnets => self.circumference / number_of_magnets as f64,
+        }
+    }
+}
+
+/// Distance and velocity worked out on the client for one status
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Recomputed {
+    /// Centimeters
+    pub distance: f64,
+    /// Average centimeters per second since the run started
+    pub velocity: f64,
+    /// Whether the server's distance is off by more than a magnet hit
+    pub distance_discrepancy: bool,
+    /// Whether the server's velocity is off by more than a magnet hit's worth
+    /// plus [`VELOCITY_DISCREPANCY_PERCENTAGE`]
+    pub velocity_discrepancy: bool,
+}
+
+/// The distance and velocity at every status, going only by the magnet hit
+/// counter and the wheel
+///
+/// Like on the server, hits taken while cautiously backing up count
+/// backwards, and the velocity is the average since the run started. The run
+/// is taken to have started at the time of the first status minus its
+/// runtime, which is only to the second, hence the leeway on the velocity.
+pub fn recompute(statuses: &[Event<StatusResponse>], wheel: &Wheel) -> Vec<Recomputed> {
+    let distance_per_hit = wheel.distance_per_hit();
+    let start_time = match statuses.first() {
+        Some(first) => first.metadata.time - first.value.runtime as f64,
+        None => return vec![],
+    };
+
+    let (mut distance, mut last_hits) = (0.0, 0);
+    statuses
+        .iter()
+        .map(|status| {
+            let server = &status.value.distance;
+            let hits = server.magnet_hit_counter.saturating_sub(last_hits) as f64;
+            last_hits = last_hits.max(server.magnet_hit_counter);
+            distance += match status.value.stage {
+                StatusStage::CautiousBackward => -hits,
+                _ => hits,
+            } * distance_per_hit;
+
+            let elapsed = status.metadata.time - start_time;
+            let (velocity, velocity_tolerance) = match elapsed > 0.0 {
+                true => (
+                    distance / elapsed,
+                    distance_per_hit / elapsed
+                        + server.velocity.abs() * VELOCITY_DISCREPANCY_PERCENTAGE / 100.0,
+                ),
+                false => (server.velocity, 0.0),
+            };
+
+            Recomputed {
+                distance,
+                velocity,
+                distance_discrepancy: (server.distance - distance).abs() > distance_per_hit,
+                velocity_discrepancy: (server.velocity - velocity).abs() > velocity_tolerance,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,87 @@
+/*!
+ * Frames waiting to be written to the car, most urgent first, so a STOP is
+ * never stuck behind anything else on a slow link
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::shared::*;
+use bindings::Command;
+use std::collections::VecDeque;
+
+/***** Outgoing queue *****/
+
+/// How urgently a frame has to go out
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Priority {
+    /// Asking for a status, see [`POLL_COMMANDS`]
+    Poll,
+    Command,
+    /// Stopping the car, ahead of everything else
+    Stop,
+}
+impl Priority {
+    pub fn of(command: Command) -> Self {
+        match command {
+            Command::Stop => Self::Stop,
+            command if POLL_COMMANDS.contains(&command) => Self::Poll,
+            _ => Self::Command,
+        }
+    }
+}
+
+/// A frame waiting to be written
+#[derive(Clone, PartialEq, Debug)]
+pub struct QueuedFrame {
+    pub command: Command,
+    pub priority: Priority,
+    pub frame: String,
+}
+
+/// Frames waiting to be written, taken most urgent first and in the order
+/// they were queued otherwise
+#[derive(Clone, Default, Debug)]
+pub struct OutgoingQueue {
+    frames: VecDeque<QueuedFrame>,
+}
+impl OutgoingQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, command: Command, frame: String) {
+        self.frames.push_back(QueuedFrame {
+            command,
+            priority: Priority::of(command),
+            frame,
+        });
+    }
+
+    /// Take the frame to write next
+    pub fn pop(&mut self) -> Option<QueuedFrame> {
+        let most_urgent = self.frames.iter().map(|queued| queued.priority).max()?;
+        let idx = self
+            .frames
+            .iter()
+            .position(|queued| queued.priority == most_urgent)?;
+        self.frames.remove(idx)
+    }
+
+    /// Whether a frame is still waiting to be written
+    pub fn contains(&self, frame: &str) -> bool {
+        self.frames.iter().any(|queued| queued.frame == frame)
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+}
@@ -0,0 +1,76 @@
+/*!
+ * Gauges for the battery and what the motor draws from it, and warning about
+ * the battery running low before it cuts out mid run
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::config::CarConfig;
+use bindings::PowerStatus;
+#[cfg(feature = "ui")]
+use egui::{Color32, ProgressBar, Ui};
+
+/***** Battery *****/
+
+/// Whether the battery is under either of the thresholds in the config
+pub fn battery_low(power: &PowerStatus, car: &CarConfig) -> bool {
+    power
+        .charge
+        .is_some_and(|charge| charge < car.low_battery_charge)
+        || car
+            .low_battery_voltage
+            .is_some_and(|voltage| power.battery_voltage < voltage)
+}
+
+/// What to warn about the battery, if it's low
+pub fn battery_warning(power: &PowerStatus, car: &CarConfig) -> Option<String> {
+    battery_low(power, car).then(|| {
+        format!(
+            "The battery is low ({:.2}V{}), charge it before the next run",
+            power.battery_voltage,
+            power
+                .charge
+                .map(|charge| format!(", {charge:.0}% left"))
+                .unwrap_or_default()
+        )
+    })
+}
+
+/// Warns once when the battery goes low, and again only once it's been
+/// charged back up and gone low again
+#[derive(Default)]
+pub struct BatteryAlert {
+    alerted: bool,
+}
+impl BatteryAlert {
+    /// The warning, the first time the battery is seen to be low
+    pub fn check(&mut self, power: &PowerStatus, car: &CarConfig) -> Option<String> {
+        let warning = battery_warning(power, car);
+        let alert = warning.filter(|_| !self.alerted);
+        self.alerted = battery_low(power, car);
+        alert
+    }
+}
+
+#[cfg(feature = "ui")]
+/// Show the battery's charge as a gauge (or its voltage, if the charge isn't
+/// known), and the current the motor draws
+pub fn show_power(ui: &mut Ui, power: &PowerStatus, car: &CarConfig) {
+    let fill = match battery_low(power, car) {
+        true => Color32::RED,
+        false => Color32::DARK_GREEN,
+    };
+    match power.charge {
+        Some(charge) => ui.add(
+            ProgressBar::new((charge / 100.0).clamp(0.0, 1.0) as f32)
+                .fill(fill)
+                .text(format!(
+                    "Battery {charge:.0}% ({:.2}V)",
+                    power.battery_voltage
+                )),
+        ),
+        None => ui.colored_label(fill, format!("Battery {:.2}V", power.battery_voltage)),
+    };
+    ui.label(format!("Motor drawing {:.2}A", power.motor_current));
+}
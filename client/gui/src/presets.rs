@@ -0,0 +1,189 @@
+/*!
+ * Named start parameters for the distances a team runs over and over
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::shared::*;
+#[cfg(feature = "ui")]
+use crate::GUIData;
+use bindings::{ClientError, DistanceEstimatorKind};
+use serde::{Deserialize, Serialize};
+use smart_default::SmartDefault;
+use std::{
+    fs::{create_dir_all, read_to_string, write},
+    path::{Path, PathBuf},
+};
+
+// Constants
+/// The presets file inside of the XDG config directory, next to the config
+pub const PRESETS_FILE_NAME: &str = "chemistry-car-controller/presets.toml";
+
+/***** Presets *****/
+
+/// Start parameters saved under a name
+#[derive(Serialize, Deserialize, SmartDefault, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Preset {
+    pub name: String,
+    /// Centimeters
+    pub distance: f64,
+    pub reverse_braking: bool,
+    pub auto_stop: bool,
+    /// Percentage of the distance to stop at
+    #[default(DEFAULT_AUTO_STOP_PERCENTAGE)]
+    pub auto_stop_percentage: f64,
+    pub speed_limit: bool,
+    /// Centimeters per second
+    #[default(DEFAULT_MAX_VELOCITY)]
+    pub max_velocity: f64,
+    /// How the server works out the distance, or `None` for its default, to
+    /// compare estimators on the same distance
+    pub distance_estimator: Option<DistanceEstimatorKind>,
+}
+impl Preset {
+    #[cfg(feature = "ui")]
+    /// The start parameters as they're set in the GUI
+    pub fn from_gui_data(name: String, gui_data: &GUIData) -> Self {
+        Self {
+            name,
+            distance: gui_data.distance,
+            reverse_braking: gui_data.reverse_braking,
+            auto_stop: gui_data.auto_stop,
+            auto_stop_percentage: gui_data.auto_stop_percentage,
+            speed_limit: gui_data.speed_limit,
+            max_velocity: gui_data.max_velocity,
+            distance_estimator: gui_data.distance_estimator,
+        }
+    }
+
+    #[cfg(feature = "ui")]
+    /// Set the start parameters in the GUI to these
+    pub fn apply(&self, gui_data: &mut GUIData) {
+        gui_data.distance = self.distance;
+        gui_data.reverse_braking = self.reverse_braking;
+        gui_data.auto_stop = self.auto_stop;
+        gui_data.auto_stop_percentage = self.auto_stop_percentage;
+        gui_data.speed_limit = self.speed_limit;
+        gui_data.max_velocity = self.max_velocity;
+        gui_data.distance_estimator = self.distance_estimator;
+    }
+}
+
+/// Every preset, as they're saved, e.g.
+///
+/// ```toml
+/// [[preset]]
+/// name = "15 m"
+/// distance = 1500.0
+/// ```
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+struct PresetsFile {
+    #[serde(default, rename = "preset")]
+    presets: Vec<Preset>,
+}
+
+/// The presets, saved to disk whenever they change
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct Presets {
+    /// In the order they were first saved
+    pub presets: Vec<Preset>,
+    /// Where they're saved, or `None` to only keep them in memory
+    pub path: Option<PathBuf>,
+}
+impl Presets {
+    /// `presets.toml` in the XDG config directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|config_dir| config_dir.join(PRESETS_FILE_NAME))
+    }
+
+    /// Load the presets saved at a path, if any
+    ///
+    /// Like the config, anything wrong with the file is handed back alongside
+    /// no presets rather than stopping the GUI. The path is kept either way,
+    /// so presets saved afterwards replace a broken file.
+    pub fn load(path: Option<PathBuf>) -> (Self, Option<ClientError>) {
+        let Some(path) = path else {
+            return (Self::default(), None);
+        };
+        if !path.exists() {
+            return (Self::at(path, vec![]), None);
+        }
+        match Self::read(&path) {
+            Ok(presets) => (Self::at(path, presets), None),
+            Err(e) => (Self::at(path, vec![]), Some(e)),
+        }
+    }
+
+    fn at(path: PathBuf, presets: Vec<Preset>) -> Self {
+        Self {
+            presets,
+            path: Some(path),
+        }
+    }
+
+    /// Read a presets file
+    pub fn read(path: &Path) -> Result<Vec<Preset>, ClientError> {
+        let contents = read_to_string(path)
+            .map_err(|e| ClientError::Presets(format!("Failed to read {}: {e}", path.display())))?;
+        Self::parse(&contents)
+    }
+
+    /// Parse a presets file
+    pub fn parse(contents: &str) -> Result<Vec<Preset>, ClientError> {
+        toml::from_str::<PresetsFile>(contents)
+            .map(|file| file.presets)
+            .map_err(|e| ClientError::Presets(e.to_string().trim_end().to_owned()))
+    }
+
+    /// The preset with a name, if there is one
+    pub fn find(&self, name: &str) -> Option<&Preset> {
+        self.presets.iter().find(|preset| preset.name == name)
+    }
+
+    /// Save a preset, replacing the one with the same name if there is one
+    pub fn save(&mut self, preset: Preset) -> Result<(), ClientError> {
+        if preset.name.trim().is_empty() {
+            return Err(ClientError::Presets("A preset needs a name".to_owned()));
+        }
+        match self
+            .presets
+            .iter_mut()
+            .find(|existing| existing.name == preset.name)
+        {
+            Some(existing) => *existing = preset,
+            None => self.presets.push(preset),
+        }
+        self.write()
+    }
+
+    /// Delete the preset with a name, if there is one
+    pub fn delete(&mut self, name: &str) -> Result<(), ClientError> {
+        let count = self.presets.len();
+        self.presets.retain(|preset| preset.name != name);
+        match self.presets.len() == count {
+            true => Ok(()),
+            false => self.write(),
+        }
+    }
+
+    fn write(&self) -> Result<(), ClientError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = PresetsFile {
+            presets: self.presets.clone(),
+        };
+        let contents =
+            toml::to_string_pretty(&file).map_err(|e| ClientError::Presets(e.to_string()))?;
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).map_err(|e| {
+                ClientError::Presets(format!("Failed to create {}: {e}", parent.display()))
+            })?;
+        }
+        write(path, contents)
+            .map_err(|e| ClientError::Presets(format!("Failed to write {}: {e}", path.display())))
+    }
+}
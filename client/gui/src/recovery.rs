@@ -0,0 +1,86 @@
+/*!
+ * What to do about an error from the server, offered as a "Fix it" button
+ * next to it in the errors window
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{ClientError, ServerError};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/***** Recovery *****/
+
+/// Something to try that usually gets past an error from the server
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Recovery {
+    /// Stop the run the server is already on, then send the start again
+    StopAndStartAgain,
+    /// Send the start again, for a server that was busy the first time
+    StartAgain,
+    /// Send the stop again, for a server that didn't manage it the first time
+    StopAgain,
+    /// The server never started, so there's no run to wait on
+    GiveUpOnRun,
+    /// Ask for the car's information again
+    RequestStaticStatus,
+    /// Ping again, for a latency that didn't make sense
+    PingAgain,
+    /// Disconnect from the car and connect to it again, for frames coming in
+    /// garbled
+    ReconnectBluetooth,
+}
+impl Recovery {
+    /// What to do about an error the server returned, if anything helps
+    pub fn for_server_error(error: ServerError) -> Option<Self> {
+        use ServerError::*;
+        match error {
+            FailedToStartAlreadyStarted => Some(Self::StopAndStartAgain),
+            // The magnets are sent with the car's information
+            FailedToStartMagnetOdometerFailed => Some(Self::RequestStaticStatus),
+            // Only held for a moment while a status is read
+            FailedToStartCouldNotAcquireDistanceLock => Some(Self::StartAgain),
+            FailedToStopNotStarted => Some(Self::GiveUpOnRun),
+            FailedToStopStartThreadWouldNotRespond => Some(Self::StopAgain),
+            FailedPingNegativeLatency => Some(Self::PingAgain),
+            MalformedRequestFailedPrefixParsing
+            | MalformedRequestFailedCommandParsing
+            | MalformedRequestFailedSeparatorParsing
+            | MalformedRequestFailedArgumentsParsing
+            | MalformedRequestFailedMetadataParsing
+            | MalformedRequestTypeError
+            | MalformedRequestOtherError
+            | MalformedResponseTypeError
+            | MalformedResponseOtherError => Some(Self::ReconnectBluetooth),
+            // Nothing the client can do about the hardware, and the next
+            // status comes in soon anyway
+            _ => None,
+        }
+    }
+
+    /// What to do about an error, only if it came from the server
+    pub fn for_error(error: &ClientError) -> Option<Self> {
+        match error {
+            ClientError::Server(error, _) => Self::for_server_error(*error),
+            _ => None,
+        }
+    }
+}
+impl fmt::Display for Recovery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::StopAndStartAgain => "Stop the car and start again",
+                Self::StartAgain => "Start again",
+                Self::StopAgain => "Stop the car again",
+                Self::GiveUpOnRun => "Finish the run, as the car never started",
+                Self::RequestStaticStatus => "Ask for the car's information again",
+                Self::PingAgain => "Ping the car again",
+                Self::ReconnectBluetooth => "Reconnect to the car",
+            }
+        )
+    }
+}
@@ -0,0 +1,83 @@
+/*!
+ * How long each command takes to be answered, split into the time spent on
+ * the car and the time spent getting there and back, for the diagnostics
+ * window
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{Command, RoundTrip};
+
+/***** Round trips *****/
+
+/// The round trips of every response to one command
+#[derive(Clone, Debug)]
+pub struct CommandRoundTrips {
+    pub command: Command,
+    pub count: usize,
+    pub last: RoundTrip,
+    /// The one with the longest round trip
+    pub slowest: RoundTrip,
+    /// Summed up, for the average
+    sum: RoundTrip,
+}
+impl CommandRoundTrips {
+    pub fn average(&self) -> RoundTrip {
+        RoundTrip {
+            total: self.sum.total / self.count as f64,
+            server: self.sum.server / self.count as f64,
+        }
+    }
+}
+
+/// The round trips of every response the server echoed the request of, by
+/// command in the order they were first answered
+///
+/// Servers from before the echo send nothing to go by, so nothing is kept.
+#[derive(Clone, Default, Debug)]
+pub struct RoundTrips {
+    commands: Vec<CommandRoundTrips>,
+}
+impl RoundTrips {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, command: Command, round_trip: RoundTrip) {
+        let Some(round_trips) = self
+            .commands
+            .iter_mut()
+            .find(|round_trips| round_trips.command == command)
+        else {
+            return self.commands.push(CommandRoundTrips {
+                command,
+                count: 1_usize,
+                last: round_trip,
+                slowest: round_trip,
+                sum: round_trip,
+            });
+        };
+        round_trips.count += 1;
+        round_trips.last = round_trip;
+        if round_trip.total > round_trips.slowest.total {
+            round_trips.slowest = round_trip;
+        }
+        round_trips.sum.total += round_trip.total;
+        round_trips.sum.server += round_trip.server;
+    }
+
+    pub fn commands(&self) -> &[CommandRoundTrips] {
+        &self.commands
+    }
+
+    pub fn get(&self, command: Command) -> Option<&CommandRoundTrips> {
+        self.commands
+            .iter()
+            .find(|round_trips| round_trips.command == command)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
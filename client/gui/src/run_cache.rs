@@ -0,0 +1,128 @@
+/*!
+ * Reading saved runs lazily, keeping the most recently opened ones in memory
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{export::*, shared::*};
+use bindings::{Event, StatusResponse};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::metadata,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/***** Run summary *****/
+
+/// Where a run ended up, going by its last status
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RunSummary {
+    /// Seconds
+    pub runtime: usize,
+    /// Centimeters
+    pub distance: f64,
+}
+impl RunSummary {
+    pub fn new(last_status: &StatusResponse) -> Self {
+        Self {
+            runtime: last_status.runtime,
+            distance: last_status.distance.distance,
+        }
+    }
+}
+
+/***** Run cache *****/
+
+/// A saved run read in full
+struct CachedRun {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    statuses: Vec<Event<StatusResponse>>,
+}
+
+/// Saved runs, read only as much as they're needed
+///
+/// Listing a directory only reads the end of each run for its summary, and
+/// the whole run is only read once it's opened. The last
+/// [`RUN_CACHE_CAPACITY`] runs opened are kept in memory, and anything is read
+/// again if the file changed since.
+#[derive(Default)]
+pub struct RunCache {
+    /// `None` for runs that couldn't be summarized, so they aren't retried
+    summaries: HashMap<PathBuf, (Option<SystemTime>, Option<RunSummary>)>,
+    /// Most recently opened last
+    runs: Vec<CachedRun>,
+}
+impl RunCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The summary of a run, or `None` if it's empty or can't be read
+    pub fn summary(&mut self, path: &Path) -> Option<RunSummary> {
+        let modified = modified(path);
+        if let Some((summary_modified, summary)) = self.summaries.get(path) {
+            if *summary_modified == modified {
+                return *summary;
+            }
+        }
+
+        // A run already read in full doesn't need to be read again
+        let summary = match self.cached(path, modified) {
+            Some(run) => run.statuses.last().map(|last| RunSummary::new(&last.value)),
+            None => read_last_of_run(path)
+                .ok()
+                .flatten()
+                .map(|last| RunSummary::new(&last.value)),
+        };
+        self.summaries
+            .insert(path.to_path_buf(), (modified, summary));
+        summary
+    }
+
+    /// Every status of a run, reading it if it isn't in memory
+    pub fn statuses(&mut self, path: &Path) -> Result<Vec<Event<StatusResponse>>, Box<dyn Error>> {
+        let modified = modified(path);
+        let run = match self.runs.iter().position(|run| run.path == path) {
+            Some(idx) if self.runs[idx].modified == modified => self.runs.remove(idx),
+            existing => {
+                if let Some(idx) = existing {
+                    self.runs.remove(idx);
+                }
+                CachedRun {
+                    path: path.to_path_buf(),
+                    modified,
+                    statuses: read_run(path)?,
+                }
+            }
+        };
+
+        let statuses = run.statuses.clone();
+        self.runs.push(run);
+        if self.runs.len() > RUN_CACHE_CAPACITY {
+            self.runs.remove(0);
+        }
+        Ok(statuses)
+    }
+
+    /// Whether a run is in memory in full
+    pub fn contains(&self, path: &Path) -> bool {
+        self.runs.iter().any(|run| run.path == path)
+    }
+
+    fn cached(&self, path: &Path, modified: Option<SystemTime>) -> Option<&CachedRun> {
+        self.runs
+            .iter()
+            .find(|run| run.path == path && run.modified == modified)
+    }
+}
+
+/***** Helper functions *****/
+
+/// When a file was last changed, if that can be told
+fn modified(path: &Path) -> Option<SystemTime> {
+    metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
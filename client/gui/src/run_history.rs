@@ -0,0 +1,287 @@
+/*!
+ * Keeping every run since the GUI started, to look back on and compare
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{
+    annotations::*,
+    clock::DisplayTimezone,
+    events::{RunData, SentCommand},
+    run_cache::RunSummary,
+    shared::*,
+    stop_reason::StopReason,
+    telemetry_plot::*,
+};
+use bindings::{Event, PingResponse, StaticStatusResponse, StatusResponse};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "ui")]
+use egui::{
+    plot::{Legend, Line, Plot},
+    Button, ComboBox, Context, ScrollArea, TextEdit, Ui, Window,
+};
+use std::{cmp::Ordering, mem::take};
+
+// Constants
+#[cfg(feature = "ui")]
+const COMPARISON_PLOT_SIZE: [f32; 2] = [220.0, 170.0];
+
+/***** Archived runs *****/
+
+/// What a run was started with
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RunParameters {
+    /// Centimeters
+    pub distance: f64,
+    pub reverse_braking: bool,
+    /// Percentage of the distance to stop at, if stopping automatically
+    pub auto_stop_percentage: Option<f64>,
+    /// Centimeters per second, if the speed was limited
+    pub max_velocity: Option<f64>,
+}
+
+/// A finished run, with everything that came in for it
+pub struct ArchivedRun {
+    pub name: String,
+    pub archived: DateTime<Utc>,
+    /// `None` for runs that were loaded rather than started
+    pub parameters: Option<RunParameters>,
+    /// Every status that came in, including any spilled to disk
+    pub statuses: Vec<Event<StatusResponse>>,
+    /// Every command sent to the car during the run
+    pub sent_commands: Vec<SentCommand>,
+    pub static_status: Option<Box<Event<StaticStatusResponse>>>,
+    /// The last ping, with the Unix time in seconds it came back at
+    pub ping: Option<(Box<Event<PingResponse>>, f64)>,
+    pub annotations: Vec<Annotation>,
+    /// Why the run was stopped by hand, if it was and it was said
+    pub stop_reason: Option<StopReason>,
+}
+impl ArchivedRun {
+    /// The round-trip latency of the last ping, in milliseconds
+    pub fn latency(&self) -> Option<f64> {
+        self.ping
+            .as_ref()
+            .map(|(ping, received_time)| (received_time - ping.value.sent_time) * 1000.0)
+    }
+
+    pub fn summary(&self) -> Option<RunSummary> {
+        self.statuses
+            .last()
+            .map(|last| RunSummary::new(&last.value))
+    }
+}
+
+/***** Run history *****/
+
+/// Every run since the GUI started, up to [`MAX_ARCHIVED_RUNS`] of them
+#[derive(Default)]
+pub struct RunHistory {
+    /// Oldest first
+    pub runs: Vec<ArchivedRun>,
+    /// How many runs have ever been archived, for naming the next one
+    archived: usize,
+    pub selected: Option<usize>,
+    /// The runs shown side by side
+    pub comparison: Option<[usize; 2]>,
+}
+impl RunHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move a run out of the run data into the history, returning its index,
+    /// or `None` if no statuses came in for it
+    ///
+    /// The oldest run is dropped to make room once the history is full.
+    pub fn archive(&mut self, run_data: &mut RunData) -> Option<usize> {
+        if run_data.status_responses.is_empty() {
+            return None;
+        }
+        if self.runs.len() >= MAX_ARCHIVED_RUNS {
+            self.remove(0_usize);
+        }
+
+        self.archived += 1;
+        self.runs.push(ArchivedRun {
+            name: format!("Run {}", self.archived),
+            archived: Utc::now(),
+            parameters: run_data.parameters.take(),
+            statuses: run_data.status_responses.take_statuses(),
+            sent_commands: take(&mut run_data.sent_commands),
+            static_status: run_data.static_status_response.take(),
+            ping: run_data.ping_status_response.take(),
+            annotations: take(&mut run_data.annotations),
+            stop_reason: run_data.stop_reason.take(),
+        });
+        Some(self.runs.len() - 1)
+    }
+
+    /// Forget a run, keeping the selection and comparison on the same runs
+    pub fn remove(&mut self, idx: usize) {
+        if idx >= self.runs.len() {
+            return;
+        }
+        self.runs.remove(idx);
+
+        let shift = |other: usize| match other.cmp(&idx) {
+            Ordering::Less => Some(other),
+            Ordering::Equal => None,
+            Ordering::Greater => Some(other - 1),
+        };
+        self.selected = self.selected.and_then(shift);
+        self.comparison = self
+            .comparison
+            .and_then(|[left, right]| Some([shift(left)?, shift(right)?]));
+    }
+
+    #[cfg(feature = "ui")]
+    /// Show the runs in the side panel, to pick one to look at or compare
+    pub fn show_panel(&mut self, ui: &mut Ui, timezone: &DisplayTimezone, units: Units) {
+        ui.heading("Runs");
+        if self.runs.is_empty() {
+            ui.label("Runs are kept here once they're reset");
+            return;
+        }
+
+        ScrollArea::vertical()
+            .id_source("archived runs")
+            .max_height(100.0)
+            .show(ui, |ui| {
+                for (idx, run) in self.runs.iter().enumerate().rev() {
+                    if ui
+                        .selectable_label(self.selected == Some(idx), &run.name)
+                        .clicked()
+                    {
+                        self.selected = Some(idx);
+                    }
+                }
+            });
+
+        let Some(selected) = self.selected else {
+            return;
+        };
+        ui.separator();
+        let run = &mut self.runs[selected];
+        ui.add(TextEdit::singleline(&mut run.name));
+        ui.label(format!(
+            "Archived {}",
+            timezone.format(run.archived, "%H:%M:%S")
+        ));
+        if let Some(parameters) = &run.parameters {
+            ui.label(format!(
+                "Target: {}",
+                units.format_long_distance(parameters.distance, 2)
+            ));
+            if let Some(max_velocity) = parameters.max_velocity {
+                ui.label(format!("Limit: {}", units.format_velocity(max_velocity, 1)));
+            }
+        }
+        if let Some(summary) = run.summary() {
+            ui.label(format!(
+                "Went {} in {}s",
+                units.format_long_distance(summary.distance, 2),
+                summary.runtime
+            ));
+        }
+        if let Some(latency) = run.latency() {
+            ui.label(format!("Latency: {latency:.0}ms"));
+        }
+        if let Some(stop_reason) = &run.stop_reason {
+            ui.label(format!("Stopped for: {}", stop_reason.describe()));
+        }
+
+        let mut compare_with = None;
+        ComboBox::from_id_source("compare with")
+            .width(130.0)
+            .selected_text("Compare with")
+            .show_ui(ui, |ui| {
+                for (idx, run) in self.runs.iter().enumerate() {
+                    if idx != selected && ui.selectable_label(false, &run.name).clicked() {
+                        compare_with = Some(idx);
+                    }
+                }
+            });
+        if let Some(other) = compare_with {
+            self.comparison = Some([selected, other]);
+        }
+        if ui.add_sized([60., 20.], Button::new("Delete")).clicked() {
+            self.remove(selected);
+        }
+    }
+
+    #[cfg(feature = "ui")]
+    /// Show the compared runs' distance and velocity side by side, on the
+    /// same scale
+    pub fn show_comparison(&mut self, ctx: &Context) {
+        let Some([left, right]) = self.comparison else {
+            return;
+        };
+        let runs = [&self.runs[left], &self.runs[right]];
+        let comparison = compare(&runs[0].statuses, &runs[1].statuses);
+
+        let mut open = true;
+        Window::new("Compare runs")
+            .resizable(false)
+            .show(ctx, |ui| {
+                if ui.add_sized([60., 20.], Button::new("Close")).clicked() {
+                    open = false;
+                }
+                ui.horizontal(|ui| {
+                    for (idx, (run, telemetry)) in runs.iter().zip(comparison.runs).enumerate() {
+                        ui.vertical(|ui| {
+                            ui.label(&run.name);
+                            Plot::new(("comparison", idx))
+                                .width(COMPARISON_PLOT_SIZE[0])
+                                .height(COMPARISON_PLOT_SIZE[1])
+                                .legend(Legend::default())
+                                .allow_drag(false)
+                                .allow_zoom(false)
+                                .allow_scroll(false)
+                                .allow_boxed_zoom(false)
+                                .include_x(0.0)
+                                .include_x(comparison.end)
+                                .include_y(0.0)
+                                .include_y(comparison.top)
+                                .x_axis_formatter(|seconds, _| format!("{seconds:.0}s"))
+                                .show(ui, |plot_ui| {
+                                    plot_ui
+                                        .line(Line::new(telemetry.distance).name("Distance (cm)"));
+                                    plot_ui.line(
+                                        Line::new(telemetry.velocity).name("Velocity (cm/s)"),
+                                    );
+                                    plot_annotations(plot_ui, &run.annotations, comparison.top);
+                                });
+                        });
+                    }
+                });
+            });
+
+        if !open {
+            self.comparison = None;
+        }
+    }
+}
+
+/***** Comparison *****/
+
+/// Two whole runs, to be plotted on the same scale
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Comparison {
+    pub runs: [Telemetry; 2],
+    /// Seconds, the end of the longer run
+    pub end: f64,
+    /// The highest distance or velocity of either run
+    pub top: f64,
+}
+
+pub fn compare(left: &[Event<StatusResponse>], right: &[Event<StatusResponse>]) -> Comparison {
+    let runs = [
+        telemetry(left, f64::INFINITY),
+        telemetry(right, f64::INFINITY),
+    ];
+    let end = runs[0].end.max(runs[1].end);
+    let top = runs[0].top().max(runs[1].top());
+    Comparison { runs, end, top }
+}
@@ -0,0 +1,90 @@
+/*!
+ * Sharing the run data with other threads (and the frame being drawn) as
+ * snapshots that never change under whoever is reading them
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{
+    annotations::Annotation, events::RunData, run_history::RunParameters, stop_reason::StopReason,
+};
+use bindings::{Event, StatusResponse};
+use std::sync::{Arc, PoisonError, RwLock};
+
+/***** Run snapshot *****/
+
+/// The run data as it was at one point, which never changes
+///
+/// Cloning it is cheap, as the statuses and annotations are shared with the
+/// snapshots before it for as long as they stay the same.
+#[derive(Clone, Default, Debug)]
+pub struct RunSnapshot {
+    /// Goes up by one every time a snapshot is published
+    pub revision: u64,
+    pub running: bool,
+    pub saved: bool,
+    pub bluetooth_bridge_connected: bool,
+    /// Those kept in memory, downsampled on long runs
+    pub statuses: Arc<[Event<StatusResponse>]>,
+    /// The [`StatusHistory::revision`](crate::status_history::StatusHistory::revision)
+    /// the statuses are from
+    pub statuses_revision: u64,
+    pub annotations: Arc<[Annotation]>,
+    pub parameters: Option<RunParameters>,
+    pub stop_reason: Option<StopReason>,
+}
+impl RunSnapshot {
+    /// Snapshot the run data, sharing whatever didn't change since `previous`
+    pub fn of(run_data: &RunData, previous: &RunSnapshot) -> Self {
+        let statuses_revision = run_data.status_responses.revision();
+        let statuses = match statuses_revision == previous.statuses_revision {
+            true => previous.statuses.clone(),
+            false => Arc::from(&run_data.status_responses[..]),
+        };
+        let annotations = match previous.annotations[..] == run_data.annotations[..] {
+            true => previous.annotations.clone(),
+            false => Arc::from(&run_data.annotations[..]),
+        };
+        Self {
+            revision: previous.revision + 1,
+            running: run_data.running,
+            saved: run_data.saved,
+            bluetooth_bridge_connected: run_data.bluetooth_bridge_connected,
+            statuses,
+            statuses_revision,
+            annotations,
+            parameters: run_data.parameters,
+            stop_reason: run_data.stop_reason.clone(),
+        }
+    }
+}
+
+/***** Shared run data *****/
+
+/// The latest snapshot of the run data, for anything that reads it
+///
+/// The run data itself is only ever changed on the GUI thread, which
+/// publishes a snapshot of it once a frame. Readers, whether drawing the
+/// frame or on a background thread, [`load`](Self::load) the latest one and
+/// keep it for as long as they need. The lock is only held to clone or swap
+/// an [`Arc`], so neither side waits on the other for longer than that.
+#[derive(Clone, Default, Debug)]
+pub struct SharedRunData(Arc<RwLock<Arc<RunSnapshot>>>);
+impl SharedRunData {
+    /// The latest snapshot
+    pub fn load(&self) -> Arc<RunSnapshot> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replace the latest snapshot with one of the run data as it is now
+    pub fn publish(&self, run_data: &RunData) -> Arc<RunSnapshot> {
+        // Built before taking the lock, so readers aren't kept waiting
+        let snapshot = Arc::new(RunSnapshot::of(run_data, &self.load()));
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = snapshot.clone();
+        snapshot
+    }
+}
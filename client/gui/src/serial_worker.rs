@@ -0,0 +1,175 @@
+/*!
+ * Reading from and writing to the car's connection on a thread of its own, so
+ * a stalled port never holds up drawing the GUI
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{ClientError, Transport};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{channel, Receiver, RecvTimeoutError, Sender, TryRecvError},
+        Arc,
+    },
+    thread::spawn,
+    time::Duration,
+};
+use tracing::debug;
+
+// Constants
+/// How long the worker waits for something to write before reading again
+/// when nothing came in
+pub const SERIAL_WORKER_IDLE: Duration = Duration::from_millis(5);
+
+/***** Serial worker *****/
+
+/// What the worker is asked to do
+enum WorkerRequest {
+    Write(String),
+    SpeakProtocolVersion(u32),
+}
+
+/// A connection owned by a worker thread, which reads everything that comes
+/// in and writes everything sent as soon as it can
+///
+/// Neither reading nor writing ever waits on the connection itself: lines
+/// read are handed over through a channel, and lines written are queued for
+/// the worker. Failing to write is found out about on a later read, like
+/// losing the connection. The worker stops once the connection fails or this
+/// is dropped, whichever comes first.
+///
+/// Only the connection is moved to the worker, so everything else about
+/// talking to the car (see [`SerialEventPropagator`]) stays where it was.
+///
+/// [`SerialEventPropagator`]: crate::events::SerialEventPropagator
+pub struct SerialWorker {
+    requests: Sender<WorkerRequest>,
+    lines: Receiver<Result<String, ClientError>>,
+    connected: Arc<AtomicBool>,
+    /// Lines sent to the worker that it hasn't written yet
+    queued: Arc<AtomicUsize>,
+}
+impl SerialWorker {
+    /// Hand a connection over to a new worker thread
+    pub fn spawn(mut transport: Box<dyn Transport>) -> Self {
+        let (requests, worker_requests) = channel();
+        let (worker_lines, lines) = channel();
+        let connected = Arc::new(AtomicBool::new(transport.connected()));
+        let worker_connected = Arc::clone(&connected);
+        let queued = Arc::new(AtomicUsize::new(0_usize));
+        let worker_queued = Arc::clone(&queued);
+        spawn(move || {
+            let result = work(
+                &mut *transport,
+                &worker_requests,
+                &worker_lines,
+                &worker_queued,
+            );
+            worker_connected.store(false, Ordering::Relaxed);
+            if let Err(e) = result {
+                debug!("The serial worker stopped: {e}");
+                // Nobody to tell if this was dropped
+                let _ = worker_lines.send(Err(e));
+            }
+        });
+        Self {
+            requests,
+            lines,
+            connected,
+            queued,
+        }
+    }
+
+    /// The worker stopped after telling us why, or without anything to tell
+    fn stopped() -> ClientError {
+        ClientError::Serial("The connection was closed".to_owned())
+    }
+}
+impl Transport for SerialWorker {
+    fn read_line(&mut self) -> Result<Option<String>, ClientError> {
+        match self.lines.try_recv() {
+            Ok(line) => line.map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(Self::stopped()),
+        }
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), ClientError> {
+        self.queued.fetch_add(1_usize, Ordering::Relaxed);
+        self.requests
+            .send(WorkerRequest::Write(line.to_owned()))
+            .map_err(|_| Self::stopped())
+    }
+
+    fn connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// The connection is the worker's, so there's no other handle to it
+    fn try_clone(&self) -> Result<Box<dyn Transport>, ClientError> {
+        Err(ClientError::Serial(
+            "A connection on a worker thread can't be cloned".to_owned(),
+        ))
+    }
+
+    fn speak_protocol_version(&mut self, version: u32) {
+        // Nothing is spoken at all once the worker has stopped
+        let _ = self
+            .requests
+            .send(WorkerRequest::SpeakProtocolVersion(version));
+    }
+
+    fn queued(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+}
+
+/// Write what's asked for and read what comes in, until the connection fails
+/// (returning why) or the [`SerialWorker`] is dropped
+fn work(
+    transport: &mut dyn Transport,
+    requests: &Receiver<WorkerRequest>,
+    lines: &Sender<Result<String, ClientError>>,
+    queued: &AtomicUsize,
+) -> Result<(), ClientError> {
+    let mut waiting = false;
+    loop {
+        // Waiting for something to write while there's nothing to read
+        let mut request = match waiting {
+            true => match requests.recv_timeout(SERIAL_WORKER_IDLE) {
+                Ok(request) => Some(request),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            },
+            false => None,
+        };
+        loop {
+            match request.take() {
+                Some(WorkerRequest::Write(line)) => {
+                    let result = transport.write_line(&line);
+                    queued.fetch_sub(1_usize, Ordering::Relaxed);
+                    result?
+                }
+                Some(WorkerRequest::SpeakProtocolVersion(version)) => {
+                    transport.speak_protocol_version(version)
+                }
+                None => (),
+            }
+            request = match requests.try_recv() {
+                Ok(request) => Some(request),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Ok(()),
+            };
+        }
+
+        waiting = true;
+        while let Some(line) = transport.read_line()? {
+            waiting = false;
+            if lines.send(Ok(line)).is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+/*!
+ * Where and how many times the car has run this session, for naming runs
+ * without typing on the touch screen
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::ClientError;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{create_dir_all, read_to_string, write},
+    path::{Path, PathBuf},
+};
+
+// Constants
+/// The session file inside of the XDG config directory, next to the config
+pub const SESSION_FILE_NAME: &str = "chemistry-car-controller/session.toml";
+
+/***** Session *****/
+
+/// What's kept of a session across restarts, e.g.
+///
+/// ```toml
+/// venue = "gym"
+///
+/// [directories]
+/// gym = "/home/pi/runs/gym"
+/// ```
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct SessionFile {
+    venue: String,
+    directories: BTreeMap<String, PathBuf>,
+}
+
+/// The session runs are named after, with the venue and where runs were last
+/// saved at each venue kept across restarts
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct Session {
+    /// Where the car is being run, e.g. "gym", or empty if it wasn't said
+    pub venue: String,
+    /// Runs started since the GUI started
+    pub runs: u32,
+    /// The directory runs were last saved to, by venue
+    pub directories: BTreeMap<String, PathBuf>,
+    /// Where the session is saved, or `None` to only keep it in memory
+    pub path: Option<PathBuf>,
+}
+impl Session {
+    /// `session.toml` in the XDG config directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|config_dir| config_dir.join(SESSION_FILE_NAME))
+    }
+
+    /// Load the session saved at a path, if any
+    ///
+    /// Like the presets, anything wrong with the file is handed back
+    /// alongside a new session, which replaces the file once it changes.
+    pub fn load(path: Option<PathBuf>) -> (Self, Option<ClientError>) {
+        let Some(path) = path else {
+            return (Self::default(), None);
+        };
+        let (file, error) = match path.exists() {
+            true => match Self::read(&path) {
+                Ok(file) => (file, None),
+                Err(e) => (SessionFile::default(), Some(e)),
+            },
+            false => (SessionFile::default(), None),
+        };
+        let session = Self {
+            venue: file.venue,
+            directories: file.directories,
+            path: Some(path),
+            ..Default::default()
+        };
+        (session, error)
+    }
+
+    fn read(path: &Path) -> Result<SessionFile, ClientError> {
+        let contents = read_to_string(path)
+            .map_err(|e| ClientError::Session(format!("Failed to read {}: {e}", path.display())))?;
+        toml::from_str(&contents)
+            .map_err(|e| ClientError::Session(e.to_string().trim_end().to_owned()))
+    }
+
+    /// The name for the latest run, without an extension, e.g.
+    /// `2026-10-16_gym_500cm_run03`
+    ///
+    /// Unlike automatically saved runs, these are never deleted to make room.
+    pub fn run_name(&self, date: &str, distance: f64) -> String {
+        let venue = venue_slug(&self.venue);
+        match venue.is_empty() {
+            true => format!("{date}_{distance:.0}cm_run{:02}", self.runs),
+            false => format!("{date}_{venue}_{distance:.0}cm_run{:02}", self.runs),
+        }
+    }
+
+    /// The directory runs were last saved to at this venue, if any
+    pub fn directory(&self) -> Option<&Path> {
+        self.directories
+            .get(self.venue.trim())
+            .map(PathBuf::as_path)
+    }
+
+    /// Remember the directory a run was saved to for this venue, if it has
+    /// one
+    pub fn remember_directory(&mut self, directory: PathBuf) -> Result<(), ClientError> {
+        let venue = self.venue.trim();
+        if venue.is_empty() || self.directories.get(venue) == Some(&directory) {
+            return Ok(());
+        }
+        self.directories.insert(venue.to_owned(), directory);
+        self.save()
+    }
+
+    /// Save the venue and directories, if the session has a path
+    pub fn save(&self) -> Result<(), ClientError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = SessionFile {
+            venue: self.venue.clone(),
+            directories: self.directories.clone(),
+        };
+        let contents =
+            toml::to_string_pretty(&file).map_err(|e| ClientError::Session(e.to_string()))?;
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).map_err(|e| {
+                ClientError::Session(format!("Failed to create {}: {e}", parent.display()))
+            })?;
+        }
+        write(path, contents)
+            .map_err(|e| ClientError::Session(format!("Failed to write {}: {e}", path.display())))
+    }
+}
+
+/***** Helper functions *****/
+
+/// A venue as it goes in a file name, only with what the file picker's keypad
+/// can type, e.g. `Main Gym` as `main-gym`
+fn venue_slug(venue: &str) -> String {
+    venue
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
@@ -0,0 +1,499 @@
+/*!
+ * An append-only log of each session (every error shown, the connection coming
+ * and going, and every start, stop, and finish), kept on disk to look back on
+ * after the GUI was closed, and a window to look back on them
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{
+    clock::*, journal::*, recovery::Recovery, shared::*, stop_reason::StopReason, ErrorData,
+};
+use bindings::ClientError;
+use chrono::{DateTime, Local, Utc};
+use csv::Writer;
+#[cfg(feature = "ui")]
+use eframe::epaint::vec2;
+#[cfg(feature = "ui")]
+use egui::{Button, Color32, Context, Label, RichText, ScrollArea, Window};
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str as serde_from_str, to_string as serde_to_string};
+use std::{
+    fs::{create_dir_all, read_dir, read_to_string, remove_file, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+// Constants
+#[cfg(feature = "ui")]
+const STEP_BUTTON_SIZE: [f32; 2] = [40., 30.];
+
+/***** Session log *****/
+
+/// What a session was started with
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct SessionMetadata {
+    /// Of the GUI
+    pub version: String,
+    /// Where the car was being run, or empty if it wasn't said
+    pub venue: String,
+    /// The serial port or TCP address of the car, if known, or `None` when
+    /// it's reached through a spawned bridge
+    pub port: Option<String>,
+}
+
+/// Something worth looking back on after the GUI was closed
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionEvent {
+    /// The GUI started
+    Opened(SessionMetadata),
+    /// An error was shown
+    Error {
+        message: String,
+        /// What was offered to fix it
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        recovery: Option<Recovery>,
+    },
+    /// The serial port (or TCP connection) to the car came or went
+    Serial { connected: bool },
+    /// The bridge connected to the car or lost it
+    Bluetooth { connected: bool },
+    /// A run was started or stopped (and why, if it was said), or an error
+    /// was fixed
+    Action(UserAction),
+    /// The run finished, with how far the car went in centimeters
+    Finished { distance: f64 },
+}
+impl SessionEvent {
+    /// A one line description
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Opened(metadata) => format!(
+                "Opened version {}{}{}",
+                metadata.version,
+                match metadata.venue.is_empty() {
+                    true => String::new(),
+                    false => format!(" at {}", metadata.venue),
+                },
+                metadata
+                    .port
+                    .as_ref()
+                    .map(|port| format!(" on {port}"))
+                    .unwrap_or_default()
+            ),
+            Self::Error { message, .. } => format!("Error: {message}"),
+            Self::Serial { connected: true } => "Serial port connected".to_owned(),
+            Self::Serial { connected: false } => "Serial port disconnected".to_owned(),
+            Self::Bluetooth { connected: true } => "Bridge connected to the car".to_owned(),
+            Self::Bluetooth { connected: false } => "Bridge lost the car".to_owned(),
+            Self::Action(action) => JournalEntryKind::Action(action.clone()).describe(),
+            Self::Finished { distance } => format!("Finished at {distance:.1}cm"),
+        }
+    }
+
+    /// What kind of event it is, as it's written to the log, e.g. `error`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Opened(_) => "opened",
+            Self::Error { .. } => "error",
+            Self::Serial { .. } => "serial",
+            Self::Bluetooth { .. } => "bluetooth",
+            Self::Action(_) => "action",
+            Self::Finished { .. } => "finished",
+        }
+    }
+
+    /// Whether to keep it, of the user actions in the journal
+    fn logs_action(action: &UserAction) -> bool {
+        matches!(
+            action,
+            UserAction::Start { .. }
+                | UserAction::Stop
+                | UserAction::EmergencyStop
+                | UserAction::Recover(_)
+                | UserAction::GiveStopReason(_)
+                | UserAction::Calibrate(_)
+        )
+    }
+}
+
+/// One line of a session log
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct SessionLogEntry {
+    /// Unix time in seconds
+    pub time: f64,
+    pub event: SessionEvent,
+}
+impl SessionLogEntry {
+    /// A one line description, with the time in a time zone
+    pub fn describe(&self, timezone: &DisplayTimezone) -> String {
+        let time = timezone.format_unix(self.time, "%H:%M:%S");
+        format!("{time} {}", self.event.describe())
+    }
+}
+
+/// Appends what happens this session to its own file in a directory, one JSON
+/// object per line, named after when it started (e.g.
+/// `2026-10-16_14-03-22.jsonl`)
+///
+/// Like the wire log, every line is written straight to the file, so closing
+/// or crashing loses nothing. Only the latest [`RETAINED_SESSION_LOGS`] are
+/// kept.
+pub struct SessionLogger {
+    pub path: PathBuf,
+    file: File,
+    /// When the newest error logged was shown, so each is only logged once
+    last_error_time: Option<DateTime<Utc>>,
+    serial_connected: Option<bool>,
+    bluetooth_connected: Option<bool>,
+    /// How many journal entries have been looked at
+    journal_entries: usize,
+}
+impl SessionLogger {
+    /// Start a new session log in a directory, creating it if needed
+    pub fn open(directory: &Path, metadata: SessionMetadata) -> Result<Self, ClientError> {
+        create_dir_all(directory).map_err(|e| {
+            ClientError::SessionLog(format!("Failed to create {}: {e}", directory.display()))
+        })?;
+        let name = Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
+        let mut path = directory.join(format!("{name}.jsonl"));
+        let mut number = 2_u32;
+        while path.exists() {
+            path = directory.join(format!("{name}_{number}.jsonl"));
+            number += 1_u32;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| {
+                ClientError::SessionLog(format!("Failed to open {}: {e}", path.display()))
+            })?;
+        let mut session_log = Self {
+            path,
+            file,
+            last_error_time: None,
+            serial_connected: None,
+            bluetooth_connected: None,
+            journal_entries: 0_usize,
+        };
+        session_log.log(unix_now(), SessionEvent::Opened(metadata))?;
+
+        let session_logs = session_logs(directory);
+        let excess = session_logs.len().saturating_sub(RETAINED_SESSION_LOGS);
+        for old_session_log in session_logs.iter().take(excess) {
+            remove_file(old_session_log).map_err(|e| {
+                ClientError::SessionLog(format!(
+                    "Failed to remove {}: {e}",
+                    old_session_log.display()
+                ))
+            })?;
+        }
+
+        Ok(session_log)
+    }
+
+    /// Append something that happened at a Unix time in seconds
+    pub fn log(&mut self, time: f64, event: SessionEvent) -> Result<(), ClientError> {
+        let line = serde_to_string(&SessionLogEntry { time, event })
+            .map_err(|e| ClientError::SessionLog(e.to_string()))?;
+        writeln!(self.file, "{line}").map_err(|e| {
+            ClientError::SessionLog(format!("Failed to write to {}: {e}", self.path.display()))
+        })
+    }
+
+    /// Log the errors shown, user actions, and connection changes since last
+    /// observed
+    pub fn observe(
+        &mut self,
+        errors: &[ErrorData],
+        journal: &Journal,
+        serial_connected: bool,
+        bluetooth_connected: bool,
+    ) -> Result<(), ClientError> {
+        for error in errors.iter() {
+            if self.last_error_time.is_some_and(|time| error.time <= time) {
+                continue;
+            }
+            self.last_error_time = Some(error.time);
+            self.log(
+                error.time.timestamp_millis() as f64 / 1000.0,
+                SessionEvent::Error {
+                    message: error.error.to_string(),
+                    recovery: error.recovery,
+                },
+            )?;
+        }
+
+        let entries = journal.entries();
+        for entry in entries.iter().skip(self.journal_entries) {
+            match &entry.kind {
+                JournalEntryKind::Action(action) if SessionEvent::logs_action(action) => {
+                    self.log(entry.time, SessionEvent::Action(action.clone()))?
+                }
+                _ => (),
+            }
+        }
+        self.journal_entries = entries.len();
+
+        if self.serial_connected != Some(serial_connected) {
+            self.serial_connected = Some(serial_connected);
+            self.log(
+                unix_now(),
+                SessionEvent::Serial {
+                    connected: serial_connected,
+                },
+            )?;
+        }
+        if self.bluetooth_connected != Some(bluetooth_connected) {
+            // Not being connected to begin with isn't worth logging
+            if self.bluetooth_connected.is_some() || bluetooth_connected {
+                self.log(
+                    unix_now(),
+                    SessionEvent::Bluetooth {
+                        connected: bluetooth_connected,
+                    },
+                )?;
+            }
+            self.bluetooth_connected = Some(bluetooth_connected);
+        }
+
+        Ok(())
+    }
+}
+
+/// Read every entry of a session log
+pub fn read_session_log(path: &Path) -> Result<Vec<SessionLogEntry>, ClientError> {
+    let contents = read_to_string(path)
+        .map_err(|e| ClientError::SessionLog(format!("Failed to read {}: {e}", path.display())))?;
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            serde_from_str(line)
+                .map_err(|e| ClientError::SessionLog(format!("Line {}: {e}", idx + 1_usize)))
+        })
+        .collect()
+}
+
+/// Every session log in a directory, oldest first
+pub fn session_logs(directory: &Path) -> Vec<PathBuf> {
+    let mut session_logs = read_dir(directory)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.extension()
+                        .is_some_and(|extension| extension == "jsonl")
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    session_logs.sort();
+    session_logs
+}
+
+/// Write the entries of a session log as CSV, with the Unix time in seconds,
+/// the kind of event, and its description
+pub fn write_session_csv<W: Write>(
+    entries: &[SessionLogEntry],
+    writer: W,
+) -> Result<(), ClientError> {
+    let mut csv_writer = Writer::from_writer(writer);
+    let error = |e: csv::Error| ClientError::CSV(e.to_string());
+    csv_writer
+        .write_record(["time", "event", "description"])
+        .map_err(error)?;
+    for entry in entries.iter() {
+        csv_writer
+            .write_record([
+                entry.time.to_string(),
+                entry.event.kind().to_owned(),
+                entry.event.describe(),
+            ])
+            .map_err(error)?;
+    }
+    csv_writer
+        .flush()
+        .map_err(|e| ClientError::CSV(e.to_string()))
+}
+
+/***** Runs of a session *****/
+
+/// How a run in a session log ended
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionRunResult {
+    /// How far the car went, in centimeters
+    Finished(f64),
+    Stopped,
+    EmergencyStop,
+    /// Nothing was logged for how it ended, e.g. the GUI was closed mid run
+    Unknown,
+}
+impl SessionRunResult {
+    /// A few words, e.g. `Finished at 297.3cm`
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Finished(distance) => format!("Finished at {distance:.1}cm"),
+            Self::Stopped => "Stopped".to_owned(),
+            Self::EmergencyStop => "Emergency stop".to_owned(),
+            Self::Unknown => "Unknown".to_owned(),
+        }
+    }
+}
+
+/// A run started during a session, as far as its session log says
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SessionRun {
+    /// Unix time in seconds it was started at
+    pub time: f64,
+    /// Centimeters
+    pub target: f64,
+    pub result: SessionRunResult,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<StopReason>,
+}
+
+/// The runs started in a session log, in order
+///
+/// A run ends however it's first logged to, as stopping after it's finished
+/// changes nothing, while a stop reason goes to the run before it.
+pub fn session_runs(entries: &[SessionLogEntry]) -> Vec<SessionRun> {
+    let mut runs = Vec::<SessionRun>::new();
+    for entry in entries.iter() {
+        let result = match &entry.event {
+            SessionEvent::Action(UserAction::Start { distance, .. }) => {
+                runs.push(SessionRun {
+                    time: entry.time,
+                    target: *distance,
+                    result: SessionRunResult::Unknown,
+                    stop_reason: None,
+                });
+                continue;
+            }
+            SessionEvent::Action(UserAction::GiveStopReason(reason)) => {
+                if let Some(run) = runs.last_mut() {
+                    run.stop_reason = Some(reason.clone());
+                }
+                continue;
+            }
+            SessionEvent::Finished { distance } => SessionRunResult::Finished(*distance),
+            SessionEvent::Action(UserAction::Stop) => SessionRunResult::Stopped,
+            SessionEvent::Action(UserAction::EmergencyStop) => SessionRunResult::EmergencyStop,
+            _ => continue,
+        };
+        if let Some(run) = runs
+            .last_mut()
+            .filter(|run| run.result == SessionRunResult::Unknown)
+        {
+            run.result = result;
+        }
+    }
+    runs
+}
+
+/***** Session log viewer *****/
+
+#[cfg(feature = "ui")]
+/// A window to look back on previous sessions, one at a time
+#[derive(Default)]
+pub struct SessionLogViewer {
+    pub open: bool,
+    /// Every session log but the current one, newest first
+    pub session_logs: Vec<PathBuf>,
+    /// Of the session log being looked at
+    pub idx: usize,
+    /// The session log being looked at and what was read from it, as reading
+    /// it every frame is not cheap
+    entries: Option<(usize, Result<Vec<SessionLogEntry>, String>)>,
+}
+#[cfg(feature = "ui")]
+impl SessionLogViewer {
+    /// Start at the most recent session before the current one
+    pub fn new(directory: &Path, current: Option<&Path>) -> Self {
+        let mut session_logs = session_logs(directory);
+        session_logs.retain(|path| Some(path.as_path()) != current);
+        session_logs.reverse();
+        Self {
+            open: true,
+            session_logs,
+            idx: 0_usize,
+            entries: None,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context, timezone: &DisplayTimezone) {
+        let Some(path) = self.session_logs.get(self.idx) else {
+            self.entries = None;
+            let mut open = true;
+            Window::new("Previous session")
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label("No previous sessions");
+                    if ui.add_sized([60., 30.], Button::new("Close")).clicked() {
+                        open = false;
+                    }
+                });
+            self.open = open;
+            return;
+        };
+        if self.entries.as_ref().map(|(idx, _)| *idx) != Some(self.idx) {
+            let entries = read_session_log(path).map_err(|e| e.to_string());
+            self.entries = Some((self.idx, entries));
+        }
+        // Safety: just set
+        let (_, entries) = self.entries.as_ref().unwrap();
+
+        let mut idx = self.idx;
+        let mut open = true;
+        Window::new("Previous session")
+            .resizable(false)
+            .default_size(vec2(400.0, 260.0))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.add_sized([60., 30.], Button::new("Close")).clicked() {
+                        open = false;
+                    }
+                    // Newest first, so older is further along
+                    if ui.add_sized(STEP_BUTTON_SIZE, Button::new("<")).clicked() {
+                        idx = (idx + 1).min(self.session_logs.len() - 1);
+                    }
+                    if ui.add_sized(STEP_BUTTON_SIZE, Button::new(">")).clicked() {
+                        idx = idx.saturating_sub(1);
+                    }
+                    ui.label(
+                        path.file_stem()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_default(),
+                    );
+                });
+                ui.separator();
+
+                let entries = match entries {
+                    Ok(entries) => entries,
+                    Err(e) => {
+                        ui.add(Label::new(RichText::new(e).color(Color32::RED)).wrap(true));
+                        return;
+                    }
+                };
+                ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for entry in entries.iter() {
+                        let text = RichText::new(entry.describe(timezone));
+                        let text = match entry.event {
+                            SessionEvent::Error { .. } => text.color(Color32::RED),
+                            _ => text,
+                        };
+                        ui.add(Label::new(text).wrap(true));
+                    }
+                });
+            });
+
+        self.idx = idx;
+        self.open = open;
+    }
+}
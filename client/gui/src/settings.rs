@@ -0,0 +1,275 @@
+/*!
+ * Carrying the whole setup over to another computer as one versioned file
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{config::Config, export::RunFormat, shared::*};
+use bindings::ClientError;
+use serde::{Deserialize, Serialize};
+use smart_default::SmartDefault;
+use std::{
+    f64::consts::PI,
+    fs::{read_to_string, write},
+    path::{Path, PathBuf},
+};
+use toml::{Table, Value};
+
+// Constants
+/// Bumped whenever an older controller couldn't make sense of a newer file
+pub const SETTINGS_VERSION: u32 = 1;
+
+/***** Settings file *****/
+
+/// What is tuned in the GUI before a run, which the config file doesn't have
+#[derive(Serialize, Deserialize, SmartDefault, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct RunSettings {
+    pub reverse_braking: bool,
+    pub auto_stop: bool,
+    /// Percentage of the distance to stop at
+    #[default(DEFAULT_AUTO_STOP_PERCENTAGE)]
+    pub auto_stop_percentage: f64,
+    pub speed_limit: bool,
+    /// Centimeters per second
+    #[default(DEFAULT_MAX_VELOCITY)]
+    pub max_velocity: f64,
+    pub override_wheel_circumference: bool,
+    /// Centimeters, as measured
+    #[default(DEFAULT_WHEEL_DIAMETER * PI)]
+    pub wheel_circumference: f64,
+    pub recompute_distance: bool,
+    /// Centimeters
+    #[default(DEFAULT_SPLIT_INTERVAL)]
+    pub split_interval: f64,
+    pub export_format: RunFormat,
+    pub bluetooth_address: Option<String>,
+}
+
+/// Everything set up on one computer, e.g.
+///
+/// ```toml
+/// version = 1
+///
+/// [config.car]
+/// wheel_diameter = 6.4
+///
+/// [run]
+/// auto_stop = true
+/// ```
+///
+/// Like the config file, anything left out is the default
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SettingsFile {
+    pub version: u32,
+    #[serde(default)]
+    pub config: Config,
+    #[serde(default)]
+    pub run: RunSettings,
+}
+impl SettingsFile {
+    pub fn new(config: Config, run: RunSettings) -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            config,
+            run,
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), ClientError> {
+        let contents =
+            toml::to_string_pretty(self).map_err(|e| ClientError::Settings(e.to_string()))?;
+        write(path, contents)
+            .map_err(|e| ClientError::Settings(format!("Failed to write {}: {e}", path.display())))
+    }
+
+    /// Read and validate a settings file
+    pub fn read(path: &Path) -> Result<Self, ClientError> {
+        let contents = read_to_string(path).map_err(|e| {
+            ClientError::Settings(format!("Failed to read {}: {e}", path.display()))
+        })?;
+        Self::parse(&contents)
+    }
+
+    /// Parse and validate a settings file
+    ///
+    /// The version is checked before anything else, so a file from a newer
+    /// controller is refused as that rather than as a pile of unknown fields
+    pub fn parse(contents: &str) -> Result<Self, ClientError> {
+        let table = toml::from_str::<Table>(contents)
+            .map_err(|e| ClientError::Settings(e.to_string().trim_end().to_owned()))?;
+        match table.get("version").and_then(Value::as_integer) {
+            None => {
+                return Err(ClientError::Settings(
+                    "No version, this isn't a settings file".to_owned(),
+                ))
+            }
+            Some(version) if version > SETTINGS_VERSION as i64 => {
+                return Err(ClientError::Settings(format!(
+                    "Made by a newer controller (version {version}, this one reads up to \
+                     {SETTINGS_VERSION})"
+                )))
+            }
+            Some(_) => (),
+        }
+
+        Self::from_table(table)
+    }
+
+    /// Every setting that differs from another settings file
+    pub fn conflicts(&self, other: &Self) -> Result<Vec<SettingConflict>, ClientError> {
+        let mut ours = vec![];
+        flatten("", &self.to_table()?, &mut ours);
+        let mut theirs = vec![];
+        flatten("", &other.to_table()?, &mut theirs);
+
+        let mut keys = ours
+            .iter()
+            .chain(theirs.iter())
+            .map(|(key, _)| key.clone())
+            .filter(|key| key != "version")
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+
+        let find = |values: &[(String, Value)], key: &str| {
+            values
+                .iter()
+                .find(|(value_key, _)| value_key == key)
+                .map(|(_, value)| value.clone())
+        };
+        Ok(keys
+            .into_iter()
+            .filter_map(|key| {
+                let current = find(&ours, &key);
+                let imported = find(&theirs, &key);
+                (current != imported).then_some(SettingConflict {
+                    key,
+                    current,
+                    imported,
+                })
+            })
+            .collect())
+    }
+
+    /// Take another settings file, except for the settings whose conflicts
+    /// are kept as they are
+    pub fn merge(&self, imported: &Self, kept: &[&SettingConflict]) -> Result<Self, ClientError> {
+        let mut table = imported.to_table()?;
+        for conflict in kept {
+            set(&mut table, &conflict.key, conflict.current.clone());
+        }
+        table.insert(
+            "version".to_owned(),
+            Value::Integer(SETTINGS_VERSION as i64),
+        );
+        Self::from_table(table)
+    }
+
+    fn to_table(&self) -> Result<Table, ClientError> {
+        match Value::try_from(self).map_err(|e| ClientError::Settings(e.to_string()))? {
+            Value::Table(table) => Ok(table),
+            _ => unreachable!("A struct is always a table"),
+        }
+    }
+
+    fn from_table(table: Table) -> Result<Self, ClientError> {
+        let settings = Value::Table(table)
+            .try_into::<Self>()
+            .map_err(|e| ClientError::Settings(e.to_string().trim_end().to_owned()))?;
+        settings
+            .config
+            .validate()
+            .map_err(|e| ClientError::Settings(e.to_string()))?;
+        Ok(settings)
+    }
+}
+
+/// One setting that differs between the current settings and an imported
+/// file, `None` where it isn't set
+#[derive(Clone, PartialEq, Debug)]
+pub struct SettingConflict {
+    /// Dotted, e.g. `config.car.wheel_diameter`
+    pub key: String,
+    pub current: Option<Value>,
+    pub imported: Option<Value>,
+}
+
+/// How a setting is shown when picking through conflicts
+pub fn setting_text(value: Option<&Value>) -> String {
+    value.map_or_else(|| "nothing".to_owned(), Value::to_string)
+}
+
+/// Every setting in a table, with dotted keys
+fn flatten(prefix: &str, table: &Table, values: &mut Vec<(String, Value)>) {
+    for (key, value) in table.iter() {
+        let key = match prefix.is_empty() {
+            true => key.clone(),
+            false => format!("{prefix}.{key}"),
+        };
+        match value {
+            Value::Table(table) => flatten(&key, table, values),
+            value => values.push((key, value.clone())),
+        }
+    }
+}
+
+/// Set a setting by its dotted key, or remove it
+fn set(table: &mut Table, key: &str, value: Option<Value>) {
+    match key.split_once('.') {
+        Some((section, rest)) => {
+            if let Some(Value::Table(section)) = table.get_mut(section) {
+                set(section, rest, value);
+            } else if let Some(value) = value {
+                let mut section_table = Table::new();
+                set(&mut section_table, rest, Some(value));
+                table.insert(section.to_owned(), Value::Table(section_table));
+            }
+        }
+        None => match value {
+            Some(value) => {
+                table.insert(key.to_owned(), value);
+            }
+            None => {
+                table.remove(key);
+            }
+        },
+    }
+}
+
+/***** Importing *****/
+
+/// A settings file being imported, waiting on which side of every conflict
+/// to go with
+pub struct SettingsImport {
+    pub path: PathBuf,
+    pub imported: SettingsFile,
+    pub conflicts: Vec<SettingConflict>,
+    /// Whether to keep the current setting, for each conflict
+    pub keep: Vec<bool>,
+}
+impl SettingsImport {
+    pub fn read(path: PathBuf, current: &SettingsFile) -> Result<Self, ClientError> {
+        let imported = SettingsFile::read(&path)?;
+        let conflicts = current.conflicts(&imported)?;
+        Ok(Self {
+            path,
+            imported,
+            keep: vec![false; conflicts.len()],
+            conflicts,
+        })
+    }
+
+    /// The settings to use, with the chosen side of every conflict
+    pub fn resolve(&self, current: &SettingsFile) -> Result<SettingsFile, ClientError> {
+        let kept = self
+            .conflicts
+            .iter()
+            .zip(self.keep.iter())
+            .filter_map(|(conflict, keep)| keep.then_some(conflict))
+            .collect::<Vec<_>>();
+        current.merge(&self.imported, &kept)
+    }
+}
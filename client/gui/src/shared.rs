@@ -3,7 +3,236 @@
  * Created by sheepy0125 | MIT license | 2023-02-23
  */
 
+use bindings::Command;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
 pub const WIDTH: f32 = 480.0;
 pub const HEIGHT: f32 = 320.0;
+/// The smallest screen everything still fits on
+pub const MIN_SCREEN_SIZE: [f32; 2] = [320.0, 240.0];
 pub const SERIAL_DELAY_TIME: f64 = 0.10;
+/// Seconds the poll interval can be tuned between from the settings window
+pub const MIN_POLL_INTERVAL: f64 = 0.01;
+pub const MAX_POLL_INTERVAL: f64 = 1.0;
+/// Append a CRC16 to every request so the server can catch corrupted frames
+pub const SEND_CHECKSUMS: bool = true;
+/// How long to wait for a response before sending a request again
+pub const REQUEST_TIMEOUT_SECONDS: f64 = 1.0;
+/// How many times a request is sent before giving up on it
+pub const MAX_REQUEST_ATTEMPTS: usize = 3;
+/// The longest request timeout (in seconds) and the most attempts that can be
+/// tuned to from the settings window
+pub const MAX_TUNED_REQUEST_TIMEOUT: f64 = 10.0;
+pub const MAX_TUNED_REQUEST_ATTEMPTS: usize = 10;
+/// How long the bridge scans for Bluetooth devices before answering
+pub const BLUETOOTH_SCAN_SECONDS: f64 = 5.0;
+/// Seconds to wait before the first attempt at reconnecting to a lost serial
+/// port, doubling after every failed attempt
+pub const RECONNECT_INITIAL_DELAY_SECONDS: f64 = 0.5;
+pub const RECONNECT_MAX_DELAY_SECONDS: f64 = 8.0;
+/// The longest (in seconds) what was left on a connection is thrown away for
+/// after connecting, before giving up and starting anyway
+pub const DEFAULT_SYNC_TIMEOUT: f64 = 1.0;
+/// Requests that are asked for again when their response comes in corrupted,
+/// as sending them twice does no harm (unlike starting or stopping)
+pub const RESENDABLE_COMMANDS: [Command; 5] = [
+    Command::Ping,
+    Command::StaticStatus,
+    Command::Status,
+    Command::BluetoothStatus,
+    Command::BluetoothMetrics,
+];
+/// Requests for a status, which are never sent while one for the same status
+/// is still on its way or waiting for its response
+pub const POLL_COMMANDS: [Command; 4] = [
+    Command::StaticStatus,
+    Command::Status,
+    Command::BluetoothStatus,
+    Command::BluetoothMetrics,
+];
 pub const MAX_DISTANCE_RANGE_CENTIMETERS: f64 = 1_000.0;
+pub const DEFAULT_NUMBER_OF_MAGNETS: usize = 2;
+pub const CENTIMETERS_PER_INCH: f64 = 2.54;
+pub const INCHES_PER_FOOT: f64 = 12.0;
+/// Centimeters (2.5 inches)
+pub const DEFAULT_WHEEL_DIAMETER: f64 = 6.35;
+/// Centimeters, for the measured wheel circumference
+pub const MIN_WHEEL_CIRCUMFERENCE: f64 = 1.0;
+pub const MAX_WHEEL_CIRCUMFERENCE: f64 = 100.0;
+/// How far past the target (as a percentage of it) the car may go before the
+/// client stops it, if automatic stopping is on
+pub const DEFAULT_AUTO_STOP_PERCENTAGE: f64 = 105.0;
+/// Seconds START has to be pressed again within before the run starts
+pub const DEFAULT_ARMING_COUNTDOWN: f64 = 5.0;
+/// Seconds without a status mid run before the client stops the car itself,
+/// with the server sending them four times a second
+pub const DEFAULT_STATUS_WATCHDOG: f64 = 3.0;
+/// Seconds without a status mid run before where the car probably is starts
+/// being guessed
+pub const ESTIMATE_AFTER_SECONDS: f64 = 1.0;
+/// How far off a guessed position could be, as a fraction of how far the car
+/// would have gone at its last velocity since the latest status
+pub const ESTIMATE_UNCERTAINTY: f64 = 0.25;
+/// Seconds after a run finalizes and is saved before getting ready for the
+/// next one, with 0 leaving it for RESET to be pressed
+pub const DEFAULT_AUTO_RESET: f64 = 0.0;
+/// Percent of charge left the battery is warned about under
+pub const DEFAULT_LOW_BATTERY_CHARGE: f64 = 20.0;
+/// Seconds the car's clock can be off from ours before it's warned about
+pub const DEFAULT_MAX_CLOCK_OFFSET: f64 = 2.0;
+/// Unix time in seconds (2023-01-01), before which a clock can't have been set
+pub const MIN_PLAUSIBLE_UNIX_TIME: f64 = 1_672_531_200.0;
+/// Centimeters per second
+pub const DEFAULT_MAX_VELOCITY: f64 = 50.0;
+pub const MIN_MAX_VELOCITY: f64 = 5.0;
+pub const MAX_MAX_VELOCITY: f64 = 500.0;
+/// Centimeters between split time markers
+pub const DEFAULT_SPLIT_INTERVAL: f64 = 100.0;
+pub const MIN_SPLIT_INTERVAL: f64 = 10.0;
+/// Seconds of the run shown in the telemetry plot
+pub const DEFAULT_PLOT_WINDOW_SECONDS: f64 = 30.0;
+pub const MIN_PLOT_WINDOW_SECONDS: f64 = 5.0;
+pub const MAX_PLOT_WINDOW_SECONDS: f64 = 600.0;
+/// How far off the server's velocity can be from the one recomputed from the
+/// magnet hits (as a percentage of it) before it's highlighted, on top of a
+/// magnet hit's worth
+pub const VELOCITY_DISCREPANCY_PERCENTAGE: f64 = 10.0;
+/// Screens this narrow or narrower get the touch friendly file picker
+pub const SMALL_SCREEN_WIDTH: f32 = 640.0;
+pub const RECENT_FILES: usize = 5;
+/// Rows shown in the expanded status table, with 0 showing all of them
+pub const DEFAULT_TABLE_ROWS: usize = 0;
+pub const MAX_TABLE_ROWS: usize = 1_000;
+/// Log events kept for the logs panel, dropping the oldest after
+pub const DEFAULT_LOG_EVENTS: usize = 500;
+pub const MAX_LOG_EVENTS: usize = 10_000;
+/// How long a toast is shown for
+pub const TOAST_SECONDS: f64 = 4.0;
+/// How many runs read in full are kept in memory
+pub const RUN_CACHE_CAPACITY: usize = 8;
+/// How many runs are kept in the run history, dropping the oldest after
+pub const MAX_ARCHIVED_RUNS: usize = 20;
+/// Where runs are saved on the Pi
+pub const PI_DATA_DIRECTORY: &str = "/home/pi/runs";
+/// Where runs are saved on desktops, inside the XDG data directory
+pub const DATA_DIRECTORY_NAME: &str = "chemistry-car-controller";
+/// How many automatically named runs to keep around
+pub const RETAINED_RUNS: usize = 200;
+/// Where session logs are kept, inside of the data directory
+pub const SESSION_LOG_DIRECTORY_NAME: &str = "sessions";
+/// How many session logs to keep around
+pub const RETAINED_SESSION_LOGS: usize = 50;
+/// How many statuses of a run are kept in memory, ten minutes' worth at four
+/// a second, before they're downsampled
+pub const DEFAULT_STATUS_HISTORY: usize = 2_400;
+pub const MIN_STATUS_HISTORY: usize = 100;
+/// Where the statuses of a downsampled run are spilled to, inside of the data
+/// directory
+pub const SPILL_FILE_NAME: &str = "spilled-statuses.jsonl";
+/// Megabytes that have to be free in the data directory before logging, with
+/// the oldest runs pruned below it
+pub const DEFAULT_MIN_FREE_MB: u64 = 200;
+/// How many of the latest automatically named runs are never pruned to make
+/// room
+pub const MIN_RETAINED_RUNS: usize = 10;
+pub const BYTES_PER_MB: u64 = 1_000_000;
+pub const DEFAULT_BRIDGE_BINARY: &str = "serial-to-bluetooth";
+
+/// The units distances and speeds are shown in, e.g. `units = "imperial"`
+///
+/// Everything is kept in centimeters whatever it's shown in, so this only
+/// changes what's on the screen and in the CSV headers.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    /// Centimeters, and meters for longer distances
+    #[default]
+    Metric,
+    /// Inches, and feet for longer distances
+    Imperial,
+}
+impl Units {
+    pub const ALL: [Self; 2] = [Self::Metric, Self::Imperial];
+
+    /// Centimeters in these units (centimeters or inches)
+    pub fn from_centimeters(&self, centimeters: f64) -> f64 {
+        match self {
+            Self::Metric => centimeters,
+            Self::Imperial => centimeters / CENTIMETERS_PER_INCH,
+        }
+    }
+
+    /// A distance in these units back in centimeters
+    pub fn to_centimeters(&self, distance: f64) -> f64 {
+        match self {
+            Self::Metric => distance,
+            Self::Imperial => distance * CENTIMETERS_PER_INCH,
+        }
+    }
+
+    /// `cm` or `in`
+    pub fn distance_unit(&self) -> &'static str {
+        match self {
+            Self::Metric => "cm",
+            Self::Imperial => "in",
+        }
+    }
+
+    /// `cm/s` or `in/s`
+    pub fn velocity_unit(&self) -> &'static str {
+        match self {
+            Self::Metric => "cm/s",
+            Self::Imperial => "in/s",
+        }
+    }
+
+    /// The distance unit spelled out, for table headers
+    pub fn distance_name(&self) -> &'static str {
+        match self {
+            Self::Metric => "centimeters",
+            Self::Imperial => "inches",
+        }
+    }
+
+    /// A distance in centimeters in these units, e.g. `12.5in`
+    pub fn format_distance(&self, centimeters: f64, decimals: usize) -> String {
+        format!(
+            "{:.decimals$}{}",
+            self.from_centimeters(centimeters),
+            self.distance_unit()
+        )
+    }
+
+    /// A velocity in centimeters per second in these units, e.g. `4.2in/s`
+    pub fn format_velocity(&self, centimeters_per_second: f64, decimals: usize) -> String {
+        format!(
+            "{:.decimals$}{}",
+            self.from_centimeters(centimeters_per_second),
+            self.velocity_unit()
+        )
+    }
+
+    /// A long distance in centimeters in meters or feet, e.g. `16.40ft`
+    pub fn format_long_distance(&self, centimeters: f64, decimals: usize) -> String {
+        match self {
+            Self::Metric => format!("{:.decimals$}m", centimeters / 100.0),
+            Self::Imperial => format!(
+                "{:.decimals$}ft",
+                self.from_centimeters(centimeters) / INCHES_PER_FOOT
+            ),
+        }
+    }
+}
+impl fmt::Display for Units {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Metric => "cm/m",
+                Self::Imperial => "in/ft",
+            }
+        )
+    }
+}
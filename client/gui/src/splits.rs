@@ -0,0 +1,139 @@
+/*!
+ * Split times at evenly spaced distance markers and distances at the same
+ * point in time, for comparing runs
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{Event, StatusResponse};
+use std::path::PathBuf;
+
+/***** Splits *****/
+
+/// When the car passed a distance marker
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Split {
+    /// Centimeters
+    pub marker: f64,
+    /// Seconds since the run started
+    pub time: f64,
+    /// Average speed since the last marker in centimeters per second
+    pub speed: f64,
+}
+
+/// The split at every `interval` centimeters the car has passed so far
+///
+/// Statuses only come in every so often, so the time a marker was passed is
+/// interpolated between the statuses on either side of it. The run is taken
+/// to have started at the time of the first status minus its runtime. Only
+/// the first time the car passes a marker counts, so backing up over it
+/// doesn't make a new split.
+pub fn split_times(statuses: &[Event<StatusResponse>], interval: f64) -> Vec<Split> {
+    let mut splits: Vec<Split> = vec![];
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
+    if !(interval > 0.0) {
+        return splits;
+    }
+    let start_time = match start_time(statuses) {
+        Some(start_time) => start_time,
+        None => return splits,
+    };
+
+    // Where the car was at the last status, starting from the start line
+    let (mut last_time, mut last_distance) = (0.0, 0.0);
+    for status in statuses.iter() {
+        let time = status.metadata.time - start_time;
+        let distance = status.value.distance.distance;
+        loop {
+            let marker = interval * (splits.len() + 1) as f64;
+            if distance < marker {
+                break;
+            }
+            let marker_time = last_time
+                + (marker - last_distance) / (distance - last_distance) * (time - last_time);
+            let previous_time = splits.last().map(|split| split.time).unwrap_or(0.0);
+            let speed = match marker_time > previous_time {
+                true => interval / (marker_time - previous_time),
+                // Passed more than one marker before any time went by (by the
+                // runtime being only to the second), so go by the server
+                false => status.value.distance.velocity,
+            };
+            splits.push(Split {
+                marker,
+                time: marker_time,
+                speed,
+            });
+        }
+        (last_time, last_distance) = (time, distance);
+    }
+
+    splits
+}
+
+/// How much sooner (negative) or later (positive) each split was than the
+/// same split in another run, for as many splits as both runs have
+pub fn split_deltas(splits: &[Split], other: &[Split]) -> Vec<f64> {
+    splits
+        .iter()
+        .zip(other.iter())
+        .map(|(split, other)| split.time - other.time)
+        .collect()
+}
+
+/***** Baseline *****/
+
+/// A stored run chosen to compare live runs against
+pub struct Baseline {
+    pub path: PathBuf,
+    pub statuses: Vec<Event<StatusResponse>>,
+}
+
+/// How far ahead (positive) or behind (negative) a run is of a baseline run,
+/// in centimeters, comparing where each was the same amount of time into the
+/// run
+///
+/// `None` if either run has no statuses yet. Past the end of the baseline, it
+/// is taken to have stayed where it ended.
+pub fn baseline_delta(
+    statuses: &[Event<StatusResponse>],
+    baseline: &[Event<StatusResponse>],
+) -> Option<f64> {
+    let last = statuses.last()?;
+    let time = last.metadata.time - start_time(statuses)?;
+    Some(last.value.distance.distance - distance_at(baseline, time)?)
+}
+
+/// Where the car was `time` seconds into a run, interpolated between the
+/// statuses on either side
+pub fn distance_at(statuses: &[Event<StatusResponse>], time: f64) -> Option<f64> {
+    let start_time = start_time(statuses)?;
+
+    let (mut last_time, mut last_distance) = (0.0, 0.0);
+    for status in statuses.iter() {
+        let status_time = status.metadata.time - start_time;
+        let distance = status.value.distance.distance;
+        if status_time >= time {
+            return Some(match status_time > last_time {
+                true => {
+                    last_distance
+                        + (time - last_time) / (status_time - last_time)
+                            * (distance - last_distance)
+                }
+                false => distance,
+            });
+        }
+        (last_time, last_distance) = (status_time, distance);
+    }
+
+    Some(last_distance)
+}
+
+/***** Helper functions *****/
+
+/// When a run started, as the time of its first status minus its runtime
+pub fn start_time(statuses: &[Event<StatusResponse>]) -> Option<f64> {
+    statuses
+        .first()
+        .map(|first| first.metadata.time - first.value.runtime as f64)
+}
@@ -0,0 +1,308 @@
+/*!
+ * Checking the computer is set up to talk to the car before starting, with
+ * what to do about anything that isn't
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs::{read_dir, read_to_string},
+    path::PathBuf,
+};
+#[cfg(unix)]
+use std::{fs::metadata, os::unix::fs::MetadataExt};
+
+// Constants
+/// What USB serial devices are named, e.g. `/dev/ttyACM0`
+const USB_SERIAL_PREFIXES: [&str; 2] = ["ttyACM", "ttyUSB"];
+
+/***** Startup checks *****/
+
+/// Something about the computer that will stop it from talking to the car
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct StartupProblem {
+    pub problem: String,
+    /// What to do about it
+    pub fix: String,
+}
+impl StartupProblem {
+    fn new(problem: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            problem: problem.into(),
+            fix: fix.into(),
+        }
+    }
+}
+impl Display for StartupProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}\n\t{}", self.problem, self.fix)
+    }
+}
+
+/// The common reasons the car can't be reached, checked before they turn
+/// into a failure to open something
+///
+/// Everything is read from files (`/dev`, `/proc`, `/sys`, and `/etc`), so
+/// nothing needs to be run and nothing is changed. Only Linux is checked;
+/// elsewhere nothing is found.
+#[derive(Clone, Debug)]
+pub struct StartupChecks {
+    /// Where the files are looked for, only not `/` for testing
+    pub root: PathBuf,
+    /// Whether to check the car's serial port can be opened
+    pub serial: bool,
+    /// The serial port to check, or `None` for any USB serial port
+    pub port: Option<String>,
+    /// Whether to check Bluetooth is ready, for the bridge
+    pub bluetooth: bool,
+}
+impl StartupChecks {
+    /// Check the car's serial port, when it's plugged in over USB
+    pub fn serial(port: Option<String>) -> Self {
+        Self {
+            root: PathBuf::from("/"),
+            serial: true,
+            port,
+            bluetooth: false,
+        }
+    }
+
+    /// Check Bluetooth, when going through the bridge
+    pub fn bluetooth() -> Self {
+        Self {
+            root: PathBuf::from("/"),
+            serial: false,
+            port: None,
+            bluetooth: true,
+        }
+    }
+
+    /// Everything found wrong
+    pub fn run(&self) -> Vec<StartupProblem> {
+        // Not Linux, so none of the files are there to check
+        if !self.path("/proc/self/status").exists() {
+            return vec![];
+        }
+
+        let mut problems = vec![];
+        if self.serial {
+            problems.append(&mut self.check_serial_port());
+        }
+        if self.bluetooth {
+            problems.append(&mut self.check_bluetooth());
+        }
+        problems
+    }
+
+    /// A path under the root
+    fn path(&self, path: &str) -> PathBuf {
+        self.root.join(path.trim_start_matches('/'))
+    }
+
+    /***** Serial port *****/
+
+    fn check_serial_port(&self) -> Vec<StartupProblem> {
+        let usb_serial_devices = read_dir(self.path("/dev"))
+            .map(|entries| {
+                let mut devices = entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.file_name().to_string_lossy().to_string())
+                    .filter(|name| {
+                        USB_SERIAL_PREFIXES
+                            .iter()
+                            .any(|prefix| name.starts_with(prefix))
+                    })
+                    .map(|name| format!("/dev/{name}"))
+                    .collect::<Vec<_>>();
+                devices.sort();
+                devices
+            })
+            .unwrap_or_default();
+
+        let devices = match &self.port {
+            Some(port) if !self.path(port).exists() => {
+                return vec![StartupProblem::new(
+                    format!("{port} doesn't exist"),
+                    match usb_serial_devices.is_empty() {
+                        true => {
+                            "Plug the car in, with a cable that carries data and not just power"
+                                .to_owned()
+                        }
+                        false => format!(
+                            "Plug the car in, or set `serial.port` in the config to one that \
+                             exists ({})",
+                            usb_serial_devices.join(", ")
+                        ),
+                    },
+                )]
+            }
+            Some(port) => vec![port.clone()],
+            None if usb_serial_devices.is_empty() => {
+                return vec![StartupProblem::new(
+                    "No USB serial devices are plugged in",
+                    "Plug the car in, with a cable that carries data and not just power",
+                )]
+            }
+            None => usb_serial_devices,
+        };
+
+        devices
+            .iter()
+            .filter_map(|device| self.check_access(device))
+            .collect()
+    }
+
+    /// Whether a device can be read and written to, going by its owner, group,
+    /// and mode
+    #[cfg(unix)]
+    fn check_access(&self, device: &str) -> Option<StartupProblem> {
+        let device_metadata = metadata(self.path(device)).ok()?;
+        let status = read_to_string(self.path("/proc/self/status")).ok()?;
+        let ids = |field: &str| -> Vec<u32> {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix(field))
+                .map(|ids| ids.split_whitespace().filter_map(|id| id.parse().ok()))
+                .into_iter()
+                .flatten()
+                .collect()
+        };
+        // The real, effective, saved, and filesystem user IDs, in that order
+        let uid = *ids("Uid:").get(1_usize)?;
+        let groups = ids("Groups:");
+        let gid = *ids("Gid:").get(1_usize)?;
+
+        let mode = device_metadata.mode();
+        let accessible = uid == 0
+            || (uid == device_metadata.uid() && mode & 0o600 == 0o600)
+            || ((gid == device_metadata.gid() || groups.contains(&device_metadata.gid()))
+                && mode & 0o060 == 0o060)
+            || mode & 0o006 == 0o006;
+        if accessible {
+            return None;
+        }
+
+        // Usually `dialout`, or `uucp` on Arch
+        let group_file = read_to_string(self.path("/etc/group")).unwrap_or_default();
+        let group = group_file.lines().find_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let group_id = fields.nth(1_usize)?.parse::<u32>().ok()?;
+            let members = fields.next().unwrap_or_default();
+            (group_id == device_metadata.gid()).then(|| (name.to_owned(), members.to_owned()))
+        });
+        let user = self.user_name(uid);
+
+        Some(match group {
+            Some((group, members))
+                if user
+                    .as_ref()
+                    .is_some_and(|user| members.split(',').any(|member| member == user)) =>
+            {
+                StartupProblem::new(
+                    format!(
+                        "You were added to the {group} group {device} needs, but haven't logged \
+                         in again since"
+                    ),
+                    "Log out and back in (or restart the Pi), and it'll open",
+                )
+            }
+            Some((group, _)) => StartupProblem::new(
+                format!("{device} needs the {group} group, which you aren't in"),
+                format!(
+                    "Run `sudo usermod -aG {group} {}`, then log out and back in",
+                    user.as_deref().unwrap_or("$USER")
+                ),
+            ),
+            None => StartupProblem::new(
+                format!("You don't have permission to open {device}"),
+                format!("Ask whoever set up this computer to give you access to {device}"),
+            ),
+        })
+    }
+
+    /// There's no owner, group, or mode to go by
+    #[cfg(not(unix))]
+    fn check_access(&self, _device: &str) -> Option<StartupProblem> {
+        None
+    }
+
+    /// The name of a user, from `/etc/passwd`
+    #[cfg(unix)]
+    fn user_name(&self, uid: u32) -> Option<String> {
+        read_to_string(self.path("/etc/passwd"))
+            .ok()?
+            .lines()
+            .find_map(|line| {
+                let mut fields = line.split(':');
+                let name = fields.next()?;
+                let user_id = fields.nth(1_usize)?.parse::<u32>().ok()?;
+                (user_id == uid).then(|| name.to_owned())
+            })
+    }
+
+    /***** Bluetooth *****/
+
+    fn check_bluetooth(&self) -> Vec<StartupProblem> {
+        let mut problems = vec![];
+
+        let adapters = read_dir(self.path("/sys/class/bluetooth"))
+            .map(|entries| entries.count())
+            .unwrap_or_default();
+        if adapters == 0 {
+            problems.push(StartupProblem::new(
+                "No Bluetooth adapter was found",
+                "Plug in a Bluetooth adapter, or look through `dmesg` for why the built-in one \
+                 didn't start",
+            ));
+        }
+
+        for rfkill in read_dir(self.path("/sys/class/rfkill"))
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+        {
+            let read = |file: &str| read_to_string(rfkill.join(file)).unwrap_or_default();
+            if read("type").trim() != "bluetooth" {
+                continue;
+            }
+            if read("hard").trim() == "1" {
+                problems.push(StartupProblem::new(
+                    "Bluetooth is turned off by a hardware switch",
+                    "Flip the wireless switch or key on the laptop, or turn Bluetooth back on in \
+                     the BIOS",
+                ));
+            } else if read("soft").trim() == "1" {
+                problems.push(StartupProblem::new(
+                    "Bluetooth is blocked by rfkill",
+                    "Run `rfkill unblock bluetooth` (or turn off airplane mode)",
+                ));
+            }
+        }
+
+        if !self.process_running("bluetoothd") {
+            problems.push(StartupProblem::new(
+                "BlueZ (bluetoothd) isn't running",
+                "Run `sudo systemctl enable --now bluetooth`, installing `bluez` first if \
+                 there's no such service",
+            ));
+        }
+
+        problems
+    }
+
+    /// Whether a process with a name is running, from `/proc`
+    fn process_running(&self, name: &str) -> bool {
+        read_dir(self.path("/proc"))
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().parse::<u32>().is_ok())
+            .any(|entry| {
+                read_to_string(entry.path().join("comm")).is_ok_and(|comm| comm.trim() == name)
+            })
+    }
+}
@@ -0,0 +1,253 @@
+/*!
+ * Keeping a long run's statuses in bounded memory, with every one of them
+ * spilled to disk once there are too many
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{export::*, shared::*};
+use bindings::{ClientError, Event, StatusResponse};
+use std::{
+    fs::{remove_file, File},
+    io::{BufWriter, Write},
+    mem::take,
+    ops::Deref,
+    path::PathBuf,
+};
+use tracing::warn;
+
+/***** Status history *****/
+
+/// Where statuses are spilled to
+#[derive(Debug)]
+struct Spill {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+/// The statuses of the run, at most `capacity` of them in memory
+///
+/// Once there are more than that, every other one is dropped, and from then on
+/// only every other one that comes in is kept (then every fourth, and so on),
+/// so the whole run can still be shown and plotted. The latest status is kept
+/// whatever it is. When it first happens, every status (those to come too) is
+/// spilled to `spill_path` first, so saving the run with
+/// [`StatusHistory::full`] still saves all of them.
+///
+/// It dereferences to the statuses kept in memory, oldest first.
+#[derive(Debug)]
+pub struct StatusHistory {
+    statuses: Vec<Event<StatusResponse>>,
+    pub capacity: usize,
+    /// Where to spill statuses to, or `None` to only downsample them
+    pub spill_path: Option<PathBuf>,
+    /// Why spilling was stopped, if writing to the spill failed
+    pub spill_error: Option<ClientError>,
+    /// Only every `stride`th status that came in is kept
+    stride: usize,
+    /// Statuses that came in since it was last cleared
+    received: usize,
+    /// Whether the latest status is one of every `stride`th, rather than only
+    /// kept for being the latest
+    latest_on_stride: bool,
+    /// Bumped whenever the statuses kept in memory change
+    revision: u64,
+    spill: Option<Spill>,
+}
+impl StatusHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            statuses: vec![],
+            capacity,
+            spill_path: None,
+            spill_error: None,
+            stride: 1_usize,
+            received: 0_usize,
+            latest_on_stride: true,
+            revision: 0_u64,
+            spill: None,
+        }
+    }
+
+    /// Add the latest status, downsampling the statuses if there are too many
+    pub fn push(&mut self, status: Event<StatusResponse>) {
+        if let Some(spill) = &mut self.spill {
+            if let Err(e) = spill_statuses(spill, &[&status]) {
+                self.stop_spilling(e);
+            }
+        }
+
+        let index = self.received;
+        self.received += 1;
+        self.revision += 1;
+        if !self.latest_on_stride {
+            self.statuses.pop();
+        }
+        self.latest_on_stride = index.is_multiple_of(self.stride);
+        self.statuses.push(status);
+
+        while self.statuses.len() > self.capacity.max(MIN_STATUS_HISTORY) {
+            self.downsample();
+        }
+    }
+
+    /// Keep every other status (and the latest), spilling them all first if
+    /// none were dropped yet
+    fn downsample(&mut self) {
+        if self.stride == 1 && self.spill.is_none() && self.spill_error.is_none() {
+            self.start_spilling();
+        }
+
+        // Safety: only called with more statuses than the capacity
+        let latest = self.statuses.pop().unwrap();
+        self.stride *= 2;
+        self.statuses = take(&mut self.statuses).into_iter().step_by(2).collect();
+        self.latest_on_stride = (self.received - 1).is_multiple_of(self.stride);
+        self.statuses.push(latest);
+    }
+
+    /// Spill every status so far, and keep spilling those that come in
+    fn start_spilling(&mut self) {
+        let Some(path) = self.spill_path.clone() else {
+            return;
+        };
+        let result = File::create(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|file| {
+                let mut spill = Spill {
+                    path,
+                    writer: BufWriter::new(file),
+                };
+                let statuses = self.statuses.iter().collect::<Vec<_>>();
+                spill_statuses(&mut spill, &statuses).map(|()| spill)
+            });
+        match result {
+            Ok(spill) => self.spill = Some(spill),
+            Err(e) => self.stop_spilling(e),
+        }
+    }
+
+    /// Stop spilling instead of failing every status from now on
+    fn stop_spilling(&mut self, e: String) {
+        let path = match self.spill.take() {
+            Some(spill) => spill.path,
+            None => self.spill_path.clone().unwrap_or_default(),
+        };
+        self.spill_error = Some(ClientError::DataDirectory(format!(
+            "Failed to spill statuses to {}, so only some of them will be saved: {e}",
+            path.display()
+        )));
+    }
+
+    /// Changes whenever the statuses kept in memory do, so they only need to
+    /// be looked at again when it has
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Whether any statuses were dropped from memory
+    pub fn downsampled(&self) -> bool {
+        self.stride > 1
+    }
+
+    /// Every status that came in, read back from the spill if they were
+    /// downsampled
+    ///
+    /// If spilling failed, only the statuses kept in memory are left.
+    pub fn full(&mut self) -> Result<Vec<Event<StatusResponse>>, ClientError> {
+        let Some(spill) = &mut self.spill else {
+            return Ok(self.statuses.clone());
+        };
+        spill
+            .writer
+            .flush()
+            .map_err(|e| ClientError::DataDirectory(e.to_string()))?;
+        RunFormat::JSONLines.read(&spill.path).map_err(|e| {
+            ClientError::DataDirectory(format!(
+                "Failed to read spilled statuses from {}: {e}",
+                spill.path.display()
+            ))
+        })
+    }
+
+    /// Forget every status, and the spill with them
+    pub fn clear(&mut self) {
+        self.forget();
+    }
+
+    /// Take every status that came in (see [`Self::full`]), leaving it empty
+    ///
+    /// The spill is only removed once they're read back from it; if they
+    /// can't be, only the statuses kept in memory are taken.
+    pub fn take_statuses(&mut self) -> Vec<Event<StatusResponse>> {
+        match self.full() {
+            Ok(statuses) => {
+                self.forget();
+                statuses
+            }
+            Err(e) => {
+                warn!("Only taking the statuses kept in memory: {e}");
+                self.forget()
+            }
+        }
+    }
+
+    /// Forget every status and the spill, returning the statuses kept in
+    /// memory
+    fn forget(&mut self) -> Vec<Event<StatusResponse>> {
+        if let Some(spill) = self.spill.take() {
+            drop(spill.writer);
+            // Only ever there for the run that was going
+            let _ = remove_file(spill.path);
+        }
+        self.spill_error = None;
+        self.stride = 1_usize;
+        self.received = 0_usize;
+        self.latest_on_stride = true;
+        self.revision += 1;
+        take(&mut self.statuses)
+    }
+
+    /// Replace every status, e.g. with a run that was opened
+    pub fn replace(&mut self, statuses: Vec<Event<StatusResponse>>) {
+        self.clear();
+        for status in statuses {
+            self.push(status);
+        }
+    }
+}
+impl Default for StatusHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_STATUS_HISTORY)
+    }
+}
+impl From<Vec<Event<StatusResponse>>> for StatusHistory {
+    fn from(statuses: Vec<Event<StatusResponse>>) -> Self {
+        let mut history = Self::default();
+        history.replace(statuses);
+        history
+    }
+}
+impl Deref for StatusHistory {
+    type Target = [Event<StatusResponse>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.statuses
+    }
+}
+impl Drop for StatusHistory {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/***** Helper functions *****/
+
+/// Append statuses to the spill, as JSON lines
+fn spill_statuses(spill: &mut Spill, statuses: &[&Event<StatusResponse>]) -> Result<(), String> {
+    for status in statuses {
+        JSONLinesDynamicStatus::write_line(&mut spill.writer, status).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
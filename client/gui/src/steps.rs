@@ -0,0 +1,199 @@
+/*!
+ * The steps a run goes through, shown as a step indicator rather than only
+ * the current job
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::journal::{JournalEntry, JournalEntryKind};
+use bindings::ClientStatus;
+#[cfg(feature = "ui")]
+use egui::{Color32, RichText, Ui};
+use std::collections::HashSet;
+
+/***** Steps *****/
+
+/// A step of a run, each covering the sending and receiving jobs for it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Step {
+    Ping,
+    StaticStatus,
+    Start,
+    Monitoring,
+    Stop,
+    Finished,
+}
+impl Step {
+    pub const ALL: [Self; 6] = [
+        Self::Ping,
+        Self::StaticStatus,
+        Self::Start,
+        Self::Monitoring,
+        Self::Stop,
+        Self::Finished,
+    ];
+
+    /// The step a job is part of, `None` before a run is started
+    pub fn of(job: ClientStatus) -> Option<Self> {
+        use ClientStatus::*;
+        match job {
+            GatheringData => None,
+            SendingPing | ReceivingPing => Some(Self::Ping),
+            RequestingStaticStatus | ReceivingStaticStatus => Some(Self::StaticStatus),
+            RequestingStart => Some(Self::Start),
+            ReceivingStatus => Some(Self::Monitoring),
+            RequestingStop => Some(Self::Stop),
+            Finished => Some(Self::Finished),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ping => "Ping",
+            Self::StaticStatus => "Car info",
+            Self::Start => "Start",
+            Self::Monitoring => "Monitoring",
+            Self::Stop => "Stop",
+            Self::Finished => "Finished",
+        }
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|step| step == self)
+            .expect("Every step is in ALL")
+    }
+}
+
+/// What happened during one step of the latest run
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct StepRecord {
+    /// Unix time the step was first reached
+    pub entered: Option<f64>,
+    /// Unix time the run moved on from it
+    pub left: Option<f64>,
+    pub errors: usize,
+    /// Requests sent again after going unanswered
+    pub retries: usize,
+}
+
+/***** Step indicator *****/
+
+/// Where the latest run is, worked out from the journal's transitions
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct StepIndicator {
+    pub current: Option<Step>,
+    records: [StepRecord; Step::ALL.len()],
+}
+impl StepIndicator {
+    /// Replay the latest run from the journal, putting errors (by Unix time)
+    /// down to the step they happened in
+    ///
+    /// Only the journal since the run started pinging is looked at, so this
+    /// is cheap enough to do every frame.
+    pub fn from_journal(entries: &[JournalEntry], error_times: &[f64]) -> Self {
+        use JournalEntryKind::*;
+        let run_start = entries
+            .iter()
+            .rposition(|entry| {
+                matches!(
+                    entry.kind,
+                    Transition { from, to }
+                        if Step::of(to) == Some(Step::Ping) && Step::of(from) != Some(Step::Ping)
+                )
+            })
+            .unwrap_or(entries.len());
+        let mut indicator = Self::default();
+        let mut sent = HashSet::new();
+
+        for entry in &entries[run_start..] {
+            match &entry.kind {
+                Transition { to, .. } => {
+                    let step = Step::of(*to);
+                    if step == indicator.current {
+                        continue;
+                    }
+                    if let Some(current) = indicator.current {
+                        indicator.records[current.index()].left = Some(entry.time);
+                    }
+                    match step {
+                        Some(step) => {
+                            let record = &mut indicator.records[step.index()];
+                            record.entered.get_or_insert(entry.time);
+                            record.left = None;
+                        }
+                        None => indicator = Self::default(),
+                    }
+                    indicator.current = step;
+                }
+                // The same frame sent twice is a request being retried
+                Sent(frame) => {
+                    if let (false, Some(current)) = (sent.insert(frame), indicator.current) {
+                        indicator.records[current.index()].retries += 1;
+                    }
+                }
+                Received(_) | Action(_) => (),
+            }
+        }
+
+        for time in error_times {
+            let step = Step::ALL.iter().rev().find(|step| {
+                let record = indicator.record(**step);
+                record.entered.is_some_and(|entered| entered <= *time)
+                    && record.left.is_none_or(|left| *time < left)
+            });
+            if let Some(step) = step {
+                indicator.records[step.index()].errors += 1;
+            }
+        }
+
+        indicator
+    }
+
+    pub fn record(&self, step: Step) -> &StepRecord {
+        &self.records[step.index()]
+    }
+
+    /// Seconds spent in the current step so far
+    pub fn elapsed(&self, now: f64) -> Option<f64> {
+        let entered = self.record(self.current?).entered?;
+        Some((now - entered).max(0.0))
+    }
+
+    #[cfg(feature = "ui")]
+    /// Show every step in a row, the current one highlighted and the ones
+    /// with errors or retries badged, followed by the current job
+    pub fn show(&self, ui: &mut Ui, job: ClientStatus, now: f64) {
+        let current = self.current.map(|step| step.index());
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 3.0;
+            for (idx, step) in Step::ALL.iter().enumerate() {
+                if idx > 0 {
+                    ui.weak("→");
+                }
+                let name = RichText::new(step.name());
+                ui.label(match current {
+                    Some(current) if idx == current => name.strong().color(Color32::LIGHT_BLUE),
+                    Some(current) if idx < current => name.color(Color32::LIGHT_GREEN),
+                    _ => name.weak(),
+                });
+
+                let record = self.record(*step);
+                if record.errors > 0 {
+                    ui.colored_label(Color32::RED, format!("⚠{}", record.errors))
+                        .on_hover_text("Errors during this step");
+                }
+                if record.retries > 0 {
+                    ui.colored_label(Color32::YELLOW, format!("↻{}", record.retries))
+                        .on_hover_text("Requests sent again during this step");
+                }
+            }
+        });
+        match self.elapsed(now) {
+            Some(elapsed) => ui.label(format!("{} ({elapsed:.1}s)", job.to_string())),
+            None => ui.label(job.to_string()),
+        };
+    }
+}
@@ -0,0 +1,149 @@
+/*!
+ * Asking why a run was stopped by hand before it finished, so what keeps
+ * cutting runs short can be looked back on
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+#[cfg(feature = "ui")]
+use egui::{Align2, Button, Context, TextEdit, Window};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Constants
+#[cfg(feature = "ui")]
+const REASON_BUTTON_SIZE: [f32; 2] = [70., 30.];
+
+/***** Stop reasons *****/
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReasonKind {
+    /// The car went before it should have, or not how it was meant to
+    FalseStart,
+    /// Something was in the way
+    Obstacle,
+    /// Something on the car broke or came loose
+    Mechanical,
+    /// Anything else, said in the note
+    Other,
+}
+impl StopReasonKind {
+    pub const ALL: [Self; 4] = [
+        Self::FalseStart,
+        Self::Obstacle,
+        Self::Mechanical,
+        Self::Other,
+    ];
+}
+impl fmt::Display for StopReasonKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::FalseStart => "False start",
+                Self::Obstacle => "Obstacle",
+                Self::Mechanical => "Mechanical",
+                Self::Other => "Other",
+            }
+        )
+    }
+}
+
+/// Why a run was stopped by hand
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct StopReason {
+    pub kind: StopReasonKind,
+    /// Anything else worth saying, or empty
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+}
+impl StopReason {
+    /// e.g. `Other (a wheel fell off)`
+    pub fn describe(&self) -> String {
+        match self.note.trim().is_empty() {
+            true => self.kind.to_string(),
+            false => format!("{} ({})", self.kind, self.note.trim()),
+        }
+    }
+}
+
+/***** Prompt *****/
+
+/// Where the stopped run is, for the reason to go with it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StoppedRun {
+    /// Still in the run data
+    Current,
+    /// Reset into the run history, at this index
+    Archived(usize),
+    /// Reset without anything coming in for it, so it wasn't kept
+    Gone,
+}
+
+#[cfg(feature = "ui")]
+/// A window off to the side asking why the run was stopped, which doesn't
+/// get in the way of anything and can be dismissed
+pub struct StopReasonPrompt {
+    pub open: bool,
+    pub note: String,
+    pub run: StoppedRun,
+}
+#[cfg(feature = "ui")]
+impl Default for StopReasonPrompt {
+    fn default() -> Self {
+        Self {
+            open: true,
+            note: String::new(),
+            run: StoppedRun::Current,
+        }
+    }
+}
+#[cfg(feature = "ui")]
+impl StopReasonPrompt {
+    /// Show the prompt, returning the reason once one is picked
+    ///
+    /// Any reason but "Other" is one tap, with whatever note was typed.
+    pub fn show(&mut self, ctx: &Context) -> Option<StopReason> {
+        let mut picked = None;
+        let mut open = true;
+        Window::new("Why was it stopped?")
+            .anchor(Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for kind in StopReasonKind::ALL {
+                        let enabled = kind != StopReasonKind::Other || !self.note.trim().is_empty();
+                        if ui
+                            .add_enabled(
+                                enabled,
+                                Button::new(kind.to_string()).min_size(REASON_BUTTON_SIZE.into()),
+                            )
+                            .clicked()
+                        {
+                            picked = Some(kind);
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.add(
+                        TextEdit::singleline(&mut self.note)
+                            .hint_text("Note (needed for other)")
+                            .desired_width(220.0),
+                    );
+                    if ui.add_sized([60., 20.], Button::new("Skip")).clicked() {
+                        open = false;
+                    }
+                });
+            });
+
+        self.open = open && picked.is_none();
+        picked.map(|kind| StopReason {
+            kind,
+            note: self.note.trim().to_owned(),
+        })
+    }
+}
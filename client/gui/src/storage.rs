@@ -0,0 +1,179 @@
+/*!
+ * Checking there's room left to log runs, and (on the Pi) that the SD card
+ * they're logged to isn't wearing out, before logging starts
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::ClientError;
+#[cfg(unix)]
+use std::{ffi::CString, mem::MaybeUninit, os::unix::ffi::OsStrExt};
+use std::{
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+// Constants
+/// How worn out an SD card or eMMC says it is in `life_time`, in tenths of
+/// its life, once it's worth replacing (0x0a is 90-100% used)
+const WORN_LIFE_TIME: u32 = 0x0a;
+/// What `pre_eol_info` says once the reserved blocks are running out (0x02 is
+/// 80% used, 0x03 is urgent)
+const WORN_PRE_EOL_INFO: u32 = 0x02;
+
+/***** Free space *****/
+
+/// Bytes free in the filesystem a path is on, for anyone but root
+#[cfg(unix)]
+pub fn free_space(path: &Path) -> Result<u64, ClientError> {
+    let error = |e: String| {
+        ClientError::DataDirectory(format!(
+            "Failed to check the space free in {}: {e}",
+            path.display()
+        ))
+    };
+    let c_path = CString::new(path.as_os_str().as_bytes()).map_err(|e| error(e.to_string()))?;
+    let mut stats = MaybeUninit::<libc::statvfs>::uninit();
+    // Safety: the path is NUL terminated and the stats are only read once
+    // they've been filled in
+    if unsafe { libc::statvfs(c_path.as_ptr(), stats.as_mut_ptr()) } != 0 {
+        return Err(error(std::io::Error::last_os_error().to_string()));
+    }
+    let stats = unsafe { stats.assume_init() };
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stats.f_bavail as u64 * stats.f_frsize as u64)
+}
+
+/// Off Unix-likes there's no `statvfs`, so there's taken to always be room
+#[cfg(not(unix))]
+pub fn free_space(_path: &Path) -> Result<u64, ClientError> {
+    Ok(u64::MAX)
+}
+
+/***** Storage checks *****/
+
+/// What's wrong with what the data directory is on, going by whether it was
+/// mounted read-only (which happens to SD cards after errors), its
+/// filesystem errors, and how worn out the card says it is
+///
+/// Like the startup checks, everything is read from files (`/proc` and
+/// `/sys`), and only SD cards and eMMC (`mmcblk`) report how worn out they
+/// are.
+#[derive(Clone, Debug)]
+pub struct StorageChecks {
+    /// Where the files are looked for, only not `/` for testing
+    pub root: PathBuf,
+    /// The data directory
+    pub directory: PathBuf,
+}
+impl StorageChecks {
+    pub fn new(directory: PathBuf) -> Self {
+        Self {
+            root: PathBuf::from("/"),
+            directory,
+        }
+    }
+
+    /// Everything found wrong, with what to do about it
+    pub fn run(&self) -> Vec<String> {
+        let Some((device, mount_point, options)) = self.mount() else {
+            return vec![];
+        };
+
+        let mut problems = vec![];
+        if options.split(',').any(|option| option == "ro") {
+            problems.push(format!(
+                "{mount_point} is mounted read-only, so runs can't be saved to {}; the SD card \
+                 may be failing, so back it up and replace it",
+                self.directory.display()
+            ));
+        }
+        let Some(partition) = self.partition(&device) else {
+            return problems;
+        };
+        let errors = self
+            .read(&format!("/sys/fs/ext4/{partition}/errors_count"))
+            .and_then(|errors| errors.trim().parse::<u32>().ok())
+            .unwrap_or_default();
+        if errors > 0 {
+            problems.push(format!(
+                "{partition} has had {errors} filesystem errors; check it with fsck, and replace \
+                 the SD card if they come back"
+            ));
+        }
+        let Some(disk) = disk_of(&partition) else {
+            return problems;
+        };
+        let life_time = self
+            .read(&format!("/sys/block/{disk}/device/life_time"))
+            .and_then(|life_time| life_time.split_whitespace().filter_map(parse_hex).max());
+        let pre_eol_info = self
+            .read(&format!("/sys/block/{disk}/device/pre_eol_info"))
+            .and_then(|pre_eol_info| parse_hex(pre_eol_info.trim()));
+        if life_time.is_some_and(|life_time| life_time >= WORN_LIFE_TIME)
+            || pre_eol_info.is_some_and(|pre_eol_info| pre_eol_info >= WORN_PRE_EOL_INFO)
+        {
+            problems.push(format!(
+                "{disk} says it's nearly worn out; back it up and replace it before it fails"
+            ));
+        }
+        problems
+    }
+
+    /// A path under the root
+    fn path(&self, path: &str) -> PathBuf {
+        self.root.join(path.trim_start_matches('/'))
+    }
+
+    /// The device, mount point, and options of the mount the directory is on
+    fn mount(&self) -> Option<(String, String, String)> {
+        let mounts = self.read("/proc/mounts")?;
+        mounts
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let device = fields.next()?;
+                let mount_point = fields.next()?;
+                let options = fields.nth(1_usize)?;
+                Some((
+                    device.to_owned(),
+                    mount_point.to_owned(),
+                    options.to_owned(),
+                ))
+            })
+            .filter(|(_, mount_point, _)| self.directory.starts_with(mount_point))
+            .max_by_key(|(_, mount_point, _)| mount_point.len())
+    }
+
+    /// The partition (e.g. `mmcblk0p2`) a mounted device is, if it's an SD
+    /// card or eMMC
+    fn partition(&self, device: &str) -> Option<String> {
+        let name = device.strip_prefix("/dev/")?;
+        // Raspberry Pi OS mounts the SD card's root partition as this
+        if name == "root" {
+            return self
+                .path("/sys/block/mmcblk0/mmcblk0p2")
+                .exists()
+                .then(|| "mmcblk0p2".to_owned());
+        }
+        name.starts_with("mmcblk").then(|| name.to_owned())
+    }
+
+    fn read(&self, path: &str) -> Option<String> {
+        read_to_string(self.path(path)).ok()
+    }
+}
+
+/***** Helper functions *****/
+
+/// The disk a partition is on, e.g. `mmcblk0` for `mmcblk0p2`
+fn disk_of(partition: &str) -> Option<&str> {
+    let (disk, number) = partition.rsplit_once('p')?;
+    (!number.is_empty() && number.chars().all(|c| c.is_ascii_digit())).then_some(disk)
+}
+
+/// e.g. `0x0a`
+fn parse_hex(hex: &str) -> Option<u32> {
+    u32::from_str_radix(hex.trim_start_matches("0x"), 16).ok()
+}
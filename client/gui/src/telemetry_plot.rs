@@ -0,0 +1,273 @@
+/*!
+ * Plotting distance and velocity over time while a run goes on
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use crate::{annotations::*, estimate::PositionEstimate, shared::*};
+use bindings::{Event, StatusResponse};
+#[cfg(feature = "ui")]
+use egui::{
+    plot::{Legend, Line, LineStyle, Plot, Polygon},
+    Button, Checkbox, Color32, ComboBox, Context, DragValue, Label, ScrollArea, TextEdit, Window,
+};
+
+// Constants
+#[cfg(feature = "ui")]
+const PLOT_SIZE: [f32; 2] = [400.0, 170.0];
+#[cfg(feature = "ui")]
+const ESTIMATE_COLOR: Color32 = Color32::from_rgb(255, 140, 0);
+
+/***** Telemetry *****/
+
+/// Distance and velocity against seconds since the run started
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Telemetry {
+    /// `[seconds, centimeters]`
+    pub distance: Vec<[f64; 2]>,
+    /// `[seconds, centimeters per second]`
+    pub velocity: Vec<[f64; 2]>,
+    /// The seconds to show, `window` seconds long once the run is long
+    /// enough
+    pub start: f64,
+    pub end: f64,
+}
+impl Telemetry {
+    /// The highest distance or velocity shown
+    pub fn top(&self) -> f64 {
+        self.distance
+            .iter()
+            .chain(self.velocity.iter())
+            .map(|[_, value]| *value)
+            .fold(0.0, f64::max)
+    }
+}
+
+/// The distance and velocity at each status in the last `window` seconds of
+/// a run
+///
+/// The run is taken to have started at the time of the first status minus
+/// its runtime.
+pub fn telemetry(statuses: &[Event<StatusResponse>], window: f64) -> Telemetry {
+    let start_time = match statuses.first() {
+        Some(first) => first.metadata.time - first.value.runtime as f64,
+        None => return Telemetry::default(),
+    };
+    // Safety: there's a first, so there's a last
+    let end = statuses.last().unwrap().metadata.time - start_time;
+    let start = (end - window).max(0.0);
+
+    let mut telemetry = Telemetry {
+        start,
+        end,
+        ..Default::default()
+    };
+    // Statuses come in order, so the ones before the window are skipped
+    // without going through them
+    let first_shown = statuses.partition_point(|status| status.metadata.time - start_time < start);
+    for status in statuses[first_shown..].iter() {
+        let time = status.metadata.time - start_time;
+        let distance = &status.value.distance;
+        telemetry.distance.push([time, distance.distance]);
+        telemetry.velocity.push([time, distance.velocity]);
+    }
+    telemetry
+}
+
+/***** Telemetry plot *****/
+
+#[cfg(feature = "ui")]
+/// A window plotting the run as it comes in
+pub struct TelemetryPlot {
+    pub open: bool,
+    pub show_distance: bool,
+    pub show_velocity: bool,
+    /// Seconds of the run shown, up to the latest status
+    pub window: f64,
+    /// What clicking on the plot adds
+    pub tool: AnnotationTool,
+    /// The label or text of what's added next
+    pub label: String,
+    /// Where the region being added starts, once clicked
+    pub region_start: Option<f64>,
+}
+#[cfg(feature = "ui")]
+impl TelemetryPlot {
+    pub fn new() -> Self {
+        Self {
+            open: true,
+            show_distance: true,
+            show_velocity: true,
+            window: DEFAULT_PLOT_WINDOW_SECONDS,
+            tool: AnnotationTool::Off,
+            label: String::new(),
+            region_start: None,
+        }
+    }
+
+    /// Show the plot with the run's annotations, and where the car probably
+    /// is if statuses have stopped coming in, returning how the annotations
+    /// were changed, if they were
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        statuses: &[Event<StatusResponse>],
+        annotations: &[Annotation],
+        estimate: Option<&PositionEstimate>,
+    ) -> Option<AnnotationEdit> {
+        let telemetry = telemetry(statuses, self.window);
+        let top = telemetry.top();
+        // Carried on from the latest status, and dashed as it's only a guess
+        let estimated_end = telemetry.end + estimate.map_or(0.0, |estimate| estimate.silent_for);
+
+        let mut edit = None;
+        let mut open = true;
+        Window::new("Telemetry").resizable(false).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.add_sized([60., 20.], Button::new("Close")).clicked() {
+                    open = false;
+                }
+                ui.add(Checkbox::new(&mut self.show_distance, "Distance"));
+                ui.add(Checkbox::new(&mut self.show_velocity, "Velocity"));
+                ui.add(
+                    DragValue::new(&mut self.window)
+                        .clamp_range(MIN_PLOT_WINDOW_SECONDS..=MAX_PLOT_WINDOW_SECONDS)
+                        .prefix("Last ")
+                        .suffix("s"),
+                );
+            });
+            ui.horizontal(|ui| {
+                let tool = self.tool;
+                ComboBox::from_id_source("annotation tool")
+                    .width(80.0)
+                    .selected_text(self.tool.label())
+                    .show_ui(ui, |ui| {
+                        for tool in AnnotationTool::ALL {
+                            ui.selectable_value(&mut self.tool, tool, tool.label());
+                        }
+                    });
+                if self.tool != tool {
+                    self.region_start = None;
+                }
+                ui.add_enabled(
+                    self.tool != AnnotationTool::Off,
+                    TextEdit::singleline(&mut self.label)
+                        .hint_text("What happened")
+                        .desired_width(150.0),
+                );
+                if self.region_start.is_some() {
+                    ui.label("Click where it ends");
+                }
+            });
+
+            if statuses.is_empty() {
+                ui.label("No dynamic status available");
+                return;
+            }
+            // Follows the run on its own, so there's nothing to drag or
+            // zoom
+            let clicked = Plot::new("telemetry")
+                .width(PLOT_SIZE[0])
+                .height(PLOT_SIZE[1])
+                .legend(Legend::default())
+                .allow_drag(false)
+                .allow_zoom(false)
+                .allow_scroll(false)
+                .allow_boxed_zoom(false)
+                .allow_double_click_reset(false)
+                .auto_bounds_x()
+                .auto_bounds_y()
+                .include_x(telemetry.start)
+                .include_x(estimated_end)
+                .include_y(0.0)
+                .x_axis_formatter(|seconds, _| format!("{seconds:.0}s"))
+                .show(ui, |plot_ui| {
+                    if self.show_distance {
+                        plot_ui.line(Line::new(telemetry.distance).name("Distance (cm)"));
+                        if let Some(estimate) = estimate {
+                            let (low, high) = estimate.range();
+                            plot_ui.polygon(
+                                Polygon::new(vec![
+                                    [telemetry.end, estimate.last_distance],
+                                    [estimated_end, high],
+                                    [estimated_end, low],
+                                ])
+                                .color(ESTIMATE_COLOR)
+                                .name("Estimate (cm)"),
+                            );
+                            plot_ui.line(
+                                Line::new(vec![
+                                    [telemetry.end, estimate.last_distance],
+                                    [estimated_end, estimate.distance],
+                                ])
+                                .color(ESTIMATE_COLOR)
+                                .style(LineStyle::dashed_loose())
+                                .name("Estimate (cm)"),
+                            );
+                        }
+                    }
+                    if self.show_velocity {
+                        plot_ui.line(Line::new(telemetry.velocity).name("Velocity (cm/s)"));
+                    }
+                    plot_annotations(plot_ui, annotations, top);
+                    plot_ui
+                        .plot_clicked()
+                        .then(|| plot_ui.pointer_coordinate())
+                        .flatten()
+                })
+                .inner;
+            if let Some(clicked) = clicked {
+                edit = self.click(clicked.x, clicked.y).map(AnnotationEdit::Add);
+            }
+
+            ScrollArea::vertical()
+                .id_source("annotations")
+                .max_height(60.0)
+                .show(ui, |ui| {
+                    for (idx, annotation) in annotations.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui.small_button("x").clicked() {
+                                edit = Some(AnnotationEdit::Remove(idx));
+                            }
+                            ui.add(Label::new(annotation.describe()).wrap(true));
+                        });
+                    }
+                });
+        });
+
+        self.open = open;
+        edit
+    }
+
+    /// What clicking on the plot at some seconds and value adds, if anything
+    pub fn click(&mut self, time: f64, value: f64) -> Option<Annotation> {
+        let label = self.label.trim().to_owned();
+        match self.tool {
+            AnnotationTool::Off => None,
+            AnnotationTool::Marker => Some(Annotation::Marker { time, label }),
+            AnnotationTool::Note => Some(Annotation::Note {
+                time,
+                value,
+                text: label,
+            }),
+            AnnotationTool::Region => match self.region_start.take() {
+                None => {
+                    self.region_start = Some(time);
+                    None
+                }
+                Some(start) => Some(Annotation::Region {
+                    start: start.min(time),
+                    end: start.max(time),
+                    label,
+                }),
+            },
+        }
+    }
+}
+#[cfg(feature = "ui")]
+impl Default for TelemetryPlot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
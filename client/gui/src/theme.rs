@@ -0,0 +1,182 @@
+/*!
+ * How the GUI looks: its theme, the kiosk and operator layouts for the
+ * touchscreen, and scaling it up to fill windows bigger than the screen it was
+ * laid out for
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+#[cfg(feature = "ui")]
+use egui::{vec2, Color32, Context, FontId, Stroke, Style, TextStyle, Vec2, Visuals};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Constants
+#[cfg(feature = "ui")]
+/// The change in scale (in pixels per point) worth laying everything out
+/// again for
+const SCALE_TOLERANCE: f32 = 0.01;
+/// Big enough to hit with gloves on, in the operator layout
+pub const OPERATOR_BUTTON_HEIGHT: f32 = 72.0;
+pub const OPERATOR_PRESET_BUTTON_SIZE: [f32; 2] = [100.0, 44.0];
+pub const OPERATOR_SMALL_BUTTON_SIZE: [f32; 2] = [100.0, 36.0];
+pub const OPERATOR_PROGRESS_HEIGHT: f32 = 32.0;
+
+/***** Theme *****/
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    /// White on black with thick outlines, for the touchscreen in sunlight
+    HighContrast,
+}
+impl Theme {
+    pub const ALL: [Self; 3] = [Self::Dark, Self::Light, Self::HighContrast];
+
+    #[cfg(feature = "ui")]
+    pub fn visuals(&self) -> Visuals {
+        match self {
+            Self::Dark => Visuals::dark(),
+            Self::Light => Visuals::light(),
+            Self::HighContrast => {
+                let mut visuals = Visuals::dark();
+                visuals.panel_fill = Color32::BLACK;
+                visuals.window_fill = Color32::BLACK;
+                visuals.extreme_bg_color = Color32::BLACK;
+                visuals.faint_bg_color = Color32::from_gray(30);
+                visuals.window_stroke = Stroke::new(2.0, Color32::WHITE);
+                visuals.selection.bg_fill = Color32::from_rgb(0, 90, 200);
+                visuals.selection.stroke = Stroke::new(2.0, Color32::WHITE);
+                visuals.hyperlink_color = Color32::YELLOW;
+                for widget in [
+                    &mut visuals.widgets.noninteractive,
+                    &mut visuals.widgets.inactive,
+                    &mut visuals.widgets.hovered,
+                    &mut visuals.widgets.active,
+                    &mut visuals.widgets.open,
+                ] {
+                    widget.fg_stroke = Stroke::new(2.0, Color32::WHITE);
+                }
+                for widget in [
+                    &mut visuals.widgets.inactive,
+                    &mut visuals.widgets.hovered,
+                    &mut visuals.widgets.active,
+                    &mut visuals.widgets.open,
+                ] {
+                    widget.bg_stroke = Stroke::new(2.0, Color32::WHITE);
+                }
+                visuals.widgets.inactive.bg_fill = Color32::from_gray(20);
+                visuals.widgets.inactive.weak_bg_fill = Color32::from_gray(20);
+                visuals.widgets.hovered.bg_stroke = Stroke::new(2.0, Color32::YELLOW);
+                visuals.widgets.active.bg_stroke = Stroke::new(3.0, Color32::YELLOW);
+                visuals
+            }
+        }
+    }
+}
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Dark => "Dark",
+                Self::Light => "Light",
+                Self::HighContrast => "High contrast",
+            }
+        )
+    }
+}
+
+/***** Layout *****/
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ScreenLayout {
+    /// For a mouse
+    #[default]
+    Standard,
+    /// Bigger checkboxes, sliders, and text, for fingers on the touchscreen
+    Kiosk,
+    /// Only the presets, progress, START, and E-STOP, as big as they go, for
+    /// gloved fingers
+    Operator,
+}
+impl ScreenLayout {
+    pub const ALL: [Self; 3] = [Self::Standard, Self::Kiosk, Self::Operator];
+}
+impl fmt::Display for ScreenLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Standard => "Standard",
+                Self::Kiosk => "Kiosk",
+                Self::Operator => "Operator",
+            }
+        )
+    }
+}
+
+/***** Applying it *****/
+
+#[cfg(feature = "ui")]
+/// Use a theme and layout, scaled to fill the window with a layout made for a
+/// screen of some size in points
+///
+/// Cheap enough to do every frame; the scale is only changed when the window
+/// has been resized.
+pub fn apply_appearance(ctx: &Context, theme: Theme, layout: ScreenLayout, screen_size: Vec2) {
+    let mut style = Style {
+        visuals: theme.visuals(),
+        ..Default::default()
+    };
+    if layout != ScreenLayout::Standard {
+        let spacing = &mut style.spacing;
+        spacing.item_spacing = vec2(8.0, 6.0);
+        spacing.button_padding = vec2(10.0, 6.0);
+        spacing.interact_size = vec2(48.0, 32.0);
+        spacing.icon_width = 24.0;
+        spacing.icon_width_inner = 14.0;
+        spacing.icon_spacing = 8.0;
+        spacing.slider_width = 120.0;
+        spacing.scroll_bar_width = 16.0;
+        let sizes = match layout {
+            ScreenLayout::Operator => [18.0, 24.0, 26.0],
+            _ => [15.0, 16.0, 20.0],
+        };
+        for (text_style, size) in [TextStyle::Body, TextStyle::Button, TextStyle::Heading]
+            .into_iter()
+            .zip(sizes)
+        {
+            if let Some(font) = style.text_styles.get_mut(&text_style) {
+                *font = FontId::new(size, font.family.clone());
+            }
+        }
+    }
+    ctx.set_style(style);
+
+    let scale = scale(
+        ctx.input(|input| input.screen_rect.size() * input.pixels_per_point()),
+        screen_size,
+    );
+    if (scale - ctx.pixels_per_point()).abs() > SCALE_TOLERANCE {
+        ctx.set_pixels_per_point(scale);
+    }
+}
+
+#[cfg(feature = "ui")]
+/// Pixels per point for a layout made for a screen of some size in points to
+/// fill a window of some size in pixels, without going off of it
+pub fn scale(window_size: Vec2, screen_size: Vec2) -> f32 {
+    let scale = (window_size.x / screen_size.x).min(window_size.y / screen_size.y);
+    match scale.is_finite() && scale > 0.0 {
+        true => scale,
+        false => 1.0,
+    }
+}
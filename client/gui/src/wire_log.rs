@@ -0,0 +1,138 @@
+/*!
+ * Logging every raw frame that goes over the wire, and replaying the logs
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::ClientError;
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str as serde_from_str, to_string as serde_to_string};
+use std::{
+    collections::VecDeque,
+    fs::{read_to_string, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+/***** Wire log *****/
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+/// One line of a wire log
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct WireLogEntry {
+    pub direction: Direction,
+    /// Seconds since logging started, which never jumps around like the
+    /// wall-clock time can
+    pub monotonic: f64,
+    /// Seconds since the Unix epoch
+    pub time: f64,
+    pub frame: String,
+}
+
+/// Appends every raw frame sent or received to a log, one JSON object per line
+///
+/// Every line is written straight to the file, so a crash mid-race loses
+/// nothing that was logged before it.
+pub struct WireLogger {
+    pub path: PathBuf,
+    file: File,
+    started: Instant,
+}
+impl WireLogger {
+    /// Start logging to a file, after anything already in it
+    pub fn open(path: PathBuf) -> Result<Self, ClientError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| ClientError::WireLog(format!("Failed to open {}: {e}", path.display())))?;
+        Ok(Self {
+            path,
+            file,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn log(&mut self, direction: Direction, frame: &str) -> Result<(), ClientError> {
+        let entry = WireLogEntry {
+            direction,
+            monotonic: self.started.elapsed().as_secs_f64(),
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|e| ClientError::Unknown(e.to_string()))?
+                .as_secs_f64(),
+            frame: frame.to_owned(),
+        };
+        let line = serde_to_string(&entry).map_err(|e| ClientError::WireLog(e.to_string()))?;
+        writeln!(self.file, "{line}").map_err(|e| {
+            ClientError::WireLog(format!("Failed to write to {}: {e}", self.path.display()))
+        })
+    }
+}
+
+/// Read every entry of a wire log
+pub fn read_wire_log(path: &Path) -> Result<Vec<WireLogEntry>, ClientError> {
+    let contents = read_to_string(path)
+        .map_err(|e| ClientError::WireLog(format!("Failed to read {}: {e}", path.display())))?;
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            serde_from_str(line)
+                .map_err(|e| ClientError::WireLog(format!("Line {}: {e}", idx + 1_usize)))
+        })
+        .collect()
+}
+
+/***** Replaying *****/
+
+/// The received frames of a wire log, given back as they were received
+///
+/// Frames come out as long after the start of the replay as they came in after
+/// the first logged frame, so a session plays out like it did live.
+pub struct WireLogReplay {
+    pub path: PathBuf,
+    frames: VecDeque<(f64, String)>,
+    started: Instant,
+}
+impl WireLogReplay {
+    pub fn read(path: PathBuf) -> Result<Self, ClientError> {
+        let entries = read_wire_log(&path)?;
+        let first = entries.first().map_or(0.0, |entry| entry.monotonic);
+        let frames = entries
+            .into_iter()
+            .filter(|entry| entry.direction == Direction::Received)
+            .map(|entry| (entry.monotonic - first, entry.frame))
+            .collect();
+        Ok(Self {
+            path,
+            frames,
+            started: Instant::now(),
+        })
+    }
+
+    /// The frames that are due to be received by now, in order
+    pub fn due(&mut self) -> Vec<String> {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let mut due = vec![];
+        while matches!(self.frames.front(), Some((received, _)) if *received <= elapsed) {
+            // Safety: there's a frame in front, checked above
+            due.push(self.frames.pop_front().unwrap().1);
+        }
+        due
+    }
+
+    /// How many frames are still to come
+    pub fn remaining(&self) -> usize {
+        self.frames.len()
+    }
+}
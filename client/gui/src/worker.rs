@@ -0,0 +1,129 @@
+/*!
+ * Running slow jobs (like saving a long run) on a background thread, with
+ * their progress shown and a way to cancel them
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::ClientError;
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{channel, Receiver, TryRecvError},
+        Arc,
+    },
+    thread::spawn,
+};
+
+/***** Progress *****/
+
+/// How far along a job is, shared between the job and whoever's waiting on it
+#[derive(Default, Debug)]
+pub struct JobProgress {
+    done: AtomicUsize,
+    total: AtomicUsize,
+    cancelled: AtomicBool,
+}
+impl JobProgress {
+    /// With `total` steps to go, e.g. rows to write
+    pub fn new(total: usize) -> Self {
+        Self {
+            total: AtomicUsize::new(total),
+            ..Default::default()
+        }
+    }
+
+    pub fn advance(&self, steps: usize) {
+        self.done.fetch_add(steps, Ordering::Relaxed);
+    }
+
+    /// Steps done and steps in total
+    pub fn steps(&self) -> (usize, usize) {
+        (
+            self.done.load(Ordering::Relaxed),
+            self.total.load(Ordering::Relaxed),
+        )
+    }
+
+    /// From 0 to 1, with nothing to do counting as done
+    pub fn fraction(&self) -> f32 {
+        match self.steps() {
+            (_, 0) => 1.0,
+            (done, total) => (done.min(total) as f64 / total as f64) as f32,
+        }
+    }
+
+    /// Ask the job to stop, which it does the next time it checks
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/***** Background job *****/
+
+/// How a job ended
+#[derive(Debug)]
+pub enum JobOutcome<T> {
+    Finished(T),
+    Cancelled,
+    Failed(ClientError),
+}
+
+/// A job running on its own thread
+///
+/// The job is handed its progress to keep up to date and to check for being
+/// cancelled. Any error it returns once cancelled counts as it being
+/// cancelled, so it can bail out however is easiest, but a job that finished
+/// before noticing still counts as finished.
+pub struct BackgroundJob<T> {
+    /// What the job is doing, e.g. "Saving run.csv"
+    pub description: String,
+    pub progress: Arc<JobProgress>,
+    receiver: Receiver<Result<T, ClientError>>,
+}
+impl<T: Send + 'static> BackgroundJob<T> {
+    pub fn spawn<F>(description: String, total: usize, job: F) -> Self
+    where
+        F: FnOnce(&JobProgress) -> Result<T, ClientError> + Send + 'static,
+    {
+        let progress = Arc::new(JobProgress::new(total));
+        let (sender, receiver) = channel();
+        let job_progress = Arc::clone(&progress);
+        spawn(move || {
+            // Nobody might be waiting anymore
+            let _ = sender.send(job(&job_progress));
+        });
+
+        Self {
+            description,
+            progress,
+            receiver,
+        }
+    }
+
+    /// How the job ended, or `None` if it's still going, without waiting on it
+    pub fn poll(&self) -> Option<JobOutcome<T>> {
+        let result = match self.receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err(ClientError::Unknown(format!(
+                "{} stopped without finishing",
+                self.description
+            ))),
+        };
+        Some(match (result, self.progress.cancelled()) {
+            (Ok(value), _) => JobOutcome::Finished(value),
+            (Err(_), true) => JobOutcome::Cancelled,
+            (Err(e), false) => JobOutcome::Failed(e),
+        })
+    }
+
+    pub fn cancel(&self) {
+        self.progress.cancel();
+    }
+}
@@ -0,0 +1,66 @@
+/*!
+ * Tests for working out when the car gets to the target and where it stops
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+#![cfg(feature = "analytics")]
+
+/***** Setup *****/
+// Imports
+mod common;
+use common::status;
+use gui::analytics::*;
+
+/***** Tests *****/
+
+#[test]
+fn steady_car_gets_there_at_its_speed() {
+    let statuses = [status(1.0, 20.0, 20.0), status(2.0, 40.0, 20.0)];
+    let analytics = RunAnalytics::of(&statuses, Some(100.0)).unwrap();
+    assert_eq!(analytics.time_to_target, Some(3.0));
+    // Not slowing down, so there's no telling where it stops
+    assert_eq!(analytics.projected_overshoot, None);
+    assert_eq!(analytics.average_velocity, 20.0);
+    assert_eq!(analytics.peak_velocity, 20.0);
+
+    let analytics = RunAnalytics::of(&statuses, None).unwrap();
+    assert_eq!(analytics.time_to_target, None);
+    assert!(RunAnalytics::of(&[], Some(100.0)).is_none());
+}
+
+#[test]
+fn slowing_car_is_projected_to_stop() {
+    // Slowing down 10cm/s every second, so stopping 20cm further along
+    let statuses = [status(1.0, 50.0, 30.0), status(2.0, 80.0, 20.0)];
+    let analytics = RunAnalytics::of(&statuses, Some(90.0)).unwrap();
+    assert_eq!(analytics.projected_overshoot, Some(10.0));
+    // 10 = 20t - 5t^2
+    let time_to_target = analytics.time_to_target.unwrap();
+    assert!((time_to_target - (2.0 - 2.0_f64.sqrt())).abs() < 1e-9);
+    assert_eq!(analytics.peak_velocity, 30.0);
+
+    // Stopping short never gets there
+    let analytics = RunAnalytics::of(&statuses, Some(150.0)).unwrap();
+    assert_eq!(analytics.projected_overshoot, Some(-50.0));
+    assert_eq!(analytics.time_to_target, None);
+}
+
+#[test]
+fn analytics_are_worked_out_after_every_status() {
+    let statuses = [
+        status(1.0, 10.0, 10.0),
+        status(2.0, 30.0, 30.0),
+        status(3.0, 45.0, 15.0),
+        status(4.0, 45.0, 0.0),
+    ];
+    let analytics = RunAnalytics::over(&statuses, Some(40.0));
+    assert_eq!(analytics.len(), statuses.len());
+    assert_eq!(analytics[0].peak_velocity, 10.0);
+    assert_eq!(analytics[3].peak_velocity, 30.0);
+    assert_eq!(analytics[2].time_to_target, Some(0.0));
+    // Stopped where it is
+    assert_eq!(analytics[3].projected_overshoot, Some(5.0));
+    assert_eq!(
+        analytics.last(),
+        RunAnalytics::of(&statuses, Some(40.0)).as_ref()
+    );
+}
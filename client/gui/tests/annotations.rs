@@ -0,0 +1,139 @@
+/*!
+ * Tests for annotating the telemetry plot and keeping the annotations with
+ * the run
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::ClientError;
+#[cfg(feature = "ui")]
+use gui::telemetry_plot::*;
+use gui::{annotations::*, clock::DisplayTimezone, data_directory::*, export::*, journal::*};
+use std::{
+    env::temp_dir,
+    fs::{create_dir_all, remove_dir_all, write},
+    path::{Path, PathBuf},
+};
+
+/***** Helper functions *****/
+
+/// An empty directory just for one test
+fn test_directory(test: &str) -> PathBuf {
+    let directory = temp_dir().join(format!("gui-annotations-{test}-{}", std::process::id()));
+    let _ = remove_dir_all(&directory);
+    create_dir_all(&directory).unwrap();
+    directory
+}
+
+fn marker(time: f64) -> Annotation {
+    Annotation::Marker {
+        time,
+        label: "Clipped the lane marker".to_owned(),
+    }
+}
+
+/***** Tests *****/
+
+#[test]
+fn annotations_are_kept_next_to_the_run() {
+    let directory = test_directory("kept");
+    let run = directory.join("2024-05-12_run03_500cm.csv");
+    assert_eq!(
+        annotations_path(&run),
+        directory.join("2024-05-12_run03_500cm.annotations.json")
+    );
+    assert_eq!(
+        annotations_path(Path::new("saved.jsonl")),
+        PathBuf::from("saved.annotations.json")
+    );
+
+    // Nothing saved reads as nothing
+    assert_eq!(read_annotations(&run).unwrap(), vec![]);
+
+    let annotations = vec![
+        marker(2.5),
+        Annotation::Note {
+            time: 4.0,
+            value: 30.0,
+            text: "Wheel slipped".to_owned(),
+        },
+        Annotation::Region {
+            start: 5.0,
+            end: 7.5,
+            label: "Wet patch".to_owned(),
+        },
+    ];
+    write_annotations(&run, &annotations).unwrap();
+    assert_eq!(read_annotations(&run).unwrap(), annotations);
+
+    // Removing them all removes the file
+    write_annotations(&run, &[]).unwrap();
+    assert!(!annotations_path(&run).exists());
+    write_annotations(&run, &[]).unwrap();
+
+    write(annotations_path(&run), "[{\"kind\": \"doodle\"}]").unwrap();
+    assert!(matches!(
+        read_annotations(&run),
+        Err(ClientError::Annotations(_))
+    ));
+}
+
+#[test]
+fn annotations_go_with_runs_past_retention() {
+    let directory = test_directory("retention");
+    let data_directory = DataDirectory::at(directory.clone()).unwrap();
+    let first = data_directory.next_run_path(500.0, RunFormat::CSV);
+    RunFormat::CSV.write(&first, &[]).unwrap();
+    write_annotations(&first, &[marker(1.0)]).unwrap();
+    let second = data_directory.next_run_path(500.0, RunFormat::CSV);
+    RunFormat::CSV.write(&second, &[]).unwrap();
+
+    // Annotations aren't runs themselves
+    assert_eq!(data_directory.runs(), vec![first.clone(), second.clone()]);
+    assert_eq!(data_directory.enforce_retention(1).unwrap(), 1);
+    assert!(!annotations_path(&first).exists());
+    assert_eq!(data_directory.runs(), vec![second]);
+}
+
+#[cfg(feature = "ui")]
+#[test]
+fn clicking_the_plot_adds_with_the_tool() {
+    let mut plot = TelemetryPlot::new();
+    plot.label = " Clipped the lane marker ".to_owned();
+    assert_eq!(plot.click(2.5, 10.0), None);
+
+    plot.tool = AnnotationTool::Marker;
+    assert_eq!(plot.click(2.5, 10.0), Some(marker(2.5)));
+
+    // Regions are clicked at both ends, in either order
+    plot.tool = AnnotationTool::Region;
+    assert_eq!(plot.click(8.0, 10.0), None);
+    assert_eq!(plot.region_start, Some(8.0));
+    assert_eq!(
+        plot.click(6.0, 20.0),
+        Some(Annotation::Region {
+            start: 6.0,
+            end: 8.0,
+            label: "Clipped the lane marker".to_owned(),
+        })
+    );
+    assert_eq!(plot.region_start, None);
+}
+
+#[test]
+fn journal_replays_annotations() {
+    let mut journal = Journal::default();
+    journal.record(JournalEntryKind::Action(UserAction::Annotate(marker(1.0))));
+    journal.record(JournalEntryKind::Action(UserAction::Annotate(marker(2.0))));
+    journal.record(JournalEntryKind::Action(UserAction::RemoveAnnotation(0)));
+    assert_eq!(journal.replay(1).run_data.annotations.len(), 2);
+    assert_eq!(journal.replay(2).run_data.annotations, vec![marker(2.0)]);
+    assert!(journal.entries()[0]
+        .describe(&DisplayTimezone::Utc)
+        .ends_with("Annotate 1.0s: Clipped the lane marker"));
+
+    // Kept through saving the journal
+    let journal = Journal::from_json(&journal.to_json().unwrap()).unwrap();
+    assert_eq!(journal.replay(2).run_data.annotations, vec![marker(2.0)]);
+}
@@ -0,0 +1,34 @@
+/*!
+ * Tests for arming before a run
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use gui::arming::*;
+use std::time::{Duration, Instant};
+
+/***** Tests *****/
+
+#[test]
+fn armed_counts_down_until_it_expires() {
+    let now = Instant::now();
+    let arming = Arming::armed(now, Duration::from_secs(5));
+
+    assert_eq!(arming.remaining(now), Some(Duration::from_secs(5)));
+    assert_eq!(
+        arming.remaining(now + Duration::from_secs(2)),
+        Some(Duration::from_secs(3))
+    );
+    assert!(!arming.expired(now + Duration::from_secs(2)));
+
+    assert_eq!(arming.remaining(now + Duration::from_secs(5)), None);
+    assert!(arming.expired(now + Duration::from_secs(5)));
+}
+
+#[test]
+fn disarmed_never_expires() {
+    let now = Instant::now();
+    assert_eq!(Arming::Disarmed.remaining(now), None);
+    assert!(!Arming::Disarmed.expired(now + Duration::from_secs(60)));
+}
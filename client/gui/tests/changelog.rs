@@ -0,0 +1,107 @@
+/*!
+ * Tests for the changelog built into the GUI and showing what's new once per
+ * version
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::ClientError;
+use gui::changelog::*;
+use std::{
+    env::temp_dir,
+    fs::{remove_file, write},
+};
+
+// Constants
+const CHANGELOG: &str = r#"
+[[releases]]
+version = "0.3.0"
+
+[[releases.notes]]
+title = "Quieter"
+details = "Less noise"
+
+[[releases.notes]]
+title = "Stops sooner"
+details = "Stops sooner"
+safety = true
+
+[[releases]]
+version = "0.2.0"
+
+[[releases.notes]]
+title = "Faster"
+details = "More speed"
+
+[[releases]]
+version = "0.1.0"
+
+[[releases.notes]]
+title = "First"
+details = "The first version"
+"#;
+
+/***** Tests *****/
+
+#[test]
+fn the_built_in_changelog_is_for_this_version() {
+    let changelog = Changelog::built_in().unwrap();
+    assert_eq!(changelog.releases[0].version, VERSION);
+    assert!(changelog
+        .releases
+        .iter()
+        .all(|release| !release.notes.is_empty()));
+}
+
+#[test]
+fn safety_notes_come_first() {
+    let changelog = Changelog::parse(CHANGELOG).unwrap();
+    let titles = changelog.releases[0]
+        .sorted_notes()
+        .into_iter()
+        .map(|note| note.title.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(titles, ["Stops sooner", "Quieter"]);
+}
+
+#[test]
+fn only_the_releases_since_the_one_seen_are_new() {
+    let changelog = Changelog::parse(CHANGELOG).unwrap();
+    let versions = |seen| {
+        changelog
+            .since(seen)
+            .into_iter()
+            .map(|release| release.version)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(versions(Some("0.1.0")), ["0.3.0", "0.2.0"]);
+    assert_eq!(versions(Some("0.3.0")), Vec::<String>::new());
+    // Everything's new the first time, so only the latest is shown
+    assert_eq!(versions(None), ["0.3.0"]);
+}
+
+#[test]
+fn whats_new_is_only_shown_once() {
+    let path = temp_dir().join(format!("gui-whats-new-{}.toml", std::process::id()));
+    let _ = remove_file(&path);
+    let changelog = Changelog::built_in().unwrap();
+
+    let (mut whats_new, error) = WhatsNew::load(Some(path.clone()));
+    assert!(error.is_none());
+    whats_new.check(&changelog);
+    assert_eq!(whats_new.releases.len(), 1);
+    whats_new.seen().unwrap();
+    assert!(whats_new.releases.is_empty());
+
+    let (mut whats_new, _) = WhatsNew::load(Some(path.clone()));
+    assert_eq!(whats_new.seen_version.as_deref(), Some(VERSION));
+    whats_new.check(&changelog);
+    assert!(whats_new.releases.is_empty());
+
+    write(&path, "seen = 1").unwrap();
+    let (whats_new, error) = WhatsNew::load(Some(path.clone()));
+    assert!(matches!(error, Some(ClientError::Changelog(_))));
+    assert_eq!(whats_new.seen_version, None);
+    let _ = remove_file(&path);
+}
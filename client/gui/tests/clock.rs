@@ -0,0 +1,96 @@
+/*!
+ * Tests for time zones and checking the clocks
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use gui::{clock::*, config::Config};
+
+// Constants
+/// 2026-10-16 12:34:56.789 UTC
+const TIME: f64 = 1_792_154_096.789;
+
+/***** Tests *****/
+
+#[test]
+fn times_are_shown_in_the_chosen_time_zone() {
+    let format = "%Y-%m-%d %H:%M:%S%.3f";
+    assert_eq!(
+        DisplayTimezone::Utc.format_unix(TIME, format),
+        "2026-10-16 12:34:56.789"
+    );
+    let timezone = "+05:30".parse::<DisplayTimezone>().unwrap();
+    assert_eq!(
+        timezone.format_unix(TIME, format),
+        "2026-10-16 18:04:56.789"
+    );
+    let timezone = "-10".parse::<DisplayTimezone>().unwrap();
+    assert_eq!(
+        timezone.format_unix(TIME, format),
+        "2026-10-16 02:34:56.789"
+    );
+    assert_eq!(DisplayTimezone::Utc.format_unix(f64::MAX, format), "?");
+}
+
+#[test]
+fn time_zones_are_read_and_written_as_text() {
+    for (text, written) in [
+        ("local", "local"),
+        ("Local", "local"),
+        ("UTC", "UTC"),
+        ("utc", "UTC"),
+        ("Z", "UTC"),
+        ("+02:00", "+02:00"),
+        ("-0330", "-03:30"),
+        ("+9", "+09:00"),
+        ("-00:30", "-00:30"),
+    ] {
+        let timezone = text.parse::<DisplayTimezone>().unwrap();
+        assert_eq!(timezone.to_string(), written, "{text}");
+        assert_eq!(written.parse::<DisplayTimezone>(), Ok(timezone));
+    }
+    for text in ["", "Europe/Paris", "02:00", "+24:00", "+02:60", "+é1", "+"] {
+        assert!(text.parse::<DisplayTimezone>().is_err(), "{text}");
+    }
+
+    let config = Config::parse("[time]\ntimezone = \"+02:00\"").unwrap();
+    assert_eq!(config.time.timezone.to_string(), "+02:00");
+    assert!(Config::parse("[time]\ntimezone = \"Mars\"").is_err());
+}
+
+#[test]
+fn clock_offset_is_from_halfway_through_the_ping() {
+    let ping = PingTimes {
+        sent: TIME,
+        car: TIME + 10.5,
+        received: TIME + 1.0,
+    };
+    assert_eq!(ping.clock_offset(), 10.0);
+}
+
+#[test]
+fn wrong_clocks_are_warned_about() {
+    let ping = |offset: f64| {
+        Some(PingTimes {
+            sent: TIME,
+            car: TIME + 0.1 + offset,
+            received: TIME + 0.2,
+        })
+    };
+
+    assert_eq!(clock_warning(TIME, None, 2.0), None);
+    assert_eq!(clock_warning(TIME, ping(1.5), 2.0), None);
+    let warning = clock_warning(TIME, ping(3.0), 2.0).unwrap();
+    assert!(warning.contains("3.0s ahead of"), "{warning}");
+    let warning = clock_warning(TIME, ping(-3.0), 2.0).unwrap();
+    assert!(warning.contains("3.0s behind"), "{warning}");
+
+    // Never set, like a Pi without a network
+    let warning = clock_warning(TIME, ping(-TIME), 2.0).unwrap();
+    assert!(warning.contains("never set"), "{warning}");
+    assert!(clock_unset(60.0));
+    let warning = clock_warning(60.0, None, 2.0).unwrap();
+    assert!(warning.contains("This computer's clock"), "{warning}");
+    assert!(warning.contains("1970-01-01"), "{warning}");
+}
@@ -0,0 +1,45 @@
+/*!
+ * Helpers shared between the tests
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::*;
+
+/***** Helper functions *****/
+
+/// A status from `time` seconds into a run going forward, received at
+/// `1000 + time`
+///
+/// Anything else a test cares about is set on what's returned.
+pub fn status(time: f64, distance: f64, velocity: f64) -> Event<StatusResponse> {
+    Event {
+        command: Command::Status,
+        transit_mode: TransitMode::ServerToClientResponse,
+        transit_type: TransitType::Response,
+        value: StatusResponse {
+            running: true,
+            uptime: 1000 + time as usize,
+            // Only to the second, like the server
+            runtime: time as usize,
+            stage: StatusStage::VehementForward,
+            distance: DistanceInformation {
+                distance,
+                velocity,
+                magnet_hit_counter: 0,
+            },
+            speed_limit_violations: 0,
+            executed_plan: None,
+            radio_stop: None,
+            power: None,
+        },
+        metadata: MetaData {
+            time: 1000.0 + time,
+            sequence: None,
+            frame: None,
+            echo: None,
+            client: None,
+        },
+    }
+}
@@ -0,0 +1,154 @@
+/*!
+ * Tests for loading settings from a config file
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{ClientError, BAUD_RATE};
+use gui::{config::*, logging::LogLevel, shared::*};
+use std::{env::temp_dir, fs::write, path::PathBuf};
+
+/***** Helper functions *****/
+
+/// The message of a config error, panicking on anything else
+fn config_error(result: Result<Config, ClientError>) -> String {
+    match result {
+        Err(ClientError::Config(message)) => message,
+        other => panic!("Expected a config error, got {other:?}"),
+    }
+}
+
+/***** Tests *****/
+
+#[test]
+fn empty_config_is_the_defaults() {
+    let config = Config::parse("").unwrap();
+    assert_eq!(config, Config::default());
+    assert_eq!(config.serial.port, None);
+    assert_eq!(config.serial.baud_rate, BAUD_RATE);
+    assert_eq!(config.serial.poll_interval, SERIAL_DELAY_TIME);
+    assert_eq!(config.run.max_distance, MAX_DISTANCE_RANGE_CENTIMETERS);
+    assert_eq!(config.run.arming_countdown, DEFAULT_ARMING_COUNTDOWN);
+    assert_eq!(config.run.status_watchdog, DEFAULT_STATUS_WATCHDOG);
+    assert_eq!(config.run.auto_reset, DEFAULT_AUTO_RESET);
+    assert_eq!([config.screen.width, config.screen.height], [WIDTH, HEIGHT]);
+    assert_eq!(config.car.number_of_magnets, DEFAULT_NUMBER_OF_MAGNETS);
+}
+
+#[test]
+fn config_overrides_only_what_it_sets() {
+    let config = Config::parse(
+        r#"
+        [serial]
+        port = "/dev/ttyACM0"
+        request_timeout = 2.5
+
+        [car]
+        wheel_diameter = 7.0
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.serial.port.as_deref(), Some("/dev/ttyACM0"));
+    assert_eq!(config.serial.request_timeout, 2.5);
+    assert_eq!(config.serial.baud_rate, BAUD_RATE);
+    assert_eq!(config.car.wheel_diameter, 7.0);
+    assert_eq!(config.car.number_of_magnets, DEFAULT_NUMBER_OF_MAGNETS);
+}
+
+#[test]
+fn invalid_configs_are_rejected() {
+    // Typos shouldn't be silently ignored
+    let message = config_error(Config::parse("[serial]\nbaud = 9600"));
+    assert!(message.contains("baud"), "{message}");
+
+    let message = config_error(Config::parse("[run]\nmax_distance = \"far\""));
+    assert!(message.contains("max_distance = \"far\""), "{message}");
+
+    // Every problem is listed at once
+    let message = config_error(Config::parse(
+        "[serial]\nbaud_rate = 0\n[screen]\nwidth = 10.0\n[car]\nnumber_of_magnets = 0",
+    ));
+    assert_eq!(message.split("; ").count(), 3, "{message}");
+    assert!(message.contains("serial.baud_rate"), "{message}");
+    assert!(message.contains("screen"), "{message}");
+    assert!(message.contains("car.number_of_magnets"), "{message}");
+
+    let message = config_error(Config::parse("[run]\narming_countdown = -1.0"));
+    assert!(message.contains("run.arming_countdown"), "{message}");
+    assert!(Config::parse("[run]\narming_countdown = 0.0").is_ok());
+
+    let message = config_error(Config::parse("[run]\nstatus_watchdog = -1.0"));
+    assert!(message.contains("run.status_watchdog"), "{message}");
+    assert!(Config::parse("[run]\nstatus_watchdog = 0.0").is_ok());
+
+    let message = config_error(Config::parse("[serial]\nsync_timeout = -1.0"));
+    assert!(message.contains("serial.sync_timeout"), "{message}");
+    assert!(Config::parse("[serial]\nsync_timeout = 0.0").is_ok());
+
+    let message = config_error(Config::parse("[run]\nauto_reset = -1.0"));
+    assert!(message.contains("run.auto_reset"), "{message}");
+
+    let message = config_error(Config::parse("[car]\ncoast_deceleration = 0.0"));
+    assert!(message.contains("car.coast_deceleration"), "{message}");
+    assert_eq!(
+        Config::parse("[car]\ncoast_deceleration = 28.5")
+            .unwrap()
+            .car
+            .coast_deceleration,
+        Some(28.5)
+    );
+
+    let message = config_error(Config::parse("[car]\nlow_battery_charge = 120.0"));
+    assert!(message.contains("car.low_battery_charge"), "{message}");
+    let message = config_error(Config::parse("[car]\nlow_battery_voltage = -1.0"));
+    assert!(message.contains("car.low_battery_voltage"), "{message}");
+
+    let message = config_error(Config::parse("[screen]\ntable_rows = 100000"));
+    assert!(message.contains("screen.table_rows"), "{message}");
+    assert!(Config::parse("[screen]\ntable_rows = 0").is_ok());
+
+    let message = config_error(Config::parse("[log]\nevents = 0"));
+    assert!(message.contains("log.events"), "{message}");
+    let message = config_error(Config::parse("[log]\nlevel = \"loud\""));
+    assert!(message.contains("loud"), "{message}");
+    assert_eq!(
+        Config::parse("[log]\nlevel = \"debug\"").unwrap().log.level,
+        LogLevel::Debug
+    );
+}
+
+#[test]
+fn bad_config_file_falls_back_to_the_defaults() {
+    let path = temp_dir().join(format!("gui-config-test-{}.toml", std::process::id()));
+    write(&path, "[serial]\npoll_interval = -1.0").unwrap();
+    let (config, error) = Config::load(Some(path.clone()));
+    assert_eq!(config, Config::default());
+    assert!(matches!(error, Some(ClientError::Config(_))));
+
+    write(&path, "[run]\nmax_distance = 300.0").unwrap();
+    let (config, error) = Config::load(Some(path.clone()));
+    assert_eq!(config.run.max_distance, 300.0);
+    assert!(error.is_none());
+    std::fs::remove_file(&path).unwrap();
+
+    // A config file that was asked for but doesn't exist is an error
+    let (_, error) = Config::load(Some(PathBuf::from("/nonexistent/config.toml")));
+    assert!(matches!(error, Some(ClientError::Config(_))));
+}
+
+#[test]
+fn tuned_config_is_written_back() {
+    let path = temp_dir()
+        .join(format!("gui-config-test-{}", std::process::id()))
+        .join("config.toml");
+    let mut config = Config::default();
+    config.serial.port = Some("/dev/ttyACM0".to_owned());
+    config.serial.poll_interval = 0.25;
+    config.serial.max_request_attempts = 5;
+    config.screen.table_rows = 50;
+    config.write(&path).unwrap();
+
+    assert_eq!(Config::read(&path).unwrap(), config);
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
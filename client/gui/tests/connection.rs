@@ -0,0 +1,208 @@
+/*!
+ * Tests for finding the car's serial port and reconnecting to it
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+#[cfg(target_os = "linux")]
+use bindings::UNIX_PREFIX;
+#[cfg(target_os = "linux")]
+use gui::shared::*;
+use gui::{config::*, events::*};
+use serialport::{SerialPortType, UsbPortInfo};
+#[cfg(target_os = "linux")]
+use std::{
+    env::temp_dir,
+    fs::remove_file,
+    io::Write,
+    os::unix::{fs::symlink, net::UnixListener},
+    thread::{sleep, spawn},
+    time::Duration,
+};
+#[cfg(target_os = "linux")]
+use vserial::PtyPair;
+
+/***** Helper functions *****/
+
+fn usb_port(vid: u16, pid: u16, product: &str) -> SerialPortType {
+    SerialPortType::UsbPort(UsbPortInfo {
+        vid,
+        pid,
+        serial_number: None,
+        manufacturer: None,
+        product: Some(product.to_owned()),
+    })
+}
+
+fn connection_manager(port: Option<String>) -> ConnectionManager {
+    ConnectionManager::new(port, &SerialConfig::default())
+}
+
+/***** Tests *****/
+
+#[test]
+fn usb_ports_are_matched() {
+    let mut connection = connection_manager(None);
+    // Anything USB without anything to go by
+    assert!(connection.matches(&usb_port(0x2341, 0x0043, "Arduino Uno")));
+    assert!(!connection.matches(&SerialPortType::PciPort));
+
+    connection.usb_vid = Some(0x2341);
+    connection.description = Some("arduino".to_owned());
+    assert!(connection.matches(&usb_port(0x2341, 0x0043, "Arduino Uno")));
+    assert!(!connection.matches(&usb_port(0x2341, 0x0043, "Genuino")));
+    assert!(!connection.matches(&usb_port(0x1a86, 0x0043, "Arduino Uno")));
+
+    connection.usb_pid = Some(0x0001);
+    assert!(!connection.matches(&usb_port(0x2341, 0x0043, "Arduino Uno")));
+}
+
+#[test]
+fn failed_attempts_back_off() {
+    let mut connection = connection_manager(Some("/nonexistent/tty".to_owned()));
+    let mut delays = vec![];
+    for _ in 0..6 {
+        delays.push(connection.delay);
+        assert!(connection.connect().is_err());
+    }
+    assert_eq!(delays, [0.5, 1.0, 2.0, 4.0, 8.0, 8.0]);
+    assert!(matches!(
+        connection.state,
+        ConnectionState::Reconnecting {
+            attempts: 6,
+            reason: Some(_),
+        }
+    ));
+    // Not time to try again yet
+    assert!(connection.poll().is_none());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn lost_connection_is_reconnected() {
+    // The car's port shows up under a name that doesn't exist yet
+    let port = temp_dir().join(format!("gui-connection-test-{}", std::process::id()));
+    let _ = remove_file(&port);
+    let mut propagator =
+        SerialEventPropagator::connecting(connection_manager(Some(port.display().to_string())));
+    assert!(propagator.transport.is_none());
+    assert!(propagator.read_from_serial().unwrap().is_none());
+    assert!(propagator.retry_pending().is_empty());
+
+    // Plugged in
+    let (mut car, car_slave) = PtyPair::open().unwrap().into_master();
+    symlink(&car_slave.path, &port).unwrap();
+    sleep(Duration::from_secs_f64(
+        RECONNECT_INITIAL_DELAY_SECONDS * 1.2,
+    ));
+    assert!(propagator.maintain_connection());
+    assert!(matches!(
+        propagator.connection.as_ref().unwrap().state,
+        ConnectionState::Connected { .. }
+    ));
+    writeln!(car, "~PING${{\"sent_time\": 1.0}}${{\"time\": 1.0}}").unwrap();
+    car.flush().unwrap();
+    sleep(Duration::from_secs_f64(SERIAL_DELAY_TIME));
+    assert!(propagator.read_from_serial().unwrap().is_some());
+
+    // Unplugged
+    drop(car);
+    remove_file(&port).unwrap();
+    assert!(propagator.read_from_serial().is_err());
+    assert!(propagator.transport.is_none());
+    assert!(matches!(
+        propagator.connection.as_ref().unwrap().state,
+        ConnectionState::Reconnecting { attempts: 0, .. }
+    ));
+    assert!(!propagator.maintain_connection());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn restarted_bridge_is_reconnected() {
+    let socket = temp_dir().join(format!("gui-bridge-test-{}.sock", std::process::id()));
+    let _ = remove_file(&socket);
+    let mut propagator = SerialEventPropagator::connecting(connection_manager(Some(format!(
+        "{UNIX_PREFIX}{}",
+        socket.display()
+    ))));
+    // Not listening yet
+    assert!(!propagator.maintain_connection());
+
+    let wait_to_reconnect = || {
+        sleep(Duration::from_secs_f64(
+            RECONNECT_INITIAL_DELAY_SECONDS * 1.2,
+        ))
+    };
+    for _ in 0..2 {
+        let bridge = UnixListener::bind(&socket).unwrap();
+        wait_to_reconnect();
+        assert!(propagator.maintain_connection());
+        let (mut client, _) = bridge.accept().unwrap();
+        writeln!(client, "~PING${{\"sent_time\": 1.0}}${{\"time\": 1.0}}").unwrap();
+        sleep(Duration::from_secs_f64(SERIAL_DELAY_TIME));
+        assert!(propagator.read_from_serial().unwrap().is_some());
+
+        // Stopped, taking its socket with it
+        drop((client, bridge));
+        remove_file(&socket).unwrap();
+        assert!(propagator.read_from_serial().is_err());
+        assert!(matches!(
+            propagator.connection.as_ref().unwrap().state,
+            ConnectionState::Reconnecting { .. }
+        ));
+    }
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn whatever_was_left_on_the_connection_is_thrown_away() {
+    let socket = temp_dir().join(format!("gui-sync-test-{}.sock", std::process::id()));
+    let _ = remove_file(&socket);
+    let bridge = UnixListener::bind(&socket).unwrap();
+    // Boot messages, then a frame cut off partway through
+    let booting = spawn(move || {
+        let (mut client, _) = bridge.accept().unwrap();
+        client
+            .write_all(b"Booting...\n~STATUS${\"running\": tr")
+            .unwrap();
+        client
+    });
+
+    let mut propagator = SerialEventPropagator::connecting(connection_manager(Some(format!(
+        "{UNIX_PREFIX}{}",
+        socket.display()
+    ))));
+    let mut client = booting.join().unwrap();
+    assert!(propagator.transport.is_some());
+
+    let ping = "~PING${\"sent_time\": 1.0}${\"time\": 1.0}";
+    writeln!(client, "{ping}").unwrap();
+    sleep(Duration::from_secs_f64(SERIAL_DELAY_TIME));
+    assert_eq!(
+        propagator.read_from_serial().unwrap().as_deref(),
+        Some(ping)
+    );
+    remove_file(&socket).unwrap();
+}
+
+#[test]
+fn tuned_timings_are_used_straight_away() {
+    let mut propagator = SerialEventPropagator::connecting(connection_manager(Some(
+        "/nonexistent/gui-connection-test".to_owned(),
+    )));
+    let config = SerialConfig {
+        poll_interval: 0.25,
+        request_timeout: 2.5,
+        max_request_attempts: 5,
+        sync_timeout: 0.5,
+        ..Default::default()
+    };
+    propagator.apply_serial_config(&config);
+
+    assert_eq!(propagator.request_timeout, 2.5);
+    assert_eq!(propagator.max_request_attempts, 5);
+    assert_eq!(propagator.connection.as_ref().unwrap().timeout, 0.25);
+    assert_eq!(propagator.connection.as_ref().unwrap().sync_timeout, 0.5);
+}
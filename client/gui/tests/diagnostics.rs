@@ -0,0 +1,124 @@
+/*!
+ * Tests for anonymizing diagnostic bundles and loading them back in
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::ClientError;
+use chrono::{TimeZone, Utc};
+use gui::{annotations::Annotation, diagnostics::*, journal::Journal, ErrorData};
+use std::{
+    env::{set_var, temp_dir},
+    fs::{create_dir_all, remove_dir_all},
+    path::PathBuf,
+};
+
+// Constants
+const ADDRESS: &str = "00:60:37:E9:0B:6F";
+const ANONYMIZED: &str = "XX:XX:XX:XX:XX:XX";
+
+/***** Helper functions *****/
+
+/// An empty directory for bundles, just for one test
+fn bundle_directory(test: &str) -> PathBuf {
+    let directory = temp_dir().join(format!("gui-diagnostics-{test}-{}", std::process::id()));
+    let _ = remove_dir_all(&directory);
+    create_dir_all(&directory).unwrap();
+    directory
+}
+
+/***** Tests *****/
+
+#[test]
+fn addresses_at_either_end_are_anonymized() {
+    assert_eq!(anonymize(ADDRESS), ANONYMIZED);
+    assert_eq!(
+        anonymize(&format!("{ADDRESS} connected to {ADDRESS}")),
+        format!("{ANONYMIZED} connected to {ANONYMIZED}")
+    );
+    // Too short to be an address, right up against the end
+    assert_eq!(anonymize("00:60:37:E9:0B:6"), "00:60:37:E9:0B:6");
+    assert_eq!(anonymize("00-60-37-E9-0B-6F"), "00-60-37-E9-0B-6F");
+}
+
+#[test]
+fn characters_next_to_addresses_are_kept_whole() {
+    assert_eq!(
+        anonymize(&format!("→{ADDRESS}✓ émis")),
+        format!("→{ANONYMIZED}✓ émis")
+    );
+    // A multibyte character where the address would end
+    assert_eq!(anonymize("00:60:37:E9:0B:6é"), "00:60:37:E9:0B:6é");
+}
+
+#[test]
+fn home_directory_and_user_name_are_anonymized() {
+    // Both in one test, as the environment is shared between tests
+    set_var("HOME", "/home/diagnostics-tester");
+    set_var("USER", "diagnostics-tester");
+    assert_eq!(
+        anonymize("Saved to /home/diagnostics-tester/runs, then C:\\Users\\diagnostics-tester"),
+        "Saved to ~/runs, then C:\\Users\\<user>"
+    );
+
+    // The Pi's user name is only taken out of paths
+    set_var("HOME", "/home/pi");
+    set_var("USER", "pi");
+    assert_eq!(
+        anonymize(
+            "Sent ?PING${\"time\": 1.0}$$ to raspberrypi.local, saved to /home/pi/runs and \
+             /Users/pi, not /home/pipeline"
+        ),
+        "Sent ?PING${\"time\": 1.0}$$ to raspberrypi.local, saved to ~/runs and \
+         /Users/<user>, not /home/pipeline"
+    );
+}
+
+#[test]
+fn bundles_are_read_back_as_written() {
+    let path = bundle_directory("round-trip").join("bundle.zip");
+    let time = Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+    let bundle = DiagnosticBundle {
+        version: version_info(),
+        config: "{\"serial\": {}}".to_owned(),
+        errors: vec![ErrorData {
+            error: ClientError::Recorded("Lost the car".to_owned()),
+            time,
+            recovery: None,
+        }],
+        bridge_output: vec![format!("Connected to {ADDRESS}"), "Ready".to_owned()],
+        frames: vec![
+            "> ~PING${\"sent_time\": 1.0}${\"time\": 1.0}".to_owned(),
+            "< ~PING${\"time_delta\": 0.1}${\"time\": 1.1}".to_owned(),
+        ],
+        status_responses: vec![],
+        journal: Journal::default(),
+        annotations: vec![Annotation::Marker {
+            time: 0.5,
+            label: "Bump".to_owned(),
+        }],
+    };
+    bundle.write(&path).unwrap();
+
+    let read = DiagnosticBundle::read(&path).unwrap();
+    assert_eq!(read.version, bundle.version);
+    assert_eq!(read.config, bundle.config);
+    assert_eq!(read.errors.len(), 1);
+    assert_eq!(
+        read.errors[0].error.to_string(),
+        bundle.errors[0].error.to_string()
+    );
+    assert_eq!(read.errors[0].time, time);
+    assert_eq!(
+        read.bridge_output,
+        vec![format!("Connected to {ANONYMIZED}"), "Ready".to_owned()]
+    );
+    assert_eq!(read.frames, bundle.frames);
+    assert_eq!(
+        read.received_frames().collect::<Vec<_>>(),
+        vec!["~PING${\"time_delta\": 0.1}${\"time\": 1.1}"]
+    );
+    assert!(read.status_responses.is_empty());
+    assert_eq!(read.annotations, bundle.annotations);
+}
@@ -0,0 +1,71 @@
+/*!
+ * Tests for counting errors by the part of the system they came from
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{ClientError, Command, ErrorResponse, ServerError};
+use gui::{error_badge::*, ErrorData};
+
+/***** Tests *****/
+
+#[test]
+fn errors_are_sorted_by_subsystem() {
+    let cases = [
+        (
+            ClientError::Serial("Not connected".to_owned()),
+            Subsystem::Serial,
+        ),
+        (
+            ClientError::Timeout {
+                command: Command::Ping,
+                attempts: 3,
+            },
+            Subsystem::Serial,
+        ),
+        (
+            ClientError::Bluetooth("The bridge exited".to_owned()),
+            Subsystem::Bluetooth,
+        ),
+        (
+            ClientError::ChecksumMismatch {
+                command: None,
+                expected: 0x1234,
+                calculated: 0x4321,
+            },
+            Subsystem::Protocol,
+        ),
+        (
+            ClientError::Parse("Bad JSON".to_owned()),
+            Subsystem::Protocol,
+        ),
+        (
+            ClientError::from(&ErrorResponse {
+                error_variant: ServerError::FailedToStartAlreadyStarted as u8,
+                message: "Already started".to_owned(),
+            }),
+            Subsystem::Server,
+        ),
+        (ClientError::CSV("Disk full".to_owned()), Subsystem::Other),
+    ];
+    for (error, subsystem) in cases {
+        assert_eq!(Subsystem::of(&error), subsystem, "{error}");
+    }
+}
+
+#[test]
+fn only_subsystems_with_errors_are_counted() {
+    assert!(error_counts(&[]).is_empty());
+
+    let errors = [
+        ClientError::Parse("Bad JSON".to_owned()),
+        ClientError::Serial("Not connected".to_owned()),
+        ClientError::Parse("Bad JSON".to_owned()),
+    ]
+    .map(ErrorData::new);
+    assert_eq!(
+        error_counts(&errors),
+        [(Subsystem::Serial, 1), (Subsystem::Protocol, 2)]
+    );
+}
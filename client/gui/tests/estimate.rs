@@ -0,0 +1,65 @@
+/*!
+ * Tests for guessing where the car is while no statuses come in
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+mod common;
+use bindings::*;
+use gui::estimate::*;
+
+/***** Helper functions *****/
+
+/// A status at `time` by the car's clock
+fn status(time: f64, distance: f64, velocity: f64, stage: StatusStage) -> Event<StatusResponse> {
+    let mut status = common::status(time, distance, velocity);
+    status.value.running = !matches!(stage, StatusStage::Finalized);
+    status.value.stage = stage;
+    status.metadata.time = time;
+    status
+}
+
+/***** Tests *****/
+
+#[test]
+fn a_steady_car_keeps_going_with_growing_uncertainty() {
+    let statuses = [
+        status(1.0, 100.0, 50.0, StatusStage::VehementForward),
+        status(2.0, 150.0, 50.0, StatusStage::VehementForward),
+    ];
+
+    let soon = PositionEstimate::after(&statuses, 1.0).unwrap();
+    let later = PositionEstimate::after(&statuses, 4.0).unwrap();
+    assert_eq!(soon.distance, 200.0);
+    assert_eq!(later.distance, 350.0);
+    assert!(!later.stopped);
+    assert!(later.uncertainty > soon.uncertainty);
+    let (low, high) = later.range();
+    assert!(low < later.distance && later.distance < high);
+}
+
+#[test]
+fn a_slowing_car_stops_where_it_would_have() {
+    // Slowing down by 20cm/s^2, so it stops 2.5s and 62.5cm later
+    let statuses = [
+        status(1.0, 100.0, 70.0, StatusStage::VehementForward),
+        status(2.0, 160.0, 50.0, StatusStage::VehementForward),
+    ];
+
+    let moving = PositionEstimate::after(&statuses, 1.0).unwrap();
+    assert_eq!(moving.distance, 200.0);
+    assert!(!moving.stopped);
+    let stopped = PositionEstimate::after(&statuses, 10.0).unwrap();
+    assert_eq!(stopped.distance, 222.5);
+    assert!(stopped.stopped);
+    // Never short of where it last was
+    assert!(stopped.range().0 >= 160.0);
+}
+
+#[test]
+fn nothing_is_guessed_once_the_run_is_over() {
+    assert!(PositionEstimate::after(&[], 1.0).is_none());
+    let statuses = [status(2.0, 500.0, 0.0, StatusStage::Finalized)];
+    assert!(PositionEstimate::after(&statuses, 1.0).is_none());
+}
@@ -0,0 +1,248 @@
+/*!
+ * Tests for exporting runs in every format
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+mod common;
+use bindings::{Event, StatusResponse, StatusStage};
+use gui::{data_directory::*, export::*, run_cache::*, shared::Units};
+use std::{
+    env::temp_dir,
+    fs::{create_dir_all, read_to_string, remove_dir_all, write},
+    path::{Path, PathBuf},
+};
+
+/***** Helper functions *****/
+
+fn status(runtime: usize) -> Event<StatusResponse> {
+    let mut status = common::status(runtime as f64, runtime as f64 * 1.5, 1.5);
+    status.value.running = runtime < 100;
+    status.value.stage = StatusStage::CautiousBackward;
+    status.value.distance.magnet_hit_counter = runtime * 3;
+    status.value.speed_limit_violations = runtime / 10;
+    status.metadata.time += 0.25;
+    status
+}
+
+/// An empty directory to export runs to, just for one test
+fn export_directory(test: &str) -> PathBuf {
+    let directory = temp_dir().join(format!("gui-export-{test}-{}", std::process::id()));
+    let _ = remove_dir_all(&directory);
+    create_dir_all(&directory).unwrap();
+    directory
+}
+
+/// What's kept of a status, as `Event` can't be compared
+fn fields(status: &Event<StatusResponse>) -> (f64, bool, usize, usize, f64, f64, usize, u8, usize) {
+    let value = &status.value;
+    (
+        status.metadata.time,
+        value.running,
+        value.uptime,
+        value.runtime,
+        value.distance.distance,
+        value.distance.velocity,
+        value.distance.magnet_hit_counter,
+        value.stage as u8,
+        value.speed_limit_violations,
+    )
+}
+
+fn assert_same_run(read: &[Event<StatusResponse>], written: &[Event<StatusResponse>]) {
+    assert_eq!(
+        read.iter().map(fields).collect::<Vec<_>>(),
+        written.iter().map(fields).collect::<Vec<_>>()
+    );
+}
+
+fn run(length: usize) -> Vec<Event<StatusResponse>> {
+    (1..=length).map(status).collect()
+}
+
+/***** Tests *****/
+
+#[test]
+fn every_format_reads_back_what_it_wrote() {
+    let directory = export_directory("round-trip");
+    let statuses = run(300);
+
+    for format in RunFormat::ALL {
+        let path = format.path(&directory.join("run"));
+        format.write(&path, &statuses).unwrap();
+        assert_eq!(RunFormat::of(&path), Some(*format));
+        assert_same_run(&read_run(&path).unwrap(), &statuses);
+
+        let last = read_last_of_run(&path).unwrap().unwrap();
+        assert_same_run(&[last], &statuses[statuses.len() - 1..]);
+
+        let empty = format.path(&directory.join("empty"));
+        format.write(&empty, &[]).unwrap();
+        assert!(read_run(&empty).unwrap().is_empty());
+        assert!(read_last_of_run(&empty).unwrap().is_none());
+    }
+}
+
+#[test]
+fn last_json_line_is_read_from_the_end() {
+    let directory = export_directory("json-lines");
+
+    let short = directory.join("short.jsonl");
+    JSONLinesDynamicStatus::write(&short, &run(1)).unwrap();
+    let last = JSONLinesDynamicStatus::read_last(&short).unwrap().unwrap();
+    assert_eq!(last.value.runtime, 1);
+
+    let broken = directory.join("broken.jsonl");
+    write(&broken, "{\"time\": 1.0}\n").unwrap();
+    assert!(JSONLinesDynamicStatus::read_last(&broken).is_err());
+    assert!(read_run(&broken).is_err());
+}
+
+#[test]
+fn csv_spells_out_the_time_in_utc() {
+    let directory = export_directory("csv-utc");
+    let path = directory.join("run.csv");
+    CSVDynamicStatus::write(&path, &run(1)).unwrap();
+
+    let contents = read_to_string(&path).unwrap();
+    let mut lines = contents.lines();
+    assert!(lines.next().unwrap().starts_with("Unix time (UTC),"));
+    assert!(lines.next().unwrap().contains(",1970-01-01T00:16:41.250Z"));
+
+    // Tables from before the column was added still read
+    let old = directory.join("old.csv");
+    write(&old, "Unix time,Running,Uptime,Runtime,Distance in centimeters,Velocity in centimeters/second,Magnet hit counter,Stage,Speed limit violations\n1001.25,true,1001,1,1.5,1.5,3,3,0\n").unwrap();
+    assert_same_run(&read_run(&old).unwrap(), &run(1));
+}
+
+#[test]
+fn csv_in_inches_is_read_back_in_centimeters() {
+    let directory = export_directory("csv-inches");
+    let path = directory.join("run.csv");
+    let statuses = run(20);
+    RunFormat::CSV
+        .write_with(
+            &path,
+            &statuses,
+            TableOptions {
+                units: Units::Imperial,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let contents = read_to_string(&path).unwrap();
+    assert!(contents
+        .lines()
+        .next()
+        .unwrap()
+        .contains(",Distance in inches,Velocity in inches/second,"));
+    let last_row = contents.lines().last().unwrap();
+    assert!(
+        last_row.contains(&format!(",{},", 30.0 / 2.54)),
+        "{last_row}"
+    );
+
+    let near = |read: &Event<StatusResponse>, written: &Event<StatusResponse>| {
+        (read.value.distance.distance - written.value.distance.distance).abs() < 1e-9
+            && (read.value.distance.velocity - written.value.distance.velocity).abs() < 1e-9
+    };
+    let read = read_run(&path).unwrap();
+    assert_eq!(read.len(), statuses.len());
+    assert!(read
+        .iter()
+        .zip(&statuses)
+        .all(|(read, written)| near(read, written)));
+    let last = read_last_of_run(&path).unwrap().unwrap();
+    assert!(near(&last, statuses.last().unwrap()));
+
+    assert_eq!(Units::Imperial.format_distance(30.48, 1), "12.0in");
+    assert_eq!(Units::Imperial.format_long_distance(30.48, 2), "1.00ft");
+    assert_eq!(Units::Metric.format_long_distance(250.0, 2), "2.50m");
+}
+
+#[test]
+fn runs_are_found_in_any_format() {
+    let directory = export_directory("found");
+    let data_directory = DataDirectory::at(directory.clone()).unwrap();
+
+    let csv = data_directory.next_run_path(500.0, RunFormat::CSV);
+    RunFormat::CSV.write(&csv, &run(5)).unwrap();
+    let json_lines = data_directory.next_run_path(500.0, RunFormat::JSONLines);
+    assert_eq!(json_lines.extension().unwrap(), "jsonl");
+    RunFormat::JSONLines.write(&json_lines, &run(10)).unwrap();
+    write(directory.join("notes.txt"), "Not a run").unwrap();
+
+    assert_eq!(data_directory.runs(), vec![csv.clone(), json_lines.clone()]);
+    let mut run_cache = RunCache::new();
+    assert_eq!(run_cache.summary(&csv).unwrap().runtime, 5);
+    assert_eq!(run_cache.summary(&json_lines).unwrap().runtime, 10);
+    assert_eq!(RunFormat::of(Path::new("notes.txt")), None);
+    assert_eq!(
+        RunFormat::JSONLines.path(Path::new("status.csv")),
+        PathBuf::from("status.jsonl")
+    );
+}
+
+#[test]
+fn sequence_and_frame_numbers_are_saved() {
+    let directory = export_directory("numbers");
+    let mut statuses = run(10);
+    for (idx, status) in statuses.iter_mut().enumerate() {
+        status.metadata.frame = Some(idx as u32 + 40);
+        // Statuses sent on their own don't answer a request
+        status.metadata.sequence = (idx % 2 == 0).then_some(idx as u32);
+    }
+    let numbers = |statuses: &[Event<StatusResponse>]| {
+        statuses
+            .iter()
+            .map(|status| (status.metadata.sequence, status.metadata.frame))
+            .collect::<Vec<_>>()
+    };
+
+    for format in RunFormat::ALL {
+        let path = format.path(&directory.join("run"));
+        format.write(&path, &statuses).unwrap();
+        assert_eq!(numbers(&read_run(&path).unwrap()), numbers(&statuses));
+        let last = read_last_of_run(&path).unwrap().unwrap();
+        assert_eq!(numbers(&[last]), numbers(&statuses[9..]));
+    }
+}
+
+#[test]
+fn merged_runs_match_statuses_by_their_numbers() {
+    let numbered = |frame: u32| {
+        let mut status = status(frame as usize);
+        status.metadata.frame = Some(frame);
+        status
+    };
+    // The client lost frames 2 and 3, and the server's log stops at 4
+    let client = [0, 1, 4, 5, 6].map(numbered).to_vec();
+    let mut server = (0..5).map(numbered).collect::<Vec<_>>();
+    // Sent in the same millisecond, but still two statuses
+    server[3].metadata.time = server[2].metadata.time;
+    server[0].value.speed_limit_violations = 99;
+
+    let merged = merge_status_tables(client, server);
+    assert_eq!(
+        merged
+            .iter()
+            .map(|status| status.metadata.frame.unwrap())
+            .collect::<Vec<_>>(),
+        [0, 1, 2, 3, 4, 5, 6]
+    );
+    // The server's copy wins
+    assert_eq!(merged[0].value.speed_limit_violations, 99);
+
+    // Runs saved without the numbers go by the time the server sent them
+    assert_eq!(merge_status_tables(run(3), run(5)).len(), 5);
+
+    let directory = export_directory("merge");
+    let (client, server) = (directory.join("client.csv"), directory.join("server.jsonl"));
+    RunFormat::CSV.write(&client, &run(3)).unwrap();
+    RunFormat::JSONLines.write(&server, &run(5)[1..]).unwrap();
+    let output = directory.join("merged.csv");
+    assert_eq!(merge_runs(&client, &server, &output).unwrap(), 5);
+    assert_same_run(&read_run(&output).unwrap(), &run(5));
+}
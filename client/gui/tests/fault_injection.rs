@@ -0,0 +1,64 @@
+/*!
+ * Tests for what the debug menu injects
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+#![cfg(feature = "debug-menu")]
+
+/***** Setup *****/
+// Imports
+use bindings::{ClientError, Command, Frame, LoopbackTransport, Response, StatusArguments};
+use gui::{events::*, fault_injection::*};
+
+/***** Tests *****/
+
+#[test]
+fn error_responses_answer_their_request() {
+    let frame = error_response(INJECTABLE_ERRORS[1], Some(7), 1.0).unwrap();
+    match Frame::decode(&frame).unwrap() {
+        Response::Error(error) => {
+            assert_eq!(error.metadata.sequence, Some(7));
+            let message = ClientError::from(&error.value).to_string();
+            assert!(message.contains(INJECTED_MESSAGE), "{message}");
+        }
+        other => panic!("Expected an error response, got {other:?}"),
+    }
+}
+
+#[test]
+fn malformed_frames_are_malformed() {
+    for malformed_frame in MalformedFrame::ALL {
+        let result = Frame::decode(&malformed_frame.frame());
+        assert!(result.is_err(), "{}", malformed_frame.label());
+    }
+    assert!(matches!(
+        Frame::decode(&MalformedFrame::BadChecksum.frame()),
+        Err(ClientError::ChecksumMismatch {
+            command: Some(Command::Status),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn injections_come_before_the_connection() {
+    let (transport, _car) = LoopbackTransport::pair();
+    let mut propagator = SerialEventPropagator::new(Box::new(transport));
+    propagator
+        .write_to_serial(Command::Status, StatusArguments {})
+        .unwrap();
+    let sequence = propagator.pending_requests[0].sequence;
+
+    propagator.injected.push_back(Injection::Frame(
+        error_response(INJECTABLE_ERRORS[0], Some(sequence), 1.0).unwrap(),
+    ));
+    propagator.injected.push_back(Injection::LinkDown);
+    assert!(propagator.read_from_serial().unwrap().is_some());
+    assert!(propagator.pending_requests.is_empty());
+    assert!(propagator.read_from_serial().is_err());
+    assert!(propagator.read_from_serial().unwrap().is_none());
+    assert!(propagator
+        .recent_frames
+        .back()
+        .unwrap()
+        .starts_with("< ~ERROR$"));
+}
@@ -0,0 +1,1858 @@
+/*!
+ * Headless tests of the GUI, driving it frame by frame with scripted input
+ * and scripted responses from the car
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+#![cfg(feature = "ui")]
+
+/***** Setup *****/
+// Imports
+use bindings::{
+    crc16, ClientError, ClientStatus, Command, Frame, LoopbackTransport, RawFrame, Response,
+    Transport, PROTOCOL_VERSION,
+};
+use egui::{
+    epaint::TextShape, Context, Event, Key, Modifiers, PointerButton, Pos2, RawInput, Rect, Shape,
+    Visuals,
+};
+use gui::{
+    changelog::*, clock::*, config::*, events::*, export::*, journal::*, settings::*, shared::*,
+    splits::*, startup_checks::*, wire_log::*, *,
+};
+use std::{
+    env::{set_var, temp_dir},
+    fs::{create_dir_all, metadata, remove_dir_all, remove_file, write},
+    thread::sleep,
+    time::{Duration, Instant},
+};
+use tracing_subscriber::layer::SubscriberExt;
+
+/***** Harness *****/
+
+/// The GUI on a 480x320 screen, with a loopback standing in for the car
+struct Harness {
+    app: ClientGUI,
+    car: LoopbackTransport,
+    ctx: Context,
+}
+impl Harness {
+    fn new() -> Self {
+        // Keep automatically saved runs out of the real data directory
+        set_var("XDG_DATA_HOME", temp_dir().join("gui-tests"));
+
+        let (transport, car) = LoopbackTransport::pair();
+        let mut app = ClientGUI::new(SerialEventPropagator::new(Box::new(transport)));
+        app.gui_data.show_bluetooth_connect_screen = false;
+        // Most tests aren't about arming, see `start_has_to_be_confirmed`
+        app.config.run.arming_countdown = 0.0;
+
+        let mut harness = Self {
+            app,
+            car,
+            ctx: Context::default(),
+        };
+        harness.frame(vec![]);
+        harness
+    }
+
+    /// Run one frame with some input events, returning the text drawn
+    fn frame(&mut self, events: Vec<Event>) -> Vec<TextShape> {
+        fn collect(shape: Shape, texts: &mut Vec<TextShape>) {
+            match shape {
+                Shape::Text(text) => texts.push(text),
+                Shape::Vec(shapes) => shapes.into_iter().for_each(|shape| collect(shape, texts)),
+                _ => (),
+            }
+        }
+
+        let input = RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, [WIDTH, HEIGHT].into())),
+            events,
+            ..Default::default()
+        };
+        let app = &mut self.app;
+        let output = self.ctx.run(input, |ctx| app.show(ctx));
+
+        let mut texts = vec![];
+        for clipped_shape in output.shapes {
+            collect(clipped_shape.1, &mut texts);
+        }
+        texts
+    }
+
+    /// All text drawn
+    fn texts(&mut self) -> Vec<String> {
+        self.frame(vec![])
+            .into_iter()
+            .map(|text| text.galley.text().to_owned())
+            .collect()
+    }
+
+    /// The text of a widget, if it's drawn
+    fn find(&mut self, label: &str) -> Option<TextShape> {
+        self.frame(vec![])
+            .into_iter()
+            .find(|text| text.galley.text() == label)
+    }
+
+    /// Whether a widget with this label is drawn and can be interacted with
+    ///
+    /// Disabled widgets have their text faded out, so this only works for
+    /// widgets not being hovered
+    fn enabled(&mut self, label: &str) -> bool {
+        let enabled_color = Visuals::dark().widgets.inactive.text_color();
+        self.find(label)
+            .is_some_and(|text| text.override_text_color == Some(enabled_color))
+    }
+
+    /// Click a widget by its label
+    fn click(&mut self, label: &str) {
+        let text = self
+            .find(label)
+            .unwrap_or_else(|| panic!("Nothing labeled `{label}` is drawn"));
+        let pos = Rect::from_min_size(text.pos, text.galley.size()).center();
+        let button = |pressed| Event::PointerButton {
+            pos,
+            button: PointerButton::Primary,
+            pressed,
+            modifiers: Modifiers::NONE,
+        };
+
+        self.frame(vec![Event::PointerMoved(pos)]);
+        self.frame(vec![button(true)]);
+        self.frame(vec![button(false)]);
+        self.frame(vec![Event::PointerGone]);
+    }
+
+    /// Press and release a key
+    fn press(&mut self, key: Key) {
+        let event = |pressed| Event::Key {
+            key,
+            pressed,
+            repeat: false,
+            modifiers: Modifiers::NONE,
+        };
+
+        self.frame(vec![event(true)]);
+        self.frame(vec![event(false)]);
+    }
+
+    /// Save the run and run frames until it's saved in the background
+    fn save(&mut self) {
+        self.click("Save");
+        for _ in 0..100 {
+            if self.app.save_job.is_none() {
+                return;
+            }
+            sleep(Duration::from_millis(10));
+            self.frame(vec![]);
+        }
+        panic!("The run never finished saving");
+    }
+
+    /// Send a frame from the car and run frames until the GUI has read it
+    fn respond(&mut self, frame: &str) {
+        self.car.write_line(frame).unwrap();
+        for _ in 0..3 {
+            sleep(Duration::from_secs_f64(SERIAL_DELAY_TIME * 1.5));
+            self.frame(vec![]);
+        }
+    }
+}
+
+/***** Tests *****/
+
+#[test]
+fn idle_shows_start() {
+    let mut harness = Harness::new();
+
+    assert!(harness.enabled("START"));
+    assert!(harness.find("STOP").is_none());
+    assert!(harness.enabled("+100"));
+    assert!(harness
+        .texts()
+        .iter()
+        .any(|text| text == "No ping information available"));
+}
+
+#[test]
+fn start_without_distance_errors() {
+    let mut harness = Harness::new();
+
+    harness.click("START");
+    assert!(!harness.app.run_data.running);
+    assert_eq!(harness.app.errors.len(), 1);
+    assert!(harness.enabled("Clear"));
+
+    harness.click("Clear");
+    assert!(harness.app.errors.is_empty());
+    assert!(harness.find("Clear").is_none());
+}
+
+#[test]
+fn start_is_not_enabled_during_run() {
+    let mut harness = Harness::new();
+
+    harness.click("+100");
+    assert_eq!(harness.app.gui_data.distance, 100.0);
+    harness.click("START");
+    assert!(harness.app.run_data.running);
+
+    assert!(harness.find("START").is_none());
+    assert!(harness.enabled("STOP"));
+    // Distance can't be changed mid run
+    assert!(!harness.enabled("+100"));
+    harness.click("+100");
+    assert_eq!(harness.app.gui_data.distance, 100.0);
+    // Neither can the file be loaded
+    assert!(!harness.enabled("Load status"));
+}
+
+#[test]
+fn stop_ends_run() {
+    let mut harness = Harness::new();
+
+    harness.click("+10");
+    harness.click("START");
+    harness.click("STOP");
+
+    assert!(!harness.app.run_data.running);
+    assert!(harness.enabled("+10"));
+}
+
+#[test]
+fn wrong_car_clock_is_warned_about() {
+    let mut harness = Harness::new();
+    let warned = |harness: &mut Harness| {
+        harness
+            .texts()
+            .iter()
+            .any(|text| text.starts_with("The car's clock appears wrong"))
+    };
+
+    let now = unix_now();
+    harness.respond(&format!(
+        "~PING${{\"sent_time\": {now}}}${{\"time\": {}}}",
+        now + 60.0
+    ));
+    assert!(warned(&mut harness));
+
+    let now = unix_now();
+    harness.respond(&format!(
+        "~PING${{\"sent_time\": {now}}}${{\"time\": {now}}}"
+    ));
+    assert!(!warned(&mut harness));
+}
+
+#[test]
+fn ping_response_is_shown() {
+    let mut harness = Harness::new();
+
+    harness.respond("~PING${\"sent_time\": 1.0}${\"time\": 1.0}");
+    assert!(harness.app.run_data.ping_status_response.is_some());
+    assert!(harness
+        .texts()
+        .iter()
+        .any(|text| text.starts_with("Round-trip latency")));
+}
+
+#[test]
+fn journal_replays_run() {
+    let mut harness = Harness::new();
+
+    harness.click("+100");
+    harness.click("START");
+    harness.respond("~PING${\"sent_time\": 1.0}${\"time\": 1.0}");
+    harness.click("STOP");
+
+    let journal = &harness.app.journal;
+    let step_of = |kind: &dyn Fn(&JournalEntryKind) -> bool| {
+        journal
+            .entries()
+            .iter()
+            .position(|entry| kind(&entry.kind))
+            .unwrap()
+    };
+    let start = step_of(&|kind| matches!(kind, JournalEntryKind::Action(UserAction::Start { .. })));
+    let sent_ping = step_of(
+        &|kind| matches!(kind, JournalEntryKind::Sent(frame) if frame.starts_with("?PING")),
+    );
+    let received_ping = step_of(&|kind| matches!(kind, JournalEntryKind::Received(_)));
+    assert!(start < sent_ping && sent_ping < received_ping);
+
+    // Before the ping came back
+    let state = journal.replay(received_ping - 1);
+    assert!(state.run_data.running);
+    assert!(state.run_data.ping_status_response.is_none());
+    // After
+    let state = journal.replay(received_ping);
+    assert!(state.run_data.ping_status_response.is_some());
+    assert!(matches!(state.current_job, ClientStatus::ReceivingPing));
+    // At the end
+    let state = journal.replay(journal.entries().len() - 1);
+    assert!(!state.run_data.running);
+    assert!(matches!(state.current_job, ClientStatus::Finished));
+}
+
+#[test]
+fn auto_stop_past_the_target() {
+    let mut harness = Harness::new();
+    let status = |distance: f64| {
+        format!(
+            "~STATUS${{\"running\": true, \"uptime\": 1, \"runtime\": 1, \"stage\": 1, \
+             \"distance\": {{\"distance\": {distance}, \"velocity\": 1.0, \
+             \"magnet_hit_counter\": 1}}}}${{\"time\": 1.0}}"
+        )
+    };
+
+    harness.click("Auto stop");
+    assert!(harness.app.gui_data.auto_stop);
+    harness.click("+100");
+    harness.click("START");
+
+    harness.respond(&status(104.0));
+    assert!(harness.app.run_data.running);
+    // Only what was set when the run started counts
+    harness.app.gui_data.distance = 50.0;
+    harness.app.gui_data.auto_stop = false;
+    harness.respond(&status(80.0));
+    assert!(harness.app.run_data.running);
+    harness.respond(&status(106.0));
+    assert!(!harness.app.run_data.running);
+    assert_eq!(harness.app.errors.len(), 1);
+}
+
+#[test]
+fn corrupted_ping_is_requested_again() {
+    let mut harness = Harness::new();
+    let sent_pings = |harness: &Harness| {
+        harness
+            .app
+            .journal
+            .entries()
+            .iter()
+            .filter(|entry| matches!(&entry.kind, JournalEntryKind::Sent(frame) if frame.starts_with("?PING")))
+            .count()
+    };
+
+    harness.click("+100");
+    harness.click("START");
+    assert_eq!(sent_pings(&harness), 1);
+
+    let frame = "~PING${\"sent_time\": 1.0}${\"time\": 1.0}";
+    let checksum = crc16(frame.as_bytes());
+    harness.respond(&format!(
+        "{}*{checksum:04X}",
+        frame.replace("1.0}$", "7.0}$")
+    ));
+    assert!(harness.app.run_data.ping_status_response.is_none());
+    assert_eq!(sent_pings(&harness), 2);
+    assert!(harness.app.errors.is_empty());
+
+    harness.respond(&format!("{frame}*{checksum:04X}"));
+    assert!(harness.app.run_data.ping_status_response.is_some());
+}
+
+#[test]
+fn speed_limit_is_sent_with_start() {
+    let mut harness = Harness::new();
+    let sent_start = |harness: &Harness| {
+        harness
+            .app
+            .journal
+            .entries()
+            .iter()
+            .find_map(|entry| match &entry.kind {
+                JournalEntryKind::Sent(frame) if frame.starts_with("?START") => Some(frame.clone()),
+                _ => None,
+            })
+    };
+
+    harness.click("Limit");
+    assert!(harness.app.gui_data.speed_limit);
+    harness.click("+100");
+    harness.click("START");
+    harness.respond("~PING${\"sent_time\": 1.0}${\"time\": 1.0}");
+    harness.respond(
+        "~STATICSTATUS${\"number_of_magnets\": 2, \"wheel_diameter\": 6.35}${\"time\": 1.0}",
+    );
+    harness.frame(vec![]);
+
+    let frame = sent_start(&harness).unwrap();
+    assert!(
+        frame.contains(&format!("\"max_velocity\":{DEFAULT_MAX_VELOCITY:?}")),
+        "{frame}"
+    );
+    let warned = |harness: &mut Harness| {
+        harness
+            .texts()
+            .iter()
+            .any(|text| text.starts_with("Went over the speed limit"))
+    };
+    assert!(!warned(&mut harness));
+
+    harness.respond(
+        "~STATUS${\"running\": true, \"uptime\": 1, \"runtime\": 1, \"stage\": 1, \
+         \"distance\": {\"distance\": 10.0, \"velocity\": 60.0, \"magnet_hit_counter\": 1}, \
+         \"speed_limit_violations\": 2}${\"time\": 1.0}",
+    );
+    assert!(warned(&mut harness));
+    assert!(harness.find("Went over the speed limit 2 times").is_some());
+}
+
+#[test]
+fn splits_are_shown_live() {
+    let mut harness = Harness::new();
+    let status = |time: f64, distance: f64| {
+        format!(
+            "~STATUS${{\"running\": true, \"uptime\": 1, \"runtime\": 0, \"stage\": 1, \
+             \"distance\": {{\"distance\": {distance:?}, \"velocity\": 1.0, \
+             \"magnet_hit_counter\": 1}}}}${{\"time\": {time:?}}}"
+        )
+    };
+
+    harness.click("+100");
+    harness.click("START");
+    harness.respond(&status(1.0, 0.0));
+    harness.respond(&status(3.0, 100.0));
+    assert!(harness.find("Split at 100cm: 2.00s, 50.0cm/s").is_some());
+}
+
+#[test]
+fn unanswered_requests_are_retried() {
+    let mut harness = Harness::new();
+    let sent = |harness: &Harness| {
+        harness
+            .app
+            .journal
+            .entries()
+            .iter()
+            .filter_map(|entry| match &entry.kind {
+                JournalEntryKind::Sent(frame) => Some(frame.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let wait_for_timeout = |harness: &mut Harness| {
+        sleep(Duration::from_secs_f64(REQUEST_TIMEOUT_SECONDS * 1.2));
+        harness.frame(vec![]);
+    };
+
+    harness.click("+100");
+    harness.click("START");
+    let ping = sent(&harness).pop().unwrap();
+    assert!(ping.starts_with("?PING"));
+
+    // Resent as is
+    for attempts in 2..=MAX_REQUEST_ATTEMPTS {
+        wait_for_timeout(&mut harness);
+        let sent = sent(&harness);
+        assert_eq!(sent.len(), attempts);
+        assert_eq!(sent.last(), Some(&ping));
+    }
+    assert!(harness.app.errors.is_empty());
+
+    // Given up on
+    wait_for_timeout(&mut harness);
+    assert_eq!(sent(&harness).len(), MAX_REQUEST_ATTEMPTS);
+    assert!(matches!(
+        harness.app.errors[..],
+        [ErrorData {
+            error: ClientError::Timeout { .. },
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn answered_requests_are_not_retried() {
+    let mut harness = Harness::new();
+
+    harness.click("+100");
+    harness.click("START");
+    let sequence = harness
+        .app
+        .serial_event_propagator
+        .pending_requests
+        .first()
+        .map(|pending| RawFrame::split(&pending.frame).unwrap().metadata.sequence)
+        .unwrap()
+        .unwrap();
+    harness.respond(&format!(
+        "~PING${{\"sent_time\": 1.0}}${{\"time\": 1.0, \"sequence\": {sequence}}}"
+    ));
+    assert!(harness
+        .app
+        .serial_event_propagator
+        .pending_requests
+        .iter()
+        .all(|pending| pending.command != Command::Ping));
+}
+
+#[test]
+fn baseline_delta_is_shown_live() {
+    let mut harness = Harness::new();
+    let status = |time: f64, distance: f64| {
+        format!(
+            "~STATUS${{\"running\": true, \"uptime\": 1, \"runtime\": 0, \"stage\": 1, \
+             \"distance\": {{\"distance\": {distance:?}, \"velocity\": 1.0, \
+             \"magnet_hit_counter\": 1}}}}${{\"time\": {time:?}}}"
+        )
+    };
+    let baseline = [status(1.0, 0.0), status(3.0, 100.0)]
+        .iter()
+        .map(|frame| match Frame::decode(frame).unwrap() {
+            Response::Status(event) => event,
+            _ => unreachable!(),
+        })
+        .collect();
+    harness.app.baseline = Some(Baseline {
+        path: "baseline.csv".into(),
+        statuses: baseline,
+    });
+
+    harness.click("+100");
+    harness.click("START");
+    harness.respond(&status(11.0, 0.0));
+    harness.respond(&status(13.0, 80.0));
+    assert!(harness.find("Behind baseline by 20.0cm").is_some());
+    harness.respond(&status(14.0, 120.0));
+    assert!(harness.find("Ahead of baseline by 20.0cm").is_some());
+}
+
+#[test]
+fn position_is_estimated_while_statuses_stop() {
+    let mut harness = Harness::new();
+    let status = |time: f64, distance: f64| {
+        format!(
+            "~STATUS${{\"running\": true, \"uptime\": 1, \"runtime\": 0, \"stage\": 1, \
+             \"distance\": {{\"distance\": {distance:?}, \"velocity\": 20.0, \
+             \"magnet_hit_counter\": 1}}}}${{\"time\": {time:?}}}"
+        )
+    };
+    let estimated = |harness: &mut Harness| {
+        harness
+            .texts()
+            .iter()
+            .any(|text| text.starts_with("ESTIMATE, no status for 2."))
+    };
+
+    harness.click("+100");
+    harness.click("START");
+    harness.respond(&status(11.0, 30.0));
+    assert!(!estimated(&mut harness));
+
+    // Going at 20cm/s for 2s
+    harness.app.last_status_received = Some(Instant::now() - Duration::from_secs(2));
+    assert!(estimated(&mut harness));
+    let estimate = harness.app.position_estimate().unwrap();
+    assert!((estimate.distance - 70.0).abs() < 1.0);
+    assert!(!estimate.stopped);
+
+    // Gone once statuses come back
+    harness.respond(&status(14.0, 75.0));
+    assert!(!estimated(&mut harness));
+
+    // Or when it isn't wanted
+    harness.app.config.run.estimate_position = false;
+    harness.app.last_status_received = Some(Instant::now() - Duration::from_secs(2));
+    assert!(!estimated(&mut harness));
+}
+
+#[test]
+fn missing_static_status_is_assumed() {
+    let mut harness = Harness::new();
+    harness.app.serial_event_propagator.max_request_attempts = 1;
+
+    harness.click("+100");
+    harness.click("START");
+    let sequence = harness.app.serial_event_propagator.pending_requests[0].sequence;
+    harness.respond(&format!(
+        "~PING${{\"sent_time\": 1.0}}${{\"time\": 1.0, \"sequence\": {sequence}}}"
+    ));
+    assert!(matches!(
+        harness.app.gui_data.current_job,
+        ClientStatus::ReceivingStaticStatus
+    ));
+
+    // The server never answers, but the run carries on
+    sleep(Duration::from_secs_f64(REQUEST_TIMEOUT_SECONDS * 1.2));
+    harness.frame(vec![]);
+    harness.frame(vec![]);
+    assert!(matches!(
+        harness.app.gui_data.current_job,
+        ClientStatus::ReceivingStatus
+    ));
+    assert_eq!(harness.app.run_data.static_status_supported, Some(false));
+    let static_status = harness
+        .app
+        .run_data
+        .static_status_response
+        .as_ref()
+        .unwrap();
+    assert_eq!(
+        static_status.value.number_of_magnets,
+        harness.app.config.car.number_of_magnets
+    );
+    assert!(matches!(
+        harness.app.errors[..],
+        [ErrorData {
+            error: ClientError::Run(_),
+            ..
+        }]
+    ));
+    assert!(harness
+        .find("Assumed from the config, the server didn't send it")
+        .is_some());
+}
+
+#[test]
+fn measured_wheel_is_recorded() {
+    let mut harness = Harness::new();
+    assert_eq!(harness.app.wheel_circumference(), None);
+
+    harness.app.gui_data.override_wheel_circumference = true;
+    harness.app.gui_data.wheel_circumference = 21.5;
+    assert_eq!(harness.app.wheel_circumference(), Some(21.5));
+
+    harness.click("+100");
+    harness.click("START");
+    assert!(harness.app.journal.entries().iter().any(|entry| matches!(
+        entry.kind,
+        JournalEntryKind::Action(UserAction::Start {
+            wheel_circumference: Some(circumference),
+            ..
+        }) if circumference == 21.5
+    )));
+}
+
+#[test]
+fn the_wheel_is_calibrated_against_the_last_run() {
+    let mut harness = Harness::new();
+
+    harness.click("+100");
+    harness.click("START");
+    harness.respond(
+        "~STATUS${\"running\": true, \"uptime\": 1, \"runtime\": 1, \"stage\": 1, \
+         \"distance\": {\"distance\": 110.0, \"velocity\": 1.0, \"magnet_hit_counter\": 11}}\
+         ${\"time\": 1.0}",
+    );
+    harness.click("Expand");
+    harness.click("Calibrate");
+    assert!(harness.find("Calibrate the wheel").is_some());
+    assert!(harness.find("The odometer counted 110.0cm").is_some());
+    harness
+        .app
+        .gui_data
+        .calibration_window
+        .as_mut()
+        .unwrap()
+        .measured_distance = 100.0;
+    // Not until the run's over
+    assert!(!harness.enabled("Send"));
+
+    harness.click("STOP");
+    harness.click("Skip");
+    while harness.car.read_line().unwrap().is_some() {}
+    harness.click("Send");
+    let sent = std::iter::from_fn(|| harness.car.read_line().unwrap())
+        .find(|frame| frame.starts_with("?CALIBRATE"))
+        .expect("The calibration was never sent");
+    assert!(sent.contains("{\"distance\":100.0}"), "{sent}");
+    assert!(harness
+        .app
+        .journal
+        .entries()
+        .iter()
+        .any(|entry| entry.kind.describe() == "Calibrate against 100cm measured"));
+
+    harness.respond(
+        "~STATICSTATUS${\"number_of_magnets\": 2, \"wheel_diameter\": 6.35}${\"time\": 1.0}",
+    );
+    harness.respond(
+        "~CALIBRATE${\"previous_wheel_circumference\": 19.95, \
+         \"wheel_circumference\": 18.14}${\"time\": 1.0}",
+    );
+    assert!(harness
+        .find("Wheel circumference: 18.14cm, was 19.95cm")
+        .is_some());
+    // Counted with from then on, unless it's measured by hand
+    assert_eq!(harness.app.wheel_circumference(), Some(18.14));
+    harness.app.gui_data.override_wheel_circumference = true;
+    harness.app.gui_data.wheel_circumference = 21.5;
+    assert_eq!(harness.app.wheel_circumference(), Some(21.5));
+}
+
+#[test]
+fn telemetry_is_plotted_live() {
+    let mut harness = Harness::new();
+    harness.click("+100");
+    harness.click("START");
+    harness.click("Expand");
+    harness.click("Plot");
+    assert!(harness.find("Telemetry").is_some());
+    assert!(harness.find("Distance (cm)").is_none());
+
+    harness.respond(
+        "~STATUS${\"running\": true, \"uptime\": 1, \"runtime\": 1, \"stage\": 1, \
+         \"distance\": {\"distance\": 10.0, \"velocity\": 10.0, \"magnet_hit_counter\": 1}}\
+         ${\"time\": 1.0}",
+    );
+    assert!(harness.find("Distance (cm)").is_some());
+    assert!(harness.find("Velocity (cm/s)").is_some());
+
+    harness.click("Velocity");
+    assert!(harness.find("Velocity (cm/s)").is_none());
+    harness.click("Close");
+    assert!(harness.app.gui_data.telemetry_plot.is_none());
+}
+
+#[test]
+fn connect_progress_is_shown_live() {
+    let mut harness = Harness::new();
+    harness.app.gui_data.show_bluetooth_connect_screen = true;
+    // Windows are only sized on their first frame
+    harness.frame(vec![]);
+    harness.click("Connect");
+    assert!(harness.find("Waiting for the bridge...").is_some());
+
+    harness.respond(
+        "&CONNECT${\"elapsed\": 1.5, \"devices\": [{\"address\": \"00:60:37:E9:0B:6F\", \
+         \"name\": \"R41Z\", \"rssi\": -60}], \"connected\": null}${\"time\": 1.0}",
+    );
+    assert!(harness.find("Looking for the device... 1.5s").is_some());
+    assert!(harness.find("R41Z (00:60:37:E9:0B:6F) -60dBm").is_some());
+
+    harness.respond(
+        "&CONNECT${\"elapsed\": 2.0, \"devices\": [], \"connected\": true}${\"time\": 1.5}",
+    );
+    assert!(harness.find("Connected after 2.0s").is_some());
+    assert!(harness.app.run_data.bluetooth_bridge_connected);
+}
+
+#[test]
+fn run_is_saved_in_the_chosen_format() {
+    let mut harness = Harness::new();
+    let directory = temp_dir().join(format!("gui-tests-export-{}", std::process::id()));
+    create_dir_all(&directory).unwrap();
+    let status = "~STATUS${\"running\": false, \"uptime\": 1, \"runtime\": 1, \"stage\": 0, \
+                  \"distance\": {\"distance\": 10.0, \"velocity\": 10.0, \"magnet_hit_counter\": 1}}\
+                  ${\"time\": 1.0}";
+    match Frame::decode(status).unwrap() {
+        Response::Status(event) => harness.app.run_data.status_responses.push(event),
+        _ => unreachable!(),
+    }
+    harness
+        .app
+        .gui_data
+        .set_file_path(directory.join("saved.csv"));
+
+    harness.click("CSV");
+    harness.click("JSONL");
+    assert_eq!(harness.app.gui_data.export_format, RunFormat::JSONLines);
+    harness.save();
+
+    let saved = directory.join("saved.jsonl");
+    assert_eq!(read_run(&saved).unwrap().len(), 1);
+    let size = format!(
+        "saved.jsonl ({})",
+        size_text(metadata(&saved).unwrap().len())
+    );
+    assert!(!directory.join("saved.csv").exists());
+    assert_eq!(harness.app.gui_data.file_path, Some(saved));
+    assert!(harness.app.run_data.saved);
+    assert!(harness
+        .texts()
+        .iter()
+        .any(|text| text.starts_with("Saved ") && text.ends_with(&size)));
+}
+
+#[test]
+fn saving_can_be_cancelled() {
+    let mut harness = Harness::new();
+    harness.app.save_job = Some(worker::BackgroundJob::spawn(
+        "Saving slow.csv".to_owned(),
+        10,
+        |progress| {
+            progress.advance(3);
+            while !progress.cancelled() {
+                sleep(Duration::from_millis(1));
+            }
+            Err(ClientError::CSV("Cancelled".to_owned()))
+        },
+    ));
+    sleep(Duration::from_millis(50));
+    harness.frame(vec![]);
+    assert!(harness.find("Saving slow.csv").is_some());
+    assert!(harness.find("3 of 10 statuses").is_some());
+
+    harness.click("Cancel");
+    for _ in 0..100 {
+        if harness.app.save_job.is_none() {
+            break;
+        }
+        sleep(Duration::from_millis(10));
+        harness.frame(vec![]);
+    }
+    assert!(harness.app.save_job.is_none());
+    assert!(harness.app.errors.is_empty());
+    assert!(harness.find("Stopped saving").is_some());
+}
+
+#[test]
+fn trusted_device_connection_is_shown() {
+    let mut harness = Harness::new();
+    harness.app.gui_data.show_bluetooth_connect_screen = true;
+    harness.frame(vec![]);
+
+    // Sent by the bridge at startup without being asked
+    harness.respond(
+        "&CONNECT${\"elapsed\": 3.0, \"devices\": [], \"connected\": true, \
+         \"address\": \"00:60:37:E9:0B:6F\"}${\"time\": 1.0}",
+    );
+    assert!(harness.app.run_data.bluetooth_bridge_connected);
+    assert!(harness.find("We *think* we are CONNECTED").is_some());
+    assert!(harness.find("Connected to 00:60:37:E9:0B:6F").is_some());
+
+    harness.app.gui_data.show_connect_progress_window = true;
+    harness.frame(vec![]);
+    assert!(harness
+        .find("Connected to 00:60:37:E9:0B:6F after 3.0s")
+        .is_some());
+}
+
+#[test]
+fn bridge_watchdog_is_shown() {
+    let mut harness = Harness::new();
+    harness.app.run_data.bluetooth_bridge_connected = true;
+
+    harness.respond(
+        "&WATCHDOG${\"silent_for\": 10.5, \"address\": \"00:60:37:E9:0B:6F\"}${\"time\": 1.0}",
+    );
+    assert!(!harness.app.run_data.bluetooth_bridge_connected);
+    assert!(matches!(
+        &harness.app.errors[..],
+        [ErrorData {
+            error: ClientError::Bluetooth(message),
+            ..
+        }] if message.contains("00:60:37:E9:0B:6F for 10.5s")
+    ));
+}
+
+#[test]
+fn bridge_metrics_are_shown() {
+    let mut harness = Harness::new();
+    harness.app.gui_data.show_bluetooth_connect_screen = true;
+    harness.frame(vec![]);
+
+    harness.click("Metrics");
+    assert!(std::iter::from_fn(|| harness.car.read_line().unwrap())
+        .any(|frame| frame.starts_with("^METRICS")));
+    harness.respond(
+        "&METRICS${\"address\": \"00:60:37:E9:0B:6F\", \"rssi\": -72, \"bytes_sent\": 1200, \
+         \"bytes_received\": 3400, \"reconnects\": 2, \"duplicates_discarded\": 5}\
+         ${\"time\": 1.0}",
+    );
+    assert!(harness.find("Signal: -72dBm").is_some());
+    assert!(harness.find("Sent 1.2 KB, received 3.4 KB").is_some());
+    assert!(harness
+        .find("Reconnected 2 time(s), discarded 5 duplicate(s)")
+        .is_some());
+}
+
+#[test]
+fn frames_are_logged_both_ways() {
+    let mut harness = Harness::new();
+    let directory = temp_dir().join(format!("gui-tests-wire-{}", std::process::id()));
+    create_dir_all(&directory).unwrap();
+    let path = directory.join("frames-both-ways.log");
+    let _ = remove_file(&path);
+    harness.app.serial_event_propagator.wire_logger = Some(WireLogger::open(path.clone()).unwrap());
+
+    harness.click("+100");
+    harness.click("START");
+    harness.respond("~PING${\"sent_time\": 1.0}${\"time\": 1.0}");
+
+    let entries = read_wire_log(&path).unwrap();
+    assert!(matches!(
+        &entries[..],
+        [sent, received, ..]
+            if sent.direction == Direction::Sent && sent.frame.starts_with("?PING$")
+                && received.direction == Direction::Received
+                && received.frame == "~PING${\"sent_time\": 1.0}${\"time\": 1.0}"
+    ));
+}
+
+#[test]
+fn wire_log_is_replayed() {
+    let mut harness = Harness::new();
+    let directory = temp_dir().join(format!("gui-tests-wire-{}", std::process::id()));
+    create_dir_all(&directory).unwrap();
+    let path = directory.join("replayed.log");
+    write(
+        &path,
+        "{\"direction\": \"received\", \"monotonic\": 0.0, \"time\": 1.0, \
+         \"frame\": \"~PING${\\\"sent_time\\\": 1.0}${\\\"time\\\": 1.0}\"}\n\
+         {\"direction\": \"received\", \"monotonic\": 1000.0, \"time\": 1001.0, \
+         \"frame\": \"~PING${\\\"sent_time\\\": 2.0}${\\\"time\\\": 2.0}\"}\n",
+    )
+    .unwrap();
+
+    harness
+        .app
+        .load_wire_log(WireLogReplay::read(path).unwrap());
+    sleep(Duration::from_secs_f64(SERIAL_DELAY_TIME * 1.5));
+    let texts = harness.texts();
+    assert!(texts
+        .iter()
+        .any(|text| text.starts_with("Round-trip latency")));
+    assert!(texts.contains(&"Replaying replayed.log (1 frames left)".to_owned()));
+}
+
+#[test]
+fn imported_settings_keep_what_was_chosen() {
+    let mut harness = Harness::new();
+    let directory = temp_dir().join(format!("gui-tests-settings-{}", std::process::id()));
+    create_dir_all(&directory).unwrap();
+    write(
+        directory.join("laptop.toml"),
+        "version = 1\n[config.car]\nwheel_diameter = 7.0\n\
+         [run]\nauto_stop = true\nmax_velocity = 30.0\n",
+    )
+    .unwrap();
+    harness
+        .app
+        .gui_data
+        .set_file_path(directory.join("status.csv"));
+    // Only what's in the file differs
+    harness.app.config.run.arming_countdown = DEFAULT_ARMING_COUNTDOWN;
+
+    harness.app.gui_data.show_settings_window = true;
+    harness.frame(vec![]);
+    harness.click("Import");
+    harness.click("laptop.toml");
+    assert_eq!(
+        harness
+            .app
+            .gui_data
+            .settings_import
+            .as_ref()
+            .map(|import| import.conflicts.len()),
+        Some(3)
+    );
+    harness.click("Keep 50.0");
+    harness.click("Apply");
+
+    assert!(harness.app.gui_data.settings_import.is_none());
+    assert_eq!(harness.app.config.car.wheel_diameter, 7.0);
+    assert!(harness.app.gui_data.auto_stop);
+    assert_eq!(harness.app.gui_data.max_velocity, DEFAULT_MAX_VELOCITY);
+
+    harness.click("Export");
+    let exported = harness.app.gui_data.settings_path.clone().unwrap();
+    assert_eq!(exported.parent(), Some(directory.as_path()));
+    assert_eq!(
+        SettingsFile::read(&exported).unwrap(),
+        harness.app.settings()
+    );
+}
+
+#[test]
+fn startup_problems_are_shown_until_dismissed() {
+    let mut harness = Harness::new();
+    harness.app.startup_problems = vec![StartupProblem {
+        problem: "Bluetooth is blocked by rfkill".to_owned(),
+        fix: "Run `rfkill unblock bluetooth` (or turn off airplane mode)".to_owned(),
+    }];
+    harness.frame(vec![]);
+
+    assert!(harness.find("Bluetooth is blocked by rfkill").is_some());
+    harness.click("Dismiss");
+    assert!(harness.app.startup_problems.is_empty());
+    assert!(harness.find("Before starting").is_none());
+}
+
+#[test]
+#[cfg(feature = "debug-menu")]
+fn injected_faults_are_handled_like_the_car_sent_them() {
+    let mut harness = Harness::new();
+    harness.app.gui_data.dev = true;
+    harness.app.gui_data.show_debug_window = true;
+    harness.frame(vec![]);
+    let settle = |harness: &mut Harness| {
+        for _ in 0..3 {
+            sleep(Duration::from_secs_f64(SERIAL_DELAY_TIME * 1.5));
+            harness.frame(vec![]);
+        }
+        let errors = harness
+            .app
+            .errors
+            .drain(..)
+            .map(|error| error.error.to_string())
+            .collect::<Vec<_>>();
+        harness.frame(vec![]);
+        errors
+    };
+
+    // Answers the request still waiting
+    harness.app.request_resend(Command::Status).unwrap();
+    harness.click("Inject error response");
+    let errors = settle(&mut harness);
+    assert!(
+        errors.len() == 1 && errors[0].contains(fault_injection::INJECTED_MESSAGE),
+        "{errors:?}"
+    );
+    assert!(harness
+        .app
+        .serial_event_propagator
+        .pending_requests
+        .is_empty());
+
+    harness.click("Inject malformed frame");
+    assert_eq!(settle(&mut harness).len(), 1);
+
+    // A corrupted status is asked for again instead
+    harness.app.gui_data.malformed_frame = fault_injection::MalformedFrame::BadChecksum;
+    harness.click("Inject malformed frame");
+    assert!(settle(&mut harness).is_empty());
+    assert!(harness
+        .app
+        .serial_event_propagator
+        .pending_requests
+        .iter()
+        .any(|pending| pending.command == Command::Status));
+
+    harness.click("Drop the link");
+    let errors = settle(&mut harness);
+    assert!(
+        errors.len() == 1 && errors[0].contains("The link went down"),
+        "{errors:?}"
+    );
+}
+
+#[test]
+fn reset_runs_are_kept_and_compared() {
+    let mut harness = Harness::new();
+    for distance in [10.0, 20.0] {
+        harness.click("+100");
+        harness.click("START");
+        harness.respond(&format!(
+            "~STATUS${{\"running\": true, \"uptime\": 1, \"runtime\": 1, \"stage\": 1, \
+             \"distance\": {{\"distance\": {distance}, \"velocity\": 10.0, \
+             \"magnet_hit_counter\": 1}}}}${{\"time\": 1.0}}"
+        ));
+        harness.click("STOP");
+        harness.click("RESET");
+    }
+    assert!(harness.app.run_data.status_responses.is_empty());
+    assert_eq!(harness.app.run_history.runs.len(), 2);
+    assert_eq!(
+        harness.app.run_history.runs[1].parameters.unwrap().distance,
+        200.0
+    );
+
+    harness.app.gui_data.show_run_history = true;
+    harness.click("Run 1");
+    assert!(harness.find("Went 0.10m in 1s").is_some());
+
+    harness.app.run_history.comparison = Some([0, 1]);
+    harness.frame(vec![]);
+    assert!(harness.find("Compare runs").is_some());
+    harness.click("Close");
+    assert_eq!(harness.app.run_history.comparison, None);
+}
+
+#[test]
+fn annotations_are_drawn_and_saved_with_the_run() {
+    let mut harness = Harness::new();
+    let directory = temp_dir().join(format!("gui-tests-annotations-{}", std::process::id()));
+    create_dir_all(&directory).unwrap();
+    harness.click("+100");
+    harness.click("START");
+    harness.respond(
+        "~STATUS${\"running\": true, \"uptime\": 1, \"runtime\": 1, \"stage\": 1, \
+         \"distance\": {\"distance\": 10.0, \"velocity\": 10.0, \"magnet_hit_counter\": 1}}\
+         ${\"time\": 1.0}",
+    );
+    harness.click("Expand");
+    harness.click("Plot");
+
+    harness.app.annotate(annotations::AnnotationEdit::Add(
+        annotations::Annotation::Marker {
+            time: 1.0,
+            label: "Clipped the lane marker".to_owned(),
+        },
+    ));
+    assert!(harness.find("Clipped the lane marker").is_some());
+    assert!(harness.find("1.0s: Clipped the lane marker").is_some());
+
+    harness.app.gui_data.telemetry_plot = None;
+    harness.app.gui_data.expanded_status_table = false;
+    harness.click("STOP");
+    let saved = directory.join("annotated.csv");
+    harness.app.gui_data.set_file_path(saved.clone());
+    harness.save();
+    assert_eq!(
+        annotations::read_annotations(&saved).unwrap(),
+        harness.app.run_data.annotations
+    );
+
+    // Removing one once saved updates the saved copy
+    harness.app.annotate(annotations::AnnotationEdit::Remove(0));
+    assert!(!annotations::annotations_path(&saved).exists());
+    assert!(harness.app.journal.entries().iter().any(|entry| matches!(
+        entry.kind,
+        JournalEntryKind::Action(UserAction::RemoveAnnotation(0))
+    )));
+}
+
+#[test]
+fn presets_set_the_start_parameters() {
+    let mut harness = Harness::new();
+    assert!(harness.find("Presets").is_some());
+    assert!(!harness.enabled("🗑"));
+
+    harness.app.gui_data.distance = 1500.0;
+    harness.app.gui_data.auto_stop = true;
+    harness.click("💾");
+    assert!(harness.app.gui_data.show_preset_window);
+    // A preset needs a name
+    harness.click("Save preset");
+    assert_eq!(harness.app.errors.len(), 1);
+    harness.app.errors.clear();
+    harness.app.gui_data.preset_name = "15 m".to_owned();
+    harness.click("Save preset");
+    assert!(!harness.app.gui_data.show_preset_window);
+    assert!(harness.find("15 m").is_some());
+    assert!(harness.enabled("🗑"));
+
+    harness.app.gui_data.distance = 2000.0;
+    harness.app.gui_data.auto_stop = false;
+    harness.click("💾");
+    harness.app.gui_data.preset_name = "20 m".to_owned();
+    harness.click("Save preset");
+    harness.click("20 m");
+    harness.click("15 m");
+    assert_eq!(harness.app.gui_data.distance, 1500.0);
+    assert!(harness.app.gui_data.auto_stop);
+    assert_eq!(
+        harness.app.gui_data.selected_preset.as_deref(),
+        Some("15 m")
+    );
+
+    harness.click("🗑");
+    assert_eq!(harness.app.gui_data.presets.presets.len(), 1);
+    assert!(harness.find("Presets").is_some());
+}
+
+#[test]
+fn start_has_to_be_confirmed() {
+    let mut harness = Harness::new();
+    harness.app.config.run.arming_countdown = 60.0;
+    harness.click("+100");
+
+    harness.click("START");
+    assert!(!harness.app.run_data.running);
+    assert!(harness.find("ARMED").is_some());
+    harness.click("DISARM");
+    assert_eq!(harness.app.arming, arming::Arming::Disarmed);
+    assert!(harness.find("ARMED").is_none());
+    assert!(harness.enabled("START"));
+
+    // Not confirmed in time
+    harness.app.config.run.arming_countdown = 0.05;
+    harness.click("START");
+    sleep(Duration::from_secs_f64(0.1));
+    harness.frame(vec![]);
+    assert!(harness.find("CONFIRM").is_none());
+    let disarms = harness
+        .app
+        .journal
+        .entries()
+        .iter()
+        .filter(|entry| entry.kind == JournalEntryKind::Action(UserAction::Disarm))
+        .count();
+    assert_eq!(disarms, 2);
+
+    harness.app.config.run.arming_countdown = 60.0;
+    harness.click("START");
+    assert!(harness
+        .app
+        .serial_event_propagator
+        .pending_requests
+        .is_empty());
+    harness.click("CONFIRM");
+    assert!(harness.app.run_data.running);
+    assert_eq!(harness.app.arming, arming::Arming::Disarmed);
+    assert!(harness.find("ARMED").is_none());
+}
+
+#[test]
+fn e_stop_sends_stop_right_away() {
+    let mut harness = Harness::new();
+    let sent_stops = |harness: &Harness| {
+        harness
+            .app
+            .journal
+            .entries()
+            .iter()
+            .filter(|entry| matches!(&entry.kind, JournalEntryKind::Sent(frame) if frame.starts_with("?STOP")))
+            .count()
+    };
+
+    // Mid way through starting
+    harness.click("+100");
+    harness.click("START");
+    assert!(matches!(
+        harness.app.gui_data.current_job,
+        ClientStatus::ReceivingPing
+    ));
+    harness.click("E-STOP");
+    assert!(!harness.app.run_data.running);
+    assert!(matches!(
+        harness.app.gui_data.current_job,
+        ClientStatus::Finished
+    ));
+    assert_eq!(sent_stops(&harness), 1);
+    // The start isn't carried on with
+    harness.respond("~PING${\"sent_time\": 1.0}${\"time\": 1.0}");
+    assert!(matches!(
+        harness.app.gui_data.current_job,
+        ClientStatus::Finished
+    ));
+    assert_eq!(sent_stops(&harness), 1);
+    harness.click("RESET");
+
+    // While armed, from the keyboard
+    harness.app.config.run.arming_countdown = 60.0;
+    harness.click("START");
+    harness.press(Key::Space);
+    assert_eq!(harness.app.arming, arming::Arming::Disarmed);
+    assert_eq!(sent_stops(&harness), 2);
+    harness.press(Key::Escape);
+    assert_eq!(sent_stops(&harness), 3);
+    assert!(harness
+        .app
+        .journal
+        .entries()
+        .iter()
+        .any(|entry| entry.kind == JournalEntryKind::Action(UserAction::EmergencyStop)));
+}
+
+#[test]
+fn car_is_stopped_when_statuses_stop() {
+    let mut harness = Harness::new();
+    harness.app.config.run.status_watchdog = 1.0;
+    let status = "~STATUS${\"running\": true, \"uptime\": 1, \"runtime\": 1, \"stage\": 1, \
+                  \"distance\": {\"distance\": 10.0, \"velocity\": 1.0, \
+                  \"magnet_hit_counter\": 1}}${\"time\": 1.0}";
+
+    harness.click("+100");
+    harness.click("START");
+    harness.respond("~PING${\"sent_time\": 1.0}${\"time\": 1.0}");
+    harness.respond(
+        "~STATICSTATUS${\"number_of_magnets\": 2, \"wheel_diameter\": 6.35}${\"time\": 1.0}",
+    );
+    harness.frame(vec![]);
+    assert!(matches!(
+        harness.app.gui_data.current_job,
+        ClientStatus::ReceivingStatus
+    ));
+
+    // Each status holds it off, for longer than the watchdog altogether
+    for _ in 0..3 {
+        harness.respond(status);
+        assert!(harness.app.run_data.running);
+    }
+
+    sleep(Duration::from_secs_f64(1.2));
+    harness.frame(vec![]);
+    assert!(!harness.app.run_data.running);
+    assert!(matches!(
+        &harness.app.errors[..],
+        [ErrorData {
+            error: ClientError::Run(message),
+            ..
+        }, ..] if message.contains("no status came in for 1.0s")
+    ));
+    assert!(harness.app.journal.entries().iter().any(
+        |entry| matches!(&entry.kind, JournalEntryKind::Sent(frame) if frame.starts_with("?STOP"))
+    ));
+}
+
+#[test]
+fn runs_are_named_after_the_session() {
+    let mut harness = Harness::new();
+    let directory = temp_dir().join(format!("gui-tests-session-{}", std::process::id()));
+    create_dir_all(&directory).unwrap();
+    let date = DisplayTimezone::Local.format_unix(unix_now(), "%Y-%m-%d");
+    let session = &mut harness.app.gui_data.session;
+    session.venue = "Gym".to_owned();
+    session
+        .directories
+        .insert("Gym".to_owned(), directory.clone());
+
+    harness.click("+100");
+    harness.click("START");
+    let first = directory.join(format!("{date}_gym_100cm_run01.csv"));
+    assert_eq!(harness.app.gui_data.file_path, Some(first));
+    assert_eq!(
+        harness.app.gui_data.display_file_path,
+        format!("{date}_gym_100cm_run01.csv")
+    );
+    harness.click("STOP");
+
+    // Saved where the last run was at a new venue
+    harness.respond(
+        "~STATUS${\"running\": false, \"uptime\": 1, \"runtime\": 1, \"stage\": 0, \
+         \"distance\": {\"distance\": 10.0, \"velocity\": 10.0, \"magnet_hit_counter\": 1}}\
+         ${\"time\": 1.0}",
+    );
+    harness.app.gui_data.session.venue = "Track".to_owned();
+    harness.save();
+    assert_eq!(
+        harness.app.gui_data.session.directories.get("Track"),
+        Some(&directory)
+    );
+
+    // Out of the way of RESET
+    harness.app.gui_data.toast = None;
+    harness.click("RESET");
+    harness.click("START");
+    assert_eq!(
+        harness.app.gui_data.file_path,
+        Some(directory.join(format!("{date}_track_100cm_run02.csv")))
+    );
+}
+
+#[test]
+fn distances_are_shown_in_the_chosen_units() {
+    let mut harness = Harness::new();
+    harness.app.config.screen.units = Units::Imperial;
+
+    // Still kept in centimeters
+    harness.click("+10");
+    assert_eq!(harness.app.gui_data.distance, 25.4);
+    assert!(harness.find("10in").is_some());
+
+    harness.app.gui_data.show_settings_window = true;
+    harness.frame(vec![]);
+    harness.click("cm/m");
+    assert_eq!(harness.app.config.screen.units, Units::Metric);
+    assert!(harness.find("25.4cm").is_some());
+}
+
+#[test]
+fn finalized_run_is_reset_for_the_next_heat() {
+    let mut harness = Harness::new();
+    harness.app.config.run.auto_reset = 0.5;
+    let finalize = |harness: &mut Harness| {
+        harness.click("+100");
+        harness.click("START");
+        harness.respond(
+            "~STATUS${\"running\": false, \"uptime\": 1, \"runtime\": 1, \"stage\": 4, \
+             \"distance\": {\"distance\": 100.0, \"velocity\": 0.0, \"magnet_hit_counter\": 1}}\
+             ${\"time\": 1.0}",
+        );
+        assert!(harness.app.run_data.saved);
+    };
+
+    finalize(&mut harness);
+    assert!(harness.find("Resetting in 1s").is_some());
+    sleep(Duration::from_secs_f64(0.6));
+    harness.frame(vec![]);
+    assert!(harness.app.run_data.status_responses.is_empty());
+    assert!(matches!(
+        harness.app.gui_data.current_job,
+        ClientStatus::GatheringData
+    ));
+    assert_eq!(harness.app.run_history.runs.len(), 1);
+
+    // Kept around when asked to
+    finalize(&mut harness);
+    harness.click("Keep");
+    sleep(Duration::from_secs_f64(0.6));
+    harness.frame(vec![]);
+    assert!(!harness.app.run_data.status_responses.is_empty());
+}
+
+#[test]
+fn tuned_settings_are_saved_to_the_config() {
+    let mut harness = Harness::new();
+    let config_path = temp_dir()
+        .join(format!("gui-tests-config-{}", std::process::id()))
+        .join("config.toml");
+    harness.app.config_path = Some(config_path.clone());
+
+    harness.app.gui_data.show_settings_window = true;
+    harness.frame(vec![]);
+    assert!(harness
+        .texts()
+        .iter()
+        .any(|text| text.starts_with("Poll every")));
+    harness.click("in/ft");
+    assert_eq!(
+        Config::read(&config_path).unwrap().screen.units,
+        Units::Imperial
+    );
+}
+
+#[test]
+fn theme_is_picked_from_the_settings_window() {
+    let mut harness = Harness::new();
+    assert!(harness.ctx.style().visuals.dark_mode);
+
+    harness.app.gui_data.show_settings_window = true;
+    harness.frame(vec![]);
+    harness.click("Dark");
+    harness.click("Light");
+    assert_eq!(harness.app.config.screen.theme, theme::Theme::Light);
+    harness.frame(vec![]);
+    assert!(!harness.ctx.style().visuals.dark_mode);
+
+    // Everything is laid out bigger for fingers
+    let spacing = harness.ctx.style().spacing.interact_size;
+    harness.app.config.screen.layout = theme::ScreenLayout::Kiosk;
+    harness.frame(vec![]);
+    assert!(harness.ctx.style().spacing.interact_size.y > spacing.y);
+}
+
+#[test]
+fn operator_layout_only_has_the_essentials() {
+    let mut harness = Harness::new();
+    harness.app.config.screen.layout = theme::ScreenLayout::Kiosk;
+    harness.app.gui_data.distance = 100.0;
+
+    harness.click("Operator");
+    assert_eq!(
+        harness.app.config.screen.layout,
+        theme::ScreenLayout::Operator
+    );
+    assert!(harness.find("+100").is_none());
+    assert!(harness.find("E-STOP").is_some());
+    // Bigger, for gloved fingers
+    let start = harness.find("START").unwrap();
+    assert!(start.galley.size().y > 20.0);
+
+    harness.click("START");
+    assert!(harness.app.run_data.running);
+    harness.click("E-STOP");
+    assert!(!harness.app.run_data.running);
+
+    // Back to where it was in one tap
+    harness.click("Details");
+    assert_eq!(harness.app.config.screen.layout, theme::ScreenLayout::Kiosk);
+    assert!(harness.find("+100").is_some());
+}
+
+#[test]
+fn errors_are_shown_in_the_logs_panel() {
+    let mut harness = Harness::new();
+    let _logging = tracing::subscriber::set_default(
+        tracing_subscriber::registry().with(harness.app.logs.clone()),
+    );
+    // Slides in over a few frames
+    harness.app.gui_data.show_logs = true;
+    for _ in 0..10 {
+        harness.frame(vec![]);
+    }
+    assert!(harness.find("Nothing logged yet").is_some());
+
+    // Errors are logged as well as shown
+    harness
+        .app
+        .errors
+        .push(ClientError::Run("The car caught fire".to_owned()).into());
+    harness.app.errors.clear();
+    tracing::info!("Nothing to see here");
+    // The scroll area grows to fit new events a frame later
+    let logged = |harness: &mut Harness, level: &str, message: &str| {
+        harness.frame(vec![]);
+        harness
+            .texts()
+            .iter()
+            .any(|text| text.contains(&format!(" {level} ")) && text.ends_with(message))
+    };
+    assert!(logged(&mut harness, "ERROR", "The car caught fire"));
+    assert!(logged(&mut harness, "INFO ", "Nothing to see here"));
+
+    harness.app.gui_data.log_level = logging::LogLevel::Error;
+    assert!(logged(&mut harness, "ERROR", "The car caught fire"));
+    assert!(!logged(&mut harness, "INFO ", "Nothing to see here"));
+}
+
+#[test]
+fn already_started_is_fixed_by_stopping_and_starting_again() {
+    let mut harness = Harness::new();
+    let sent = |harness: &Harness, prefix: &str| {
+        harness
+            .app
+            .journal
+            .entries()
+            .iter()
+            .filter(|entry| matches!(&entry.kind, JournalEntryKind::Sent(frame) if frame.starts_with(prefix)))
+            .count()
+    };
+
+    harness.click("+100");
+    harness.click("START");
+    harness.respond(
+        "~ERROR${\"error_variant\": 21, \"message\": \"Already started\"}${\"time\": 1.0}",
+    );
+    assert!(matches!(
+        &harness.app.errors[..],
+        [ErrorData {
+            recovery: Some(recovery::Recovery::StopAndStartAgain),
+            ..
+        }]
+    ));
+    assert_eq!(sent(&harness, "?STOP$"), 0);
+
+    // As if "Fix it" was pressed in the errors window
+    let recovery = harness.app.errors.remove(0).recovery.unwrap();
+    harness.app.recover(recovery);
+    assert_eq!(sent(&harness, "?STOP$"), 1);
+    harness.frame(vec![]);
+    assert_eq!(sent(&harness, "?START$"), 1);
+    assert!(harness.app.run_data.running);
+    assert!(matches!(
+        harness.app.gui_data.current_job,
+        ClientStatus::ReceivingStatus
+    ));
+    assert!(harness
+        .app
+        .journal
+        .entries()
+        .iter()
+        .any(|entry| entry.kind == JournalEntryKind::Action(UserAction::Recover(recovery))));
+}
+
+#[test]
+fn previous_sessions_are_looked_back_on() {
+    let mut harness = Harness::new();
+    let directory = harness
+        .app
+        .data_directory
+        .as_ref()
+        .unwrap()
+        .session_log_directory();
+    let _ = remove_dir_all(&directory);
+    let mut previous = session_log::SessionLogger::open(&directory, Default::default()).unwrap();
+    previous
+        .log(
+            1000.0,
+            session_log::SessionEvent::Error {
+                message: "The car caught fire".to_owned(),
+                recovery: None,
+            },
+        )
+        .unwrap();
+    // Opened in the same second, so it's numbered after the previous one
+    harness.app.session_log =
+        Some(session_log::SessionLogger::open(&directory, Default::default()).unwrap());
+
+    harness
+        .app
+        .errors
+        .push(ClientError::Run("Out of fuel".to_owned()).into());
+    harness.frame(vec![]);
+    let logged = session_log::read_session_log(&harness.app.session_log.as_ref().unwrap().path)
+        .unwrap()
+        .iter()
+        .map(|entry| entry.event.describe())
+        .collect::<Vec<_>>();
+    assert!(logged.contains(&"Error: There was an error with running: Out of fuel".to_owned()));
+
+    // As if "Previous session" was pressed, which is below the bottom of the
+    // screen
+    harness.app.errors.clear();
+    harness.app.gui_data.session_log_viewer = Some(session_log::SessionLogViewer::new(
+        &directory,
+        Some(&harness.app.session_log.as_ref().unwrap().path),
+    ));
+    // Windows are only drawn from their second frame
+    harness.frame(vec![]);
+    assert!(harness
+        .texts()
+        .iter()
+        .any(|text| text.ends_with("Error: The car caught fire")));
+    assert!(!harness
+        .texts()
+        .iter()
+        .any(|text| text.contains("Out of fuel")));
+}
+
+#[test]
+fn stopping_by_hand_asks_why() {
+    let mut harness = Harness::new();
+    let status = |distance: f64| {
+        format!(
+            "~STATUS${{\"running\": true, \"uptime\": 1, \"runtime\": 1, \"stage\": 1, \
+             \"distance\": {{\"distance\": {distance}, \"velocity\": 1.0, \
+             \"magnet_hit_counter\": 1}}}}${{\"time\": 1.0}}"
+        )
+    };
+
+    harness.click("+100");
+    harness.click("START");
+    harness.respond(&status(10.0));
+    harness.click("STOP");
+    assert!(harness.find("Why was it stopped?").is_some());
+    // A note is needed to say it was something else
+    assert!(!harness.enabled("Other"));
+    harness.click("Obstacle");
+    assert!(harness.find("Why was it stopped?").is_none());
+    assert_eq!(
+        harness
+            .app
+            .run_data
+            .stop_reason
+            .as_ref()
+            .map(|reason| reason.kind),
+        Some(stop_reason::StopReasonKind::Obstacle)
+    );
+    assert!(harness
+        .app
+        .journal
+        .entries()
+        .iter()
+        .any(|entry| entry.kind.describe() == "Stopped for: Obstacle"));
+    harness.click("RESET");
+    assert_eq!(
+        harness.app.run_history.runs[0]
+            .stop_reason
+            .as_ref()
+            .map(|reason| reason.kind),
+        Some(stop_reason::StopReasonKind::Obstacle)
+    );
+
+    // Answered once the run was already reset, it still goes with the run
+    harness.click("START");
+    harness.respond(&status(10.0));
+    harness.press(Key::Escape);
+    harness.click("RESET");
+    harness
+        .app
+        .gui_data
+        .stop_reason_prompt
+        .as_mut()
+        .unwrap()
+        .note = "A wheel fell off".to_owned();
+    harness.click("Other");
+    assert_eq!(
+        harness.app.run_history.runs[1]
+            .stop_reason
+            .as_ref()
+            .map(stop_reason::StopReason::describe),
+        Some("Other (A wheel fell off)".to_owned())
+    );
+    assert_eq!(harness.app.run_data.stop_reason, None);
+
+    // Not asked when the car is stopped automatically, or can be skipped
+    harness.click("Auto stop");
+    harness.click("START");
+    harness.respond(&status(200.0));
+    assert!(!harness.app.run_data.running);
+    assert!(harness.find("Why was it stopped?").is_none());
+    harness.click("RESET");
+    harness.click("START");
+    harness.click("STOP");
+    harness.click("Skip");
+    assert!(harness.find("Why was it stopped?").is_none());
+    assert_eq!(harness.app.run_data.stop_reason, None);
+}
+
+#[test]
+fn errors_are_counted_in_the_banner_mid_run() {
+    let mut harness = Harness::new();
+    harness.click("+100");
+    harness.click("START");
+    harness.respond("~ERROR${\"error_variant\": 25, \"message\": \"Not started\"}${\"time\": 1.0}");
+    harness.respond(
+        "&WATCHDOG${\"silent_for\": 10.5, \"address\": \"00:60:37:E9:0B:6F\"}${\"time\": 1.0}",
+    );
+    assert!(harness.app.run_data.running);
+    assert!(harness.find("Errors!").is_none());
+    assert!(harness.find("Bluetooth 1").is_some());
+
+    harness.click("Server 1");
+    harness.frame(vec![]);
+    assert!(harness.find("Errors!").is_some());
+    assert_eq!(
+        harness.app.gui_data.error_filter,
+        Some(error_badge::Subsystem::Server)
+    );
+    assert!(harness.find("Server (1)").is_some());
+    assert!(harness.find("Bluetooth (1)").is_some());
+    harness.click("Hide");
+    assert!(harness.find("Errors!").is_none());
+
+    // Shown by itself once the run is over
+    harness.click("STOP");
+    harness.frame(vec![]);
+    assert!(harness.find("Errors!").is_some());
+    harness.click("Clear");
+    assert!(harness.find("Server 1").is_none());
+    assert_eq!(harness.app.gui_data.error_filter, None);
+}
+
+#[test]
+fn protocol_version_is_agreed_on_when_connected() {
+    let mut harness = Harness::new();
+    let sent_hello = |harness: &Harness| {
+        harness
+            .app
+            .journal
+            .entries()
+            .iter()
+            .rev()
+            .find_map(|entry| match &entry.kind {
+                JournalEntryKind::Sent(frame) if frame.starts_with("?HELLO") => Some(frame.clone()),
+                _ => None,
+            })
+    };
+
+    harness.app.serial_event_propagator.hello_due = true;
+    sleep(Duration::from_secs_f64(SERIAL_DELAY_TIME * 1.5));
+    harness.frame(vec![]);
+    let hello = sent_hello(&harness).expect("Never said HELLO");
+    let sequence = RawFrame::split(&hello).unwrap().metadata.sequence.unwrap();
+    harness.respond(&format!(
+        "~HELLO${{\"protocol_version\": {PROTOCOL_VERSION}}}${{\"time\": 1.0, \"sequence\": {sequence}}}"
+    ));
+
+    assert!(harness.app.errors.is_empty());
+    assert!(harness
+        .app
+        .serial_event_propagator
+        .pending_requests
+        .is_empty());
+    assert_eq!(
+        harness.app.serial_event_propagator.protocol_version,
+        PROTOCOL_VERSION
+    );
+}
+
+#[test]
+fn firmware_from_before_hello_is_spoken_to_in_its_version() {
+    let mut harness = Harness::new();
+
+    harness.app.serial_event_propagator.hello_due = true;
+    sleep(Duration::from_secs_f64(SERIAL_DELAY_TIME * 1.5));
+    harness.frame(vec![]);
+    // Old firmware doesn't know the command, nor say which request it's
+    // answering
+    harness
+        .respond("~ERROR${\"error_variant\": 1, \"message\": \"Invalid command\"}${\"time\": 1.0}");
+    assert!(matches!(
+        harness.app.errors[..],
+        [ErrorData {
+            error: ClientError::ProtocolVersion {
+                car: 1,
+                fallback: true,
+                ..
+            },
+            ..
+        }]
+    ));
+    assert_eq!(harness.app.serial_event_propagator.protocol_version, 1);
+
+    // Sent the way it used to be
+    harness.click("+100");
+    harness.click("START");
+    let ping = std::iter::from_fn(|| harness.car.read_line().unwrap())
+        .find(|frame| frame.starts_with("?PING"))
+        .expect("Never sent a PING");
+    let metadata = RawFrame::split(&ping).unwrap().metadata;
+    assert_eq!((metadata.sequence, metadata.frame), (None, None));
+    assert!(!ping.contains('*'));
+
+    // And answered in order
+    harness.respond("~PING${\"sent_time\": 1.0}${\"time\": 1.0}");
+    assert!(harness
+        .app
+        .serial_event_propagator
+        .pending_requests
+        .iter()
+        .all(|pending| pending.command != Command::Ping));
+}
+
+#[test]
+fn whats_new_is_shown_until_read() {
+    let mut harness = Harness::new();
+    harness
+        .app
+        .gui_data
+        .whats_new
+        .check(&Changelog::built_in().unwrap());
+    harness.frame(vec![]);
+    assert!(harness.find("Safety: E-STOP from anywhere").is_some());
+
+    harness.click("Got it");
+    harness.frame(vec![]);
+    assert!(harness.find("Safety: E-STOP from anywhere").is_none());
+    assert_eq!(
+        harness.app.gui_data.whats_new.seen_version.as_deref(),
+        Some(VERSION)
+    );
+}
+
+#[test]
+fn frames_are_drawn_from_a_snapshot_of_the_run() {
+    let mut harness = Harness::new();
+    let status = |time: f64, distance: f64| {
+        format!(
+            "~STATUS${{\"running\": true, \"uptime\": 1, \"runtime\": 0, \"stage\": 1, \
+             \"distance\": {{\"distance\": {distance:?}, \"velocity\": 1.0, \
+             \"magnet_hit_counter\": 1}}}}${{\"time\": {time:?}}}"
+        )
+    };
+
+    harness.click("+100");
+    harness.click("START");
+    harness.respond(&status(1.0, 0.0));
+    let held = harness.app.shared_run_data.load();
+    assert_eq!(held.statuses.len(), 1);
+    assert!(held.running);
+
+    harness.respond(&status(2.0, 50.0));
+    let latest = harness.app.shared_run_data.load();
+    assert_eq!(
+        latest.statuses.len(),
+        harness.app.run_data.status_responses.len()
+    );
+    assert_eq!(latest.statuses.len(), 2);
+    // What was already being read is left alone
+    assert_eq!(held.statuses.len(), 1);
+}
+
+#[test]
+fn low_batteries_are_warned_about() {
+    let mut harness = Harness::new();
+    let status = |charge: f64| {
+        format!(
+            "~STATUS${{\"running\": false, \"uptime\": 1, \"runtime\": 0, \"stage\": 0, \
+             \"distance\": {{\"distance\": 0.0, \"velocity\": 0.0, \"magnet_hit_counter\": 0}}, \
+             \"power\": {{\"battery_voltage\": 7.2, \"motor_current\": 0.25, \
+             \"charge\": {charge:?}}}}}${{\"time\": 1.0}}"
+        )
+    };
+
+    harness.respond(&status(80.0));
+    assert!(harness.find("Battery 80% (7.20V)").is_some());
+    assert!(harness.find("Motor drawing 0.25A").is_some());
+    assert!(harness.app.gui_data.toast.is_none());
+
+    harness.respond(&status(10.0));
+    let warning = "The battery is low (7.20V, 10% left), charge it before the next run";
+    assert!(harness.find(warning).is_some());
+    assert_eq!(
+        harness
+            .app
+            .gui_data
+            .toast
+            .as_ref()
+            .map(|toast| toast.text.as_str()),
+        Some(warning)
+    );
+}
@@ -0,0 +1,70 @@
+/*!
+ * Tests for keeping what's logged for the logs panel
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{Command, LoopbackTransport, StatusArguments};
+use gui::{events::*, logging::*};
+use tracing::{debug_span, info, subscriber::with_default, warn, Level};
+use tracing_subscriber::{layer::SubscriberExt, registry};
+
+/***** Tests *****/
+
+#[test]
+fn events_are_kept_with_their_spans() {
+    let logs = LogBuffer::new(10);
+    with_default(registry().with(logs.clone()), || {
+        let _request = debug_span!("request", command = "START", sequence = 3).entered();
+        info!(frame = "?START", "Sent");
+    });
+
+    let events = logs.recent(LogLevel::Trace);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].level, Level::INFO);
+    assert_eq!(events[0].target, "logging");
+    assert_eq!(
+        events[0].describe(),
+        "request{command=START sequence=3}: Sent frame=?START"
+    );
+}
+
+#[test]
+fn only_the_most_recent_events_are_kept() {
+    let logs = LogBuffer::new(2);
+    with_default(registry().with(logs.clone()), || {
+        info!("First");
+        warn!("Second");
+        info!("Third");
+    });
+
+    let messages = |level| {
+        logs.recent(level)
+            .into_iter()
+            .map(|event| event.message)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(messages(LogLevel::Info), ["Second", "Third"]);
+    assert_eq!(messages(LogLevel::Warn), ["Second"]);
+    assert!(messages(LogLevel::Error).is_empty());
+}
+
+#[test]
+fn requests_are_logged_in_their_own_span() {
+    let logs = LogBuffer::new(10);
+    let (transport, _car) = LoopbackTransport::pair();
+    let mut serial_event_propagator = SerialEventPropagator::new(Box::new(transport));
+    with_default(registry().with(logs.clone()), || {
+        serial_event_propagator
+            .write_to_serial(Command::Status, StatusArguments {})
+            .unwrap();
+    });
+
+    let events = logs.recent(LogLevel::Debug);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].spans, ["request{command=STATUS sequence=0}"]);
+    assert!(events[0].message.starts_with("Sent frame=?STATUS$"));
+    // Frames aren't worth showing by default
+    assert!(logs.recent(LogLevel::Info).is_empty());
+}
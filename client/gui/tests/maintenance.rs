@@ -0,0 +1,167 @@
+/*!
+ * Tests for counting how much the car has been used and reminding to service
+ * it
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+mod common;
+use bindings::*;
+use gui::{config::*, maintenance::*};
+use std::{
+    env::temp_dir,
+    fs::{remove_file, write},
+};
+
+/***** Helper functions *****/
+
+fn status(runtime: usize, distance: f64, running: bool) -> Event<StatusResponse> {
+    let mut status = common::status(runtime as f64, distance, 0.0);
+    status.value.running = running;
+    status
+}
+
+fn every_runs(task: &str, runs: u32) -> ServiceReminder {
+    ServiceReminder {
+        task: task.to_owned(),
+        every_runs: Some(runs),
+        ..Default::default()
+    }
+}
+
+/***** Tests *****/
+
+#[test]
+fn runs_are_counted_from_their_statuses() {
+    // The runtime goes back to 0 once the car stops
+    let statuses = [
+        status(1, 50.0, true),
+        status(12, 480.0, true),
+        status(0, 500.0, false),
+    ];
+    assert_eq!(
+        Usage::of_run(&statuses),
+        Usage {
+            distance: 500.0,
+            motor_seconds: 12.0,
+            runs: 1,
+        }
+    );
+    assert_eq!(Usage::of_run(&[]), Usage::default());
+
+    // Runs that never sent anything aren't counted
+    let mut maintenance = Maintenance::default();
+    maintenance.record_run(&Usage::of_run(&[])).unwrap();
+    maintenance.record_run(&Usage::of_run(&statuses)).unwrap();
+    maintenance.record_run(&Usage::of_run(&statuses)).unwrap();
+    assert_eq!(maintenance.total.runs, 2);
+    assert_eq!(maintenance.total.distance, 1000.0);
+}
+
+#[test]
+fn reminders_are_due_until_serviced() {
+    let reminders = [
+        ServiceReminder::defaults().remove(0),
+        every_runs("Check the battery terminals", 3),
+        ServiceReminder {
+            task: "Oil the axles".to_owned(),
+            every_motor_hours: Some(1.0),
+            ..Default::default()
+        },
+    ];
+    let mut maintenance = Maintenance::default();
+    let run = Usage {
+        distance: 50_000.0,
+        motor_seconds: 900.0,
+        runs: 1,
+    };
+    for _ in 0..3 {
+        maintenance.record_run(&run).unwrap();
+    }
+    let due = maintenance.due(&reminders);
+    assert_eq!(due, [&reminders[1]]);
+
+    maintenance.serviced("Check the battery terminals").unwrap();
+    assert!(maintenance.due(&reminders).is_empty());
+
+    // 2.5km and 1.25h in, but only 2 runs since the battery terminals were
+    // checked
+    maintenance.record_run(&run).unwrap();
+    maintenance.record_run(&run).unwrap();
+    let due = maintenance.due(&reminders);
+    assert_eq!(due, [&reminders[0], &reminders[2]]);
+    assert_eq!(
+        maintenance
+            .since_serviced("Check the battery terminals")
+            .runs,
+        2
+    );
+}
+
+#[test]
+fn counters_are_loaded_back() {
+    let path = temp_dir()
+        .join(format!("gui-maintenance-{}", std::process::id()))
+        .join("maintenance.toml");
+    let _ = remove_file(&path);
+
+    let (mut maintenance, error) = Maintenance::load(Some(path.clone()));
+    assert!(error.is_none());
+    maintenance
+        .record_run(&Usage {
+            distance: 250.5,
+            motor_seconds: 8.0,
+            runs: 1,
+        })
+        .unwrap();
+    maintenance.serviced("Inspect the wheels").unwrap();
+
+    let (loaded, error) = Maintenance::load(Some(path.clone()));
+    assert!(error.is_none());
+    assert_eq!(loaded, maintenance);
+
+    // Starting from nothing, without replacing the file until the next run
+    write(&path, "total = 12").unwrap();
+    let (loaded, error) = Maintenance::load(Some(path.clone()));
+    assert!(matches!(error, Some(ClientError::Maintenance(_))));
+    assert_eq!(loaded.total, Usage::default());
+    let _ = remove_file(path);
+}
+
+#[test]
+fn reminders_are_configured() {
+    assert_eq!(
+        Config::default().maintenance.reminders,
+        ServiceReminder::defaults()
+    );
+
+    let config = Config::parse(
+        r#"
+        [[maintenance.reminders]]
+        task = "Check the battery terminals"
+        every_runs = 50
+        every_motor_hours = 2.5
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.maintenance.reminders,
+        [ServiceReminder {
+            every_motor_hours: Some(2.5),
+            ..every_runs("Check the battery terminals", 50)
+        }]
+    );
+
+    for reminders in [
+        r#"[{ task = "Nothing to go by" }]"#,
+        r#"[{ task = "Every run", every_runs = 0 }]"#,
+        r#"[{ task = "", every_distance = 100.0 }]"#,
+    ] {
+        let error = Config::parse(&format!("[maintenance]\nreminders = {reminders}")).unwrap_err();
+        assert!(
+            error.to_string().contains("maintenance.reminders"),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+/*!
+ * Tests for lining up the commands sent with the statuses received
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+mod common;
+use bindings::{Command, Event, StatusResponse};
+use gui::{clock::DisplayTimezone, events::*, narrative::*, shared::Units};
+use std::path::Path;
+
+/***** Helper functions *****/
+
+/// A status at `time` by the car's clock
+fn status(time: f64, distance: f64) -> Event<StatusResponse> {
+    let mut status = common::status(1.0, distance, 25.0);
+    status.metadata.time = time;
+    status
+}
+
+fn sent(command: Command, time: f64, retry: bool) -> SentCommand {
+    SentCommand {
+        command,
+        arguments: "{}".to_owned(),
+        time,
+        retry,
+    }
+}
+
+/***** Tests *****/
+
+#[test]
+fn sent_commands_are_put_between_statuses() {
+    // The car's clock is 100 seconds ahead
+    let statuses = [status(101.0, 10.0), status(102.0, 20.0)];
+    let sent_commands = [
+        sent(Command::Start, 0.5, false),
+        sent(Command::Start, 1.0, true),
+        sent(Command::Stop, 1.5, false),
+        sent(Command::Stop, 3.0, true),
+    ];
+
+    assert_eq!(
+        timeline(&statuses, &sent_commands, 100.0),
+        vec![
+            TimelineEntry::Sent(&sent_commands[0]),
+            TimelineEntry::Sent(&sent_commands[1]),
+            TimelineEntry::Status(0),
+            TimelineEntry::Sent(&sent_commands[2]),
+            TimelineEntry::Status(1),
+            TimelineEntry::Sent(&sent_commands[3]),
+        ]
+    );
+    assert_eq!(timeline(&statuses, &[], 100.0).len(), 2);
+}
+
+#[test]
+fn narrative_reads_in_order() {
+    let statuses = [status(1.25, 12.5)];
+    let mut start = sent(Command::Start, 0.5, false);
+    start.arguments = "{\"distance\":500.0}".to_owned();
+    let sent_commands = [start, sent(Command::Stop, 2.0, true)];
+
+    let narrative = narrative(
+        &statuses,
+        &sent_commands,
+        0.0,
+        &DisplayTimezone::Utc,
+        Units::Metric,
+    );
+    assert_eq!(
+        narrative,
+        "00:00:00.500 > START {\"distance\":500.0}\n\
+         00:00:01.250 < STATUS 12.500cm at 25.000cm/s, Forward\n\
+         00:00:02.000 > STOP (again) {}\n"
+    );
+    assert_eq!(
+        narrative_path(Path::new("runs/heat 1.csv")),
+        Path::new("runs/heat 1.narrative.txt")
+    );
+}
+
+#[test]
+fn only_commands_to_the_car_are_recorded() {
+    let mut run_data = RunData::default();
+    run_data.record_sent("?START${\"distance\":500.0}${\"time\":1.0}", 1.0, false);
+    run_data.record_sent("?STOP${}${\"time\":2.0}", 2.0, true);
+    run_data.record_sent("^SCAN${}${\"time\":3.0}", 3.0, false);
+    run_data.record_sent("garbage", 4.0, false);
+
+    assert_eq!(
+        run_data.sent_commands,
+        vec![
+            SentCommand {
+                command: Command::Start,
+                arguments: "{\"distance\":500.0}".to_owned(),
+                time: 1.0,
+                retry: false,
+            },
+            sent(Command::Stop, 2.0, true),
+        ]
+    );
+}
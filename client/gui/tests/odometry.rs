@@ -0,0 +1,89 @@
+/*!
+ * Tests for recomputing distance and velocity from the magnet hits
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+mod common;
+use bindings::{Event, StatusResponse, StatusStage};
+use gui::odometry::*;
+
+// Constants
+/// 10cm between hits
+const WHEEL: Wheel = Wheel {
+    number_of_magnets: 2,
+    circumference: 20.0,
+};
+
+/***** Helper functions *****/
+
+/// A status `time` seconds after a run that started at 1000, as the server
+/// reported it
+fn status(
+    time: f64,
+    stage: StatusStage,
+    magnet_hit_counter: usize,
+    distance: f64,
+    velocity: f64,
+) -> Event<StatusResponse> {
+    let mut status = common::status(time, distance, velocity);
+    status.value.stage = stage;
+    status.value.distance.magnet_hit_counter = magnet_hit_counter;
+    status
+}
+
+/***** Tests *****/
+
+#[test]
+fn distance_is_recomputed_from_hits() {
+    use StatusStage::*;
+    let statuses = [
+        status(1.0, VehementForward, 3, 30.0, 30.0),
+        status(2.0, VehementForward, 8, 80.0, 40.0),
+        // Backing up two hits
+        status(4.0, CautiousBackward, 10, 60.0, 15.0),
+    ];
+    let recomputed = recompute(&statuses, &WHEEL);
+    let distances = recomputed.iter().map(|r| r.distance).collect::<Vec<_>>();
+    assert_eq!(distances, [30.0, 80.0, 60.0]);
+    let velocities = recomputed.iter().map(|r| r.velocity).collect::<Vec<_>>();
+    assert_eq!(velocities, [30.0, 40.0, 15.0]);
+    assert!(recomputed
+        .iter()
+        .all(|r| !r.distance_discrepancy && !r.velocity_discrepancy));
+
+    assert!(recompute(&[], &WHEEL).is_empty());
+}
+
+#[test]
+fn discrepancies_are_caught() {
+    use StatusStage::*;
+    let statuses = [
+        status(1.0, VehementForward, 3, 30.0, 30.0),
+        // Within a hit of each other
+        status(2.0, VehementForward, 8, 89.0, 44.0),
+        // Counting a hit twice, and averaging over the wrong time
+        status(4.0, VehementForward, 10, 120.0, 60.0),
+    ];
+    let recomputed = recompute(&statuses, &WHEEL);
+    assert!(!recomputed[1].distance_discrepancy);
+    assert!(!recomputed[1].velocity_discrepancy);
+    assert!(recomputed[2].distance_discrepancy);
+    assert!(recomputed[2].velocity_discrepancy);
+}
+
+#[test]
+fn magnetless_wheels_go_nowhere() {
+    let wheel = Wheel {
+        number_of_magnets: 0,
+        circumference: 20.0,
+    };
+    assert_eq!(wheel.distance_per_hit(), 0.0);
+    let recomputed = recompute(
+        &[status(1.0, StatusStage::VehementForward, 3, 30.0, 30.0)],
+        &wheel,
+    );
+    assert_eq!(recomputed[0].distance, 0.0);
+    assert!(recomputed[0].distance_discrepancy);
+}
@@ -0,0 +1,153 @@
+/*!
+ * Tests for queueing frames to the car, most urgent first
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{
+    ClientError, Command, LoopbackTransport, PingArguments, RawFrame, StaticStatusArguments,
+    StatusArguments, StopArguments, Transport,
+};
+use gui::{events::*, outgoing::*};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/***** Helper functions *****/
+
+/// A connection that says it still has however many lines the test wants
+/// waiting to go out, like a slow Bluetooth link
+struct BackloggedTransport {
+    transport: LoopbackTransport,
+    backlog: Arc<AtomicUsize>,
+}
+impl Transport for BackloggedTransport {
+    fn read_line(&mut self) -> Result<Option<String>, ClientError> {
+        self.transport.read_line()
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), ClientError> {
+        self.transport.write_line(line)
+    }
+
+    fn connected(&self) -> bool {
+        self.transport.connected()
+    }
+
+    fn try_clone(&self) -> Result<Box<dyn Transport>, ClientError> {
+        self.transport.try_clone()
+    }
+
+    fn queued(&self) -> usize {
+        self.backlog.load(Ordering::Relaxed)
+    }
+}
+
+/// A propagator with a backlog, and the car's end of its connection
+fn backlogged() -> (SerialEventPropagator, Arc<AtomicUsize>, LoopbackTransport) {
+    let (transport, car) = LoopbackTransport::pair();
+    let backlog = Arc::new(AtomicUsize::new(1_usize));
+    let propagator = SerialEventPropagator::new(Box::new(BackloggedTransport {
+        transport,
+        backlog: Arc::clone(&backlog),
+    }));
+    (propagator, backlog, car)
+}
+
+/// The commands the car got, in the order it got them
+fn received(car: &mut LoopbackTransport) -> Vec<Command> {
+    let mut commands = vec![];
+    while let Some(line) = car.read_line().unwrap() {
+        commands.push(RawFrame::split(&line).unwrap().command);
+    }
+    commands
+}
+
+/***** Tests *****/
+
+#[test]
+fn the_most_urgent_frames_are_taken_first() {
+    let mut outgoing = OutgoingQueue::new();
+    outgoing.push(Command::Status, "status".to_owned());
+    outgoing.push(Command::Ping, "first ping".to_owned());
+    outgoing.push(Command::Stop, "stop".to_owned());
+    outgoing.push(Command::Ping, "second ping".to_owned());
+
+    let order = std::iter::from_fn(|| outgoing.pop())
+        .map(|queued| queued.frame)
+        .collect::<Vec<_>>();
+    assert_eq!(order, ["stop", "first ping", "second ping", "status"]);
+    assert!(outgoing.is_empty());
+}
+
+#[test]
+fn stop_goes_ahead_of_everything_waiting() {
+    let (mut propagator, backlog, mut car) = backlogged();
+    propagator
+        .write_to_serial(Command::Ping, PingArguments { time: 1.0 })
+        .unwrap();
+    propagator
+        .write_to_serial(Command::Status, StatusArguments {})
+        .unwrap();
+    propagator
+        .write_to_serial(Command::Stop, StopArguments {})
+        .unwrap();
+
+    // Nothing is handed over while the link is still busy
+    assert_eq!(propagator.queue_depth(), 4);
+    assert!(received(&mut car).is_empty());
+
+    backlog.store(0_usize, Ordering::Relaxed);
+    propagator.flush_outgoing().unwrap();
+    assert_eq!(
+        received(&mut car),
+        [Command::Stop, Command::Ping, Command::Status]
+    );
+    assert_eq!(propagator.queue_depth(), 0);
+    assert_eq!(propagator.recent_frames.len(), 3);
+}
+
+#[test]
+fn waiting_requests_are_not_resent() {
+    let (mut propagator, _backlog, _car) = backlogged();
+    propagator.request_timeout = 0.0;
+    propagator
+        .write_to_serial(Command::Ping, PingArguments { time: 1.0 })
+        .unwrap();
+
+    assert!(propagator.retry_pending().is_empty());
+    assert_eq!(propagator.outgoing.len(), 1);
+    assert_eq!(propagator.pending_requests[0].attempts, 1);
+}
+
+#[test]
+fn status_polls_are_coalesced() {
+    let (mut propagator, backlog, mut car) = backlogged();
+    backlog.store(0_usize, Ordering::Relaxed);
+
+    assert!(propagator
+        .queue(Command::Status, StatusArguments {})
+        .unwrap()
+        .is_some());
+    assert!(propagator
+        .queue(Command::Status, StatusArguments {})
+        .unwrap()
+        .is_none());
+    // Only the same status
+    assert!(propagator
+        .queue(Command::StaticStatus, StaticStatusArguments {})
+        .unwrap()
+        .is_some());
+    assert_eq!(received(&mut car), [Command::Status, Command::StaticStatus]);
+
+    // Asked for again once it's answered (or given up on)
+    propagator
+        .pending_requests
+        .retain(|pending| pending.command != Command::Status);
+    assert!(propagator
+        .queue(Command::Status, StatusArguments {})
+        .unwrap()
+        .is_some());
+}
@@ -0,0 +1,55 @@
+/*!
+ * Tests for warning about the battery running low
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::PowerStatus;
+use gui::{config::CarConfig, power::*};
+
+/***** Helper functions *****/
+
+fn power(battery_voltage: f64, charge: Option<f64>) -> PowerStatus {
+    PowerStatus {
+        battery_voltage,
+        motor_current: 1.0,
+        charge,
+    }
+}
+
+/***** Tests *****/
+
+#[test]
+fn the_battery_is_low_under_either_threshold() {
+    let car = CarConfig::default();
+    assert!(!battery_low(&power(7.4, Some(50.0)), &car));
+    assert!(battery_low(&power(7.4, Some(10.0)), &car));
+    // Without the charge or a voltage to go by, it's never known to be low
+    assert!(!battery_low(&power(5.0, None), &car));
+
+    let car = CarConfig {
+        low_battery_voltage: Some(6.6),
+        ..Default::default()
+    };
+    assert!(battery_low(&power(6.5, None), &car));
+    assert!(battery_low(&power(6.5, Some(50.0)), &car));
+    assert!(!battery_low(&power(7.0, None), &car));
+    assert_eq!(
+        battery_warning(&power(6.5, Some(15.0)), &car).as_deref(),
+        Some("The battery is low (6.50V, 15% left), charge it before the next run")
+    );
+}
+
+#[test]
+fn low_batteries_are_alerted_about_once() {
+    let car = CarConfig::default();
+    let mut alert = BatteryAlert::default();
+    assert_eq!(alert.check(&power(7.4, Some(30.0)), &car), None);
+    assert!(alert.check(&power(6.8, Some(15.0)), &car).is_some());
+    assert_eq!(alert.check(&power(6.7, Some(12.0)), &car), None);
+
+    // Charged back up, then run down again
+    assert_eq!(alert.check(&power(8.2, Some(95.0)), &car), None);
+    assert!(alert.check(&power(6.8, Some(15.0)), &car).is_some());
+}
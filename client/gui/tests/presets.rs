@@ -0,0 +1,136 @@
+/*!
+ * Tests for saving the start parameters as named presets
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{ClientError, DistanceEstimatorKind};
+use gui::presets::*;
+use std::{
+    env::temp_dir,
+    fs::{create_dir_all, remove_file, write},
+};
+
+/***** Helper functions *****/
+
+fn preset(name: &str, distance: f64) -> Preset {
+    Preset {
+        name: name.to_owned(),
+        distance,
+        ..Default::default()
+    }
+}
+
+/***** Tests *****/
+
+#[test]
+fn saved_presets_are_loaded_back() {
+    let directory = temp_dir().join(format!("gui-presets-{}", std::process::id()));
+    let path = directory.join("round-trip").join("presets.toml");
+    let _ = remove_file(&path);
+
+    // Nothing saved yet is fine
+    let (mut presets, error) = Presets::load(Some(path.clone()));
+    assert!(error.is_none());
+    assert!(presets.presets.is_empty());
+
+    presets.save(preset("15 m", 1500.0)).unwrap();
+    presets
+        .save(Preset {
+            auto_stop: true,
+            ..preset("20 m", 2000.0)
+        })
+        .unwrap();
+    presets.save(preset("25 m", 2500.0)).unwrap();
+    // Saving under the same name replaces it where it was
+    presets
+        .save(Preset {
+            distance_estimator: Some(DistanceEstimatorKind::AccelerometerFusion),
+            ..preset("20 m", 2050.0)
+        })
+        .unwrap();
+    presets.delete("25 m").unwrap();
+    presets.delete("30 m").unwrap();
+
+    let (loaded, error) = Presets::load(Some(path));
+    assert!(error.is_none());
+    assert_eq!(loaded, presets);
+    assert_eq!(
+        loaded
+            .presets
+            .iter()
+            .map(|preset| (
+                preset.name.as_str(),
+                preset.distance,
+                preset.auto_stop,
+                preset.distance_estimator
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            ("15 m", 1500.0, false, None),
+            (
+                "20 m",
+                2050.0,
+                false,
+                Some(DistanceEstimatorKind::AccelerometerFusion)
+            )
+        ]
+    );
+    assert_eq!(loaded.find("15 m"), Some(&preset("15 m", 1500.0)));
+}
+
+#[test]
+fn presets_need_a_name() {
+    let mut presets = Presets::default();
+    assert!(matches!(
+        presets.save(preset("  ", 1500.0)),
+        Err(ClientError::Presets(_))
+    ));
+    assert!(presets.presets.is_empty());
+}
+
+#[test]
+fn presets_file_is_parsed() {
+    let presets = Presets::parse(
+        "[[preset]]\n\
+         name = \"15 m\"\n\
+         distance = 1500.0\n\
+         \n\
+         [[preset]]\n\
+         name = \"Practice\"\n\
+         distance = 500.0\n\
+         speed_limit = true\n\
+         max_velocity = 20.0\n\
+         distance_estimator = 2\n",
+    )
+    .unwrap();
+    assert_eq!(presets[0], preset("15 m", 1500.0));
+    assert!(presets[1].speed_limit);
+    assert_eq!(presets[1].max_velocity, 20.0);
+    assert_eq!(
+        presets[1].distance_estimator,
+        Some(DistanceEstimatorKind::Rangefinder)
+    );
+    assert!(Presets::parse("").unwrap().is_empty());
+
+    assert!(matches!(
+        Presets::parse("[[preset]]\nname = \"15 m\"\ndistanse = 1500.0\n"),
+        Err(ClientError::Presets(_))
+    ));
+}
+
+#[test]
+fn broken_presets_file_is_reported_and_replaced() {
+    let directory = temp_dir().join(format!("gui-presets-{}", std::process::id()));
+    create_dir_all(&directory).unwrap();
+    let path = directory.join("broken.toml");
+    write(&path, "[[preset]\n").unwrap();
+
+    let (mut presets, error) = Presets::load(Some(path.clone()));
+    assert!(matches!(error, Some(ClientError::Presets(_))));
+    assert!(presets.presets.is_empty());
+
+    presets.save(preset("15 m", 1500.0)).unwrap();
+    assert_eq!(Presets::read(&path).unwrap(), presets.presets);
+}
@@ -0,0 +1,220 @@
+/*!
+ * Property-based tests of what's worked out from a run's statuses, so edge
+ * cases (like runs with no statuses, or a car standing still) can't quietly
+ * give wrong numbers
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+mod common;
+use bindings::{Event, StatusResponse};
+use gui::{odometry::*, splits::*};
+use proptest::prelude::*;
+
+// Constants
+/// For comparing floats worked out two different ways
+const TOLERANCE: f64 = 1e-6;
+
+/***** Helper functions *****/
+
+/// A status `time` seconds into a run that started at 1000
+fn status(
+    time: f64,
+    distance: f64,
+    velocity: f64,
+    magnet_hit_counter: usize,
+) -> Event<StatusResponse> {
+    let mut status = common::status(time, distance, velocity);
+    status.value.distance.magnet_hit_counter = magnet_hit_counter;
+    status
+}
+
+/// Seconds into the run a status is, going by when the run is taken to have
+/// started
+fn elapsed(statuses: &[Event<StatusResponse>], status: &Event<StatusResponse>) -> f64 {
+    status.metadata.time - start_time(statuses).unwrap()
+}
+
+/***** Strategies *****/
+
+/// A run that only goes forward (or stands still), with statuses coming in
+/// every so often and whatever velocity the server said
+fn forward_run() -> impl Strategy<Value = Vec<Event<StatusResponse>>> {
+    prop::collection::vec((0.05..2.0_f64, 0.0..50.0_f64, 0.0..200.0_f64), 0..30).prop_map(|steps| {
+        let (mut time, mut distance) = (0.0, 0.0);
+        steps
+            .into_iter()
+            .map(|(time_step, moved, velocity)| {
+                time += time_step;
+                distance += moved;
+                status(time, distance, velocity, 0)
+            })
+            .collect()
+    })
+}
+
+/// A run as the server would report it from the magnet hits, with the wheel
+/// it has
+fn counted_run() -> impl Strategy<Value = (Vec<Event<StatusResponse>>, Wheel)> {
+    (
+        prop::collection::vec((0.05..2.0_f64, 0..5_usize), 0..30),
+        1..8_usize,
+        1.0..50.0_f64,
+    )
+        .prop_map(|(steps, number_of_magnets, circumference)| {
+            let wheel = Wheel {
+                number_of_magnets,
+                circumference,
+            };
+            let (mut time, mut hits) = (0.0, 0);
+            let statuses = steps
+                .into_iter()
+                .map(|(time_step, new_hits)| {
+                    time += time_step;
+                    hits += new_hits;
+                    let distance = hits as f64 * wheel.distance_per_hit();
+                    status(time, distance, 0.0, hits)
+                })
+                .collect();
+            (statuses, wheel)
+        })
+}
+
+/***** Tests *****/
+
+#[test]
+fn zero_length_runs_have_nothing_worked_out() {
+    assert!(split_times(&[], 10.0).is_empty());
+    assert_eq!(distance_at(&[], 1.0), None);
+    assert_eq!(baseline_delta(&[], &[]), None);
+    assert!(recompute(
+        &[],
+        &Wheel {
+            number_of_magnets: 1,
+            circumference: 10.0
+        }
+    )
+    .is_empty());
+
+    // No time has gone by yet
+    let statuses = [status(0.5, 20.0, 40.0, 0)];
+    assert!(split_times(&statuses, 10.0)
+        .iter()
+        .all(|split| split.speed.is_finite()));
+    assert_eq!(distance_at(&statuses, 0.0), Some(20.0));
+}
+
+proptest! {
+    #[test]
+    fn splits_are_at_every_marker_passed(run in forward_run(), interval in 1.0..100.0_f64) {
+        let splits = split_times(&run, interval);
+        let furthest = run.last().map(|status| status.value.distance.distance).unwrap_or(0.0);
+        prop_assert_eq!(splits.len(), (furthest / interval).floor() as usize);
+
+        let mut previous_time = 0.0;
+        for (idx, split) in splits.iter().enumerate() {
+            prop_assert_eq!(split.marker, interval * (idx + 1) as f64);
+            prop_assert!(split.time >= previous_time, "{splits:?}");
+            prop_assert!(split.time <= elapsed(&run, run.last().unwrap()) + TOLERANCE);
+            prop_assert!(split.speed >= 0.0 && split.speed.is_finite(), "{splits:?}");
+            previous_time = split.time;
+        }
+    }
+
+    #[test]
+    fn distance_is_interpolated_between_statuses(
+        run in forward_run(),
+        times in prop::collection::vec(0.0..70.0_f64, 1..10),
+    ) {
+        let Some(last) = run.last() else {
+            return Ok(());
+        };
+        let furthest = last.value.distance.distance;
+
+        let mut times = times;
+        times.sort_by(f64::total_cmp);
+        let mut previous = 0.0;
+        for time in times {
+            let distance = distance_at(&run, time).unwrap();
+            // Going forward, it's never further back than it was before
+            prop_assert!(distance >= previous - TOLERANCE);
+            prop_assert!(distance <= furthest + TOLERANCE);
+            previous = distance;
+        }
+        prop_assert_eq!(distance_at(&run, elapsed(&run, last)), Some(furthest));
+
+        // A run is never ahead or behind itself
+        let delta = baseline_delta(&run, &run).unwrap();
+        prop_assert!(delta.abs() < TOLERANCE, "{delta}");
+    }
+
+    #[test]
+    fn magnet_hits_add_up_to_the_distance((run, wheel) in counted_run()) {
+        let recomputed = recompute(&run, &wheel);
+        prop_assert_eq!(recomputed.len(), run.len());
+
+        let mut previous = 0.0;
+        for (status, recomputed) in run.iter().zip(recomputed.iter()) {
+            let distance = status.value.distance.distance;
+            prop_assert!((recomputed.distance - distance).abs() < TOLERANCE);
+            prop_assert!(recomputed.distance >= previous);
+            prop_assert!(recomputed.velocity >= 0.0 && recomputed.velocity.is_finite());
+            // The server went by the same magnets
+            prop_assert!(!recomputed.distance_discrepancy);
+            previous = recomputed.distance;
+        }
+    }
+}
+
+#[cfg(feature = "analytics")]
+proptest! {
+    #[test]
+    fn analytics_hold_after_every_status(run in forward_run(), target in 1.0..1000.0_f64) {
+        use gui::analytics::*;
+
+        let analytics = RunAnalytics::over(&run, Some(target));
+        prop_assert_eq!(analytics.len(), run.len());
+        prop_assert_eq!(analytics.last().copied(), RunAnalytics::of(&run, Some(target)));
+
+        let mut peak_velocity = f64::MIN;
+        for (status, analytics) in run.iter().zip(analytics.iter()) {
+            let distance = status.value.distance.distance;
+            let velocity = status.value.distance.velocity;
+            peak_velocity = peak_velocity.max(velocity);
+            prop_assert_eq!(analytics.peak_velocity, peak_velocity);
+
+            // Only going forward, so it can't have averaged going backward
+            prop_assert!(analytics.average_velocity >= 0.0);
+            prop_assert!(analytics.average_velocity.is_finite());
+
+            match analytics.time_to_target {
+                Some(time) => prop_assert!(time >= 0.0 && time.is_finite(), "{analytics:?}"),
+                None => prop_assert!(distance < target),
+            }
+            if distance >= target {
+                prop_assert_eq!(analytics.time_to_target, Some(0.0));
+            }
+
+            // Never projected to stop behind where it already is
+            if let Some(overshoot) = analytics.projected_overshoot {
+                prop_assert!(overshoot >= distance - target - TOLERANCE, "{analytics:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn stopped_car_overshoots_by_where_it_stopped(run in forward_run(), target in 1.0..1000.0_f64) {
+        use gui::analytics::*;
+
+        let mut run = run;
+        let (time, distance) = run
+            .last()
+            .map(|last| (last.metadata.time - 1000.0, last.value.distance.distance))
+            .unwrap_or((0.0, 0.0));
+        run.push(status(time + 1.0, distance, 0.0, 0));
+
+        let analytics = RunAnalytics::of(&run, Some(target)).unwrap();
+        prop_assert_eq!(analytics.projected_overshoot, Some(distance - target));
+    }
+}
@@ -0,0 +1,150 @@
+/*!
+ * Tests against runs recorded on the real car, in every shape of table the
+ * GUI has saved them in, so changes to how runs are stored keep old ones
+ * loading and adding up the same
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{Event, StatusResponse, StatusStage};
+use gui::{export::*, run_cache::*, run_history::*, splits::*};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "analytics")]
+use {csv::Reader, gui::analytics::*};
+
+// Constants
+/// The first recorded run (from `run-tests`), 150cm on a hardwood floor, saved
+/// before the speed limit and the time columns existed
+const HARDWOOD_HOME: &str = "hardwood-home-2023-04-28.csv";
+/// The same run saved again in inches, with the analytics columns
+const HARDWOOD_HOME_INCHES: &str = "hardwood-home-2023-04-28-inches.csv";
+/// The same run saved again with the analytics columns for a 150cm target
+const HARDWOOD_HOME_ANALYTICS: &str = "hardwood-home-2023-04-28-analytics.csv";
+
+/***** Helper functions *****/
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+fn recorded_run(name: &str) -> Vec<Event<StatusResponse>> {
+    read_run(&fixture(name)).unwrap_or_else(|e| panic!("Couldn't load {name}: {e}"))
+}
+
+fn close(left: f64, right: f64) -> bool {
+    (left - right).abs() < 1e-9
+}
+
+/***** Tests *****/
+
+#[test]
+fn recorded_runs_load_however_they_were_saved() {
+    let original = recorded_run(HARDWOOD_HOME);
+    assert_eq!(original.len(), 27);
+    assert_eq!(original[0].metadata.time, 1682643287.653952);
+    assert!(original
+        .iter()
+        .all(|status| status.value.speed_limit_violations == 0));
+
+    for name in [HARDWOOD_HOME_INCHES, HARDWOOD_HOME_ANALYTICS] {
+        let resaved = recorded_run(name);
+        assert_eq!(resaved.len(), original.len(), "{name}");
+        for (resaved, original) in resaved.iter().zip(&original) {
+            assert_eq!(resaved.metadata.time, original.metadata.time, "{name}");
+            assert_eq!(resaved.value.runtime, original.value.runtime, "{name}");
+            assert_eq!(
+                resaved.value.stage as u8, original.value.stage as u8,
+                "{name}"
+            );
+            assert!(
+                close(
+                    resaved.value.distance.distance,
+                    original.value.distance.distance
+                ),
+                "{name}"
+            );
+            assert!(
+                close(
+                    resaved.value.distance.velocity,
+                    original.value.distance.velocity
+                ),
+                "{name}"
+            );
+        }
+
+        // Only the end of the file is read for the last status
+        let last = read_last_of_run(&fixture(name)).unwrap().unwrap();
+        assert_eq!(last.metadata.time, 1682643294.0665169, "{name}");
+        assert!(matches!(last.value.stage, StatusStage::Finalized), "{name}");
+    }
+}
+
+#[test]
+fn recorded_runs_are_summed_up() {
+    for name in [HARDWOOD_HOME, HARDWOOD_HOME_INCHES, HARDWOOD_HOME_ANALYTICS] {
+        let statuses = recorded_run(name);
+        let summary = RunSummary::new(&statuses.last().unwrap().value);
+        assert_eq!(summary.runtime, 6, "{name}");
+        // Backed up 3 magnets after coasting to 149.6cm
+        assert!(close(summary.distance, 119.69468010177113), "{name}");
+    }
+}
+
+#[cfg(feature = "analytics")]
+#[test]
+fn recorded_runs_are_analyzed() {
+    let statuses = recorded_run(HARDWOOD_HOME);
+    let analytics = RunAnalytics::of(&statuses, Some(150.0)).unwrap();
+    // 119.7cm over the 6.41s the run took
+    assert!(close(analytics.average_velocity, 18.665647870112846));
+    assert!(close(analytics.peak_velocity, 57.66898900642644));
+    // Stopping short while backing up
+    assert_eq!(analytics.time_to_target, None);
+    assert!(close(
+        analytics.projected_overshoot.unwrap(),
+        -17.938419837747034
+    ));
+
+    // What was saved alongside the statuses is what's worked out now
+    let mut table = Reader::from_path(fixture(HARDWOOD_HOME_ANALYTICS)).unwrap();
+    let saved = table.records().map(Result::unwrap).collect::<Vec<_>>();
+    let derived = |value: &str| value.parse::<f64>().ok();
+    for (saved, analytics) in saved.iter().zip(RunAnalytics::over(&statuses, Some(150.0))) {
+        assert!(close(
+            saved[10].parse().unwrap(),
+            analytics.average_velocity
+        ));
+        assert!(close(saved[11].parse().unwrap(), analytics.peak_velocity));
+        assert_eq!(derived(&saved[12]), analytics.time_to_target);
+        assert_eq!(derived(&saved[13]), analytics.projected_overshoot);
+    }
+    assert_eq!(saved.len(), statuses.len());
+}
+
+#[test]
+fn recorded_runs_are_split() {
+    for name in [HARDWOOD_HOME, HARDWOOD_HOME_INCHES] {
+        let statuses = recorded_run(name);
+        let splits = split_times(&statuses, 50.0);
+        let markers = splits.iter().map(|split| split.marker).collect::<Vec<_>>();
+        assert_eq!(markers, [50.0, 100.0], "{name}");
+        assert!(close(splits[0].time, 1.0021104341918528), "{name}");
+        assert!(close(splits[0].speed, 49.89470051803449), "{name}");
+        assert!(close(splits[1].time, 1.6271394796802647), "{name}");
+        assert!(close(splits[1].speed, 79.99628235025278), "{name}");
+        assert!(close(
+            distance_at(&statuses, 2.0).unwrap(),
+            129.91467716474722
+        ));
+    }
+
+    // Against itself cut off before it stopped
+    let statuses = recorded_run(HARDWOOD_HOME);
+    let comparison = compare(&statuses, &statuses[..12]);
+    assert!(close(comparison.end, 6.412564992904663));
+    assert!(close(comparison.top, 149.61835012721392));
+}
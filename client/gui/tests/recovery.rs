@@ -0,0 +1,102 @@
+/*!
+ * Tests for what's offered to fix errors from the server
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{ClientError, ErrorResponse, ServerError};
+use gui::{journal::*, recovery::*, ErrorData};
+
+/***** Tests *****/
+
+#[test]
+fn server_errors_are_offered_a_fix() {
+    let cases = [
+        (
+            ServerError::FailedToStartAlreadyStarted,
+            Some(Recovery::StopAndStartAgain),
+        ),
+        (
+            ServerError::FailedToStartMagnetOdometerFailed,
+            Some(Recovery::RequestStaticStatus),
+        ),
+        (
+            ServerError::FailedToStartCouldNotAcquireDistanceLock,
+            Some(Recovery::StartAgain),
+        ),
+        (
+            ServerError::FailedToStopNotStarted,
+            Some(Recovery::GiveUpOnRun),
+        ),
+        (
+            ServerError::FailedToStopStartThreadWouldNotRespond,
+            Some(Recovery::StopAgain),
+        ),
+        (
+            ServerError::FailedPingNegativeLatency,
+            Some(Recovery::PingAgain),
+        ),
+        (
+            ServerError::MalformedRequestFailedPrefixParsing,
+            Some(Recovery::ReconnectBluetooth),
+        ),
+        (
+            ServerError::MalformedResponseOtherError,
+            Some(Recovery::ReconnectBluetooth),
+        ),
+        (ServerError::FailedToStartMotorControlFailed, None),
+        (ServerError::FailedStatusCouldNotAcquireDistanceLock, None),
+        (ServerError::AnyOtherError, None),
+    ];
+    for (error, recovery) in cases {
+        assert_eq!(Recovery::for_server_error(error), recovery, "{error:?}");
+    }
+}
+
+#[test]
+fn only_server_errors_are_offered_a_fix() {
+    let error = ClientError::from(&ErrorResponse {
+        error_variant: ServerError::FailedToStartAlreadyStarted as u8,
+        message: "Already started".to_owned(),
+    });
+    assert_eq!(
+        error.to_string(),
+        "Failed to start - Already started: Already started"
+    );
+    assert_eq!(
+        ErrorData::new(error).recovery,
+        Some(Recovery::StopAndStartAgain)
+    );
+
+    assert_eq!(
+        ErrorData::new(ClientError::Run("Already started".to_owned())).recovery,
+        None
+    );
+}
+
+#[test]
+fn fixes_are_replayed_from_the_journal() {
+    let mut journal = Journal::default();
+    journal.record(JournalEntryKind::Action(UserAction::Start {
+        distance: 100.0,
+        reverse_braking: false,
+        wheel_circumference: None,
+    }));
+    journal.record(JournalEntryKind::Action(UserAction::Recover(
+        Recovery::GiveUpOnRun,
+    )));
+    journal.record(JournalEntryKind::Action(UserAction::Recover(
+        Recovery::StopAndStartAgain,
+    )));
+
+    assert!(!journal.replay(1).run_data.running);
+    assert!(journal.replay(2).run_data.running);
+
+    // Kept in diagnostic bundles
+    let journal = Journal::from_json(&journal.to_json().unwrap()).unwrap();
+    assert_eq!(
+        journal.entries()[2].kind.describe(),
+        "Fix it: Stop the car and start again"
+    );
+}
@@ -0,0 +1,66 @@
+/*!
+ * Tests for keeping the round trips of each command
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{Command, RoundTrip};
+use gui::round_trips::*;
+
+/***** Tests *****/
+
+#[test]
+fn round_trips_are_kept_per_command() {
+    let mut round_trips = RoundTrips::new();
+    assert!(round_trips.is_empty());
+
+    round_trips.record(
+        Command::Ping,
+        RoundTrip {
+            total: 0.1,
+            server: 0.02,
+        },
+    );
+    round_trips.record(
+        Command::Status,
+        RoundTrip {
+            total: 0.05,
+            server: 0.01,
+        },
+    );
+    round_trips.record(
+        Command::Ping,
+        RoundTrip {
+            total: 0.3,
+            server: 0.04,
+        },
+    );
+    round_trips.record(
+        Command::Ping,
+        RoundTrip {
+            total: 0.2,
+            server: 0.06,
+        },
+    );
+
+    // In the order they were first answered
+    let commands = round_trips
+        .commands()
+        .iter()
+        .map(|round_trips| round_trips.command)
+        .collect::<Vec<_>>();
+    assert_eq!(commands, vec![Command::Ping, Command::Status]);
+
+    let ping = round_trips.get(Command::Ping).unwrap();
+    assert_eq!(ping.count, 3);
+    assert_eq!(ping.last.total, 0.2);
+    // The slowest is by the whole round trip, not the time on the car
+    assert_eq!(ping.slowest.total, 0.3);
+    assert_eq!(ping.slowest.server, 0.04);
+    assert!((ping.average().total - 0.2).abs() < 1e-9);
+    assert!((ping.average().server - 0.04).abs() < 1e-9);
+
+    assert_eq!(round_trips.get(Command::Status).unwrap().count, 1);
+    assert!(round_trips.get(Command::Start).is_none());
+}
@@ -0,0 +1,121 @@
+/*!
+ * Tests for reading saved runs lazily
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+mod common;
+use bindings::{Event, StatusResponse};
+use gui::{export::*, run_cache::*, shared::*};
+use std::{
+    env::temp_dir,
+    fs::{create_dir_all, remove_dir_all, write, File},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/***** Helper functions *****/
+
+fn status(runtime: usize, distance: f64) -> Event<StatusResponse> {
+    common::status(runtime as f64, distance, 0.0)
+}
+
+/// An empty directory to save runs in, just for one test
+fn run_directory(test: &str) -> PathBuf {
+    let directory = temp_dir().join(format!("gui-run-cache-{test}-{}", std::process::id()));
+    let _ = remove_dir_all(&directory);
+    create_dir_all(&directory).unwrap();
+    directory
+}
+
+/// A run `length` statuses long going 1cm per second
+fn save_run(path: &Path, length: usize) {
+    let statuses = (1..=length)
+        .map(|runtime| status(runtime, runtime as f64))
+        .collect::<Vec<_>>();
+    CSVDynamicStatus::write(path, &statuses).unwrap();
+}
+
+/***** Tests *****/
+
+#[test]
+fn last_status_is_read_from_the_end() {
+    let directory = run_directory("last");
+    // Much longer than the bit at the end that's read
+    let long = directory.join("long.csv");
+    save_run(&long, 500);
+    let last = CSVDynamicStatus::read_last(&long).unwrap().unwrap();
+    assert_eq!(last.value.runtime, 500);
+
+    let short = directory.join("short.csv");
+    save_run(&short, 1);
+    let last = CSVDynamicStatus::read_last(&short).unwrap().unwrap();
+    assert_eq!(last.value.runtime, 1);
+
+    let empty = directory.join("empty.csv");
+    save_run(&empty, 0);
+    assert!(CSVDynamicStatus::read_last(&empty).unwrap().is_none());
+
+    let broken = directory.join("broken.csv");
+    write(&broken, "Unix time\nnot a number\n").unwrap();
+    assert!(CSVDynamicStatus::read_last(&broken).is_err());
+}
+
+#[test]
+fn runs_are_summarized_without_being_read() {
+    let directory = run_directory("summary");
+    let run = directory.join("run.csv");
+    save_run(&run, 30);
+
+    let mut run_cache = RunCache::new();
+    assert_eq!(
+        run_cache.summary(&run),
+        Some(RunSummary {
+            runtime: 30,
+            distance: 30.0,
+        })
+    );
+    assert!(!run_cache.contains(&run));
+    assert!(run_cache.summary(&directory.join("missing.csv")).is_none());
+
+    // Saved over
+    save_run(&run, 40);
+    File::options()
+        .write(true)
+        .open(&run)
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(1))
+        .unwrap();
+    assert_eq!(run_cache.summary(&run).unwrap().runtime, 40);
+}
+
+#[test]
+fn opened_runs_are_kept_in_memory() {
+    let directory = run_directory("opened");
+    let mut run_cache = RunCache::new();
+    let runs = (0..=RUN_CACHE_CAPACITY)
+        .map(|idx| {
+            let run = directory.join(format!("{idx}.csv"));
+            save_run(&run, idx + 1);
+            run
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(run_cache.statuses(&runs[0]).unwrap().len(), 1);
+    assert!(run_cache.contains(&runs[0]));
+    for run in runs.iter().skip(1) {
+        run_cache.statuses(run).unwrap();
+    }
+    // The first one opened is the first to go
+    assert!(!run_cache.contains(&runs[0]));
+    assert!(run_cache.contains(&runs[RUN_CACHE_CAPACITY]));
+
+    // Opening one again keeps it around the longest
+    run_cache.statuses(&runs[1]).unwrap();
+    run_cache.statuses(&runs[0]).unwrap();
+    assert!(run_cache.contains(&runs[1]));
+    assert!(!run_cache.contains(&runs[2]));
+
+    assert!(run_cache.statuses(&directory.join("missing.csv")).is_err());
+}
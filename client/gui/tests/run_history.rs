@@ -0,0 +1,154 @@
+/*!
+ * Tests for keeping runs around to look back on and compare
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+mod common;
+use bindings::{Command, Event, MetaData, PingResponse, TransitMode, TransitType};
+use common::status;
+use gui::{events::*, run_history::*, shared::*, status_history::*};
+use std::{env::temp_dir, fs::create_dir_all};
+
+/***** Helper functions *****/
+
+/// Run data for a run that went `distance` centimeters in `runtime` seconds
+fn run_data(runtime: usize, distance: f64) -> RunData {
+    RunData {
+        status_responses: vec![status(runtime as f64, distance, 10.0)].into(),
+        ..Default::default()
+    }
+}
+
+/***** Tests *****/
+
+#[test]
+fn runs_are_archived_with_what_came_in() {
+    let mut history = RunHistory::new();
+    let mut data = run_data(5, 100.0);
+    data.parameters = Some(RunParameters {
+        distance: 100.0,
+        reverse_braking: false,
+        auto_stop_percentage: Some(105.0),
+        max_velocity: None,
+    });
+    data.ping_status_response = Some((
+        Box::new(Event {
+            command: Command::Ping,
+            transit_mode: TransitMode::ServerToClientResponse,
+            transit_type: TransitType::Response,
+            value: PingResponse { sent_time: 10.0 },
+            metadata: MetaData {
+                time: 10.01,
+                sequence: None,
+                frame: None,
+                echo: None,
+                client: None,
+            },
+        }),
+        10.025,
+    ));
+
+    assert_eq!(history.archive(&mut data), Some(0));
+    assert!(data.status_responses.is_empty());
+    assert!(data.parameters.is_none());
+    assert!(data.ping_status_response.is_none());
+
+    let run = &history.runs[0];
+    assert_eq!(run.name, "Run 1");
+    assert_eq!(run.parameters.unwrap().auto_stop_percentage, Some(105.0));
+    assert_eq!(run.summary().unwrap().distance, 100.0);
+    assert!((run.latency().unwrap() - 25.0).abs() < 1e-6);
+
+    // Nothing came in, so there's nothing to keep
+    assert_eq!(history.archive(&mut RunData::default()), None);
+    assert_eq!(history.archive(&mut run_data(3, 50.0)), Some(1));
+    assert_eq!(history.runs[1].name, "Run 2");
+}
+
+#[test]
+fn long_runs_are_archived_with_every_status() {
+    let directory = temp_dir().join(format!("gui-run-history-spill-{}", std::process::id()));
+    create_dir_all(&directory).unwrap();
+    let spill_path = directory.join(SPILL_FILE_NAME);
+    let mut data = RunData {
+        status_responses: StatusHistory::new(MIN_STATUS_HISTORY),
+        ..Default::default()
+    };
+    data.status_responses.spill_path = Some(spill_path.clone());
+    for runtime in 0..1_000 {
+        data.status_responses
+            .push(status(runtime as f64, runtime as f64, 10.0));
+    }
+    assert!(data.status_responses.downsampled());
+
+    let mut history = RunHistory::new();
+    history.archive(&mut data);
+    assert_eq!(
+        history.runs[0]
+            .statuses
+            .iter()
+            .map(|status| status.value.runtime)
+            .collect::<Vec<_>>(),
+        (0..1_000).collect::<Vec<_>>()
+    );
+    assert!(data.status_responses.is_empty());
+    assert!(!spill_path.exists());
+}
+
+#[test]
+fn the_oldest_runs_are_dropped_once_full() {
+    let mut history = RunHistory::new();
+    for number in 0..MAX_ARCHIVED_RUNS + 2 {
+        history.archive(&mut run_data(number, 10.0));
+    }
+    assert_eq!(history.runs.len(), MAX_ARCHIVED_RUNS);
+    assert_eq!(history.runs[0].name, "Run 3");
+    // Named by how many were ever archived, so names aren't reused
+    assert_eq!(
+        history.runs.last().unwrap().name,
+        format!("Run {}", MAX_ARCHIVED_RUNS + 2)
+    );
+}
+
+#[test]
+fn removing_a_run_keeps_the_selection() {
+    let mut history = RunHistory::new();
+    for number in 0..4 {
+        history.archive(&mut run_data(number, 10.0));
+    }
+    history.selected = Some(3);
+    history.comparison = Some([3, 1]);
+
+    history.remove(0);
+    assert_eq!(history.selected, Some(2));
+    assert_eq!(history.comparison, Some([2, 0]));
+    assert_eq!(history.runs[2].name, "Run 4");
+
+    // Removing a compared run ends the comparison
+    history.remove(0);
+    assert_eq!(history.selected, Some(1));
+    assert_eq!(history.comparison, None);
+    history.remove(1);
+    assert_eq!(history.selected, None);
+}
+
+#[test]
+fn compared_runs_share_a_scale() {
+    let short = [status(1.0, 10.0, 10.0), status(2.0, 30.0, 20.0)];
+    let long = [
+        status(1.0, 5.0, 5.0),
+        status(4.0, 20.0, 5.0),
+        status(8.0, 60.0, 10.0),
+    ];
+    let comparison = compare(&short, &long);
+
+    // Both runs in full, not just the last stretch
+    assert_eq!(comparison.runs[0].distance, [[1.0, 10.0], [2.0, 30.0]]);
+    assert_eq!(comparison.runs[1].distance.len(), 3);
+    assert_eq!(comparison.end, 8.0);
+    assert_eq!(comparison.top, 60.0);
+
+    assert_eq!(compare(&[], &[]), Comparison::default());
+}
@@ -0,0 +1,96 @@
+/*!
+ * Tests for sharing the run data as snapshots
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+mod common;
+use bindings::*;
+use gui::{annotations::Annotation, events::RunData, run_snapshot::*};
+use std::{sync::Arc, thread};
+
+/***** Helper functions *****/
+
+fn status(runtime: usize) -> Event<StatusResponse> {
+    common::status(runtime as f64, runtime as f64 * 10.0, 10.0)
+}
+
+/***** Tests *****/
+
+#[test]
+fn snapshots_being_read_never_change() {
+    let shared = SharedRunData::default();
+    let mut run_data = RunData {
+        running: true,
+        ..Default::default()
+    };
+    run_data.status_responses.push(status(0));
+    shared.publish(&run_data);
+
+    let held = shared.load();
+    run_data.status_responses.push(status(1));
+    run_data.running = false;
+    shared.publish(&run_data);
+
+    assert_eq!(held.statuses.len(), 1);
+    assert!(held.running);
+    let latest = shared.load();
+    assert_eq!(latest.statuses.len(), 2);
+    assert!(!latest.running);
+    assert_eq!(latest.revision, held.revision + 1);
+}
+
+#[test]
+fn unchanged_statuses_are_shared_between_snapshots() {
+    let shared = SharedRunData::default();
+    let mut run_data = RunData::default();
+    run_data.status_responses.push(status(0));
+    let first = shared.publish(&run_data);
+
+    run_data.annotations.push(Annotation::Marker {
+        time: 0.5,
+        label: "Bump".to_owned(),
+    });
+    let second = shared.publish(&run_data);
+    assert!(Arc::ptr_eq(&first.statuses, &second.statuses));
+    assert_eq!(second.annotations.len(), 1);
+
+    run_data.status_responses.push(status(1));
+    let third = shared.publish(&run_data);
+    assert!(!Arc::ptr_eq(&second.statuses, &third.statuses));
+    assert!(Arc::ptr_eq(&second.annotations, &third.annotations));
+
+    // Cleared and filled back up the same is still new
+    run_data
+        .status_responses
+        .replace(vec![status(0), status(1)]);
+    let fourth = shared.publish(&run_data);
+    assert!(!Arc::ptr_eq(&third.statuses, &fourth.statuses));
+}
+
+#[test]
+fn readers_on_other_threads_see_whole_snapshots() {
+    let shared = SharedRunData::default();
+    let reader = {
+        let shared = shared.clone();
+        thread::spawn(move || {
+            let mut last_revision = 0;
+            for _ in 0..1000 {
+                let snapshot = shared.load();
+                // Statuses are only ever added, one per snapshot
+                assert_eq!(snapshot.statuses.len() as u64, snapshot.revision);
+                assert!(snapshot.revision >= last_revision);
+                last_revision = snapshot.revision;
+            }
+        })
+    };
+
+    let mut run_data = RunData::default();
+    for runtime in 0..200 {
+        run_data.status_responses.push(status(runtime));
+        shared.publish(&run_data);
+    }
+    reader.join().unwrap();
+    assert_eq!(shared.load().statuses.len(), 200);
+}
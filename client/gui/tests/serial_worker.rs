@@ -0,0 +1,105 @@
+/*!
+ * Tests for reading from and writing to the car's connection on a worker
+ * thread
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{ClientError, LoopbackTransport, Transport};
+use gui::serial_worker::*;
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+/***** Helper functions *****/
+
+/// A connection that takes a second to write anything, like a port the other
+/// end stopped reading from
+struct StalledTransport(LoopbackTransport);
+impl Transport for StalledTransport {
+    fn read_line(&mut self) -> Result<Option<String>, ClientError> {
+        self.0.read_line()
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), ClientError> {
+        sleep(Duration::from_secs(1));
+        self.0.write_line(line)
+    }
+
+    fn connected(&self) -> bool {
+        self.0.connected()
+    }
+
+    fn try_clone(&self) -> Result<Box<dyn Transport>, ClientError> {
+        self.0.try_clone()
+    }
+}
+
+/// Read from a transport until something comes in, or it's been too long
+fn read_eventually(transport: &mut dyn Transport) -> Result<Option<String>, ClientError> {
+    let start = Instant::now();
+    loop {
+        match transport.read_line() {
+            Ok(None) if start.elapsed() < Duration::from_secs(2) => {
+                sleep(Duration::from_millis(5))
+            }
+            result => return result,
+        }
+    }
+}
+
+/***** Tests *****/
+
+#[test]
+fn lines_go_both_ways() {
+    let (transport, mut car) = LoopbackTransport::pair();
+    let mut worker = SerialWorker::spawn(Box::new(transport));
+
+    worker.write_line("?ping$null$1.0$").unwrap();
+    assert_eq!(
+        read_eventually(&mut car).unwrap().as_deref(),
+        Some("?ping$null$1.0$")
+    );
+    car.write_line("~ping$1$2$").unwrap();
+    car.write_line("~ping$3$4$").unwrap();
+    assert_eq!(
+        read_eventually(&mut worker).unwrap().as_deref(),
+        Some("~ping$1$2$")
+    );
+    assert_eq!(
+        read_eventually(&mut worker).unwrap().as_deref(),
+        Some("~ping$3$4$")
+    );
+    assert!(worker.connected());
+}
+
+#[test]
+fn stalled_port_never_waits() {
+    let (transport, mut car) = LoopbackTransport::pair();
+    let mut worker = SerialWorker::spawn(Box::new(StalledTransport(transport)));
+
+    let start = Instant::now();
+    for _ in 0..3 {
+        worker.write_line("?ping$null$1.0$").unwrap();
+        assert_eq!(worker.read_line().unwrap(), None);
+    }
+    assert!(start.elapsed() < Duration::from_millis(500));
+
+    // Still written in the end
+    assert!(read_eventually(&mut car).unwrap().is_some());
+}
+
+#[test]
+fn losing_the_connection_is_read() {
+    let (transport, car) = LoopbackTransport::pair();
+    let mut worker = SerialWorker::spawn(Box::new(transport));
+    drop(car);
+
+    assert!(read_eventually(&mut worker).is_err());
+    assert!(!worker.connected());
+    // Nothing can be written once the worker has stopped
+    assert!(worker.write_line("?ping$null$1.0$").is_err());
+    assert!(worker.read_line().is_err());
+}
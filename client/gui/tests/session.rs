@@ -0,0 +1,104 @@
+/*!
+ * Tests for naming runs after the session
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::ClientError;
+use gui::{data_directory::*, export::RunFormat, session::*};
+use std::{
+    env::temp_dir,
+    fs::{create_dir_all, remove_file, write},
+    path::PathBuf,
+};
+
+/***** Tests *****/
+
+#[test]
+fn runs_are_named_after_the_session() {
+    let mut session = Session {
+        runs: 3,
+        ..Default::default()
+    };
+    assert_eq!(
+        session.run_name("2026-10-16", 500.0),
+        "2026-10-16_500cm_run03"
+    );
+
+    session.venue = "  Main Gym (north)! ".to_owned();
+    assert_eq!(
+        session.run_name("2026-10-16", 1234.6),
+        "2026-10-16_main-gym-north_1235cm_run03"
+    );
+}
+
+#[test]
+fn suggested_names_are_not_taken_for_automatically_saved_runs() {
+    let directory = temp_dir().join(format!("gui-session-names-{}", std::process::id()));
+    create_dir_all(&directory).unwrap();
+    let data_directory = DataDirectory::at(directory.clone()).unwrap();
+    let session = Session {
+        runs: 1,
+        ..Default::default()
+    };
+    let path = directory.join(format!("{}.csv", session.run_name("2026-10-16", 500.0)));
+    RunFormat::CSV.write(&path, &[]).unwrap();
+
+    // So retention never deletes them
+    assert!(!data_directory.runs().contains(&path));
+    let _ = remove_file(path);
+}
+
+#[test]
+fn venue_directories_are_loaded_back() {
+    let directory = temp_dir().join(format!("gui-session-{}", std::process::id()));
+    let path = directory.join("round-trip").join("session.toml");
+    let _ = remove_file(&path);
+
+    let (mut session, error) = Session::load(Some(path.clone()));
+    assert!(error.is_none());
+    assert_eq!(session.directory(), None);
+
+    // Nowhere to remember it under without a venue
+    session.remember_directory(PathBuf::from("/runs")).unwrap();
+    assert!(session.directories.is_empty());
+
+    session.venue = "gym".to_owned();
+    session
+        .remember_directory(PathBuf::from("/runs/gym"))
+        .unwrap();
+    session.venue = "track".to_owned();
+    session
+        .remember_directory(PathBuf::from("/runs/track"))
+        .unwrap();
+    session.runs = 4;
+
+    let (mut loaded, error) = Session::load(Some(path));
+    assert!(error.is_none());
+    assert_eq!(loaded.venue, "track");
+    // Runs are counted from every start of the GUI
+    assert_eq!(loaded.runs, 0);
+    assert_eq!(
+        loaded.directory(),
+        Some(PathBuf::from("/runs/track").as_path())
+    );
+    loaded.venue = "gym".to_owned();
+    assert_eq!(
+        loaded.directory(),
+        Some(PathBuf::from("/runs/gym").as_path())
+    );
+}
+
+#[test]
+fn bad_session_file_starts_a_new_session() {
+    let directory = temp_dir().join(format!("gui-session-bad-{}", std::process::id()));
+    create_dir_all(&directory).unwrap();
+    let path = directory.join("session.toml");
+    write(&path, "venue = 3").unwrap();
+
+    let (session, error) = Session::load(Some(path.clone()));
+    assert!(matches!(error, Some(ClientError::Session(_))));
+    assert_eq!(session.venue, "");
+    assert_eq!(session.path, Some(path));
+}
@@ -0,0 +1,204 @@
+/*!
+ * Tests for logging each session to disk and looking back on previous ones
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::{ClientError, ErrorResponse, ServerError};
+use gui::{journal::*, recovery::Recovery, session_log::*, shared::*, stop_reason::*, ErrorData};
+use std::{
+    env::temp_dir,
+    fs::{create_dir_all, remove_dir_all, write},
+    path::PathBuf,
+};
+
+/***** Helper functions *****/
+
+/// An empty directory for session logs, just for one test
+fn log_directory(test: &str) -> PathBuf {
+    let directory = temp_dir().join(format!("gui-session-log-{test}-{}", std::process::id()));
+    let _ = remove_dir_all(&directory);
+    create_dir_all(&directory).unwrap();
+    directory
+}
+
+fn metadata() -> SessionMetadata {
+    SessionMetadata {
+        version: "0.1.0".to_owned(),
+        venue: "gym".to_owned(),
+        port: Some("/dev/ttyUSB0".to_owned()),
+    }
+}
+
+fn describe(entries: &[SessionLogEntry]) -> Vec<String> {
+    entries.iter().map(|entry| entry.event.describe()).collect()
+}
+
+/***** Tests *****/
+
+#[test]
+fn errors_actions_and_connections_are_logged_once() {
+    let directory = log_directory("observe");
+    let mut session_log = SessionLogger::open(&directory, metadata()).unwrap();
+
+    let mut errors = vec![ErrorData::new(ClientError::from(&ErrorResponse {
+        error_variant: ServerError::FailedToStartAlreadyStarted as u8,
+        message: "Already started".to_owned(),
+    }))];
+    let mut journal = Journal::default();
+    journal.record(JournalEntryKind::Action(UserAction::Start {
+        distance: 500.0,
+        reverse_braking: false,
+        wheel_circumference: None,
+    }));
+    // Not worth looking back on
+    journal.record(JournalEntryKind::Sent("~PING${}".to_owned()));
+    session_log.observe(&errors, &journal, true, false).unwrap();
+    // Nothing new
+    session_log.observe(&errors, &journal, true, false).unwrap();
+
+    // A dismissed error doesn't make the next one logged again
+    errors.remove(0);
+    errors.push(ErrorData::new(ClientError::Run("Lost the car".to_owned())));
+    journal.record(JournalEntryKind::Action(UserAction::EmergencyStop));
+    session_log.observe(&errors, &journal, false, true).unwrap();
+    session_log.observe(&errors, &journal, true, false).unwrap();
+
+    let entries = read_session_log(&session_log.path).unwrap();
+    assert_eq!(
+        describe(&entries),
+        [
+            "Opened version 0.1.0 at gym on /dev/ttyUSB0",
+            "Error: Failed to start - Already started: Already started",
+            "Start 500cm (reverse braking: false)",
+            "Serial port connected",
+            "Error: There was an error with running: Lost the car",
+            "Emergency stop",
+            "Serial port disconnected",
+            "Bridge connected to the car",
+            "Serial port connected",
+            "Bridge lost the car",
+        ]
+    );
+    assert!(matches!(
+        entries[1].event,
+        SessionEvent::Error {
+            recovery: Some(Recovery::StopAndStartAgain),
+            ..
+        }
+    ));
+}
+
+#[cfg(feature = "ui")]
+#[test]
+fn previous_sessions_are_newest_first_without_the_current_one() {
+    let directory = log_directory("previous");
+    write(directory.join("2026-10-14_09-00-00.jsonl"), "").unwrap();
+    write(directory.join("2026-10-15_09-00-00.jsonl"), "").unwrap();
+    // Not a session log
+    write(directory.join("notes.txt"), "").unwrap();
+    let session_log = SessionLogger::open(&directory, metadata()).unwrap();
+
+    let viewer = SessionLogViewer::new(&directory, Some(&session_log.path));
+    assert_eq!(
+        viewer.session_logs,
+        [
+            directory.join("2026-10-15_09-00-00.jsonl"),
+            directory.join("2026-10-14_09-00-00.jsonl"),
+        ]
+    );
+}
+
+#[test]
+fn only_the_latest_sessions_are_kept() {
+    let directory = log_directory("retention");
+    for day in 0..RETAINED_SESSION_LOGS {
+        write(directory.join(format!("2020-01-01_{day:04}.jsonl")), "").unwrap();
+    }
+    let session_log = SessionLogger::open(&directory, metadata()).unwrap();
+
+    let session_logs = session_logs(&directory);
+    assert_eq!(session_logs.len(), RETAINED_SESSION_LOGS);
+    assert_eq!(session_logs[0], directory.join("2020-01-01_0001.jsonl"));
+    assert_eq!(session_logs.last(), Some(&session_log.path));
+}
+
+#[test]
+fn broken_lines_are_reported() {
+    let path = log_directory("broken").join("2026-10-16_09-00-00.jsonl");
+    write(
+        &path,
+        "{\"time\": 1.0, \"event\": {\"serial\": {\"connected\": true}}}\nnope\n",
+    )
+    .unwrap();
+
+    let Err(e) = read_session_log(&path) else {
+        panic!("Read a broken session log");
+    };
+    assert!(e.to_string().contains("Line 2"), "{e}");
+}
+
+#[test]
+fn runs_are_summed_up_from_the_session_log() {
+    let directory = log_directory("runs");
+    let mut session_log = SessionLogger::open(&directory, metadata()).unwrap();
+    let start = |distance| {
+        SessionEvent::Action(UserAction::Start {
+            distance,
+            reverse_braking: false,
+            wheel_circumference: None,
+        })
+    };
+    for (time, event) in [
+        (10.0, start(500.0)),
+        (20.0, SessionEvent::Finished { distance: 497.3 }),
+        // Stopping after it finished changes nothing
+        (21.0, SessionEvent::Action(UserAction::Stop)),
+        (30.0, start(300.0)),
+        (31.0, SessionEvent::Action(UserAction::Stop)),
+        (
+            35.0,
+            SessionEvent::Action(UserAction::GiveStopReason(StopReason {
+                kind: StopReasonKind::Obstacle,
+                note: "a backpack".to_owned(),
+            })),
+        ),
+        (40.0, start(300.0)),
+    ] {
+        session_log.log(time, event).unwrap();
+    }
+
+    let entries = read_session_log(&session_log.path).unwrap();
+    let runs = session_runs(&entries)
+        .into_iter()
+        .map(|run| {
+            (
+                run.target,
+                run.result.describe(),
+                run.stop_reason.map(|reason| reason.describe()),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        runs,
+        [
+            (500.0, "Finished at 497.3cm".to_owned(), None),
+            (
+                300.0,
+                "Stopped".to_owned(),
+                Some("Obstacle (a backpack)".to_owned())
+            ),
+            (300.0, "Unknown".to_owned(), None),
+        ]
+    );
+
+    let mut csv = vec![];
+    write_session_csv(&entries[1..3], &mut csv).unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "time,event,description\n\
+         10,action,Start 500cm (reverse braking: false)\n\
+         20,finished,Finished at 497.3cm\n"
+    );
+}
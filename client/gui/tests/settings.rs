@@ -0,0 +1,99 @@
+/*!
+ * Tests for carrying the settings over to another computer
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::ClientError;
+use gui::{config::*, export::RunFormat, settings::*};
+use std::{env::temp_dir, fs::create_dir_all};
+use toml::Value;
+
+/***** Helper functions *****/
+
+/// The message of a settings error, panicking on anything else
+fn settings_error(result: Result<SettingsFile, ClientError>) -> String {
+    match result {
+        Err(ClientError::Settings(message)) => message,
+        other => panic!("Expected a settings error, got {other:?}"),
+    }
+}
+
+/***** Tests *****/
+
+#[test]
+fn exported_settings_are_read_back() {
+    let directory = temp_dir().join(format!("gui-settings-{}", std::process::id()));
+    create_dir_all(&directory).unwrap();
+    let path = directory.join("round-trip.toml");
+
+    let mut settings = SettingsFile::new(Config::default(), RunSettings::default());
+    settings.config.serial.port = Some("/dev/ttyACM0".to_owned());
+    settings.config.car.wheel_diameter = 6.4;
+    settings.run.auto_stop = true;
+    settings.run.export_format = RunFormat::JSONLines;
+    settings.run.bluetooth_address = Some("00:60:37:E9:0B:6F".to_owned());
+    settings.write(&path).unwrap();
+
+    assert_eq!(SettingsFile::read(&path).unwrap(), settings);
+    assert!(SettingsFile::read(&directory.join("missing.toml")).is_err());
+}
+
+#[test]
+fn settings_are_checked_before_importing() {
+    let settings = SettingsFile::parse("version = 1\n[run]\nauto_stop = true").unwrap();
+    assert!(settings.run.auto_stop);
+    assert_eq!(settings.config, Config::default());
+
+    // A config file isn't a settings file
+    let message = settings_error(SettingsFile::parse("[car]\nwheel_diameter = 7.0"));
+    assert!(message.contains("No version"), "{message}");
+    let message = settings_error(SettingsFile::parse(
+        "version = 2\n[profiles.practice]\nmax_velocity = 20.0",
+    ));
+    assert!(message.contains("newer"), "{message}");
+    let message = settings_error(SettingsFile::parse("version = 1\n[run]\nauto_stp = true"));
+    assert!(message.contains("auto_stp"), "{message}");
+    let message = settings_error(SettingsFile::parse(
+        "version = 1\n[config.car]\nnumber_of_magnets = 0",
+    ));
+    assert!(message.contains("car.number_of_magnets"), "{message}");
+}
+
+#[test]
+fn conflicts_are_merged_as_chosen() {
+    let current = SettingsFile::new(Config::default(), RunSettings::default());
+    let mut imported = current.clone();
+    imported.config.car.wheel_diameter = 7.0;
+    imported.run.max_velocity = 30.0;
+    imported.run.bluetooth_address = Some("00:60:37:E9:0B:6F".to_owned());
+
+    let conflicts = current.conflicts(&imported).unwrap();
+    assert_eq!(
+        conflicts
+            .iter()
+            .map(|conflict| conflict.key.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "config.car.wheel_diameter",
+            "run.bluetooth_address",
+            "run.max_velocity"
+        ]
+    );
+    assert_eq!(conflicts[1].current, None);
+    assert_eq!(
+        conflicts[1].imported,
+        Some(Value::String("00:60:37:E9:0B:6F".to_owned()))
+    );
+    assert!(current.conflicts(&current).unwrap().is_empty());
+
+    // Keep the current speed limit and not having a Bluetooth address
+    let merged = current
+        .merge(&imported, &[&conflicts[1], &conflicts[2]])
+        .unwrap();
+    assert_eq!(merged.config.car.wheel_diameter, 7.0);
+    assert_eq!(merged.run.bluetooth_address, None);
+    assert_eq!(merged.run.max_velocity, current.run.max_velocity);
+    assert_eq!(current.merge(&imported, &[]).unwrap(), imported);
+}
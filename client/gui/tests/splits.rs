@@ -0,0 +1,66 @@
+/*!
+ * Tests for split times at distance markers
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+mod common;
+use bindings::{Event, StatusResponse};
+use gui::splits::*;
+
+/***** Helper functions *****/
+
+/// A status `time` seconds after a run that started at 1000
+fn status(time: f64, distance: f64) -> Event<StatusResponse> {
+    common::status(time, distance, 0.0)
+}
+
+/***** Tests *****/
+
+#[test]
+fn splits_are_interpolated() {
+    // 50cm/s for 3 seconds, then backing up over the 100cm marker
+    let statuses = [
+        status(1.0, 50.0),
+        status(3.0, 150.0),
+        status(4.0, 80.0),
+        status(5.0, 110.0),
+    ];
+    let splits = split_times(&statuses, 100.0);
+    assert_eq!(splits.len(), 1);
+    assert_eq!(splits[0].marker, 100.0);
+    assert!((splits[0].time - 2.0).abs() < 1e-9);
+    assert!((splits[0].speed - 50.0).abs() < 1e-9);
+
+    // Passing more than one marker between statuses
+    let splits = split_times(&statuses, 25.0);
+    let times = splits.iter().map(|split| split.time).collect::<Vec<_>>();
+    assert_eq!(times, [0.5, 1.0, 1.5, 2.0, 2.5, 3.0]);
+
+    assert!(split_times(&statuses, 0.0).is_empty());
+    assert!(split_times(&[], 100.0).is_empty());
+}
+
+#[test]
+fn splits_compare_to_other_runs() {
+    let faster = split_times(&[status(2.0, 100.0), status(4.0, 200.0)], 100.0);
+    let slower = split_times(&[status(3.0, 100.0)], 100.0);
+    assert_eq!(split_deltas(&faster, &slower), [-1.0]);
+    assert_eq!(split_deltas(&slower, &faster), [1.0]);
+}
+
+#[test]
+fn baseline_is_compared_at_the_same_time() {
+    let baseline = [status(1.0, 50.0), status(3.0, 150.0)];
+    assert_eq!(distance_at(&baseline, 2.0), Some(100.0));
+    assert_eq!(distance_at(&baseline, 0.5), Some(25.0));
+    // Stays where it ended
+    assert_eq!(distance_at(&baseline, 10.0), Some(150.0));
+    assert_eq!(distance_at(&[], 1.0), None);
+
+    assert_eq!(baseline_delta(&[status(2.0, 120.0)], &baseline), Some(20.0));
+    assert_eq!(baseline_delta(&[status(2.0, 90.0)], &baseline), Some(-10.0));
+    assert_eq!(baseline_delta(&[], &baseline), None);
+    assert_eq!(baseline_delta(&[status(2.0, 90.0)], &[]), None);
+}
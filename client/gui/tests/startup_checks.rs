@@ -0,0 +1,188 @@
+/*!
+ * Tests for the startup checks, against a made up computer
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+#![cfg(unix)]
+
+/***** Setup *****/
+// Imports
+use gui::startup_checks::*;
+use std::{
+    env::temp_dir,
+    fs::{create_dir_all, metadata, remove_dir_all, remove_file, set_permissions, write},
+    os::unix::fs::{MetadataExt, PermissionsExt},
+    path::{Path, PathBuf},
+};
+
+// Constants
+/// Of the made up user, who isn't whoever runs the tests
+const UID: u32 = 4242;
+
+/***** Helper functions *****/
+
+/// An empty computer for one test, with the user `racer` in no groups and a
+/// serial device only the `dialout` group can open
+fn computer(test: &str) -> PathBuf {
+    let root = temp_dir().join(format!("gui-startup-{test}-{}", std::process::id()));
+    let _ = remove_dir_all(&root);
+    for directory in [
+        "proc/self",
+        "dev",
+        "etc",
+        "sys/class/bluetooth",
+        "sys/class/rfkill",
+    ] {
+        create_dir_all(root.join(directory)).unwrap();
+    }
+
+    let device = root.join("dev/ttyACM0");
+    write(&device, "").unwrap();
+    set_permissions(&device, PermissionsExt::from_mode(0o660)).unwrap();
+    write(
+        root.join("etc/passwd"),
+        format!("root:x:0:0::/root:/bin/sh\nracer:x:{UID}:{UID}::/home/racer:/bin/sh\n"),
+    )
+    .unwrap();
+    set_groups(&root, "", "");
+    root
+}
+
+/// Who is in the device's group, and which groups the user has right now
+fn set_groups(root: &Path, members: &str, groups: &str) {
+    let device_gid = metadata(root.join("dev/ttyACM0")).unwrap().gid();
+    write(
+        root.join("etc/group"),
+        format!("racer:x:{UID}:\ndialout:x:{device_gid}:{members}\n"),
+    )
+    .unwrap();
+    write(
+        root.join("proc/self/status"),
+        format!(
+            "Name:\tgui\nUid:\t{UID}\t{UID}\t{UID}\t{UID}\n\
+             Gid:\t{UID}\t{UID}\t{UID}\t{UID}\nGroups:\t{groups}\n"
+        ),
+    )
+    .unwrap();
+}
+
+fn checks(root: &Path, serial: bool, port: Option<&str>, bluetooth: bool) -> StartupChecks {
+    StartupChecks {
+        root: root.to_path_buf(),
+        serial,
+        port: port.map(str::to_owned),
+        bluetooth,
+    }
+}
+
+/***** Tests *****/
+
+#[test]
+fn missing_serial_ports_are_explained() {
+    let root = computer("missing");
+    set_groups(
+        &root,
+        "",
+        &metadata(root.join("dev/ttyACM0"))
+            .unwrap()
+            .gid()
+            .to_string(),
+    );
+
+    let problems = checks(&root, true, Some("/dev/ttyACM1"), false).run();
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].problem, "/dev/ttyACM1 doesn't exist");
+    assert!(
+        problems[0].fix.contains("(/dev/ttyACM0)"),
+        "{}",
+        problems[0].fix
+    );
+    assert!(checks(&root, true, Some("/dev/ttyACM0"), false)
+        .run()
+        .is_empty());
+
+    remove_file(root.join("dev/ttyACM0")).unwrap();
+    let problems = checks(&root, true, None, false).run();
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].problem, "No USB serial devices are plugged in");
+}
+
+#[test]
+fn serial_permissions_point_at_the_group() {
+    let root = computer("permissions");
+    let device_gid = metadata(root.join("dev/ttyACM0"))
+        .unwrap()
+        .gid()
+        .to_string();
+
+    let problems = checks(&root, true, None, false).run();
+    assert_eq!(problems.len(), 1);
+    assert_eq!(
+        problems[0].problem,
+        "/dev/ttyACM0 needs the dialout group, which you aren't in"
+    );
+    assert!(
+        problems[0].fix.contains("sudo usermod -aG dialout racer"),
+        "{}",
+        problems[0].fix
+    );
+
+    // Added, but still logged in from before
+    set_groups(&root, "someone,racer", "");
+    let problems = checks(&root, true, None, false).run();
+    assert_eq!(problems.len(), 1);
+    assert!(
+        problems[0].problem.contains("haven't logged in again"),
+        "{}",
+        problems[0].problem
+    );
+
+    set_groups(&root, "someone,racer", &format!("27 {device_gid}"));
+    assert!(checks(&root, true, None, false).run().is_empty());
+}
+
+#[test]
+fn bluetooth_problems_are_found() {
+    let root = computer("bluetooth");
+    let rfkill = root.join("sys/class/rfkill/rfkill0");
+    create_dir_all(&rfkill).unwrap();
+    write(rfkill.join("type"), "bluetooth\n").unwrap();
+    write(rfkill.join("soft"), "1\n").unwrap();
+    write(rfkill.join("hard"), "0\n").unwrap();
+
+    // The serial port isn't checked when going through the bridge
+    let problems = checks(&root, false, None, true)
+        .run()
+        .into_iter()
+        .map(|problem| problem.problem)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        problems,
+        vec![
+            "No Bluetooth adapter was found",
+            "Bluetooth is blocked by rfkill",
+            "BlueZ (bluetoothd) isn't running"
+        ]
+    );
+
+    write(rfkill.join("hard"), "1\n").unwrap();
+    assert!(checks(&root, false, None, true)
+        .run()
+        .iter()
+        .any(|problem| problem.problem.contains("hardware switch")));
+
+    create_dir_all(root.join("sys/class/bluetooth/hci0")).unwrap();
+    write(rfkill.join("soft"), "0\n").unwrap();
+    write(rfkill.join("hard"), "0\n").unwrap();
+    create_dir_all(root.join("proc/812")).unwrap();
+    write(root.join("proc/812/comm"), "bluetoothd\n").unwrap();
+    assert!(checks(&root, false, None, true).run().is_empty());
+}
+
+#[test]
+fn nothing_is_checked_off_linux() {
+    let root = computer("off-linux");
+    remove_file(root.join("proc/self/status")).unwrap();
+    assert!(checks(&root, true, Some("/dev/ttyACM1"), true)
+        .run()
+        .is_empty());
+}
@@ -0,0 +1,103 @@
+/*!
+ * Tests for keeping a long run's statuses bounded in memory
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+mod common;
+use bindings::*;
+use gui::{shared::*, status_history::*};
+use std::{env::temp_dir, fs::create_dir_all, path::PathBuf};
+
+/***** Helper functions *****/
+
+fn status(runtime: usize) -> Event<StatusResponse> {
+    common::status(runtime as f64, runtime as f64 * 10.0, 10.0)
+}
+
+fn runtimes(statuses: &[Event<StatusResponse>]) -> Vec<usize> {
+    statuses.iter().map(|status| status.value.runtime).collect()
+}
+
+/// A fresh directory for a test to spill into
+fn spill_directory(test: &str) -> PathBuf {
+    let directory = temp_dir().join(format!("gui-status-history-{test}-{}", std::process::id()));
+    create_dir_all(&directory).unwrap();
+    directory
+}
+
+/***** Tests *****/
+
+#[test]
+fn short_runs_are_kept_whole() {
+    let mut history = StatusHistory::new(MIN_STATUS_HISTORY);
+    for runtime in 0..MIN_STATUS_HISTORY {
+        history.push(status(runtime));
+    }
+
+    assert!(!history.downsampled());
+    assert_eq!(history.len(), MIN_STATUS_HISTORY);
+    assert_eq!(history.full().unwrap().len(), MIN_STATUS_HISTORY);
+}
+
+#[test]
+fn long_runs_are_downsampled_keeping_the_first_and_latest() {
+    let mut history = StatusHistory::new(MIN_STATUS_HISTORY);
+    for runtime in 0..10_000 {
+        history.push(status(runtime));
+        assert!(history.len() <= MIN_STATUS_HISTORY);
+        assert_eq!(history.last().unwrap().value.runtime, runtime);
+    }
+
+    assert!(history.downsampled());
+    let kept = runtimes(&history);
+    assert_eq!(kept[0], 0);
+    // Still spread over the whole run, in order
+    assert!(kept.len() > MIN_STATUS_HISTORY / 2);
+    assert!(kept.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn the_capacity_is_never_below_the_minimum() {
+    let mut history = StatusHistory::new(1);
+    for runtime in 0..MIN_STATUS_HISTORY {
+        history.push(status(runtime));
+    }
+
+    assert!(!history.downsampled());
+}
+
+#[test]
+fn every_status_is_read_back_from_the_spill() {
+    let spill_path = spill_directory("spill").join(SPILL_FILE_NAME);
+    let mut history = StatusHistory::new(MIN_STATUS_HISTORY);
+    history.spill_path = Some(spill_path.clone());
+    for runtime in 0..1_000 {
+        history.push(status(runtime));
+    }
+
+    assert!(history.downsampled());
+    assert!(history.spill_error.is_none());
+    assert_eq!(
+        runtimes(&history.full().unwrap()),
+        (0..1_000).collect::<Vec<_>>()
+    );
+
+    // Only there for the run that was going
+    history.clear();
+    assert!(history.is_empty());
+    assert!(!spill_path.exists());
+}
+
+#[test]
+fn failing_to_spill_keeps_the_downsampled_statuses() {
+    let mut history = StatusHistory::new(MIN_STATUS_HISTORY);
+    history.spill_path = Some(spill_directory("unspillable").join("missing/statuses.jsonl"));
+    for runtime in 0..1_000 {
+        history.push(status(runtime));
+    }
+
+    assert!(history.spill_error.take().is_some());
+    assert_eq!(runtimes(&history.full().unwrap()), runtimes(&history));
+}
@@ -0,0 +1,93 @@
+/*!
+ * Tests for following a run's steps from the journal
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use bindings::ClientStatus::{self, *};
+use gui::{journal::*, steps::*};
+
+/***** Helper functions *****/
+
+fn transition(time: f64, from: ClientStatus, to: ClientStatus) -> JournalEntry {
+    JournalEntry {
+        time,
+        kind: JournalEntryKind::Transition { from, to },
+    }
+}
+
+fn sent(time: f64, frame: &str) -> JournalEntry {
+    JournalEntry {
+        time,
+        kind: JournalEntryKind::Sent(frame.to_owned()),
+    }
+}
+
+/// A run that's gone as far as monitoring, with the ping sent twice
+fn monitoring() -> Vec<JournalEntry> {
+    vec![
+        transition(10.0, GatheringData, SendingPing),
+        transition(10.1, SendingPing, ReceivingPing),
+        sent(10.1, "?PING${\"time\": 10.1}"),
+        sent(11.1, "?PING${\"time\": 10.1}"),
+        transition(11.5, ReceivingPing, RequestingStaticStatus),
+        transition(11.6, RequestingStaticStatus, ReceivingStaticStatus),
+        sent(11.6, "?STATIC_STATUS${}"),
+        transition(12.0, ReceivingStaticStatus, RequestingStart),
+        transition(12.25, RequestingStart, ReceivingStatus),
+        sent(12.25, "?START${\"distance\": 500}"),
+        // Not a retry, as it's a new ping
+        sent(13.0, "?PING${\"time\": 13.0}"),
+    ]
+}
+
+/***** Tests *****/
+
+#[test]
+fn steps_follow_the_journal() {
+    let indicator = StepIndicator::from_journal(&monitoring(), &[]);
+    assert_eq!(indicator.current, Some(Step::Monitoring));
+    assert_eq!(indicator.elapsed(14.25), Some(2.0));
+
+    let ping = indicator.record(Step::Ping);
+    assert_eq!(ping.entered, Some(10.0));
+    assert_eq!(ping.left, Some(11.5));
+    assert_eq!(ping.retries, 1);
+    assert_eq!(indicator.record(Step::Monitoring).retries, 0);
+    assert_eq!(indicator.record(Step::Stop).entered, None);
+
+    assert_eq!(
+        StepIndicator::from_journal(&[], &[]),
+        StepIndicator::default()
+    );
+    assert_eq!(StepIndicator::default().elapsed(14.1), None);
+}
+
+#[test]
+fn errors_are_put_down_to_their_step() {
+    let indicator = StepIndicator::from_journal(&monitoring(), &[5.0, 11.0, 11.7, 11.8, 20.0]);
+    assert_eq!(indicator.record(Step::Ping).errors, 1);
+    assert_eq!(indicator.record(Step::StaticStatus).errors, 2);
+    assert_eq!(indicator.record(Step::Start).errors, 0);
+    assert_eq!(indicator.record(Step::Monitoring).errors, 1);
+}
+
+#[test]
+fn new_run_starts_the_steps_over() {
+    let mut entries = monitoring();
+    entries.extend([
+        transition(20.0, ReceivingStatus, RequestingStop),
+        transition(20.1, RequestingStop, Finished),
+        transition(30.0, Finished, SendingPing),
+        // The same frame as last run isn't a retry either
+        sent(30.0, "?PING${\"time\": 10.1}"),
+    ]);
+
+    let indicator = StepIndicator::from_journal(&entries, &[15.0]);
+    assert_eq!(indicator.current, Some(Step::Ping));
+    assert_eq!(indicator.record(Step::Ping).entered, Some(30.0));
+    assert_eq!(indicator.record(Step::Ping).retries, 0);
+    assert_eq!(indicator.record(Step::Monitoring), &StepRecord::default());
+    assert_eq!(indicator.record(Step::Finished).entered, None);
+}
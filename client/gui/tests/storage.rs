@@ -0,0 +1,134 @@
+/*!
+ * Tests for checking there's room to log runs and that the SD card they're
+ * logged to isn't failing, against a made up computer
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use gui::{annotations::annotations_path, data_directory::*, shared::*, storage::*};
+use std::{
+    env::temp_dir,
+    fs::{create_dir_all, remove_dir_all, write},
+    path::{Path, PathBuf},
+};
+
+/***** Helper functions *****/
+
+/// A Pi for one test, with its SD card mounted at `/` as Raspberry Pi OS does
+fn computer(test: &str, mount_options: &str) -> PathBuf {
+    let root = temp_dir().join(format!("gui-storage-{test}-{}", std::process::id()));
+    let _ = remove_dir_all(&root);
+    for directory in [
+        "proc",
+        "sys/block/mmcblk0/mmcblk0p2",
+        "sys/block/mmcblk0/device",
+        "sys/fs/ext4/mmcblk0p2",
+    ] {
+        create_dir_all(root.join(directory)).unwrap();
+    }
+    write(
+        root.join("proc/mounts"),
+        format!(
+            "/dev/root / ext4 {mount_options} 0 0\n\
+             /dev/mmcblk0p1 /boot vfat rw,relatime 0 0\n\
+             tmpfs /tmp tmpfs rw,nosuid 0 0\n"
+        ),
+    )
+    .unwrap();
+    write(root.join("sys/fs/ext4/mmcblk0p2/errors_count"), "0\n").unwrap();
+    root
+}
+
+fn checks(root: &Path, directory: &str) -> StorageChecks {
+    StorageChecks {
+        root: root.to_owned(),
+        directory: PathBuf::from(directory),
+    }
+}
+
+/// A data directory with runs of 1000 bytes each, oldest first
+fn data_directory(test: &str, runs: usize) -> (DataDirectory, Vec<PathBuf>) {
+    let directory = temp_dir().join(format!("gui-storage-runs-{test}-{}", std::process::id()));
+    let _ = remove_dir_all(&directory);
+    let data_directory = DataDirectory::at(directory.clone()).unwrap();
+    let runs = (1..=runs)
+        .map(|run_number| {
+            let run = directory.join(format!("2026-10-16_run{run_number:02}_500cm.csv"));
+            write(&run, vec![b'0'; 1_000]).unwrap();
+            run
+        })
+        .collect();
+    (data_directory, runs)
+}
+
+/***** Tests *****/
+
+#[test]
+fn a_healthy_card_has_no_problems() {
+    let root = computer("healthy", "rw,noatime");
+    write(
+        root.join("sys/block/mmcblk0/device/life_time"),
+        "0x02 0x01\n",
+    )
+    .unwrap();
+
+    assert_eq!(checks(&root, "/home/pi/runs").run(), Vec::<String>::new());
+}
+
+#[test]
+fn a_card_remounted_read_only_is_a_problem() {
+    let root = computer("read-only", "ro,noatime");
+
+    let problems = checks(&root, "/home/pi/runs").run();
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].contains("read-only"));
+    // Only the mount the directory is on counts
+    assert!(checks(&root, "/tmp/runs").run().is_empty());
+}
+
+#[test]
+fn filesystem_errors_and_wear_are_problems() {
+    let root = computer("worn", "rw,noatime");
+    write(root.join("sys/fs/ext4/mmcblk0p2/errors_count"), "3\n").unwrap();
+    write(root.join("sys/block/mmcblk0/device/pre_eol_info"), "0x02\n").unwrap();
+
+    let problems = checks(&root, "/home/pi/runs").run();
+    assert_eq!(problems.len(), 2);
+    assert!(problems[0].contains("3 filesystem errors"));
+    assert!(problems[1].contains("mmcblk0 says it's nearly worn out"));
+}
+
+#[test]
+fn the_oldest_runs_are_pruned_for_space() {
+    let (data_directory, runs) = data_directory("pruned", MIN_RETAINED_RUNS + 5);
+    write(annotations_path(&runs[0]), vec![b'0'; 500]).unwrap();
+    write(data_directory.path.join("notes.txt"), "Named by hand").unwrap();
+
+    // The first run and its annotations, then the second
+    assert_eq!(
+        data_directory.prune_for_space(500, 2_500).unwrap(),
+        (2_usize, 2_500_u64)
+    );
+    assert!(!runs[0].exists() && !annotations_path(&runs[0]).exists());
+    assert!(!runs[1].exists() && runs[2].exists());
+    assert!(data_directory.path.join("notes.txt").exists());
+
+    // Enough free already
+    assert_eq!(
+        data_directory.prune_for_space(5_000, 2_500).unwrap(),
+        (0_usize, 0_u64)
+    );
+}
+
+#[test]
+fn the_latest_runs_are_never_pruned() {
+    let (data_directory, runs) = data_directory("kept", MIN_RETAINED_RUNS + 1);
+
+    assert_eq!(
+        data_directory.prune_for_space(0, u64::MAX).unwrap(),
+        (1_usize, 1_000_u64)
+    );
+    assert_eq!(data_directory.runs(), runs[1..].to_vec());
+    assert!(free_space(&data_directory.path).unwrap() > 0);
+}
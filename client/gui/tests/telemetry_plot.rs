@@ -0,0 +1,36 @@
+/*!
+ * Tests for plotting distance and velocity over time
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+mod common;
+use common::status;
+use gui::telemetry_plot::*;
+
+/***** Tests *****/
+
+#[test]
+fn whole_short_runs_are_plotted() {
+    let statuses = [status(1.0, 10.0, 10.0), status(2.0, 30.0, 15.0)];
+    let plotted = telemetry(&statuses, 30.0);
+    assert_eq!(plotted.distance, [[1.0, 10.0], [2.0, 30.0]]);
+    assert_eq!(plotted.velocity, [[1.0, 10.0], [2.0, 15.0]]);
+    // From the start line
+    assert_eq!((plotted.start, plotted.end), (0.0, 2.0));
+
+    assert_eq!(telemetry(&[], 30.0), Telemetry::default());
+}
+
+#[test]
+fn only_the_window_is_plotted() {
+    let statuses = (1..=60)
+        .map(|time| status(time as f64, time as f64 * 2.0, 2.0))
+        .collect::<Vec<_>>();
+    let plotted = telemetry(&statuses, 10.0);
+    assert_eq!((plotted.start, plotted.end), (50.0, 60.0));
+    assert_eq!(plotted.distance.len(), 11);
+    assert_eq!(plotted.distance.first(), Some(&[50.0, 100.0]));
+    assert_eq!(plotted.distance.last(), Some(&[60.0, 120.0]));
+}
@@ -0,0 +1,45 @@
+/*!
+ * Tests for theming the GUI and scaling it to the window
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+#[cfg(feature = "ui")]
+use egui::{vec2, Color32};
+use gui::{config::*, theme::*};
+
+/***** Tests *****/
+
+#[cfg(feature = "ui")]
+#[test]
+fn layout_is_scaled_to_fill_the_window() {
+    let screen_size = vec2(480.0, 320.0);
+    assert_eq!(scale(vec2(480.0, 320.0), screen_size), 1.0);
+    assert_eq!(scale(vec2(960.0, 640.0), screen_size), 2.0);
+    // Without going off of the shorter side
+    assert_eq!(scale(vec2(1920.0, 640.0), screen_size), 2.0);
+    assert_eq!(scale(vec2(240.0, 320.0), screen_size), 0.5);
+    // Not before the window has a size
+    assert_eq!(scale(vec2(0.0, 0.0), screen_size), 1.0);
+}
+
+#[test]
+fn themes_and_layouts_are_read_from_the_config() {
+    let config = Config::parse("[screen]\ntheme = \"high-contrast\"\nlayout = \"kiosk\"").unwrap();
+    assert_eq!(config.screen.theme, Theme::HighContrast);
+    assert_eq!(config.screen.layout, ScreenLayout::Kiosk);
+    assert!(Config::parse("[screen]\ntheme = \"neon\"").is_err());
+
+    let default = Config::default();
+    assert_eq!(default.screen.theme, Theme::Dark);
+    assert_eq!(default.screen.layout, ScreenLayout::Standard);
+}
+
+#[cfg(feature = "ui")]
+#[test]
+fn themes_are_drawn_in_their_own_colors() {
+    assert!(Theme::Dark.visuals().dark_mode);
+    assert!(!Theme::Light.visuals().dark_mode);
+    assert_eq!(Theme::HighContrast.visuals().panel_fill, Color32::BLACK);
+}
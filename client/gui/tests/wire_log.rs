@@ -0,0 +1,102 @@
+/*!
+ * Tests for logging raw frames and replaying the logs
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use gui::wire_log::*;
+use std::{
+    env::temp_dir,
+    fs::{create_dir_all, remove_dir_all, write},
+    path::PathBuf,
+};
+
+/***** Helper functions *****/
+
+/// An empty directory for wire logs, just for one test
+fn log_directory(test: &str) -> PathBuf {
+    let directory = temp_dir().join(format!("gui-wire-log-{test}-{}", std::process::id()));
+    let _ = remove_dir_all(&directory);
+    create_dir_all(&directory).unwrap();
+    directory
+}
+
+fn entry_line(direction: &str, monotonic: f64, frame: &str) -> String {
+    format!(
+        "{{\"direction\": \"{direction}\", \"monotonic\": {monotonic}, \"time\": 1000.0, \
+         \"frame\": {frame:?}}}\n"
+    )
+}
+
+/***** Tests *****/
+
+#[test]
+fn logged_frames_are_read_back_in_order() {
+    let path = log_directory("round-trip").join("wire.log");
+
+    let mut logger = WireLogger::open(path.clone()).unwrap();
+    logger
+        .log(Direction::Sent, "~PING${\"time\": 1.0}")
+        .unwrap();
+    logger.log(Direction::Received, "~PING$$").unwrap();
+    drop(logger);
+    // Logging again adds to the end instead of starting over
+    let mut logger = WireLogger::open(path.clone()).unwrap();
+    logger.log(Direction::Received, "~STATUS$$").unwrap();
+
+    let entries = read_wire_log(&path).unwrap();
+    assert_eq!(
+        entries
+            .iter()
+            .map(|entry| (entry.direction, entry.frame.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            (Direction::Sent, "~PING${\"time\": 1.0}"),
+            (Direction::Received, "~PING$$"),
+            (Direction::Received, "~STATUS$$"),
+        ]
+    );
+    assert!(entries[0].monotonic <= entries[1].monotonic);
+    assert!(entries.iter().all(|entry| entry.time > 0.0));
+}
+
+#[test]
+fn replay_gives_received_frames_when_they_are_due() {
+    let path = log_directory("replay").join("wire.log");
+    write(
+        &path,
+        [
+            entry_line("sent", 5.0, "~PING$sent$"),
+            entry_line("received", 5.25, "~PING$first$"),
+            "\n".to_owned(),
+            entry_line("received", 5.5, "~PING$second$"),
+            entry_line("received", 1005.0, "~PING$much later$"),
+        ]
+        .concat(),
+    )
+    .unwrap();
+
+    let mut replay = WireLogReplay::read(path).unwrap();
+    assert_eq!(replay.remaining(), 3);
+    std::thread::sleep(std::time::Duration::from_secs_f64(0.6));
+    assert_eq!(replay.due(), vec!["~PING$first$", "~PING$second$"]);
+    assert!(replay.due().is_empty());
+    assert_eq!(replay.remaining(), 1);
+}
+
+#[test]
+fn broken_wire_log_is_an_error() {
+    let directory = log_directory("broken");
+
+    let broken = directory.join("broken.log");
+    write(
+        &broken,
+        entry_line("received", 0.0, "~PING$$") + "{\"direction\": \"sideways\"}\n",
+    )
+    .unwrap();
+    let error = read_wire_log(&broken).unwrap_err().to_string();
+    assert!(error.contains("Line 2"), "{error}");
+
+    assert!(WireLogReplay::read(directory.join("missing.log")).is_err());
+}
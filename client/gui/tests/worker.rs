@@ -0,0 +1,138 @@
+/*!
+ * Tests for running slow jobs in the background
+ * Created by sheepy0125 | MIT license | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+mod common;
+use bindings::{ClientError, Event, StatusResponse};
+use gui::{export::*, worker::*};
+use std::{
+    env::temp_dir,
+    fs::{create_dir_all, metadata},
+    thread::sleep,
+    time::Duration,
+};
+
+/***** Helper functions *****/
+
+/// Wait on a job to end
+fn wait<T: Send + 'static>(job: &BackgroundJob<T>) -> JobOutcome<T> {
+    for _ in 0..500 {
+        if let Some(outcome) = job.poll() {
+            return outcome;
+        }
+        sleep(Duration::from_millis(10));
+    }
+    panic!("The job never ended");
+}
+
+fn statuses(count: usize) -> Vec<Event<StatusResponse>> {
+    (0..count)
+        .map(|runtime| {
+            let mut status = common::status(runtime as f64, runtime as f64 * 1.5, 1.5);
+            status.value.distance.magnet_hit_counter = runtime * 3;
+            status.metadata.time += 0.25;
+            status
+        })
+        .collect()
+}
+
+/***** Tests *****/
+
+#[test]
+fn progress_is_counted_off() {
+    let progress = JobProgress::new(4);
+    assert_eq!(progress.fraction(), 0.0);
+    progress.advance(1);
+    assert_eq!(progress.steps(), (1, 4));
+    assert_eq!(progress.fraction(), 0.25);
+    progress.advance(10);
+    assert_eq!(progress.fraction(), 1.0);
+
+    // Nothing to do is done
+    assert_eq!(JobProgress::new(0).fraction(), 1.0);
+}
+
+#[test]
+fn jobs_finish_fail_or_are_cancelled() {
+    let job = BackgroundJob::spawn("Adding".to_owned(), 1, |progress| {
+        progress.advance(1);
+        Ok(1 + 1)
+    });
+    assert!(matches!(wait(&job), JobOutcome::Finished(2)));
+    assert_eq!(job.progress.fraction(), 1.0);
+
+    let job = BackgroundJob::<()>::spawn("Failing".to_owned(), 1, |_| {
+        Err(ClientError::CSV("Disk full".to_owned()))
+    });
+    assert!(matches!(
+        wait(&job),
+        JobOutcome::Failed(ClientError::CSV(_))
+    ));
+
+    let job = BackgroundJob::<()>::spawn("Waiting".to_owned(), 1, |progress| {
+        while !progress.cancelled() {
+            sleep(Duration::from_millis(1));
+        }
+        Err(ClientError::CSV("Cancelled".to_owned()))
+    });
+    assert!(job.poll().is_none());
+    job.cancel();
+    assert!(matches!(wait(&job), JobOutcome::Cancelled));
+}
+
+#[test]
+fn runs_are_saved_in_the_background() {
+    let directory = temp_dir().join(format!("gui-worker-{}", std::process::id()));
+    create_dir_all(&directory).unwrap();
+    let path = directory.join("long.csv");
+
+    let job = save_in_background(
+        RunFormat::CSV,
+        TableOptions::default(),
+        path.clone(),
+        statuses(20_000),
+        vec![],
+    );
+    assert_eq!(job.description, "Saving long.csv");
+    let JobOutcome::Finished(saved_run) = wait(&job) else {
+        panic!("The run wasn't saved");
+    };
+    assert_eq!(saved_run.path, path);
+    assert_eq!(saved_run.size, metadata(&path).unwrap().len());
+    assert_eq!(job.progress.steps(), (20_000, 20_000));
+    assert_eq!(read_run(&path).unwrap().len(), 20_000);
+}
+
+#[test]
+fn cancelled_runs_are_not_left_behind() {
+    let directory = temp_dir().join(format!("gui-worker-{}", std::process::id()));
+    create_dir_all(&directory).unwrap();
+    let path = directory.join("cancelled.jsonl");
+
+    let job = save_in_background(
+        RunFormat::JSONLines,
+        TableOptions::default(),
+        path.clone(),
+        statuses(200_000),
+        vec![],
+    );
+    job.cancel();
+    // Cancelling once it's done is too late, which is fine too
+    match wait(&job) {
+        JobOutcome::Cancelled => assert!(!path.exists()),
+        JobOutcome::Finished(_) => assert!(path.exists()),
+        JobOutcome::Failed(e) => panic!("{e}"),
+    }
+}
+
+#[test]
+fn sizes_are_readable() {
+    assert_eq!(size_text(0), "0 B");
+    assert_eq!(size_text(999), "999 B");
+    assert_eq!(size_text(1_500), "1.5 KB");
+    assert_eq!(size_text(2_345_678), "2.3 MB");
+    assert_eq!(size_text(5_000_000_000_000), "5000.0 GB");
+}
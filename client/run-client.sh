@@ -2,14 +2,13 @@
 
 pushd ~/car-control/
 
-echo "=== Starting socat"
-nohup socat -d -d pty,raw,echo=0 pty,raw,echo=0 > socat.log &
-SOCAT_PROCESS=$!
+echo "=== Linking PTYs"
+nohup bash -c "cd vserial && cargo run --release" > vserial.log 2>/dev/null &
+VSERIAL_PROCESS=$!
 
-echo "=== Getting PTYs from socat"
-(tail -f socat.log &) | grep -q "starting data transfer"
-SERIAL_DEVS_STR=$(cat socat.log | sed -n -e 's/^.*N PTY is //p')
-readarray -t SERIAL_DEVS_ARR <<< $SERIAL_DEVS_STR
+echo "=== Getting PTYs from vserial"
+until [ "$(wc -l < vserial.log)" -ge 2 ]; do sleep 0.1; done
+readarray -t SERIAL_DEVS_ARR < vserial.log
 BRIDGE_PTY="${SERIAL_DEVS_ARR[0]}"
 CLIENT_PTY="${SERIAL_DEVS_ARR[1]}"
 
@@ -25,6 +24,6 @@ cargo run $CLIENT_PTY
 popd
 
 echo "=== Cleaning up"
-kill $SOCAT_PROCESS
+kill $VSERIAL_PROCESS
 kill $BRIDGE_PROCESS
 popd
@@ -0,0 +1,212 @@
+/*!
+ * Virtual serial ports backed by pseudo-terminals, replacing `socat`
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+/***** Setup *****/
+// Imports
+use serialport::{SerialPort, TTYPort};
+use std::{
+    ffi::CStr,
+    io::{Error as IoError, ErrorKind, Read, Write},
+    mem::MaybeUninit,
+    os::unix::io::{AsRawFd, FromRawFd, RawFd},
+    ptr::{null, null_mut},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{sleep, spawn, JoinHandle},
+    time::Duration,
+};
+use thiserror::Error as ThisError;
+
+// Constants
+/// How long the forwarding threads of linked pseudo-terminals wait for data
+const FORWARD_TIMEOUT: Duration = Duration::from_millis(50);
+
+/***** Error *****/
+
+/// An error creating or using a virtual serial port
+#[derive(ThisError, Debug)]
+pub enum VirtualSerialError {
+    #[error("Failed to create a pseudo-terminal: {0}")]
+    Pty(IoError),
+    #[error("A serial error has occurred: {0}")]
+    Serial(serialport::Error),
+}
+impl From<serialport::Error> for VirtualSerialError {
+    fn from(value: serialport::Error) -> Self {
+        Self::Serial(value)
+    }
+}
+
+/***** Pseudo-terminal pair *****/
+
+/// A pseudo-terminal pair, created with `openpty`
+///
+/// Whatever is written to the master side can be read from the slave side and
+/// vice versa. The slave side can also be opened by path from another
+/// process, while we hold on to our own handle to it.
+pub struct PtyPair {
+    pub master: TTYPort,
+    pub slave: TTYPort,
+    pub slave_path: String,
+}
+impl PtyPair {
+    /// Open a new raw pseudo-terminal pair
+    pub fn open() -> Result<Self, VirtualSerialError> {
+        let mut master_fd: RawFd = -1;
+        let mut slave_fd: RawFd = -1;
+        // Safety: the pointers are valid for the duration of the call and the
+        // name, termios, and window size are all optional
+        if unsafe { libc::openpty(&mut master_fd, &mut slave_fd, null_mut(), null(), null()) } != 0
+        {
+            Err(VirtualSerialError::Pty(IoError::last_os_error()))?;
+        }
+
+        // Wrap them right away so they get closed on error
+        // Safety: `openpty` just gave us these and nothing else owns them
+        let mut master = unsafe { TTYPort::from_raw_fd(master_fd) };
+        let mut slave = unsafe { TTYPort::from_raw_fd(slave_fd) };
+
+        let slave_path = Self::path_of(slave_fd)?;
+        Self::make_raw(slave_fd)?;
+
+        // Let other processes open the slave side by its path
+        slave.set_exclusive(false)?;
+        master.set_exclusive(false)?;
+
+        Ok(Self {
+            master,
+            slave,
+            slave_path,
+        })
+    }
+
+    /// Split the pair into its master side and our handle to its slave side,
+    /// for another process to open the slave side by path
+    ///
+    /// Keep the handle for as long as the master side is used, as reads from
+    /// the master side fail (with `EIO`) whenever nothing has the slave side
+    /// open, e.g. before the other process opens it or while it restarts.
+    pub fn into_master(self) -> (TTYPort, HeldSlave) {
+        // Let go of our lock on it, so the other process can lock it for
+        // itself when opening it
+        // Safety: the descriptor stays open for as long as the slave side
+        let _ = unsafe { libc::flock(self.slave.as_raw_fd(), libc::LOCK_UN) };
+        (
+            self.master,
+            HeldSlave {
+                path: self.slave_path,
+                _slave: self.slave,
+            },
+        )
+    }
+
+    /// Split the pair into boxed serial ports
+    pub fn into_ports(self) -> (Box<dyn SerialPort>, Box<dyn SerialPort>) {
+        (Box::new(self.master), Box::new(self.slave))
+    }
+
+    /// Get the path of a terminal's file descriptor
+    fn path_of(fd: RawFd) -> Result<String, VirtualSerialError> {
+        let mut buffer = [0 as libc::c_char; 128];
+        // Safety: the buffer's length is passed alongside it
+        let result = unsafe { libc::ttyname_r(fd, buffer.as_mut_ptr(), buffer.len()) };
+        if result != 0 {
+            Err(VirtualSerialError::Pty(IoError::from_raw_os_error(result)))?;
+        }
+        // Safety: `ttyname_r` NUL terminates on success
+        let path = unsafe { CStr::from_ptr(buffer.as_ptr()) };
+        Ok(path.to_string_lossy().into_owned())
+    }
+
+    /// Put a terminal into raw mode (no echo, no line discipline)
+    fn make_raw(fd: RawFd) -> Result<(), VirtualSerialError> {
+        let mut termios = MaybeUninit::uninit();
+        // Safety: `tcgetattr` initializes the termios on success
+        if unsafe { libc::tcgetattr(fd, termios.as_mut_ptr()) } != 0 {
+            Err(VirtualSerialError::Pty(IoError::last_os_error()))?;
+        }
+        let mut termios = unsafe { termios.assume_init() };
+        unsafe { libc::cfmakeraw(&mut termios) };
+        if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &termios) } != 0 {
+            Err(VirtualSerialError::Pty(IoError::last_os_error()))?;
+        }
+        Ok(())
+    }
+}
+
+/// The slave side of a pair, held open (but never read from) so the master
+/// side keeps working while another process opens it by path, see
+/// [`PtyPair::into_master`]
+pub struct HeldSlave {
+    pub path: String,
+    _slave: TTYPort,
+}
+
+/***** Linked pseudo-terminals *****/
+
+/// Two pseudo-terminals linked together, the equivalent of
+/// `socat pty,raw,echo=0 pty,raw,echo=0`
+///
+/// Both ends are meant to be opened by path (e.g. one by the bridge, one by the
+/// GUI). Data is forwarded between them until this is dropped.
+pub struct LinkedPtys {
+    pub paths: [String; 2],
+    _slaves: [HeldSlave; 2],
+    running: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<()>>,
+}
+impl LinkedPtys {
+    /// Open two pseudo-terminals and start forwarding between them
+    pub fn open() -> Result<Self, VirtualSerialError> {
+        let (first_master, first_slave) = PtyPair::open()?.into_master();
+        let (second_master, second_slave) = PtyPair::open()?.into_master();
+
+        let running = Arc::new(AtomicBool::new(true));
+        let threads = vec![
+            Self::forward(
+                first_master.try_clone_native()?,
+                second_master.try_clone_native()?,
+                running.clone(),
+            ),
+            Self::forward(second_master, first_master, running.clone()),
+        ];
+
+        Ok(Self {
+            paths: [first_slave.path.clone(), second_slave.path.clone()],
+            _slaves: [first_slave, second_slave],
+            running,
+            threads,
+        })
+    }
+
+    /// Forward everything read from one master to the other
+    fn forward(mut from: TTYPort, mut to: TTYPort, running: Arc<AtomicBool>) -> JoinHandle<()> {
+        spawn(move || {
+            let _ = from.set_timeout(FORWARD_TIMEOUT);
+            let mut buffer = [0_u8; 256];
+            while running.load(Ordering::Relaxed) {
+                match from.read(&mut buffer) {
+                    Ok(0) => (),
+                    Ok(length) => {
+                        let _ = to.write_all(&buffer[..length]);
+                    }
+                    Err(e) if e.kind() == ErrorKind::TimedOut => (),
+                    // Nothing has the slave side open yet (or anymore)
+                    Err(_) => sleep(FORWARD_TIMEOUT),
+                }
+            }
+        })
+    }
+}
+impl Drop for LinkedPtys {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
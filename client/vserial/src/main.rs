@@ -0,0 +1,21 @@
+/*!
+ * Link two pseudo-terminals together until killed, a stand-in for `socat`
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+use std::{io::Write, thread::park};
+use vserial::LinkedPtys;
+
+fn main() -> Result<(), vserial::VirtualSerialError> {
+    let linked_ptys = LinkedPtys::open()?;
+
+    // One path per line so scripts can `readarray` them
+    for path in linked_ptys.paths.iter() {
+        println!("{path}");
+    }
+    let _ = std::io::stdout().flush();
+
+    loop {
+        park();
+    }
+}
@@ -0,0 +1,73 @@
+/*!
+ * Round trips through virtual serial ports
+ * Created by sheepy0125 | MIT License | 2026-10-16
+ */
+
+use serialport::{new as new_serialport, SerialPort};
+use std::{
+    io::{ErrorKind, Read, Write},
+    time::Duration,
+};
+use vserial::{LinkedPtys, PtyPair};
+
+fn read_exactly(port: &mut dyn serialport::SerialPort, length: usize) -> Vec<u8> {
+    port.set_timeout(Duration::from_secs(2)).unwrap();
+    let mut buffer = vec![0_u8; length];
+    port.read_exact(&mut buffer).unwrap();
+    buffer
+}
+
+#[test]
+fn pty_pair_round_trip() {
+    let (mut master, mut slave) = PtyPair::open().unwrap().into_ports();
+
+    master.write_all(b"?PING${}${\"time\":0.0}\n").unwrap();
+    assert_eq!(read_exactly(&mut *slave, 22), b"?PING${}${\"time\":0.0}\n");
+
+    slave.write_all(b"~PING$").unwrap();
+    assert_eq!(read_exactly(&mut *master, 6), b"~PING$");
+}
+
+#[test]
+fn pty_pair_slave_opened_by_path() {
+    let (mut master, held_slave) = PtyPair::open().unwrap().into_master();
+    let mut slave = new_serialport(&held_slave.path, 115200).open().unwrap();
+
+    slave.write_all(b"hello").unwrap();
+    assert_eq!(read_exactly(&mut master, 5), b"hello");
+
+    // The master side is only waiting for data while the slave side is closed
+    // and opened again, like when the bridge restarts
+    drop(slave);
+    master.set_timeout(Duration::from_millis(100)).unwrap();
+    let error = master.read(&mut [0_u8; 1]).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::TimedOut, "{error}");
+    let mut slave = new_serialport(&held_slave.path, 115200).open().unwrap();
+    slave.write_all(b"again").unwrap();
+    assert_eq!(read_exactly(&mut master, 5), b"again");
+}
+
+#[test]
+fn linked_ptys_forward_both_ways() {
+    let linked_ptys = LinkedPtys::open().unwrap();
+    let mut first = new_serialport(&linked_ptys.paths[0], 115200)
+        .open()
+        .unwrap();
+    let mut second = new_serialport(&linked_ptys.paths[1], 115200)
+        .open()
+        .unwrap();
+
+    first.write_all(b"^CONNECT${}${}\n").unwrap();
+    assert_eq!(read_exactly(&mut *second, 15), b"^CONNECT${}${}\n");
+
+    second.write_all(b"&BLUETOOTHSTATUS").unwrap();
+    assert_eq!(read_exactly(&mut *first, 16), b"&BLUETOOTHSTATUS");
+
+    // Reopening after the other side went away still works
+    drop(second);
+    let mut second = new_serialport(&linked_ptys.paths[1], 115200)
+        .open()
+        .unwrap();
+    first.write_all(b"again").unwrap();
+    assert_eq!(read_exactly(&mut *second, 5), b"again");
+}
@@ -21,6 +21,8 @@
  * This is basically the Wireless UART example.
  * I've touched the `BleApp_SendUartStream` function along with the custom GATT service in
  * `gatt_db.h` and `gatt_uuid128.h`.
+ * Frames to and from the bridge are now sent in numbered chunks (see the bridge's readme),
+ * which `BleApp_ReceivedUartStream` and `BleApp_FlushUartStream` take apart and put together.
  *
  * Soon, I will meander my way down onto the accelerometer readings.
  * For now, though, I've yet to figure them out. (logan plz help)
@@ -73,6 +75,14 @@
 #define mAppUartFlushIntervalInMs_c   	(100) 	/* Flush Timeout in Ms */
 
 #define mBatteryLevelReportInterval_c   (10)	/* battery level report interval in seconds  */
+
+/* Chunks, the same as the bridge's `fragment.rs` */
+#define mChunkStart_c                   (0x01)  /* Starts every chunk */
+#define mChunkMore_c                    (0x17)  /* Ends a chunk with more of the frame after it */
+#define mChunkEnd_c                     (0x04)  /* Ends the last chunk of a frame */
+#define mChunkHeaderSize_c              (4)     /* Start, sequence, index, payload length */
+#define mChunkMaxSize_c                 (mChunkHeaderSize_c + 255 + 1)
+#define mChunkMaxPayload_c              (mAppUartBufferSize_c - mChunkHeaderSize_c - 1)  /* As written in one go */
 /************************************************************************************
  *************************************************************************************
  * Private type definitions
@@ -132,6 +142,26 @@ static tmrTimerID_t mBatteryMeasurementTimerId;
 
 static uint8_t gAppSerMgrIf;
 static uint16_t mAppUartBufferSize = mAppUartBufferSize_c; /* 244 */
+
+/* Chunks being received from the bridge */
+static uint8_t  mRxChunk[mChunkMaxSize_c];
+static uint16_t mRxChunkLength = 0;
+static bool_t   mRxInFrame = FALSE;
+static uint8_t  mRxSequence;
+static uint8_t  mRxNextIdx;
+/* The whole of the last chunk, as only a chunk that's byte for byte the same is a duplicate */
+static uint8_t  mRxLastChunk[mChunkMaxSize_c];
+static uint16_t mRxLastChunkLength = 0;
+/* Read after the start of a chunk that wasn't one, to go back over for where the next chunk starts */
+static uint8_t  mRxRescan[mChunkMaxSize_c];
+static uint16_t mRxRescanLength = 0;
+static uint16_t mRxRescanIdx = 0;
+
+/* Read from the UART but not sent yet, as only one chunk is sent per flush */
+static uint8_t  mTxPending[mAppUartBufferSize_c];
+static uint16_t mTxPendingLength = 0;
+static uint8_t  mTxSequence = 0;
+static uint8_t  mTxIdx = 0;
 /************************************************************************************
  *************************************************************************************
  * Private functions prototypes
@@ -193,6 +223,10 @@ static void Uart_TxCallBack(void *pBuffer);
 
 static void BleApp_FlushUartStream(void *pParam);
 static void BleApp_ReceivedUartStream(uint8_t *pStream, uint16_t streamLength);
+static void BleApp_ReceivedChunkByte(uint8_t byte);
+static void BleApp_ResynchronizeChunk(void);
+static void BleApp_ReceivedChunk(uint8_t *pChunk, uint16_t chunkLength, bool_t last);
+static void BleApp_WriteUart(uint8_t *pData, uint16_t length);
 /************************************************************************************
 *************************************************************************************
 * Public functions
@@ -839,42 +873,210 @@ static void BleApp_FlushUartStream(void *pParam)
 {
 	uint8_t *pMsg = NULL;
 	uint16_t bytesRead = 0;
+	uint16_t maxPayload;
+	uint16_t payloadLength;
+	bool_t last = FALSE;
     
     if (mPeerInformation.appState != mAppRunning_c)
     {
 //        return;  // not today
     }
 
-    /* Allocate buffer for GATT Write */
-    pMsg = MEM_BufferAlloc(mAppUartBufferSize);
-    if (pMsg == NULL)
+    /* Leave room for the chunk's header and end */
+    maxPayload = MIN(mAppUartBufferSize, mAppUartBufferSize_c) - mChunkHeaderSize_c - 1;
+
+    /* Collect the data from the serial manager buffer after what's left over */
+    if (mTxPendingLength < maxPayload)
+    {
+        (void)Serial_Read(gAppSerMgrIf, &mTxPending[mTxPendingLength], maxPayload - mTxPendingLength, &bytesRead);
+        mTxPendingLength += bytesRead;
+    }
+    if (mTxPendingLength == 0)
     {
     	return;
     }
 
-    /* Collect the data from the serial manager buffer */
-    if ( Serial_Read( gAppSerMgrIf, pMsg, mAppUartBufferSize, &bytesRead) == gSerial_Success_c )
+    /* A frame is a line, so one ends after a newline */
+    payloadLength = MIN(mTxPendingLength, maxPayload);
+    for (uint16_t i = 0; i < payloadLength; i++)
     {
-        if (bytesRead != 0)
+        if (mTxPending[i] == '\n')
         {
-            /* Send data over the air */
-            BleApp_SendUartStream(mPeerInformation.deviceId, pMsg, bytesRead);
+            payloadLength = i + 1;
+            last = TRUE;
+            break;
         }
-    }    
+    }
+
+    /* Allocate buffer for GATT Write */
+    pMsg = MEM_BufferAlloc(payloadLength + mChunkHeaderSize_c + 1);
+    if (pMsg == NULL)
+    {
+    	return;
+    }
+    pMsg[0] = mChunkStart_c;
+    pMsg[1] = mTxSequence;
+    pMsg[2] = mTxIdx;
+    pMsg[3] = (uint8_t)payloadLength;
+    FLib_MemCpy(&pMsg[mChunkHeaderSize_c], mTxPending, payloadLength);
+    pMsg[mChunkHeaderSize_c + payloadLength] = last ? mChunkEnd_c : mChunkMore_c;
+
+    /* Send data over the air */
+    BleApp_SendUartStream(mPeerInformation.deviceId, pMsg, payloadLength + mChunkHeaderSize_c + 1);
+
     /* Free Buffer */
     MEM_BufferFree(pMsg);
+
+    if (last)
+    {
+        mTxSequence++;
+        mTxIdx = 0;
+    }
+    else
+    {
+        mTxIdx++;
+    }
+
+    /* Keep the rest for the next flush, after the bridge has had time to read this chunk */
+    mTxPendingLength -= payloadLength;
+    if (mTxPendingLength != 0)
+    {
+        FLib_MemInPlaceCpy(mTxPending, &mTxPending[payloadLength], mTxPendingLength);
+        TMR_StartLowPowerTimer(mUartStreamFlushTimerId,
+            gTmrLowPowerSingleShotMillisTimer_c,
+            mAppUartFlushIntervalInMs_c,
+            UartStreamFlushTimerCallback, NULL);
+    }
 }
 
 static void BleApp_ReceivedUartStream(uint8_t *pStream, uint16_t streamLength)
+{
+    /* The bridge writes a byte at a time, padding with zeros, so put chunks together byte by byte */
+    for (uint16_t i = 0; i < streamLength; i++)
+    {
+        BleApp_ReceivedChunkByte(pStream[i]);
+
+        /* Anything given back by a chunk that wasn't one goes before the rest of the stream */
+        while (mRxRescanIdx < mRxRescanLength)
+        {
+            BleApp_ReceivedChunkByte(mRxRescan[mRxRescanIdx++]);
+        }
+        mRxRescanLength = 0;
+        mRxRescanIdx = 0;
+    }
+}
+
+static void BleApp_ReceivedChunkByte(uint8_t byte)
+{
+    uint8_t length;
+
+    if (mRxChunkLength == 0 && byte != mChunkStart_c)
+    {
+        return;
+    }
+    mRxChunk[mRxChunkLength++] = byte;
+    if (mRxChunkLength < mChunkHeaderSize_c)
+    {
+        return;
+    }
+
+    length = mRxChunk[mChunkHeaderSize_c - 1];
+    if (length == 0 || length > mChunkMaxPayload_c)
+    {
+        BleApp_ResynchronizeChunk();
+        return;
+    }
+    if (mRxChunkLength < mChunkHeaderSize_c + length + 1)
+    {
+        return;
+    }
+
+    switch (mRxChunk[mChunkHeaderSize_c + length])
+    {
+        case mChunkMore_c:
+            BleApp_ReceivedChunk(mRxChunk, mRxChunkLength, FALSE);
+            break;
+        case mChunkEnd_c:
+            BleApp_ReceivedChunk(mRxChunk, mRxChunkLength, TRUE);
+            break;
+        default:
+            /* Not a chunk after all */
+            BleApp_ResynchronizeChunk();
+            return;
+    }
+    mRxChunkLength = 0;
+}
+
+static void BleApp_ResynchronizeChunk(void)
+{
+    uint16_t remaining = mRxRescanLength - mRxRescanIdx;
+
+    /* A chunk's start could be anywhere after the one given up on, so go back over it all, ahead of
+     * anything already to go back over (the chunk was read from just before that, so it fits) */
+    FLib_MemInPlaceCpy(&mRxRescan[mRxChunkLength - 1], &mRxRescan[mRxRescanIdx], remaining);
+    FLib_MemCpy(mRxRescan, &mRxChunk[1], mRxChunkLength - 1);
+    mRxRescanLength = mRxChunkLength - 1 + remaining;
+    mRxRescanIdx = 0;
+    mRxChunkLength = 0;
+}
+
+static void BleApp_ReceivedChunk(uint8_t *pChunk, uint16_t chunkLength, bool_t last)
+{
+    uint8_t sequence = pChunk[1];
+    uint8_t idx = pChunk[2];
+    uint8_t *pPayload = &pChunk[mChunkHeaderSize_c];
+    uint8_t length = pChunk[mChunkHeaderSize_c - 1];
+
+    /* The same chunk twice, though not a restarted bridge numbering from the start again */
+    if (chunkLength == mRxLastChunkLength && FLib_MemCmp(pChunk, mRxLastChunk, chunkLength))
+    {
+        return;
+    }
+    FLib_MemCpy(mRxLastChunk, pChunk, chunkLength);
+    mRxLastChunkLength = chunkLength;
+
+    if (idx == 0)
+    {
+        /* End what's left of the last frame so the server drops it */
+        if (mRxInFrame)
+        {
+            BleApp_WriteUart((uint8_t *)"\n", 1);
+        }
+        mRxInFrame = TRUE;
+        mRxSequence = sequence;
+        mRxNextIdx = 0;
+    }
+    else if (!mRxInFrame || sequence != mRxSequence || idx != mRxNextIdx)
+    {
+        /* A chunk went missing, so give up on the frame */
+        if (mRxInFrame)
+        {
+            BleApp_WriteUart((uint8_t *)"\n", 1);
+        }
+        mRxInFrame = FALSE;
+        return;
+    }
+
+    /* The frame goes out to the UART as it comes in */
+    BleApp_WriteUart(pPayload, length);
+    mRxNextIdx++;
+    if (last)
+    {
+        mRxInFrame = FALSE;
+    }
+}
+
+static void BleApp_WriteUart(uint8_t *pData, uint16_t length)
 {
     uint8_t *pBuffer = NULL;
 
     /* Allocate buffer for asynchronous write */
-    pBuffer = MEM_BufferAlloc(streamLength);
+    pBuffer = MEM_BufferAlloc(length);
 
     if (pBuffer != NULL)
     {
-        Serial_AsyncWrite(gAppSerMgrIf, pStream, streamLength, Uart_TxCallBack, pBuffer);
+        FLib_MemCpy(pBuffer, pData, length);
+        Serial_AsyncWrite(gAppSerMgrIf, pBuffer, length, Uart_TxCallBack, pBuffer);
     }
 }
 
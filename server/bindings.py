@@ -7,6 +7,7 @@ MIT License | 2023-02-20
 ### Setup ###
 # Imports
 from json import dumps
+from shared import MAX_PLAN_BREAKPOINTS
 
 
 ### Helper functions ###
@@ -105,7 +106,7 @@ class Error(Enum):
     FailedToStopNotStarted = 25
     FailedToStopStartThreadWouldNotRespond = 26
     FailedStatusCouldNotAcquireDistanceLock = 27
-    FailedPingNegativeLatency = 27
+    FailedPingNegativeLatency = 28
     AnyOtherError = 99
 
 
@@ -161,6 +162,43 @@ class RunStage(Enum):
                 return None
 
 
+class DistanceEstimatorKind(Enum):
+    """How the distance is worked out during a run, chosen with each start;
+    see :class:`estimators.DistanceEstimator`
+    """
+
+    # Counting the magnets going past the hall effect sensors
+    Odometer = 0
+    # The odometer, filled in between magnets by an accelerometer
+    AccelerometerFusion = 1
+    # The odometer, corrected by a rangefinder facing the way it's going
+    Rangefinder = 2
+
+    @classmethod
+    def lookup_by_prefix(cls, prefix: str) -> int | None:
+        match prefix:
+            case "ODOMETER":
+                return cls.Odometer
+            case "FUSION":
+                return cls.AccelerometerFusion
+            case "RANGEFINDER":
+                return cls.Rangefinder
+            case _:
+                return None
+
+    @classmethod
+    def lookup_by_variant(cls, variant: int) -> str | None:
+        match variant:
+            case cls.Odometer:
+                return "ODOMETER"
+            case cls.AccelerometerFusion:
+                return "FUSION"
+            case cls.Rangefinder:
+                return "RANGEFINDER"
+            case _:
+                return None
+
+
 class GPIOPin(Enum):
     """This enum is non serializable"""
 
@@ -170,6 +208,7 @@ class GPIOPin(Enum):
     MotorControllerBackwardNegative = 24  # GPIO_GEN5
     MagnetHallEffectSensor1 = 25  # GPIO_GEN6
     MagnetHallEffectSensor2 = 7  # CE1
+    RadioStopReceiver = 22  # GPIO_GEN3
 
 
 class TransitMode(Enum):
@@ -236,6 +275,11 @@ class Command(Enum):
     Stop = 2
     StaticStatus = 3
     Status = 4
+    Nack = 5
+    Hello = 6
+    UploadPlan = 7
+    Tune = 8
+    Calibrate = 9
     Unknown = Error = 99
 
     @classmethod
@@ -254,6 +298,16 @@ class Command(Enum):
                 return cls.StaticStatus
             case "STATUS":
                 return cls.Status
+            case "NACK":
+                return cls.Nack
+            case "HELLO":
+                return cls.Hello
+            case "UPLOADPLAN":
+                return cls.UploadPlan
+            case "TUNE":
+                return cls.Tune
+            case "CALIBRATE":
+                return cls.Calibrate
             case "ERROR" | "UNKNOWN":
                 return cls.Error
             case _:
@@ -274,6 +328,16 @@ class Command(Enum):
                 return "STATUS"
             case cls.StaticStatus:
                 return "STATICSTATUS"
+            case cls.Nack:
+                return "NACK"
+            case cls.Hello:
+                return "HELLO"
+            case cls.UploadPlan:
+                return "UPLOADPLAN"
+            case cls.Tune:
+                return "TUNE"
+            case cls.Calibrate:
+                return "CALIBRATE"
             case cls.Unknown:
                 return "UNKNOWN"
             case _:
@@ -298,13 +362,55 @@ class SerializableStruct:
 # Metadata
 
 
+class Echo:
+    """Sent back with the response to a request, so the client can tell the
+    time spent here apart from the time spent getting here and back
+    """
+
+    def __init__(self, time: float, received: float):
+        # The time the request was sent with, by the client's clock
+        self.time = unsigned_float(time)
+        # When the request was read, by this clock
+        self.received = unsigned_float(received)
+
+    @property
+    def __dict__(self) -> dict:
+        return {"time": self.time, "received": self.received}
+
+
 class MetaData:
-    def __init__(self, time: float):
+    def __init__(
+        self,
+        time: float,
+        sequence: int | None = None,
+        frame: int | None = None,
+        echo: Echo | dict | None = None,
+        client: int | None = None,
+    ):
         self.time = unsigned_float(time)
+        # Numbers a request, and is sent back with its response
+        self.sequence = unsigned_int(sequence) if sequence is not None else None
+        # Numbers the frames sent each way, so a missing one can be asked for
+        # again with a `NACK`
+        self.frame = unsigned_int(frame) if frame is not None else None
+        # Only sent with responses
+        self.echo = Echo(**echo) if isinstance(echo, dict) else echo
+        # Which of the clients sharing the serial bridge sent a request, sent
+        # back with its response
+        self.client = unsigned_int(client) if client is not None else None
 
     @property
     def __dict__(self) -> dict:
-        return {"time": self.time}
+        metadata = {"time": self.time}
+        if self.sequence is not None:
+            metadata["sequence"] = self.sequence
+        if self.frame is not None:
+            metadata["frame"] = self.frame
+        if self.echo is not None:
+            metadata["echo"] = self.echo.__dict__
+        if self.client is not None:
+            metadata["client"] = self.client
+        return metadata
 
 
 # Error
@@ -320,6 +426,43 @@ class ErrorResponse(SerializableStruct):
         return {"error_variant": self.error_variant, "message": self.message}
 
 
+# Nack
+
+
+class NackArguments(SerializableStruct):
+    """Asks for a frame to be sent again, which is never responded to"""
+
+    def __init__(self, frame: int):
+        self.frame = unsigned_int(frame)
+
+    @property
+    def __dict__(self) -> dict:
+        return {"frame": self.frame}
+
+
+# Hello
+
+
+class HelloArguments(SerializableStruct):
+    """Says which version of the protocol the client speaks"""
+
+    def __init__(self, protocol_version: int):
+        self.protocol_version = unsigned_int(protocol_version)
+
+    @property
+    def __dict__(self) -> dict:
+        return {"protocol_version": self.protocol_version}
+
+
+class HelloResponse(SerializableStruct):
+    def __init__(self, protocol_version: int):
+        self.protocol_version = protocol_version
+
+    @property
+    def __dict__(self) -> dict:
+        return {"protocol_version": self.protocol_version}
+
+
 # Ping
 
 
@@ -345,15 +488,43 @@ class PingResponse(SerializableStruct):
 
 
 class StartArguments(SerializableStruct):
-    def __init__(self, distance: float, reverse_brake: bool):
+    def __init__(
+        self,
+        distance: float,
+        reverse_brake: bool,
+        max_velocity: float | None = None,
+        distance_estimator: int | None = None,
+        coast_deceleration: float | None = None,
+    ):
         self.distance = unsigned_float(distance)
         self.reverse_brake = bool(reverse_brake)
+        # Centimeters per second, or None for no speed limit
+        self.max_velocity = (
+            unsigned_float(max_velocity) if max_velocity is not None else None
+        )
+        # A :class:`DistanceEstimatorKind`, or None for the default
+        if (
+            distance_estimator is not None
+            and DistanceEstimatorKind.lookup_by_variant(distance_estimator) is None
+        ):
+            raise ValueError(f"Unknown distance estimator: {distance_estimator}")
+        self.distance_estimator = distance_estimator
+        # Centimeters per second squared the car slows down by once power is
+        # cut, or None to cut it a fixed leeway before the distance
+        self.coast_deceleration = (
+            unsigned_float(coast_deceleration)
+            if coast_deceleration is not None
+            else None
+        )
 
     @property
     def __dict__(self) -> dict:
         return {
             "distance": self.distance,
             "reverse_brake": self.reverse_brake,
+            "max_velocity": self.max_velocity,
+            "distance_estimator": self.distance_estimator,
+            "coast_deceleration": self.coast_deceleration,
         }
 
 
@@ -361,6 +532,156 @@ class StartResponse(SerializableStruct):
     ...
 
 
+# Upload plan
+
+
+class PlanBreakpoint(SerializableStruct):
+    def __init__(self, distance: float, max_velocity: float | None = None):
+        # Centimeters from the start
+        self.distance = unsigned_float(distance)
+        # Centimeters per second from here on, or None for no speed limit
+        self.max_velocity = (
+            unsigned_float(max_velocity) if max_velocity is not None else None
+        )
+        if self.max_velocity == 0.0:
+            raise ValueError(
+                f"The speed limit at {self.distance}cm has to be over 0cm/s"
+            )
+
+    @property
+    def __dict__(self) -> dict:
+        return {"distance": self.distance, "max_velocity": self.max_velocity}
+
+
+class UploadPlanArguments(SerializableStruct):
+    """A run the car carries out on its own, changing the speed limit at each
+    breakpoint it goes past, so it finishes even if the link to the client
+    drops"""
+
+    def __init__(self, start: dict, breakpoints: list):
+        self.start = StartArguments(**start)
+        if len(breakpoints) > MAX_PLAN_BREAKPOINTS:
+            raise ValueError(
+                f"A plan can have at most {MAX_PLAN_BREAKPOINTS} breakpoints, not "
+                f"{len(breakpoints)}"
+            )
+        self.breakpoints = [PlanBreakpoint(**breakpoint) for breakpoint in breakpoints]
+        previous = None
+        for breakpoint in self.breakpoints:
+            if breakpoint.distance >= self.start.distance:
+                raise ValueError(
+                    f"The breakpoint at {breakpoint.distance}cm isn't before the "
+                    f"distance of {self.start.distance}cm"
+                )
+            if previous is not None and breakpoint.distance <= previous:
+                raise ValueError(
+                    f"The breakpoint at {breakpoint.distance}cm is out of order"
+                )
+            previous = breakpoint.distance
+
+    @property
+    def __dict__(self) -> dict:
+        return {
+            "start": self.start.__dict__,
+            "breakpoints": [breakpoint.__dict__ for breakpoint in self.breakpoints],
+        }
+
+
+class UploadPlanResponse(SerializableStruct):
+    ...
+
+
+# Tune
+
+
+class ApproachGains(SerializableStruct):
+    """The gains of the controller that eases the car in over the last stretch,
+    instead of running it at full power until it's close enough to cut
+
+    The relays can only turn the motor on or off, so the controller's output is
+    how much of the time the motor is on, from 0 to 1"""
+
+    def __init__(self, proportional: float, integral: float, derivative: float):
+        # Per centimeter left to go
+        self.proportional = unsigned_float(proportional)
+        # Per centimeter second left to go, built up over the approach
+        self.integral = unsigned_float(integral)
+        # Per centimeter per second the car is going, to keep it from
+        # overshooting
+        self.derivative = unsigned_float(derivative)
+
+    @property
+    def __dict__(self) -> dict:
+        return {
+            "proportional": self.proportional,
+            "integral": self.integral,
+            "derivative": self.derivative,
+        }
+
+
+class TuneArguments(SerializableStruct):
+    def __init__(self, approach_gains: dict | None = None):
+        # None to cut power once close enough, like before being tuned
+        self.approach_gains = (
+            ApproachGains(**approach_gains) if approach_gains is not None else None
+        )
+
+    @property
+    def __dict__(self) -> dict:
+        return {
+            "approach_gains": (
+                self.approach_gains.__dict__
+                if self.approach_gains is not None
+                else None
+            )
+        }
+
+
+class TuneResponse(SerializableStruct):
+    def __init__(self, approach_gains: ApproachGains | None):
+        # The gains in effect now
+        self.approach_gains = approach_gains
+
+    @property
+    def __dict__(self) -> dict:
+        return {
+            "approach_gains": (
+                self.approach_gains.__dict__
+                if self.approach_gains is not None
+                else None
+            )
+        }
+
+
+# Calibrate
+
+
+class CalibrateArguments(SerializableStruct):
+    def __init__(self, distance: float):
+        # Centimeters the car was measured to have really gone on the last run
+        self.distance = unsigned_float(distance)
+        if not self.distance > 0:
+            raise ValueError(f"The distance gone has to be over 0cm, not {distance}")
+
+    @property
+    def __dict__(self) -> dict:
+        return {"distance": self.distance}
+
+
+class CalibrateResponse(SerializableStruct):
+    def __init__(self, previous_wheel_circumference: float, wheel_circumference: float):
+        # Centimeters, what distances were counted with before and are from now
+        self.previous_wheel_circumference = unsigned_float(previous_wheel_circumference)
+        self.wheel_circumference = unsigned_float(wheel_circumference)
+
+    @property
+    def __dict__(self) -> dict:
+        return {
+            "previous_wheel_circumference": self.previous_wheel_circumference,
+            "wheel_circumference": self.wheel_circumference,
+        }
+
+
 # Stop
 
 
@@ -380,16 +701,35 @@ class StaticStatusArguments(SerializableStruct):
 
 
 class StaticStatusResponse(SerializableStruct):
-    def __init__(self, number_of_magnets: int, wheel_diameter: float):
+    def __init__(
+        self,
+        number_of_magnets: int,
+        wheel_diameter: float,
+        distance_estimator: int,
+        approach_gains: ApproachGains | None = None,
+        wheel_circumference: float | None = None,
+    ):
         self.number_of_magnets = number_of_magnets
         self.wheel_diameter = wheel_diameter
+        # The :class:`DistanceEstimatorKind` of the last run, or the default
+        self.distance_estimator = distance_estimator
+        # What the car is eased in with, or None if it isn't
+        self.approach_gains = approach_gains
+        # Centimeters, once it's been calibrated with `CALIBRATE`
+        self.wheel_circumference = wheel_circumference
 
     @property
     def __dict__(self) -> dict:
-        return {
+        static_status = {
             "number_of_magnets": self.number_of_magnets,
             "wheel_diameter": self.wheel_diameter,
+            "distance_estimator": self.distance_estimator,
         }
+        if self.approach_gains is not None:
+            static_status["approach_gains"] = self.approach_gains.__dict__
+        if self.wheel_circumference is not None:
+            static_status["wheel_circumference"] = self.wheel_circumference
+        return static_status
 
 
 # Dynamic (regular) status
@@ -427,22 +767,44 @@ class StatusResponse(SerializableStruct):
         runtime: int,
         distance: DistanceInformation,
         stage: int,
+        speed_limit_violations: int = 0,
+        executed_plan: list[dict] | None = None,
+        radio_stop: dict | None = None,
+        power: dict | None = None,
     ):
         self.running = bool(running)
         self.uptime = unsigned_int(uptime)
         self.runtime = unsigned_int(runtime)
         self.distance = distance.__dict__
         self.stage = stage
+        self.speed_limit_violations = unsigned_int(speed_limit_violations)
+        # The breakpoints the last run was uploaded with, each with the
+        # seconds into the run it was `reached` (or None), once it's over
+        self.executed_plan = executed_plan
+        # Whether the radio's stop button is `held`, and how many runs it
+        # `stops`, or None without a radio
+        self.radio_stop = radio_stop
+        # The `battery_voltage`, `motor_current`, and `charge` left (if known),
+        # or None without a power monitor
+        self.power = power
 
     @property
     def __dict__(self) -> dict:
-        return {
+        status = {
             "running": self.running,
             "uptime": self.uptime,
             "runtime": self.runtime,
             "distance": self.distance,
             "stage": self.stage,
+            "speed_limit_violations": self.speed_limit_violations,
         }
+        if self.executed_plan is not None:
+            status["executed_plan"] = self.executed_plan
+        if self.radio_stop is not None:
+            status["radio_stop"] = self.radio_stop
+        if self.power is not None:
+            status["power"] = self.power
+        return status
 
 
 ### Lookup tables ###
@@ -469,4 +831,23 @@ COMMAND_SERIALIZABLE_LUT = {
         TransitType.Request: StaticStatusArguments,
         TransitType.Response: StaticStatusResponse,
     },
+    Command.Nack: {
+        TransitType.Request: NackArguments,
+    },
+    Command.Hello: {
+        TransitType.Request: HelloArguments,
+        TransitType.Response: HelloResponse,
+    },
+    Command.UploadPlan: {
+        TransitType.Request: UploadPlanArguments,
+        TransitType.Response: UploadPlanResponse,
+    },
+    Command.Tune: {
+        TransitType.Request: TuneArguments,
+        TransitType.Response: TuneResponse,
+    },
+    Command.Calibrate: {
+        TransitType.Request: CalibrateArguments,
+        TransitType.Response: CalibrateResponse,
+    },
 }
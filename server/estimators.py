@@ -0,0 +1,171 @@
+"""
+Ways of working out how far the car has gone, chosen with each start so they
+can be compared against each other without changing the control loop
+Created by sheepy0125 | MIT License | 2026-10-16
+"""
+
+### Setup ###
+# Imports
+from abc import ABC, abstractmethod
+from bindings import DistanceEstimatorKind
+from shared import (
+    DEFAULT_DISTANCE_ESTIMATOR,
+    NUMBER_OF_MAGNETS,
+    RANGEFINDER_WEIGHT,
+    SENSOR_READING_TIMEOUT_SECONDS,
+    WHEEL_CIRCUMFERENCE_CENTIMETERS,
+)
+
+### Classes ###
+
+
+class DistanceEstimator(ABC):
+    """Works out how far the car has gone from what its sensors pick up
+
+    The control loop tells it about every magnet hit and asks it for the
+    distance; readings from the accelerometer and rangefinder are given to it
+    with :meth:`accelerometer_reading` and :meth:`rangefinder_reading` by
+    whatever reads them. Readings an estimator doesn't use are ignored, and one
+    whose sensor stops sending readings goes by the odometer alone
+    """
+
+    kind: int
+
+    def __init__(self, wheel_circumference: float = WHEEL_CIRCUMFERENCE_CENTIMETERS):
+        # Centimeters, as calibrated when the run started
+        self.wheel_circumference = wheel_circumference
+        self.distance_per_magnet = wheel_circumference / NUMBER_OF_MAGNETS
+        # Centimeters, going by the magnets alone
+        self.odometer_distance = 0.0
+        self.magnet_time: float | None = None
+
+    def magnet_hit(self, direction: int, time: float) -> None:
+        """A magnet went past, going forward (1) or backward (-1)"""
+
+        self.odometer_distance += self.distance_per_magnet * direction
+        self.magnet_time = time
+
+    def accelerometer_reading(self, acceleration: float, time: float) -> None:
+        """Centimeters per second squared along the way the car points"""
+
+    def rangefinder_reading(self, distance_ahead: float, time: float) -> None:
+        """Centimeters to whatever is in front of the car"""
+
+    @abstractmethod
+    def distance(self, time: float) -> float:
+        """Centimeters gone as of the time given"""
+
+
+class OdometerEstimator(DistanceEstimator):
+    """Counts magnets, and nothing else"""
+
+    kind = DistanceEstimatorKind.Odometer
+
+    def distance(self, time: float) -> float:
+        return self.odometer_distance
+
+
+class AccelerometerFusionEstimator(DistanceEstimator):
+    """Fills in between magnets by integrating the accelerometer, starting again
+    from the odometer at every magnet so drift can't build up
+
+    The car can't have gone further than the next magnet between magnets, so
+    the integrated distance is kept within one
+    """
+
+    kind = DistanceEstimatorKind.AccelerometerFusion
+
+    def __init__(self, wheel_circumference: float = WHEEL_CIRCUMFERENCE_CENTIMETERS):
+        super().__init__(wheel_circumference)
+        # Centimeters per second
+        self.velocity = 0.0
+        # Centimeters since the last magnet
+        self.since_magnet = 0.0
+        self.reading_time: float | None = None
+
+    def magnet_hit(self, direction: int, time: float) -> None:
+        # The speed over the last magnet is known better than the integrated one
+        if self.magnet_time is not None and time > self.magnet_time:
+            self.velocity = (self.distance_per_magnet * direction) / (
+                time - self.magnet_time
+            )
+        super().magnet_hit(direction, time)
+        self.since_magnet = 0.0
+
+    def accelerometer_reading(self, acceleration: float, time: float) -> None:
+        if self.reading_time is not None:
+            elapsed = time - self.reading_time
+            self.velocity += acceleration * elapsed
+            self.since_magnet += self.velocity * elapsed
+        self.reading_time = time
+
+    def distance(self, time: float) -> float:
+        if (
+            self.reading_time is None
+            or time - self.reading_time > SENSOR_READING_TIMEOUT_SECONDS
+        ):
+            return self.odometer_distance
+
+        since_magnet = max(
+            -self.distance_per_magnet,
+            min(self.distance_per_magnet, self.since_magnet),
+        )
+        return self.odometer_distance + since_magnet
+
+
+class RangefinderEstimator(DistanceEstimator):
+    """Corrects the odometer with how much closer whatever is ahead (e.g. a wall
+    past the finish line) has got since the first reading, weighing the two by
+    `RANGEFINDER_WEIGHT`
+    """
+
+    kind = DistanceEstimatorKind.Rangefinder
+
+    def __init__(self, wheel_circumference: float = WHEEL_CIRCUMFERENCE_CENTIMETERS):
+        super().__init__(wheel_circumference)
+        # Centimeters
+        self.first_distance_ahead: float | None = None
+        self.distance_ahead: float | None = None
+        self.reading_time: float | None = None
+
+    def rangefinder_reading(self, distance_ahead: float, time: float) -> None:
+        if self.first_distance_ahead is None:
+            self.first_distance_ahead = distance_ahead
+        self.distance_ahead = distance_ahead
+        self.reading_time = time
+
+    def distance(self, time: float) -> float:
+        if (
+            self.reading_time is None
+            or time - self.reading_time > SENSOR_READING_TIMEOUT_SECONDS
+        ):
+            return self.odometer_distance
+
+        ranged_distance = self.first_distance_ahead - self.distance_ahead
+        return (
+            RANGEFINDER_WEIGHT * ranged_distance
+            + (1.0 - RANGEFINDER_WEIGHT) * self.odometer_distance
+        )
+
+
+### Functions ###
+
+
+def new_distance_estimator(
+    kind: int | None, wheel_circumference: float = WHEEL_CIRCUMFERENCE_CENTIMETERS
+) -> DistanceEstimator:
+    """A fresh estimator of a :class:`DistanceEstimatorKind` for a run, or the
+    default one for None, counting magnets with the wheel circumference given
+    """
+
+    if kind is None:
+        kind = DEFAULT_DISTANCE_ESTIMATOR
+    return DISTANCE_ESTIMATORS_LUT[kind](wheel_circumference)
+
+
+### Lookup tables ###
+DISTANCE_ESTIMATORS_LUT = {
+    DistanceEstimatorKind.Odometer: OdometerEstimator,
+    DistanceEstimatorKind.AccelerometerFusion: AccelerometerFusionEstimator,
+    DistanceEstimatorKind.Rangefinder: RangefinderEstimator,
+}
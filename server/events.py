@@ -8,6 +8,8 @@ Created by sheepy0125 | MIT License | 2023-02-20
 from serial import Serial
 from json import loads, dumps
 from asyncio import sleep
+from collections import deque
+from threading import Lock as ThreadLock
 from bindings import (
     Command,
     SerializableStruct,
@@ -17,22 +19,30 @@ from bindings import (
     Error,
     ErrorResponse,
     MetaData,
+    Echo,
     COMMAND_SERIALIZABLE_LUT,
 )
 from motor_controller import Motor
 
-from utils import Logger
+from utils import Logger, crc16, frame_number, strip_checksum
 from shared import (
+    ARQ_WINDOW,
     SERIAL_DELAY_TIME_SECONDS,
     SERIAL_BAUD_RATE,
     SERIAL_BYTE_SIZE,
     SERIAL_STOP_BITS,
     SERIAL_PARITY,
     GPIO_DEBOUNCE_TIME_MS,
+    SEND_CHECKSUMS,
 )
 from time import time as unix_epoch
 import RPi.GPIO as GPIO
 
+### Constants ###
+# Frame numbers wrap around to 0 after this many
+FRAME_NUMBERS: int = 2**32
+
+
 ### Classes ###
 
 
@@ -57,12 +67,91 @@ class SerialEvent:
         transit_mode: int,
         value: SerializableStruct | None,
         metadata: MetaData,
+        received_time: float | None = None,
     ):
         self.command = command
         self.transit_type = transit_type
         self.transit_mode = transit_mode
         self.value = value
         self.metadata = metadata
+        # When the request was read, to echo back with its response
+        self.received_time = received_time
+
+
+class ReceivedFrames:
+    """Which frames came in from the client, to ask again for the ones that
+    didn't (the same as the client's `bindings::ReceivedFrames`)
+    """
+
+    def __init__(self):
+        # The number of the next frame, once any have come in
+        self.expected: int | None = None
+        # Frames asked for again that haven't come in yet
+        self.missing: deque[int] = deque(maxlen=ARQ_WINDOW)
+
+    def receive(self, number: int) -> list[int]:
+        """Note down a frame that came in
+
+        Frames skipped over are missing, though only the ones the client still
+        keeps are asked for. A frame from well before the one expected means
+        the client started over, so counting starts over from it.
+
+        :returns: The frames to ask for again
+        """
+
+        if self.found(number):
+            return []
+        if self.expected is None:
+            self.expected = number
+        skipped = (number - self.expected) % FRAME_NUMBERS
+
+        # Already came in (e.g. sent again for a request that timed out),
+        # unless it's from so far back that the client started over
+        if skipped >= FRAME_NUMBERS // 2:
+            if (self.expected - number) % FRAME_NUMBERS > ARQ_WINDOW:
+                self.expected = (number + 1) % FRAME_NUMBERS
+                self.missing.clear()
+            return []
+
+        self.expected = (number + 1) % FRAME_NUMBERS
+        missing = [
+            (number - back) % FRAME_NUMBERS
+            for back in range(min(skipped, ARQ_WINDOW - 1), 0, -1)
+        ]
+        self.missing.extend(missing)
+        return missing
+
+    def corrupted(self, number: int) -> list[int]:
+        """Note down a frame that came in corrupted by what its number seems
+        to be
+
+        The number could be corrupted too, so it's only trusted if it could
+        come next; otherwise, the gap is noticed once the next frame comes in.
+        A frame that was already asked for again isn't asked for a second time.
+
+        :returns: The frames to ask for again, including it
+        """
+
+        if self.found(number) or self.expected is None:
+            return []
+        if (number - self.expected) % FRAME_NUMBERS >= ARQ_WINDOW:
+            return []
+
+        missing = self.receive(number)
+        missing.append(number)
+        self.missing.append(number)
+        return missing
+
+    def found(self, number: int) -> bool:
+        """Take a frame off of the missing ones
+
+        :returns: Whether it was missing
+        """
+
+        if number not in self.missing:
+            return False
+        self.missing.remove(number)
+        return True
 
 
 class SerialEventContext:
@@ -70,6 +159,18 @@ class SerialEventContext:
 
     # A buffer for the serial RX incase there is no `\n` available yet
     serial_rx_buffer: str = ""
+    # The sequence of the last request handled (with the client sharing the
+    # serial bridge that sent it, if any) and what was sent back for it, so a
+    # retried request is answered again without running it twice
+    last_sequence: tuple[int | None, int] | None = None
+    last_response: str | None = None
+    # The number of the next frame sent, and the last few sent with their
+    # numbers, to send again if the client asks for them with a `NACK`
+    # Statuses are sent from the start thread too, hence the lock
+    next_frame: int = 0
+    sent_frames: deque[tuple[int, str]] = deque(maxlen=ARQ_WINDOW)
+    frame_lock: ThreadLock = ThreadLock()
+    received_frames: ReceivedFrames = ReceivedFrames()
 
 
 class SerialEventPropagator:
@@ -156,6 +257,14 @@ class SerialEventPropagator:
         :raises ServerException: If the input is malformed
         """
 
+        # Check the checksum, if the client sent one
+        data, checksum_matched = strip_checksum(data.strip())
+        if not checksum_matched:
+            raise ServerException(
+                enum_variant=Error.MalformedRequestOtherError,
+                inner=ValueError("Checksum mismatch"),
+            )
+
         # Find the prefix
         try:
             prefix = data[0]
@@ -179,7 +288,10 @@ class SerialEventPropagator:
             )
 
         # Find command
-        split_data = data.split("$")
+        # The arguments are JSON, which may have a `$` inside of a string, so
+        # only the first and last separators count (the same as the client)
+        command_data, _, rest = data.partition("$")
+        split_data = [command_data, *rest.rsplit("$", 1)] if rest else [command_data]
         if not len(split_data) == 3:
             raise ServerException(
                 enum_variant=Error.MalformedRequestFailedSeparatorParsing,
@@ -276,11 +388,99 @@ class SerialEventPropagator:
         # Stringify the response
         response = dumps(event.value.__dict__)
 
-        # Get new metadata
+        with SerialEventContext.frame_lock:
+            # Get new metadata, answering the request's sequence (and client)
+            # and numbering the frame
+            number = SerialEventContext.next_frame
+            SerialEventContext.next_frame = (number + 1) % FRAME_NUMBERS
+            # Echo the time the request was sent with, if it was read
+            echo = (
+                Echo(event.metadata.time, event.received_time)
+                if event.received_time is not None
+                else None
+            )
+            metadata = dumps(
+                MetaData(
+                    float(unix_epoch()),
+                    event.metadata.sequence,
+                    number,
+                    echo,
+                    event.metadata.client,
+                ).__dict__
+            )
+
+            # Encapsulate everything into a string
+            frame = f"{prefix}{command}${response}${metadata}"
+            if SEND_CHECKSUMS:
+                frame += f"*{crc16(frame.encode()):04X}"
+            SerialEventContext.sent_frames.append((number, f"{frame}\r\n"))
+        return f"{frame}\r\n"
+
+    @staticmethod
+    def nack_frame(number: int) -> str:
+        """A `NACK` asking the client to send a frame again, which isn't
+        numbered itself
+        """
+
+        prefix = TransitMode.lookup_by_variant(TransitMode.ServerToClientResponse)
+        command = Command.lookup_by_variant(Command.Nack)
         metadata = dumps(MetaData(float(unix_epoch())).__dict__)
+        frame = f"{prefix}{command}${dumps({'frame': number})}${metadata}"
+        if SEND_CHECKSUMS:
+            frame += f"*{crc16(frame.encode()):04X}"
+        return f"{frame}\r\n"
+
+    def handle_retransmission(self, data: str) -> bool:
+        """Ask again for frames from the client that were skipped over or
+        came in corrupted, and send a frame again if this asks for one
+
+        Frames without a number (from older clients) are left alone.
+
+        :returns: Whether there's nothing more to do with the frame, as it was
+            a `NACK` or a corrupted frame that's being asked for again
+        """
+
+        body, checksum_matched = strip_checksum(data.strip())
+        number = frame_number(body)
+
+        # The client asking for a frame again
+        if body.upper().startswith("?NACK$"):
+            if not checksum_matched:
+                return True
+            try:
+                asked_for = loads(body.partition("$")[2].rpartition("$")[0])["frame"]
+            except Exception:
+                return True
+            with SerialEventContext.frame_lock:
+                frames = [
+                    frame
+                    for sent, frame in SerialEventContext.sent_frames
+                    if sent == asked_for
+                ]
+            for frame in frames:
+                Logger.warn(f"Frame {asked_for} was lost, sending it again")
+                self.write_response(frame)
+            return True
+
+        if number is None:
+            return False
+        if checksum_matched:
+            missing = SerialEventContext.received_frames.receive(number)
+        else:
+            missing = SerialEventContext.received_frames.corrupted(number)
+        for missing_number in missing:
+            Logger.warn(
+                f"Frame {missing_number} from the client was lost, asking again"
+            )
+            self.write_response(self.nack_frame(missing_number))
+        return not checksum_matched and len(missing) > 0
 
-        # Encapsulate everything into a string
-        return f"{prefix}{command}${response}${metadata}\r\n"
+    def write_response(self, to_send: str):
+        try:
+            self.serial.write(bytes(to_send, "utf-8"))
+        except Exception as e:
+            Logger.fatal(f"Failed to send {to_send!r}!")
+            Logger.log_error(e)
 
     async def main_loop(self):  # NOSONAR
         while True:
@@ -301,12 +501,19 @@ class SerialEventPropagator:
                 continue
             if len(rx) == 0:
                 continue
+            received_time = unix_epoch()
+
+            # Lost frames are asked for again rather than answered
+            if self.handle_retransmission(rx):
+                await sleep(SERIAL_DELAY_TIME_SECONDS)
+                continue
 
             # Parse
             parsed_rx = None
             if rx is not None:
                 try:
                     parsed_rx = self.parse_request(rx)
+                    parsed_rx.received_time = received_time
                     Logger.verbose(
                         f"{parsed_rx.command=} {parsed_rx.transit_type=} {parsed_rx.transit_mode=} {parsed_rx.value.__dict__=}"
                     )
@@ -322,6 +529,24 @@ class SerialEventPropagator:
                         error_variant=Error.MalformedRequestOtherError, message=str(e)
                     )
 
+            # A retry of the last request, whose response must have been lost
+            sequence = (
+                (parsed_rx.metadata.client, parsed_rx.metadata.sequence)
+                if parsed_rx is not None and parsed_rx.metadata.sequence is not None
+                else None
+            )
+            if (
+                sequence is not None
+                and sequence == SerialEventContext.last_sequence
+                and SerialEventContext.last_response is not None
+            ):
+                Logger.warn(
+                    f"Request {sequence[1]} was retried, sending the response again"
+                )
+                self.write_response(SerialEventContext.last_response)
+                await sleep(SERIAL_DELAY_TIME_SECONDS)
+                continue
+
             # Callback
             response = None
             if parsed_rx is not None:
@@ -385,13 +610,12 @@ class SerialEventPropagator:
                         metadata=MetaData(unix_epoch()),
                     )
             to_send = self.parse_response(event)
+            if sequence is not None:
+                SerialEventContext.last_sequence = sequence
+                SerialEventContext.last_response = to_send
 
             # Transmit response
-            try:
-                self.serial.write(bytes(to_send, "utf-8"))
-            except Exception as e:
-                Logger.fatal(f"Failed to send {to_send!r}!")
-                Logger.log_error(e)
+            self.write_response(to_send)
 
             # Delay
             await sleep(SERIAL_DELAY_TIME_SECONDS)
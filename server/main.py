@@ -8,6 +8,7 @@ MIT License | 2023-02-20
 # Imports
 from serial import Serial
 from asyncio import run
+from json import dumps, loads
 from threading import Thread, Event as ThreadEvent, Lock as ThreadLock
 from time import time as unix_epoch
 from sys import argv
@@ -17,6 +18,8 @@ from bindings import (
     Command,
     Direction,
     GPIOPin,
+    HelloArguments,
+    HelloResponse,
     MetaData,
     PingArguments,
     PingResponse,
@@ -29,6 +32,13 @@ from bindings import (
     StaticStatusResponse,
     StatusArguments,
     StatusResponse,
+    ApproachGains,
+    CalibrateArguments,
+    CalibrateResponse,
+    TuneArguments,
+    TuneResponse,
+    UploadPlanArguments,
+    UploadPlanResponse,
     DistanceInformation,
     ServerException,
     Error,
@@ -37,17 +47,28 @@ from bindings import (
     unsigned_float,
 )
 from shared import (
+    APPROACH_DISTANCE_CENTIMETERS,
+    APPROACH_WINDOW_SECONDS,
     BACKWARD_COOLDOWN_SECONDS,
     BACKWARD_LEEWAY_DISTANCE_CENTIMETERS,
     CAUTIOUS_REVERSE_STALL_FOR_SECONDS,
     FORWARD_LEEWAY_DISTANCE_CENTIMETERS,
     NUMBER_OF_MAGNETS,
     MAGNET_FREE_STALL_FOR_SECONDS,
+    PROTOCOL_VERSION,
+    POWER_MONITOR_FITTED,
+    POWER_MONITOR_I2C_ADDRESS,
+    POWER_MONITOR_I2C_BUS,
+    POWER_MONITOR_SHUNT_OHMS,
+    RADIO_STOP_FITTED,
     STATUS_POLL_DURATION_SECONDS,
+    TUNING_PATH,
     WHEEL_DIAMETER_CENTIMETERS,
     WHEEL_CIRCUMFERENCE_CENTIMETERS,
 )
 from motor_controller import Motor
+from power_monitor import PowerMonitor
+from estimators import DistanceEstimator, new_distance_estimator
 from RPi import GPIO
 
 
@@ -75,16 +96,55 @@ class MutexStartData:
     lock: ThreadLock = ThreadLock()
     distance: DistanceInformation | None = None
     run_stage: RunStage = RunStage.Stopped
+    speed_limit_violations: int = 0
+    # The one the last run was started with, reported in the static status
+    distance_estimator: DistanceEstimator = new_distance_estimator(None)
+    # The breakpoints the last run was uploaded with, each with when it was
+    # reached, or None if it was started without a plan
+    executed_plan: list[dict] | None = None
+
+
+class RadioStopData:
+    """The stop button on the radio, see `RADIO_STOP_FITTED`"""
+
+    held: bool = False
+    # Runs it stopped since the server started
+    stops: int = 0
+
+
+class PowerData:
+    """The power monitor, see `POWER_MONITOR_FITTED`"""
+
+    # None until it's been set up, or if it couldn't be
+    monitor: PowerMonitor | None = None
+
+
+class Tuning:
+    """How the car is driven, set with `TUNE` and `CALIBRATE` and kept in
+    `TUNING_PATH`"""
+
+    # What the car is eased in with from the next run on, or None to cut power
+    # once close enough
+    approach_gains: ApproachGains | None = None
+    # Centimeters the wheel goes around in from the next run on, or None to go
+    # by its diameter until it's been calibrated
+    wheel_circumference: float | None = None
 
 
 class RunData:
     """Run data stored for starting the car"""
 
     magnet_time = 0.0
+    # Seconds between the last two magnet hits
+    magnet_interval = float("inf")
     next_status_poll_time = 0.0
     start_cautious_reversing_time = 0.0
     stop_cautious_reversing_time = 0.0
     magnet_hits_cautiously_reversing = 0
+    # Centimeter seconds left to go, built up while easing in
+    approach_integral = 0.0
+    # When the car was last eased in, once it's started to be
+    approach_time: float | None = None
 
 
 ### Event callbacks ###
@@ -104,6 +164,62 @@ def magnet_event(event: GPIOEvent) -> None:
         MutexStartData.magnet_hit_flag.clear()
 
 
+def radio_stop_event(event: GPIOEvent) -> None:
+    """An event from the radio receiver, low while the stop button is held"""
+
+    RadioStopData.held = not event.value
+    if RadioStopData.held:
+        # Straight away, without waiting on the start thread, which stops the
+        # run once it sees the button held
+        Logger.warn("Radio stop held")
+        Motor.stop()
+
+
+def setup_radio_stop() -> None:
+    """Set up the radio receiver's pin, going by whether it's already held
+
+    The receiver drives the pin high until the stop button is held, and it's
+    pulled down, so a receiver that comes loose or loses power reads as held
+    and stops the car rather than leaving it without a way to be stopped.
+    """
+
+    GPIO.setup(GPIOPin.RadioStopReceiver, GPIO.IN, pull_up_down=GPIO.PUD_DOWN)
+    # Only changes are seen from now on
+    RadioStopData.held = not GPIO.input(GPIOPin.RadioStopReceiver)
+    if RadioStopData.held:
+        Logger.warn("Radio stop held, or the receiver isn't connected")
+
+
+def radio_stop_status() -> dict | None:
+    if not RADIO_STOP_FITTED:
+        return None
+    return {"held": RadioStopData.held, "stops": RadioStopData.stops}
+
+
+def power_status() -> dict | None:
+    if PowerData.monitor is None:
+        return None
+    try:
+        return PowerData.monitor.read()
+    except OSError as e:
+        # Sent without it rather than not at all
+        Logger.warn(f"Failed to read the power monitor: {e}")
+        return None
+
+
+def hello(event: SerialEvent) -> HelloResponse:
+    hello_request: HelloArguments = event.value
+
+    # The client falls back to older versions itself, so this is only logged
+    if hello_request.protocol_version != PROTOCOL_VERSION:
+        Logger.warn(
+            f"The client speaks protocol version {hello_request.protocol_version}, "
+            f"but this server speaks version {PROTOCOL_VERSION}"
+        )
+
+    return HelloResponse(PROTOCOL_VERSION)
+
+
 def ping(event: SerialEvent) -> PingResponse:
     ping_request: PingArguments = event.value
 
@@ -120,6 +236,122 @@ def ping(event: SerialEvent) -> PingResponse:
 
 
 def start(event: SerialEvent) -> StartResponse:
+    begin_run(event.value, executed_plan=None)
+
+    return StartResponse()
+
+
+def upload_plan(event: SerialEvent) -> UploadPlanResponse:
+    """Start a run that changes the speed limit at each breakpoint of the plan
+    on its own, without needing the client again"""
+
+    arguments: UploadPlanArguments = event.value
+    executed_plan = [
+        {
+            "distance": breakpoint.distance,
+            "max_velocity": breakpoint.max_velocity,
+            "reached": None,
+        }
+        for breakpoint in arguments.breakpoints
+    ]
+    begin_run(arguments.start, executed_plan=executed_plan)
+
+    return UploadPlanResponse()
+
+
+def tune(event: SerialEvent) -> TuneResponse:
+    """Set how the car is eased in from the next run on, keeping it for after a
+    restart"""
+
+    arguments: TuneArguments = event.value
+    Tuning.approach_gains = arguments.approach_gains
+    save_tuning()
+
+    return TuneResponse(approach_gains=Tuning.approach_gains)
+
+
+def calibrate(event: SerialEvent) -> CalibrateResponse:
+    """Scale the wheel circumference the last run was counted with by how far
+    the car was measured to have really gone, keeping it for after a restart
+
+    There's no EEPROM on the car, so it's kept in `TUNING_PATH` with the gains
+    """
+
+    arguments: CalibrateArguments = event.value
+    if MutexStartData.started_flag.is_set():
+        raise ServerException(
+            enum_variant=Error.AnyOtherError,
+            inner=ValueError("Can't calibrate during a run"),
+        )
+    # Going by the magnets alone, as that's what the circumference counts
+    estimator = MutexStartData.distance_estimator
+    if estimator.odometer_distance <= 0:
+        raise ServerException(
+            enum_variant=Error.AnyOtherError,
+            inner=ValueError("There's no run gone forward to calibrate against"),
+        )
+
+    previous_wheel_circumference = wheel_circumference()
+    Tuning.wheel_circumference = (
+        estimator.wheel_circumference * arguments.distance / estimator.odometer_distance
+    )
+    save_tuning()
+
+    return CalibrateResponse(
+        previous_wheel_circumference=previous_wheel_circumference,
+        wheel_circumference=Tuning.wheel_circumference,
+    )
+
+
+def wheel_circumference() -> float:
+    """Centimeters the wheel is taken to go around in, as calibrated or from
+    its diameter before it's been"""
+
+    if Tuning.wheel_circumference is None:
+        return WHEEL_CIRCUMFERENCE_CENTIMETERS
+    return Tuning.wheel_circumference
+
+
+def save_tuning():
+    """Keep the tuning in `TUNING_PATH`, so it outlasts a restart"""
+
+    tuning = {
+        "approach_gains": (
+            Tuning.approach_gains.__dict__
+            if Tuning.approach_gains is not None
+            else None
+        ),
+        "wheel_circumference": Tuning.wheel_circumference,
+    }
+    try:
+        with open(TUNING_PATH, "w") as file:
+            file.write(dumps(tuning))
+    except OSError as e:
+        Logger.warn(f"Couldn't keep the tuning in {TUNING_PATH}, only until a restart")
+        Logger.log_error(e)
+
+
+def load_tuning():
+    """Load the gains and wheel circumference last set with `TUNE` and
+    `CALIBRATE`, if they were kept"""
+
+    try:
+        with open(TUNING_PATH) as file:
+            tuning = loads(file.read())
+        Tuning.approach_gains = TuneArguments(
+            tuning.get("approach_gains")
+        ).approach_gains
+        wheel_circumference = tuning.get("wheel_circumference")
+        if wheel_circumference is not None:
+            Tuning.wheel_circumference = unsigned_float(wheel_circumference)
+    except FileNotFoundError:
+        return
+    except Exception as e:
+        Logger.warn(f"Couldn't load the tuning from {TUNING_PATH}, so going without it")
+        Logger.log_error(e)
+
+
+def begin_run(arguments: StartArguments, executed_plan: list[dict] | None):
     # Ensure not already started
     if MutexStartData.started_flag.is_set():
         raise ServerException(
@@ -127,8 +359,6 @@ def start(event: SerialEvent) -> StartResponse:
             inner=ValueError("Already started!"),
         )
 
-    arguments: StartArguments = event.value
-
     # Reset flags
     MutexStartData.started_flag.set()
     MutexStartData.magnet_hit_flag.clear()
@@ -137,19 +367,73 @@ def start(event: SerialEvent) -> StartResponse:
     MutexStartData.distance = DistanceInformation(
         distance=0, velocity=0.0, magnet_hit_counter=0
     )
+    MutexStartData.speed_limit_violations = 0
+    MutexStartData.distance_estimator = new_distance_estimator(
+        arguments.distance_estimator, wheel_circumference()
+    )
+    MutexStartData.executed_plan = executed_plan
 
     # Run threads
     MutexStartData.start_thread = Thread(target=start_thread, args=(arguments,))
     MutexStartData.start_thread.start()
 
-    return StartResponse()
-
 
 def start_thread(arguments: StartArguments):
     should_reverse_brake = arguments.reverse_brake  # FIXME: Use this
 
     RunData.next_status_poll_time = unix_epoch() + STATUS_POLL_DURATION_SECONDS
+    RunData.magnet_interval = float("inf")
+    RunData.approach_integral = 0.0
+    RunData.approach_time = None
     MutexStartData.magnet_hits = 0
+    # Tuning the car mid-run only changes the next one
+    approach_gains = Tuning.approach_gains
+
+    def current_velocity() -> float:
+        """The speed in centimeters per second right now, unlike the average
+        speed in the distance information
+
+        Between magnet hits the car can't be going any faster than if the next
+        one were about to happen, so that bounds it while coasting
+        """
+
+        seconds_per_hit = max(
+            RunData.magnet_interval, unix_epoch() - RunData.magnet_time
+        )
+        return MutexStartData.distance_estimator.distance_per_magnet / seconds_per_hit
+
+    def speed_limit() -> float | None:
+        """The speed limit in centimeters per second, changed by the last
+        breakpoint of an uploaded plan the car went past"""
+
+        max_velocity = arguments.max_velocity
+        for breakpoint in MutexStartData.executed_plan or []:
+            if breakpoint["reached"] is not None:
+                max_velocity = breakpoint["max_velocity"]
+        return max_velocity
+
+    def easing_off() -> bool:
+        """Whether to cut power to ease the car in over the last stretch
+
+        The controller says how much of the time the motor should be on, from 0
+        to 1, so the motor's turned on for that much of each window
+        """
+
+        remaining = arguments.distance - MutexStartData.distance.distance
+        if approach_gains is None or remaining > APPROACH_DISTANCE_CENTIMETERS:
+            return False
+
+        now = unix_epoch()
+        last_time = RunData.approach_time if RunData.approach_time is not None else now
+        RunData.approach_time = now
+        RunData.approach_integral += remaining * (now - last_time)
+        power = (
+            approach_gains.proportional * remaining
+            + approach_gains.integral * RunData.approach_integral
+            - approach_gains.derivative * current_velocity()
+        )
+        power = min(max(power, 0.0), 1.0)
+        return now % APPROACH_WINDOW_SECONDS >= power * APPROACH_WINDOW_SECONDS
 
     def send_status():
         current_time = unix_epoch()
@@ -164,6 +448,10 @@ def start_thread(arguments: StartArguments):
                     runtime=current_time - MutexStartData.started_time,
                     distance=MutexStartData.distance,
                     stage=MutexStartData.run_stage,
+                    speed_limit_violations=MutexStartData.speed_limit_violations,
+                    executed_plan=finished_plan(),
+                    radio_stop=radio_stop_status(),
+                    power=power_status(),
                 ),
                 metadata=MetaData(unix_epoch()),
             )
@@ -186,6 +474,12 @@ def start_thread(arguments: StartArguments):
         if MutexStartData.e_stop_flag.is_set():
             MutexStartData.run_stage = RunStage.Stopped
             break
+        # The radio stop, which also keeps the car from starting while held
+        if RadioStopData.held:
+            Logger.warn("Stopped by the radio")
+            RadioStopData.stops += 1
+            MutexStartData.run_stage = RunStage.Stopped
+            break
 
         # Status
         if unix_epoch() >= RunData.next_status_poll_time:
@@ -205,28 +499,68 @@ def start_thread(arguments: StartArguments):
                 or direction == Direction.Backward
             ):
                 multiplier = -1
-            MutexStartData.distance.distance += (
-                WHEEL_CIRCUMFERENCE_CENTIMETERS / NUMBER_OF_MAGNETS
-            ) * multiplier
+            MutexStartData.distance_estimator.magnet_hit(multiplier, unix_epoch())
+            MutexStartData.distance.distance = (
+                MutexStartData.distance_estimator.distance(unix_epoch())
+            )
             MutexStartData.distance.velocity = MutexStartData.distance.distance / (
                 unix_epoch() - MutexStartData.started_time
             )
+            RunData.magnet_interval = unix_epoch() - RunData.magnet_time
             RunData.magnet_time = unix_epoch()
+        else:
+            # Estimators going by more than the magnets can move in between
+            MutexStartData.distance.distance = (
+                MutexStartData.distance_estimator.distance(unix_epoch())
+            )
 
         match MutexStartData.run_stage:
             case RunStage.Stopped | RunStage.Finalized:
                 break
             case RunStage.VehementForward:
-                if direction != Direction.Forward:
+                runtime = unix_epoch() - MutexStartData.started_time
+                for breakpoint in MutexStartData.executed_plan or []:
+                    if (
+                        breakpoint["reached"] is None
+                        and MutexStartData.distance.distance >= breakpoint["distance"]
+                    ):
+                        breakpoint["reached"] = runtime
+                        Logger.verbose(
+                            f"Reached the breakpoint at {breakpoint['distance']:.3f}cm"
+                        )
+
+                # The relays can only turn the motor on or off, so throttle it by
+                # cutting power while over the speed limit
+                max_velocity = speed_limit()
+                speeding = (
+                    max_velocity is not None and current_velocity() > max_velocity
+                )
+                if speeding and direction == Direction.Forward:
+                    MutexStartData.speed_limit_violations += 1
+                    Logger.warn(
+                        f"Going {current_velocity():.3f}cm/s, over the speed limit of "
+                        f"{max_velocity:.3f}cm/s; cutting power"
+                    )
+                    Motor.stop()
+                    direction = Direction.Stopped
+                elif easing_off():
+                    if direction != Direction.Stopped:
+                        Motor.stop()
+                        direction = Direction.Stopped
+                elif not speeding and direction != Direction.Forward:
                     Logger.verbose("Vehement forward stage")
                     Motor.forward()
                     direction = Direction.Forward
 
-                # Exceeded distance
-                if (
-                    MutexStartData.distance.distance
-                    >= arguments.distance - FORWARD_LEEWAY_DISTANCE_CENTIMETERS
-                ):
+                # Close enough to coast the rest of the way, going by how quickly
+                # the car was measured to slow down if we were given that and
+                # it isn't being eased in
+                leeway = FORWARD_LEEWAY_DISTANCE_CENTIMETERS
+                if arguments.coast_deceleration and approach_gains is None:
+                    leeway = current_velocity() ** 2 / (
+                        2 * arguments.coast_deceleration
+                    )
+                if MutexStartData.distance.distance >= arguments.distance - leeway:
                     MutexStartData.run_stage += 1
             case RunStage.StallOvershoot:
                 if direction != Direction.Stopped:
@@ -296,7 +630,11 @@ def static_status(_: SerialEvent) -> StaticStatusResponse:
     """Return static status about the car"""
 
     return StaticStatusResponse(
-        number_of_magnets=NUMBER_OF_MAGNETS, wheel_diameter=WHEEL_DIAMETER_CENTIMETERS
+        number_of_magnets=NUMBER_OF_MAGNETS,
+        wheel_diameter=WHEEL_DIAMETER_CENTIMETERS,
+        distance_estimator=MutexStartData.distance_estimator.kind,
+        approach_gains=Tuning.approach_gains,
+        wheel_circumference=Tuning.wheel_circumference,
     )
 
 
@@ -309,6 +647,8 @@ def status(_: SerialEvent) -> StatusResponse:
         )
     distance = MutexStartData.distance
     stage = MutexStartData.run_stage
+    speed_limit_violations = MutexStartData.speed_limit_violations
+    executed_plan = finished_plan()
     MutexStartData.lock.release()
 
     return StatusResponse(
@@ -317,13 +657,30 @@ def status(_: SerialEvent) -> StatusResponse:
         uptime=(unix_epoch() - START_UPTIME),
         distance=distance,
         stage=stage,
+        speed_limit_violations=speed_limit_violations,
+        executed_plan=executed_plan,
+        radio_stop=radio_stop_status(),
+        power=power_status(),
     )
 
 
+def finished_plan() -> list[dict] | None:
+    """The plan the last run was uploaded with, only sent back once it's
+    over"""
+
+    if MutexStartData.started_flag.is_set():
+        return None
+    return MutexStartData.executed_plan
+
+
 ### Lookup tables ###
 SERIAL_CALLBACKS_LUT = {
+    Command.Hello: hello,
     Command.Ping: ping,
     Command.Start: start,
+    Command.UploadPlan: upload_plan,
+    Command.Tune: tune,
+    Command.Calibrate: calibrate,
     Command.Stop: stop,
     Command.Status: status,
     Command.StaticStatus: static_status,
@@ -340,10 +697,26 @@ GPIO_CALLBACKS_LUT = {
     GPIOPin.MagnetHallEffectSensor1: magnet_event,
     GPIOPin.MagnetHallEffectSensor2: magnet_event,
 }
+if RADIO_STOP_FITTED:
+    SETUP_GPIO_CALLBACKS_LUT[GPIOPin.RadioStopReceiver] = setup_radio_stop
+    GPIO_CALLBACKS_LUT[GPIOPin.RadioStopReceiver] = radio_stop_event
 
 
 ### Main ###
 async def main():
+    load_tuning()
+    if POWER_MONITOR_FITTED:
+        try:
+            PowerData.monitor = PowerMonitor(
+                bus=POWER_MONITOR_I2C_BUS,
+                address=POWER_MONITOR_I2C_ADDRESS,
+                shunt_ohms=POWER_MONITOR_SHUNT_OHMS,
+            )
+        except Exception as e:
+            # The car still runs without it
+            Logger.warn("Setting up the power monitor failed")
+            Logger.log_error(e)
+
     # Connect to serial port
     try:
         serial_port = argv[1]
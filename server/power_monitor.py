@@ -0,0 +1,69 @@
+"""
+Measures the battery and what the motor draws from it, with an INA219 on the
+Pi's I2C bus
+Created by sheepy0125 | MIT License | 2026-10-16
+"""
+
+from shared import BATTERY_EMPTY_VOLTS, BATTERY_FULL_VOLTS
+
+# Registers
+INA219_SHUNT_VOLTAGE_REGISTER: int = 0x01
+INA219_BUS_VOLTAGE_REGISTER: int = 0x02
+# Volts per bit, with the bus voltage's in its top 13 bits
+INA219_SHUNT_VOLTAGE_LSB: float = 10e-6
+INA219_BUS_VOLTAGE_LSB: float = 4e-3
+
+
+class PowerMonitor:
+    """An INA219 with its shunt between the battery and the motor relays, so
+    the current through it is what the motor draws"""
+
+    def __init__(self, bus: int, address: int, shunt_ohms: float) -> None:
+        # Only needed with one fitted
+        from smbus2 import SMBus
+
+        self.bus = SMBus(bus)
+        self.address = address
+        self.shunt_ohms = shunt_ohms
+
+    def read_register(self, register: int) -> int:
+        # The INA219 sends its most significant byte first, SMBus its least
+        word = self.bus.read_word_data(self.address, register)
+        return ((word & 0xFF) << 8) | (word >> 8)
+
+    def shunt_voltage(self) -> float:
+        shunt = self.read_register(INA219_SHUNT_VOLTAGE_REGISTER)
+        if shunt & 0x8000:
+            shunt -= 0x10000
+        return shunt * INA219_SHUNT_VOLTAGE_LSB
+
+    def read(self) -> dict:
+        """The battery voltage and motor current, with the charge left if the
+        battery's voltages are known"""
+
+        shunt_voltage = self.shunt_voltage()
+        bus_voltage = (
+            self.read_register(INA219_BUS_VOLTAGE_REGISTER) >> 3
+        ) * INA219_BUS_VOLTAGE_LSB
+        # The bus voltage is measured after the shunt
+        battery_voltage = bus_voltage + shunt_voltage
+        power = {
+            "battery_voltage": battery_voltage,
+            "motor_current": max(shunt_voltage / self.shunt_ohms, 0.0),
+        }
+        charge = battery_charge(battery_voltage)
+        if charge is not None:
+            power["charge"] = charge
+        return power
+
+
+def battery_charge(battery_voltage: float) -> float | None:
+    """Percent left, going by where the voltage is between empty and full, or
+    None if they aren't set"""
+
+    if BATTERY_EMPTY_VOLTS is None or BATTERY_FULL_VOLTS is None:
+        return None
+    fraction = (battery_voltage - BATTERY_EMPTY_VOLTS) / (
+        BATTERY_FULL_VOLTS - BATTERY_EMPTY_VOLTS
+    )
+    return min(max(fraction, 0.0), 1.0) * 100.0
@@ -20,7 +20,48 @@ SERIAL_BAUD_RATE: int = 115200
 SERIAL_BYTE_SIZE: int = 8
 SERIAL_STOP_BITS: int = 1
 SERIAL_PARITY: int | str = "N"  # None
+# Append a CRC16 to every response so the client can catch corrupted frames
+SEND_CHECKSUMS: bool = True
+# How many responses are kept to be sent again if the client asks for them
+# with a `NACK`, and so how far back a missing request is asked for
+ARQ_WINDOW: int = 8
+# The version of the protocol spoken, sent back when the client says `HELLO`;
+# must match `PROTOCOL_VERSION` in the client's bindings
+PROTOCOL_VERSION: int = 2
+# The most breakpoints an uploaded plan can have; must match
+# `MAX_PLAN_BREAKPOINTS` in the client's bindings
+MAX_PLAN_BREAKPOINTS: int = 16
+# How the distance is worked out when the client doesn't say (a
+# `DistanceEstimatorKind`, the odometer by default)
+DEFAULT_DISTANCE_ESTIMATOR: int = 0
+# How much the rangefinder counts for against the odometer, from 0 to 1
+RANGEFINDER_WEIGHT: float = 0.5
+# How long after its last reading a sensor is assumed to be gone, leaving the
+# odometer to go by
+SENSOR_READING_TIMEOUT_SECONDS: float = 0.5
+# How far before the distance the car starts being eased in, once it's been
+# given gains to with `TUNE`
+APPROACH_DISTANCE_CENTIMETERS: float = 50.0
+# Seconds the motor is turned on for part of while easing in
+APPROACH_WINDOW_SECONDS: float = 0.2
+# Where the gains from `TUNE` are kept, so they outlast a restart
+TUNING_PATH: str = "tuning.json"
 GPIO_DEBOUNCE_TIME_MS: int = 10
+# Whether a radio receiver (like a 433MHz key fob's) is wired to
+# `GPIOPin.RadioStopReceiver`, driving it high until its stop button is held,
+# to stop the car even if the link to the client (or the receiver) is lost
+RADIO_STOP_FITTED: bool = False
+# Whether an INA219 is wired between the battery and the motor relays on the
+# Pi's I2C bus, to send the battery voltage and motor current in every status
+POWER_MONITOR_FITTED: bool = False
+POWER_MONITOR_I2C_BUS: int = 1
+POWER_MONITOR_I2C_ADDRESS: int = 0x40
+# Ohms, of the shunt resistor the current is measured across
+POWER_MONITOR_SHUNT_OHMS: float = 0.1
+# The battery's voltage when empty and when full (a 2S LiPo by default), to
+# estimate the charge left from, or None to send no charge
+BATTERY_EMPTY_VOLTS: float | None = 6.4
+BATTERY_FULL_VOLTS: float | None = 8.4
 MOTOR_CONTROLLER_SAFETY_DELAY_SECONDS: float = 0.1
 FORWARD_LEEWAY_DISTANCE_CENTIMETERS: float = (
     WHEEL_DIAMETER_CENTIMETERS / NUMBER_OF_MAGNETS
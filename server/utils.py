@@ -3,6 +3,11 @@ Helper utilities
 Created by sheepy0125 | MIT License | 2021-10-02
 """
 
+### Setup ###
+# Imports
+from json import loads
+
+
 ### Classes ###
 
 
@@ -62,3 +67,49 @@ class Logger:
             f"{error_type}: {error!s} (lines {', '.join(str(error_line) for error_line in reversed(error_lines))} "
             f"in file {error_file})"
         )
+
+
+### Functions ###
+
+
+def crc16(data: bytes) -> int:
+    """CRC-16/CCITT-FALSE, the same as the client's `bindings::crc16`"""
+
+    crc = 0xFFFF
+    for byte in data:
+        crc ^= byte << 8
+        for _ in range(8):
+            crc = ((crc << 1) ^ 0x1021) if crc & 0x8000 else (crc << 1)
+            crc &= 0xFFFF
+    return crc
+
+
+def frame_number(frame: str) -> int | None:
+    """The number of a frame (without its checksum) from its metadata, if it
+    has one
+    """
+
+    _, _, metadata = frame.rpartition("$")
+    try:
+        number = loads(metadata).get("frame")
+    except Exception:
+        return None
+    return number if isinstance(number, int) and number >= 0 else None
+
+
+def strip_checksum(frame: str) -> tuple[str, bool]:
+    """Take the `*XXXX` checksum off of the end of a frame, if it has one
+
+    :returns: The frame without the checksum, and whether the checksum matched
+        (always true if there was none)
+    """
+
+    body, separator, checksum = frame.rpartition("*")
+    if (
+        not separator
+        or len(checksum) != 4
+        or any(c not in "0123456789abcdefABCDEF" for c in checksum)
+    ):
+        return frame, True
+    return body, crc16(body.encode()) == int(checksum, 16)
+